@@ -0,0 +1,15 @@
+#![no_main]
+
+use huff_lexer::Lexer;
+use huff_utils::prelude::FullFileSource;
+use libfuzzer_sys::fuzz_target;
+
+// The lexer must never panic, any input either lexes or returns a `LexicalError`.
+fuzz_target!(|source: &str| {
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    for token in Lexer::new(full_source) {
+        if token.is_err() {
+            break
+        }
+    }
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::{FullFileSource, LexicalError, Token};
+use libfuzzer_sys::fuzz_target;
+
+// The parser must never panic on a token stream the lexer accepts.
+fuzz_target!(|source: &str| {
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = match Lexer::new(full_source).collect::<Result<Vec<Token>, LexicalError>>() {
+        Ok(tokens) => tokens,
+        Err(_) => return,
+    };
+    let _ = Parser::new(tokens, None).parse();
+});
@@ -9,36 +9,65 @@
 
 use clap::{App, CommandFactory, Parser as ClapParser, Subcommand};
 use ethers_core::utils::hex;
-use huff_codegen::Codegen;
-use huff_core::Compiler;
+use huff_codegen::DEFAULT_MACRO_NESTING_LIMIT;
+use huff_core::{
+    diff::BytecodeDiff,
+    fix::Fix,
+    output::{OutputFormat, OutputLayout},
+    packages::{InstallStatus, Installer},
+    proxy::{Proxy, ProxyKind},
+    size_report::SizeReport,
+    snapshot::{Snapshot, DEFAULT_SNAPSHOT_PATH},
+    sstore2::DataContract,
+    std_lib::StdLib,
+    verify::{VerifyPayload, DEFAULT_VERIFY_API_URL},
+    Compiler,
+};
+use huff_lexer::Lexer;
+use huff_lint::{
+    prelude::{Level, LintConfig},
+    Linter,
+};
 use huff_tests::{
-    prelude::{print_test_report, ReportKind},
+    prelude::{
+        print_coverage_report, print_test_report, CoverageReport, FuzzConfig, GasDiff, GasSnapshot,
+        Repl, ReportKind, TestStatus, DEFAULT_FUZZ_RUNS, DEFAULT_GAS_SNAPSHOT_PATH,
+    },
     HuffTester,
 };
 use huff_utils::{
-    file_provider::FileSystemFileProvider,
+    file_provider::{FileProvider, FileSystemFileProvider, InMemoryFileProvider},
+    grammar::textmate_grammar,
     prelude::{
-        export_interfaces, gen_sol_interfaces, str_to_bytes32, unpack_files, AstSpan, CodegenError,
-        CodegenErrorKind, CompilerError, FileSource, Literal, OutputLocation, Span,
+        export_interfaces, gen_sol_interfaces, str_to_bytes32, unpack_files, Artifact, AstSpan,
+        CodegenError, CodegenErrorKind, CompilerError, ConstructorArgs, EVMVersion, Edition,
+        FileSource, FullFileSource, LexicalError, Literal, OutputLocation, Span, Token,
     },
 };
 use isatty::stdout_isatty;
 use spinners::{Spinner, Spinners};
-use std::{collections::BTreeMap, io::Write, path::Path, rc::Rc, sync::Arc, time::Instant};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{Read, Write},
+    path::Path,
+    rc::Rc,
+    sync::Arc,
+    time::Instant,
+};
 use yansi::Paint;
 
 /// The Huff CLI Args
 #[derive(ClapParser, Debug, Clone)]
 #[clap(name = "huffc", version, about, long_about = None)]
 struct Huff {
-    /// The contract(s) to compile.
+    /// The contract(s) to compile, or "-" to read a contract from stdin.
     pub path: Option<String>,
 
     /// The contracts source path.
     #[clap(short = 's', long = "source-path", default_value = "./contracts")]
     source: String,
 
-    /// The output file path.
+    /// The output file path, or "-" to write artifacts to stdout.
     #[clap(short = 'o', long = "output")]
     output: Option<String>,
 
@@ -46,10 +75,31 @@ struct Huff {
     #[clap(short = 'd', long = "output-directory", default_value = "./artifacts")]
     outputdir: String,
 
+    /// How artifacts are laid out in the output directory, either "uppercase" or "mirror".
+    #[clap(long = "layout", default_value = "uppercase")]
+    layout: OutputLayout,
+
+    /// Write Hardhat artifacts, laid out like a Hardhat artifacts directory.
+    #[clap(long = "hardhat", conflicts_with = "foundry")]
+    hardhat: bool,
+
+    /// Write Foundry artifacts, laid out like a Foundry out directory.
+    #[clap(long = "foundry")]
+    foundry: bool,
+
     /// The input constructor arguments
     #[clap(short = 'i', long = "inputs", multiple_values = true)]
     inputs: Option<Vec<String>>,
 
+    /// A file of constructor arguments, either a json array or ABI-encoded hex
+    #[clap(long = "constructor-args-path", conflicts_with_all = &["inputs", "interactive"])]
+    constructor_args_path: Option<String>,
+
+    /// Check the storage layout against a previous layout, either an artifact or its
+    /// "storageLayout"
+    #[clap(long = "storage-check")]
+    storage_check: Option<String>,
+
     /// Interactively input the constructor args
     #[clap(short = 'n', long = "interactive")]
     interactive: bool,
@@ -67,13 +117,25 @@ struct Huff {
     interface: Option<String>,
 
     /// Generate and log bytecode.
-    #[clap(short = 'b', long = "bytecode")]
+    #[clap(short = 'b', long = "bytecode", alias = "bin")]
     bytecode: bool,
 
     /// Generate and log runtime bytecode.
     #[clap(short = 'r', long = "bin-runtime")]
     bin_runtime: bool,
 
+    /// Print the calldata registering each contract in an on-chain metadata registry.
+    #[clap(long = "registry-calldata")]
+    registry_calldata: bool,
+
+    /// Print how many bytes of each contract's runtime bytecode each macro generates.
+    #[clap(long = "size-report")]
+    size_report: bool,
+
+    /// Label the compiled contracts, followed by their names when several are compiled.
+    #[clap(long = "name")]
+    name: Option<String>,
+
     /// Prints out to the terminal.
     #[clap(short = 'p', long = "print")]
     print: bool,
@@ -86,14 +148,71 @@ struct Huff {
     #[clap(short = 'c', long = "constants", multiple_values = true)]
     constants: Option<Vec<String>>,
 
-    /// Compile a specific macro
-    #[clap(short = 'm', long = "alt-main")]
+    /// Compile a specific macro as the main entry point, instead of MAIN
+    #[clap(short = 'm', long = "alt-main", visible_alias = "main", value_name = "MACRO")]
     alternative_main: Option<String>,
 
-    /// Compile a specific constructor macro
-    #[clap(short = 'l', long = "alt-constructor")]
+    /// Compile a single macro into standalone bytecode and print it.
+    #[clap(long = "macro")]
+    macro_name: Option<String>,
+
+    /// Compile a specific macro as the constructor, instead of CONSTRUCTOR
+    #[clap(
+        short = 'l',
+        long = "alt-constructor",
+        visible_alias = "constructor",
+        value_name = "MACRO"
+    )]
     alternative_constructor: Option<String>,
 
+    /// The EVM version (hard fork) to target.
+    #[clap(short = 'e', long = "evm-version", default_value = "cancun")]
+    evm_version: EVMVersion,
+
+    /// The language edition of files not declaring their own with `#pragma edition`.
+    #[clap(long = "edition", default_value = "2024")]
+    edition: Edition,
+
+    /// Read "std/" includes from a directory instead of the embedded standard library.
+    #[clap(long = "std-path")]
+    std_path: Option<String>,
+
+    /// Fail unless the standard library in use is this version.
+    #[clap(long = "std-version")]
+    std_version: Option<String>,
+
+    /// The maximum number of macros that may be expanded within one another.
+    #[clap(long = "macro-nesting-limit", default_value_t = DEFAULT_MACRO_NESTING_LIMIT)]
+    macro_nesting_limit: usize,
+
+    /// Error on jumps whose destination isn't a label, i.e. computed at runtime.
+    #[clap(long = "strict-jumps")]
+    strict_jumps: bool,
+
+    /// Error unless each declared function's selector reaches its own body in the dispatch.
+    #[clap(long = "check-dispatch")]
+    check_dispatch: bool,
+
+    /// Output EOF containers (EIP-3540) separating code from tables [experimental].
+    #[clap(long = "eof")]
+    eof: bool,
+
+    /// Revert calls matching none of the selectors of the main macro's dispatch.
+    #[clap(long = "auto-revert")]
+    auto_revert: bool,
+
+    /// Print the parsed AST of each contract as JSON.
+    #[clap(long = "ast-json")]
+    ast_json: bool,
+
+    /// Only run the lexer, printing the token stream of each contract as JSON.
+    #[clap(long = "lex")]
+    lex: bool,
+
+    /// Print the control flow graph of the main macro of each contract as a DOT graph.
+    #[clap(long = "cfg")]
+    cfg: bool,
+
     /// Test subcommand
     #[clap(subcommand)]
     test: Option<TestCommands>,
@@ -110,9 +229,155 @@ enum TestCommands {
         /// Match a specific test
         #[clap(short = 'm', long = "match")]
         match_: Option<String>,
+
+        /// Report the lines and macros executed by the tests.
+        #[clap(long = "coverage")]
+        coverage: bool,
+
+        /// Write the coverage report to an lcov tracefile, implies --coverage.
+        #[clap(long = "lcov")]
+        lcov: Option<String>,
+
+        /// The number of calls of each fuzzed test, unless set with the `runs` decorator flag.
+        #[clap(long = "fuzz-runs", default_value_t = DEFAULT_FUZZ_RUNS)]
+        fuzz_runs: u64,
+
+        /// The seed of fuzzed test arguments, unless set with the `seed` decorator flag.
+        #[clap(long = "fuzz-seed")]
+        fuzz_seed: Option<u64>,
+    },
+    /// Lint subcommand
+    Lint {
+        /// The lint configuration file, defaults to `huff-lint.toml` in the current directory.
+        #[clap(long = "config")]
+        config: Option<String>,
+    },
+    /// Migrate sources from deprecated syntax to the latest edition, keeping their comments and
+    /// formatting
+    Fix {
+        /// Print the changes as a diff instead of writing them, failing if there are any.
+        #[clap(long = "dry-run")]
+        dry_run: bool,
+    },
+    /// Install the dependencies declared in huff.toml under lib/, adding their remappings
+    Install {
+        /// Fetch dependencies even if they're already installed at the declared tag.
+        #[clap(long = "force")]
+        force: bool,
+    },
+    /// Generate an ERC-1167 minimal proxy or an EIP-1967 transparent proxy
+    Proxy {
+        /// The kind of proxy, either "minimal" or "transparent".
+        kind: ProxyKind,
+
+        /// The implementation address, a constructor argument if unset.
+        #[clap(long = "implementation")]
+        implementation: Option<String>,
+
+        /// The admin address of a transparent proxy, a constructor argument if unset.
+        #[clap(long = "admin")]
+        admin: Option<String>,
+
+        /// The file to write the proxy's artifact to, otherwise its creation code is printed.
+        #[clap(short = 'o', long = "output")]
+        output: Option<String>,
+    },
+    /// Generate an SSTORE2 data contract storing the bytes of a data section or of a file
+    ///
+    /// The bytes of the file at the path are stored, unless a data section of the contract at
+    /// the path is passed.
+    Sstore2 {
+        /// The data section to store, i.e. `PALETTE` for `#define data PALETTE = 0x...`.
+        #[clap(long = "data")]
+        data: Option<String>,
+
+        /// The deployer of the data contract, printing the address it's deployed at by CREATE2.
+        #[clap(long = "deployer", requires = "salt")]
+        deployer: Option<String>,
+
+        /// The CREATE2 salt, a hex encoded word.
+        #[clap(long = "salt", requires = "deployer")]
+        salt: Option<String>,
+
+        /// The file to write the data contract's artifact to, otherwise its creation code is
+        /// printed.
+        #[clap(short = 'o', long = "output")]
+        output: Option<String>,
+    },
+    /// Record the runtime bytecode size and hash of each contract and macro to a snapshot file
+    Snapshot {
+        /// The snapshot file, defaults to .huff-snapshot, or .gas-snapshot with --gas.
+        #[clap(long = "snapshot")]
+        snapshot: Option<String>,
+
+        /// Compare against the snapshot file instead of updating it, failing if they differ.
+        #[clap(long = "check")]
+        check: bool,
+
+        /// Record the gas used by each test instead of the bytecode.
+        #[clap(long = "gas")]
+        gas: bool,
+    },
+    /// Generate the payload to verify a contract on a block explorer, submitting it with an API
+    /// key
+    VerifyPayload {
+        /// The contract to verify, if several are compiled.
+        #[clap(long = "contract")]
+        contract: Option<String>,
+
+        /// The address of the deployed contract.
+        #[clap(long = "address")]
+        address: Option<String>,
+
+        /// The explorer API key, submitting the payload if set.
+        #[clap(long = "api-key", requires = "address")]
+        api_key: Option<String>,
+
+        /// The explorer API the payload is submitted to.
+        #[clap(long = "api-url", default_value = DEFAULT_VERIFY_API_URL)]
+        api_url: String,
+
+        /// The file to write the payload to, otherwise it's printed unless submitted.
+        #[clap(short = 'o', long = "output")]
+        output: Option<String>,
+    },
+    /// Diff the runtime bytecode of two contracts instruction by instruction, aligned by macro
+    /// and label
+    ///
+    /// Each side is either a Huff source, compiled with the other options passed, or a JSON
+    /// artifact. Exits with an error if the bytecodes differ.
+    Diff {
+        /// The old contract, a Huff source or an artifact.
+        old: String,
+
+        /// The new contract, a Huff source or an artifact.
+        new: String,
+    },
+    /// Interactively run Huff statements, printing the resulting stack and memory
+    ///
+    /// The definitions of the contract at the path, if any, may be used by the statements.
+    Repl,
+    /// Internal tooling subcommands
+    #[clap(subcommand)]
+    Internal(InternalCommands),
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum InternalCommands {
+    /// Emit a TextMate syntax highlighting grammar generated from the lexer's tables
+    EmitGrammar {
+        /// The file to write the grammar to, defaults to stdout.
+        #[clap(short = 'o', long = "output")]
+        output: Option<String>,
     },
 }
 
+/// The path argument and output location that stand for stdin and stdout
+const STDIO: &str = "-";
+
+/// The path contracts read from stdin are compiled as
+const STDIN_FILE: &str = "stdin.huff";
+
 /// Helper function to read an stdin input
 pub(crate) fn get_input(prompt: &str) -> String {
     // let mut sp = Spinner::new(Spinners::Line, format!("{}{}",
@@ -125,6 +390,136 @@ pub(crate) fn get_input(prompt: &str) -> String {
     input.trim().to_string()
 }
 
+/// The commands of the REPL, other than definitions and statements
+const REPL_HELP: &str = "Enter statements to run, or definitions to use in later statements
+  .undo   discard the last statements
+  .reset  discard all statements and definitions
+  .state  print the state after the statements entered
+  .exit   exit the REPL";
+
+/// Read lines from stdin into a REPL session until exited, printing the result of each entry
+fn run_repl(mut repl: Repl) {
+    println!("{}", Paint::blue(REPL_HELP));
+    loop {
+        print!("{} ", Paint::blue("huff>"));
+        let _ = std::io::stdout().flush();
+        let mut input = String::new();
+        match std::io::stdin().read_line(&mut input) {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {}
+        }
+
+        let res = match input.trim() {
+            ".exit" => return,
+            ".help" => {
+                println!("{REPL_HELP}");
+                continue
+            }
+            ".undo" => {
+                match repl.undo() {
+                    Some(statements) => println!("Discarded \"{statements}\""),
+                    None => println!("No statements to discard"),
+                }
+                continue
+            }
+            ".reset" => {
+                repl.reset();
+                continue
+            }
+            ".state" => repl.current().map(Some),
+            input => repl.eval(input),
+        };
+        match res {
+            Ok(Some(res)) if matches!(res.status, TestStatus::Revert) => {
+                println!("{}", Paint::red(res))
+            }
+            Ok(Some(res)) => println!("{res}"),
+            Ok(None) => {}
+            Err(e) => eprintln!("{}", Paint::red(e)),
+        }
+    }
+}
+
+/// Prints the creation code of a data contract, or writes its artifact to the output, along with
+/// the address it's deployed at by the deployer with the salt, if set
+fn print_data_contract(
+    data_contract: Result<DataContract, CompilerError>,
+    deployer: Option<&str>,
+    salt: Option<&str>,
+    output: Option<&str>,
+) {
+    let res = data_contract.and_then(|d| {
+        let address = match (deployer, salt) {
+            (Some(deployer), Some(salt)) => Some(d.create2_address(deployer, salt)?),
+            _ => None,
+        };
+        Ok((d.artifact()?, address))
+    });
+    let (artifact, address) = match res {
+        Ok(res) => res,
+        Err(e) => {
+            eprintln!("{}", Paint::red(e));
+            std::process::exit(1);
+        }
+    };
+    match output {
+        Some(path) => {
+            if let Err(e) = artifact.export(path) {
+                eprintln!(
+                    "{}",
+                    Paint::red(format!("Failed to write data contract to {path}: {e}"))
+                );
+                std::process::exit(1);
+            }
+        }
+        None => println!("{}", artifact.bytecode),
+    }
+    if let Some(address) = address {
+        println!("CREATE2 address: {address}");
+    }
+}
+
+/// The artifact of a side of a diff, read from a JSON artifact or compiled from a Huff source
+fn diff_artifact(compiler: &Compiler, path: &str) -> Arc<Artifact> {
+    if path.ends_with(".json") {
+        let artifact = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read artifact {path}: {e}"))
+            .and_then(|json| {
+                serde_json::from_str::<Artifact>(&json)
+                    .map_err(|e| format!("Invalid artifact {path}: {e}"))
+            });
+        return match artifact {
+            Ok(artifact) => Arc::new(artifact),
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let compiler = Compiler {
+        sources: Arc::new(vec![path.to_string()]),
+        output: None,
+        cached: false,
+        ..compiler.clone()
+    };
+    match compiler.execute() {
+        // The contract of the file itself rather than those of its `#define contract` blocks
+        Ok(artifacts) => match artifacts.iter().find(|a| a.contract_name.is_none()) {
+            Some(artifact) => Arc::clone(artifact),
+            None => {
+                eprintln!("{}", Paint::red(format!("No contract compiled from {path}")));
+                std::process::exit(1);
+            }
+        },
+        Err(e) => {
+            tracing::error!(target: "cli", "COMPILER ERRORED: {}", e);
+            eprintln!("{}", Paint::red(e));
+            std::process::exit(1);
+        }
+    }
+}
+
 fn main() {
     // Into App
     let app: App = Huff::into_app();
@@ -137,6 +532,109 @@ fn main() {
         Compiler::init_tracing_subscriber(Some(vec![tracing::Level::DEBUG.into()]));
     }
 
+    if let Some(TestCommands::Internal(InternalCommands::EmitGrammar { output })) = &cli.test {
+        let grammar = match serde_json::to_string_pretty(&textmate_grammar()) {
+            Ok(g) => g,
+            Err(e) => {
+                eprintln!("{}", Paint::red(format!("Failed to serialize grammar: {e}")));
+                std::process::exit(1);
+            }
+        };
+        match output {
+            Some(path) => {
+                if let Err(e) = std::fs::write(path, grammar) {
+                    eprintln!("{}", Paint::red(format!("Failed to write grammar to {path}: {e}")));
+                    std::process::exit(1);
+                }
+            }
+            None => println!("{grammar}"),
+        }
+        return;
+    }
+
+    if let Some(TestCommands::Install { force }) = &cli.test {
+        match Installer::new(".", *force).install() {
+            Ok(packages) => {
+                for package in &packages {
+                    let status = match package.status {
+                        InstallStatus::Installed => Paint::green("installed"),
+                        InstallStatus::UpToDate => Paint::blue("up to date"),
+                    };
+                    println!("{} {} ({status})", package.name, package.rev);
+                }
+                println!("{}", Paint::green(format!("Resolved {} dependencies", packages.len())));
+            }
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(TestCommands::Proxy { kind, implementation, admin, output }) = &cli.test {
+        let proxy = Proxy::new(*kind, implementation.as_deref(), admin.as_deref());
+        let artifact = match proxy.artifact() {
+            Ok(a) => a,
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        };
+        match output {
+            Some(path) => {
+                if let Err(e) = artifact.export(path) {
+                    eprintln!("{}", Paint::red(format!("Failed to write proxy to {path}: {e}")));
+                    std::process::exit(1);
+                }
+            }
+            None => println!("{}", artifact.bytecode),
+        }
+        return;
+    }
+
+    // Files are stored as they are, rather than compiled
+    if let Some(TestCommands::Sstore2 { data: None, deployer, salt, output }) = &cli.test {
+        let data_contract = match cli.path.as_deref() {
+            Some(path) => {
+                std::fs::read(path).map(|bytes| DataContract::new(path, bytes)).map_err(|e| {
+                    CompilerError::InvalidDataContract(format!("Failed to read {path}: {e}"))
+                })
+            }
+            None => Err(CompilerError::InvalidDataContract(
+                "Pass the file to store as the path, or a data section with --data".to_string(),
+            )),
+        };
+        print_data_contract(data_contract, deployer.as_deref(), salt.as_deref(), output.as_deref());
+        return;
+    }
+
+    // Read the contract from stdin, so it's compiled without touching the filesystem
+    let from_stdin = cli.path.as_deref() == Some(STDIO);
+    let file_provider: Arc<dyn FileProvider> = match from_stdin {
+        true => {
+            if cli.interactive {
+                eprintln!(
+                    "{}",
+                    Paint::red(
+                        "Cannot interactively input constructor args when reading from stdin"
+                    )
+                );
+                std::process::exit(1);
+            }
+            let mut source = String::new();
+            if let Err(e) = std::io::stdin().read_to_string(&mut source) {
+                eprintln!("{}", Paint::red(format!("Failed to read from stdin: {e}")));
+                std::process::exit(1);
+            }
+            Arc::new(InMemoryFileProvider::new(HashMap::from([(STDIN_FILE.to_string(), source)])))
+        }
+        false => Arc::new(FileSystemFileProvider {}),
+    };
+
+    // Write artifacts to stdout instead of an output location
+    let to_stdout = cli.output.as_deref() == Some(STDIO);
+
     // Create compiler from the Huff Args
     let sources: Arc<Vec<String>> = match cli.get_inputs() {
         Ok(s) => Arc::new(s),
@@ -173,7 +671,8 @@ fn main() {
             .collect()
     });
 
-    let mut use_cache = true;
+    // Cached artifacts are read from the filesystem
+    let mut use_cache = !from_stdin && !to_stdout;
     if cli.interactive {
         // Don't accept configured inputs
         cli.inputs = None;
@@ -185,7 +684,15 @@ fn main() {
         use_cache = false;
     }
 
-    let output = match (&cli.output, cli.artifacts) {
+    let output_format = match (cli.hardhat, cli.foundry) {
+        (true, _) => OutputFormat::Hardhat,
+        (_, true) => OutputFormat::Foundry,
+        _ => OutputFormat::Huff,
+    };
+
+    // Hardhat and Foundry artifacts are always written
+    let output = match (&cli.output, cli.artifacts || output_format != OutputFormat::Huff) {
+        _ if to_stdout => None,
         (Some(o), true) => Some(o.clone()),
         (None, true) => Some(cli.outputdir.clone()),
         _ => None,
@@ -194,26 +701,499 @@ fn main() {
     let compiler: Compiler = Compiler {
         sources: Arc::clone(&sources),
         output,
+        name: cli.name.clone(),
+        output_layout: cli.layout,
+        output_format,
         alternative_main: cli.alternative_main,
         alternative_constructor: cli.alternative_constructor,
         construct_args: cli.inputs,
+        constructor_args_path: cli.constructor_args_path,
+        storage_check_path: cli.storage_check,
         constant_overrides: constants,
+        evm_version: cli.evm_version,
+        edition: cli.edition,
+        macro_nesting_limit: cli.macro_nesting_limit,
+        strict_jumps: cli.strict_jumps,
+        check_dispatch: cli.check_dispatch,
         optimize: cli.optimize,
+        eof: cli.eof,
+        auto_revert: cli.auto_revert,
         bytecode: cli.bytecode,
         cached: use_cache,
-        file_provider: Arc::new(FileSystemFileProvider {}),
+        file_provider,
+        std_lib: StdLib::new(cli.std_path, cli.std_version),
     };
 
-    if let Some(TestCommands::Test { format, match_ }) = cli.test {
+    if cli.lex {
+        let files = match compiler.file_provider.transform_paths(&compiler.sources) {
+            Ok(paths) => Compiler::fetch_sources(paths, compiler.file_provider.clone()),
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        };
+
+        // Lex each file on its own, without flattening its imports
+        let mut streams: BTreeMap<String, Vec<Token>> = BTreeMap::new();
+        for file in files {
+            let file = match file {
+                Ok(f) => f,
+                Err(e) => {
+                    eprintln!("{}", Paint::red(e));
+                    std::process::exit(1);
+                }
+            };
+            let flattened = FileSource::fully_flatten(Arc::clone(&file));
+            let full_source = FullFileSource {
+                source: &flattened.0,
+                file: Some(Arc::clone(&file)),
+                spans: flattened.1,
+            };
+            let mut lexer = Lexer::new(full_source);
+            lexer.edition = cli.edition;
+            match lexer.into_iter().collect::<Result<Vec<Token>, LexicalError>>() {
+                Ok(tokens) => {
+                    streams.insert(file.path.clone(), tokens);
+                }
+                Err(e) => {
+                    tracing::error!(target: "cli", "LEXER ERRORED!");
+                    eprintln!("{}", Paint::red(CompilerError::LexicalError(e)));
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        match serde_json::to_string_pretty(&streams) {
+            Ok(json) => println!("{json}"),
+            Err(e) => {
+                eprintln!("{}", Paint::red(format!("Failed to serialize tokens: {e}")));
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if cli.ast_json {
+        match compiler.grab_contracts() {
+            Ok(contracts) => match serde_json::to_string_pretty(&contracts) {
+                Ok(json) => println!("{json}"),
+                Err(e) => {
+                    eprintln!("{}", Paint::red(format!("Failed to serialize AST: {e}")));
+                    std::process::exit(1);
+                }
+            },
+            Err(e) => {
+                tracing::error!(target: "cli", "PARSER ERRORED!");
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if cli.cfg {
+        match compiler.control_flow_graphs() {
+            Ok(graphs) => graphs.values().for_each(|cfg| print!("{}", cfg.to_dot())),
+            Err(e) => {
+                tracing::error!(target: "cli", "COMPILER ERRORED: {}", e);
+                eprintln!("{}", Paint::red(format!("{e}")));
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(name) = &cli.macro_name {
+        match compiler.compile_macro(name) {
+            Ok(compiled) => match compiled.len() {
+                1 => println!("{}", compiled.values().next().unwrap()),
+                _ => compiled
+                    .iter()
+                    .for_each(|(path, bytecode)| println!("\"{path}\" {name}: {bytecode}")),
+            },
+            Err(e) => {
+                tracing::error!(target: "cli", "COMPILER ERRORED: {}", e);
+                eprintln!("{}", Paint::red(format!("{e}")));
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(TestCommands::Lint { config }) = &cli.test {
+        let config = match config {
+            Some(path) => LintConfig::from_file(Path::new(path)),
+            None => LintConfig::discover(Path::new(".")),
+        };
+        let linter = match config {
+            Ok(config) => Linter::new(config),
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        };
+
+        match compiler.grab_contracts() {
+            Ok(contracts) => {
+                // Imported macros are shared between contracts, only report them once
+                let mut lints = vec![];
+                for lint in contracts.iter().flat_map(|contract| linter.lint(contract)) {
+                    if !lints.contains(&lint) {
+                        lints.push(lint);
+                    }
+                }
+
+                for lint in &lints {
+                    match lint.level {
+                        Level::Deny => eprintln!("{}", Paint::red(lint)),
+                        _ => eprintln!("{}", Paint::yellow(lint)),
+                    }
+                }
+                if lints.iter().any(|lint| lint.level == Level::Deny) {
+                    std::process::exit(1);
+                }
+                println!("{}", Paint::green(format!("Linting produced {} warnings", lints.len())));
+            }
+            Err(e) => {
+                tracing::error!(target: "cli", "PARSER ERRORED!");
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(TestCommands::Fix { dry_run }) = &cli.test {
+        let files = match compiler.file_provider.transform_paths(&compiler.sources) {
+            Ok(paths) => Compiler::fetch_sources(paths, compiler.file_provider.clone()),
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        };
+
+        let mut fixed = 0;
+        for file in files {
+            let file = match file {
+                Ok(f) => f,
+                Err(e) => {
+                    eprintln!("{}", Paint::red(e));
+                    std::process::exit(1);
+                }
+            };
+            let source = file.source.as_deref().unwrap_or_default();
+            let fix = match Fix::new(&file.path, source, &compiler.evm_version) {
+                // Sources read from stdin are written to stdout, fixed or not
+                Ok(fix) if from_stdin && !*dry_run => {
+                    print!("{}", fix.fixed);
+                    return
+                }
+                Ok(fix) if fix.is_empty() => continue,
+                Ok(fix) => fix,
+                Err(e) => {
+                    eprintln!("{}", Paint::red(e));
+                    std::process::exit(1);
+                }
+            };
+            fixed += 1;
+            let migrations =
+                fix.migrations().iter().map(|m| m.to_string()).collect::<Vec<String>>().join(", ");
+            if *dry_run {
+                print!("{}", fix.diff());
+            } else if let Err(e) = std::fs::write(&fix.path, &fix.fixed) {
+                eprintln!("{}", Paint::red(format!("Failed to write {}: {e}", fix.path)));
+                std::process::exit(1);
+            }
+            eprintln!("{}", Paint::yellow(format!("{}: {migrations}", fix.path)));
+        }
+
+        match (fixed, *dry_run) {
+            (0, _) => println!("{}", Paint::green("No deprecated syntax found")),
+            (n, true) => {
+                eprintln!("{}", Paint::red(format!("{n} files use deprecated syntax")));
+                std::process::exit(1);
+            }
+            (n, false) => println!("{}", Paint::green(format!("Fixed {n} files"))),
+        }
+        return;
+    }
+
+    if let Some(TestCommands::Diff { old, new }) = &cli.test {
+        let (old_artifact, new_artifact) =
+            (diff_artifact(&compiler, old), diff_artifact(&compiler, new));
+        match BytecodeDiff::new(&old_artifact, &new_artifact) {
+            Ok(diff) if diff.is_empty() => {
+                println!("{}", Paint::green(format!("No differences between {old} and {new}")))
+            }
+            Ok(diff) => {
+                println!("--- {old}\n+++ {new}");
+                print!("{diff}");
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("{}", Paint::red(format!("Failed to disassemble bytecode: {e}")));
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(TestCommands::Repl) = &cli.test {
+        let contract = match &cli.path {
+            Some(_) => match compiler.grab_contracts() {
+                Ok(mut contracts) if !contracts.is_empty() => contracts.remove(0),
+                Ok(_) => Default::default(),
+                Err(e) => {
+                    tracing::error!(target: "cli", "PARSER ERRORED!");
+                    eprintln!("{}", Paint::red(e));
+                    std::process::exit(1);
+                }
+            },
+            None => Default::default(),
+        };
+        run_repl(Repl::new(contract, compiler.evm_version));
+        return;
+    }
+
+    if let Some(TestCommands::Sstore2 { data: Some(section), deployer, salt, output }) = &cli.test {
+        let data_contract = match compiler.grab_contracts() {
+            Ok(contracts) => match contracts.first() {
+                Some(contract) => DataContract::from_section(contract, section),
+                None => Err(CompilerError::InvalidDataContract(format!(
+                    "Missing data section \"{section}\""
+                ))),
+            },
+            Err(e) => {
+                tracing::error!(target: "cli", "PARSER ERRORED!");
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        };
+        print_data_contract(data_contract, deployer.as_deref(), salt.as_deref(), output.as_deref());
+        return;
+    }
+
+    if let Some(TestCommands::Snapshot { snapshot, check, gas: true }) = &cli.test {
+        let snapshot = snapshot.as_deref().unwrap_or(DEFAULT_GAS_SNAPSHOT_PATH);
+        let contracts = match compiler.grab_contracts() {
+            Ok(contracts) => contracts,
+            Err(e) => {
+                tracing::error!(target: "cli", "PARSER ERRORED!");
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        };
+
+        // Fuzzed tests use a fixed seed, so the mean gas of their calls is reproducible
+        let mut new = GasSnapshot::default();
+        let mut failed = false;
+        for contract in &contracts {
+            let mut tester = HuffTester::new(contract, Rc::new(None));
+            tester.runner.fuzz = FuzzConfig { runs: DEFAULT_FUZZ_RUNS, seed: Some(0) };
+            let start = Instant::now();
+            match tester.execute() {
+                Ok(res) => {
+                    failed |= res.iter().any(|r| matches!(r.status, TestStatus::Revert));
+                    new.record(contract, &res);
+                    print_test_report(res, ReportKind::List, start);
+                }
+                Err(e) => {
+                    eprintln!("{}", Paint::red(e));
+                    std::process::exit(1);
+                }
+            }
+        }
+        if failed {
+            eprintln!("{}", Paint::red("Failed to snapshot gas, some tests failed"));
+            std::process::exit(1);
+        }
+
+        let old = match std::fs::read_to_string(snapshot) {
+            Ok(contents) => match contents.parse::<GasSnapshot>() {
+                Ok(old) => Some(old),
+                Err(e) => {
+                    eprintln!("{}", Paint::red(format!("Failed to parse {snapshot}: {e}")));
+                    std::process::exit(1);
+                }
+            },
+            Err(_) => None,
+        };
+
+        if let Some(old) = &old {
+            let diffs = old.diff(&new);
+            for diff in &diffs {
+                match diff.delta() {
+                    d if d < 0 => eprintln!("{}", Paint::green(diff)),
+                    d if d > 0 => eprintln!("{}", Paint::red(diff)),
+                    _ => eprintln!("{}", Paint::yellow(diff)),
+                }
+            }
+            let delta = diffs.iter().map(GasDiff::delta).sum::<i128>();
+            println!(
+                "Overall gas change: {delta:+} ({:+.3}%)",
+                delta as f64 * 100.0 / old.total().max(1) as f64
+            );
+        }
+
+        if *check {
+            match &old {
+                None => {
+                    eprintln!("{}", Paint::red(format!("Snapshot file {snapshot} not found")));
+                    std::process::exit(1);
+                }
+                Some(old) if *old != new => {
+                    eprintln!("{}", Paint::red(format!("Gas usage differs from {snapshot}")));
+                    std::process::exit(1);
+                }
+                Some(_) => {
+                    println!("{}", Paint::green(format!("Gas usage matches {snapshot}")));
+                    return;
+                }
+            }
+        }
+
+        if let Err(e) = std::fs::write(snapshot, new.to_string()) {
+            eprintln!("{}", Paint::red(format!("Failed to write {snapshot}: {e}")));
+            std::process::exit(1);
+        }
+        println!("{}", Paint::green(format!("Wrote gas snapshot to {snapshot}")));
+        return;
+    }
+
+    if let Some(TestCommands::Snapshot { snapshot, check, .. }) = &cli.test {
+        let snapshot = snapshot.as_deref().unwrap_or(DEFAULT_SNAPSHOT_PATH);
+        let new = match compiler.snapshot() {
+            Ok(new) => new,
+            Err(e) => {
+                tracing::error!(target: "cli", "COMPILER ERRORED: {}", e);
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        };
+        let old = match std::fs::read_to_string(snapshot) {
+            Ok(contents) => match contents.parse::<Snapshot>() {
+                Ok(old) => Some(old),
+                Err(e) => {
+                    eprintln!("{}", Paint::red(format!("Failed to parse {snapshot}: {e}")));
+                    std::process::exit(1);
+                }
+            },
+            Err(_) => None,
+        };
+
+        let diffs = old.as_ref().map(|old| old.diff(&new)).unwrap_or_default();
+        for diff in &diffs {
+            match (&diff.old, &diff.new) {
+                (None, _) => eprintln!("{}", Paint::green(diff)),
+                (_, None) => eprintln!("{}", Paint::red(diff)),
+                _ => eprintln!("{}", Paint::yellow(diff)),
+            }
+        }
+
+        if *check {
+            if old.is_none() {
+                eprintln!("{}", Paint::red(format!("Snapshot file {snapshot} not found")));
+                std::process::exit(1);
+            }
+            if !diffs.is_empty() {
+                eprintln!("{}", Paint::red(format!("Bytecode differs from {snapshot}")));
+                std::process::exit(1);
+            }
+            println!("{}", Paint::green(format!("Bytecode matches {snapshot}")));
+            return;
+        }
+
+        if let Err(e) = std::fs::write(snapshot, new.to_string()) {
+            eprintln!("{}", Paint::red(format!("Failed to write {snapshot}: {e}")));
+            std::process::exit(1);
+        }
+        println!("{}", Paint::green(format!("Wrote snapshot to {snapshot}")));
+        return;
+    }
+
+    if let Some(TestCommands::VerifyPayload { contract, address, api_key, api_url, output }) =
+        &cli.test
+    {
+        let artifacts = match compiler.execute() {
+            Ok(artifacts) => artifacts,
+            Err(e) => {
+                tracing::error!(target: "cli", "COMPILER ERRORED: {}", e);
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        };
+        let names = artifacts.iter().map(|a| a.default_name()).collect::<Vec<String>>();
+        let selected = artifacts
+            .iter()
+            .filter(|a| contract.as_ref().map(|c| a.default_name() == *c).unwrap_or(true))
+            .collect::<Vec<_>>();
+        let artifact = match (selected.as_slice(), contract) {
+            ([artifact], _) => artifact,
+            ([], Some(contract)) => {
+                eprintln!("{}", Paint::red(format!("No contract named \"{contract}\" compiled")));
+                std::process::exit(1);
+            }
+            _ => {
+                eprintln!(
+                    "{}",
+                    Paint::red(format!(
+                        "Compiled {} contracts, select one with --contract: {}",
+                        names.len(),
+                        names.join(", ")
+                    ))
+                );
+                std::process::exit(1);
+            }
+        };
+
+        let payload = VerifyPayload::new(artifact, address.clone());
+        let json = serde_json::to_string_pretty(&payload).unwrap_or_default();
+        match output {
+            Some(path) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    eprintln!("{}", Paint::red(format!("Failed to write payload to {path}: {e}")));
+                    std::process::exit(1);
+                }
+            }
+            None if api_key.is_none() => println!("{json}"),
+            None => {}
+        }
+        if let Some(api_key) = api_key {
+            match payload.submit(api_url, api_key) {
+                Ok(guid) => {
+                    println!("{}", Paint::green(format!("Submitted verification request {guid}")))
+                }
+                Err(e) => {
+                    eprintln!("{}", Paint::red(e));
+                    std::process::exit(1);
+                }
+            }
+        }
+        return;
+    }
+
+    if let Some(TestCommands::Test { format, match_, coverage, lcov, fuzz_runs, fuzz_seed }) =
+        cli.test
+    {
         match compiler.grab_contracts() {
             Ok(contracts) => {
                 let match_ = Rc::new(match_);
+                let mut report: Option<CoverageReport> =
+                    (coverage || lcov.is_some()).then(CoverageReport::default);
 
                 for contract in &contracts {
-                    let tester = HuffTester::new(contract, Rc::clone(&match_));
+                    let mut tester = HuffTester::new(contract, Rc::clone(&match_));
+                    tester.runner.fuzz = FuzzConfig { runs: fuzz_runs, seed: fuzz_seed };
 
                     let start = Instant::now();
-                    match tester.execute() {
+                    let res = match &mut report {
+                        Some(report) => tester.execute_with_coverage().map(|(res, coverage)| {
+                            report.merge(coverage);
+                            res
+                        }),
+                        None => tester.execute(),
+                    };
+                    match res {
                         Ok(res) => {
                             print_test_report(res, ReportKind::from(&format), start);
                         }
@@ -223,6 +1203,19 @@ fn main() {
                         }
                     };
                 }
+
+                if let Some(report) = report {
+                    print_coverage_report(&report);
+                    if let Some(path) = lcov {
+                        if let Err(e) = std::fs::write(&path, report.to_lcov()) {
+                            eprintln!(
+                                "{}",
+                                Paint::red(format!("Failed to write coverage to {path}: {e}"))
+                            );
+                            std::process::exit(1);
+                        }
+                    }
+                }
             }
             Err(e) => {
                 tracing::error!(target: "cli", "PARSER ERRORED!");
@@ -230,7 +1223,7 @@ fn main() {
                 std::process::exit(1);
             }
         }
-        return
+        return;
     }
 
     // Create compiling spinner
@@ -276,6 +1269,23 @@ fn main() {
                 std::process::exit(1);
             }
 
+            // Print any warnings surfaced by static analysis
+            match compiler.analyze() {
+                Ok(warnings) => warnings.iter().for_each(|w| eprintln!("{}", Paint::yellow(w))),
+                Err(e) => tracing::warn!(target: "cli", "STATIC ANALYSIS FAILED: {}", e),
+            }
+
+            // Note the contracts deployed without a constructor
+            for artifact in artifacts.iter().filter(|a| a.default_constructor) {
+                eprintln!(
+                    "{}",
+                    Paint::cyan(format!(
+                        "Note: \"{}\" has no CONSTRUCTOR macro, its runtime bytecode is deployed with only the bootstrap code",
+                        artifact.identifier()
+                    ))
+                );
+            }
+
             if app.get_matches().is_present("interface") {
                 let mut interface: Option<String> = None;
                 if artifacts.len() == 1 {
@@ -338,7 +1348,7 @@ fn main() {
                                     println!(
                                         "{} Constructor Arguments for Contract: \"{}\"",
                                         Paint::blue("[INTERACTIVE]".to_string()),
-                                        artifact.file.path
+                                        artifact.identifier()
                                     );
                                     for input in &args.inputs {
                                         let arg_input = get_input(&format!(
@@ -348,17 +1358,18 @@ fn main() {
                                                 .then(|| format!(" \"{}\"", input.name))
                                                 .unwrap_or_default()
                                         ));
-                                        let encoded =
-                                            Codegen::encode_constructor_args(vec![arg_input])
-                                                .iter()
-                                                .fold(String::default(), |acc, str| {
-                                                    let inner: Vec<u8> =
-                                                        ethers_core::abi::encode(&[str.clone()]);
-                                                    let hex_args: String =
-                                                        hex::encode(inner.as_slice());
-                                                    format!("{acc}{hex_args}")
-                                                });
-                                        appended_args.push_str(&encoded);
+                                        let tokens = match ConstructorArgs::Values(vec![arg_input])
+                                            .tokenize(Some(std::slice::from_ref(input)))
+                                        {
+                                            Ok(tokens) => tokens,
+                                            Err(e) => {
+                                                eprintln!("{}", Paint::red(e));
+                                                std::process::exit(1);
+                                            }
+                                        };
+                                        appended_args.push_str(&hex::encode(
+                                            ethers_core::abi::encode(&tokens),
+                                        ));
                                     }
                                 }
                                 None => {
@@ -378,14 +1389,20 @@ fn main() {
                             }
                         }
                     }
-                    tracing::debug!(target: "cli", "Re-exporting artifacts...");
-                    Compiler::export_artifacts(
-                        &artifacts,
-                        &OutputLocation(cli.output.unwrap_or_else(|| cli.outputdir.clone())),
-                    );
-                    tracing::info!(target: "cli", "RE-EXPORTED INTERACTIVE ARTIFACTS");
+                    if !to_stdout {
+                        tracing::debug!(target: "cli", "Re-exporting artifacts...");
+                        Compiler::export_artifacts(
+                            &artifacts,
+                            &OutputLocation(
+                                cli.output.clone().unwrap_or_else(|| cli.outputdir.clone()),
+                            ),
+                            cli.layout,
+                            output_format,
+                        );
+                        tracing::info!(target: "cli", "RE-EXPORTED INTERACTIVE ARTIFACTS");
+                    }
                 }
-                match sources.len() {
+                match artifacts.len() {
                     1 => {
                         if cli.bin_runtime {
                             println!("\nbytecode: {}", artifacts[0].bytecode)
@@ -395,12 +1412,12 @@ fn main() {
                     }
                     _ => artifacts
                         .iter()
-                        .for_each(|a| println!("\"{}\" bytecode: {}", a.file.path, a.bytecode)),
+                        .for_each(|a| println!("\"{}\" bytecode: {}", a.identifier(), a.bytecode)),
                 }
             }
 
             if cli.bin_runtime {
-                match sources.len() {
+                match artifacts.len() {
                     1 => {
                         if cli.bytecode {
                             println!("\nruntime: {}", artifacts[0].runtime)
@@ -410,7 +1427,41 @@ fn main() {
                     }
                     _ => artifacts
                         .iter()
-                        .for_each(|a| println!("\"{}\" runtime: {}", a.file.path, a.runtime)),
+                        .for_each(|a| println!("\"{}\" runtime: {}", a.identifier(), a.runtime)),
+                }
+            }
+
+            if cli.registry_calldata {
+                for artifact in &artifacts {
+                    match artifact.registry_calldata() {
+                        Some(calldata) => println!("\"{}\" registry: {calldata}", artifact.label()),
+                        None => eprintln!(
+                            "{}",
+                            Paint::yellow(format!(
+                                "\"{}\" has no ABI to register",
+                                artifact.label()
+                            ))
+                        ),
+                    }
+                }
+            }
+
+            if cli.size_report {
+                artifacts.iter().for_each(|a| print!("{}", SizeReport::new(a)));
+            }
+
+            if to_stdout {
+                // A single contract is written as an artifact object, multiple as an array
+                let json = match artifacts.len() {
+                    1 => serde_json::to_string_pretty(&artifacts[0]),
+                    _ => serde_json::to_string_pretty(&artifacts),
+                };
+                match json {
+                    Ok(json) => println!("{json}"),
+                    Err(e) => {
+                        eprintln!("{}", Paint::red(format!("Failed to serialize artifacts: {e}")));
+                        std::process::exit(1);
+                    }
                 }
             }
         }
@@ -426,6 +1477,7 @@ impl Huff {
     /// Preprocesses input files for compiling
     pub fn get_inputs(&self) -> Result<Vec<String>, CompilerError> {
         match &self.path {
+            Some(path) if path == STDIO => Ok(vec![STDIN_FILE.to_string()]),
             Some(path) => {
                 tracing::debug!(target: "io", "FETCHING INPUT: {}", path);
                 // If the file is huff, we can use it
@@ -0,0 +1,276 @@
+use huff_utils::prelude::*;
+use std::{collections::BTreeMap, fmt::Write};
+
+/// Opcodes after which execution never continues with the next instruction
+const TERMINATING: [Opcode; 6] = [
+    Opcode::Jump,
+    Opcode::Stop,
+    Opcode::Return,
+    Opcode::Revert,
+    Opcode::Invalid,
+    Opcode::Selfdestruct,
+];
+
+/// The kind of an [Edge] between basic blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EdgeKind {
+    /// A `JUMP` to a known destination
+    Jump,
+    /// The taken branch of a `JUMPI`
+    Branch,
+    /// Execution continuing with the next block, including the untaken branch of a `JUMPI`
+    Fallthrough,
+}
+
+/// An edge of a [ControlFlowGraph], between the offsets of the first instructions of two blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Edge {
+    /// The block the edge leaves
+    pub from: usize,
+    /// The block the edge enters
+    pub to: usize,
+    /// The kind of edge
+    pub kind: EdgeKind,
+}
+
+/// A sequence of instructions only entered at its first and left at its last instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicBlock {
+    /// The offset of the first instruction
+    pub start: usize,
+    /// The offset after the last instruction
+    pub end: usize,
+    /// The instructions and their offsets
+    pub instructions: Vec<(usize, Instruction)>,
+    /// Whether the block ends with a jump whose destination isn't known at compile time
+    pub dynamic_jump: bool,
+}
+
+/// The control flow graph of generated bytecode
+///
+/// Blocks start at offset zero, at every `JUMPDEST` and after every jump or halting opcode. Jump
+/// destinations are known for jumps to labels and to literal offsets. The macro invocations the
+/// bytecode was generated by are kept, so the graph can be rendered with macro boundaries.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ControlFlowGraph {
+    /// The name of the compiled macro
+    pub name: String,
+    /// The basic blocks, ordered by offset
+    pub blocks: Vec<BasicBlock>,
+    /// The edges between blocks
+    pub edges: Vec<Edge>,
+    /// The bytecode generated by each macro invocation, parents before their children
+    pub activations: Vec<MacroActivation>,
+}
+
+impl ControlFlowGraph {
+    /// Builds the graph of the bytecode generated for a macro
+    pub fn new(name: &str, res: &BytecodeRes, mut activations: Vec<MacroActivation>) -> Self {
+        activations.sort_by_key(|a| (a.start, std::cmp::Reverse(a.end)));
+
+        // The jump destinations pushed by each push, by the offset of the push
+        let targets: BTreeMap<usize, usize> = res
+            .relocations
+            .iter()
+            .filter(|r| r.kind == RelocationKind::Label)
+            .filter_map(|r| r.target.map(|t| (r.offset - 1, t)))
+            .collect();
+
+        let mut blocks: Vec<BasicBlock> = vec![];
+        let mut edges = vec![];
+        // The destination pushed by the previous instruction, if any
+        let mut pushed = None;
+        for (offset, instruction) in &res.instructions {
+            let starts_block = blocks.last().map(|b: &BasicBlock| {
+                instruction.opcode == Some(Opcode::Jumpdest) ||
+                    b.instructions
+                        .last()
+                        .and_then(|(_, i)| i.opcode)
+                        .map(|o| o == Opcode::Jumpi || TERMINATING.contains(&o))
+                        .unwrap_or(false)
+            });
+            if starts_block.unwrap_or(true) {
+                if let Some(previous) = blocks.last() {
+                    let falls_through = previous
+                        .instructions
+                        .last()
+                        .and_then(|(_, i)| i.opcode)
+                        .map(|o| !TERMINATING.contains(&o))
+                        .unwrap_or(true);
+                    if falls_through {
+                        edges.push(Edge {
+                            from: previous.start,
+                            to: *offset,
+                            kind: EdgeKind::Fallthrough,
+                        });
+                    }
+                }
+                blocks.push(BasicBlock {
+                    start: *offset,
+                    end: *offset,
+                    instructions: vec![],
+                    dynamic_jump: false,
+                });
+            }
+
+            let block = blocks.last_mut().unwrap();
+            block.end = offset + instruction.len();
+            block.instructions.push((*offset, instruction.clone()));
+            match instruction.opcode {
+                Some(o @ (Opcode::Jump | Opcode::Jumpi)) => match pushed {
+                    Some(to) => edges.push(Edge {
+                        from: block.start,
+                        to,
+                        kind: if o == Opcode::Jump { EdgeKind::Jump } else { EdgeKind::Branch },
+                    }),
+                    None => block.dynamic_jump = true,
+                },
+                Some(o) if o.is_push() => {
+                    pushed =
+                        targets.get(offset).copied().or_else(|| match instruction.placeholder {
+                            0 => instruction.immediate.iter().try_fold(0usize, |acc, b| {
+                                acc.checked_mul(256)?.checked_add(*b as usize)
+                            }),
+                            _ => None,
+                        });
+                    continue
+                }
+                _ => {}
+            }
+            pushed = None;
+        }
+
+        // Jumps to offsets that aren't a JUMPDEST can't be taken
+        edges.retain(|e| {
+            e.kind == EdgeKind::Fallthrough ||
+                res.instructions
+                    .iter()
+                    .any(|(o, i)| *o == e.to && i.opcode == Some(Opcode::Jumpdest))
+        });
+        Self { name: name.to_string(), blocks, edges, activations }
+    }
+
+    /// Renders the graph in the DOT language of Graphviz
+    ///
+    /// Each macro invocation is drawn as a cluster around the blocks starting within it, jumps as
+    /// solid edges, the taken branches of `JUMPI`s as green edges and fallthroughs as dashed
+    /// edges. Blocks ending with a dynamic jump are drawn in red.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        let _ = writeln!(dot, "digraph \"{}\" {{", escape(&self.name));
+        let _ = writeln!(dot, "    node [shape=box, fontname=\"monospace\"];");
+
+        // Activations are ordered parents first, so the last one containing an offset is the
+        // innermost
+        let mut members: BTreeMap<Option<usize>, Vec<&BasicBlock>> = BTreeMap::new();
+        for block in &self.blocks {
+            let activation = self
+                .activations
+                .iter()
+                .rposition(|a| a.start <= block.start && block.start < a.end);
+            members.entry(activation).or_default().push(block);
+        }
+        let mut children: BTreeMap<Option<usize>, Vec<usize>> = BTreeMap::new();
+        for (i, a) in self.activations.iter().enumerate() {
+            let parent =
+                self.activations[..i].iter().rposition(|p| p.start <= a.start && a.end <= p.end);
+            children.entry(parent).or_default().push(i);
+        }
+        self.write_cluster(&mut dot, None, &members, &children, 1);
+
+        for edge in &self.edges {
+            let style = match edge.kind {
+                EdgeKind::Jump => "",
+                EdgeKind::Branch => " [color=\"darkgreen\", label=\"jumpi\"]",
+                EdgeKind::Fallthrough => " [style=dashed]",
+            };
+            let _ = writeln!(dot, "    b{} -> b{}{style};", edge.from, edge.to);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Writes the blocks of an invocation, or of no invocation, and the clusters of the
+    /// invocations within it
+    fn write_cluster(
+        &self,
+        dot: &mut String,
+        activation: Option<usize>,
+        members: &BTreeMap<Option<usize>, Vec<&BasicBlock>>,
+        children: &BTreeMap<Option<usize>, Vec<usize>>,
+        depth: usize,
+    ) {
+        let indent = "    ".repeat(depth);
+        for block in members.get(&activation).into_iter().flatten() {
+            let _ =
+                writeln!(dot, "{indent}b{} [label=\"{}\"{}];", block.start, self.label(block), {
+                    if block.dynamic_jump {
+                        ", color=\"red\""
+                    } else {
+                        ""
+                    }
+                });
+        }
+        for child in children.get(&activation).into_iter().flatten() {
+            // Invocations generating no jump destination are part of the blocks around them
+            if !members.contains_key(&Some(*child)) && !children.contains_key(&Some(*child)) {
+                continue
+            }
+            let a = &self.activations[*child];
+            let _ = writeln!(dot, "{indent}subgraph cluster_{child} {{");
+            let _ = writeln!(dot, "{indent}    label=\"{}\";", escape(&a.name));
+            self.write_cluster(dot, Some(*child), members, children, depth + 1);
+            let _ = writeln!(dot, "{indent}}}");
+        }
+    }
+
+    /// The label of a block: the labels defined at its start followed by its instructions
+    fn label(&self, block: &BasicBlock) -> String {
+        let mut label = String::new();
+        for name in self
+            .activations
+            .iter()
+            .flat_map(|a| a.labels.iter())
+            .filter(|(_, o)| **o == block.start)
+            .map(|(l, _)| l)
+        {
+            let _ = write!(label, "{}:\\l", escape(name));
+        }
+        for (offset, i) in &block.instructions {
+            let _ = write!(label, "{offset:#06x} ");
+            match i.opcode {
+                Some(o) => {
+                    let op = DisassembledOp {
+                        offset: *offset,
+                        byte: o.byte(),
+                        opcode: Some(o),
+                        immediate: vec![],
+                    };
+                    label.push_str(&op.mnemonic());
+                    if !i.immediate.is_empty() || i.placeholder > 0 {
+                        label.push(' ');
+                    }
+                }
+                None => label.push_str("data "),
+            }
+            if i.placeholder > 0 {
+                label.push_str(&"xx".repeat(i.placeholder));
+            }
+            if !i.immediate.is_empty() {
+                if i.placeholder == 0 {
+                    label.push_str("0x");
+                }
+                i.immediate.iter().for_each(|b| {
+                    let _ = write!(label, "{b:02x}");
+                });
+            }
+            label.push_str("\\l");
+        }
+        label
+    }
+}
+
+/// Escapes quotes and backslashes of a DOT string
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,157 @@
+use huff_utils::prelude::*;
+use std::collections::BTreeMap;
+
+/// Opcodes ending the execution of a block, after which stores are no longer compared
+const TERMINATING: [Opcode; 7] = [
+    Opcode::Jump,
+    Opcode::Jumpi,
+    Opcode::Stop,
+    Opcode::Return,
+    Opcode::Revert,
+    Opcode::Invalid,
+    Opcode::Selfdestruct,
+];
+
+/// Opcodes that may read memory, so the memory stores preceding them aren't dead
+const MEMORY_READS: [Opcode; 15] = [
+    Opcode::Mload,
+    Opcode::Mcopy,
+    Opcode::Msize,
+    Opcode::Sha3,
+    Opcode::Log0,
+    Opcode::Log1,
+    Opcode::Log2,
+    Opcode::Log3,
+    Opcode::Log4,
+    Opcode::Create,
+    Opcode::Create2,
+    Opcode::Call,
+    Opcode::Callcode,
+    Opcode::Delegatecall,
+    Opcode::Staticcall,
+];
+
+/// Opcodes that may read storage, so the storage stores preceding them aren't dead
+const STORAGE_READS: [Opcode; 7] = [
+    Opcode::Sload,
+    Opcode::Create,
+    Opcode::Create2,
+    Opcode::Call,
+    Opcode::Callcode,
+    Opcode::Delegatecall,
+    Opcode::Staticcall,
+];
+
+/// Flags stores overwritten before they can be read: an `MSTORE` to a constant offset followed
+/// by another `MSTORE` to the same offset, or an `SSTORE` to a constant slot followed by another
+/// `SSTORE` to the same slot.
+///
+/// Only stores within the same label block are compared. Jumps, macro invocations, arg calls and
+/// opcodes that may read the memory or storage written to end the comparison, so only obviously
+/// redundant stores are flagged.
+pub fn dead_stores(contract: &Contract, macro_def: &MacroDefinition) -> Vec<CompilerWarning> {
+    let mut warnings = vec![];
+    check_block(contract, macro_def, &macro_def.statements, &mut warnings);
+    warnings
+}
+
+fn check_block(
+    contract: &Contract,
+    macro_def: &MacroDefinition,
+    statements: &[Statement],
+    warnings: &mut Vec<CompilerWarning>,
+) {
+    // The unread stores by offset or slot, with the spans of the stores
+    let mut memory: BTreeMap<String, Vec<Span>> = BTreeMap::new();
+    let mut storage: BTreeMap<String, Vec<Span>> = BTreeMap::new();
+
+    for (i, s) in statements.iter().enumerate() {
+        let (unread, store) = match &s.ty {
+            StatementType::Label(l) => {
+                memory.clear();
+                storage.clear();
+                check_block(contract, macro_def, &l.inner, warnings);
+                continue
+            }
+            StatementType::Opcode(Opcode::Mstore) => (&mut memory, "mstore"),
+            StatementType::Opcode(Opcode::Sstore) => (&mut storage, "sstore"),
+            StatementType::Opcode(o) => {
+                if MEMORY_READS.contains(o) || TERMINATING.contains(o) {
+                    memory.clear();
+                }
+                if STORAGE_READS.contains(o) || TERMINATING.contains(o) {
+                    storage.clear();
+                }
+                continue
+            }
+            StatementType::MacroInvocation(_) |
+            StatementType::ArgCall(_) |
+            StatementType::BuiltinFunctionCall(BuiltinFunctionCall {
+                kind:
+                    BuiltinFunctionKind::Verbatim |
+                    BuiltinFunctionKind::DynConstructorArg |
+                    BuiltinFunctionKind::CalldataLoadArg |
+                    BuiltinFunctionKind::DomainSeparator |
+                    BuiltinFunctionKind::Ecrecover |
+                    BuiltinFunctionKind::Sha256 |
+                    BuiltinFunctionKind::Modexp |
+                    BuiltinFunctionKind::EcAdd |
+                    BuiltinFunctionKind::EcMul |
+                    BuiltinFunctionKind::EcPairing |
+                    BuiltinFunctionKind::PointEvaluation,
+                ..
+            }) => {
+                memory.clear();
+                storage.clear();
+                continue
+            }
+            _ => continue,
+        };
+
+        // Only stores to an offset or slot pushed right before are compared
+        let prev = match i.checked_sub(1) {
+            Some(i) => &statements[i],
+            None => continue,
+        };
+        let location = match constant_location(contract, macro_def, prev) {
+            Some(location) => location,
+            None => continue,
+        };
+        let spans = [prev.span.0.clone(), s.span.0.clone()].concat();
+        if let Some(overwritten) = unread.insert(location.clone(), spans) {
+            tracing::warn!(target: "codegen", "DEAD {} TO {} IN MACRO \"{}\"", store.to_uppercase(), location, macro_def.name);
+            warnings.push(CompilerWarning::new(
+                WarningKind::DeadStore(macro_def.name.clone(), format!("{store} To {location}")),
+                AstSpan(overwritten),
+            ));
+        }
+    }
+}
+
+/// The offset or slot pushed by a statement, if it's a literal or a literal constant
+fn constant_location(
+    contract: &Contract,
+    macro_def: &MacroDefinition,
+    s: &Statement,
+) -> Option<String> {
+    match &s.ty {
+        StatementType::Literal(l) => Some(bytes32_to_string(l, true)),
+        StatementType::Constant(name) => {
+            let value = match macro_def.find_constant(name) {
+                Some(c) => Some(c.value.clone()),
+                None => contract
+                    .constants
+                    .lock()
+                    .ok()?
+                    .iter()
+                    .find(|c| c.name.eq(name))
+                    .map(|c| c.value.clone()),
+            };
+            match value {
+                Some(ConstVal::Literal(l)) => Some(bytes32_to_string(&l, true)),
+                _ => Some(format!("[{name}]")),
+            }
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,309 @@
+use crate::analysis::unreachable::TERMINATING_OPCODES;
+use ethers_core::types::U256;
+use huff_utils::prelude::*;
+use std::collections::BTreeSet;
+
+/// The maximum number of instructions executed per selector when validating a dispatch
+pub const MAX_DISPATCH_STEPS: usize = 10_000;
+
+/// Flags a selector dispatch that falls through into the first label.
+///
+/// A dispatch is a chain of `<label> jumpi` statements ahead of the macro's first label. Unless
+/// the chain is followed by a terminating opcode, calls matching none of the selectors execute
+/// whichever function body was defined first.
+pub fn missing_fallthrough(
+    contract: &Contract,
+    macro_def: &MacroDefinition,
+) -> Option<CompilerWarning> {
+    let last = &macro_def.statements[fallthrough_index(contract, macro_def)?];
+    tracing::warn!(target: "codegen", "SELECTOR DISPATCH IN MACRO \"{}\" FALLS THROUGH", macro_def.name);
+    Some(CompilerWarning::new(
+        WarningKind::MissingFallthrough(macro_def.name.clone()),
+        last.span.clone(),
+    ))
+}
+
+/// Appends `0x00 dup1 revert` to the selector dispatch of a macro if it falls through into the
+/// first label, so calls matching none of the selectors revert.
+///
+/// Dispatches already ending execution are left as they are, so inserting the revert again is a
+/// no-op. Returns whether the revert was inserted.
+pub fn insert_fallthrough_revert(contract: &mut Contract, macro_name: &str) -> bool {
+    let index = match contract
+        .macros
+        .iter()
+        .find(|m| m.name == macro_name)
+        .and_then(|m| fallthrough_index(contract, m))
+    {
+        Some(i) => i,
+        None => return false,
+    };
+    let macro_def = match contract.macros.iter_mut().find(|m| m.name == macro_name) {
+        Some(m) => m,
+        None => return false,
+    };
+    let span = macro_def.statements[index].span.clone();
+    let revert = [
+        StatementType::Literal(str_to_bytes32("00")),
+        StatementType::Opcode(Opcode::Dup1),
+        StatementType::Opcode(Opcode::Revert),
+    ];
+    macro_def.statements.splice(
+        index + 1..index + 1,
+        revert.into_iter().map(|ty| Statement { ty, span: span.clone() }),
+    );
+    tracing::info!(target: "codegen", "INSERTED REVERT AFTER SELECTOR DISPATCH IN MACRO \"{}\"", macro_name);
+    true
+}
+
+/// The index of the last statement of a macro's selector dispatch if the dispatch falls through
+/// into the first label.
+fn fallthrough_index(contract: &Contract, macro_def: &MacroDefinition) -> Option<usize> {
+    let dispatch: Vec<&Statement> = macro_def
+        .statements
+        .iter()
+        .take_while(|s| !matches!(s.ty, StatementType::Label(_)))
+        .collect();
+
+    // Only consider macros that dispatch into labels they define
+    let has_labels = dispatch.len() < macro_def.statements.len();
+    let jumps_to_label = dispatch.windows(2).any(|w| {
+        matches!(w[0].ty, StatementType::LabelCall(_)) &&
+            matches!(w[1].ty, StatementType::Opcode(Opcode::Jumpi))
+    });
+    if !has_labels || !jumps_to_label {
+        return None
+    }
+
+    let last = dispatch.last()?;
+    if terminates(contract, last, &mut vec![]) {
+        return None
+    }
+    Some(dispatch.len() - 1)
+}
+
+/// Whether a statement unconditionally ends execution, following macro invocations into the
+/// last statement of the invoked macro.
+fn terminates(contract: &Contract, statement: &Statement, visited: &mut Vec<String>) -> bool {
+    match &statement.ty {
+        StatementType::Opcode(o) => TERMINATING_OPCODES.contains(o),
+        StatementType::MacroInvocation(mi) => {
+            if visited.iter().any(|v| v.eq(&mi.macro_name)) {
+                return false
+            }
+            visited.push(mi.macro_name.clone());
+            contract
+                .macros
+                .iter()
+                .find(|m| m.name.eq(&mi.macro_name))
+                .and_then(|m| m.statements.last())
+                .is_some_and(|s| terminates(contract, s, visited))
+        }
+        _ => false,
+    }
+}
+
+/// Validates that calls with each declared function's selector reach a body of their own in the
+/// runtime bytecode of the main macro, returning the offset of each function's body.
+///
+/// The bytecode is executed abstractly for every selector, with calldata holding the selector
+/// followed by zeroed arguments and no value sent. Values unknown at compile time, like those read
+/// from storage, end the execution, as does reaching [MAX_DISPATCH_STEPS] instructions. The body a
+/// selector reaches is where execution continues after the last `JUMPI` branching on the
+/// selector, or the destination of the last `JUMP` to an offset derived from it.
+///
+/// A function is unreachable if its selector reaches the same code as a selector matching no
+/// declared function, i.e. falls through the dispatch, and two functions collide if their
+/// selectors reach the same body.
+pub fn validate_dispatch(
+    contract: &Contract,
+    bytecode: &str,
+) -> Result<Vec<(String, usize)>, CodegenError> {
+    let ops = disassemble(bytecode).map_err(|e| CodegenError {
+        kind: CodegenErrorKind::MalformedBytecode(e),
+        span: AstSpan(vec![]),
+        token: None,
+    })?;
+
+    // A selector matching no declared function
+    let selectors = contract.functions.iter().map(|f| f.signature).collect::<BTreeSet<_>>();
+    let unmatched =
+        (0u32..).map(|s| s.to_be_bytes()).find(|s| !selectors.contains(s)).unwrap_or_default();
+    let fallthrough = dispatched_body(&ops, unmatched, 0);
+
+    let mut bodies: Vec<(String, usize)> = vec![];
+    for f in &contract.functions {
+        let args = f
+            .inputs
+            .iter()
+            .map(|i| {
+                FunctionParamType::convert_string_to_type(i.arg_type.as_deref().unwrap_or_default())
+                    .map(|ty| ty.head_size())
+                    .unwrap_or(32)
+            })
+            .sum();
+        let body = match dispatched_body(&ops, f.signature, args) {
+            Some(body) if Some(body) != fallthrough => body.0,
+            _ => {
+                tracing::error!(target: "codegen", "FUNCTION \"{}\" IS UNREACHABLE FROM THE DISPATCH", f.name);
+                return Err(CodegenError {
+                    kind: CodegenErrorKind::UnreachableFunction(f.name.clone()),
+                    span: f.span.clone(),
+                    token: None,
+                })
+            }
+        };
+        if let Some((other, _)) = bodies.iter().find(|(_, b)| *b == body) {
+            tracing::error!(target: "codegen", "FUNCTIONS \"{}\" AND \"{}\" DISPATCH TO THE SAME BODY", other, f.name);
+            return Err(CodegenError {
+                kind: CodegenErrorKind::DispatchCollision(other.clone(), f.name.clone(), body),
+                span: f.span.clone(),
+                token: None,
+            })
+        }
+        bodies.push((f.name.clone(), body));
+    }
+    Ok(bodies)
+}
+
+/// A stack item of the abstract execution, with whether it's derived from the selector
+#[derive(Debug, Clone, Copy)]
+struct AbstractValue {
+    word: Option<U256>,
+    selector: bool,
+}
+
+/// Executes the bytecode with the selector followed by `args` zeroed bytes as calldata, returning
+/// the offset of the body the selector reaches, if it branches on the selector at all, along with
+/// whether it's reached by jumping.
+///
+/// Whether the body is jumped to tells apart a selector jumping to a label from selectors falling
+/// through into it. A `JUMPI` falling through past the end of the code reaches the offset after
+/// it.
+fn dispatched_body(
+    ops: &[DisassembledOp],
+    selector: [u8; 4],
+    args: usize,
+) -> Option<(usize, bool)> {
+    let index_of = |offset: usize| ops.binary_search_by_key(&offset, |op| op.offset).ok();
+    let jumpdest = |offset: U256| {
+        let offset = (offset < U256::from(usize::MAX)).then(|| offset.as_usize())?;
+        index_of(offset).filter(|i| ops[*i].opcode == Some(Opcode::Jumpdest))
+    };
+    let mut calldata = selector.to_vec();
+    calldata.resize(4 + args, 0);
+
+    let mut body = None;
+    let mut stack: Vec<AbstractValue> = vec![];
+    let mut i = 0;
+    for _ in 0..MAX_DISPATCH_STEPS {
+        // Running past the end of the code stops execution
+        let (op, opcode) = match ops.get(i) {
+            Some(op @ DisassembledOp { opcode: Some(o), .. }) => (op, *o),
+            _ => return body,
+        };
+        let inputs = opcode.stack_inputs();
+        if stack.len() < inputs {
+            return body
+        }
+        let args = stack.split_off(stack.len() - inputs);
+        let from_selector = args.iter().any(|a| a.selector);
+        // The inputs, top first, if all are known
+        let words = args.iter().rev().map(|a| a.word).collect::<Option<Vec<U256>>>();
+        let mut push =
+            |word: Option<U256>| stack.push(AbstractValue { word, selector: from_selector });
+        i += 1;
+        match opcode {
+            Opcode::Stop |
+            Opcode::Return |
+            Opcode::Revert |
+            Opcode::Invalid |
+            Opcode::Selfdestruct => return body,
+            Opcode::Jump => {
+                i = match args[0].word.and_then(jumpdest) {
+                    Some(dest) => dest,
+                    None => return body,
+                };
+                if args[0].selector {
+                    body = Some((ops[i].offset, true));
+                }
+            }
+            Opcode::Jumpi => {
+                let (condition, dest) = (args[0], args[1]);
+                let taken = match condition.word {
+                    Some(word) => !word.is_zero(),
+                    None => return body,
+                };
+                if taken {
+                    i = match dest.word.and_then(jumpdest) {
+                        Some(dest) => dest,
+                        None => return body,
+                    };
+                }
+                if condition.selector || (taken && dest.selector) {
+                    body = Some((ops.get(i).map_or(op.offset + 1, |next| next.offset), taken));
+                }
+            }
+            _ if opcode.is_push() => push(Some(U256::from_big_endian(&op.immediate))),
+            Opcode::Push0 => push(Some(U256::zero())),
+            Opcode::Pop | Opcode::Jumpdest => {}
+            o if (0x80..=0x8f).contains(&o.byte()) => {
+                stack.extend(&args);
+                stack.push(args[0]);
+            }
+            o if (0x90..=0x9f).contains(&o.byte()) => {
+                let mut args = args;
+                let last = args.len() - 1;
+                args.swap(0, last);
+                stack.extend(args);
+            }
+            Opcode::Callvalue => push(Some(U256::zero())),
+            Opcode::Calldatasize => push(Some(U256::from(calldata.len()))),
+            Opcode::Calldataload => {
+                let offset = match words {
+                    Some(w) if w[0] < U256::from(calldata.len()) => w[0].as_usize(),
+                    Some(_) => calldata.len(),
+                    None => return body,
+                };
+                let mut word = [0u8; 32];
+                let end = calldata.len().min(offset + 32);
+                word[..end - offset].copy_from_slice(&calldata[offset..end]);
+                stack.push(AbstractValue {
+                    word: Some(U256::from_big_endian(&word)),
+                    selector: from_selector || offset < 4,
+                });
+            }
+            Opcode::Pc => push(Some(U256::from(op.offset))),
+            _ => push(words.and_then(|w| evaluate(opcode, &w))),
+        }
+    }
+    body
+}
+
+/// Evaluates an arithmetic, comparison or bitwise opcode with known inputs, top first
+fn evaluate(opcode: Opcode, w: &[U256]) -> Option<U256> {
+    let bool_word = |b: bool| U256::from(b as u8);
+    Some(match opcode {
+        Opcode::Add => w[0].overflowing_add(w[1]).0,
+        Opcode::Sub => w[0].overflowing_sub(w[1]).0,
+        Opcode::Mul => w[0].overflowing_mul(w[1]).0,
+        Opcode::Div => w[0].checked_div(w[1]).unwrap_or_default(),
+        Opcode::Mod => w[0].checked_rem(w[1]).unwrap_or_default(),
+        Opcode::Lt => bool_word(w[0] < w[1]),
+        Opcode::Gt => bool_word(w[0] > w[1]),
+        Opcode::Eq => bool_word(w[0] == w[1]),
+        Opcode::Iszero => bool_word(w[0].is_zero()),
+        Opcode::And => w[0] & w[1],
+        Opcode::Or => w[0] | w[1],
+        Opcode::Xor => w[0] ^ w[1],
+        Opcode::Not => !w[0],
+        Opcode::Byte if w[0] < U256::from(32) => {
+            (w[1] >> (8 * (31 - w[0].as_usize()))) & 0xff.into()
+        }
+        Opcode::Byte => U256::zero(),
+        Opcode::Shl if w[0] < U256::from(256) => w[1] << w[0].as_usize(),
+        Opcode::Shr if w[0] < U256::from(256) => w[1] >> w[0].as_usize(),
+        Opcode::Shl | Opcode::Shr => U256::zero(),
+        _ => return None,
+    })
+}
@@ -0,0 +1,89 @@
+use crate::Codegen;
+use huff_utils::prelude::*;
+
+/// Estimates the gas used by a macro as the sum of the static gas costs of the opcodes it compiles
+/// to.
+///
+/// Every opcode is counted once, so the estimate is an upper bound of a single run through code
+/// without loops, excluding the dynamic costs described in [Opcode::static_gas].
+pub fn estimate_gas(
+    evm_version: &EVMVersion,
+    contract: &Contract,
+    macro_def: &MacroDefinition,
+    nesting_limit: usize,
+) -> Result<u64, CodegenError> {
+    let res = Codegen::macro_to_bytecode(
+        evm_version,
+        macro_def,
+        contract,
+        &mut vec![macro_def],
+        0,
+        &mut vec![],
+        false,
+        None,
+        nesting_limit,
+    )?;
+    Ok(res.static_gas())
+}
+
+/// Validates the gas budgets declared with `__GAS_LIMIT(MACRO, max)` in the contract's macros,
+/// erroring if the estimated gas of a macro exceeds its budget.
+pub fn validate_gas_limits(
+    evm_version: &EVMVersion,
+    contract: &Contract,
+    nesting_limit: usize,
+) -> Result<(), CodegenError> {
+    let mut statements: Vec<&Statement> =
+        contract.macros.iter().flat_map(|m| m.statements.iter()).collect();
+    while let Some(s) = statements.pop() {
+        let bf = match &s.ty {
+            StatementType::BuiltinFunctionCall(bf) if bf.kind == BuiltinFunctionKind::GasLimit => {
+                bf
+            }
+            StatementType::Label(l) => {
+                statements.extend(l.inner.iter());
+                continue
+            }
+            _ => continue,
+        };
+
+        let invalid = |msg: String| {
+            tracing::error!(target: "codegen", "INVALID __GAS_LIMIT ARGUMENTS: {}", msg);
+            CodegenError {
+                kind: CodegenErrorKind::InvalidArguments(msg),
+                span: bf.span.clone(),
+                token: None,
+            }
+        };
+        let (name, limit) = match bf.args.iter().map(|a| a.name.as_deref()).collect::<Vec<_>>()[..]
+        {
+            [Some(name), Some(limit)] => (name, limit),
+            _ => {
+                return Err(invalid(format!(
+                    "Incorrect number of arguments passed to __GAS_LIMIT, should be 2: {}",
+                    bf.args.len()
+                )))
+            }
+        };
+        // Numbers are parsed into hex encoded literals
+        let limit = u64::from_str_radix(limit, 16)
+            .map_err(|_| invalid(format!("Invalid gas limit passed to __GAS_LIMIT: 0x{limit}")))?;
+        let macro_def = contract.find_macro_by_name(name).ok_or_else(|| CodegenError {
+            kind: CodegenErrorKind::MissingMacroDefinition(name.to_string()),
+            span: bf.span.clone(),
+            token: None,
+        })?;
+
+        let estimate = estimate_gas(evm_version, contract, macro_def, nesting_limit)?;
+        tracing::info!(target: "codegen", "ESTIMATED {} GAS FOR \"{}\", LIMIT {}", estimate, name, limit);
+        if estimate > limit {
+            tracing::error!(target: "codegen", "MACRO \"{}\" EXCEEDS ITS GAS LIMIT", name);
+            return Err(CodegenError {
+                kind: CodegenErrorKind::GasLimitExceeded(name.to_string(), estimate, limit),
+                span: bf.span.clone(),
+                token: None,
+            })
+        }
+    }
+    Ok(())
+}
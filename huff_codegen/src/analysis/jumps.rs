@@ -0,0 +1,64 @@
+use crate::Codegen;
+use huff_utils::prelude::*;
+
+/// Validates that every `JUMP` and `JUMPI` a macro compiles to jumps to a label, erroring on the
+/// first jump whose destination is computed at runtime.
+///
+/// A jump's destination is known if the jump directly follows the push of a label, either
+/// written out or passed as a macro argument. Jumps to literal offsets are rejected too, as they
+/// break once the code before their destination changes.
+pub fn validate_static_jumps(
+    evm_version: &EVMVersion,
+    contract: &Contract,
+    macro_def: &MacroDefinition,
+    nesting_limit: usize,
+) -> Result<(), CodegenError> {
+    let res = Codegen::macro_to_bytecode(
+        evm_version,
+        macro_def,
+        contract,
+        &mut vec![macro_def],
+        0,
+        &mut vec![],
+        false,
+        None,
+        nesting_limit,
+    )?;
+
+    // Empty instructions, i.e. of `__VERBATIM("")`, don't separate a push from its jump
+    let instructions = res.instructions.iter().filter(|(_, i)| !i.is_empty()).collect::<Vec<_>>();
+    for (i, (offset, instruction)) in instructions.iter().enumerate() {
+        if !matches!(instruction.opcode, Some(Opcode::Jump | Opcode::Jumpi)) {
+            continue
+        }
+        let to_label = i
+            .checked_sub(1)
+            .map(|i| instructions[i])
+            .filter(|(_, push)| push.opcode.map(|o| o.is_push()).unwrap_or(false))
+            .map(|(push, _)| {
+                res.relocations
+                    .iter()
+                    .any(|r| r.kind == RelocationKind::Label && r.offset == push + 1)
+            })
+            .unwrap_or(false);
+        if to_label {
+            continue
+        }
+
+        // The innermost macro invocation generating the jump
+        let name = res
+            .activations
+            .iter()
+            .filter(|a| a.start <= *offset && *offset < a.end)
+            .min_by_key(|a| a.end - a.start)
+            .map(|a| a.name.clone())
+            .unwrap_or_else(|| macro_def.name.clone());
+        tracing::error!(target: "codegen", "DYNAMIC JUMP IN MACRO \"{}\" AT OFFSET {}", name, offset);
+        return Err(CodegenError {
+            kind: CodegenErrorKind::DynamicJump(name),
+            span: instruction.source_span.clone(),
+            token: None,
+        })
+    }
+    Ok(())
+}
@@ -0,0 +1,31 @@
+/// Stack Height Analysis Module
+pub mod stack;
+
+/// Unreachable Code Analysis Module
+pub mod unreachable;
+
+/// Selector Dispatch Analysis Module
+pub mod dispatch;
+
+/// Static Gas Estimation Module
+pub mod gas;
+
+/// Control Flow Graph Module
+pub mod cfg;
+
+/// Dead Store Analysis Module
+pub mod dead_store;
+
+/// Static Jump Analysis Module
+pub mod jumps;
+
+/// Unchecked Call Analysis Module
+pub mod unchecked_call;
+
+/// Prelude wraps common utilities.
+pub mod prelude {
+    pub use super::{
+        cfg::*, dead_store::*, dispatch::*, gas::*, jumps::*, stack::*, unchecked_call::*,
+        unreachable::*,
+    };
+}
@@ -0,0 +1,349 @@
+use huff_utils::prelude::*;
+use std::{collections::HashMap, str::FromStr};
+
+/// The maximum number of items the EVM stack can hold
+pub const STACK_LIMIT: usize = 1024;
+
+/// Opcodes after which the stack height of a macro depends on the path taken
+const CONTROL_FLOW: [Opcode; 7] = [
+    Opcode::Jump,
+    Opcode::Jumpi,
+    Opcode::Stop,
+    Opcode::Return,
+    Opcode::Revert,
+    Opcode::Invalid,
+    Opcode::Selfdestruct,
+];
+
+/// The maximum stack height reached while expanding a macro
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StackHeight {
+    /// The maximum number of items on the stack
+    pub max: usize,
+    /// The chain of macros being expanded when the maximum was reached
+    pub chain: Vec<String>,
+    /// The invocation spans along the chain, followed by the statement reaching the maximum
+    pub span: AstSpan,
+    /// The loops jumping back to a label of the macro itself with more items on the stack than
+    /// at the label, by the label, the items added per iteration and the span of the jump
+    pub growing_loops: Vec<(String, usize, AstSpan)>,
+}
+
+/// A macro being expanded, along with the arguments it was invoked with
+struct Frame<'a> {
+    macro_def: &'a MacroDefinition,
+    args: &'a [MacroArg],
+    span: &'a AstSpan,
+    /// Identifies the expansion, distinguishing the labels of separate invocations
+    id: usize,
+}
+
+/// Tracks the stack height through the full expansion of a macro, starting from the number of
+/// items the macro `takes`.
+///
+/// Statements are walked in order, following jumps to labels: the height at a label is the
+/// highest of the heights jumping forward to it and of the code falling through into it, so
+/// mutually exclusive branches aren't added up. Code following a jump or a halting opcode is only
+/// reached through its labels. Jumps back to a label are loops, recorded when they grow the
+/// stack. Outlined macros leave their declared `returns` in place of their `takes` once walked.
+/// Heights reached within macros annotated with `#pragma unsafe-stack` are left out of the
+/// maximum.
+pub fn stack_height(contract: &Contract, macro_def: &MacroDefinition) -> StackHeight {
+    let mut height = macro_def.takes;
+    let mut res = StackHeight {
+        max: height,
+        chain: vec![macro_def.name.clone()],
+        span: macro_def.span.clone(),
+        growing_loops: vec![],
+    };
+    let mut frames = vec![Frame { macro_def, args: &[], span: &macro_def.span, id: 0 }];
+    walk_statements(contract, &macro_def.statements, &mut frames, &mut height, &mut res);
+    res
+}
+
+/// Validates that the stack height can never exceed the [STACK_LIMIT] while executing a macro.
+pub fn validate_stack_depth(
+    contract: &Contract,
+    macro_def: &MacroDefinition,
+) -> Result<StackHeight, CodegenError> {
+    let res = stack_height(contract, macro_def);
+    if res.max > STACK_LIMIT {
+        tracing::error!(target: "codegen", "STACK HEIGHT {} EXCEEDS LIMIT IN \"{}\"", res.max, res.chain.join(" -> "));
+        return Err(CodegenError {
+            kind: CodegenErrorKind::StackTooDeep(res.max, res.chain.join(" -> ")),
+            span: if res.span.0.is_empty() { macro_def.span.clone() } else { res.span },
+            token: None,
+        })
+    }
+    Ok(res)
+}
+
+/// Warns about the loops of a macro leaving more items on the stack on every iteration, which
+/// overflow the stack unless they stop iterating early enough.
+pub fn growing_loops(contract: &Contract, macro_def: &MacroDefinition) -> Vec<CompilerWarning> {
+    if macro_def.has_pragma(MacroPragma::UnsafeStack) {
+        return vec![]
+    }
+    stack_height(contract, macro_def)
+        .growing_loops
+        .into_iter()
+        .map(|(label, growth, span)| {
+            tracing::warn!(target: "codegen", "LOOP AT \"{}\" IN \"{}\" GROWS THE STACK BY {}", label, macro_def.name, growth);
+            CompilerWarning::new(
+                WarningKind::GrowingLoop(macro_def.name.clone(), label, growth),
+                span,
+            )
+        })
+        .collect()
+}
+
+/// Checks that a macro naming its outputs, i.e. `returns (quotient, remainder)`, leaves as many
+/// items on the stack as it names.
+///
+/// Only macros expanding to straight-line code are checked, as the stack height of code with
+/// labels and jumps depends on the path taken.
+pub fn mismatched_outputs(
+    contract: &Contract,
+    macro_def: &MacroDefinition,
+) -> Option<CompilerWarning> {
+    if macro_def.outputs.is_empty() ||
+        macro_def.has_pragma(MacroPragma::UnsafeStack) ||
+        !straight_line(contract, macro_def)
+    {
+        return None
+    }
+    let mut height = macro_def.takes;
+    let mut res = StackHeight::default();
+    let mut frames = vec![Frame { macro_def, args: &[], span: &macro_def.span, id: 0 }];
+    walk_statements(contract, &macro_def.statements, &mut frames, &mut height, &mut res);
+    if height == macro_def.returns {
+        return None
+    }
+    tracing::warn!(target: "codegen", "MACRO \"{}\" LEAVES {} STACK ITEMS", macro_def.name, height);
+    Some(CompilerWarning::new(
+        WarningKind::OutputMismatch(macro_def.name.clone(), macro_def.outputs.clone(), height),
+        macro_def.span.clone(),
+    ))
+}
+
+/// Whether the full expansion of a macro is free of labels, jumps and halting opcodes, treating
+/// outlined macros as the outputs they declare
+fn straight_line(contract: &Contract, macro_def: &MacroDefinition) -> bool {
+    let mut pending = vec![macro_def];
+    let mut seen = vec![macro_def.name.as_str()];
+    while let Some(m) = pending.pop() {
+        for s in &m.statements {
+            match &s.ty {
+                StatementType::Label(_) => return false,
+                StatementType::Opcode(o) if CONTROL_FLOW.contains(o) => return false,
+                StatementType::BuiltinFunctionCall(bf)
+                    if matches!(
+                        bf.kind,
+                        BuiltinFunctionKind::Verbatim | BuiltinFunctionKind::CalldataCheck
+                    ) =>
+                {
+                    return false
+                }
+                StatementType::MacroInvocation(mi) => {
+                    // Macros passed as arguments are invoked where their argument is called
+                    if mi.args.iter().any(|a| matches!(a, MacroArg::MacroInvocation(_))) {
+                        return false
+                    }
+                    let invoked = match contract.macros.iter().find(|m| m.name == mi.macro_name) {
+                        Some(m) => m,
+                        None => return false,
+                    };
+                    if !invoked.is_outlined() && !seen.contains(&invoked.name.as_str()) {
+                        seen.push(&invoked.name);
+                        pending.push(invoked);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    true
+}
+
+/// A block of statements being walked, either a macro body or a label's inner statements
+struct Block<'a> {
+    statements: std::slice::Iter<'a, Statement>,
+    /// The stack height before the invocation, if the block is an invoked macro's body
+    invoked_at: Option<usize>,
+    /// Whether the invocation was reachable, if the block is an invoked macro's body
+    reachable: bool,
+}
+
+/// Identifies a label by the expansion defining it and its name
+type LabelKey<'a> = (usize, &'a str);
+
+/// Walks the statements with an explicit work-list rather than recursing per invocation, so
+/// deeply nested macros can't overflow the native stack.
+fn walk_statements<'a>(
+    contract: &'a Contract,
+    statements: &'a [Statement],
+    frames: &mut Vec<Frame<'a>>,
+    height: &mut usize,
+    res: &mut StackHeight,
+) {
+    let mut blocks =
+        vec![Block { statements: statements.iter(), invoked_at: None, reachable: true }];
+    let mut next_id = frames.len();
+    // Whether the code being walked can be reached by falling through from the preceding code
+    let mut reachable = true;
+    // The label pushed by the previous statement, the destination of a following jump
+    let mut destination: Option<LabelKey> = None;
+    // The highest heights jumping forward to labels not walked yet
+    let mut entries: HashMap<LabelKey, usize> = HashMap::new();
+    // The heights at the labels walked so far
+    let mut walked: HashMap<LabelKey, usize> = HashMap::new();
+
+    while let Some(block) = blocks.last_mut() {
+        let s = match block.statements.next() {
+            Some(s) => s,
+            None => {
+                if let Some(start) = block.invoked_at {
+                    if let Some(frame) = frames.pop() {
+                        if frame.macro_def.is_outlined() {
+                            *height = start.saturating_sub(frame.macro_def.takes) +
+                                frame.macro_def.returns;
+                            reachable = block.reachable;
+                        }
+                    }
+                }
+                blocks.pop();
+                continue
+            }
+        };
+        let pushed = destination.take();
+
+        let opcode = match &s.ty {
+            StatementType::Opcode(o) => Some(*o),
+            StatementType::ArgCall(name) => resolve_opcode_arg(name, frames),
+            _ => None,
+        };
+        match &s.ty {
+            StatementType::Opcode(_) | StatementType::ArgCall(_) => match opcode {
+                Some(o) => {
+                    *height = height.saturating_sub(o.stack_inputs()) + o.stack_outputs();
+                    if let (Some(key), Opcode::Jump | Opcode::Jumpi) = (pushed, o) {
+                        match walked.get(&key) {
+                            // Jumping back to a label of the macro itself closes a loop
+                            Some(at) if *height > *at && key.0 == 0 => res.growing_loops.push((
+                                key.1.to_string(),
+                                *height - at,
+                                s.span.clone(),
+                            )),
+                            Some(_) => {}
+                            None if reachable => {
+                                let entry = entries.entry(key).or_insert(*height);
+                                *entry = (*entry).max(*height);
+                            }
+                            None => {}
+                        }
+                    }
+                    if o != Opcode::Jumpi && CONTROL_FLOW.contains(&o) {
+                        reachable = false;
+                    }
+                }
+                None => *height += 1,
+            },
+            StatementType::MacroInvocation(mi) => {
+                let macro_def = match contract.macros.iter().find(|m| m.name.eq(&mi.macro_name)) {
+                    Some(m) => m,
+                    None => continue,
+                };
+                // Circular invocations are never fully expanded
+                if frames.iter().any(|f| f.macro_def.name.eq(&macro_def.name)) {
+                    continue
+                }
+
+                frames.push(Frame { macro_def, args: &mi.args, span: &s.span, id: next_id });
+                next_id += 1;
+                blocks.push(Block {
+                    statements: macro_def.statements.iter(),
+                    invoked_at: Some(*height),
+                    reachable,
+                });
+                continue
+            }
+            StatementType::Label(l) => {
+                let key = (frames.last().map(|f| f.id).unwrap_or_default(), l.name.as_str());
+                // Labels only reached through tables or dynamic jumps keep the current height
+                *height = match (reachable, entries.remove(&key)) {
+                    (true, Some(entry)) => entry.max(*height),
+                    (false, Some(entry)) => entry,
+                    (_, None) => *height,
+                };
+                walked.insert(key, *height);
+                reachable = true;
+                blocks.push(Block { statements: l.inner.iter(), invoked_at: None, reachable });
+                continue
+            }
+            StatementType::LabelCall(name) => {
+                *height += 1;
+                destination = frames
+                    .iter()
+                    .rev()
+                    .find(|f| defines_label(&f.macro_def.statements, name))
+                    .map(|f| (f.id, name.as_str()));
+            }
+            StatementType::Code(_) |
+            StatementType::Assertion(_) |
+            StatementType::OffsetAssertion(_) => continue,
+            StatementType::BuiltinFunctionCall(bf) => match bf.kind {
+                BuiltinFunctionKind::DynConstructorArg |
+                BuiltinFunctionKind::Verbatim |
+                BuiltinFunctionKind::GasLimit |
+                BuiltinFunctionKind::CalldataCheck => continue,
+                BuiltinFunctionKind::CodeSlice => *height += 2,
+                // Precompile calls consume their inputs
+                _ => match bf.kind.precompile() {
+                    Some(p) => {
+                        *height = height.saturating_sub(p.stack_inputs()) + p.stack_outputs()
+                    }
+                    None => *height += 1,
+                },
+            },
+            StatementType::Literal(_) | StatementType::Constant(_) => *height += 1,
+        }
+
+        // The heights reached in unreachable code and in macros opting out of the stack check
+        // aren't tracked
+        if reachable &&
+            *height > res.max &&
+            !frames.iter().any(|f| f.macro_def.has_pragma(MacroPragma::UnsafeStack))
+        {
+            res.max = *height;
+            res.chain = frames.iter().map(|f| f.macro_def.name.clone()).collect();
+            res.span = AstSpan(
+                frames
+                    .iter()
+                    .skip(1)
+                    .flat_map(|f| f.span.0.clone())
+                    .chain(s.span.0.clone())
+                    .collect(),
+            );
+        }
+    }
+}
+
+/// Whether a label is defined by the statements, jumps resolving to the innermost macro defining
+/// the label as they do in codegen
+fn defines_label(statements: &[Statement], name: &str) -> bool {
+    statements.iter().any(|s| match &s.ty {
+        StatementType::Label(l) => l.name == name || defines_label(&l.inner, name),
+        _ => false,
+    })
+}
+
+/// Resolves an argument call to the opcode passed in its place, if any
+fn resolve_opcode_arg(name: &str, frames: &[Frame]) -> Option<Opcode> {
+    let (frame, outer) = frames.split_last()?;
+    let index = frame.macro_def.parameters.iter().position(|p| p.name.as_deref() == Some(name))?;
+    match frame.args.get(index)? {
+        MacroArg::Ident(iden) => Opcode::from_str(iden).ok(),
+        MacroArg::ArgCall(inner) => resolve_opcode_arg(inner, outer),
+        MacroArg::Literal(_) | MacroArg::MacroInvocation(_) => None,
+    }
+}
@@ -0,0 +1,143 @@
+use huff_utils::prelude::*;
+
+/// Opcodes pushing the success flag of an external call
+const CALLS: [Opcode; 4] =
+    [Opcode::Call, Opcode::Callcode, Opcode::Delegatecall, Opcode::Staticcall];
+
+/// A call opcode whose success flag is followed through the stack
+struct TrackedCall {
+    opcode: Opcode,
+    span: AstSpan,
+    /// The span of the `pop` dropping a copy of the flag, if one does
+    popped: Option<AstSpan>,
+    /// Whether an opcode other than `pop` consumes a copy of the flag
+    checked: bool,
+}
+
+/// Flags `call`, `callcode`, `delegatecall` and `staticcall` opcodes whose success flag is popped
+/// without being checked, i.e. `call pop`.
+///
+/// The flag is followed through `dup`s and `swap`s within the same label block, and counts as
+/// checked once any opcode other than `pop` consumes a copy of it, so storing or returning the
+/// flag isn't flagged. Macro invocations, arg calls and labels end the tracking, leaving the flags
+/// still on the stack unflagged, so only flags certainly dropped are reported. Intentionally
+/// ignored flags are silenced with a `// huff-ignore: unchecked-call` comment following the call
+/// or the `pop`.
+pub fn unchecked_calls(macro_def: &MacroDefinition) -> Vec<CompilerWarning> {
+    let mut warnings = vec![];
+    check_block(macro_def, &macro_def.statements, &mut warnings);
+    warnings
+}
+
+fn check_block(
+    macro_def: &MacroDefinition,
+    statements: &[Statement],
+    warnings: &mut Vec<CompilerWarning>,
+) {
+    // The index of the call pushing each stack item, from the bottom of the tracked stack
+    let mut stack: Vec<Option<usize>> = vec![];
+    let mut calls: Vec<TrackedCall> = vec![];
+
+    for s in statements {
+        match &s.ty {
+            StatementType::Opcode(o) => {
+                let byte = o.byte();
+                let (inputs, outputs) = (o.stack_inputs(), o.stack_outputs());
+                // Items below the tracked stack were pushed before the block
+                while stack.len() < inputs {
+                    stack.insert(0, None);
+                }
+                match byte {
+                    // DUP1 to DUP16 copy the nth item
+                    0x80..=0x8f => stack.push(stack[stack.len() - inputs]),
+                    // SWAP1 to SWAP16 exchange the top item with the one below the nth
+                    0x90..=0x9f => {
+                        let top = stack.len() - 1;
+                        stack.swap(top, top + 1 - inputs);
+                    }
+                    _ => {
+                        for item in stack.split_off(stack.len() - inputs).into_iter().flatten() {
+                            if *o == Opcode::Pop {
+                                calls[item].popped = Some(s.span.clone());
+                            } else {
+                                calls[item].checked = true;
+                            }
+                        }
+                        if CALLS.contains(o) {
+                            stack.push(Some(calls.len()));
+                            calls.push(TrackedCall {
+                                opcode: *o,
+                                span: s.span.clone(),
+                                popped: None,
+                                checked: false,
+                            });
+                        } else {
+                            stack.resize(stack.len() + outputs, None);
+                        }
+                    }
+                }
+            }
+            StatementType::Literal(_) |
+            StatementType::Constant(_) |
+            StatementType::LabelCall(_) => stack.push(None),
+            StatementType::BuiltinFunctionCall(bf) => match bf.kind {
+                BuiltinFunctionKind::Verbatim => {
+                    report(macro_def, &stack, &calls, warnings);
+                    stack.clear();
+                    calls.clear();
+                }
+                BuiltinFunctionKind::DynConstructorArg |
+                BuiltinFunctionKind::GasLimit |
+                BuiltinFunctionKind::CalldataCheck => {}
+                BuiltinFunctionKind::CodeSlice => stack.extend([None, None]),
+                // Flags passed to a precompile call are consumed by it
+                _ => match bf.kind.precompile() {
+                    Some(p) => {
+                        let inputs = stack.len().saturating_sub(p.stack_inputs());
+                        for item in stack.split_off(inputs).into_iter().flatten() {
+                            calls[item].checked = true;
+                        }
+                        stack.resize(stack.len() + p.stack_outputs(), None);
+                    }
+                    None => stack.push(None),
+                },
+            },
+            StatementType::Label(l) => {
+                report(macro_def, &stack, &calls, warnings);
+                stack.clear();
+                calls.clear();
+                check_block(macro_def, &l.inner, warnings);
+            }
+            StatementType::MacroInvocation(_) | StatementType::ArgCall(_) => {
+                report(macro_def, &stack, &calls, warnings);
+                stack.clear();
+                calls.clear();
+            }
+            StatementType::Code(_) |
+            StatementType::Assertion(_) |
+            StatementType::OffsetAssertion(_) => {}
+        }
+    }
+    report(macro_def, &stack, &calls, warnings);
+}
+
+/// Reports the calls whose flag is popped, never checked, and no longer on the stack
+fn report(
+    macro_def: &MacroDefinition,
+    stack: &[Option<usize>],
+    calls: &[TrackedCall],
+    warnings: &mut Vec<CompilerWarning>,
+) {
+    for (i, call) in calls.iter().enumerate() {
+        let popped = match &call.popped {
+            Some(popped) if !call.checked && !stack.contains(&Some(i)) => popped,
+            _ => continue,
+        };
+        let opcode = format!("{:?}", call.opcode).to_lowercase();
+        tracing::warn!(target: "codegen", "UNCHECKED {} IN MACRO \"{}\"", opcode.to_uppercase(), macro_def.name);
+        warnings.push(CompilerWarning::new(
+            WarningKind::UncheckedCall(macro_def.name.clone(), opcode),
+            AstSpan([call.span.0.clone(), popped.0.clone()].concat()),
+        ));
+    }
+}
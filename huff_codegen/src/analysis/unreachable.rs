@@ -0,0 +1,82 @@
+use huff_utils::prelude::*;
+
+/// Opcodes after which execution never continues to the next statement
+pub const TERMINATING_OPCODES: [Opcode; 5] =
+    [Opcode::Stop, Opcode::Return, Opcode::Revert, Opcode::Invalid, Opcode::Jump];
+
+/// Flags statements following a terminating opcode within the same label block.
+///
+/// Execution can only resume at the next label, so anything in between is dead code. A single
+/// warning is generated for each run of unreachable statements.
+pub fn unreachable_code(contract: &Contract, macro_def: &MacroDefinition) -> Vec<CompilerWarning> {
+    let mut warnings = vec![];
+    check_block(contract, macro_def, &macro_def.statements, &mut warnings);
+    warnings
+}
+
+fn check_block(
+    contract: &Contract,
+    macro_def: &MacroDefinition,
+    statements: &[Statement],
+    warnings: &mut Vec<CompilerWarning>,
+) {
+    let mut terminated = false;
+    let mut unreachable: Vec<Span> = vec![];
+
+    for s in statements {
+        let resumes = match &s.ty {
+            StatementType::Label(_) => true,
+            // A label defined by the invoked macro may be jumped to
+            StatementType::MacroInvocation(mi) => {
+                terminated && defines_label(contract, &mi.macro_name, &mut vec![])
+            }
+            _ => false,
+        };
+        if resumes {
+            flush(macro_def, &mut unreachable, warnings);
+            terminated = false;
+        }
+
+        match &s.ty {
+            StatementType::Label(l) => check_block(contract, macro_def, &l.inner, warnings),
+            _ if terminated => unreachable.extend(s.span.0.clone()),
+            StatementType::Opcode(o) if TERMINATING_OPCODES.contains(o) => terminated = true,
+            _ => {}
+        }
+    }
+
+    flush(macro_def, &mut unreachable, warnings);
+}
+
+/// Generates a warning for the collected unreachable spans, if any
+fn flush(
+    macro_def: &MacroDefinition,
+    unreachable: &mut Vec<Span>,
+    warnings: &mut Vec<CompilerWarning>,
+) {
+    if unreachable.is_empty() {
+        return
+    }
+    tracing::warn!(target: "codegen", "UNREACHABLE CODE IN MACRO \"{}\"", macro_def.name);
+    warnings.push(CompilerWarning::new(
+        WarningKind::UnreachableCode(macro_def.name.clone()),
+        AstSpan(std::mem::take(unreachable)),
+    ));
+}
+
+/// Whether the expansion of a macro defines a label
+fn defines_label(contract: &Contract, name: &str, visited: &mut Vec<String>) -> bool {
+    if visited.iter().any(|v| v.eq(name)) {
+        return false
+    }
+    visited.push(name.to_string());
+
+    match contract.macros.iter().find(|m| m.name.eq(name)) {
+        Some(m) => m.statements.iter().any(|s| match &s.ty {
+            StatementType::Label(_) => true,
+            StatementType::MacroInvocation(mi) => defines_label(contract, &mi.macro_name, visited),
+            _ => false,
+        }),
+        None => false,
+    }
+}
@@ -2,173 +2,242 @@ use huff_utils::prelude::*;
 use std::str::FromStr;
 
 // Arguments can be literals, labels, opcodes, or constants
-// !! IF THERE IS AMBIGUOUS NOMENCLATURE
-// !! (E.G. BOTH OPCODE AND LABEL ARE THE SAME STRING)
-// !! COMPILATION _WILL_ ERROR
+// Labels, constants and macro arguments named after opcodes are rejected by the parser, so an
+// identifier is never both an opcode and a label
 
 /// Arg Call Bubbling
+///
+/// Arg calls passed on as arguments are bubbled up through the parent scopes in a loop rather
+/// than recursively, so deeply nested macros can't overflow the native stack.
+///
+/// Returns the macro invocation passed in place of the arg call, if any, to be expanded at the
+/// usage site. The arg calls passed to it are resolved in the macro it was passed from.
 #[allow(clippy::too_many_arguments)]
-pub fn bubble_arg_call(
+pub fn bubble_arg_call<'a>(
+    evm_version: &EVMVersion,
     arg_name: &str,
-    bytes: &mut Vec<(usize, Bytes)>,
-    macro_def: &MacroDefinition,
-    contract: &Contract,
-    scope: &mut Vec<MacroDefinition>,
+    instructions: &mut Vec<(usize, Instruction)>,
+    macro_def: &'a MacroDefinition,
+    contract: &'a Contract,
+    scope: &[&'a MacroDefinition],
     offset: &mut usize,
     // mis: Parent macro invocations and their indices
-    mis: &mut Vec<(usize, MacroInvocation)>,
-    jump_table: &mut JumpTable,
-) -> Result<(), CodegenError> {
+    mis: &[(usize, MacroInvocation)],
+    relocations: &mut Relocations,
+) -> Result<Option<MacroInvocation>, CodegenError> {
     let starting_offset = *offset;
+    let depth = scope.len();
+
+    let (arg, macro_invoc, scope, mis) = match resolve_arg_call(arg_name, macro_def, scope, mis)? {
+        Resolution::Arg(arg, macro_invoc, scope, mis) => (arg, macro_invoc, scope, mis),
+        Resolution::Missing => return Ok(None),
+        Resolution::NoInvocation => {
+            // This is a label call
+            tracing::info!(target: "codegen", "RECURSE_BYTECODE ARG CALL DEFAULTING TO LABEL CALL: \"{}\"", arg_name);
+            relocations.push(Relocation::label(arg_name, *offset, AstSpan(vec![])));
+            instructions.push((*offset, Instruction::push_placeholder(2, AstSpan(vec![]))));
+            *offset += 3;
+            return Ok(None)
+        }
+    };
+
+    match arg {
+        MacroArg::Literal(l) => {
+            tracing::info!(target: "codegen", "GOT LITERAL {} ARG FROM MACRO INVOCATION", bytes32_to_string(l, false));
+
+            let push = Instruction::push_value(l, macro_invoc.span.clone());
+            *offset += push.len();
+            instructions.push((starting_offset, push));
+        }
+        // Arg calls are bubbled up while resolving
+        MacroArg::ArgCall(_) => {}
+        MacroArg::MacroInvocation(mi) => {
+            tracing::debug!(target: "codegen", "Found MacroArg::MacroInvocation IN \"{}\" Macro Invocation: \"{}\"!", macro_invoc.macro_name, mi.macro_name);
+            return resolve_invocation_args(mi, scope, mis).map(Some)
+        }
+        MacroArg::Ident(iden) => {
+            tracing::debug!(target: "codegen", "Found MacroArg::Ident IN \"{}\" Macro Invocation: \"{}\"!", macro_invoc.macro_name, iden);
+
+            // Check for a constant first, those declared in the macro the argument was passed
+            // from taking precedence
+            let invoking = scope.len().checked_sub(2).map(|i| scope[i]);
+            let constant = match invoking.and_then(|m| m.find_constant(iden)) {
+                Some(constant) => Some(constant.clone()),
+                None => contract
+                    .constants
+                    .lock()
+                    .map_err(|_| {
+                        CodegenError::new(CodegenErrorKind::LockingError, AstSpan(vec![]), None)
+                    })?
+                    .iter()
+                    .find(|const_def| const_def.name.eq(iden))
+                    .cloned(),
+            };
+            if let Some(constant) = constant {
+                tracing::info!(target: "codegen", "ARGCALL IS CONSTANT: {:?}", constant);
+                let push = match &constant.value {
+                    ConstVal::Literal(l) => Instruction::push_value(l, macro_invoc.span.clone()),
+                    ConstVal::FreeStoragePointer(_) | ConstVal::FreeMemoryPointer(_) => {
+                        // If this is reached in codegen stage, `derive_storage_pointers`
+                        // method was not called on the AST.
+                        tracing::error!(target: "codegen", "STORAGE POINTERS INCORRECTLY DERIVED FOR \"{:?}\"", constant.value);
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::StoragePointersNotDerived,
+                            span: AstSpan(vec![]),
+                            token: None,
+                        })
+                    }
+                };
+                *offset += push.len();
+                tracing::info!(target: "codegen", "OFFSET: {}, PUSH: {}", offset, push);
+                instructions.push((starting_offset, push));
+            } else if let Ok(o) = Opcode::from_str(iden) {
+                tracing::debug!(target: "codegen", "Found Opcode: {}", o);
+                if !evm_version.supports(&o) {
+                    tracing::error!(target: "codegen", "OPCODE \"{:?}\" UNAVAILABLE IN EVM VERSION \"{}\"", o, evm_version);
+                    return Err(CodegenError {
+                        kind: CodegenErrorKind::UnsupportedOpcode(o, *evm_version),
+                        span: macro_invoc.span.clone(),
+                        token: None,
+                    })
+                }
+                instructions.push((starting_offset, Instruction::op(o, macro_invoc.span.clone())));
+                *offset += 1;
+            } else {
+                tracing::debug!(target: "codegen", "Found Label Call: {}", iden);
+
+                // This should be equivalent to a label call, to the label of the macro the
+                // argument was passed from rather than one of the macros it was passed into
+                instructions
+                    .push((*offset, Instruction::push_placeholder(2, macro_invoc.span.clone())));
+                relocations.push(Relocation {
+                    pending_scopes: depth - scope.len() + 1,
+                    ..Relocation::label(iden, *offset, macro_invoc.span.clone())
+                });
+                *offset += 3;
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// The argument an arg call resolved to
+enum Resolution<'m, 'a> {
+    /// The argument, the invocation it was passed to, and the scope and parent invocations of
+    /// the invoked macro
+    Arg(
+        &'m MacroArg,
+        &'m MacroInvocation,
+        &'m [&'a MacroDefinition],
+        &'m [(usize, MacroInvocation)],
+    ),
+    /// The argument isn't a parameter of the macro, or wasn't passed to its invocation
+    Missing,
+    /// The macro has no invocation to take the argument from
+    NoInvocation,
+}
+
+/// Resolves an arg call to the argument passed in its place, bubbling arg calls passed on as
+/// arguments up through the parent scopes.
+fn resolve_arg_call<'m, 'a>(
+    arg_name: &str,
+    mut macro_def: &'a MacroDefinition,
+    mut scope: &'m [&'a MacroDefinition],
+    mut mis: &'m [(usize, MacroInvocation)],
+) -> Result<Resolution<'m, 'a>, CodegenError> {
+    let mut arg_name = arg_name;
+
+    loop {
+        let macro_invoc = match mis.last() {
+            Some(macro_invoc) => macro_invoc,
+            None => return Ok(Resolution::NoInvocation),
+        };
 
-    if let Some(macro_invoc) = mis.last() {
         // Literal, Ident & Arg Call Check
         // First get this arg_nam position in the macro definition params
-        if let Some(pos) = macro_def
+        let pos = match macro_def
             .parameters
             .iter()
             .position(|r| r.name.as_ref().map_or(false, |s| s.eq(arg_name)))
         {
-            tracing::info!(target: "codegen", "GOT \"{}\" POS IN ARG LIST: {}", arg_name, pos);
-
-            if let Some(arg) = macro_invoc.1.args.get(pos) {
-                tracing::info!(target: "codegen", "GOT \"{:?}\" ARG FROM MACRO INVOCATION", arg);
-                match arg {
-                    MacroArg::Literal(l) => {
-                        tracing::info!(target: "codegen", "GOT LITERAL {} ARG FROM MACRO INVOCATION", bytes32_to_string(l, false));
-
-                        let hex_literal: String = bytes32_to_string(l, false);
-                        let push_bytes = format!("{:02x}{hex_literal}", 95 + hex_literal.len() / 2);
-                        let b = Bytes(push_bytes);
-                        *offset += b.0.len() / 2;
-                        bytes.push((starting_offset, b));
-                    }
-                    MacroArg::ArgCall(ac) => {
-                        tracing::info!(target: "codegen", "GOT ARG CALL \"{}\" ARG FROM MACRO INVOCATION", ac);
-                        tracing::debug!(target: "codegen", "~~~ BUBBLING UP ARG CALL");
-                        let mut new_scope = Vec::from(&scope[..scope.len().saturating_sub(1)]);
-                        let bubbled_macro_invocation = new_scope.last().unwrap().clone();
-                        tracing::debug!(target: "codegen", "BUBBLING UP WITH MACRO DEF: {}", bubbled_macro_invocation.name);
-                        tracing::debug!(target: "codegen", "CURRENT MACRO DEF: {}", macro_def.name);
-
-                        // Only remove an invocation if not at bottom level, otherwise we'll
-                        // remove one too many
-                        let last_mi = match mis.last() {
-                            Some(mi) => mi,
-                            None => {
-                                return Err(CodegenError {
-                                    kind: CodegenErrorKind::MissingMacroInvocation(
-                                        macro_def.name.clone(),
-                                    ),
-                                    span: bubbled_macro_invocation.span,
-                                    token: None,
-                                })
-                            }
-                        };
-                        return if last_mi.1.macro_name.eq(&macro_def.name) {
-                            bubble_arg_call(
-                                arg_name,
-                                bytes,
-                                &bubbled_macro_invocation,
-                                contract,
-                                &mut new_scope,
-                                offset,
-                                &mut Vec::from(&mis[..mis.len().saturating_sub(1)]),
-                                jump_table,
-                            )
-                        } else {
-                            bubble_arg_call(
-                                arg_name,
-                                bytes,
-                                &bubbled_macro_invocation,
-                                contract,
-                                &mut new_scope,
-                                offset,
-                                mis,
-                                jump_table,
-                            )
-                        }
-                    }
-                    MacroArg::Ident(iden) => {
-                        tracing::debug!(target: "codegen", "Found MacroArg::Ident IN \"{}\" Macro Invocation: \"{}\"!", macro_invoc.1.macro_name, iden);
-
-                        // Check for a constant first
-                        if let Some(constant) = contract
-                            .constants
-                            .lock()
-                            .map_err(|_| {
-                                CodegenError::new(
-                                    CodegenErrorKind::LockingError,
-                                    AstSpan(vec![]),
-                                    None,
-                                )
-                            })?
-                            .iter()
-                            .find(|const_def| const_def.name.eq(iden))
-                        {
-                            tracing::info!(target: "codegen", "ARGCALL IS CONSTANT: {:?}", constant);
-                            let push_bytes = match &constant.value {
-                                ConstVal::Literal(l) => {
-                                    let hex_literal: String = bytes32_to_string(l, false);
-                                    format!("{:02x}{hex_literal}", 95 + hex_literal.len() / 2)
-                                }
-                                ConstVal::FreeStoragePointer(fsp) => {
-                                    // If this is reached in codegen stage,
-                                    // `derive_storage_pointers`
-                                    // method was not called on the AST.
-                                    tracing::error!(target: "codegen", "STORAGE POINTERS INCORRECTLY DERIVED FOR \"{:?}\"", fsp);
-                                    return Err(CodegenError {
-                                        kind: CodegenErrorKind::StoragePointersNotDerived,
-                                        span: AstSpan(vec![]),
-                                        token: None,
-                                    })
-                                }
-                            };
-                            *offset += push_bytes.len() / 2;
-                            tracing::info!(target: "codegen", "OFFSET: {}, PUSH BYTES: {:?}", offset, push_bytes);
-                            bytes.push((starting_offset, Bytes(push_bytes)));
-                        } else if let Ok(o) = Opcode::from_str(iden) {
-                            tracing::debug!(target: "codegen", "Found Opcode: {}", o);
-                            let b = Bytes(o.to_string());
-                            *offset += b.0.len() / 2;
-                            bytes.push((starting_offset, b));
-                        } else {
-                            tracing::debug!(target: "codegen", "Found Label Call: {}", iden);
-
-                            // This should be equivalent to a label call.
-                            bytes.push((*offset, Bytes(format!("{}xxxx", Opcode::Push2))));
-                            jump_table.insert(
-                                *offset,
-                                vec![Jump {
-                                    label: iden.to_owned(),
-                                    bytecode_index: 0,
-                                    span: macro_invoc.1.span.clone(),
-                                }],
-                            );
-                            *offset += 3;
-                        }
-                    }
-                }
-            } else {
+            Some(pos) => pos,
+            None => {
+                tracing::warn!(target: "codegen", "\"{}\" NOT IN ARG LIST", arg_name);
+                return Ok(Resolution::Missing)
+            }
+        };
+        tracing::info!(target: "codegen", "GOT \"{}\" POS IN ARG LIST: {}", arg_name, pos);
+
+        let arg = match macro_invoc.1.args.get(pos) {
+            Some(arg) => arg,
+            None => {
                 tracing::warn!(target: "codegen", "\"{}\" FOUND IN MACRO DEF BUT NOT IN MACRO INVOCATION!", arg_name);
+                return Ok(Resolution::Missing)
             }
-        } else {
-            tracing::warn!(target: "codegen", "\"{}\" NOT IN ARG LIST", arg_name);
-        }
-    } else {
-        // This is a label call
-        tracing::info!(target: "codegen", "RECURSE_BYTECODE ARG CALL DEFAULTING TO LABEL CALL: \"{}\"", arg_name);
-        let new_span = match mis.last() {
-            Some(mi) => mi.1.span.clone(),
-            None => AstSpan(vec![]),
         };
-        jump_table.insert(
-            mis.last().map(|mi| mi.0).unwrap_or_else(|| 0),
-            vec![Jump { label: arg_name.to_owned(), bytecode_index: 0, span: new_span }],
-        );
-        bytes.push((*offset, Bytes(format!("{}xxxx", Opcode::Push2))));
-        *offset += 3;
+        tracing::info!(target: "codegen", "GOT \"{:?}\" ARG FROM MACRO INVOCATION", arg);
+
+        let ac = match arg {
+            MacroArg::ArgCall(ac) => ac,
+            _ => return Ok(Resolution::Arg(arg, &macro_invoc.1, scope, mis)),
+        };
+        tracing::info!(target: "codegen", "GOT ARG CALL \"{}\" ARG FROM MACRO INVOCATION", ac);
+        tracing::debug!(target: "codegen", "~~~ BUBBLING UP ARG CALL");
+        let new_scope = &scope[..scope.len().saturating_sub(1)];
+        let bubbled_macro_invocation = match new_scope.last() {
+            Some(m) => *m,
+            None => {
+                return Err(CodegenError {
+                    kind: CodegenErrorKind::MissingMacroInvocation(macro_def.name.clone()),
+                    span: macro_invoc.1.span.clone(),
+                    token: None,
+                })
+            }
+        };
+        tracing::debug!(target: "codegen", "BUBBLING UP WITH MACRO DEF: {}", bubbled_macro_invocation.name);
+        tracing::debug!(target: "codegen", "CURRENT MACRO DEF: {}", macro_def.name);
+
+        // Only remove an invocation if not at bottom level, otherwise we'll remove one too many
+        if macro_invoc.1.macro_name.eq(&macro_def.name) {
+            mis = &mis[..mis.len().saturating_sub(1)];
+        }
+        macro_def = bubbled_macro_invocation;
+        scope = new_scope;
+        arg_name = ac;
     }
+}
+
+/// Resolves the arg calls passed to a macro invocation that was itself passed as an argument,
+/// in the macro the invocation was written in: the parent of the macro it was passed to.
+fn resolve_invocation_args(
+    mi: &MacroInvocation,
+    scope: &[&MacroDefinition],
+    mis: &[(usize, MacroInvocation)],
+) -> Result<MacroInvocation, CodegenError> {
+    let mut resolved = mi.clone();
+    let (macro_def, parent_scope) = match scope.split_last() {
+        Some((macro_def, parent_scope)) if !parent_scope.is_empty() => (macro_def, parent_scope),
+        _ => return Ok(resolved),
+    };
+    let parent_mis = match mis.last() {
+        Some(last) if last.1.macro_name.eq(&macro_def.name) => &mis[..mis.len() - 1],
+        _ => mis,
+    };
 
-    Ok(())
+    for arg in resolved.args.iter_mut() {
+        let resolution = match arg {
+            MacroArg::ArgCall(ac) => resolve_arg_call(
+                ac,
+                parent_scope[parent_scope.len() - 1],
+                parent_scope,
+                parent_mis,
+            )?,
+            _ => continue,
+        };
+        if let Resolution::Arg(value, ..) = resolution {
+            *arg = value.clone();
+        }
+    }
+    Ok(resolved)
 }
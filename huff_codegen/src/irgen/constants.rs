@@ -1,19 +1,25 @@
 use huff_utils::prelude::{
-    bytes32_to_string, AstSpan, CodegenError, CodegenErrorKind, ConstVal, Contract,
+    AstSpan, CodegenError, CodegenErrorKind, ConstVal, Contract, Instruction, MacroDefinition,
 };
 
 /// Transforms a constant definition into it's respective bytecode
+///
+/// Constants declared in the body of the macro take precedence over the contract's constants.
 pub fn constant_gen(
     name: &str,
+    macro_def: &MacroDefinition,
     contract: &Contract,
     ir_byte_span: AstSpan,
-) -> Result<String, CodegenError> {
+) -> Result<Instruction, CodegenError> {
     // Get the first `ConstantDefinition` that matches the constant's name
     let constants = contract
         .constants
         .lock()
         .map_err(|_| CodegenError::new(CodegenErrorKind::LockingError, AstSpan(vec![]), None))?;
-    let constant = if let Some(m) = constants.iter().find(|const_def| const_def.name.eq(&name)) {
+    let constant = if let Some(m) = macro_def
+        .find_constant(name)
+        .or_else(|| constants.iter().find(|const_def| const_def.name.eq(&name)))
+    {
         m
     } else {
         tracing::error!(target: "codegen", "MISSING CONSTANT DEFINITION \"{}\"", name);
@@ -29,15 +35,23 @@ pub fn constant_gen(
     // Should always be a `Literal` if storage pointers were derived in the AST
     // prior to generating the IR bytes.
     tracing::info!(target: "codegen", "FOUND CONSTANT DEFINITION: {}", constant.name);
-    let push_bytes = match &constant.value {
+    let instruction = match &constant.value {
         ConstVal::Literal(l) => {
-            let hex_literal: String = bytes32_to_string(l, false);
-            format!("{:02x}{hex_literal}", 95 + hex_literal.len() / 2)
+            // Constant overrides bypass the parser, so re-check the type annotation
+            if let Some(ty) = constant.ty.filter(|ty| !ty.fits_literal(l)) {
+                tracing::error!(target: "codegen", "CONSTANT \"{}\" DOES NOT FIT TYPE \"{}\"", constant.name, ty);
+                return Err(CodegenError {
+                    kind: CodegenErrorKind::ConstantTypeMismatch(constant.name.clone()),
+                    span: ir_byte_span,
+                    token: None,
+                })
+            }
+            Instruction::push_value(l, ir_byte_span)
         }
-        ConstVal::FreeStoragePointer(fsp) => {
+        ConstVal::FreeStoragePointer(_) | ConstVal::FreeMemoryPointer(_) => {
             // If this is reached in codegen stage, the `derive_storage_pointers`
             // method was not called on the AST.
-            tracing::error!(target: "codegen", "STORAGE POINTERS INCORRECTLY DERIVED FOR \"{:?}\"", fsp);
+            tracing::error!(target: "codegen", "STORAGE POINTERS INCORRECTLY DERIVED FOR \"{:?}\"", constant.value);
             return Err(CodegenError {
                 kind: CodegenErrorKind::StoragePointersNotDerived,
                 span: constant.span.clone(),
@@ -46,5 +60,5 @@ pub fn constant_gen(
         }
     };
 
-    Ok(push_bytes)
+    Ok(instruction)
 }
@@ -1,24 +1,42 @@
-use huff_utils::prelude::*;
+use huff_utils::{eip712, prelude::*};
 
-use crate::Codegen;
+/// The bytecode generated for a statement
+pub enum StatementGen<'a> {
+    /// The statement's bytecode
+    Instructions(Vec<(usize, Instruction)>),
+    /// The statement needs a macro expanded before its bytecode is known
+    Expand(&'a MacroDefinition, Expansion),
+}
+
+/// Why a macro is expanded, determining what is done with its bytecode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expansion {
+    /// The macro's bytecode is inlined at the invocation
+    Invocation,
+    /// Only the size of the macro's bytecode is pushed, at the given offset
+    Codesize(usize),
+}
 
 /// Generates the respective Bytecode for a given Statement
+///
+/// Inlined macro invocations and `__codesize` calls are not expanded here, the macro to expand
+/// is returned instead so that the caller can expand it without recursing.
 #[allow(clippy::too_many_arguments)]
-pub fn statement_gen(
+pub fn statement_gen<'a>(
+    evm_version: &EVMVersion,
     s: &Statement,
-    contract: &Contract,
-    macro_def: &MacroDefinition,
-    scope: &mut Vec<MacroDefinition>,
+    contract: &'a Contract,
+    macro_def: &'a MacroDefinition,
+    scope: &mut Vec<&'a MacroDefinition>,
     offset: &mut usize,
     mis: &mut Vec<(usize, MacroInvocation)>,
-    jump_table: &mut JumpTable,
+    relocations: &mut Relocations,
     label_indices: &mut LabelIndices,
-    table_instances: &mut Jumps,
     utilized_tables: &mut Vec<TableDefinition>,
     circular_codesize_invocations: &mut CircularCodeSizeIndices,
     starting_offset: usize,
-) -> Result<Vec<(usize, Bytes)>, CodegenError> {
-    let mut bytes = vec![];
+) -> Result<StatementGen<'a>, CodegenError> {
+    let mut instructions = vec![];
 
     tracing::debug!(target: "codegen", "Got Statement: {}", s.ty);
 
@@ -47,116 +65,154 @@ pub fn statement_gen(
                 tracing::error!(target: "codegen", "Tests may not be invoked: {}", ir_macro.name);
                 return Err(CodegenError {
                     kind: CodegenErrorKind::TestInvocation(ir_macro.name.clone()),
-                    span: ir_macro.span,
+                    span: ir_macro.span.clone(),
+                    token: None,
+                })
+            }
+
+            // Every parameter must be passed exactly one argument
+            if mi.args.len() != ir_macro.parameters.len() {
+                tracing::error!(
+                    target: "codegen",
+                    "MACRO \"{}\" TAKES {} ARGUMENTS BUT {} WERE PROVIDED",
+                    ir_macro.name,
+                    ir_macro.parameters.len(),
+                    mi.args.len()
+                );
+                return Err(CodegenError {
+                    kind: CodegenErrorKind::ArgumentCountMismatch(
+                        ir_macro.name.clone(),
+                        ir_macro.parameters.len(),
+                        mi.args.len(),
+                    ),
+                    span: mi.span.clone(),
                     token: None,
                 })
             }
 
             // If invoked macro is a function (outlined), insert a jump to the function's code and a
-            // jumpdest to return to. If it is inlined, insert the macro's code at the
-            // current offset.
-            if ir_macro.outlined {
+            // jumpdest to return to, or a `callf` to its code section for EOF code. If it is
+            // inlined, insert the macro's code at the current offset.
+            if ir_macro.is_outlined() && evm_version.eof() {
+                // The code sections of functions follow the one of the contract
+                let index = contract
+                    .macros
+                    .iter()
+                    .filter(|m| m.is_outlined())
+                    .position(|m| m.name == ir_macro.name)
+                    .unwrap_or_default() +
+                    1;
+                let callf = Instruction {
+                    opcode: Some(Opcode::Callf),
+                    immediate: (index as u16).to_be_bytes().to_vec(),
+                    placeholder: 0,
+                    written: None,
+                    source_span: s.span.clone(),
+                };
+                let size = callf.len();
+                instructions.push((*offset, callf));
+                *offset += size;
+            } else if ir_macro.is_outlined() {
                 // Get necessary swap ops to reorder stack
                 // PC of the return jumpdest should be below the function's stack inputs
-                let stack_swaps = (0..ir_macro.takes)
-                    .rev()
-                    .map(|i| format!("{:02x}", 0x90 + i))
-                    .collect::<Vec<_>>();
+                let mut stack_swaps = stack_swaps(ir_macro.takes, ir_macro)?;
+                stack_swaps.reverse();
 
                 // Insert a jump to the outlined macro's code
-                jump_table.insert(
-                    *offset + stack_swaps.len() + 3, // PUSH2 + 2 bytes + stack_swaps.len()
-                    vec![Jump {
-                        label: format!("goto_{}", &ir_macro.name),
-                        bytecode_index: 0,
-                        span: s.span.clone(),
-                    }],
-                );
+                let jump_offset = *offset + stack_swaps.len() + 3; // PUSH2 + 2 bytes + stack_swaps.len()
+                relocations.push(Relocation::label(
+                    format!("goto_{}", ir_macro.name),
+                    jump_offset,
+                    s.span.clone(),
+                ));
 
                 // Store return JUMPDEST PC on the stack and re-order the stack so that
                 // the return JUMPDEST PC is below the function's stack inputs
-                bytes.push((
-                    *offset,
-                    Bytes(format!(
-                        "{}{:04x}{}",
-                        Opcode::Push2,
-                        *offset + stack_swaps.len() + 7,
-                        stack_swaps.join("")
-                    )),
-                ));
+                let return_offset = jump_offset + 4; // PUSH2 + 2 bytes + JUMP
+                relocations.push(Relocation {
+                    target: Some(return_offset),
+                    ..Relocation::label(format!("goto_{}", ir_macro.name), *offset, s.span.clone())
+                });
+                instructions.push((*offset, Instruction::push_placeholder(2, s.span.clone())));
+                for (i, swap) in stack_swaps.into_iter().enumerate() {
+                    instructions.push((*offset + 3 + i, Instruction::op(swap, s.span.clone())));
+                }
                 // Insert jump to outlined macro + jumpdest to return to
-                bytes.push((
-                    *offset + stack_swaps.len() + 3, // PUSH2 + 2 bytes + stack_swaps.len()
-                    Bytes(format!("{}xxxx{}{}", Opcode::Push2, Opcode::Jump, Opcode::Jumpdest)),
-                ));
-                // PUSH2 + 2 bytes + stack_swaps.len() + PUSH2 + 2 bytes + JUMP + JUMPDEST
-                *offset += stack_swaps.len() + 8;
+                instructions.push((jump_offset, Instruction::push_placeholder(2, s.span.clone())));
+                instructions.push((jump_offset + 3, Instruction::op(Opcode::Jump, s.span.clone())));
+                instructions
+                    .push((return_offset, Instruction::op(Opcode::Jumpdest, s.span.clone())));
+                *offset = return_offset + 1;
             } else {
-                // Recurse into macro invocation
-                scope.push(ir_macro.clone());
+                // Expand the macro invocation, its bytecode is inlined by the caller
+                scope.push(ir_macro);
                 mis.push((*offset, mi.clone()));
-
-                let mut res: BytecodeRes = match Codegen::macro_to_bytecode(
-                    ir_macro.clone(),
-                    contract,
-                    scope,
-                    *offset,
-                    mis,
-                    false,
-                    Some(circular_codesize_invocations),
-                ) {
-                    Ok(r) => r,
-                    Err(e) => {
-                        tracing::error!(
-                            target: "codegen",
-                            "FAILED TO RECURSE INTO MACRO \"{}\"",
-                            ir_macro.name
-                        );
-                        return Err(e)
-                    }
-                };
-
-                // Set jump table values
-                tracing::debug!(target: "codegen", "Unmatched jumps: {:?}", res.unmatched_jumps.iter().map(|uj| uj.label.clone()).collect::<Vec<String>>());
-                for j in res.unmatched_jumps.iter_mut() {
-                    let new_index = j.bytecode_index;
-                    j.bytecode_index = 0;
-                    let mut new_jumps = if let Some(jumps) = jump_table.get(&new_index) {
-                        jumps.clone()
-                    } else {
-                        vec![]
-                    };
-                    new_jumps.push(j.clone());
-                    jump_table.insert(new_index, new_jumps);
-                }
-                table_instances.extend(res.table_instances);
-                label_indices.extend(res.label_indices);
-                utilized_tables.extend(res.utilized_tables);
-
-                // Increase offset by byte length of recursed macro
-                *offset += res.bytes.iter().map(|(_, b)| b.0.len()).sum::<usize>() / 2;
-                // Add the macro's bytecode to the final result
-                bytes = [bytes, res.bytes].concat()
+                return Ok(StatementGen::Expand(ir_macro, Expansion::Invocation))
             }
         }
         StatementType::Label(label) => {
             // Add JUMPDEST opcode to final result and add to label_indices
             tracing::info!(target: "codegen", "RECURSE BYTECODE GOT LABEL: {:?}", label.name);
-            label_indices.insert(label.name.clone(), *offset);
-            bytes.push((*offset, Bytes(Opcode::Jumpdest.to_string())));
+            label_indices.insert(Symbol::new(&label.name), *offset);
+            instructions.push((*offset, Instruction::op(Opcode::Jumpdest, s.span.clone())));
             *offset += 1;
         }
         StatementType::LabelCall(label) => {
             // Generate code for a `LabelCall`
-            // PUSH2 + 2 byte destination (placeholder for now, filled in `Codegen::fill_unmatched`
+            // PUSH2 + 2 byte destination (placeholder for now, patched in
+            // `Codegen::apply_relocations`
             tracing::info!(target: "codegen", "RECURSE BYTECODE GOT LABEL CALL: {}", label);
-            jump_table.insert(
-                *offset,
-                vec![Jump { label: label.to_string(), bytecode_index: 0, span: s.span.clone() }],
-            );
-            bytes.push((*offset, Bytes(format!("{}xxxx", Opcode::Push2))));
+            relocations.push(Relocation::label(label, *offset, s.span.clone()));
+            instructions.push((*offset, Instruction::push_placeholder(2, s.span.clone())));
             *offset += 3;
         }
+        StatementType::Assertion(assertion) => {
+            // Assertions generate no code, failing compilation if they don't hold
+            let constants = contract.constants.lock().map_err(|_| {
+                CodegenError::new(CodegenErrorKind::LockingError, AstSpan(vec![]), None)
+            })?;
+            let resolve = |name: &str| {
+                let constant = macro_def
+                    .find_constant(name)
+                    .or_else(|| constants.iter().find(|c| c.name == name))?;
+                match constant.value {
+                    ConstVal::Literal(l) => Some(l),
+                    _ => None,
+                }
+            };
+            let value = assertion.condition.eval(&resolve).map_err(|reason| {
+                tracing::error!(target: "codegen", "INVALID CONSTANT EXPRESSION: {}", reason);
+                CodegenError {
+                    kind: CodegenErrorKind::InvalidConstantExpression(reason),
+                    span: assertion.span.clone(),
+                    token: None,
+                }
+            })?;
+            if value.is_zero() {
+                let message =
+                    assertion.message.clone().unwrap_or_else(|| assertion.condition.to_string());
+                tracing::error!(target: "codegen", "ASSERTION FAILED IN \"{}\": {}", macro_def.name, message);
+                return Err(CodegenError {
+                    kind: CodegenErrorKind::AssertionFailed(message),
+                    span: assertion.span.clone(),
+                    token: None,
+                })
+            }
+        }
+        StatementType::OffsetAssertion(expected) => {
+            // Offset assertions generate no code, failing compilation if the code before them
+            // isn't as long as expected
+            if *offset != *expected {
+                tracing::error!(target: "codegen", "OFFSET ASSERTION FAILED IN \"{}\": {:#x} != {:#x}", macro_def.name, offset, expected);
+                return Err(CodegenError {
+                    kind: CodegenErrorKind::AssertionFailed(format!(
+                        "expected offset {expected:#x}, found {offset:#x}"
+                    )),
+                    span: s.span.clone(),
+                    token: None,
+                })
+            }
+        }
         StatementType::BuiltinFunctionCall(bf) => {
             // Generate code for a `BuiltinFunctionCall`
             // __codesize, __tablesize, or __tablestart
@@ -197,41 +253,21 @@ pub fn statement_gen(
                         tracing::debug!(target: "codegen", "CIRCULAR CODESIZE INVOCATION DETECTED INJECTING PLACEHOLDER | macro: {}", ir_macro.name);
 
                         // Save the invocation for later
-                        circular_codesize_invocations.insert((codesize_arg.to_string(), *offset));
+                        circular_codesize_invocations
+                            .insert((Symbol::new(codesize_arg), *offset));
 
                         // Progress offset by placeholder size
                         *offset += 2;
-                        bytes.push((starting_offset, Bytes("cccc".to_string())));
-                    } else {
-                        // We will still need to recurse to get accurate values
-                        let res: BytecodeRes = match Codegen::macro_to_bytecode(
-                            ir_macro.clone(),
-                            contract,
-                            scope,
-                            *offset,
-                            mis,
-                            ir_macro.name.eq("CONSTRUCTOR"),
-                            Some(circular_codesize_invocations),
-                        ) {
-                            Ok(r) => r,
-                            Err(e) => {
-                                tracing::error!(
-                                    target: "codegen",
-                                    "FAILED TO RECURSE INTO MACRO \"{}\"",
-                                    ir_macro.name
-                                );
-                                return Err(e)
-                            }
-                        };
-
-                        let size = format_even_bytes(format!(
-                            "{:02x}",
-                            (res.bytes.iter().map(|(_, b)| b.0.len()).sum::<usize>() / 2)
+                        instructions.push((
+                            starting_offset,
+                            Instruction::data_placeholder(2, vec![], bf.span.clone()),
                         ));
-                        let push_bytes = format!("{:02x}{size}", 95 + size.len() / 2);
-
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
+                    } else {
+                        // We will still need to expand the macro to get accurate values
+                        return Ok(StatementGen::Expand(
+                            ir_macro,
+                            Expansion::Codesize(starting_offset),
+                        ))
                     }
                 }
                 BuiltinFunctionKind::Tablesize => {
@@ -254,30 +290,30 @@ pub fn statement_gen(
                         })
                     };
 
-                    let size = bytes32_to_string(&ir_table.size, false);
-                    let push_bytes = format!("{:02x}{size}", 95 + size.len() / 2);
+                    let push = Instruction::push_value(&ir_table.size, bf.span.clone());
 
-                    if !utilized_tables.contains(&ir_table) {
-                        utilized_tables.push(ir_table);
+                    if !utilized_tables.contains(ir_table) {
+                        utilized_tables.push(ir_table.clone());
                     }
 
-                    *offset += push_bytes.len() / 2;
-                    bytes.push((starting_offset, Bytes(push_bytes)));
+                    *offset += push.len();
+                    instructions.push((starting_offset, push));
                 }
                 BuiltinFunctionKind::Tablestart => {
                     // Make sure the table exists
                     if let Some(t) = contract.find_table_by_name(bf.args[0].name.as_ref().unwrap())
                     {
-                        table_instances.push(Jump {
-                            label: bf.args[0].name.as_ref().unwrap().to_owned(),
-                            bytecode_index: *offset,
-                            span: bf.span.clone(),
-                        });
-                        if !utilized_tables.contains(&t) {
-                            utilized_tables.push(t);
+                        relocations.push(Relocation::table(
+                            bf.args[0].name.as_ref().unwrap(),
+                            *offset,
+                            bf.span.clone(),
+                        ));
+                        if !utilized_tables.contains(t) {
+                            utilized_tables.push(t.clone());
                         }
 
-                        bytes.push((*offset, Bytes(format!("{}xxxx", Opcode::Push2))));
+                        instructions
+                            .push((*offset, Instruction::push_placeholder(2, bf.span.clone())));
                         *offset += 3;
                     } else {
                         tracing::error!(
@@ -313,31 +349,38 @@ pub fn statement_gen(
                         });
                     }
 
-                    if let Some(func) = contract
-                        .functions
-                        .iter()
-                        .find(|f| bf.args[0].name.as_ref().unwrap().eq(&f.name))
-                    {
-                        let push_bytes =
-                            format!("{}{}", Opcode::Push4, hex::encode(func.signature));
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
-                    } else if let Some(error) = contract
-                        .errors
-                        .iter()
-                        .find(|e| bf.args[0].name.as_ref().unwrap().eq(&e.name))
+                    if let Some(func) = contract.find_function(bf.args[0].name.as_ref().unwrap()) {
+                        let push = Instruction::push(func.signature.to_vec(), bf.span.clone());
+                        *offset += push.len();
+                        instructions.push((starting_offset, push));
+                    } else if let Some(error) =
+                        contract.find_error(bf.args[0].name.as_ref().unwrap())
                     {
-                        let push_bytes =
-                            format!("{}{}", Opcode::Push4, hex::encode(error.selector));
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
-                    } else if let Some(s) = &bf.args[0].name {
+                        let push = Instruction::push(error.selector.to_vec(), bf.span.clone());
+                        *offset += push.len();
+                        instructions.push((starting_offset, push));
+                    } else if let Some(s) = bf.args[0].name.as_ref().filter(|s| s.contains('(')) {
                         let mut signature = [0u8; 4]; // Only keep first 4 bytes
                         hash_bytes(&mut signature, s);
 
-                        let push_bytes = format!("{}{}", Opcode::Push4, hex::encode(signature));
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
+                        let push = Instruction::push(signature.to_vec(), bf.span.clone());
+                        *offset += push.len();
+                        instructions.push((starting_offset, push));
+                    } else if let Some(s) = &bf.args[0].name {
+                        // A name without a parameter list can't be a signature to hash
+                        tracing::error!(
+                            target: "codegen",
+                            "UNDECLARED FUNCTION PASSED TO __FUNC_SIG: \"{}\"",
+                            s
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::UndeclaredFunction(
+                                s.to_string(),
+                                contract.functions.iter().map(|f| f.name.clone()).collect(),
+                            ),
+                            span: bf.span.clone(),
+                            token: None,
+                        })
                     } else {
                         tracing::error!(
                             target: "codegen",
@@ -372,22 +415,17 @@ pub fn statement_gen(
                         });
                     }
 
-                    if let Some(event) = contract
-                        .events
-                        .iter()
-                        .find(|e| bf.args[0].name.as_ref().unwrap().eq(&e.name))
-                    {
-                        let hash = bytes32_to_string(&event.hash, false);
-                        let push_bytes = format!("{}{hash}", Opcode::Push32);
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
+                    if let Some(event) = contract.find_event(bf.args[0].name.as_ref().unwrap()) {
+                        let push = Instruction::push(event.hash.to_vec(), bf.span.clone());
+                        *offset += push.len();
+                        instructions.push((starting_offset, push));
                     } else if let Some(s) = &bf.args[0].name {
                         let mut hash = [0u8; 32];
                         hash_bytes(&mut hash, s);
 
-                        let push_bytes = format!("{}{}", Opcode::Push32, hex::encode(hash));
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
+                        let push = Instruction::push(hash.to_vec(), bf.span.clone());
+                        *offset += push.len();
+                        instructions.push((starting_offset, push));
                     } else {
                         tracing::error!(
                             target: "codegen",
@@ -420,17 +458,13 @@ pub fn statement_gen(
                         })
                     }
 
-                    if let Some(error) = contract
-                        .errors
-                        .iter()
-                        .find(|e| bf.args[0].name.as_ref().unwrap().eq(&e.name))
-                    {
-                        // Add 28 bytes to left-pad the 4 byte selector
-                        let selector =
-                            format!("{}{}", hex::encode(error.selector), "00".repeat(28));
-                        let push_bytes = format!("{}{selector}", Opcode::Push32);
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
+                    if let Some(error) = contract.find_error(bf.args[0].name.as_ref().unwrap()) {
+                        // Add 28 bytes to right-pad the 4 byte selector
+                        let mut selector = [0u8; 32];
+                        selector[..4].copy_from_slice(&error.selector);
+                        let push = Instruction::push(selector.to_vec(), bf.span.clone());
+                        *offset += push.len();
+                        instructions.push((starting_offset, push));
                     } else {
                         tracing::error!(
                             target: "codegen",
@@ -464,10 +498,25 @@ pub fn statement_gen(
                     }
 
                     let hex = format_even_bytes(bf.args[0].name.as_ref().unwrap().clone());
-                    let push_bytes =
-                        format!("{}{hex}{}", Opcode::Push32, "0".repeat(64 - hex.len()));
-                    *offset += push_bytes.len() / 2;
-                    bytes.push((starting_offset, Bytes(push_bytes)));
+                    let mut padded = match str_to_vec(&hex) {
+                        Ok(bytes) if bytes.len() <= 32 => bytes,
+                        _ => {
+                            tracing::error!(
+                                target: "codegen",
+                                "INVALID HEX STRING PASSED TO __RIGHTPAD: \"{}\"",
+                                hex
+                            );
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::InvalidHex(hex),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                    };
+                    padded.resize(32, 0);
+                    let push = Instruction::push(padded, bf.span.clone());
+                    *offset += push.len();
+                    instructions.push((starting_offset, push));
                 }
                 BuiltinFunctionKind::DynConstructorArg => {
                     if bf.args.len() != 2 {
@@ -510,27 +559,43 @@ pub fn statement_gen(
                     // <len (2 bytes)> <dest_mem_ptr (2 bytes)> mstore
                     // <len (2 bytes)> <contents_code_ptr (2 bytes)> <dest_mem_ptr + 0x20 (2 bytes)>
                     // codecopy
-                    *offset += 17;
-                    bytes.push((
-                        starting_offset,
-                        Bytes(format!(
-                            "{}{}{}",
-                            "xx".repeat(14),
-                            bf.args[0].name.as_ref().unwrap(),
-                            pad_n_bytes(bf.args[1].name.as_ref().unwrap(), 2)
-                        )),
-                    ));
+                    let (arg_index, dest_offset) = match (
+                        u8::from_str_radix(arg_index, 16),
+                        u16::from_str_radix(dest_offset, 16),
+                    ) {
+                        (Ok(arg_index), Ok(dest_offset)) => (arg_index, dest_offset),
+                        _ => {
+                            tracing::error!(
+                                target = "codegen",
+                                "Invalid hex in argument passed to __CODECOPY_DYN_ARG"
+                            );
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(String::from(
+                                    "Invalid hex in argument passed to __CODECOPY_DYN_ARG",
+                                )),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                    };
+                    let placeholder = Instruction::data_placeholder(
+                        14,
+                        [&[arg_index][..], &dest_offset.to_be_bytes()].concat(),
+                        bf.span.clone(),
+                    );
+                    *offset += placeholder.len();
+                    instructions.push((starting_offset, placeholder));
                 }
                 BuiltinFunctionKind::Verbatim => {
                     if bf.args.len() != 1 {
                         tracing::error!(
                             target = "codegen",
-                            "Incorrect number of arguments passed to __INJECT, should be 1: {}",
+                            "Incorrect number of arguments passed to __VERBATIM, should be 1: {}",
                             bf.args.len()
                         );
                         return Err(CodegenError {
                             kind: CodegenErrorKind::InvalidArguments(format!(
-                                "Incorrect number of arguments passed to __INJECT, should be 1: {}",
+                                "Incorrect number of arguments passed to __VERBATIM, should be 1: {}",
                                 bf.args.len()
                             )),
                             span: bf.span.clone(),
@@ -539,19 +604,14 @@ pub fn statement_gen(
                     }
 
                     let verbatim_str = bf.args[0].name.as_ref().unwrap();
-                    // check if verbatim was passed a hex string
-                    let mut is_hex = true;
-                    for c in verbatim_str.chars() {
-                        if !c.is_ascii_hexdigit() {
-                            is_hex = false;
-                            break
-                        }
-                    }
-                    if !is_hex {
+                    // Strings may be passed with a `0x` prefix, i.e. `__VERBATIM("0x60ff")`
+                    let hex = verbatim_str.strip_prefix("0x").unwrap_or(verbatim_str);
+                    // The bytes are injected unmodified, so they must form whole bytes of hex
+                    if hex.len() % 2 != 0 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
                         tracing::error!(
                             target: "codegen",
                             "INVALID HEX STRING PASSED TO __VERBATIM: \"{}\"",
-                            bf.args[0].name.as_ref().unwrap()
+                            verbatim_str
                         );
                         return Err(CodegenError {
                             kind: CodegenErrorKind::InvalidHex(verbatim_str.to_string()),
@@ -560,12 +620,613 @@ pub fn statement_gen(
                         })
                     }
 
-                    tracing::debug!(target: "codegen", "INJECTING as verbatim: {}", verbatim_str);
-                    let hex = format_even_bytes(verbatim_str.clone());
-                    let push_bytes = hex.to_string();
-                    *offset += hex.len() / 2;
+                    tracing::debug!(target: "codegen", "INJECTING as verbatim: {}", hex);
+                    let data = Instruction::code(hex, bf.span.clone())
+                        .unwrap_or_else(|| Instruction::data(vec![], bf.span.clone()));
+                    *offset += data.len();
+
+                    instructions.push((starting_offset, data));
+                }
+                BuiltinFunctionKind::CalldataLoadArg => {
+                    if bf.args.len() != 2 {
+                        tracing::error!(
+                            target = "codegen",
+                            "Incorrect number of arguments passed to __CALLDATA_LOAD_ARG, should be 2: {}",
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to __CALLDATA_LOAD_ARG, should be 2: {}",
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                        })
+                    }
+
+                    let index = bf.args[0].name.as_ref().unwrap();
+                    let ty = bf.args[1].name.as_ref().unwrap();
+                    let index = match usize::from_str_radix(index, 16) {
+                        Ok(index) if index <= u16::MAX as usize => index,
+                        _ => {
+                            tracing::error!(
+                                target: "codegen",
+                                "INVALID ARGUMENT INDEX PASSED TO __CALLDATA_LOAD_ARG: \"{}\"",
+                                index
+                            );
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(format!(
+                                    "Invalid argument index passed to __CALLDATA_LOAD_ARG: {index}"
+                                )),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                    };
+                    let ty = match PrimitiveEVMType::try_from(ty.clone()) {
+                        Ok(ty) if is_valid_abi_type(&ty) => ty,
+                        _ => {
+                            tracing::error!(
+                                target: "codegen",
+                                "INVALID TYPE PASSED TO __CALLDATA_LOAD_ARG: \"{}\"",
+                                ty
+                            );
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(format!(
+                                    "Invalid type passed to __CALLDATA_LOAD_ARG: {ty}"
+                                )),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                    };
+
+                    for instruction in calldata_load_arg(index, &ty, &bf.span) {
+                        *offset += instruction.len();
+                        instructions.push((starting_offset, instruction));
+                    }
+                }
+                BuiltinFunctionKind::CalldataCheck => {
+                    if bf.args.is_empty() || bf.args.len() > 2 {
+                        tracing::error!(
+                            target: "codegen",
+                            "Incorrect number of arguments passed to __CALLDATA_CHECK, should be 1 or 2: {}",
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to __CALLDATA_CHECK, should be 1 or 2: {}",
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                        })
+                    }
+
+                    let name = bf.args[0].name.as_ref().unwrap();
+                    let func = match contract.find_function(name) {
+                        Some(func) => func,
+                        None => {
+                            tracing::error!(
+                                target: "codegen",
+                                "MISSING FUNCTION INTERFACE PASSED TO __CALLDATA_CHECK: \"{}\"",
+                                name
+                            );
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::MissingFunctionInterface(name.to_string()),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                    };
+
+                    // The selector is followed by the head of the arguments, unless the minimum
+                    // size is passed, i.e. to also require the length of a dynamic argument
+                    let min_size = match bf.args.get(1).and_then(|a| a.name.as_ref()) {
+                        Some(size) => match usize::from_str_radix(size, 16) {
+                            Ok(size) if size > 0 && size <= u16::MAX as usize => size,
+                            _ => {
+                                tracing::error!(
+                                    target: "codegen",
+                                    "INVALID MINIMUM SIZE PASSED TO __CALLDATA_CHECK: \"{}\"",
+                                    size
+                                );
+                                return Err(CodegenError {
+                                    kind: CodegenErrorKind::InvalidArguments(format!(
+                                        "Invalid minimum size passed to __CALLDATA_CHECK: {size}"
+                                    )),
+                                    span: bf.span.clone(),
+                                    token: None,
+                                })
+                            }
+                        },
+                        None => {
+                            let mut size = 4;
+                            for input in &func.inputs {
+                                let ty = input.arg_type.as_deref().unwrap_or_default();
+                                match FunctionParamType::convert_string_to_type(ty) {
+                                    Ok(ty) => size += ty.head_size(),
+                                    Err(e) => {
+                                        tracing::error!(
+                                            target: "codegen",
+                                            "INVALID ARGUMENT TYPE OF FUNCTION \"{}\": {}",
+                                            func.name,
+                                            e
+                                        );
+                                        return Err(CodegenError {
+                                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                                "Invalid argument type of function {}: {ty}",
+                                                func.name
+                                            )),
+                                            span: bf.span.clone(),
+                                            token: None,
+                                        })
+                                    }
+                                }
+                            }
+                            size
+                        }
+                    };
+
+                    for instruction in
+                        calldata_check(min_size, *offset, evm_version.eof(), &bf.span).ok_or_else(
+                            || CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(format!(
+                                    "Offset of __CALLDATA_CHECK doesn't fit in 2 bytes: {offset}"
+                                )),
+                                span: bf.span.clone(),
+                                token: None,
+                            },
+                        )?
+                    {
+                        let len = instruction.len();
+                        instructions.push((*offset, instruction));
+                        *offset += len;
+                    }
+                }
+                BuiltinFunctionKind::TypeHash => {
+                    if bf.args.len() != 1 {
+                        tracing::error!(
+                            target: "codegen",
+                            "Incorrect number of arguments passed to __TYPEHASH, should be 1: {}",
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to __TYPEHASH, should be 1: {}",
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                        })
+                    }
 
-                    bytes.push((starting_offset, Bytes(push_bytes)));
+                    // Either a struct defined with `#define struct`, or the encoded type
+                    let name = bf.args[0].name.as_deref().unwrap_or_default();
+                    let encode_type = match contract.structs.iter().find(|s| s.name == name) {
+                        Some(def) => eip712::struct_type(def),
+                        None => name.to_string(),
+                    };
+                    match eip712::type_hash(&encode_type) {
+                        Ok(hash) => {
+                            let push = Instruction::push(hash.to_vec(), bf.span.clone());
+                            *offset += push.len();
+                            instructions.push((starting_offset, push));
+                        }
+                        Err(msg) => {
+                            tracing::error!(target: "codegen", "INVALID TYPE PASSED TO __TYPEHASH: {}", msg);
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(msg),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                    }
+                }
+                BuiltinFunctionKind::DomainSeparator => {
+                    if bf.args.len() < 2 || bf.args.len() > 3 {
+                        tracing::error!(
+                            target: "codegen",
+                            "Incorrect number of arguments passed to __DOMAIN_SEPARATOR, should be 2 or 3: {}",
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to __DOMAIN_SEPARATOR, should be 2 or 3: {}",
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                        })
+                    }
+
+                    // The domain is hashed in scratch memory, at the given offset or at zero
+                    let ptr = match bf.args.get(2).and_then(|a| a.name.as_ref()) {
+                        Some(ptr) => match usize::from_str_radix(ptr, 16) {
+                            Ok(ptr) if ptr <= u32::MAX as usize => ptr,
+                            _ => {
+                                tracing::error!(
+                                    target: "codegen",
+                                    "INVALID MEMORY OFFSET PASSED TO __DOMAIN_SEPARATOR: \"{}\"",
+                                    ptr
+                                );
+                                return Err(CodegenError {
+                                    kind: CodegenErrorKind::InvalidArguments(format!(
+                                        "Invalid memory offset passed to __DOMAIN_SEPARATOR: {ptr}"
+                                    )),
+                                    span: bf.span.clone(),
+                                    token: None,
+                                })
+                            }
+                        },
+                        None => 0,
+                    };
+                    let name = bf.args[0].name.as_deref().unwrap_or_default();
+                    let version = bf.args[1].name.as_deref().unwrap_or_default();
+                    for instruction in domain_separator(name, version, ptr, &bf.span) {
+                        *offset += instruction.len();
+                        instructions.push((starting_offset, instruction));
+                    }
+                }
+                BuiltinFunctionKind::Ecrecover |
+                BuiltinFunctionKind::Sha256 |
+                BuiltinFunctionKind::Modexp |
+                BuiltinFunctionKind::EcAdd |
+                BuiltinFunctionKind::EcMul |
+                BuiltinFunctionKind::EcPairing |
+                BuiltinFunctionKind::PointEvaluation => {
+                    let name = bf.kind.name();
+                    if bf.args.len() > 1 {
+                        tracing::error!(
+                            target: "codegen",
+                            "Incorrect number of arguments passed to {}, should be 0 or 1: {}",
+                            name,
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to {name}, should be 0 or 1: {}",
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                        })
+                    }
+
+                    // The inputs are laid out in scratch memory, at the given offset or at zero
+                    let ptr = match bf.args.first().and_then(|a| a.name.as_ref()) {
+                        Some(ptr) => match usize::from_str_radix(ptr, 16) {
+                            Ok(ptr) if ptr <= u32::MAX as usize => ptr,
+                            _ => {
+                                tracing::error!(
+                                    target: "codegen",
+                                    "INVALID MEMORY OFFSET PASSED TO {}: \"{}\"",
+                                    name,
+                                    ptr
+                                );
+                                return Err(CodegenError {
+                                    kind: CodegenErrorKind::InvalidArguments(format!(
+                                        "Invalid memory offset passed to {name}: {ptr}"
+                                    )),
+                                    span: bf.span.clone(),
+                                    token: None,
+                                })
+                            }
+                        },
+                        None => 0,
+                    };
+                    let precompile = match bf.kind.precompile() {
+                        Some(p) if evm_version.version() < p.introduced_in() => {
+                            tracing::error!(target: "codegen", "PRECOMPILE \"{}\" UNAVAILABLE IN EVM VERSION \"{}\"", p, evm_version);
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::UnsupportedPrecompile(p, *evm_version),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                        // Precompiles are called with `staticcall`, from Byzantium on
+                        Some(_) if !evm_version.supports(&Opcode::Staticcall) => {
+                            tracing::error!(target: "codegen", "OPCODE \"{:?}\" UNAVAILABLE IN EVM VERSION \"{}\"", Opcode::Staticcall, evm_version);
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::UnsupportedOpcode(
+                                    Opcode::Staticcall,
+                                    *evm_version,
+                                ),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                        Some(p) => p,
+                        None => {
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(format!(
+                                    "{name} doesn't call a precompile"
+                                )),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                    };
+
+                    for instruction in
+                        precompile_call(precompile, ptr, evm_version, *offset, &bf.span)
+                            .ok_or_else(|| CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(format!(
+                                    "Offset of {name} doesn't fit in 2 bytes: {offset}"
+                                )),
+                                span: bf.span.clone(),
+                                token: None,
+                            })?
+                    {
+                        let len = instruction.len();
+                        instructions.push((*offset, instruction));
+                        *offset += len;
+                    }
+                }
+                BuiltinFunctionKind::Erc7201 |
+                BuiltinFunctionKind::Eip1967ImplSlot |
+                BuiltinFunctionKind::Eip1967AdminSlot |
+                BuiltinFunctionKind::Eip1967BeaconSlot => {
+                    let args = bf
+                        .args
+                        .iter()
+                        .map(|a| a.name.as_deref().unwrap_or_default())
+                        .collect::<Vec<&str>>();
+                    let slot = bf.kind.storage_slot(&args).unwrap_or_else(|| {
+                        Err(format!("{} doesn't compute a storage slot", bf.kind.name()))
+                    });
+                    match slot {
+                        Ok(slot) => {
+                            let push = Instruction::push(slot.to_vec(), bf.span.clone());
+                            *offset += push.len();
+                            instructions.push((starting_offset, push));
+                        }
+                        Err(msg) => {
+                            tracing::error!(target: "codegen", "INVALID STORAGE SLOT BUILTIN: {}", msg);
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(msg),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                    }
+                }
+                BuiltinFunctionKind::StrLit => {
+                    if bf.args.is_empty() || bf.args.len() > 2 {
+                        tracing::error!(
+                            target: "codegen",
+                            "Incorrect number of arguments passed to __STR_LIT, should be 1 or 2: {}",
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to __STR_LIT, should be 1 or 2: {}",
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                        })
+                    }
+
+                    // Hex literals and identifiers are names too, only quoted strings are pushed
+                    let string = match &bf.args[0] {
+                        Argument { arg_type: Some(ty), name: Some(string), .. }
+                            if ty == "string" =>
+                        {
+                            string.as_str()
+                        }
+                        arg => {
+                            let arg = arg.name.as_deref().unwrap_or_default();
+                            tracing::error!(
+                                target: "codegen",
+                                "NON STRING ARGUMENT PASSED TO __STR_LIT: \"{}\"",
+                                arg
+                            );
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(format!(
+                                    "__STR_LIT expects a string literal, got \"{arg}\""
+                                )),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                    };
+                    if !string.is_ascii() || string.len() > 32 {
+                        tracing::error!(
+                            target: "codegen",
+                            "INVALID STRING PASSED TO __STR_LIT: \"{}\"",
+                            string
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "String passed to __STR_LIT must be at most 32 ASCII characters: \"{string}\""
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                        })
+                    }
+
+                    // Strings are left aligned like `bytes32` values unless aligned to the right
+                    let push = match bf.args.get(1).and_then(|a| a.name.as_deref()) {
+                        None | Some("left") => {
+                            let mut padded = string.as_bytes().to_vec();
+                            padded.resize(32, 0);
+                            Instruction::push(padded, bf.span.clone())
+                        }
+                        Some("right") => {
+                            Instruction::push_value(string.as_bytes(), bf.span.clone())
+                        }
+                        Some(alignment) => {
+                            tracing::error!(
+                                target: "codegen",
+                                "INVALID ALIGNMENT PASSED TO __STR_LIT: \"{}\"",
+                                alignment
+                            );
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(format!(
+                                    "Invalid alignment passed to __STR_LIT, should be left or right: {alignment}"
+                                )),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                    };
+                    *offset += push.len();
+                    instructions.push((starting_offset, push));
+                }
+                BuiltinFunctionKind::Pc => {
+                    if !bf.args.is_empty() {
+                        tracing::error!(
+                            target: "codegen",
+                            "Incorrect number of arguments passed to __PC, should be 0: {}",
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to __PC, should be 0: {}",
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                        })
+                    }
+
+                    // The offset is always pushed as 2 bytes, like labels, so the size of the
+                    // code doesn't depend on where it's expanded
+                    let pc = crate::be_bytes(*offset, 2).ok_or_else(|| CodegenError {
+                        kind: CodegenErrorKind::InvalidArguments(format!(
+                            "Offset passed to __PC doesn't fit in 2 bytes: {offset}"
+                        )),
+                        span: bf.span.clone(),
+                        token: None,
+                    })?;
+                    let push = Instruction::push(pc, bf.span.clone());
+                    *offset += push.len();
+                    instructions.push((starting_offset, push));
+                }
+                BuiltinFunctionKind::Offset => {
+                    let label = match bf.args.as_slice() {
+                        [arg] if arg.name.is_some() => arg.name.as_ref().unwrap(),
+                        _ => {
+                            tracing::error!(
+                                target: "codegen",
+                                "Incorrect number of arguments passed to __OFFSET, should be 1: {}",
+                                bf.args.len()
+                            );
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(format!(
+                                    "Incorrect number of arguments passed to __OFFSET, should be 1: {}",
+                                    bf.args.len()
+                                )),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                    };
+
+                    // Pushed like a label call, and resolved the same way, but not to be jumped to
+                    relocations.push(Relocation::label(label, *offset, bf.span.clone()));
+                    instructions.push((*offset, Instruction::push_placeholder(2, bf.span.clone())));
+                    *offset += 3;
+                }
+                BuiltinFunctionKind::CodeSlice => {
+                    let (start, end) = match bf.args.as_slice() {
+                        [start, end] if start.name.is_some() && end.name.is_some() => {
+                            (start.name.as_ref().unwrap(), end.name.as_ref().unwrap())
+                        }
+                        _ => {
+                            tracing::error!(
+                                target: "codegen",
+                                "Incorrect number of arguments passed to __CODE_SLICE, should be 2: {}",
+                                bf.args.len()
+                            );
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(format!(
+                                    "Incorrect number of arguments passed to __CODE_SLICE, should be 2: {}",
+                                    bf.args.len()
+                                )),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                    };
+
+                    // The size is the offset of the end label minus the offset of the start label,
+                    // both resolved like label calls once the code is laid out
+                    relocations.push(Relocation::label(end, *offset, bf.span.clone()));
+                    relocations.push(Relocation {
+                        negated: true,
+                        ..Relocation::label(start, *offset, bf.span.clone())
+                    });
+                    instructions.push((*offset, Instruction::push_placeholder(2, bf.span.clone())));
+                    relocations.push(Relocation::label(start, *offset + 3, bf.span.clone()));
+                    instructions
+                        .push((*offset + 3, Instruction::push_placeholder(2, bf.span.clone())));
+                    *offset += 6;
+                }
+                BuiltinFunctionKind::DataOffset | BuiltinFunctionKind::DataSize => {
+                    let name = match bf.args.as_slice() {
+                        [arg] if arg.name.is_some() => arg.name.as_ref().unwrap(),
+                        _ => {
+                            tracing::error!(
+                                target: "codegen",
+                                "Incorrect number of arguments passed to {}, should be 1: {}",
+                                bf.kind.name(),
+                                bf.args.len()
+                            );
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(format!(
+                                    "Incorrect number of arguments passed to {}, should be 1: {}",
+                                    bf.kind.name(),
+                                    bf.args.len()
+                                )),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                    };
+                    let data = match contract.find_table_by_name(name) {
+                        Some(t) if t.kind == TableKind::Data => t,
+                        _ => {
+                            tracing::error!(
+                                target: "codegen",
+                                "MISSING DATA SECTION PASSED TO {} \"{}\"",
+                                bf.kind.name(),
+                                name
+                            );
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::MissingDataDefinition(name.to_string()),
+                                span: bf.span.clone(),
+                                token: None,
+                            })
+                        }
+                    };
+
+                    // Data sections are appended after the code along with the tables in use
+                    if !utilized_tables.contains(data) {
+                        utilized_tables.push(data.clone());
+                    }
+                    if bf.kind == BuiltinFunctionKind::DataOffset {
+                        relocations.push(Relocation::table(name, *offset, bf.span.clone()));
+                        instructions
+                            .push((*offset, Instruction::push_placeholder(2, bf.span.clone())));
+                        *offset += 3;
+                    } else {
+                        let push = Instruction::push_value(&data.size, bf.span.clone());
+                        *offset += push.len();
+                        instructions.push((starting_offset, push));
+                    }
+                }
+                // Gas budgets generate no code, they are validated before generating bytecode
+                BuiltinFunctionKind::GasLimit => {}
+                BuiltinFunctionKind::Assert => {
+                    // Assertions are parsed as `StatementType::Assertion`, not as builtin calls
+                    tracing::error!(target: "codegen", "UNEXPECTED ASSERTION BUILTIN CALL");
+                    return Err(CodegenError {
+                        kind: CodegenErrorKind::InvalidMacroStatement,
+                        span: bf.span.clone(),
+                        token: None,
+                    })
                 }
             }
         }
@@ -580,5 +1241,365 @@ pub fn statement_gen(
         }
     }
 
-    Ok(bytes)
+    Ok(StatementGen::Instructions(instructions))
+}
+
+/// Whether a primitive type has a valid size for ABI encoding
+fn is_valid_abi_type(ty: &PrimitiveEVMType) -> bool {
+    match ty {
+        PrimitiveEVMType::Uint(size) | PrimitiveEVMType::Int(size) => {
+            (8..=256).contains(size) && size % 8 == 0
+        }
+        PrimitiveEVMType::Bytes(size) => (1..=32).contains(size),
+        _ => true,
+    }
+}
+
+/// The instructions loading the `index`th argument of type `ty` from ABI-encoded calldata
+///
+/// The value is cleaned to its type: unsigned integers and addresses are masked, signed integers
+/// are sign extended, the trailing bytes of fixed bytes are cleared and bools are normalized to
+/// `0` or `1`. For `bytes` and `string`, the calldata offset of the argument's length is loaded
+/// instead.
+pub fn calldata_load_arg(index: usize, ty: &PrimitiveEVMType, span: &AstSpan) -> Vec<Instruction> {
+    let mut instructions = vec![
+        // Arguments start after the 4 byte function selector
+        Instruction::push_value(&(4 + 32 * index).to_be_bytes(), span.clone()),
+        Instruction::op(Opcode::Calldataload, span.clone()),
+    ];
+    match *ty {
+        PrimitiveEVMType::Uint(256) | PrimitiveEVMType::Int(256) | PrimitiveEVMType::Bytes(32) => {}
+        PrimitiveEVMType::Uint(size) => {
+            instructions.push(Instruction::push_value(&vec![0xff; size / 8], span.clone()));
+            instructions.push(Instruction::op(Opcode::And, span.clone()));
+        }
+        PrimitiveEVMType::Address => {
+            instructions.push(Instruction::push_value(&[0xff; 20], span.clone()));
+            instructions.push(Instruction::op(Opcode::And, span.clone()));
+        }
+        PrimitiveEVMType::Int(size) => {
+            instructions.push(Instruction::push_value(&[(size / 8 - 1) as u8], span.clone()));
+            instructions.push(Instruction::op(Opcode::Signextend, span.clone()));
+        }
+        PrimitiveEVMType::Bytes(size) => {
+            // Values are left aligned, so the trailing bytes are masked out with the complement
+            // of a mask over them
+            instructions.push(Instruction::push_value(&vec![0xff; 32 - size], span.clone()));
+            instructions.push(Instruction::op(Opcode::Not, span.clone()));
+            instructions.push(Instruction::op(Opcode::And, span.clone()));
+        }
+        PrimitiveEVMType::Bool => {
+            instructions.push(Instruction::op(Opcode::Iszero, span.clone()));
+            instructions.push(Instruction::op(Opcode::Iszero, span.clone()));
+        }
+        PrimitiveEVMType::String | PrimitiveEVMType::DynBytes => {
+            // The encoded offset is relative to the start of the arguments
+            instructions.push(Instruction::push_value(&[4], span.clone()));
+            instructions.push(Instruction::op(Opcode::Add, span.clone()));
+        }
+    }
+    instructions
+}
+
+/// The instructions pushing the EIP-712 domain separator of the executing contract, hashing the
+/// domain in the `0xa0` bytes of memory at `ptr`
+///
+/// The name and version are hashed at compile time, while the chain id and address are read at
+/// runtime, so the separator stays valid after a fork or when the code runs behind a proxy.
+pub fn domain_separator(name: &str, version: &str, ptr: usize, span: &AstSpan) -> Vec<Instruction> {
+    let hash = |s: &str| {
+        let mut hash = [0u8; 32];
+        hash_bytes(&mut hash, &s.to_string());
+        Instruction::push(hash.to_vec(), span.clone())
+    };
+    let fields = [
+        hash(eip712::DOMAIN_TYPE),
+        hash(name),
+        hash(version),
+        Instruction::op(Opcode::Chainid, span.clone()),
+        Instruction::op(Opcode::Address, span.clone()),
+    ];
+    let mut instructions = vec![];
+    for (i, field) in fields.into_iter().enumerate() {
+        instructions.push(field);
+        instructions.push(Instruction::push_value(&(ptr + 32 * i).to_be_bytes(), span.clone()));
+        instructions.push(Instruction::op(Opcode::Mstore, span.clone()));
+    }
+    instructions.push(Instruction::push_value(&[0xa0], span.clone()));
+    instructions.push(Instruction::push_value(&ptr.to_be_bytes(), span.clone()));
+    instructions.push(Instruction::op(Opcode::Sha3, span.clone()));
+    instructions
+}
+
+/// The instructions calling a precompile with its inputs on the stack, laid out in the memory at
+/// `ptr`, expanded at `offset`
+///
+/// Each call is given the exact gas it costs in the targeted hard fork, computed from the size of
+/// the input for sha256 and the pairing check. Calls which can't succeed without a valid output
+/// revert when they fail, while ecrecover pushes zero for an invalid signature and the pairing
+/// and point evaluation checks push whether they hold. Returns `None` if the offset past the
+/// revert doesn't fit in 2 bytes.
+pub fn precompile_call(
+    precompile: Precompile,
+    ptr: usize,
+    evm_version: &EVMVersion,
+    offset: usize,
+    span: &AstSpan,
+) -> Option<Vec<Instruction>> {
+    let push = |value: usize| Instruction::push_value(&value.to_be_bytes(), span.clone());
+    let op = |opcode: Opcode| Instruction::op(opcode, span.clone());
+    // Stores the top `n` stack items in consecutive words, the top one first
+    let store = |instructions: &mut Vec<Instruction>, at: usize, n: usize| {
+        for i in 0..n {
+            instructions.push(push(at + 32 * i));
+            instructions.push(op(Opcode::Mstore));
+        }
+    };
+    let (base_gas, unit_gas) = precompile.gas(evm_version.version());
+    let gas = Instruction::push_value(&base_gas.to_be_bytes(), span.clone());
+    let address = push(precompile.address() as usize);
+
+    let mut instructions = vec![];
+    let (input_size, output_size) = match precompile {
+        // [hash, v, r, s] -> [signer]
+        Precompile::Ecrecover => {
+            store(&mut instructions, ptr, 4);
+            (0x80, 0x20)
+        }
+        // [base, exponent, modulus] -> [result], following the lengths of the operands
+        Precompile::Modexp => {
+            store(&mut instructions, ptr + 0x60, 3);
+            instructions.extend([push(0x20), op(Opcode::Dup1), op(Opcode::Dup1)]);
+            store(&mut instructions, ptr, 3);
+            (0xc0, 0x20)
+        }
+        // [x1, y1, x2, y2] -> [x, y]
+        Precompile::EcAdd => {
+            store(&mut instructions, ptr, 4);
+            (0x80, 0x40)
+        }
+        // [x, y, scalar] -> [x, y]
+        Precompile::EcMul => {
+            store(&mut instructions, ptr, 3);
+            (0x60, 0x40)
+        }
+        // [versioned_hash, z, y, offset] -> [valid], where the commitment and the proof are the
+        // 96 bytes of memory at `offset`
+        Precompile::PointEvaluation => {
+            store(&mut instructions, ptr, 3);
+            instructions.extend([
+                push(0x60),
+                op(Opcode::Swap1),
+                push(ptr + 0x60),
+                op(Opcode::Mcopy),
+            ]);
+            (0xc0, 0)
+        }
+        // [offset, size] -> [hash] or [valid], of the memory at `offset`
+        Precompile::Sha256 | Precompile::EcPairing => {
+            // [retSize, retOffset] are moved below [argsOffset, argsSize]
+            instructions.extend([
+                push(0x20),
+                push(ptr),
+                op(Opcode::Swap2),
+                op(Opcode::Swap1),
+                op(Opcode::Swap3),
+                op(Opcode::Swap1),
+                address,
+            ]);
+            // The gas grows with the words hashed, or the pairs of points checked
+            instructions.push(op(Opcode::Dup3));
+            match precompile {
+                Precompile::Sha256 => {
+                    instructions.extend([push(0x1f), op(Opcode::Add), push(0x20)])
+                }
+                _ => instructions.push(push(0xc0)),
+            }
+            instructions.extend([op(Opcode::Swap1), op(Opcode::Div)]);
+            instructions.extend([
+                Instruction::push_value(&unit_gas.to_be_bytes(), span.clone()),
+                op(Opcode::Mul),
+                gas,
+                op(Opcode::Add),
+                op(Opcode::Staticcall),
+            ]);
+            let mut instructions =
+                revert_unless(instructions, revert(None, span), offset, evm_version.eof(), span)?;
+            instructions.extend([push(ptr), op(Opcode::Mload)]);
+            return Some(instructions)
+        }
+    };
+    instructions.extend([push(output_size), push(ptr), push(input_size), push(ptr), address, gas]);
+    instructions.push(op(Opcode::Staticcall));
+    match precompile {
+        // An invalid signature returns no data, leaving the input in memory
+        Precompile::Ecrecover => instructions.extend([
+            op(Opcode::Pop),
+            push(0x20),
+            op(Opcode::Returndatasize),
+            op(Opcode::Div),
+            push(ptr),
+            op(Opcode::Mload),
+            op(Opcode::Mul),
+        ]),
+        Precompile::PointEvaluation => {}
+        _ => {
+            instructions =
+                revert_unless(instructions, revert(None, span), offset, evm_version.eof(), span)?;
+            if output_size == 0x40 {
+                instructions.extend([push(ptr + 0x20), op(Opcode::Mload)]);
+            }
+            instructions.extend([push(ptr), op(Opcode::Mload)]);
+        }
+    }
+    Some(instructions)
+}
+
+/// The instructions reverting unless the calldata is at least `min_size` bytes, expanded at
+/// `offset`
+///
+/// Returns `None` if the offset past the revert doesn't fit in 2 bytes.
+pub fn calldata_check(
+    min_size: usize,
+    offset: usize,
+    eof: bool,
+    span: &AstSpan,
+) -> Option<Vec<Instruction>> {
+    let condition = vec![
+        // `calldatasize > min_size - 1`, as there's no greater or equal comparison
+        Instruction::push_value(&(min_size - 1).to_be_bytes(), span.clone()),
+        Instruction::op(Opcode::Calldatasize, span.clone()),
+        Instruction::op(Opcode::Gt, span.clone()),
+    ];
+    revert_unless(condition, revert(None, span), offset, eof, span)
+}
+
+/// The instructions reverting unless the caller is the address pushed by `owner`, expanded at
+/// `offset`, with the selector of a custom error if one is given
+///
+/// Returns `None` if the offset past the revert doesn't fit in 2 bytes.
+pub fn caller_check(
+    mut owner: Vec<Instruction>,
+    error: Option<[u8; 4]>,
+    offset: usize,
+    eof: bool,
+    span: &AstSpan,
+) -> Option<Vec<Instruction>> {
+    owner.push(Instruction::op(Opcode::Caller, span.clone()));
+    owner.push(Instruction::op(Opcode::Eq, span.clone()));
+    revert_unless(owner, revert(error, span), offset, eof, span)
+}
+
+/// The instructions locking the reentrancy guard whose slot `slot` pushes, expanded at `offset`,
+/// reverting if it's already locked
+///
+/// The lock is kept in transient storage if the targeted hard fork supports it, otherwise in
+/// storage. Returns `None` if the offset past the revert doesn't fit in 2 bytes.
+pub fn reentrancy_lock(
+    evm_version: &EVMVersion,
+    slot: Instruction,
+    offset: usize,
+    span: &AstSpan,
+) -> Option<Vec<Instruction>> {
+    let (load, store) = lock_opcodes(evm_version);
+    let condition = vec![
+        slot.clone(),
+        Instruction::op(load, span.clone()),
+        Instruction::op(Opcode::Iszero, span.clone()),
+    ];
+    let mut instructions =
+        revert_unless(condition, revert(None, span), offset, evm_version.eof(), span)?;
+    instructions.push(Instruction::push_value(&[1], span.clone()));
+    instructions.push(slot);
+    instructions.push(Instruction::op(store, span.clone()));
+    Some(instructions)
+}
+
+/// The instructions unlocking the reentrancy guard whose slot `slot` pushes
+pub fn reentrancy_unlock(
+    evm_version: &EVMVersion,
+    slot: Instruction,
+    span: &AstSpan,
+) -> Vec<Instruction> {
+    let (_, store) = lock_opcodes(evm_version);
+    vec![Instruction::push_value(&[0], span.clone()), slot, Instruction::op(store, span.clone())]
+}
+
+/// The opcodes loading and storing the reentrancy lock, transient storage's if available
+fn lock_opcodes(evm_version: &EVMVersion) -> (Opcode, Opcode) {
+    if evm_version.supports(&Opcode::TStore) {
+        (Opcode::TLoad, Opcode::TStore)
+    } else {
+        (Opcode::Sload, Opcode::Sstore)
+    }
+}
+
+/// The instructions reverting with the selector of a custom error, or without data
+fn revert(error: Option<[u8; 4]>, span: &AstSpan) -> Vec<Instruction> {
+    match error {
+        // The selector is stored right aligned in the word at 0, so it starts at 0x1c
+        Some(selector) => vec![
+            Instruction::push(selector.to_vec(), span.clone()),
+            Instruction::push_value(&[0], span.clone()),
+            Instruction::op(Opcode::Mstore, span.clone()),
+            Instruction::push_value(&[4], span.clone()),
+            Instruction::push_value(&[0x1c], span.clone()),
+            Instruction::op(Opcode::Revert, span.clone()),
+        ],
+        None => vec![
+            Instruction::push_value(&[0], span.clone()),
+            Instruction::op(Opcode::Dup1, span.clone()),
+            Instruction::op(Opcode::Revert, span.clone()),
+        ],
+    }
+}
+
+/// Appends to the instructions pushing a condition, expanded at `offset`, a jump over the revert
+/// when the condition holds, a relative `rjumpi` for EOF code
+///
+/// Returns `None` if the offset past the revert doesn't fit in 2 bytes.
+fn revert_unless(
+    mut instructions: Vec<Instruction>,
+    revert: Vec<Instruction>,
+    offset: usize,
+    eof: bool,
+    span: &AstSpan,
+) -> Option<Vec<Instruction>> {
+    let revert_size = revert.iter().map(|i| i.len()).sum::<usize>();
+    if eof {
+        instructions.push(Instruction {
+            immediate: (revert_size as u16).to_be_bytes().to_vec(),
+            ..Instruction::op(Opcode::Rjumpi, span.clone())
+        });
+        instructions.extend(revert);
+    } else {
+        // The `JUMPDEST` follows the 4 bytes of the `PUSH2` and `JUMPI`, then the revert
+        let size = instructions.iter().map(|i| i.len()).sum::<usize>();
+        let destination = offset + size + 4 + revert_size;
+        instructions.push(Instruction::push(crate::be_bytes(destination, 2)?, span.clone()));
+        instructions.push(Instruction::op(Opcode::Jumpi, span.clone()));
+        instructions.extend(revert);
+        instructions.push(Instruction::op(Opcode::Jumpdest, span.clone()));
+    }
+    Some(instructions)
+}
+
+/// The `SWAP1` to `SWAPn` opcodes used to move a value below the top `n` stack items of an
+/// outlined macro
+pub fn stack_swaps(n: usize, macro_def: &MacroDefinition) -> Result<Vec<Opcode>, CodegenError> {
+    (1..=n)
+        .map(|i| {
+            Opcode::swap_n(i).ok_or_else(|| {
+                tracing::error!(target: "codegen", "OUTLINED MACRO \"{}\" HAS TOO MANY STACK ITEMS TO SWAP: {}", macro_def.name, n);
+                CodegenError {
+                    kind: CodegenErrorKind::InvalidArguments(format!(
+                        "Outlined macro \"{}\" can't swap {n} stack items, the maximum is 16",
+                        macro_def.name
+                    )),
+                    span: macro_def.span.clone(),
+                    token: None,
+                }
+            })
+        })
+        .collect()
 }
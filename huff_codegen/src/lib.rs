@@ -10,10 +10,14 @@ use huff_utils::{
     ast::*,
     bytecode::*,
     bytes_util,
+    eof::{EofContainer, EofFunction, MAX_SECTION_SIZE},
     error::CodegenError,
     evm::Opcode,
-    prelude::{format_even_bytes, pad_n_bytes, CodegenErrorKind, FileSource, Span},
+    evm_version::EVMVersion,
+    symbol::Symbol,
+    prelude::{pad_n_bytes, CodegenErrorKind, FileSource, Span},
     types::EToken,
+    warning::CompilerWarning,
 };
 use regex::Regex;
 use std::{cmp::Ordering, collections::HashMap, fs, path::Path, sync::Arc};
@@ -21,6 +25,13 @@ use std::{cmp::Ordering, collections::HashMap, fs, path::Path, sync::Arc};
 mod irgen;
 use crate::irgen::prelude::*;
 
+/// Static Analysis Module
+pub mod analysis;
+use crate::analysis::prelude::*;
+
+/// The default maximum number of macros that may be expanded within one another
+pub const DEFAULT_MACRO_NESTING_LIMIT: usize = 1024;
+
 /// ### Codegen
 ///
 /// Code Generation Manager responsible for generating bytecode from a
@@ -56,37 +67,161 @@ impl Codegen {
 
     /// Generates main bytecode from a Contract AST
     pub fn generate_main_bytecode(
+        evm_version: &EVMVersion,
+        contract: &Contract,
+        alternative_main: Option<String>,
+    ) -> Result<String, CodegenError> {
+        Codegen::generate_main_bytecode_with_limit(
+            evm_version,
+            contract,
+            alternative_main,
+            DEFAULT_MACRO_NESTING_LIMIT,
+        )
+    }
+
+    /// Generates main bytecode from a Contract AST, erroring if macros are nested deeper than
+    /// `nesting_limit`
+    pub fn generate_main_bytecode_with_limit(
+        evm_version: &EVMVersion,
         contract: &Contract,
         alternative_main: Option<String>,
+        nesting_limit: usize,
     ) -> Result<String, CodegenError> {
+        Codegen::generate_main_bytecode_with_symbols(
+            evm_version,
+            contract,
+            alternative_main,
+            nesting_limit,
+        )
+        .map(|(bytecode, _)| bytecode)
+    }
+
+    /// Generates main bytecode from a Contract AST along with the bytecode generated by each
+    /// macro invocation, ordered by offset with parents before their children
+    pub fn generate_main_bytecode_with_symbols(
+        evm_version: &EVMVersion,
+        contract: &Contract,
+        alternative_main: Option<String>,
+        nesting_limit: usize,
+    ) -> Result<(String, Vec<MacroActivation>), CodegenError> {
+        Codegen::generate_main_sections(evm_version, contract, alternative_main, nesting_limit)
+            .map(|(sections, activations)| (sections.to_string(), activations))
+    }
+
+    /// Generates main bytecode from a Contract AST split into its code and the tables appended to
+    /// it, along with the bytecode generated by each macro invocation.
+    ///
+    /// When generating EOF code, table offsets point into the data section of an EOF container
+    /// holding the sections rather than past the end of the code, and functions are generated
+    /// into code sections of their own.
+    pub fn generate_main_sections(
+        evm_version: &EVMVersion,
+        contract: &Contract,
+        alternative_main: Option<String>,
+        nesting_limit: usize,
+    ) -> Result<(BytecodeSections, Vec<MacroActivation>), CodegenError> {
+        let mut bytecode_res =
+            Codegen::main_bytecode_res(evm_version, contract, alternative_main, nesting_limit)?;
+
+        tracing::debug!(target: "codegen", "Generated main bytecode. Appending table bytecode...");
+
+        let mut activations = std::mem::take(&mut bytecode_res.activations);
+        activations.sort_by_key(|a| (a.start, std::cmp::Reverse(a.end)));
+
+        // Generate the fully baked bytecode
+        Ok((Codegen::gen_sections(bytecode_res, evm_version.eof())?, activations))
+    }
+
+    /// Generates the control flow graph of the main bytecode of a Contract AST
+    pub fn generate_main_cfg(
+        evm_version: &EVMVersion,
+        contract: &Contract,
+        alternative_main: Option<String>,
+        nesting_limit: usize,
+    ) -> Result<ControlFlowGraph, CodegenError> {
+        let main_macro = alternative_main.clone().unwrap_or_else(|| String::from("MAIN"));
+        let mut bytecode_res =
+            Codegen::main_bytecode_res(evm_version, contract, alternative_main, nesting_limit)?;
+        let activations = std::mem::take(&mut bytecode_res.activations);
+
+        // Fail on unmatched jumps and tables like the bytecode would
+        Codegen::gen_table_bytecode(bytecode_res.clone())?;
+        Codegen::apply_relocations(&mut bytecode_res.instructions, &bytecode_res.relocations)?;
+        Ok(ControlFlowGraph::new(&main_macro, &bytecode_res, activations))
+    }
+
+    /// Validates the main macro and generates its bytecode, without the tables
+    fn main_bytecode_res(
+        evm_version: &EVMVersion,
+        contract: &Contract,
+        alternative_main: Option<String>,
+        nesting_limit: usize,
+    ) -> Result<BytecodeRes, CodegenError> {
         // If an alternative main is provided, then use it as the compilation target
         let main_macro = alternative_main.unwrap_or_else(|| String::from("MAIN"));
 
         // Find the main macro
         let m_macro = Codegen::get_macro_by_name(&main_macro, contract)?;
 
+        // Make sure no path through the macro can overflow the stack
+        validate_stack_depth(contract, m_macro)?;
+
+        // Make sure the macros with a gas budget stay within it
+        validate_gas_limits(evm_version, contract, nesting_limit)?;
+
         // For each MacroInvocation Statement, recurse into bytecode
-        let bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
-            m_macro.clone(),
+        Codegen::macro_to_bytecode(
+            evm_version,
+            m_macro,
             contract,
             &mut vec![m_macro],
             0,
             &mut Vec::default(),
             false,
             None,
-        )?;
-
-        tracing::debug!(target: "codegen", "Generated main bytecode. Appending table bytecode...");
-
-        // Generate the fully baked bytecode
-        Codegen::gen_table_bytecode(bytecode_res)
+            nesting_limit,
+        )
     }
 
     /// Generates constructor bytecode from a Contract AST
     pub fn generate_constructor_bytecode(
+        evm_version: &EVMVersion,
+        contract: &Contract,
+        alternative_constructor: Option<String>,
+    ) -> Result<(String, bool), CodegenError> {
+        Codegen::generate_constructor_bytecode_with_limit(
+            evm_version,
+            contract,
+            alternative_constructor,
+            DEFAULT_MACRO_NESTING_LIMIT,
+        )
+    }
+
+    /// Generates constructor bytecode from a Contract AST, erroring if macros are nested deeper
+    /// than `nesting_limit`
+    pub fn generate_constructor_bytecode_with_limit(
+        evm_version: &EVMVersion,
         contract: &Contract,
         alternative_constructor: Option<String>,
+        nesting_limit: usize,
     ) -> Result<(String, bool), CodegenError> {
+        Codegen::generate_constructor_sections(
+            evm_version,
+            contract,
+            alternative_constructor,
+            nesting_limit,
+        )
+        .map(|(sections, has_custom_bootstrap)| (sections.to_string(), has_custom_bootstrap))
+    }
+
+    /// Generates constructor bytecode from a Contract AST split into its code and the tables
+    /// appended to it, along with whether the constructor returns its own runtime code
+    pub fn generate_constructor_sections(
+        evm_version: &EVMVersion,
+        contract: &Contract,
+        alternative_constructor: Option<String>,
+        nesting_limit: usize,
+    ) -> Result<(BytecodeSections, bool), CodegenError> {
         // If an alternative constructor macro is provided, then use it as the compilation target
         let constructor_macro =
             alternative_constructor.unwrap_or_else(|| String::from("CONSTRUCTOR"));
@@ -94,32 +229,38 @@ impl Codegen {
         // Find the constructor macro
         let c_macro = Codegen::get_macro_by_name(&constructor_macro, contract)?;
 
+        // Make sure no path through the macro can overflow the stack
+        validate_stack_depth(contract, c_macro)?;
+
         // For each MacroInvocation Statement, recurse into bytecode
         let bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
-            c_macro.clone(),
+            evm_version,
+            c_macro,
             contract,
             &mut vec![c_macro],
             0,
             &mut Vec::default(),
             false,
             None,
+            nesting_limit,
         )?;
 
         // Check if the constructor performs its own code generation
-        let has_custom_bootstrap = bytecode_res.bytes.iter().any(|bytes| bytes.1 .0 == *"f3");
+        let has_custom_bootstrap =
+            bytecode_res.instructions.iter().any(|(_, i)| i.opcode == Some(Opcode::Return));
 
         tracing::info!(target: "codegen", "Constructor is self-generating: {}", has_custom_bootstrap);
 
-        let bytecode = Codegen::gen_table_bytecode(bytecode_res)?;
+        let sections = Codegen::gen_sections(bytecode_res, evm_version.eof())?;
 
-        Ok((bytecode, has_custom_bootstrap))
+        Ok((sections, has_custom_bootstrap))
     }
 
     /// Helper function to find a macro or generate a CodegenError
-    pub(crate) fn get_macro_by_name(
+    pub(crate) fn get_macro_by_name<'a>(
         name: &str,
-        contract: &Contract,
-    ) -> Result<MacroDefinition, CodegenError> {
+        contract: &'a Contract,
+    ) -> Result<&'a MacroDefinition, CodegenError> {
         if let Some(m) = contract.find_macro_by_name(name) {
             Ok(m)
         } else {
@@ -132,22 +273,77 @@ impl Codegen {
         }
     }
 
+    /// Helper function to check that all opcodes within a list of statements are available in the
+    /// targeted EVM version, generating a CodegenError for the first one that is not.
+    pub(crate) fn validate_opcodes(
+        evm_version: &EVMVersion,
+        statements: &[Statement],
+    ) -> Result<(), CodegenError> {
+        for statement in statements {
+            match &statement.ty {
+                StatementType::Opcode(o) if !evm_version.supports(o) => {
+                    tracing::error!(target: "codegen", "OPCODE \"{:?}\" UNAVAILABLE IN EVM VERSION \"{}\"", o, evm_version);
+                    return Err(CodegenError {
+                        kind: CodegenErrorKind::UnsupportedOpcode(*o, *evm_version),
+                        span: statement.span.clone(),
+                        token: None,
+                    })
+                }
+                StatementType::Label(l) => Codegen::validate_opcodes(evm_version, &l.inner)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs static analysis over every macro in a Contract AST, collecting any warnings.
+    pub fn analyze(contract: &Contract) -> Vec<CompilerWarning> {
+        Codegen::analyze_with_main(contract, "MAIN")
+    }
+
+    /// Runs static analysis over every macro in a Contract AST, checking the dispatch of the given
+    /// main macro, collecting any warnings not silenced by a suppression comment.
+    pub fn analyze_with_main(contract: &Contract, main: &str) -> Vec<CompilerWarning> {
+        let mut warnings: Vec<CompilerWarning> =
+            contract.macros.iter().flat_map(|m| unreachable_code(contract, m)).collect();
+        warnings.extend(contract.macros.iter().flat_map(|m| dead_stores(contract, m)));
+        warnings.extend(contract.macros.iter().filter_map(|m| mismatched_outputs(contract, m)));
+        warnings.extend(contract.macros.iter().flat_map(|m| growing_loops(contract, m)));
+        warnings.extend(contract.macros.iter().flat_map(unchecked_calls));
+        if let Some(m) = contract.macros.iter().find(|m| m.name.eq(main)) {
+            warnings.extend(missing_fallthrough(contract, m));
+        }
+        warnings.retain(|w| !contract.suppressions.iter().any(|s| s.silences(w)));
+        warnings
+    }
+
     /// Appends table bytecode to the end of the BytecodeRes output.
     /// Fills table JUMPDEST placeholders.
     pub fn gen_table_bytecode(res: BytecodeRes) -> Result<String, CodegenError> {
-        if !res.unmatched_jumps.is_empty() {
+        Codegen::gen_sections(res, false).map(|sections| sections.to_string())
+    }
+
+    /// Generates the bytecode of a BytecodeRes output split into its code and the tables
+    /// following it, filling the jump and table placeholders.
+    ///
+    /// With `eof`, table offsets are offsets in an EOF container of the sections, i.e. past its
+    /// header, as `codecopy` copies from the whole container.
+    pub fn gen_sections(res: BytecodeRes, eof: bool) -> Result<BytecodeSections, CodegenError> {
+        // Jumps can't leave the code section of a function
+        let unmatched_jumps = res
+            .unmatched_jumps()
+            .chain(res.functions.iter().flat_map(|f| f.res.unmatched_jumps()))
+            .collect::<Vec<&Relocation>>();
+        if !unmatched_jumps.is_empty() {
             tracing::error!(
                 target: "codegen",
                 "Source contains unmatched jump labels \"{}\"",
-                res.unmatched_jumps.iter().map(|uj| uj.label.to_string()).collect::<Vec<String>>().join(", ")
+                unmatched_jumps.iter().map(|uj| uj.label.to_string()).collect::<Vec<String>>().join(", ")
             );
             return Err(CodegenError {
                 kind: CodegenErrorKind::UnmatchedJumpLabel,
                 span: AstSpan(
-                    res.unmatched_jumps
-                        .iter()
-                        .flat_map(|uj| uj.span.0.clone())
-                        .collect::<Vec<Span>>(),
+                    unmatched_jumps.iter().flat_map(|uj| uj.span.0.clone()).collect::<Vec<Span>>(),
                 ),
                 token: None,
             })
@@ -155,12 +351,22 @@ impl Codegen {
 
         tracing::info!(target: "codegen", "GENERATING JUMPTABLE BYTECODE");
 
-        let mut bytecode = res.bytes.into_iter().map(|(_, b)| b.0).collect::<String>();
-        let mut table_offsets: HashMap<String, usize> = HashMap::new(); // table name -> bytecode offset
-        let mut table_offset = bytecode.len() / 2;
+        let res_size = res.size();
+        let mut table_offset = res_size;
+        let mut instructions = res.instructions;
+
+        // The tables used by functions follow the code sections along with the others
+        let mut utilized_tables = res.utilized_tables;
+        for table in res.functions.iter().flat_map(|f| &f.res.utilized_tables) {
+            if !utilized_tables.contains(table) {
+                utilized_tables.push(table.clone());
+            }
+        }
 
-        res.utilized_tables.iter().try_for_each(|jt| {
-            table_offsets.insert(jt.name.to_string(), table_offset);
+        let mut table_offsets: HashMap<&str, usize> = HashMap::new(); // table name -> bytecode offset
+        utilized_tables.iter().try_for_each(|jt| {
+            table_offsets.insert(&jt.name, table_offset);
+            let table_start = table_offset;
             let size = match bytes_util::hex_to_usize(bytes_util::bytes32_to_string(&jt.size, false).as_str()) {
                 Ok(s) => s,
                 Err(e) => {
@@ -176,14 +382,14 @@ impl Codegen {
 
             tracing::info!(target: "codegen", "GENERATING BYTECODE FOR TABLE: \"{}\"", jt.name);
 
-            let mut table_code = String::new();
+            let mut table_code: Vec<Instruction> = vec![];
             jt
                 .statements
                 .iter()
                 .try_for_each(|s| {
                     match &s.ty {
                         StatementType::LabelCall(label) => {
-                            let offset = match res.label_indices.get(label) {
+                            let offset = match res.label_indices.get(label.as_str()) {
                                 Some(l) => l,
                                 None => {
                                     tracing::error!(
@@ -198,16 +404,25 @@ impl Codegen {
                                     });
                                 }
                             };
-                            let hex = format_even_bytes(format!("{offset:02x}"));
-
-                            table_code = format!("{table_code}{}", pad_n_bytes(
-                                hex.as_str(),
-                                if matches!(jt.kind, TableKind::JumpTablePacked) { 0x02 } else { 0x20 },
-                            ));
+                            let width = if matches!(jt.kind, TableKind::JumpTablePacked) { 0x02 } else { 0x20 };
+                            match be_bytes(*offset, width) {
+                                Some(bytes) => table_code.push(Instruction::data(bytes, s.span.clone())),
+                                None => {
+                                    tracing::error!(target: "codegen", "JUMP TABLE LABEL \"{}\" OFFSET DOESN'T FIT IN {} BYTES", label, width);
+                                    return Err(CodegenError {
+                                        kind: CodegenErrorKind::InvalidRelocation(
+                                            label.clone(),
+                                            format!("offset {offset} doesn't fit in {width} bytes"),
+                                        ),
+                                        span: s.span.clone(),
+                                        token: None,
+                                    });
+                                }
+                            }
                         }
                         StatementType::Code(code) => {
                             // Check if code length is even
-                            if code.len() % 2 != 0 {
+                            if code.len() % 2 == 1 {
                                 return Err(CodegenError {
                                     kind: CodegenErrorKind::InvalidCodeLength(code.len()),
                                     span: s.span.clone(),
@@ -215,7 +430,16 @@ impl Codegen {
                                 });
                             }
 
-                            table_code = format!("{table_code}{code}");
+                            match Instruction::code(code, s.span.clone()) {
+                                Some(code) => table_code.push(code),
+                                None => {
+                                    return Err(CodegenError {
+                                        kind: CodegenErrorKind::InvalidHex(code.clone()),
+                                        span: s.span.clone(),
+                                        token: None,
+                                    });
+                                }
+                            }
                         }
                         _ => {
                             return Err(CodegenError {
@@ -228,228 +452,426 @@ impl Codegen {
                     Ok(())
                 })?;
             tracing::info!(target: "codegen", "SUCCESSFULLY GENERATED BYTECODE FOR TABLE: \"{}\"", jt.name);
-            bytecode = format!("{bytecode}{table_code}");
+            let mut entry_offset = table_start;
+            for entry in table_code {
+                let size = entry.len();
+                instructions.push((entry_offset, entry));
+                entry_offset += size;
+            }
             Ok(())
         })?;
 
-        res.table_instances.iter().for_each(|jump| {
-            if let Some(o) = table_offsets.get(&jump.label) {
-                let before = &bytecode[0..jump.bytecode_index * 2 + 2];
-                let after = &bytecode[jump.bytecode_index * 2 + 6..];
-
-                bytecode =
-                    format!("{before}{}{after}", pad_n_bytes(format!("{o:02x}").as_str(), 2));
-                tracing::info!(target: "codegen", "FILLED JUMPDEST FOR LABEL \"{}\"", jump.label);
+        // The header of a container and the code sections of its functions precede the tables,
+        // the data section is omitted without tables
+        let code_size = res_size;
+        let mut functions = res.functions;
+        let shift = if eof {
+            EofContainer::header_size(functions.len(), table_offset - code_size) +
+                functions.iter().map(|f| f.res.size()).sum::<usize>()
+        } else {
+            0
+        };
+        let mut relocations = res.relocations;
+        let function_relocations = functions.iter_mut().flat_map(|f| &mut f.res.relocations);
+        for relocation in relocations
+            .iter_mut()
+            .chain(function_relocations)
+            .filter(|r| r.kind == RelocationKind::Table)
+        {
+            if let Some(o) = table_offsets.get(relocation.label.as_str()) {
+                relocation.target = Some(*o + shift);
+                tracing::info!(target: "codegen", "FILLED JUMPDEST FOR LABEL \"{}\"", relocation.label);
             } else {
                 tracing::error!(
                     target: "codegen",
                     "Jump table offset not present for jump label \"{}\"",
-                    jump.label
+                    relocation.label
                 );
             }
-        });
+        }
+
+        Codegen::apply_relocations(&mut instructions, &relocations)?;
+        Codegen::check_placeholders(&instructions)?;
+
+        let functions = functions
+            .into_iter()
+            .map(|mut f| {
+                Codegen::apply_relocations(&mut f.res.instructions, &f.res.relocations)?;
+                Codegen::check_placeholders(&f.res.instructions)?;
+                // Validating the container reports functions taking or returning too many items
+                let items = |n: usize| u8::try_from(n).unwrap_or(u8::MAX);
+                let code = f.res.instructions.iter().flat_map(|(_, i)| {
+                    i.opcode.map(|o| o.byte()).into_iter().chain(i.immediate.iter().copied())
+                });
+                Ok(EofFunction {
+                    inputs: items(f.inputs),
+                    outputs: items(f.outputs),
+                    code: code.collect(),
+                })
+            })
+            .collect::<Result<Vec<EofFunction>, CodegenError>>()?;
+
+        let (code, data): (Vec<_>, Vec<_>) =
+            instructions.iter().partition(|(offset, _)| *offset < code_size);
+        Ok(BytecodeSections {
+            code: code.iter().map(|(_, i)| i.to_string()).collect(),
+            functions,
+            data: data.iter().map(|(_, i)| i.to_string()).collect(),
+        })
+    }
 
-        Ok(bytecode)
+    /// Jumps and table offsets must all be filled in once the bytecode is generated, only the
+    /// placeholders of `__CODECOPY_DYN_ARG` are left for the constructor arguments
+    fn check_placeholders(instructions: &[(usize, Instruction)]) -> Result<(), CodegenError> {
+        if let Some((offset, instruction)) =
+            instructions.iter().find(|(_, i)| i.opcode.is_some() && i.placeholder > 0)
+        {
+            tracing::error!(target: "codegen", "UNFILLED PLACEHOLDER AT OFFSET {}: {}", offset, instruction);
+            return Err(CodegenError {
+                kind: CodegenErrorKind::MalformedBytecode(format!(
+                    "unfilled placeholder in \"{instruction}\" at offset {offset}"
+                )),
+                span: instruction.source_span.clone(),
+                token: None,
+            })
+        }
+        Ok(())
+    }
+
+    /// Checks that bytecode is an even number of hex characters, erroring with the offset of the
+    /// first invalid character otherwise.
+    ///
+    /// `name` describes the bytecode in the error, i.e. "runtime bytecode".
+    pub fn validate_bytecode(
+        name: &str,
+        bytecode: &str,
+        span: AstSpan,
+    ) -> Result<(), CodegenError> {
+        let reason = match bytecode.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+            Some((i, c)) => format!("{name} contains \"{c}\" at byte {}", i / 2),
+            None if bytecode.len() % 2 == 1 => {
+                format!("{name} has an odd number of hex characters ({})", bytecode.len())
+            }
+            None => return Ok(()),
+        };
+        tracing::error!(target: "codegen", "MALFORMED BYTECODE: {}", reason);
+        Err(CodegenError { kind: CodegenErrorKind::MalformedBytecode(reason), span, token: None })
     }
 
-    /// Recurses a MacroDefinition to generate Bytecode
+    /// Expands a MacroDefinition to generate Bytecode
     ///
     /// ## Overview
     ///
     /// `macro_to_bytecode` first transforms the macro definition into "IR" Bytecode - a vec of
     /// intermediate bytes. It then iterates over each byte, converting the
-    /// [IRByte](struct.IRByte.html) into a `Bytes`. Once done iterating over the macro
-    /// definition IRBytes, we use the JumpTable to match any unmatched jumps. If jumps are not
-    /// matched, they are appended to a vec of unmatched jumps.
+    /// [IRByte](struct.IRByte.html) into [Instruction]s. Once done iterating over the macro
+    /// definition IRBytes, jumps are bound to the labels defined in the macro. Jumps that aren't
+    /// bound are left for a parent macro to resolve.
+    ///
+    /// Invoked macros are expanded on an explicit stack of [MacroExpansion]s rather than by
+    /// recursing, so deeply nested macros can't overflow the native stack. Once more than
+    /// `nesting_limit` macros are being expanded at once, a `MacroNestingTooDeep` error is
+    /// returned instead.
+    ///
+    /// Macros invoked repeatedly with the same literal arguments are only expanded once. As long
+    /// as their bytecode doesn't depend on where they're expanded, it is relocated to the offset
+    /// of each following invocation instead.
     ///
     /// On success, a [BytecodeRes](struct.BytecodeRes.html) is returned,
-    /// containing the generated bytes, label indices, unmatched jumps, and table indices.
+    /// containing the generated instructions, label indices, relocations, and utilized tables.
     ///
     /// ## Arguments
     ///
+    /// * `evm_version` - The EVM version targeted during compilation
     /// * `macro_def` - Macro definition to convert to bytecode
     /// * `contract` - Reference to the `Contract` AST generated by the parser
-    /// * `scope` - Current scope of the recursion. Contains all macro definitions recursed so far.
+    /// * `scope` - Current scope of the expansion. Contains all macro definitions expanded so far.
     /// * `offset` - Current bytecode offset
     /// * `mis` - Vector of tuples containing parent macro invocations as well as their offsets.
-    pub fn macro_to_bytecode(
-        macro_def: MacroDefinition,
-        contract: &Contract,
-        scope: &mut Vec<MacroDefinition>,
-        mut offset: usize,
+    /// * `nesting_limit` - The maximum number of macros being expanded at once
+    #[allow(clippy::too_many_arguments)]
+    pub fn macro_to_bytecode<'a>(
+        evm_version: &EVMVersion,
+        macro_def: &'a MacroDefinition,
+        contract: &'a Contract,
+        scope: &mut Vec<&'a MacroDefinition>,
+        offset: usize,
         mis: &mut Vec<(usize, MacroInvocation)>,
         recursing_constructor: bool,
         circular_codesize_invocations: Option<&mut CircularCodeSizeIndices>,
+        nesting_limit: usize,
     ) -> Result<BytecodeRes, CodegenError> {
-        // Get intermediate bytecode representation of the macro definition
-        let mut bytes: Vec<(usize, Bytes)> = Vec::default();
-        let ir_bytes = macro_def.to_irbytecode()?.0;
-
-        // Define outer loop variables
-        let mut jump_table = JumpTable::new();
-        let mut label_indices = LabelIndices::new();
-        let mut table_instances = Jumps::new();
-        let mut utilized_tables: Vec<TableDefinition> = Vec::new();
         let mut ccsi = CircularCodeSizeIndices::new();
         let circular_codesize_invocations = circular_codesize_invocations.unwrap_or(&mut ccsi);
 
-        // Loop through all intermediate bytecode representations generated from the AST
-        for (_ir_bytes_index, ir_byte) in ir_bytes.into_iter().enumerate() {
-            let starting_offset = offset;
-            match ir_byte.ty {
-                IRByteType::Bytes(b) => {
-                    offset += b.0.len() / 2;
-                    bytes.push((starting_offset, b));
-                }
-                IRByteType::Constant(name) => {
-                    let push_bytes = constant_gen(&name, contract, ir_byte.span)?;
-                    offset += push_bytes.len() / 2;
-                    tracing::debug!(target: "codegen", "OFFSET: {}, PUSH BYTES: {:?}", offset, push_bytes);
-                    bytes.push((starting_offset, Bytes(push_bytes)));
-                }
-                IRByteType::Statement(s) => {
-                    // if we have a codesize call for the constructor here, from within the
-                    // constructor, we skip
-                    if recursing_constructor {
-                        continue
+        let mut root = MacroExpansion::new(
+            evm_version,
+            macro_def,
+            offset,
+            recursing_constructor,
+            Expansion::Invocation,
+        )?;
+        root.prologue(evm_version, contract)?;
+        let mut expansions = vec![root];
+        let mut expanded: HashMap<ExpansionKey, (usize, BytecodeRes)> = HashMap::new();
+
+        loop {
+            let depth = expansions.len();
+            let guarded = expansions.iter().any(|e| e.guarded);
+            let expansion = match expansions.last_mut() {
+                Some(expansion) => expansion,
+                None => unreachable!("the root macro expansion returns its bytecode once finished"),
+            };
+
+            // Loop through all intermediate bytecode representations generated from the AST
+            let ir_byte = match expansion.ir_bytes.next() {
+                Some(ir_byte) => ir_byte,
+                None => {
+                    let mut expansion = expansions.pop().unwrap();
+                    if expansion.guarded {
+                        expansion.unlock(evm_version, contract)?;
                     }
-                    let mut push_bytes = statement_gen(
-                        &s,
+                    let (kind, start, key) =
+                        (expansion.kind, expansion.start, expansion.key.clone());
+                    let (res, relocatable) = expansion.finish(
+                        evm_version,
                         contract,
-                        &macro_def,
                         scope,
-                        &mut offset,
                         mis,
-                        &mut jump_table,
-                        &mut label_indices,
-                        &mut table_instances,
-                        &mut utilized_tables,
                         circular_codesize_invocations,
-                        starting_offset,
+                        nesting_limit,
                     )?;
-                    bytes.append(&mut push_bytes);
+                    let parent = match expansions.last_mut() {
+                        Some(parent) => parent,
+                        None => return Ok(res),
+                    };
+                    parent.relocatable &= relocatable;
+                    if let (Some(key), true) = (key, relocatable) {
+                        expanded.insert(key, (start, res.clone()));
+                    }
+                    parent.merge(res, kind);
+                    continue
                 }
+            };
+
+            let starting_offset = expansion.offset;
+            let s = match ir_byte.ty {
+                IRByteType::Instruction(i) => {
+                    // Guarded code unlocks the reentrancy guard before halting
+                    if guarded && matches!(i.opcode, Some(Opcode::Return | Opcode::Stop)) {
+                        expansion.unlock(evm_version, contract)?;
+                    }
+                    let starting_offset = expansion.offset;
+                    // EOF code jumps to labels relative to the jump rather than through the stack
+                    if evm_version.eof() {
+                        if let Some((offset, rjump)) = relative_jump(
+                            &mut expansion.instructions,
+                            &mut expansion.relocations,
+                            &i,
+                        ) {
+                            expansion.offset = offset + rjump.len();
+                            expansion.instructions.push((offset, rjump));
+                            continue
+                        }
+                    }
+                    expansion.offset += i.len();
+                    expansion.instructions.push((starting_offset, i));
+                    continue
+                }
+                IRByteType::Constant(name) => {
+                    let push = constant_gen(&name, expansion.macro_def, contract, ir_byte.span)?;
+                    expansion.offset += push.len();
+                    tracing::debug!(target: "codegen", "OFFSET: {}, PUSH: {}", expansion.offset, push);
+                    expansion.instructions.push((starting_offset, push));
+                    continue
+                }
+                IRByteType::Statement(s) => s,
                 IRByteType::ArgCall(arg_name) => {
                     // Bubble up arg call by looking through the previous scopes.
-                    // Once the arg value is found, add it to `bytes`
-                    bubble_arg_call(
+                    // Once the arg value is found, add it to `instructions`
+                    match bubble_arg_call(
+                        evm_version,
                         &arg_name,
-                        &mut bytes,
-                        &macro_def,
+                        &mut expansion.instructions,
+                        expansion.macro_def,
                         contract,
                         scope,
-                        &mut offset,
+                        &mut expansion.offset,
                         mis,
-                        &mut jump_table,
-                    )?
+                        &mut expansion.relocations,
+                    )? {
+                        // A macro invocation passed as the argument is expanded in its place
+                        Some(mi) => {
+                            Statement { ty: StatementType::MacroInvocation(mi), span: ir_byte.span }
+                        }
+                        None => continue,
+                    }
                 }
-            }
-        }
-
-        // We're done, let's pop off the macro invocation
-        if mis.pop().is_none() {
-            tracing::warn!(target: "codegen", "ATTEMPTED MACRO INVOCATION POP FAILED AT SCOPE: {}", scope.len());
-        }
+            };
 
-        // Add functions (outlined macros) to the end of the bytecode if the scope length == 1
-        // (i.e., we're at the top level of recursion)
-        if scope.len() == 1 {
-            bytes = Codegen::append_functions(
+            // if we have a codesize call for the constructor here, from within the
+            // constructor, we skip
+            if expansion.recursing_constructor {
+                continue
+            }
+            if !is_relocatable(&s, contract) {
+                expansion.relocatable = false;
+            }
+            match statement_gen(
+                evm_version,
+                &s,
                 contract,
+                expansion.macro_def,
                 scope,
-                &mut offset,
+                &mut expansion.offset,
                 mis,
-                &mut jump_table,
-                &mut label_indices,
-                &mut table_instances,
-                bytes,
-            )?;
-        } else {
-            // If the scope length is > 1, we're processing a child macro. Since we're done
-            // with it, it can be popped.
-            scope.pop();
-        }
-
-        // Fill JUMPDEST placeholders
-        let (bytes, unmatched_jumps) = Codegen::fill_unmatched(bytes, &jump_table, &label_indices)?;
+                &mut expansion.relocations,
+                &mut expansion.label_indices,
+                &mut expansion.utilized_tables,
+                circular_codesize_invocations,
+                starting_offset,
+            )? {
+                StatementGen::Instructions(mut instructions) => {
+                    expansion.instructions.append(&mut instructions)
+                }
+                StatementGen::Expand(ir_macro, kind) => {
+                    if depth >= nesting_limit {
+                        tracing::error!(
+                            target: "codegen",
+                            "MACRO \"{}\" NESTED DEEPER THAN THE LIMIT OF {}",
+                            ir_macro.name,
+                            nesting_limit
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::MacroNestingTooDeep(
+                                nesting_limit,
+                                ir_macro.name.clone(),
+                            ),
+                            span: s.span,
+                            token: None,
+                        })
+                    }
+                    // Guarded code differs from the same macro's code elsewhere, as it unlocks the
+                    // reentrancy guard before halting
+                    let key = match kind {
+                        _ if guarded || ir_macro.has_pragma(MacroPragma::Nonreentrant) => None,
+                        Expansion::Invocation => mis
+                            .last()
+                            .and_then(|(_, mi)| ExpansionKey::from_invocation(ir_macro, mi)),
+                        Expansion::Codesize(_) => None,
+                    };
+
+                    // Reuse the bytecode of a previous identical invocation
+                    if let Some((start, res)) = key.as_ref().and_then(|k| expanded.get(k)) {
+                        tracing::debug!(target: "codegen", "REUSING EXPANSION OF MACRO \"{}\"", ir_macro.name);
+                        scope.pop();
+                        mis.pop();
+                        let res = relocate(res, *start, expansion.offset);
+                        expansion.merge(res, kind);
+                        continue
+                    }
 
-        // Fill in circular codesize invocations
-        // Workout how to increase the offset the correct amount within here if it is longer than 2
-        // bytes
-        let bytes = Codegen::fill_circular_codesize_invocations(
-            bytes,
-            circular_codesize_invocations,
-            &macro_def.name,
-        )?;
+                    let offset = expansion.offset;
+                    let mut child = MacroExpansion::new(
+                        evm_version,
+                        ir_macro,
+                        offset,
+                        ir_macro.name.eq("CONSTRUCTOR") && matches!(kind, Expansion::Codesize(_)),
+                        kind,
+                    )?;
+                    child.key = key;
+                    child.prologue(evm_version, contract)?;
+                    expansions.push(child);
+                }
+            }
+        }
+    }
 
-        Ok(BytecodeRes { bytes, label_indices, unmatched_jumps, table_instances, utilized_tables })
+    /// Helper associated function to resolve jumps to labels.
+    ///
+    /// ## Overview
+    ///
+    /// Binds every unresolved label [Relocation] to the offset of its label if the label is
+    /// defined in `label_indices`. Relocations whose label isn't defined are left unresolved, to
+    /// be resolved against the labels of a parent macro.
+    pub fn resolve_labels(relocations: &mut Relocations, label_indices: &LabelIndices) {
+        for relocation in
+            relocations.iter_mut().filter(|r| r.kind == RelocationKind::Label && r.target.is_none())
+        {
+            // Jumps to labels passed as arguments skip the macros they were passed into
+            if relocation.pending_scopes > 0 {
+                relocation.pending_scopes -= 1;
+                continue
+            }
+            relocation.target = label_indices.get(&relocation.label).copied();
+        }
     }
 
-    /// Helper associated function to fill unmatched jump dests.
+    /// Helper associated function to patch resolved relocations into the generated instructions.
     ///
     /// ## Overview
     ///
-    /// Iterates over the vec of generated bytes. At each index, check if a jump is tracked.
-    /// If one is, find the index of label and inplace the formatted location.
-    /// If there is no label matching the jump, we append the jump to a list of unmatched jumps,
-    /// updating the jump's bytecode index.
+    /// Fills the placeholder of each relocation with the offset it resolved to. The placeholder
+    /// is located by its bytecode offset, so a relocation that doesn't point at a placeholder of
+    /// its width, or whose offset doesn't fit in it, is an error rather than silently overwriting
+    /// other bytes. The offsets of negated relocations are subtracted from the offset of the
+    /// other relocation of their placeholder, filling it with the distance between two labels.
+    /// Relative relocations are filled with the signed distance from the end of their
+    /// placeholder to their target, as the destinations of `rjump`s.
     ///
-    /// On success, returns a tuple of generated bytes and unmatched jumps.
     /// On failure, returns a CodegenError.
-    #[allow(clippy::type_complexity)]
-    pub fn fill_unmatched(
-        bytes: Vec<(usize, Bytes)>,
-        jump_table: &JumpTable,
-        label_indices: &LabelIndices,
-    ) -> Result<(Vec<(usize, Bytes)>, Vec<Jump>), CodegenError> {
-        let mut unmatched_jumps = Jumps::default();
-        let bytes =
-            bytes.into_iter().fold(Vec::default(), |mut acc, (code_index, mut formatted_bytes)| {
-                // Check if a jump table exists at `code_index` (starting offset of `b`)
-                if let Some(jt) = jump_table.get(&code_index) {
-                    // Loop through jumps inside of the found JumpTable
-                    for jump in jt {
-                        // Check if the jump label has been defined. If not, add `jump` to the
-                        // unmatched jumps and define its `bytecode_index`
-                        // at `code_index`
-                        if let Some(jump_index) = label_indices.get(jump.label.as_str()) {
-                            // Format the jump index as a 2 byte hex number
-                            let jump_value = format!("{jump_index:04x}");
-
-                            // Get the bytes before & after the placeholder
-                            let before = &formatted_bytes.0[0..jump.bytecode_index + 2];
-                            let after = &formatted_bytes.0[jump.bytecode_index + 6..];
-
-                            // Check if a jump dest placeholder is present
-                            if !&formatted_bytes.0[jump.bytecode_index + 2..jump.bytecode_index + 6]
-                                .eq("xxxx")
-                            {
-                                tracing::error!(
-                                    target: "codegen",
-                                    "JUMP DESTINATION PLACEHOLDER NOT FOUND FOR JUMPLABEL {}",
-                                    jump.label
-                                );
-                            }
+    pub fn apply_relocations(
+        instructions: &mut [(usize, Instruction)],
+        relocations: &[Relocation],
+    ) -> Result<(), CodegenError> {
+        for relocation in relocations.iter().filter(|r| !r.negated) {
+            let target = match relocation.target {
+                Some(target) => target,
+                None => continue,
+            };
+            let invalid = |reason: String| {
+                tracing::error!(target: "codegen", "INVALID RELOCATION FOR \"{}\": {}", relocation.label, reason);
+                CodegenError {
+                    kind: CodegenErrorKind::InvalidRelocation(relocation.label.to_string(), reason),
+                    span: relocation.span.clone(),
+                    token: None,
+                }
+            };
 
-                            // Replace the "xxxx" placeholder with the jump value
-                            formatted_bytes = Bytes(format!("{before}{jump_value}{after}"));
-                        } else {
-                            // The jump did not have a corresponding label index. Add it to the
-                            // unmatched jumps vec.
-                            unmatched_jumps.push(Jump {
-                                label: jump.label.clone(),
-                                bytecode_index: code_index,
-                                span: jump.span.clone(),
-                            });
-                        }
-                    }
+            let index = instructions.partition_point(|(o, _)| *o <= relocation.offset);
+            let instruction = match index.checked_sub(1).map(|i| &mut instructions[i]) {
+                Some((offset, instruction))
+                    if *offset + usize::from(instruction.opcode.is_some()) == relocation.offset &&
+                        instruction.placeholder == relocation.width =>
+                {
+                    instruction
                 }
+                _ => return Err(invalid(format!("no placeholder at offset {}", relocation.offset))),
+            };
 
-                acc.push((code_index, formatted_bytes));
-                acc
-            });
+            let base = relocations
+                .iter()
+                .filter(|r| r.negated && r.offset == relocation.offset)
+                .filter_map(|r| r.target)
+                .sum::<usize>();
+            let target = target.checked_sub(base).ok_or_else(|| {
+                invalid(format!("offset {target} precedes the start of the slice at {base}"))
+            })?;
+            let value = if relocation.relative {
+                // Relative to the instruction following the placeholder, i.e. the `rjump`
+                let relative = target as isize - (relocation.offset + relocation.width) as isize;
+                i16::try_from(relative).map(|r| r.to_be_bytes().to_vec()).map_err(|_| {
+                    invalid(format!("relative offset {relative} doesn't fit in 2 bytes"))
+                })?
+            } else {
+                be_bytes(target, relocation.width).ok_or_else(|| {
+                    invalid(format!("offset {target} doesn't fit in {} bytes", relocation.width))
+                })?
+            };
+            instruction.fill(&value);
+        }
 
-        Ok((bytes, unmatched_jumps))
+        Ok(())
     }
 
     /// Helper associated function to fill circular codesize invocations.
@@ -461,23 +883,25 @@ impl Codegen {
     /// fill them in with the correct offset.
     ///
     /// If there are multiple invocations of the same macro, the function will take into
-    /// account the total number of invocations and increase its offset accordingly.
+    /// account the total number of invocations and increase its offset accordingly. The offsets
+    /// of relocations past a widened placeholder are increased along with the bytes.
     ///
     /// On success, returns a tuple of generated bytes.
     /// On failure, returns a CodegenError.
     pub fn fill_circular_codesize_invocations(
-        bytes: Vec<(usize, Bytes)>,
+        instructions: Vec<(usize, Instruction)>,
+        relocations: &mut Relocations,
         circular_codesize_invocations: &mut CircularCodeSizeIndices,
         macro_name: &str,
-    ) -> Result<Vec<(usize, Bytes)>, CodegenError> {
+    ) -> Result<Vec<(usize, Instruction)>, CodegenError> {
         // Get the length of the macro
         let num_invocations = circular_codesize_invocations.len();
         if num_invocations == 0 {
-            return Ok(bytes)
+            return Ok(instructions)
         }
 
-        tracing::debug!(target: "codegen", "Circular Codesize Invocation: Bytes before expansion: {:#?}", bytes);
-        let length: usize = bytes.iter().map(|(_, b)| b.0.len()).sum::<usize>() / 2;
+        tracing::debug!(target: "codegen", "Circular Codesize Invocation: Instructions before expansion: {:#?}", instructions);
+        let length: usize = instructions.iter().map(|(_, i)| i.len()).sum();
 
         // If there are more than 256 opcodes in a macro, we need 2 bytes to represent it
         // The next threshold is 65536 opcodes which is past the codesize limit
@@ -488,111 +912,153 @@ impl Codegen {
         // Codesize will increase by 1 byte for every codesize that exists
         let extended_length = length + (offset_increase * num_invocations);
 
-        let push_size = format_even_bytes(format!("{extended_length:02x}"));
-        let push_bytes = format!("{:02x}{push_size}", 95 + push_size.len() / 2);
-
         // Track the number of bytes added if there is an offset increase with codesize
         let mut running_increase = 0;
-        let bytes = bytes.into_iter().fold(
+        let mut placeholders = vec![];
+        let macro_name = Symbol::new(macro_name);
+        let instructions = instructions.into_iter().fold(
             Vec::default(),
-            |mut acc, (mut code_index, mut formatted_bytes)| {
-                // Check if a jump table exists at `code_index` (starting offset of `b`)
-                if let Some((_, _index)) =
-                    circular_codesize_invocations.get(&(macro_name.to_string(), code_index))
-                {
-                    // Check if a jump dest placeholder is present
-                    if !&formatted_bytes.0.eq("cccc") {
+            |mut acc, (code_index, mut instruction)| {
+                // Increase the code index by the number of bytes added before the instruction
+                let shifted_index = code_index + running_increase;
+
+                // Check if a circular codesize invocation exists at `code_index`
+                if circular_codesize_invocations.contains(&(macro_name.clone(), code_index)) {
+                    // Check if a codesize placeholder is present
+                    if instruction.placeholder == 0 {
                         tracing::error!(
                             target: "codegen",
                             "CIRCULAR CODESIZE PLACEHOLDER NOT FOUND"
                         );
                     }
 
-                    // Replace the "cccc" placeholder with the jump value
-                    formatted_bytes = Bytes(push_bytes.to_string());
+                    // Replace the placeholder with a push of the codesize
+                    instruction = Instruction::push_value(
+                        &extended_length.to_be_bytes(),
+                        instruction.source_span,
+                    );
                     running_increase += offset_increase;
-                } else {
-                    // Increase the code index by the number of bytes added past the placeholder
-                    code_index += running_increase;
+                    placeholders.push(code_index);
                 }
 
-                acc.push((code_index, formatted_bytes));
+                acc.push((shifted_index, instruction));
                 acc
             },
         );
 
-        Ok(bytes)
+        for relocation in relocations.iter_mut() {
+            let preceding = placeholders.iter().filter(|p| **p < relocation.offset).count();
+            relocation.offset += preceding * offset_increase;
+        }
+
+        Ok(instructions)
     }
 
     /// Helper associated function to append functions to the end of the bytecode.
     ///
     /// ## Overview
     ///
-    /// Iterates over the contract's functions, generates their bytecode, fills unmatched jumps &
-    /// label indices, and appends the functions' bytecode to the end of the contract's bytecode.
+    /// Iterates over the contract's functions, generates their bytecode, collects their
+    /// relocations, label indices & macro activations, and appends the functions' bytecode to the
+    /// end of the contract's bytecode.
     ///
-    /// On success, passes ownership of `bytes` back to the caller.
+    /// On success, passes ownership of `instructions` back to the caller.
     /// On failure, returns a CodegenError.
     #[allow(clippy::too_many_arguments)]
-    pub fn append_functions(
-        contract: &Contract,
-        scope: &mut Vec<MacroDefinition>,
+    pub fn append_functions<'a>(
+        evm_version: &EVMVersion,
+        contract: &'a Contract,
+        scope: &mut Vec<&'a MacroDefinition>,
         offset: &mut usize,
         mis: &mut Vec<(usize, MacroInvocation)>,
-        jump_table: &mut JumpTable,
+        relocations: &mut Relocations,
         label_indices: &mut LabelIndices,
-        table_instances: &mut Jumps,
-        mut bytes: Vec<(usize, Bytes)>,
-    ) -> Result<Vec<(usize, Bytes)>, CodegenError> {
-        for macro_def in contract.macros.iter().filter(|m| m.outlined) {
+        activations: &mut Vec<MacroActivation>,
+        mut instructions: Vec<(usize, Instruction)>,
+        nesting_limit: usize,
+    ) -> Result<Vec<(usize, Instruction)>, CodegenError> {
+        for macro_def in contract.macros.iter().filter(|m| m.is_outlined()) {
             // Push the function to the scope
-            scope.push(macro_def.clone());
+            scope.push(macro_def);
 
             // Add 1 to starting offset to account for the JUMPDEST opcode
             let mut res = Codegen::macro_to_bytecode(
-                macro_def.clone(),
+                evm_version,
+                macro_def,
                 contract,
                 scope,
                 *offset + 1,
                 mis,
                 false,
                 None,
+                nesting_limit,
             )?;
 
-            for j in res.unmatched_jumps.iter_mut() {
-                let new_index = j.bytecode_index;
-                j.bytecode_index = 0;
-                let mut new_jumps = if let Some(jumps) = jump_table.get(&new_index) {
-                    jumps.clone()
-                } else {
-                    vec![]
-                };
-                new_jumps.push(j.clone());
-                jump_table.insert(new_index, new_jumps);
-            }
-            table_instances.extend(res.table_instances);
+            let macro_code_len = res.size();
+            relocations.extend(res.relocations);
             label_indices.extend(res.label_indices);
-
-            let macro_code_len = res.bytes.iter().map(|(_, b)| b.0.len()).sum::<usize>() / 2;
+            activations.append(&mut res.activations);
 
             // Get necessary swap ops to reorder stack
             // PC of the return jumpdest should be above the function's outputs on the stack
-            let stack_swaps =
-                (0..macro_def.returns).map(|i| format!("{:02x}", 0x90 + i)).collect::<Vec<_>>();
+            let stack_swaps = stack_swaps(macro_def.returns, macro_def)?;
 
             // Insert JUMPDEST, stack swaps, and final JUMP back to the location of invocation.
-            bytes.push((*offset, Bytes(Opcode::Jumpdest.to_string())));
-            res.bytes.push((
-                *offset + macro_code_len + 1,
-                Bytes(format!("{}{}", stack_swaps.join(""), Opcode::Jump)),
-            ));
-            bytes = [bytes, res.bytes].concat();
+            instructions.push((*offset, Instruction::op(Opcode::Jumpdest, macro_def.span.clone())));
+            let mut end = *offset + macro_code_len + 1; // JUMPDEST + MACRO_CODE_LEN
+            for opcode in stack_swaps.into_iter().chain([Opcode::Jump]) {
+                res.instructions.push((end, Instruction::op(opcode, macro_def.span.clone())));
+                end += 1;
+            }
+            instructions.append(&mut res.instructions);
             // Add the jumpdest to the beginning of the outlined macro.
-            label_indices.insert(format!("goto_{}", macro_def.name.clone()), *offset);
-            *offset += macro_code_len + stack_swaps.len() + 2; // JUMPDEST + MACRO_CODE_LEN +
-                                                               // stack_swaps.len() + JUMP
+            label_indices.insert(Symbol::from(format!("goto_{}", macro_def.name)), *offset);
+            *offset = end;
+        }
+        Ok(instructions)
+    }
+
+    /// Helper associated function to generate the code sections of functions for EOF code.
+    ///
+    /// ## Overview
+    ///
+    /// Generates the bytecode of each of the contract's functions (outlined macros) from offset 0
+    /// of a code section of its own, ending with a `retf` back to the section calling it. The
+    /// functions are called with `callf` by the index of their section, counting from 1 in the
+    /// order they're defined in.
+    ///
+    /// On failure, returns a CodegenError.
+    pub fn function_sections<'a>(
+        evm_version: &EVMVersion,
+        contract: &'a Contract,
+        scope: &mut Vec<&'a MacroDefinition>,
+        mis: &mut Vec<(usize, MacroInvocation)>,
+        nesting_limit: usize,
+    ) -> Result<Vec<FunctionSection>, CodegenError> {
+        let mut functions = vec![];
+        for macro_def in contract.macros.iter().filter(|m| m.is_outlined()) {
+            scope.push(macro_def);
+            let mut res = Codegen::macro_to_bytecode(
+                evm_version,
+                macro_def,
+                contract,
+                scope,
+                0,
+                mis,
+                false,
+                None,
+                nesting_limit,
+            )?;
+            res.instructions
+                .push((res.size(), Instruction::op(Opcode::Retf, macro_def.span.clone())));
+            functions.push(FunctionSection {
+                name: macro_def.name.clone(),
+                inputs: macro_def.takes,
+                outputs: macro_def.returns,
+                res,
+            });
         }
-        Ok(bytes)
+        Ok(functions)
     }
 
     /// Generate a codegen artifact
@@ -605,12 +1071,12 @@ impl Codegen {
     pub fn churn(
         &mut self,
         file: Arc<FileSource>,
-        mut args: Vec<ethers_core::abi::token::Token>,
+        args: Vec<ethers_core::abi::token::Token>,
         main_bytecode: &str,
         constructor_bytecode: &str,
         has_custom_bootstrap: bool,
     ) -> Result<Artifact, CodegenError> {
-        let mut artifact: &mut Artifact = if let Some(art) = &mut self.artifact {
+        let artifact: &mut Artifact = if let Some(art) = &mut self.artifact {
             art
         } else {
             self.artifact = Some(Artifact::default());
@@ -623,6 +1089,152 @@ impl Codegen {
         let contract_length = main_bytecode.len() / 2;
         let constructor_length = constructor_bytecode.len() / 2;
 
+        let constructor_args = Codegen::encode_args(args, &mut main_bytecode, contract_length);
+
+        // Sucks that we can't provide a span on this error. Need to refactor at some point.
+        if main_bytecode.contains('x') {
+            tracing::error!(target="codegen", "Failed to fill `__CODECOPY_DYN_ARG` placeholders. Dynamic argument index is invalid.");
+            return Err(CodegenError {
+                kind: CodegenErrorKind::InvalidDynArgIndex,
+                span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
+                token: None,
+            })
+        }
+
+        // Constructor size optimizations
+        let mut bootstrap_code_size = 9;
+        let contract_size = if contract_length < 256 {
+            format!("60{}", pad_n_bytes(format!("{contract_length:x}").as_str(), 1))
+        } else {
+            bootstrap_code_size += 1;
+
+            format!("61{}", pad_n_bytes(format!("{contract_length:x}").as_str(), 2))
+        };
+        let contract_code_offset = if (bootstrap_code_size + constructor_length) < 256 {
+            format!(
+                "60{}",
+                pad_n_bytes(format!("{:x}", bootstrap_code_size + constructor_length).as_str(), 1)
+            )
+        } else {
+            bootstrap_code_size += 1;
+
+            format!(
+                "61{}",
+                pad_n_bytes(format!("{:x}", bootstrap_code_size + constructor_length).as_str(), 2)
+            )
+        };
+
+        let bootstrap_code = if has_custom_bootstrap {
+            String::default()
+        } else {
+            format!("{contract_size}80{contract_code_offset}3d393df3")
+        };
+
+        // Make sure no malformed bytes slipped through before writing the artifact
+        let span = AstSpan(vec![Span { start: 0, end: 0, file: Some(Arc::clone(&file)) }]);
+        Codegen::validate_bytecode("constructor bytecode", constructor_bytecode, span.clone())?;
+        Codegen::validate_bytecode("runtime bytecode", &main_bytecode, span)?;
+
+        // Generate the final bytecode
+        let constructor_code = format!("{constructor_bytecode}{bootstrap_code}");
+        artifact.bytecode =
+            format!("{constructor_code}{main_bytecode}{constructor_args}").to_lowercase();
+        artifact.runtime = main_bytecode.to_lowercase();
+        artifact.file = file;
+        Ok(artifact.clone())
+    }
+
+    /// Generate a codegen artifact of EOF containers
+    ///
+    /// The runtime container holds the main code, and its tables as data. The creation container
+    /// holds the constructor code followed by a bootstrap returning the runtime container, which
+    /// is its data along with the constructor arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - A vector of Tokens representing constructor arguments
+    /// * `main` - The compiled MAIN Macro sections
+    /// * `constructor` - The compiled `CONSTRUCTOR` Macro sections
+    pub fn churn_eof(
+        &mut self,
+        file: Arc<FileSource>,
+        args: Vec<ethers_core::abi::token::Token>,
+        main: &BytecodeSections,
+        constructor: &BytecodeSections,
+        has_custom_bootstrap: bool,
+    ) -> Result<Artifact, CodegenError> {
+        let span = AstSpan(vec![Span { start: 0, end: 0, file: Some(Arc::clone(&file)) }]);
+        let invalid = |reason: String| {
+            tracing::error!(target: "codegen", "Invalid EOF container: {}", reason);
+            CodegenError {
+                kind: CodegenErrorKind::InvalidEofContainer(reason),
+                span: span.clone(),
+                token: None,
+            }
+        };
+
+        if has_custom_bootstrap {
+            return Err(invalid(
+                "constructors returning their own runtime code are unsupported".into(),
+            ))
+        }
+        if !constructor.data.is_empty() {
+            return Err(invalid("tables used by the constructor are unsupported".into()))
+        }
+        if main.code.contains('x') {
+            return Err(invalid("`__CODECOPY_DYN_ARG` is unsupported".into()))
+        }
+
+        // Make sure no malformed bytes slipped through before writing the artifact
+        Codegen::validate_bytecode("constructor bytecode", &constructor.code, span.clone())?;
+        Codegen::validate_bytecode("runtime bytecode", &main.code, span.clone())?;
+        Codegen::validate_bytecode("table bytecode", &main.data, span.clone())?;
+
+        // There are no placeholders to fill, so the offset of the arguments doesn't matter
+        let constructor_args = Codegen::encode_args(args, &mut main.code.clone(), 0);
+        let decode = |hex: &str| hex::decode(hex).map_err(|e| invalid(e.to_string()));
+
+        let runtime = EofContainer::new(decode(&main.code)?, decode(&main.data)?)
+            .with_functions(main.functions.clone());
+        runtime.validate().map_err(|e| invalid(format!("runtime container: {e}")))?;
+        let runtime_bytes = runtime.encode();
+        if runtime_bytes.len() > MAX_SECTION_SIZE {
+            return Err(invalid(format!(
+                "the runtime container is {} bytes, more than the {MAX_SECTION_SIZE} bytes of a section",
+                runtime_bytes.len()
+            )))
+        }
+
+        // Copies the runtime container, at the start of the data section, to memory and returns it
+        let bootstrap_size = 11;
+        let data_offset =
+            EofContainer::header_size(constructor.functions.len(), runtime_bytes.len()) +
+                constructor.code.len() / 2 +
+                bootstrap_size +
+                constructor.functions.iter().map(|f| f.code.len()).sum::<usize>();
+        let bootstrap_code = format!("61{:04x}8061{data_offset:04x}3d393df3", runtime_bytes.len());
+        let code = decode(&format!("{}{bootstrap_code}", constructor.code))?;
+
+        let mut data = runtime_bytes;
+        data.extend(decode(&constructor_args)?);
+        let creation = EofContainer::new(code, data).with_functions(constructor.functions.clone());
+        creation.validate().map_err(|e| invalid(format!("creation container: {e}")))?;
+
+        let artifact = self.artifact.get_or_insert_with(Artifact::default);
+        artifact.bytecode = creation.to_string();
+        artifact.runtime = runtime.to_string();
+        artifact.file = file;
+        Ok(artifact.clone())
+    }
+
+    /// Encodes constructor arguments, statically sized ones last, filling the
+    /// `__CODECOPY_DYN_ARG` placeholders of the main bytecode with the offset of each dynamically
+    /// sized argument, starting at `args_offset`
+    fn encode_args(
+        mut args: Vec<ethers_core::abi::token::Token>,
+        main_bytecode: &mut String,
+        args_offset: usize,
+    ) -> String {
         // Sort constructor arguments so that statically sized args are inserted last.
         args.sort_by(|a, b| {
             if a.is_dynamic() && !b.is_dynamic() {
@@ -634,7 +1246,7 @@ impl Codegen {
             }
         });
 
-        let mut arg_offset_acc = contract_length;
+        let mut arg_offset_acc = args_offset;
         let encoded: Vec<Vec<u8>> = args
             .into_iter()
             .enumerate()
@@ -687,54 +1299,7 @@ impl Codegen {
             })
             .collect();
         let hex_args: Vec<String> = encoded.iter().map(|tok| hex::encode(tok.as_slice())).collect();
-        let constructor_args = hex_args.join("");
-
-        // Sucks that we can't provide a span on this error. Need to refactor at some point.
-        if main_bytecode.contains('x') {
-            tracing::error!(target="codegen", "Failed to fill `__CODECOPY_DYN_ARG` placeholders. Dynamic argument index is invalid.");
-            return Err(CodegenError {
-                kind: CodegenErrorKind::InvalidDynArgIndex,
-                span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
-                token: None,
-            })
-        }
-
-        // Constructor size optimizations
-        let mut bootstrap_code_size = 9;
-        let contract_size = if contract_length < 256 {
-            format!("60{}", pad_n_bytes(format!("{contract_length:x}").as_str(), 1))
-        } else {
-            bootstrap_code_size += 1;
-
-            format!("61{}", pad_n_bytes(format!("{contract_length:x}").as_str(), 2))
-        };
-        let contract_code_offset = if (bootstrap_code_size + constructor_length) < 256 {
-            format!(
-                "60{}",
-                pad_n_bytes(format!("{:x}", bootstrap_code_size + constructor_length).as_str(), 1)
-            )
-        } else {
-            bootstrap_code_size += 1;
-
-            format!(
-                "61{}",
-                pad_n_bytes(format!("{:x}", bootstrap_code_size + constructor_length).as_str(), 2)
-            )
-        };
-
-        let bootstrap_code = if has_custom_bootstrap {
-            String::default()
-        } else {
-            format!("{contract_size}80{contract_code_offset}3d393df3")
-        };
-
-        // Generate the final bytecode
-        let constructor_code = format!("{constructor_bytecode}{bootstrap_code}");
-        artifact.bytecode =
-            format!("{constructor_code}{main_bytecode}{constructor_args}").to_lowercase();
-        artifact.runtime = main_bytecode.to_lowercase();
-        artifact.file = file;
-        Ok(artifact.clone())
+        hex_args.join("")
     }
 
     /// Encode constructor arguments as ethers_core::abi::token::Token
@@ -807,16 +1372,8 @@ impl Codegen {
         let abi: Abi = ast.into();
 
         // Set the abi on self
-        let art: &Artifact = match &mut self.artifact {
-            Some(artifact) => {
-                artifact.abi = Some(abi.clone());
-                artifact
-            }
-            None => {
-                self.artifact = Some(Artifact { abi: Some(abi.clone()), ..Default::default() });
-                self.artifact.as_ref().unwrap()
-            }
-        };
+        let art: &mut Artifact = self.artifact.get_or_insert_with(Artifact::default);
+        art.set_abi(abi.clone());
 
         // If an output's specified, write the artifact out
         if let Some(o) = output {
@@ -828,3 +1385,438 @@ impl Codegen {
         Ok(abi)
     }
 }
+
+/// A macro being expanded by [Codegen::macro_to_bytecode]
+struct MacroExpansion<'a> {
+    macro_def: &'a MacroDefinition,
+    ir_bytes: std::vec::IntoIter<IRBytes>,
+    instructions: Vec<(usize, Instruction)>,
+    relocations: Relocations,
+    label_indices: LabelIndices,
+    utilized_tables: Vec<TableDefinition>,
+    activations: Vec<MacroActivation>,
+    functions: Vec<FunctionSection>,
+    start: usize,
+    offset: usize,
+    recursing_constructor: bool,
+    kind: Expansion,
+    /// Identifies the expansion for reuse, `None` if it can't be reused
+    key: Option<ExpansionKey<'a>>,
+    /// Whether the bytecode can be relocated by shifting its offsets
+    relocatable: bool,
+    /// Whether the macro locks the reentrancy guard, unlocking it before its code halts
+    guarded: bool,
+}
+
+impl<'a> MacroExpansion<'a> {
+    /// Starts expanding a macro at the given offset
+    fn new(
+        evm_version: &EVMVersion,
+        macro_def: &'a MacroDefinition,
+        offset: usize,
+        recursing_constructor: bool,
+        kind: Expansion,
+    ) -> Result<Self, CodegenError> {
+        // Check that all opcodes used are available in the targeted EVM version
+        Codegen::validate_opcodes(evm_version, &macro_def.statements)?;
+
+        // Get intermediate bytecode representation of the macro definition
+        let ir_bytes = macro_def.to_irbytecode()?.0;
+
+        Ok(Self {
+            macro_def,
+            ir_bytes: ir_bytes.into_iter(),
+            instructions: Vec::default(),
+            relocations: Relocations::new(),
+            label_indices: LabelIndices::new(),
+            utilized_tables: Vec::new(),
+            activations: Vec::new(),
+            functions: Vec::new(),
+            start: offset,
+            offset,
+            recursing_constructor,
+            kind,
+            key: None,
+            relocatable: true,
+            guarded: false,
+        })
+    }
+
+    /// Generates the checks of the macro's `only_caller` decorator flags, then locks the
+    /// reentrancy guard of a `#pragma nonreentrant` macro, ahead of its code
+    fn prologue(
+        &mut self,
+        evm_version: &EVMVersion,
+        contract: &Contract,
+    ) -> Result<(), CodegenError> {
+        let flags = self.macro_def.decorator.iter().flat_map(|d| d.flags.iter());
+        for flag in flags {
+            if let DecoratorFlag::OnlyCaller(owner, error) = flag {
+                self.check_caller(evm_version, contract, owner, error.as_deref())?;
+            }
+        }
+        if self.macro_def.has_pragma(MacroPragma::Nonreentrant) {
+            self.lock(evm_version, contract)?;
+        }
+        Ok(())
+    }
+
+    /// Reverts unless the caller is the address of the `owner` constant, or the address stored in
+    /// its slot if it's a storage pointer, with the custom error if one is named
+    fn check_caller(
+        &mut self,
+        evm_version: &EVMVersion,
+        contract: &Contract,
+        owner: &str,
+        error: Option<&str>,
+    ) -> Result<(), CodegenError> {
+        let span = self.macro_def.span.clone();
+        let mut address = vec![constant_gen(owner, self.macro_def, contract, span.clone())?];
+        if contract.storage_layout.iter().any(|s| s.name == owner) {
+            address.push(Instruction::op(Opcode::Sload, span.clone()));
+        }
+        let selector = match error {
+            Some(name) => match contract.find_error(name) {
+                Some(e) => Some(e.selector),
+                None => {
+                    tracing::error!(target: "codegen", "MISSING ERROR DEFINITION PASSED TO only_caller: \"{}\"", name);
+                    return Err(CodegenError {
+                        kind: CodegenErrorKind::MissingErrorDefinition(name.to_string()),
+                        span,
+                        token: None,
+                    })
+                }
+            },
+            None => None,
+        };
+        let instructions = caller_check(address, selector, self.offset, evm_version.eof(), &span)
+            .ok_or_else(|| CodegenError {
+            kind: CodegenErrorKind::InvalidArguments(format!(
+                "Offset of the caller check of \"{}\" doesn't fit in 2 bytes: {}",
+                self.macro_def.name, self.offset
+            )),
+            span,
+            token: None,
+        })?;
+        self.extend(instructions);
+        // The check jumps over its revert to an absolute offset
+        self.relocatable = false;
+        Ok(())
+    }
+
+    /// Locks the reentrancy guard of a `#pragma nonreentrant` macro ahead of its code
+    fn lock(&mut self, evm_version: &EVMVersion, contract: &Contract) -> Result<(), CodegenError> {
+        let span = self.macro_def.span.clone();
+        let slot = constant_gen(REENTRANCY_LOCK, self.macro_def, contract, span.clone())?;
+        let instructions =
+            reentrancy_lock(evm_version, slot, self.offset, &span).ok_or_else(|| CodegenError {
+                kind: CodegenErrorKind::InvalidArguments(format!(
+                    "Offset of the reentrancy guard of \"{}\" doesn't fit in 2 bytes: {}",
+                    self.macro_def.name, self.offset
+                )),
+                span,
+                token: None,
+            })?;
+        self.extend(instructions);
+        self.guarded = true;
+        // The lock jumps over its revert to an absolute offset
+        self.relocatable = false;
+        Ok(())
+    }
+
+    /// Unlocks the reentrancy guard, before the guarded code halts or once it's done
+    fn unlock(
+        &mut self,
+        evm_version: &EVMVersion,
+        contract: &Contract,
+    ) -> Result<(), CodegenError> {
+        let span = self.macro_def.span.clone();
+        let slot = constant_gen(REENTRANCY_LOCK, self.macro_def, contract, span.clone())?;
+        self.extend(reentrancy_unlock(evm_version, slot, &span));
+        Ok(())
+    }
+
+    /// Appends instructions at the current offset
+    fn extend(&mut self, instructions: Vec<Instruction>) {
+        for instruction in instructions {
+            let len = instruction.len();
+            self.instructions.push((self.offset, instruction));
+            self.offset += len;
+        }
+    }
+
+    /// Finishes the expansion once all of its IRBytes are generated, filling in its jumps.
+    ///
+    /// Returns the bytecode along with whether it can be relocated.
+    fn finish(
+        mut self,
+        evm_version: &EVMVersion,
+        contract: &'a Contract,
+        scope: &mut Vec<&'a MacroDefinition>,
+        mis: &mut Vec<(usize, MacroInvocation)>,
+        circular_codesize_invocations: &mut CircularCodeSizeIndices,
+        nesting_limit: usize,
+    ) -> Result<(BytecodeRes, bool), CodegenError> {
+        // We're done, let's pop off the macro invocation
+        if mis.pop().is_none() {
+            tracing::warn!(target: "codegen", "ATTEMPTED MACRO INVOCATION POP FAILED AT SCOPE: {}", scope.len());
+        }
+
+        // Record the bytecode of the invocation along with the labels it defines, which are the
+        // ones that weren't merged from the macros it invokes
+        let labels = self
+            .label_indices
+            .iter()
+            .filter(|(l, o)| !self.activations.iter().any(|a| a.labels.get(l.as_str()) == Some(o)))
+            .map(|(l, o)| (l.to_string(), *o))
+            .collect();
+        self.activations.push(MacroActivation {
+            name: self.macro_def.name.clone(),
+            start: self.start,
+            end: self.offset,
+            labels,
+        });
+
+        // Add functions (outlined macros) to the end of the bytecode if the scope length == 1
+        // (i.e., we're at the top level of the expansion), or into code sections of their own for
+        // EOF code
+        if scope.len() == 1 && evm_version.eof() {
+            self.relocatable = false;
+            self.functions =
+                Codegen::function_sections(evm_version, contract, scope, mis, nesting_limit)?;
+        } else if scope.len() == 1 {
+            self.relocatable = false;
+            self.instructions = Codegen::append_functions(
+                evm_version,
+                contract,
+                scope,
+                &mut self.offset,
+                mis,
+                &mut self.relocations,
+                &mut self.label_indices,
+                &mut self.activations,
+                self.instructions,
+                nesting_limit,
+            )?;
+        } else {
+            // If the scope length is > 1, we're processing a child macro. Since we're done
+            // with it, it can be popped.
+            scope.pop();
+        }
+
+        // Bind jumps to the labels defined so far, the placeholders are patched once all of the
+        // bytecode is generated
+        Codegen::resolve_labels(&mut self.relocations, &self.label_indices);
+
+        // Fill in circular codesize invocations
+        // Workout how to increase the offset the correct amount within here if it is longer than 2
+        // bytes
+        let instructions = Codegen::fill_circular_codesize_invocations(
+            self.instructions,
+            &mut self.relocations,
+            circular_codesize_invocations,
+            &self.macro_def.name,
+        )?;
+
+        let res = BytecodeRes {
+            instructions,
+            label_indices: self.label_indices,
+            relocations: self.relocations,
+            utilized_tables: self.utilized_tables,
+            activations: self.activations,
+            functions: self.functions,
+        };
+        Ok((res, self.relocatable))
+    }
+
+    /// Merges the bytecode of a finished child expansion into this one
+    fn merge(&mut self, mut res: BytecodeRes, kind: Expansion) {
+        let size = res.size();
+        match kind {
+            Expansion::Invocation => {
+                tracing::debug!(target: "codegen", "Unmatched jumps: {:?}", res.unmatched_jumps().map(|uj| uj.label.clone()).collect::<Vec<Symbol>>());
+                self.relocations.append(&mut res.relocations);
+                // Labels defined so far shadow the labels of the invoked macro
+                for (label, offset) in res.label_indices {
+                    self.label_indices.entry(label).or_insert(offset);
+                }
+                // Tables used by several macros are appended to the bytecode once
+                for table in res.utilized_tables {
+                    if !self.utilized_tables.contains(&table) {
+                        self.utilized_tables.push(table);
+                    }
+                }
+                self.activations.append(&mut res.activations);
+
+                // Increase offset by byte length of the expanded macro
+                self.offset += size;
+                // Add the macro's bytecode to the final result
+                self.instructions.append(&mut res.instructions);
+            }
+            Expansion::Codesize(starting_offset) => {
+                let push = Instruction::push_value(&size.to_be_bytes(), AstSpan(vec![]));
+
+                self.offset += push.len();
+                self.instructions.push((starting_offset, push));
+            }
+        }
+    }
+}
+
+/// Identifies an inlined macro invocation by the macro and the literal arguments it's passed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ExpansionKey<'a>(&'a str, Vec<Literal>);
+
+impl<'a> ExpansionKey<'a> {
+    /// The key of an invocation, `None` if any argument isn't a literal since those are resolved
+    /// through the invoking macros
+    fn from_invocation(macro_def: &'a MacroDefinition, mi: &MacroInvocation) -> Option<Self> {
+        let args = mi
+            .args
+            .iter()
+            .map(|arg| match arg {
+                MacroArg::Literal(l) => Some(*l),
+                _ => None,
+            })
+            .collect::<Option<Vec<Literal>>>()?;
+        Some(Self(&macro_def.name, args))
+    }
+}
+
+/// Whether the bytecode generated for a statement is the same wherever, and inside of whichever
+/// macros, it is generated, once relocated. Offsets are only relocated through [Relocation]s, so
+/// statements are only relocatable if they push no offset, or push it through a relocation.
+///
+/// Every statement and builtin is listed, so a new one can't be reused without being classified.
+fn is_relocatable(s: &Statement, contract: &Contract) -> bool {
+    match &s.ty {
+        StatementType::Literal(_) |
+        StatementType::Opcode(_) |
+        StatementType::Code(_) |
+        StatementType::Constant(_) |
+        StatementType::ArgCall(_) |
+        StatementType::LabelCall(_) |
+        StatementType::Assertion(_) => true,
+        // Labels are bound to the absolute offsets of their jumps, and offset assertions have to
+        // be checked at every offset
+        StatementType::Label(_) | StatementType::OffsetAssertion(_) => false,
+        // Outlined macros are jumped to and back from their absolute offsets
+        StatementType::MacroInvocation(mi) => {
+            !contract.macros.iter().any(|m| m.is_outlined() && m.name == mi.macro_name)
+        }
+        StatementType::BuiltinFunctionCall(bf) => is_relocatable_builtin(&bf.kind),
+    }
+}
+
+/// Whether the bytecode generated for a builtin is the same wherever it is generated, once
+/// relocated. `__PC`, `__CALLDATA_CHECK` and the precompile calls push absolute offsets, and
+/// `__codesize` depends on the macros being expanded.
+fn is_relocatable_builtin(kind: &BuiltinFunctionKind) -> bool {
+    match kind {
+        BuiltinFunctionKind::Tablesize |
+        BuiltinFunctionKind::Tablestart |
+        BuiltinFunctionKind::FunctionSignature |
+        BuiltinFunctionKind::EventHash |
+        BuiltinFunctionKind::Error |
+        BuiltinFunctionKind::RightPad |
+        BuiltinFunctionKind::DynConstructorArg |
+        BuiltinFunctionKind::Verbatim |
+        BuiltinFunctionKind::CalldataLoadArg |
+        BuiltinFunctionKind::Erc7201 |
+        BuiltinFunctionKind::Eip1967ImplSlot |
+        BuiltinFunctionKind::Eip1967AdminSlot |
+        BuiltinFunctionKind::Eip1967BeaconSlot |
+        BuiltinFunctionKind::Assert |
+        BuiltinFunctionKind::GasLimit |
+        BuiltinFunctionKind::StrLit |
+        BuiltinFunctionKind::Offset |
+        BuiltinFunctionKind::CodeSlice |
+        BuiltinFunctionKind::DataOffset |
+        BuiltinFunctionKind::DataSize |
+        BuiltinFunctionKind::TypeHash |
+        BuiltinFunctionKind::DomainSeparator => true,
+        BuiltinFunctionKind::Codesize |
+        BuiltinFunctionKind::Pc |
+        BuiltinFunctionKind::CalldataCheck |
+        BuiltinFunctionKind::Ecrecover |
+        BuiltinFunctionKind::Sha256 |
+        BuiltinFunctionKind::Modexp |
+        BuiltinFunctionKind::EcAdd |
+        BuiltinFunctionKind::EcMul |
+        BuiltinFunctionKind::EcPairing |
+        BuiltinFunctionKind::PointEvaluation => false,
+    }
+}
+
+/// Relocates the bytecode of an expansion from the offset it was generated at to another
+fn relocate(res: &BytecodeRes, from: usize, to: usize) -> BytecodeRes {
+    let shift = |offset: usize| offset - from + to;
+    let shift_relocation = |r: &Relocation| Relocation {
+        offset: shift(r.offset),
+        target: r.target.map(shift),
+        ..r.clone()
+    };
+    BytecodeRes {
+        instructions: res
+            .instructions
+            .iter()
+            .map(|(offset, i)| (shift(*offset), i.clone()))
+            .collect(),
+        label_indices: res
+            .label_indices
+            .iter()
+            .map(|(l, offset)| (l.clone(), shift(*offset)))
+            .collect(),
+        relocations: res.relocations.iter().map(shift_relocation).collect(),
+        utilized_tables: res.utilized_tables.clone(),
+        activations: res
+            .activations
+            .iter()
+            .map(|a| MacroActivation {
+                name: a.name.clone(),
+                start: shift(a.start),
+                end: shift(a.end),
+                labels: a.labels.iter().map(|(l, o)| (l.clone(), shift(*o))).collect(),
+            })
+            .collect(),
+        functions: res.functions.clone(),
+    }
+}
+
+/// Lowers a jump to the label pushed right before it into a relative jump, for EOF code
+///
+/// Returns the relative jump replacing the push of the label along with its offset, `None` if the
+/// destination of the jump isn't a label pushed right before it.
+fn relative_jump(
+    instructions: &mut Vec<(usize, Instruction)>,
+    relocations: &mut Relocations,
+    jump: &Instruction,
+) -> Option<(usize, Instruction)> {
+    let opcode = match jump.opcode {
+        Some(Opcode::Jump) => Opcode::Rjump,
+        Some(Opcode::Jumpi) => Opcode::Rjumpi,
+        _ => return None,
+    };
+    let offset = match instructions.last() {
+        Some((offset, push)) if push.opcode == Some(Opcode::Push2) && push.placeholder == 2 => {
+            *offset
+        }
+        _ => return None,
+    };
+    let relocation = relocations
+        .iter_mut()
+        .rev()
+        .find(|r| r.kind == RelocationKind::Label && !r.negated && r.offset == offset + 1)?;
+    relocation.relative = true;
+    instructions.pop();
+    let rjump = Instruction { opcode: Some(opcode), placeholder: 2, ..jump.clone() };
+    Some((offset, rjump))
+}
+
+/// The big endian bytes of `value` padded to `width` bytes, `None` if it doesn't fit
+fn be_bytes(value: usize, width: usize) -> Option<Vec<u8>> {
+    let significant = value.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect::<Vec<u8>>();
+    let mut bytes = vec![0; width.checked_sub(significant.len())?];
+    bytes.extend(significant);
+    Some(bytes)
+}
@@ -18,16 +18,27 @@ fn constructs_valid_abi() {
         span: AstSpan(vec![]),
         outlined: false,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     let contract = Contract {
         macros: vec![constructor],
         invocations: vec![],
         imports: vec![],
+        abi_imports: vec![],
         constants: Arc::new(Mutex::new(vec![])),
         errors: vec![],
         functions: vec![],
         events: vec![],
+        structs: vec![],
+        merkle_trees: vec![],
         tables: vec![],
+        contracts: vec![],
+        memory_layout: vec![],
+        storage_layout: vec![],
+        pragmas: vec![],
+        suppressions: vec![],
     };
 
     // Generate the abi from the contract
@@ -58,16 +69,27 @@ fn missing_constructor_fails() {
         span: AstSpan(vec![]),
         outlined: false,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     let contract = Contract {
         macros: vec![],
         invocations: vec![],
         imports: vec![],
+        abi_imports: vec![],
         constants: Arc::new(Mutex::new(vec![])),
         errors: vec![],
         functions: vec![],
         events: vec![],
+        structs: vec![],
+        merkle_trees: vec![],
         tables: vec![],
+        contracts: vec![],
+        memory_layout: vec![],
+        storage_layout: vec![],
+        pragmas: vec![],
+        suppressions: vec![],
     };
 
     // Generate the abi from the contract
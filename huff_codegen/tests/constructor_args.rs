@@ -137,3 +137,20 @@ fn encode_missing_brackets_array_constructor_args() {
     assert_eq!(results[4], expected_array);
     assert_eq!(results[5], expected_array);
 }
+
+#[test]
+fn encode_json_constructor_args() {
+    // Json arrays may nest, hold strings with spaces and commas, and mix kinds as tuples
+    let args = vec![r#"[1, ["a, b c", 2], [[1, 2], [3]]]"#.to_string()];
+
+    let results = Codegen::encode_constructor_args(args);
+    let uint = |n: u64| Token::Uint(U256::from(n));
+    assert_eq!(
+        results[0],
+        Token::Tuple(vec![
+            uint(1),
+            Token::Tuple(vec![Token::String("a, b c".to_string()), uint(2)]),
+            Token::Array(vec![Token::Array(vec![uint(1), uint(2)]), Token::Array(vec![uint(3)])]),
+        ])
+    );
+}
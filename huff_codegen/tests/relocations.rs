@@ -0,0 +1,110 @@
+use huff_codegen::*;
+use huff_utils::prelude::*;
+
+fn resolved(label: &str, push_offset: usize, target: usize) -> Relocation {
+    Relocation { target: Some(target), ..Relocation::label(label, push_offset, AstSpan(vec![])) }
+}
+
+fn span() -> AstSpan {
+    AstSpan(vec![])
+}
+
+fn encode(instructions: &[(usize, Instruction)]) -> String {
+    instructions.iter().map(|(_, i)| i.to_string()).collect()
+}
+
+#[test]
+fn test_apply_relocations_patches_placeholders() {
+    let mut instructions = vec![
+        (0, Instruction::push(vec![0x01], span())),
+        (2, Instruction::push_placeholder(2, span())),
+        (5, Instruction::op(Opcode::Jumpi, span())),
+        (6, Instruction::op(Opcode::Jumpdest, span())),
+    ];
+    assert_eq!(encode(&instructions), "600161xxxx575b");
+
+    let relocations = vec![resolved("done", 2, 6)];
+    Codegen::apply_relocations(&mut instructions, &relocations).unwrap();
+    assert_eq!(encode(&instructions), "6001610006575b");
+}
+
+#[test]
+fn test_apply_relocations_requires_placeholder() {
+    let mut instructions = vec![
+        (0, Instruction::push(vec![0x01], span())),
+        (2, Instruction::push(vec![0x00, 0x00], span())),
+    ];
+    let relocations = vec![resolved("done", 2, 6)];
+
+    match Codegen::apply_relocations(&mut instructions, &relocations) {
+        Err(CodegenError { kind: CodegenErrorKind::InvalidRelocation(label, _), .. }) => {
+            assert_eq!(label, "done")
+        }
+        res => panic!("Expected an InvalidRelocation error, got {res:?}"),
+    }
+}
+
+#[test]
+fn test_apply_relocations_rejects_wide_targets() {
+    let mut instructions = vec![(0, Instruction::push_placeholder(2, span()))];
+    let relocations = vec![resolved("far", 0, 0x10000)];
+
+    match Codegen::apply_relocations(&mut instructions, &relocations) {
+        Err(CodegenError { kind: CodegenErrorKind::InvalidRelocation(label, _), .. }) => {
+            assert_eq!(label, "far")
+        }
+        res => panic!("Expected an InvalidRelocation error, got {res:?}"),
+    }
+}
+
+#[test]
+fn test_resolve_labels_skips_resolved_relocations() {
+    let mut relocations = vec![
+        Relocation::label("a", 0, AstSpan(vec![])),
+        resolved("b", 3, 9),
+        Relocation::table("a", 6, AstSpan(vec![])),
+    ];
+    let label_indices = LabelIndices::from([(Symbol::new("a"), 12), (Symbol::new("b"), 15)]);
+
+    Codegen::resolve_labels(&mut relocations, &label_indices);
+    assert_eq!(relocations[0].target, Some(12));
+    assert_eq!(relocations[1].target, Some(9));
+    assert_eq!(relocations[2].target, None);
+}
+
+#[test]
+fn test_apply_relocations_subtracts_negated() {
+    let mut instructions = vec![(0, Instruction::push_placeholder(2, span()))];
+    let relocations =
+        vec![resolved("end", 0, 0x20), Relocation { negated: true, ..resolved("start", 0, 0x08) }];
+    Codegen::apply_relocations(&mut instructions, &relocations).unwrap();
+    assert_eq!(encode(&instructions), "610018");
+}
+
+#[test]
+fn test_apply_relocations_relative() {
+    let rjump =
+        |offset| (offset, Instruction { placeholder: 2, ..Instruction::op(Opcode::Rjump, span()) });
+    let mut instructions = vec![
+        (0, Instruction::op(Opcode::Jumpdest, span())),
+        rjump(1),
+        rjump(4),
+        (7, Instruction::op(Opcode::Jumpdest, span())),
+    ];
+    // Backwards from the end of the first `rjump`, forwards from the end of the second
+    let relocations = vec![
+        Relocation { relative: true, ..resolved("start", 1, 0) },
+        Relocation { relative: true, ..resolved("end", 4, 7) },
+    ];
+    Codegen::apply_relocations(&mut instructions, &relocations).unwrap();
+    assert_eq!(encode(&instructions), "5be0fffce000005b");
+
+    let mut instructions = vec![rjump(0)];
+    let relocations = vec![Relocation { relative: true, ..resolved("far", 0, 0x8003) }];
+    match Codegen::apply_relocations(&mut instructions, &relocations) {
+        Err(CodegenError { kind: CodegenErrorKind::InvalidRelocation(_, reason), .. }) => {
+            assert_eq!(reason, "relative offset 32768 doesn't fit in 2 bytes")
+        }
+        res => panic!("Expected an InvalidRelocation error, got {res:?}"),
+    }
+}
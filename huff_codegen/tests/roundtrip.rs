@@ -0,0 +1,82 @@
+use huff_codegen::Codegen;
+use huff_utils::prelude::*;
+use proptest::prelude::*;
+
+/// A statement in a generated macro body.
+#[derive(Debug, Clone)]
+enum Op {
+    Opcode(Opcode),
+    Literal(Vec<u8>),
+}
+
+/// Every non-push opcode, pushes are covered by literals.
+fn opcodes() -> Vec<Opcode> {
+    OPCODES.iter().map(|name| OPCODES_MAP[name]).filter(|o| !o.is_push()).collect()
+}
+
+/// Generates a literal as its minimal big endian bytes.
+fn literal() -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(any::<u8>(), 1..=32).prop_map(|bytes| {
+        let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len() - 1);
+        bytes[start..].to_vec()
+    })
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        prop::sample::select(opcodes()).prop_map(Op::Opcode),
+        literal().prop_map(Op::Literal),
+    ]
+}
+
+fn to_literal(bytes: &[u8]) -> Literal {
+    let mut literal = [0u8; 32];
+    literal[32 - bytes.len()..].copy_from_slice(bytes);
+    literal
+}
+
+fn compile(main: MacroDefinition) -> String {
+    let contract = ContractBuilder::new().macro_definition(main).build();
+    Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap()
+}
+
+proptest! {
+    #[test]
+    fn test_statements_roundtrip_through_disassembly(ops in prop::collection::vec(op(), 0..64)) {
+        let main = ops.iter().fold(MacroDefinitionBuilder::new("MAIN"), |builder, op| match op {
+            Op::Opcode(o) => builder.opcode(*o),
+            Op::Literal(bytes) => builder.literal(to_literal(bytes)),
+        });
+
+        let disassembled = disassemble(&compile(main.build())).unwrap();
+        prop_assert_eq!(disassembled.len(), ops.len());
+        for (op, dis) in ops.iter().zip(&disassembled) {
+            match op {
+                Op::Opcode(o) => {
+                    prop_assert_eq!(format!("{:02x}", dis.byte), o.string());
+                    prop_assert!(dis.immediate.is_empty());
+                }
+                Op::Literal(bytes) => {
+                    prop_assert_eq!(dis.byte as usize, 0x5f + bytes.len());
+                    prop_assert_eq!(&dis.immediate, bytes);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_label_calls_resolve_to_jumpdest(padding in 0usize..300) {
+        let main = (0..padding)
+            .fold(MacroDefinitionBuilder::new("MAIN").label_call("target").opcode(Opcode::Jump), |b, _| {
+                b.opcode(Opcode::Invalid)
+            })
+            .label("target")
+            .opcode(Opcode::Stop);
+
+        let disassembled = disassemble(&compile(main.build())).unwrap();
+        let target = disassembled[0].immediate.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        let jumpdest = disassembled.iter().find(|op| op.offset == target).unwrap();
+        prop_assert_eq!(jumpdest.opcode, Some(Opcode::Jumpdest));
+        prop_assert_eq!(disassembled.last().unwrap().opcode, Some(Opcode::Stop));
+    }
+}
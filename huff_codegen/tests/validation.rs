@@ -0,0 +1,71 @@
+use huff_codegen::*;
+use huff_utils::prelude::*;
+use std::sync::Arc;
+
+fn span(start: usize, end: usize) -> AstSpan {
+    AstSpan(vec![Span { start, end, file: None }])
+}
+
+#[test]
+fn test_unfilled_placeholder_is_malformed() {
+    // A table that was never utilized leaves its `__tablestart` placeholder unfilled
+    let res = BytecodeRes {
+        instructions: vec![
+            (0, Instruction::push(vec![0x01], span(0, 4))),
+            (2, Instruction::push_placeholder(2, span(5, 24))),
+        ],
+        relocations: vec![Relocation::table("MISSING", 2, span(5, 24))],
+        ..Default::default()
+    };
+
+    match Codegen::gen_table_bytecode(res) {
+        Err(CodegenError {
+            kind: CodegenErrorKind::MalformedBytecode(reason), span: s, ..
+        }) => {
+            assert_eq!(reason, "unfilled placeholder in \"61xxxx\" at offset 2");
+            assert_eq!(s, span(5, 24));
+        }
+        res => panic!("Expected a MalformedBytecode error, got {res:?}"),
+    }
+}
+
+#[test]
+fn test_validate_bytecode() {
+    assert!(Codegen::validate_bytecode("runtime bytecode", "", span(0, 0)).is_ok());
+    assert!(Codegen::validate_bytecode("runtime bytecode", "6001aB", span(0, 0)).is_ok());
+
+    match Codegen::validate_bytecode("runtime bytecode", "600", span(0, 0)) {
+        Err(CodegenError { kind: CodegenErrorKind::MalformedBytecode(reason), .. }) => {
+            assert_eq!(reason, "runtime bytecode has an odd number of hex characters (3)")
+        }
+        res => panic!("Expected a MalformedBytecode error, got {res:?}"),
+    }
+    match Codegen::validate_bytecode("runtime bytecode", "60016g02", span(0, 0)) {
+        Err(CodegenError { kind: CodegenErrorKind::MalformedBytecode(reason), .. }) => {
+            assert_eq!(reason, "runtime bytecode contains \"g\" at byte 2")
+        }
+        res => panic!("Expected a MalformedBytecode error, got {res:?}"),
+    }
+}
+
+#[test]
+fn test_churn_rejects_malformed_bytecode() {
+    let file = Arc::new(FileSource { path: "main.huff".to_string(), ..Default::default() });
+
+    let err = Codegen::new().churn(Arc::clone(&file), vec![], "60016", "", false).unwrap_err();
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::MalformedBytecode(
+            "runtime bytecode has an odd number of hex characters (5)".to_string()
+        )
+    );
+    assert_eq!(err.span.0[0].file, Some(file.clone()));
+
+    let err = Codegen::new().churn(file, vec![], "6001", "33z0", false).unwrap_err();
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::MalformedBytecode(
+            "constructor bytecode contains \"z\" at byte 1".to_string()
+        )
+    );
+}
@@ -105,8 +105,8 @@ fn codegen_erc20_benchmark(c: &mut Criterion) {
     // Isolate codegen to benchmark
     c.bench_function("Codegen: ERC-20", |b| b.iter(|| {
         // Create main and constructor bytecode
-        let main_bytecode = Codegen::generate_main_bytecode(&contract, None).unwrap();
-        let (constructor_bytecode, has_custom_bootstrap) = Codegen::generate_constructor_bytecode(&contract, None).unwrap();
+        let main_bytecode = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+        let (constructor_bytecode, has_custom_bootstrap) = Codegen::generate_constructor_bytecode(&EVMVersion::default(), &contract, None).unwrap();
 
         // Churn
         let mut cg = Codegen::new();
@@ -150,8 +150,8 @@ fn erc20_compilation_benchmark(c: &mut Criterion) {
         contract.derive_storage_pointers();
 
         // Create main and constructor bytecode
-        let main_bytecode = Codegen::generate_main_bytecode(&contract, None).unwrap();
-        let (constructor_bytecode, has_custom_bootstrap) = Codegen::generate_constructor_bytecode(&contract, None).unwrap();
+        let main_bytecode = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+        let (constructor_bytecode, has_custom_bootstrap) = Codegen::generate_constructor_bytecode(&EVMVersion::default(), &contract, None).unwrap();
 
         // Churn
         let mut cg = Codegen::new();
@@ -195,8 +195,8 @@ fn erc721_compilation_benchmark(c: &mut Criterion) {
         contract.derive_storage_pointers();
 
         // Create main and constructor bytecode
-        let main_bytecode = Codegen::generate_main_bytecode(&contract, None).unwrap();
-        let (constructor_bytecode, has_custom_bootstrap) = Codegen::generate_constructor_bytecode(&contract, None).unwrap();
+        let main_bytecode = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+        let (constructor_bytecode, has_custom_bootstrap) = Codegen::generate_constructor_bytecode(&EVMVersion::default(), &contract, None).unwrap();
 
         // Churn
         let mut cg = Codegen::new();
@@ -0,0 +1,191 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use huff_codegen::Codegen;
+use huff_core::Compiler;
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::{
+    file_provider::FileSystemFileProvider,
+    files,
+    prelude::{EVMVersion, FileSource, FullFileSource, Token},
+};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+/// Real-world contracts from the huff-examples submodule.
+const EXAMPLES: [(&str, &str); 2] = [
+    ("ERC-20", "../huff-examples/erc20/contracts/ERC20.huff"),
+    ("ERC-721", "../huff-examples/erc721/contracts/ERC721.huff"),
+];
+
+/// Number of functions in the generated dispatch contracts.
+const DISPATCH_SIZES: [usize; 3] = [16, 64, 128];
+
+/// Generates a contract dispatching `functions` selectors, each to a macro that invokes a chain
+/// of nested helper macros to exercise recursive codegen.
+fn dispatch_contract(functions: usize) -> String {
+    let mut source = String::new();
+    for i in 0..functions {
+        source.push_str(&format!("#define function fn{i}(uint256) nonpayable returns (uint256)\n"));
+    }
+    source.push_str(
+        r#"
+#define constant SLOT = FREE_STORAGE_POINTER()
+
+#define macro LOAD() = takes(0) returns(1) {
+    0x04 calldataload
+}
+
+#define macro HASH() = takes(1) returns(1) {
+    LOAD() add 0x00 mstore 0x20 0x00 sha3
+}
+
+#define macro STORE() = takes(1) returns(0) {
+    HASH() [SLOT] sstore
+}
+"#,
+    );
+    for i in 0..functions {
+        source.push_str(&format!(
+            "\n#define macro FN{i}() = takes(0) returns(0) {{\n    {i:#x} STORE()\n    0x00 0x00 return\n}}\n"
+        ));
+    }
+
+    source.push_str(
+        "\n#define macro MAIN() = takes(0) returns(0) {\n    0x00 calldataload 0xe0 shr\n",
+    );
+    for i in 0..functions {
+        source.push_str(&format!("    dup1 __FUNC_SIG(fn{i}) eq fn{i}_jump jumpi\n"));
+    }
+    source.push_str("    0x00 dup1 revert\n");
+    for i in 0..functions {
+        source.push_str(&format!("    fn{i}_jump:\n        FN{i}()\n"));
+    }
+    source.push_str("}\n");
+    source
+}
+
+/// Reads and flattens a contract from the filesystem, `None` if it doesn't exist.
+fn flattened_example(path: &str) -> Option<String> {
+    if !PathBuf::from(path).exists() {
+        eprintln!("Skipping {path}, run `git submodule update` to fetch huff-examples");
+        return None
+    }
+    let file_provider = Arc::new(FileSystemFileProvider::new());
+    let file_source = Compiler::fetch_sources(vec![PathBuf::from(path)], file_provider.clone())
+        .remove(0)
+        .unwrap();
+    let recursed = Compiler::recurse_deps(
+        Arc::clone(&file_source),
+        &files::Remapper::new("./"),
+        file_provider,
+    )
+    .unwrap();
+    Some(FileSource::fully_flatten(recursed).0)
+}
+
+/// Every benchmarked source as `(name, flattened source)`.
+fn sources() -> Vec<(String, String)> {
+    let mut sources = EXAMPLES
+        .iter()
+        .filter_map(|(name, path)| flattened_example(path).map(|s| (name.to_string(), s)))
+        .collect::<Vec<_>>();
+    sources.extend(DISPATCH_SIZES.iter().map(|n| (format!("Dispatch-{n}"), dispatch_contract(*n))));
+    sources
+}
+
+fn lex(source: &str) -> Vec<Token> {
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    Lexer::new(full_source).map(|t| t.unwrap()).collect()
+}
+
+fn lexer_throughput_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Lexer tokens/sec");
+    for (name, source) in sources() {
+        group.throughput(Throughput::Elements(lex(&source).len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(&name), &source, |b, source| {
+            b.iter(|| lex(source))
+        });
+    }
+    group.finish();
+}
+
+fn parser_throughput_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Parser tokens/sec");
+    for (name, source) in sources() {
+        let tokens = lex(&source);
+        group.throughput(Throughput::Elements(tokens.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(&name), &tokens, |b, tokens| {
+            b.iter(|| {
+                let mut contract = Parser::new(tokens.clone(), None).parse().unwrap();
+                contract.derive_storage_pointers();
+                contract
+            })
+        });
+    }
+    group.finish();
+}
+
+fn codegen_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Codegen");
+    for (name, source) in sources() {
+        let mut contract = Parser::new(lex(&source), None).parse().unwrap();
+        contract.derive_storage_pointers();
+        group.bench_with_input(BenchmarkId::from_parameter(&name), &contract, |b, contract| {
+            b.iter(|| {
+                Codegen::generate_main_bytecode(&EVMVersion::default(), contract, None).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn end_to_end_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("End-to-end compilation");
+    for (name, path) in EXAMPLES {
+        if !PathBuf::from(path).exists() {
+            continue
+        }
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let compiler = Compiler::new(
+                    Arc::new(vec![path.to_string()]),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    false,
+                    false,
+                );
+                compiler.execute().unwrap()
+            })
+        });
+    }
+    for n in DISPATCH_SIZES {
+        let file = format!("Dispatch{n}.huff");
+        let sources = HashMap::from([(file.clone(), dispatch_contract(n))]);
+        group.bench_function(format!("Dispatch-{n}"), |b| {
+            b.iter(|| {
+                let compiler = Compiler::new_in_memory(
+                    Arc::new(vec![file.clone()]),
+                    sources.clone(),
+                    None,
+                    None,
+                    None,
+                    None,
+                    false,
+                );
+                compiler.execute().unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    lexer_throughput_benchmark,
+    parser_throughput_benchmark,
+    codegen_benchmark,
+    end_to_end_benchmark
+);
+criterion_main!(benches);
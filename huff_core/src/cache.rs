@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
-use huff_utils::prelude::{Artifact, FileSource, OutputLocation};
+use crate::{output::MANIFEST_FILE, VERSION};
+use huff_utils::prelude::{Artifact, CompilerSettings, FileSource, OutputLocation};
 use walkdir::WalkDir;
 
 /// Parallelized Artifact Cachcing
@@ -8,11 +9,12 @@ pub fn get_cached_artifacts(
     files: &[Arc<FileSource>],
     out: &OutputLocation,
     constructor_args: String,
+    settings: &CompilerSettings,
 ) -> Option<Vec<Arc<Artifact>>> {
     // Check if the file artifacts are already generated the the default "./artifacts/" directory or
     // the specified output dir
     let artifacts: Vec<(Arc<FileSource>, Artifact)> =
-        resolve_existing_artifacts(files, out, constructor_args)?;
+        resolve_existing_artifacts(files, out, constructor_args, settings)?;
 
     // Return the artifacts if cached
     Some(artifacts.into_iter().map(|(_, artifact)| Arc::new(artifact)).collect())
@@ -23,12 +25,16 @@ pub fn resolve_existing_artifacts(
     files: &[Arc<FileSource>],
     output: &OutputLocation,
     constructor_args: String,
+    settings: &CompilerSettings,
 ) -> Option<Vec<(Arc<FileSource>, Artifact)>> {
     let mut artifacts: Vec<(Arc<FileSource>, Artifact)> = Vec::new();
 
     // Transform file sources into a hashmap of path to file source
     let mut file_sources: std::collections::HashMap<String, Arc<FileSource>> =
         files.iter().map(|f| (f.path.clone().to_lowercase(), Arc::clone(f))).collect();
+    // File sources already matched to an artifact
+    let mut matched: std::collections::HashMap<String, Arc<FileSource>> =
+        std::collections::HashMap::new();
 
     // If outputdir is not specified, use the default "./artifacts/" directory
     let output_dir = if !output.0.is_empty() { &*output.0 } else { "./artifacts" };
@@ -38,7 +44,7 @@ pub fn resolve_existing_artifacts(
     for entry in WalkDir::new(output_dir)
         .into_iter()
         .filter_map(Result::ok)
-        .filter(|e| !e.file_type().is_dir())
+        .filter(|e| !e.file_type().is_dir() && e.file_name() != MANIFEST_FILE)
     {
         // Are we expecting this file to be compiled
         let formatted_path = entry
@@ -48,11 +54,21 @@ pub fn resolve_existing_artifacts(
             .replace(".json", "")
             .replace(output_dir, ".")
             .to_lowercase();
-        let expected = file_sources.remove(&formatted_path);
 
         // Try to read the file into an artifact
         match serde_json::from_str::<Artifact>(&std::fs::read_to_string(entry.path()).unwrap()) {
             Ok(artifact) => {
+                // A file with multiple contracts has an artifact per contract, named after the
+                // contract, so they're matched to their source by the path they were compiled from
+                let key = match artifact.contract_name {
+                    Some(_) => artifact.file.path.to_lowercase(),
+                    None => formatted_path,
+                };
+                let expected = file_sources.remove(&key).or_else(|| matched.get(&key).cloned());
+                if let Some(expected_fs) = &expected {
+                    matched.insert(key, Arc::clone(expected_fs));
+                }
+
                 // If we expected compilation, the sources must match
                 match expected {
                     Some(expected_fs) => {
@@ -60,6 +76,13 @@ pub fn resolve_existing_artifacts(
                             tracing::warn!(target: "core", "Mismatched Constructor Args for Cached Artifact \"{}\"", artifact.file.path);
                             return None
                         }
+                        let metadata = artifact.metadata.as_ref();
+                        if metadata.map(|m| (m.version.as_str(), &m.settings)) !=
+                            Some((VERSION, settings))
+                        {
+                            tracing::warn!(target: "core", "Cache Resolution Failed: \"{}\" Artifact Compiled With Other Settings", artifact.file.path);
+                            return None
+                        }
                         if artifact.file.source != expected_fs.source {
                             tracing::warn!(target: "core", "Cache Resolution Failed: \"{}\" Artifact Outdated", artifact.file.path);
                             return None
@@ -75,7 +98,7 @@ pub fn resolve_existing_artifacts(
             Err(e) => {
                 // If the artifact is invalid, log the error and continue
                 tracing::error!(target: "core", "Invalid artifact file: {}", e);
-                if expected.is_some() {
+                if file_sources.contains_key(&formatted_path) {
                     tracing::error!(target: "core", "Expected artifact file to be compiled: {}", entry.path().display());
                     return None
                 }
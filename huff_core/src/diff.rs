@@ -0,0 +1,241 @@
+//! ## Bytecode Diffs
+//!
+//! Compares the runtime bytecode of two artifacts instruction by instruction, to review what a
+//! change of the source does to the generated code.
+//!
+//! Rather than diffing hex, both bytecodes are disassembled and split into sections at the
+//! boundaries of macro invocations and labels, using the symbols of the artifacts. Sections are
+//! aligned by the macro and label they belong to, and only the instructions of aligned sections
+//! are compared, so a change in one macro doesn't misalign the rest of the diff. Jump
+//! destinations are shown as the labels they jump to, so code that only moved doesn't show up as
+//! changed.
+//!
+//! ```text
+//! @@ MAIN::transfer @@
+//!   0x0012 0x0014 jumpdest
+//! - 0x0013        push1 0x01
+//! +        0x0015 push1 0x02
+//! ```
+
+use ethers_core::utils::hex;
+use huff_utils::prelude::{disassemble, Artifact, MacroActivation, Opcode};
+use std::{collections::BTreeMap, fmt};
+
+/// The section of bytes outside of any macro invocation, i.e. appended tables
+pub const TABLES_SECTION: &str = "<tables>";
+
+/// Whether a line of a diff is in both bytecodes, or only in one of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffTag {
+    /// The instruction is in both bytecodes
+    Unchanged,
+    /// The instruction is only in the old bytecode
+    Removed,
+    /// The instruction is only in the new bytecode
+    Added,
+}
+
+/// An instruction of a [SectionDiff]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    /// Whether the instruction was added, removed or is unchanged
+    pub tag: DiffTag,
+    /// The offset of the instruction in the old bytecode, if it's in it
+    pub old_offset: Option<usize>,
+    /// The offset of the instruction in the new bytecode, if it's in it
+    pub new_offset: Option<usize>,
+    /// The disassembled instruction, with jump destinations shown as labels
+    pub instruction: String,
+}
+
+/// The instructions of a changed section, i.e. `MAIN` or `MAIN::label`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionDiff {
+    /// The macro the section is generated by, followed by the label it starts at, if any
+    pub section: String,
+    /// The instructions of the section in either bytecode
+    pub lines: Vec<DiffLine>,
+}
+
+/// The sections that differ between the runtime bytecode of two artifacts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BytecodeDiff {
+    /// The changed sections, in the order of the bytecode
+    pub sections: Vec<SectionDiff>,
+}
+
+impl BytecodeDiff {
+    /// Diff the runtime bytecode of two artifacts.
+    pub fn new(old: &Artifact, new: &Artifact) -> Result<Self, String> {
+        let (old, new) = (sections(old)?, sections(new)?);
+
+        let mut diff = BytecodeDiff::default();
+        let pairs = align(&old, &new, |a, b| a.0 == b.0);
+        for pair in pairs {
+            let (section, lines) = match pair {
+                (Some(o), Some(n)) => {
+                    let (o, n) = (&old[o], &new[n]);
+                    let lines = align(&o.1, &n.1, |a, b| a.1 == b.1)
+                        .into_iter()
+                        .map(|pair| match pair {
+                            (Some(a), Some(b)) => DiffLine {
+                                tag: DiffTag::Unchanged,
+                                old_offset: Some(o.1[a].0),
+                                new_offset: Some(n.1[b].0),
+                                instruction: o.1[a].1.clone(),
+                            },
+                            (Some(a), None) => removed(&o.1[a]),
+                            (None, Some(b)) => added(&n.1[b]),
+                            (None, None) => unreachable!("aligned items are in either sequence"),
+                        })
+                        .collect::<Vec<DiffLine>>();
+                    (o.0.clone(), lines)
+                }
+                (Some(o), None) => (old[o].0.clone(), old[o].1.iter().map(removed).collect()),
+                (None, Some(n)) => (new[n].0.clone(), new[n].1.iter().map(added).collect()),
+                (None, None) => unreachable!("aligned items are in either sequence"),
+            };
+            if lines.iter().any(|l| l.tag != DiffTag::Unchanged) {
+                diff.sections.push(SectionDiff { section, lines });
+            }
+        }
+        Ok(diff)
+    }
+
+    /// Whether the bytecodes have the same instructions
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+}
+
+impl fmt::Display for BytecodeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = |o: Option<usize>| o.map(|o| format!("{o:#06x}")).unwrap_or(" ".repeat(6));
+        for section in &self.sections {
+            writeln!(f, "@@ {} @@", section.section)?;
+            for line in &section.lines {
+                let tag = match line.tag {
+                    DiffTag::Unchanged => ' ',
+                    DiffTag::Removed => '-',
+                    DiffTag::Added => '+',
+                };
+                writeln!(
+                    f,
+                    "{tag} {} {} {}",
+                    offset(line.old_offset),
+                    offset(line.new_offset),
+                    line.instruction
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A line of an instruction only in the old bytecode
+fn removed((offset, instruction): &(usize, String)) -> DiffLine {
+    DiffLine {
+        tag: DiffTag::Removed,
+        old_offset: Some(*offset),
+        new_offset: None,
+        instruction: instruction.clone(),
+    }
+}
+
+/// A line of an instruction only in the new bytecode
+fn added((offset, instruction): &(usize, String)) -> DiffLine {
+    DiffLine {
+        tag: DiffTag::Added,
+        old_offset: None,
+        new_offset: Some(*offset),
+        instruction: instruction.clone(),
+    }
+}
+
+/// A section of bytecode and its instructions along with their offsets
+type Section = (String, Vec<(usize, String)>);
+
+/// Splits the disassembled runtime bytecode of an artifact at macro and label boundaries
+fn sections(artifact: &Artifact) -> Result<Vec<Section>, String> {
+    let ops = disassemble(&artifact.runtime)?;
+
+    // Symbols are ordered parents first, so the last one containing an offset is the innermost
+    let mut symbols = artifact.symbols.clone();
+    symbols.sort_by_key(|a| (a.start, std::cmp::Reverse(a.end)));
+    let labels: BTreeMap<usize, &str> =
+        symbols.iter().flat_map(|a| a.labels.iter().map(|(l, o)| (*o, l.as_str()))).collect();
+    let section_of = |offset: usize| {
+        let activation = symbols.iter().rev().find(|a| a.start <= offset && offset < a.end);
+        match activation {
+            Some(MacroActivation { name, labels, .. }) => {
+                match labels.iter().filter(|(_, o)| **o <= offset).max_by_key(|(_, o)| **o) {
+                    Some((label, _)) => format!("{name}::{label}"),
+                    None => name.clone(),
+                }
+            }
+            None => TABLES_SECTION.to_string(),
+        }
+    };
+
+    let mut sections: Vec<Section> = vec![];
+    for (i, op) in ops.iter().enumerate() {
+        let section = section_of(op.offset);
+        // Jump destinations move along with the code, so they're compared by label
+        let jumps = ops
+            .get(i + 1)
+            .map(|next| matches!(next.opcode, Some(Opcode::Jump | Opcode::Jumpi)))
+            .unwrap_or(false);
+        let destination = op
+            .immediate
+            .iter()
+            .try_fold(0usize, |acc, b| acc.checked_mul(256)?.checked_add(*b as usize))
+            .and_then(|d| labels.get(&d));
+        let instruction = match (op.opcode.map(|o| o.is_push()), jumps, destination) {
+            (Some(true), true, Some(label)) => format!("{} {label}", op.mnemonic()),
+            _ if op.immediate.is_empty() => op.mnemonic(),
+            _ => format!("{} 0x{}", op.mnemonic(), hex::encode(&op.immediate)),
+        };
+
+        match sections.last_mut() {
+            Some((last, instructions)) if *last == section => {
+                instructions.push((op.offset, instruction))
+            }
+            _ => sections.push((section, vec![(op.offset, instruction)])),
+        }
+    }
+    Ok(sections)
+}
+
+/// Aligns two sequences along their longest common subsequence, pairing the indices of equal
+/// items and leaving the others unpaired
+pub(crate) fn align<T>(
+    old: &[T],
+    new: &[T],
+    eq: impl Fn(&T, &T) -> bool,
+) -> Vec<(Option<usize>, Option<usize>)> {
+    // The length of the longest common subsequence of the suffixes
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = match eq(&old[i], &new[j]) {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
+        }
+    }
+
+    let (mut i, mut j, mut pairs) = (0, 0, vec![]);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && eq(&old[i], &new[j]) {
+            pairs.push((Some(i), Some(j)));
+            (i, j) = (i + 1, j + 1);
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            pairs.push((Some(i), None));
+            i += 1;
+        } else {
+            pairs.push((None, Some(j)));
+            j += 1;
+        }
+    }
+    pairs
+}
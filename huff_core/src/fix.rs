@@ -0,0 +1,355 @@
+//! ## Migrations
+//!
+//! Rewrites sources using deprecated syntax to the syntax of the latest edition, for `huffc fix`.
+//!
+//! Sources are lexed on their own, without their includes, and only the tokens a migration
+//! changes are rewritten, so comments and formatting are kept as they are. Migrations are
+//! mechanical and keep the generated bytecode the same:
+//!
+//! - `deprecated-opcodes`: `difficulty` is renamed `prevrandao`, when targeting Paris or later.
+//! - `table-syntax`: the optional parentheses and assignment of table definitions are dropped, i.e.
+//!   `#define table T() = {` becomes `#define table T {`.
+//! - `opcode-names`: labels, constants and macro arguments named after opcodes, which the 2022
+//!   edition allows, are renamed like the compiler suggests, i.e. `stop:` becomes `stop_:`.
+//!   References to such labels in macro bodies were compiled as the opcode, so they're kept.
+//! - `edition`: `#pragma edition "2022"` is raised to the latest edition.
+//!
+//! ```text
+//! --- src/Legacy.huff
+//! +++ src/Legacy.huff
+//! @@ -4 +4 @@
+//! -    difficulty
+//! +    prevrandao
+//! ```
+
+use crate::diff::align;
+use huff_lexer::Lexer;
+use huff_utils::prelude::{
+    CompilerError, EVMVersion, Edition, FullFileSource, LexicalError, Opcode, Token, TokenKind,
+    OPCODES_MAP,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+/// A mechanical migration from deprecated syntax
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Migration {
+    /// Renames opcodes to their current names
+    DeprecatedOpcodes,
+    /// Drops the optional parentheses and assignment of table definitions
+    TableSyntax,
+    /// Renames labels, constants and macro arguments named after opcodes
+    OpcodeNames,
+    /// Raises `#pragma edition` to the latest edition
+    Edition,
+}
+
+impl Migration {
+    /// All migrations, in the order they're applied
+    pub const ALL: [Migration; 4] = [
+        Migration::DeprecatedOpcodes,
+        Migration::TableSyntax,
+        Migration::OpcodeNames,
+        Migration::Edition,
+    ];
+}
+
+impl fmt::Display for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Migration::DeprecatedOpcodes => write!(f, "deprecated-opcodes"),
+            Migration::TableSyntax => write!(f, "table-syntax"),
+            Migration::OpcodeNames => write!(f, "opcode-names"),
+            Migration::Edition => write!(f, "edition"),
+        }
+    }
+}
+
+/// A replacement of a byte range of a source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    /// The migration the edit is made by
+    pub migration: Migration,
+    /// The start of the replaced range
+    pub start: usize,
+    /// The end of the replaced range
+    pub end: usize,
+    /// The text the range is replaced with
+    pub replacement: String,
+}
+
+/// The migrated source of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    /// The path of the file
+    pub path: String,
+    /// The original source
+    pub source: String,
+    /// The source with the edits applied
+    pub fixed: String,
+    /// The edits, ordered by their position in the source
+    pub edits: Vec<Edit>,
+}
+
+impl Fix {
+    /// Migrate a source, targeting an EVM version.
+    pub fn new(path: &str, source: &str, evm_version: &EVMVersion) -> Result<Self, String> {
+        let full_source = FullFileSource { source, file: None, spans: vec![] };
+        let tokens = Lexer::new(full_source)
+            .filter(|t| {
+                !matches!(
+                    t.as_ref().map(|t| &t.kind),
+                    Ok(TokenKind::Whitespace | TokenKind::Comment(_))
+                )
+            })
+            .collect::<Result<Vec<Token>, LexicalError>>()
+            .map_err(|e| CompilerError::LexicalError(e).to_string())?;
+
+        let mut edits = vec![];
+        for migration in Migration::ALL {
+            let migrated = match migration {
+                Migration::DeprecatedOpcodes => deprecated_opcodes(&tokens, evm_version),
+                Migration::TableSyntax => table_syntax(source, &tokens),
+                Migration::OpcodeNames => opcode_names(source, &tokens),
+                Migration::Edition => edition(&tokens),
+            };
+            edits.extend(migrated.into_iter().map(|(start, end, replacement)| Edit {
+                migration,
+                start,
+                end,
+                replacement,
+            }));
+        }
+        edits.sort_by_key(|e| e.start);
+
+        let mut fixed = String::with_capacity(source.len());
+        let mut last = 0;
+        for edit in &edits {
+            fixed.push_str(&source[last..edit.start]);
+            fixed.push_str(&edit.replacement);
+            last = edit.end;
+        }
+        fixed.push_str(&source[last..]);
+
+        Ok(Self { path: path.to_string(), source: source.to_string(), fixed, edits })
+    }
+
+    /// Whether the source has no deprecated syntax
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// The migrations making the edits, without duplicates
+    pub fn migrations(&self) -> Vec<Migration> {
+        let mut migrations = self.edits.iter().map(|e| e.migration).collect::<Vec<Migration>>();
+        migrations.sort();
+        migrations.dedup();
+        migrations
+    }
+
+    /// The changed lines of the source, as a unified diff without context lines
+    pub fn diff(&self) -> String {
+        let old = self.source.lines().collect::<Vec<&str>>();
+        let new = self.fixed.lines().collect::<Vec<&str>>();
+        let mut diff = format!("--- {}\n+++ {}\n", self.path, self.path);
+
+        let mut hunks: Vec<Hunk> = vec![];
+        let (mut o, mut n, mut changed) = (1, 1, false);
+        for pair in align(&old, &new, |a, b| a == b) {
+            if let (Some(_), Some(_)) = pair {
+                (o, n, changed) = (o + 1, n + 1, false);
+                continue
+            }
+            if !changed {
+                hunks.push(((o, n), vec![], vec![]));
+                changed = true;
+            }
+            let (_, removed, added) = hunks.last_mut().unwrap();
+            match pair {
+                (Some(a), _) => {
+                    removed.push(old[a].to_string());
+                    o += 1;
+                }
+                (_, Some(b)) => {
+                    added.push(new[b].to_string());
+                    n += 1;
+                }
+                (None, None) => unreachable!("aligned items are in either sequence"),
+            }
+        }
+
+        let range = |start: usize, len: usize| match len {
+            1 => start.to_string(),
+            _ => format!("{start},{len}"),
+        };
+        for ((o, n), removed, added) in hunks {
+            diff.push_str(&format!(
+                "@@ -{} +{} @@\n",
+                range(o, removed.len()),
+                range(n, added.len())
+            ));
+            removed.iter().for_each(|l| diff.push_str(&format!("-{l}\n")));
+            added.iter().for_each(|l| diff.push_str(&format!("+{l}\n")));
+        }
+        diff
+    }
+}
+
+/// A run of changed lines of a diff: the line numbers it starts at, and the removed and added lines
+type Hunk = ((usize, usize), Vec<String>, Vec<String>);
+
+/// The byte ranges of a source a migration replaces, along with their replacements
+type Replacements = Vec<(usize, usize, String)>;
+
+/// Renames `difficulty` to `prevrandao`, which replaced it in Paris
+fn deprecated_opcodes(tokens: &[Token], evm_version: &EVMVersion) -> Replacements {
+    if !evm_version.supports(&Opcode::Prevrandao) {
+        return vec![]
+    }
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(i, t)| {
+            t.kind == TokenKind::Opcode(Opcode::Difficulty) &&
+                // Constants and arguments named `difficulty` are renamed by `opcode_names`
+                !matches!(
+                    i.checked_sub(1).map(|i| &tokens[i].kind),
+                    Some(TokenKind::OpenBracket | TokenKind::LeftAngle)
+                )
+        })
+        .map(|(_, t)| (t.span.start, t.span.end, "prevrandao".to_string()))
+        .collect()
+}
+
+/// Drops the parentheses and assignment between the name and the body of table definitions
+fn table_syntax(source: &str, tokens: &[Token]) -> Replacements {
+    let mut replacements = vec![];
+    for (i, w) in tokens.windows(3).enumerate() {
+        let is_table = w[0].kind == TokenKind::Define &&
+            matches!(
+                w[1].kind,
+                TokenKind::JumpTable | TokenKind::JumpTablePacked | TokenKind::CodeTable
+            ) &&
+            matches!(w[2].kind, TokenKind::Ident(_));
+        if !is_table {
+            continue
+        }
+        let body = match tokens[i + 3..].iter().find(|t| {
+            !matches!(t.kind, TokenKind::OpenParen | TokenKind::CloseParen | TokenKind::Assign)
+        }) {
+            Some(t) if t.kind == TokenKind::OpenBrace => t,
+            _ => continue,
+        };
+        // Comments between the name and the body are kept
+        let (start, end) = (w[2].span.end, body.span.start);
+        let removed = &source[start..end];
+        if removed.trim() != "" && removed.chars().all(|c| c.is_whitespace() || "()=".contains(c)) {
+            replacements.push((start, end, " ".to_string()));
+        }
+    }
+    replacements
+}
+
+/// Renames labels, constants and macro arguments named after opcodes, along with the references
+/// to them that weren't compiled as the opcode
+fn opcode_names(source: &str, tokens: &[Token]) -> Replacements {
+    let text = |t: &Token| &source[t.span.start..t.span.end];
+    let kind = |i: Option<usize>| i.and_then(|i| tokens.get(i)).map(|t| &t.kind);
+
+    // Renames must not collide with the other names of the source
+    let taken = tokens.iter().map(text).collect::<HashSet<&str>>();
+    let unique = |mut name: String| {
+        while taken.contains(name.as_str()) {
+            name.push('_');
+        }
+        name
+    };
+
+    // Constants and labels may be referenced before they're defined
+    let mut constants = HashMap::new();
+    let mut labels = HashMap::new();
+    for (i, t) in tokens.iter().enumerate() {
+        let name = text(t);
+        if !OPCODES_MAP.contains_key(name) {
+            continue
+        }
+        match (&t.kind, kind(i.checked_sub(1))) {
+            (TokenKind::Ident(_), Some(TokenKind::Constant)) => {
+                constants.insert(name, unique(name.to_uppercase()));
+            }
+            (TokenKind::Label(_), _) => {
+                labels.insert(name, unique(format!("{name}_")));
+            }
+            _ => {}
+        }
+    }
+
+    let mut replacements = vec![];
+    // The renamed arguments of the current macro
+    let mut args: HashMap<&str, String> = HashMap::new();
+    let (mut in_args, mut in_table) = (false, false);
+    for (i, t) in tokens.iter().enumerate() {
+        match t.kind {
+            TokenKind::Define => {
+                args.clear();
+                in_table = false;
+            }
+            TokenKind::JumpTable | TokenKind::JumpTablePacked | TokenKind::CodeTable => {
+                in_table = true
+            }
+            TokenKind::OpenParen => {
+                in_args = matches!(
+                    kind(i.checked_sub(2)),
+                    Some(TokenKind::Macro | TokenKind::Fn | TokenKind::Test)
+                )
+            }
+            TokenKind::CloseParen => in_args = false,
+            _ => {}
+        }
+
+        let name = text(t);
+        if !OPCODES_MAP.contains_key(name) {
+            continue
+        }
+        let (prev, next) = (kind(i.checked_sub(1)), kind(Some(i + 1)));
+        let renamed = match (&t.kind, prev, next) {
+            (TokenKind::Ident(_), _, _) if in_args => {
+                let renamed = unique(format!("{name}_"));
+                args.insert(name, renamed.clone());
+                Some(renamed)
+            }
+            (TokenKind::Ident(_), Some(TokenKind::Constant), _) |
+            (_, Some(TokenKind::OpenBracket), Some(TokenKind::CloseBracket)) => {
+                constants.get(name).cloned()
+            }
+            (_, Some(TokenKind::LeftAngle), Some(TokenKind::RightAngle)) => args.get(name).cloned(),
+            (TokenKind::Label(_), _, _) => labels.get(name).cloned(),
+            (TokenKind::Ident(_), _, _) if in_table => labels.get(name).cloned(),
+            _ => None,
+        };
+        if let Some(renamed) = renamed {
+            replacements.push((t.span.start, t.span.end, renamed));
+        }
+    }
+    replacements
+}
+
+/// Raises `#pragma edition` declarations of older editions to the latest edition
+fn edition(tokens: &[Token]) -> Replacements {
+    tokens
+        .windows(3)
+        .filter(|w| {
+            let declared = match &w[2].kind {
+                TokenKind::Str(v) => v.parse::<Edition>().ok(),
+                TokenKind::Num(n) => n.to_string().parse::<Edition>().ok(),
+                _ => None,
+            };
+            w[0].kind == TokenKind::Pragma &&
+                matches!(&w[1].kind, TokenKind::Ident(name) if name == "edition") &&
+                declared.map(|e| e != Edition::default()).unwrap_or(false)
+        })
+        .map(|w| (w[2].span.start, w[2].span.end, format!("\"{}\"", Edition::default())))
+        .collect()
+}
@@ -5,7 +5,12 @@
 #![forbid(where_clauses_object_safety)]
 
 use ethers_core::utils::hex;
-use huff_codegen::*;
+use huff_codegen::{
+    analysis::prelude::{
+        insert_fallthrough_revert, validate_dispatch, validate_static_jumps, ControlFlowGraph,
+    },
+    *,
+};
 use huff_lexer::*;
 use huff_parser::*;
 #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
@@ -20,14 +25,33 @@ use rayon::prelude::*;
 use std::{
     collections::{BTreeMap, HashMap},
     ffi::OsString,
-    fs,
     iter::Iterator,
     path::PathBuf,
-    sync::Arc,
+    sync::{Arc, Mutex},
 };
 use tracing_subscriber::{filter::Directive, EnvFilter};
 
+/// The version of huff-rs, recorded in the metadata of artifacts
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 pub(crate) mod cache;
+pub mod diff;
+pub mod fix;
+pub mod output;
+use output::{Manifest, OutputFormat, OutputLayout, OutputManager};
+pub mod packages;
+pub mod playground;
+pub use playground::compile_str;
+pub mod proxy;
+pub mod size_report;
+pub mod snapshot;
+use snapshot::Snapshot;
+#[cfg(feature = "solidity")]
+pub mod solidity;
+pub mod sstore2;
+pub mod std_lib;
+pub mod verify;
+use std_lib::{StdFileProvider, StdLib};
 
 /// ## The Core Huff Compiler
 ///
@@ -62,22 +86,52 @@ pub struct Compiler<'a> {
     pub sources: Arc<Vec<String>>,
     /// The output location
     pub output: Option<String>,
+    /// The name artifacts are labeled with
+    pub name: Option<String>,
+    /// How artifacts are laid out in the output directory
+    pub output_layout: OutputLayout,
+    /// The format artifacts are written in
+    pub output_format: OutputFormat,
     /// Macro to use a main
     pub alternative_main: Option<String>,
     /// Constructor macro to use
     pub alternative_constructor: Option<String>,
     /// Constructor Input Arguments
     pub construct_args: Option<Vec<String>>,
+    /// A file of constructor arguments, either a json array or ABI-encoded hex, taking precedence
+    /// over `construct_args`
+    pub constructor_args_path: Option<String>,
+    /// A previous storage layout, either an artifact or its `storageLayout`, that the storage
+    /// layouts of the compiled contracts must be compatible with
+    pub storage_check_path: Option<String>,
     /// Constant Overrides
     pub constant_overrides: Option<BTreeMap<&'a str, Literal>>,
+    /// The EVM version to target
+    pub evm_version: EVMVersion,
+    /// The language edition of files not declaring their own
+    pub edition: Edition,
+    /// The maximum number of macros that may be expanded within one another
+    pub macro_nesting_limit: usize,
+    /// Whether every jump of the entry points must jump to a label
+    pub strict_jumps: bool,
+    /// Whether every declared function must be reachable from the main macro's dispatch with its
+    /// selector, reaching a body of its own
+    pub check_dispatch: bool,
     /// Whether to optimize compilation or not.
     pub optimize: bool,
+    /// Whether to output EOF containers rather than legacy bytecode, experimental
+    pub eof: bool,
+    /// Whether to revert calls matching none of the selectors of the main macro's dispatch,
+    /// rather than falling through into its first label
+    pub auto_revert: bool,
     /// Generate and log bytecode
     pub bytecode: bool,
     /// Whether to check cached artifacts
     pub cached: bool,
     /// The implementation of a FileReader
     pub file_provider: Arc<dyn FileProvider<'a>>,
+    /// Where `std/` includes are read from
+    pub std_lib: StdLib,
 }
 
 impl<'a> Compiler<'a> {
@@ -99,14 +153,27 @@ impl<'a> Compiler<'a> {
         Self {
             sources,
             output,
+            name: None,
+            output_layout: OutputLayout::default(),
+            output_format: OutputFormat::default(),
             alternative_main,
             alternative_constructor,
             construct_args,
+            constructor_args_path: None,
+            storage_check_path: None,
             constant_overrides,
+            evm_version: EVMVersion::default(),
+            edition: Edition::default(),
+            macro_nesting_limit: DEFAULT_MACRO_NESTING_LIMIT,
+            strict_jumps: false,
+            check_dispatch: false,
             optimize: false,
+            eof: false,
+            auto_revert: false,
             bytecode: false,
             cached,
             file_provider: Arc::new(FileSystemFileProvider {}),
+            std_lib: StdLib::default(),
         }
     }
 
@@ -127,14 +194,27 @@ impl<'a> Compiler<'a> {
         Self {
             sources,
             output: None,
+            name: None,
+            output_layout: OutputLayout::default(),
+            output_format: OutputFormat::default(),
             alternative_main,
             alternative_constructor,
             construct_args,
+            constructor_args_path: None,
+            storage_check_path: None,
             constant_overrides,
+            evm_version: EVMVersion::default(),
+            edition: Edition::default(),
+            macro_nesting_limit: DEFAULT_MACRO_NESTING_LIMIT,
+            strict_jumps: false,
+            check_dispatch: false,
             optimize: false,
+            eof: false,
+            auto_revert: false,
             bytecode: false,
             cached: false,
             file_provider: Arc::new(InMemoryFileProvider::new(file_sources)),
+            std_lib: StdLib::default(),
         }
     }
 
@@ -163,10 +243,13 @@ impl<'a> Compiler<'a> {
     /// 1. Transform inputs into File Paths with [transform_paths](Compiler::transform_paths).
     /// 2. Fetch file sources in parallel with [fetch_sources](Compiler::fetch_sources).
     /// 3. Recurse file dependencies in parallel with [recurse_deps](Compiler::recurse_deps).
-    /// 4. For each top-level file [Parallelized], generate the artifact using
-    /// [gen_artifact](Compiler::gen_artifact).
+    /// 4. For each top-level file [Parallelized], generate the artifacts using
+    /// [gen_artifacts](Compiler::gen_artifacts).
     /// 5. Return the compiling error(s) or successfully generated artifacts.
     pub fn execute(&self) -> Result<Vec<Arc<Artifact>>, Arc<CompilerError<'a>>> {
+        // Validate the pinned standard library version
+        self.std_lib.check_version().map_err(|e| Arc::new(CompilerError::StdLibError(e)))?;
+
         // Grab the input files
         let file_paths: Vec<PathBuf> = self.file_provider.transform_paths(&self.sources)?;
 
@@ -200,17 +283,32 @@ impl<'a> Compiler<'a> {
 
         let mut artifacts: Vec<Arc<Artifact>> = vec![];
 
-        // Get our constructor arguments as a hex encoded string to compare to the cache
-        let inputs = self.get_constructor_args();
-        let encoded_inputs = Codegen::encode_constructor_args(inputs);
-        let encoded: Vec<Vec<u8>> =
-            encoded_inputs.iter().map(|tok| ethers_core::abi::encode(&[tok.clone()])).collect();
-        let constructor_args = encoded.iter().map(|tok| hex::encode(tok.as_slice())).collect();
+        // Get our constructor arguments as a hex encoded string to compare to the cache, skipping
+        // the cache if they can't be encoded without the contract's constructor
+        let cached = match self.constructor_args().map(|args| args.tokenize(None)) {
+            Ok(Ok(tokens)) if self.cached => {
+                let constructor_args = tokens
+                    .into_iter()
+                    .map(|tok| hex::encode(ethers_core::abi::encode(&[tok])))
+                    .collect();
+                tracing::debug!(target: "core", "Output directory: {}", output.0);
+                cache::get_cached_artifacts(&files, &output, constructor_args, &self.settings())
+            }
+            _ => None,
+        };
+
+        // Cached artifacts labeled with another name are recompiled
+        let cached = cached.filter(|arts| {
+            let names = arts.iter().map(|a| a.name.clone()).collect::<Vec<Option<String>>>();
+            names == self.artifact_names(arts)
+        });
 
         // Get Cached or Generate Artifacts
-        tracing::debug!(target: "core", "Output directory: {}", output.0);
-        match cache::get_cached_artifacts(&files, &output, constructor_args) {
-            Some(arts) => artifacts = arts,
+        match cached {
+            Some(arts) => {
+                self.check_storage_layouts(&arts).map_err(Arc::new)?;
+                artifacts = arts
+            }
             None => {
                 tracing::debug!(target: "core", "FINISHED RECURSING DEPENDENCIES!");
                 // Parallel Dependency Resolution
@@ -218,7 +316,7 @@ impl<'a> Compiler<'a> {
                     files
                         .into_par_iter()
                         .map(|v| {
-                            Self::recurse_deps(v, &Remapper::new("./"), self.file_provider.clone())
+                            Self::recurse_deps(v, &Remapper::new("./"), self.dependency_provider())
                         })
                         .collect();
 
@@ -240,15 +338,15 @@ impl<'a> Compiler<'a> {
                 tracing::info!(target: "core", "COMPILER RECURSED {} FILE DEPENDENCIES", files.len());
 
                 // Parallel Compilation
-                let potential_artifacts: Vec<Result<Artifact, CompilerError<'a>>> =
-                    files.into_par_iter().map(|f| self.gen_artifact(f)).collect();
+                let potential_artifacts: Vec<Result<Vec<Artifact>, CompilerError<'a>>> =
+                    files.into_par_iter().map(|f| self.gen_artifacts(f)).collect();
 
                 let mut gen_errors: Vec<CompilerError<'a>> = vec![];
 
                 // Output errors + return OR print # of successfully compiled files
                 for r in potential_artifacts {
                     match r {
-                        Ok(a) => artifacts.push(Arc::new(a)),
+                        Ok(a) => artifacts.extend(a.into_iter().map(Arc::new)),
                         Err(ce) => gen_errors.push(ce),
                     }
                 }
@@ -258,8 +356,22 @@ impl<'a> Compiler<'a> {
                     return Err(Arc::new(CompilerError::FailedCompiles(gen_errors)))
                 }
 
+                // Check the storage layouts before exporting anything
+                self.check_storage_layouts(&artifacts).map_err(Arc::new)?;
+
+                // Label the artifacts
+                let names = self.artifact_names(&artifacts);
+                for (artifact, name) in artifacts.iter_mut().zip(names) {
+                    Arc::make_mut(artifact).name = name;
+                }
+
                 // Export
-                Compiler::export_artifacts(&artifacts, &output);
+                Compiler::export_artifacts(
+                    &artifacts,
+                    &output,
+                    self.output_layout,
+                    self.output_format,
+                );
             }
         }
 
@@ -276,6 +388,17 @@ impl<'a> Compiler<'a> {
     /// 4. For each top-level file, parse its contents and return a vec of [Contract](Contract)
     ///    ASTs.
     pub fn grab_contracts(&self) -> Result<Vec<Contract>, Arc<CompilerError<'a>>> {
+        let contracts = self.grab_contracts_with_warnings()?;
+        Ok(contracts.into_iter().map(|(contract, _)| contract).collect())
+    }
+
+    /// Grab the ASTs for all file sources, along with the warnings of lexing their sources.
+    fn grab_contracts_with_warnings(
+        &self,
+    ) -> Result<Vec<(Contract, Vec<CompilerWarning>)>, Arc<CompilerError<'a>>> {
+        // Validate the pinned standard library version
+        self.std_lib.check_version().map_err(|e| Arc::new(CompilerError::StdLibError(e)))?;
+
         // Grab the input files
         let file_paths: Vec<PathBuf> = self.file_provider.transform_paths(&self.sources)?;
 
@@ -303,7 +426,7 @@ impl<'a> Compiler<'a> {
                 Self::recurse_deps(
                     f,
                     &huff_utils::files::Remapper::new("./"),
-                    self.file_provider.clone(),
+                    self.dependency_provider(),
                 )
             })
             .collect();
@@ -342,66 +465,526 @@ impl<'a> Compiler<'a> {
 
                 // Perform Lexical Analysis
                 // Create a new lexer from the FileSource, flattening dependencies
-                let lexer: Lexer = Lexer::new(full_source);
+                let mut lexer: Lexer = Lexer::new(full_source);
+                lexer.edition = self.edition;
 
                 // Grab the tokens from the lexer
-                let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+                let tokens = lexer
+                    .by_ref()
+                    .collect::<Result<Vec<Token>, LexicalError>>()
+                    .map_err(|e| CompilerError::LexicalError(e.into_owned()))?;
                 tracing::info!(target: "core", "LEXICAL ANALYSIS COMPLETE FOR \"{}\"", file.path);
                 tracing::info!(target: "core", "└─ TOKEN COUNT: {}", tokens.len());
+                let warnings = lexer.warnings;
 
                 // Parser incantation
                 let mut parser = Parser::new(tokens, Some(file.path.clone()));
+                parser.edition = self.edition;
 
                 // Parse into an AST
                 let parse_res = parser.parse().map_err(CompilerError::ParserError);
                 let mut contract = parse_res?;
-                contract.derive_storage_pointers();
+                self.check_pragmas(&contract)?;
+                self.load_abi_imports(&mut contract)?;
+                self.load_merkle_roots(&mut contract)?;
+                contract.derive_storage_pointers_from(
+                    self.alternative_main.as_deref().unwrap_or("MAIN"),
+                    self.alternative_constructor.as_deref().unwrap_or("CONSTRUCTOR"),
+                );
                 contract.add_override_constants(&self.constant_overrides);
+                self.insert_auto_revert(&mut contract);
                 tracing::info!(target: "core", "PARSED CONTRACT [{}]", file.path);
-                Ok(contract)
+                Ok((contract, warnings))
             })
-            .collect::<Result<Vec<Contract>, Arc<CompilerError<'a>>>>()
+            .collect::<Result<Vec<(Contract, Vec<CompilerWarning>)>, Arc<CompilerError<'a>>>>()
+    }
+
+    /// Statically analyze the ASTs for all file sources.
+    ///
+    /// Grabs the ASTs with [grab_contracts](Compiler::grab_contracts) and collects the warnings
+    /// of lexing and analyzing every contract, omitting duplicates from code shared through
+    /// imports.
+    pub fn analyze(&self) -> Result<Vec<CompilerWarning>, Arc<CompilerError<'a>>> {
+        let contracts = self.grab_contracts_with_warnings()?;
+        let mut warnings: Vec<CompilerWarning> = vec![];
+        let main = self.alternative_main.as_deref().unwrap_or("MAIN");
+        for warning in contracts.iter().flat_map(|(c, lexed)| {
+            lexed
+                .iter()
+                .filter(|w| !c.suppressions.iter().any(|s| s.silences(w)))
+                .cloned()
+                .chain(Codegen::analyze_with_main(c, main))
+        }) {
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+        tracing::info!(target: "core", "ANALYSIS GENERATED {} WARNINGS", warnings.len());
+        Ok(warnings)
+    }
+
+    /// Compile a single macro into standalone bytecode.
+    ///
+    /// The macro is compiled as the main macro would be, with its jumps resolved and the tables
+    /// and functions it uses appended, but without a constructor. Returns its bytecode by the path
+    /// of the file defining it, for every contract including the macro.
+    pub fn compile_macro(
+        &self,
+        name: &str,
+    ) -> Result<BTreeMap<String, String>, Arc<CompilerError<'a>>> {
+        let contracts = self.grab_contracts()?;
+        let mut compiled = BTreeMap::new();
+        for contract in &contracts {
+            let macro_def = match contract.macros.iter().find(|m| m.name == name) {
+                Some(m) => m,
+                None => continue,
+            };
+            let path = macro_def
+                .span
+                .0
+                .iter()
+                .find_map(|s| s.file.as_ref().map(|f| f.path.clone()))
+                .unwrap_or_default();
+            let bytecode = Codegen::generate_main_bytecode_with_limit(
+                &self.evm_version,
+                contract,
+                Some(name.to_string()),
+                self.macro_nesting_limit,
+            )
+            .map_err(|e| Arc::new(CompilerError::CodegenError(e)))?;
+            tracing::info!(target: "core", "MACRO \"{}\" COMPILED [{}]", name, bytecode);
+            compiled.insert(path, bytecode);
+        }
+
+        if compiled.is_empty() {
+            tracing::error!(target: "core", "MISSING \"{}\" MACRO!", name);
+            return Err(Arc::new(CompilerError::CodegenError(CodegenError {
+                kind: CodegenErrorKind::MissingMacroDefinition(name.to_string()),
+                span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
+                token: None,
+            })))
+        }
+        Ok(compiled)
+    }
+
+    /// Generates the control flow graph of the main macro of each contract, by the path of the
+    /// file defining it.
+    ///
+    /// Contracts without a main macro are skipped.
+    pub fn control_flow_graphs(
+        &self,
+    ) -> Result<BTreeMap<String, ControlFlowGraph>, Arc<CompilerError<'a>>> {
+        let main = self.alternative_main.clone().unwrap_or_else(|| "MAIN".to_string());
+        let contracts = self.grab_contracts()?;
+        let mut graphs = BTreeMap::new();
+        for contract in &contracts {
+            let macro_def = match contract.macros.iter().find(|m| m.name == main) {
+                Some(m) => m,
+                None => continue,
+            };
+            let path = macro_def
+                .span
+                .0
+                .iter()
+                .find_map(|s| s.file.as_ref().map(|f| f.path.clone()))
+                .unwrap_or_default();
+            let cfg = Codegen::generate_main_cfg(
+                &self.evm_version,
+                contract,
+                Some(main.clone()),
+                self.macro_nesting_limit,
+            )
+            .map_err(|e| Arc::new(CompilerError::CodegenError(e)))?;
+            graphs.insert(path, cfg);
+        }
+
+        if graphs.is_empty() {
+            tracing::error!(target: "core", "MISSING \"{}\" MACRO!", main);
+            return Err(Arc::new(CompilerError::CodegenError(CodegenError {
+                kind: CodegenErrorKind::MissingMacroDefinition(main),
+                span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
+                token: None,
+            })))
+        }
+        Ok(graphs)
+    }
+
+    /// The settings affecting the generated bytecode, recorded in the metadata of artifacts
+    pub fn settings(&self) -> CompilerSettings {
+        CompilerSettings {
+            evm_version: self.evm_version.to_string(),
+            edition: self.edition.to_string(),
+            optimize: self.optimize,
+            eof: self.eof,
+            auto_revert: self.auto_revert,
+            main_macro: self.alternative_main.clone().unwrap_or_else(|| "MAIN".to_string()),
+            constructor_macro: self
+                .alternative_constructor
+                .clone()
+                .unwrap_or_else(|| "CONSTRUCTOR".to_string()),
+            constant_overrides: self
+                .constant_overrides
+                .iter()
+                .flatten()
+                .map(|(name, value)| (name.to_string(), bytes32_to_string(value, true)))
+                .collect(),
+            macro_nesting_limit: self.macro_nesting_limit,
+        }
+    }
+
+    /// Snapshot the runtime bytecode of all file sources.
+    ///
+    /// Compiles the artifacts with [execute](Compiler::execute) and the ASTs with
+    /// [grab_contracts](Compiler::grab_contracts), recording the size and hash of each artifact's
+    /// runtime bytecode and of the bytes emitted by each of its macros.
+    pub fn snapshot(&self) -> Result<Snapshot, Arc<CompilerError<'a>>> {
+        let artifacts = self.execute()?;
+        let contracts = self.grab_contracts()?;
+        Ok(Snapshot::new(
+            &artifacts,
+            &contracts,
+            &self.evm_version,
+            self.alternative_main.as_deref().unwrap_or("MAIN"),
+            self.macro_nesting_limit,
+        ))
     }
 
     /// Artifact Generation
     ///
     /// Compiles a FileSource into an Artifact.
+    ///
+    /// Only the top level definitions of a file defining contracts with `#define contract` blocks
+    /// are compiled, see [gen_artifacts](Compiler::gen_artifacts) to compile its contracts.
     pub fn gen_artifact(&self, file: Arc<FileSource>) -> Result<Artifact, CompilerError<'a>> {
+        let parsed = self.parse_file(&file)?;
+        let args = self.constructor_args()?;
+        self.gen_contract_artifact(file, &parsed, None, &args, &mut vec![])
+    }
+
+    /// Artifacts Generation
+    ///
+    /// Compiles a FileSource into an Artifact per contract it defines.
+    ///
+    /// A file defining contracts with `#define contract` blocks compiles into an artifact for each
+    /// of them, and one for its top level definitions if they include a `MAIN` macro. Any other
+    /// file compiles into a single artifact, like with [gen_artifact](Compiler::gen_artifact).
+    pub fn gen_artifacts(&self, file: Arc<FileSource>) -> Result<Vec<Artifact>, CompilerError<'a>> {
+        let parsed = self.parse_file(&file)?;
+        let args = self.constructor_args()?;
+        if parsed.contracts.is_empty() {
+            return Ok(vec![self.gen_contract_artifact(file, &parsed, None, &args, &mut vec![])?])
+        }
+
+        let mut artifacts = vec![];
+        let main = self.alternative_main.as_deref().unwrap_or("MAIN");
+        if parsed.macros.iter().any(|m| m.name == main) {
+            artifacts.push(self.gen_contract_artifact(
+                Arc::clone(&file),
+                &parsed,
+                None,
+                &args,
+                &mut vec![],
+            )?);
+        }
+        for def in &parsed.contracts {
+            artifacts.push(self.gen_contract_artifact(
+                Arc::clone(&file),
+                &parsed,
+                Some(&def.name),
+                &args,
+                &mut vec![],
+            )?);
+        }
+        Ok(artifacts)
+    }
+
+    /// Parses a FileSource, flattened with its dependencies, into a Contract AST
+    fn parse_file(&self, file: &Arc<FileSource>) -> Result<Contract, CompilerError<'a>> {
         // Fully Flatten a file into a source string containing source code of file and all
         // its dependencies
-        let flattened = FileSource::fully_flatten(Arc::clone(&file));
+        let flattened = FileSource::fully_flatten(Arc::clone(file));
         tracing::info!(target: "core", "FLATTENED SOURCE FILE \"{}\"", file.path);
         let full_source = FullFileSource {
             source: &flattened.0,
-            file: Some(Arc::clone(&file)),
+            file: Some(Arc::clone(file)),
             spans: flattened.1,
         };
         tracing::debug!(target: "core", "GOT FULL SOURCE FOR PATH: {:?}", file.path);
 
         // Perform Lexical Analysis
         // Create a new lexer from the FileSource, flattening dependencies
-        let lexer: Lexer = Lexer::new(full_source);
+        let mut lexer: Lexer = Lexer::new(full_source);
+        lexer.edition = self.edition;
 
         // Grab the tokens from the lexer
-        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let tokens = lexer
+            .collect::<Result<Vec<Token>, LexicalError>>()
+            .map_err(|e| CompilerError::LexicalError(e.into_owned()))?;
         tracing::info!(target: "core", "LEXICAL ANALYSIS COMPLETE FOR \"{}\"", file.path);
         tracing::info!(target: "core", "└─ TOKEN COUNT: {}", tokens.len());
 
         // Parser incantation
         let mut parser = Parser::new(tokens, Some(file.path.clone()));
+        parser.edition = self.edition;
 
         // Parse into an AST
-        let parse_res = parser.parse().map_err(CompilerError::ParserError);
-        let mut contract = parse_res?;
-        contract.derive_storage_pointers();
-        contract.add_override_constants(&self.constant_overrides);
+        let mut contract = parser.parse().map_err(CompilerError::ParserError)?;
+        self.check_pragmas(&contract)?;
+        self.load_abi_imports(&mut contract)?;
+        self.load_merkle_roots(&mut contract)?;
         tracing::info!(target: "core", "PARSED CONTRACT [{}]", file.path);
+        Ok(contract)
+    }
+
+    /// Checks the requirements a parsed source declares with `#pragma huff-version` and
+    /// `#pragma evm-version` against the compiler version and the targeted EVM version.
+    pub fn check_pragmas(&self, contract: &Contract) -> Result<(), CompilerError<'a>> {
+        contract.pragmas.iter().try_for_each(|pragma| {
+            pragma.check(VERSION, &self.evm_version).map_err(|e| {
+                tracing::error!(target: "core", "INCOMPATIBLE PRAGMA: {}", e);
+                CompilerError::IncompatiblePragma(e, pragma.span.clone())
+            })
+        })
+    }
+
+    /// Reads the files imported with `#include abi` and `#include sol`, declaring their entries
+    /// in the contract.
+    ///
+    /// Paths are remapped or localized relative to the including file, as includes of Huff files
+    /// are.
+    pub fn load_abi_imports(&self, contract: &mut Contract) -> Result<(), CompilerError<'a>> {
+        let mut loaded = vec![];
+        for import in std::mem::take(&mut contract.abi_imports) {
+            let path = Self::included_path(&import.path, &import.span);
+            let declared = match self.file_provider.read_file(PathBuf::from(&path)) {
+                Ok(file) => {
+                    Self::declare_import(import.clone(), file.source.as_deref().unwrap_or_default())
+                }
+                Err(_) => Err(format!("\"{path}\" can't be read")),
+            };
+            match declared {
+                Ok(imports) => {
+                    imports.iter().for_each(|i| {
+                        tracing::info!(target: "core", "IMPORTED ABI \"{}\" AS {}", path, i.name)
+                    });
+                    loaded.extend(imports);
+                }
+                Err(e) => {
+                    tracing::error!(target: "core", "INVALID ABI IMPORT \"{}\": {}", path, e);
+                    return Err(CompilerError::InvalidAbiImport(e, import.span))
+                }
+            }
+        }
+        contract.abi_imports = loaded;
+        Ok(())
+    }
+
+    /// Reads the leaves of the merkle trees of the contract and of its contract blocks defined
+    /// with a json file, declaring the root of each tree as a constant, i.e. `AIRDROP_ROOT`.
+    ///
+    /// Paths resolve like the paths of `#include abi`.
+    pub fn load_merkle_roots(&self, contract: &mut Contract) -> Result<(), CompilerError<'a>> {
+        for def in contract.contracts.iter_mut() {
+            self.load_merkle_roots(&mut def.contract)?;
+        }
+        for tree in &contract.merkle_trees {
+            let included = match &tree.leaves {
+                Some(included) => included,
+                None => continue,
+            };
+            let path = Self::included_path(included, &tree.span);
+            let name = merkle::root_name(&tree.name);
+            let root = match self.file_provider.read_file(PathBuf::from(&path)) {
+                Ok(file) => leaves_from_json(file.source.as_deref().unwrap_or_default()),
+                Err(_) => Err(format!("\"{path}\" can't be read")),
+            }
+            .and_then(|leaves| match tree.depth {
+                // Every proof of a fixed depth tree has as many nodes, so the tree is full
+                Some(depth) if leaves.len() != 1 << depth => Err(format!(
+                    "a tree of depth {depth} has {} leaves, not {}",
+                    1usize << depth,
+                    leaves.len()
+                )),
+                _ => Ok(leaves),
+            })
+            .and_then(|leaves| {
+                let mut constants = contract.constants.lock().unwrap();
+                match constants.iter().any(|c| c.name == name) {
+                    true => Err(format!("the constant \"{name}\" is already defined")),
+                    false => {
+                        let root = tree.hash.root(&leaves).unwrap_or_default();
+                        constants.push(ConstantDefinition {
+                            name: name.clone(),
+                            ty: None,
+                            value: ConstVal::Literal(root),
+                            span: tree.span.clone(),
+                        });
+                        Ok(root)
+                    }
+                }
+            });
+            match root {
+                Ok(root) => {
+                    tracing::info!(target: "core", "DECLARED {} = 0x{}", name, hex::encode(root))
+                }
+                Err(e) => {
+                    tracing::error!(target: "core", "INVALID MERKLE LEAVES \"{}\": {}", path, e);
+                    return Err(CompilerError::InvalidMerkleLeaves(e, tree.span.clone()))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The path of a file included by a definition, remapped or localized relative to the
+    /// including file
+    fn included_path(included: &FilePath, span: &AstSpan) -> String {
+        let included = included.to_string_lossy().to_string();
+        let including = span.0.first().and_then(|s| s.file.as_ref()).map(|f| &f.path);
+        match (Remapper::new("./").remap(&included), including) {
+            (Some(remapped), _) => remapped,
+            (None, Some(including)) => {
+                FileSource::localize_file(including, &included).unwrap_or(included)
+            }
+            (None, None) => included,
+        }
+    }
+
+    /// Declares the entries of an imported file's source, once for each interface of a Solidity
+    /// file
+    fn declare_import(mut import: AbiImport, source: &str) -> Result<Vec<AbiImport>, String> {
+        match import.kind {
+            AbiImportKind::Abi => import.declare(source).map(|_| vec![import]),
+            #[cfg(feature = "solidity")]
+            AbiImportKind::Sol => {
+                let interfaces = solidity::interfaces(source)?;
+                if interfaces.is_empty() {
+                    return Err("no interfaces are declared".to_string())
+                }
+                interfaces
+                    .into_iter()
+                    .map(|(name, abi)| {
+                        let mut interface = AbiImport { name, ..import.clone() };
+                        interface.declare(&abi.to_string()).map(|_| interface)
+                    })
+                    .collect()
+            }
+            #[cfg(not(feature = "solidity"))]
+            AbiImportKind::Sol => {
+                Err("Solidity imports require the \"solidity\" feature of huff_core".to_string())
+            }
+        }
+    }
+
+    /// Appends a revert to the main macro's selector dispatch if it falls through into the first
+    /// label, when enabled with `auto_revert` or by `#pragma auto-revert` above the main macro.
+    pub fn insert_auto_revert(&self, contract: &mut Contract) {
+        let main = self.alternative_main.as_deref().unwrap_or("MAIN");
+        let enabled = self.auto_revert ||
+            contract
+                .macros
+                .iter()
+                .any(|m| m.name == main && m.has_pragma(MacroPragma::AutoRevert));
+        if enabled {
+            insert_fallthrough_revert(contract, main);
+        }
+    }
+
+    /// Compiles a contract of a parsed file into an Artifact
+    ///
+    /// The contract is the file's top level definitions, or the contract defined with the
+    /// `#define contract` block of the given name. The creation code of the file's other contracts
+    /// it references with `__tablestart` and `__tablesize` is embedded as a code table of the same
+    /// name, so a factory can deploy them. `compiling` holds the contracts whose creation code is
+    /// being generated, to catch contracts embedding each other.
+    fn gen_contract_artifact(
+        &self,
+        file: Arc<FileSource>,
+        parsed: &Contract,
+        name: Option<&str>,
+        args: &ConstructorArgs,
+        compiling: &mut Vec<String>,
+    ) -> Result<Artifact, CompilerError<'a>> {
+        // Scope the contract, with its own copy of the constants to derive storage pointers for
+        let mut contract = match name.and_then(|n| parsed.find_contract_by_name(n)) {
+            Some(c) => c,
+            None => Contract {
+                constants: Arc::new(Mutex::new(parsed.constants.lock().unwrap().clone())),
+                ..parsed.clone()
+            },
+        };
+
+        // Embed the creation code of referenced contracts
+        for def in Self::referenced_contracts(&contract, parsed) {
+            let span = AstSpan(
+                def.span
+                    .0
+                    .iter()
+                    .cloned()
+                    .map(|mut s| {
+                        s.file = Some(Arc::clone(&file));
+                        s
+                    })
+                    .collect(),
+            );
+            if let Some(i) = compiling.iter().position(|c| *c == def.name) {
+                let mut cycle = compiling[i..].to_vec();
+                cycle.push(def.name.clone());
+                tracing::error!(target: "core", "CIRCULAR CONTRACT REFERENCE: {}", cycle.join(" -> "));
+                return Err(CompilerError::CodegenError(CodegenError {
+                    kind: CodegenErrorKind::CircularContractReference(cycle),
+                    span,
+                    token: None,
+                }))
+            }
+            compiling.push(def.name.clone());
+            let embedded = self.gen_contract_artifact(
+                Arc::clone(&file),
+                parsed,
+                Some(&def.name),
+                &ConstructorArgs::default(),
+                compiling,
+            )?;
+            compiling.pop();
+            tracing::info!(target: "core", "EMBEDDING CONTRACT \"{}\" AS A CODE TABLE", def.name);
+            let size = embedded.bytecode.len() / 2;
+            contract.tables.push(TableDefinition::new(
+                def.name.clone(),
+                TableKind::CodeTable,
+                vec![Statement { ty: StatementType::Code(embedded.bytecode), span: span.clone() }],
+                str_to_bytes32(format!("{size:02x}").as_str()),
+                span,
+            ));
+        }
+
+        contract.derive_storage_pointers_from(
+            self.alternative_main.as_deref().unwrap_or("MAIN"),
+            self.alternative_constructor.as_deref().unwrap_or("CONSTRUCTOR"),
+        );
+        if let Err(mut e) = contract.check_storage_collisions() {
+            e.span = AstSpan(
+                e.span
+                    .0
+                    .into_iter()
+                    .map(|mut s| {
+                        s.file = Some(Arc::clone(&file));
+                        s
+                    })
+                    .collect(),
+            );
+            return Err(CompilerError::CodegenError(e))
+        }
+        contract.add_override_constants(&self.constant_overrides);
+        self.insert_auto_revert(&mut contract);
 
         // Primary Bytecode Generation
         let mut cg = Codegen::new();
-        let main_bytecode = match Codegen::generate_main_bytecode(
+        let evm_version = self.evm_version.with_eof(self.eof);
+        let (main_sections, symbols) = match Codegen::generate_main_sections(
+            &evm_version,
             &contract,
             self.alternative_main.clone(),
+            self.macro_nesting_limit,
         ) {
             Ok(mb) => mb,
             Err(mut e) => {
@@ -421,21 +1004,70 @@ impl<'a> Compiler<'a> {
                 return Err(CompilerError::CodegenError(e))
             }
         };
-        tracing::info!(target: "core", "MAIN BYTECODE GENERATED [{}]", main_bytecode);
+        tracing::info!(target: "core", "MAIN BYTECODE GENERATED [{}]", main_sections);
+
+        // Make sure every jump of the entry points jumps to a label
+        if self.strict_jumps {
+            let entry_points = [
+                self.alternative_main.as_deref().unwrap_or("MAIN"),
+                self.alternative_constructor.as_deref().unwrap_or("CONSTRUCTOR"),
+            ];
+            for macro_def in
+                entry_points.iter().filter_map(|name| contract.find_macro_by_name(name))
+            {
+                if let Err(mut e) = validate_static_jumps(
+                    &self.evm_version,
+                    &contract,
+                    macro_def,
+                    self.macro_nesting_limit,
+                ) {
+                    e.span = AstSpan(
+                        e.span
+                            .0
+                            .into_iter()
+                            .map(|mut s| {
+                                s.file = Some(Arc::clone(&file));
+                                s
+                            })
+                            .collect(),
+                    );
+                    return Err(CompilerError::CodegenError(e))
+                }
+            }
+        }
+
+        // Make sure each function's selector reaches its own body, EOF code isn't executed
+        if self.check_dispatch && !self.eof {
+            if let Err(mut e) = validate_dispatch(&contract, &main_sections.to_string()) {
+                e.span = AstSpan(
+                    e.span
+                        .0
+                        .into_iter()
+                        .map(|mut s| {
+                            s.file = Some(Arc::clone(&file));
+                            s
+                        })
+                        .collect(),
+                );
+                return Err(CompilerError::CodegenError(e))
+            }
+        }
 
         // Generate Constructor Bytecode
-        let inputs = self.get_constructor_args();
-        let (constructor_bytecode, has_custom_bootstrap) =
-            match Codegen::generate_constructor_bytecode(
+        let mut default_constructor = false;
+        let (constructor_sections, has_custom_bootstrap) =
+            match Codegen::generate_constructor_sections(
+                &evm_version,
                 &contract,
                 self.alternative_constructor.clone(),
+                self.macro_nesting_limit,
             ) {
                 Ok(mb) => mb,
                 Err(mut e) => {
                     // Return any errors except if the inputs is empty and the constructor
                     // definition is missing
                     if e.kind != CodegenErrorKind::MissingMacroDefinition("CONSTRUCTOR".to_string()) ||
-                        !inputs.is_empty()
+                        !args.is_empty()
                     {
                         // Add File Source to Span
                         let mut errs = e
@@ -453,33 +1085,86 @@ impl<'a> Compiler<'a> {
                         return Err(CompilerError::CodegenError(e))
                     }
 
-                    // If the kind is a missing constructor we can ignore it
-                    tracing::warn!(target: "codegen", "Contract has no \"CONSTRUCTOR\" macro definition!");
-                    (String::default(), false)
+                    // If the kind is a missing constructor, deploy with only the bootstrap code
+                    tracing::info!(target: "codegen", "Contract has no \"CONSTRUCTOR\" macro definition, using the default constructor");
+                    default_constructor = true;
+                    (BytecodeSections::default(), false)
                 }
             };
-        tracing::info!(target: "core", "CONSTRUCTOR BYTECODE GENERATED [{}]", constructor_bytecode);
+        tracing::info!(target: "core", "CONSTRUCTOR BYTECODE GENERATED [{}]", constructor_sections);
 
-        // Encode Constructor Arguments
-        let encoded_inputs = Codegen::encode_constructor_args(inputs);
+        // Encode Constructor Arguments, typed against the declared constructor if there is one
+        let constructor =
+            contract.functions.iter().find(|f| f.name.to_lowercase() == "constructor");
+        let constructor_inputs = constructor.map(|c| {
+            c.inputs
+                .iter()
+                .map(|argument| FunctionParam {
+                    name: argument.name.clone().unwrap_or_default(),
+                    kind: argument.arg_type.clone().unwrap_or_default().into(),
+                    internal_type: None,
+                })
+                .collect::<Vec<FunctionParam>>()
+        });
+        let encoded_inputs = match args.tokenize(constructor_inputs.as_deref()) {
+            Ok(tokens) => tokens,
+            Err(msg) => {
+                tracing::error!(target: "core", "INVALID CONSTRUCTOR ARGUMENTS: {}", msg);
+                let spans = match constructor {
+                    Some(c) => c.span.0.clone(),
+                    None => vec![Span { start: 0, end: 0, file: None }],
+                };
+                return Err(CompilerError::CodegenError(CodegenError {
+                    kind: CodegenErrorKind::InvalidConstructorArgs(msg),
+                    span: AstSpan(
+                        spans
+                            .into_iter()
+                            .map(|mut s| {
+                                s.file = Some(Arc::clone(&file));
+                                s
+                            })
+                            .collect(),
+                    ),
+                    token: None,
+                }))
+            }
+        };
         tracing::info!(target: "core", "ENCODED {} INPUTS", encoded_inputs.len());
 
         // Generate Artifact with ABI
-        let churn_res = cg.churn(
-            file,
-            encoded_inputs,
-            &main_bytecode,
-            &constructor_bytecode,
-            has_custom_bootstrap,
-        );
+        let churn_res = if self.eof {
+            cg.churn_eof(
+                file,
+                encoded_inputs,
+                &main_sections,
+                &constructor_sections,
+                has_custom_bootstrap,
+            )
+        } else {
+            cg.churn(
+                file,
+                encoded_inputs,
+                &main_sections.to_string(),
+                &constructor_sections.to_string(),
+                has_custom_bootstrap,
+            )
+        };
         match churn_res {
             Ok(mut artifact) => {
+                artifact.contract_name = name.map(String::from);
+                artifact.default_constructor = default_constructor;
+                artifact.memory_layout = contract.memory_layout.clone();
+                artifact.storage_layout = contract.storage_layout.clone();
+                artifact.symbols = symbols;
+                artifact.metadata =
+                    Some(ArtifactMetadata::new(VERSION, self.settings(), &artifact.file));
+
                 // Then we can have the code gen output the artifact
                 let abiout = cg.abi_gen(contract, None);
                 match abiout {
                     Ok(abi) => {
                         tracing::info!(target: "core", "GENERATED ABI");
-                        artifact.abi = Some(abi)
+                        artifact.set_abi(abi)
                     }
                     Err(e) => {
                         tracing::error!(target: "core", "ARTIFACT GENERATION FAILED: {:?}", e)
@@ -494,19 +1179,59 @@ impl<'a> Compiler<'a> {
         }
     }
 
+    /// The contracts defined in a file that a contract references with `__tablestart` and
+    /// `__tablesize`, unless it defines a table of the same name
+    fn referenced_contracts<'c>(
+        contract: &Contract,
+        parsed: &'c Contract,
+    ) -> Vec<&'c ContractDefinition> {
+        let mut referenced: Vec<&ContractDefinition> = vec![];
+        let calls =
+            contract.macros.iter().flat_map(|m| &m.statements).filter_map(|s| match &s.ty {
+                StatementType::BuiltinFunctionCall(bf)
+                    if matches!(
+                        bf.kind,
+                        BuiltinFunctionKind::Tablestart | BuiltinFunctionKind::Tablesize
+                    ) =>
+                {
+                    bf.args.first().and_then(|a| a.name.as_deref())
+                }
+                _ => None,
+            });
+        for table in calls {
+            if contract.tables.iter().any(|t| t.name == table) {
+                continue
+            }
+            if let Some(def) = parsed.contracts.iter().find(|c| c.name == table) {
+                if !referenced.iter().any(|r| r.name == def.name) {
+                    referenced.push(def);
+                }
+            }
+        }
+        referenced
+    }
+
     /// Get the file sources for a vec of PathBufs
     pub fn fetch_sources(
         paths: Vec<PathBuf>,
-        reader: Arc<dyn FileProvider<'a>>,
+        reader: Arc<dyn FileProvider<'a> + 'a>,
     ) -> Vec<Result<Arc<FileSource>, CompilerError<'a>>> {
         paths.into_par_iter().map(|pb| reader.read_file(pb)).collect()
     }
 
+    /// The FileProvider for dependencies, reading `std/` includes from the standard library
+    pub fn dependency_provider(&self) -> Arc<dyn FileProvider<'a> + 'a> {
+        Arc::new(StdFileProvider::new(self.file_provider.clone(), self.std_lib.clone()))
+    }
+
     /// Recurses file dependencies
+    ///
+    /// Includes are remapped or localized relative to the including file, except for `std/`
+    /// includes which are left for the reader to resolve from the standard library.
     pub fn recurse_deps(
         fs: Arc<FileSource>,
         remapper: &Remapper,
-        reader: Arc<dyn FileProvider<'a>>,
+        reader: Arc<dyn FileProvider<'a> + 'a>,
     ) -> Result<Arc<FileSource>, Arc<CompilerError<'a>>> {
         tracing::debug!(target: "core", "RECURSING DEPENDENCIES FOR {}", fs.path);
         let mut new_fs = FileSource { path: fs.path.clone(), ..Default::default() };
@@ -539,6 +1264,9 @@ impl<'a> Compiler<'a> {
                         tracing::debug!(target: "core", "REMAPPED IMPORT PATH \"{}\"", import);
                         import = remapped;
                     }
+                    None if StdLib::is_std_import(&import) => {
+                        tracing::debug!(target: "core", "STANDARD LIBRARY IMPORT \"{}\"", import);
+                    }
                     None => {
                         import = FileSource::localize_file(&fs.path, &import)
                             .unwrap_or_default()
@@ -580,43 +1308,32 @@ impl<'a> Compiler<'a> {
         Ok(Arc::new(new_fs))
     }
 
+    /// The names artifacts are labeled with: the compiler's name, followed by the contract name
+    /// or file stem of each artifact when several are compiled, i.e. `Token.ERC20`
+    pub fn artifact_names(&self, artifacts: &[Arc<Artifact>]) -> Vec<Option<String>> {
+        artifacts
+            .iter()
+            .map(|a| {
+                let name = self.name.as_ref()?;
+                match artifacts.len() {
+                    1 => Some(name.clone()),
+                    _ => Some(format!("{name}.{}", a.default_name())),
+                }
+            })
+            .collect()
+    }
+
     /// Export Artifacts
     ///
-    /// 1. Cleans any previous artifacts in the output directory.
-    /// 2. Exports artifacts in parallel as serialized json `Artifact` objects.
-    pub fn export_artifacts(artifacts: &Vec<Arc<Artifact>>, output: &OutputLocation) {
-        // Exit if empty output location
-        if output.0.is_empty() {
-            tracing::warn!(target: "core", "Exiting artifact export with empty output location!");
-            return
-        }
-
-        // Clean the Output Directory
-        tracing::warn!(target: "core", "REMOVING DIRECTORY: \"{}\"", output.0);
-        if !output.0.is_empty() && fs::remove_dir_all(&output.0).is_ok() {
-            tracing::info!(target: "core", "OUTPUT DIRECTORY DELETED!");
-        }
-
-        // Is the output a directory or a file?
-        let is_file = std::path::PathBuf::from(&output.0).extension().is_some();
-
-        // Export the artifacts with parallelized io
-        artifacts.into_par_iter().for_each(|a| {
-            // If it's a file type, we just export to `output.0`
-            let json_out = match is_file {
-                true => output.0.clone(),
-                false => format!(
-                    "{}/{}.json",
-                    output.0,
-                    a.file.path.to_uppercase().replacen("./", "", 1)
-                ),
-            };
-
-            if let Err(e) = a.export(&json_out) {
-                tracing::error!(target: "core", "ARTIFACT EXPORT FAILED!\nError: {:?}", e);
-            }
-            tracing::info!(target: "core", "EXPORTED ARTIFACT TO \"{}\"", json_out);
-        });
+    /// Exports artifacts to the output location with an [OutputManager], returning a [Manifest]
+    /// of the written artifacts.
+    pub fn export_artifacts(
+        artifacts: &[Arc<Artifact>],
+        output: &OutputLocation,
+        layout: OutputLayout,
+        format: OutputFormat,
+    ) -> Manifest {
+        OutputManager { format, ..OutputManager::new(output.clone(), layout) }.export(artifacts)
     }
 
     /// Derives Constructor Input Arguments
@@ -627,6 +1344,66 @@ impl<'a> Compiler<'a> {
         }
     }
 
+    /// Derives the constructor arguments, read from the constructor arguments file if one is set
+    pub fn constructor_args(&self) -> Result<ConstructorArgs, CompilerError<'a>> {
+        let path = match &self.constructor_args_path {
+            Some(path) => path,
+            None => return Ok(ConstructorArgs::Values(self.get_constructor_args())),
+        };
+        let contents = std::fs::read_to_string(path).map_err(|_| {
+            tracing::error!(target: "core", "FAILED TO READ CONSTRUCTOR ARGUMENTS FILE \"{}\"", path);
+            CompilerError::FileUnpackError(UnpackError::MissingFile(path.clone()))
+        })?;
+        ConstructorArgs::from_file_contents(&contents).map_err(|msg| {
+            CompilerError::CodegenError(CodegenError {
+                kind: CodegenErrorKind::InvalidConstructorArgs(msg),
+                span: AstSpan(vec![Span {
+                    start: 0,
+                    end: 0,
+                    file: Some(Arc::new(FileSource {
+                        path: path.clone(),
+                        source: Some(contents.clone()),
+                        ..Default::default()
+                    })),
+                }]),
+                token: None,
+            })
+        })
+    }
+
+    /// Checks the storage layouts of the artifacts against the previous storage layout, if set
+    pub fn check_storage_layouts(
+        &self,
+        artifacts: &[Arc<Artifact>],
+    ) -> Result<(), CompilerError<'a>> {
+        let path = match &self.storage_check_path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let contents = std::fs::read_to_string(path).map_err(|_| {
+            tracing::error!(target: "core", "FAILED TO READ STORAGE LAYOUT FILE \"{}\"", path);
+            CompilerError::FileUnpackError(UnpackError::MissingFile(path.clone()))
+        })?;
+        let check = StorageCheck::from_file_contents(&contents)
+            .map_err(|msg| CompilerError::IncompatibleStorageLayout(vec![msg]))?;
+        let incompatibilities = artifacts
+            .iter()
+            .filter(|a| check.applies_to(a))
+            .flat_map(|a| {
+                check
+                    .incompatibilities(&a.storage_layout)
+                    .into_iter()
+                    .map(|i| format!("{}: {i}", a.identifier()))
+                    .collect::<Vec<String>>()
+            })
+            .collect::<Vec<String>>();
+        if !incompatibilities.is_empty() {
+            tracing::error!(target: "core", "INCOMPATIBLE STORAGE LAYOUT: {:?}", incompatibilities);
+            return Err(CompilerError::IncompatibleStorageLayout(incompatibilities))
+        }
+        Ok(())
+    }
+
     /// Derives an output location
     pub fn get_outputs(&self) -> OutputLocation {
         match &self.output {
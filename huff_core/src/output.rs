@@ -0,0 +1,317 @@
+//! ## Output
+//!
+//! Lays out compiled artifacts in an output directory, mirroring the source tree, or as the
+//! artifacts of Hardhat and Foundry projects.
+
+use huff_utils::prelude::{Artifact, OutputLocation};
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use huff_utils::wasm::IntoParallelIterator;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    fmt, fs,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+/// The file name of the manifest written alongside exported artifacts
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// How artifact files are laid out in the output directory
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OutputLayout {
+    /// Mirrors the source tree with uppercased paths, i.e. `./src/ERC20.huff` is written to
+    /// `<output>/SRC/ERC20.HUFF.json`
+    #[default]
+    Uppercase,
+    /// Mirrors the source tree as is, i.e. `./src/ERC20.huff` is written to
+    /// `<output>/src/ERC20.huff.json`
+    Mirror,
+}
+
+impl FromStr for OutputLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "uppercase" => Ok(OutputLayout::Uppercase),
+            "mirror" => Ok(OutputLayout::Mirror),
+            _ => {
+                Err(format!("Unknown output layout \"{s}\", expected \"uppercase\" or \"mirror\""))
+            }
+        }
+    }
+}
+
+impl fmt::Display for OutputLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputLayout::Uppercase => write!(f, "uppercase"),
+            OutputLayout::Mirror => write!(f, "mirror"),
+        }
+    }
+}
+
+/// The format artifact files are written in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OutputFormat {
+    /// Serialized [Artifact]s, laid out by the [OutputLayout]
+    #[default]
+    Huff,
+    /// Hardhat artifacts, i.e. `./src/ERC20.huff` is written to
+    /// `<output>/src/ERC20.huff/ERC20.json`
+    Hardhat,
+    /// Foundry artifacts, i.e. `./src/ERC20.huff` is written to `<output>/ERC20.huff/ERC20.json`
+    Foundry,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "huff" => Ok(OutputFormat::Huff),
+            "hardhat" => Ok(OutputFormat::Hardhat),
+            "foundry" => Ok(OutputFormat::Foundry),
+            _ => Err(format!(
+                "Unknown output format \"{s}\", expected \"huff\", \"hardhat\" or \"foundry\""
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Huff => write!(f, "huff"),
+            OutputFormat::Hardhat => write!(f, "hardhat"),
+            OutputFormat::Foundry => write!(f, "foundry"),
+        }
+    }
+}
+
+/// The source path of an artifact without root, `.` and `..` components, i.e. `src/ERC20.huff`
+fn source_name(artifact: &Artifact) -> String {
+    Path::new(&artifact.file.path)
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(c) => Some(c.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+/// The artifact of a contract in the format of Hardhat's `hh-sol-artifact-1` artifacts
+pub fn hardhat_artifact(artifact: &Artifact) -> Value {
+    json!({
+        "_format": "hh-sol-artifact-1",
+        "contractName": artifact.default_name(),
+        "sourceName": source_name(artifact),
+        "abi": json_abi(artifact),
+        "bytecode": format!("0x{}", artifact.bytecode),
+        "deployedBytecode": format!("0x{}", artifact.runtime),
+        "linkReferences": {},
+        "deployedLinkReferences": {},
+    })
+}
+
+/// The artifact of a contract in the format of Foundry's `out/` artifacts
+pub fn foundry_artifact(artifact: &Artifact) -> Value {
+    json!({
+        "abi": json_abi(artifact),
+        "bytecode": {
+            "object": format!("0x{}", artifact.bytecode),
+            "sourceMap": "",
+            "linkReferences": {},
+        },
+        "deployedBytecode": {
+            "object": format!("0x{}", artifact.runtime),
+            "sourceMap": "",
+            "linkReferences": {},
+            "immutableReferences": {},
+        },
+        "methodIdentifiers": artifact.method_identifiers,
+    })
+}
+
+/// The standard JSON ABI of an artifact, empty without an ABI
+fn json_abi(artifact: &Artifact) -> Value {
+    artifact.abi.as_ref().map(|abi| abi.to_json_abi()).unwrap_or_else(|| json!([]))
+}
+
+/// An artifact written by the [OutputManager]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ManifestEntry {
+    /// The path of the compiled source file
+    pub source: String,
+    /// The path the artifact was written to, relative to the output directory
+    pub artifact: String,
+}
+
+/// The artifacts written by an export
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    /// The written artifacts, sorted by source path
+    pub artifacts: Vec<ManifestEntry>,
+}
+
+/// Writes artifacts to an output location
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OutputManager {
+    /// The output directory, or file if it has an extension
+    pub output: OutputLocation,
+    /// How artifacts are laid out in the output directory
+    pub layout: OutputLayout,
+    /// The format artifacts are written in
+    pub format: OutputFormat,
+}
+
+impl OutputManager {
+    /// Public associated function to instantiate a new OutputManager.
+    pub fn new(output: OutputLocation, layout: OutputLayout) -> Self {
+        Self { output, layout, format: OutputFormat::default() }
+    }
+
+    /// The path of an artifact relative to the output directory
+    ///
+    /// Root, `.` and `..` components of the source path are dropped, so artifacts are always
+    /// written inside the output directory. Artifacts of contracts defined with `#define contract`
+    /// blocks are suffixed with the contract name, i.e. `src/Factory.huff.Child.json`.
+    ///
+    /// Hardhat and Foundry artifacts are laid out the way those tools expect instead: one file per
+    /// contract, in a directory named after the source, i.e. `src/Factory.huff/Child.json` for
+    /// Hardhat and `Factory.huff/Child.json` for Foundry.
+    pub fn relative_path(&self, artifact: &Artifact) -> PathBuf {
+        let source = source_name(artifact);
+        match self.format {
+            OutputFormat::Huff => {}
+            OutputFormat::Hardhat => {
+                return PathBuf::from(format!("{source}/{}.json", artifact.default_name()))
+            }
+            OutputFormat::Foundry => {
+                let file = source.rsplit('/').next().unwrap_or_default();
+                return PathBuf::from(format!("{file}/{}.json", artifact.default_name()))
+            }
+        }
+        let source = match &artifact.contract_name {
+            Some(name) => format!("{source}.{name}"),
+            None => source,
+        };
+        let source = match self.layout {
+            OutputLayout::Uppercase => source.to_uppercase(),
+            OutputLayout::Mirror => source,
+        };
+        PathBuf::from(format!("{source}.json"))
+    }
+
+    /// Write an artifact to a file in the manager's format
+    pub fn write(&self, artifact: &Artifact, out: &Path) -> std::io::Result<()> {
+        let contents = match self.format {
+            OutputFormat::Huff => return artifact.export(&out.to_string_lossy()),
+            OutputFormat::Hardhat => hardhat_artifact(artifact),
+            OutputFormat::Foundry => foundry_artifact(artifact),
+        };
+        if let Some(p) = out.parent() {
+            fs::create_dir_all(p)?
+        }
+        fs::write(out, serde_json::to_string_pretty(&contents)?)
+    }
+
+    /// Exports artifacts as json
+    ///
+    /// 1. Cleans any previous artifacts in the output directory.
+    /// 2. Exports artifacts in parallel as json objects of the manager's [OutputFormat].
+    /// 3. Writes a [Manifest] of the exported artifacts to [MANIFEST_FILE] in the output directory.
+    ///    Hardhat and Foundry read every json file of their artifacts directory, so the manifest is
+    ///    only returned, not written, for those formats.
+    ///
+    /// If the output location is a file, a single artifact is written to it directly. Multiple
+    /// artifacts are laid out in the file's directory instead, as they'd overwrite each other.
+    pub fn export(&self, artifacts: &[Arc<Artifact>]) -> Manifest {
+        // Exit if empty output location
+        if self.output.0.is_empty() {
+            tracing::warn!(target: "core", "Exiting artifact export with empty output location!");
+            return Manifest::default()
+        }
+
+        // Is the output a directory or a file?
+        let output = PathBuf::from(&self.output.0);
+        if output.extension().is_some() {
+            if let [artifact] = artifacts {
+                return match self.write(artifact, &output) {
+                    Ok(()) => {
+                        tracing::info!(target: "core", "EXPORTED ARTIFACT TO \"{}\"", self.output.0);
+                        Manifest {
+                            artifacts: vec![ManifestEntry {
+                                source: artifact.file.path.clone(),
+                                artifact: self.output.0.clone(),
+                            }],
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!(target: "core", "ARTIFACT EXPORT FAILED!\nError: {:?}", e);
+                        Manifest::default()
+                    }
+                }
+            }
+            // Don't clean the file's directory, it may hold anything
+            let dir = match output.parent() {
+                Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+                _ => PathBuf::from("."),
+            };
+            tracing::warn!(target: "core", "Exporting {} artifacts to the directory of output file \"{}\"", artifacts.len(), self.output.0);
+            return self.export_to_dir(&dir, artifacts)
+        }
+
+        // Clean the Output Directory
+        tracing::warn!(target: "core", "REMOVING DIRECTORY: \"{}\"", self.output.0);
+        if fs::remove_dir_all(&output).is_ok() {
+            tracing::info!(target: "core", "OUTPUT DIRECTORY DELETED!");
+        }
+
+        self.export_to_dir(&output, artifacts)
+    }
+
+    /// Exports artifacts into a directory, writing the [Manifest] alongside them
+    fn export_to_dir(&self, output: &Path, artifacts: &[Arc<Artifact>]) -> Manifest {
+        // Export the artifacts with parallelized io
+        let mut entries = artifacts
+            .iter()
+            .collect::<Vec<&Arc<Artifact>>>()
+            .into_par_iter()
+            .filter_map(|a| {
+                let relative = self.relative_path(a);
+                let json_out = output.join(&relative);
+                if let Err(e) = self.write(a, &json_out) {
+                    tracing::error!(target: "core", "ARTIFACT EXPORT FAILED!\nError: {:?}", e);
+                    return None
+                }
+                tracing::info!(target: "core", "EXPORTED ARTIFACT TO \"{}\"", json_out.display());
+                Some(ManifestEntry {
+                    source: a.file.path.clone(),
+                    artifact: relative.to_string_lossy().to_string(),
+                })
+            })
+            .collect::<Vec<ManifestEntry>>();
+        entries.sort();
+
+        let manifest = Manifest { artifacts: entries };
+        if self.format != OutputFormat::Huff {
+            return manifest
+        }
+        let manifest_out = output.join(MANIFEST_FILE);
+        let written = fs::create_dir_all(output)
+            .and_then(|_| fs::write(&manifest_out, serde_json::to_string_pretty(&manifest)?));
+        match written {
+            Ok(()) => {
+                tracing::info!(target: "core", "EXPORTED MANIFEST TO \"{}\"", manifest_out.display())
+            }
+            Err(e) => tracing::error!(target: "core", "MANIFEST EXPORT FAILED!\nError: {:?}", e),
+        }
+        manifest
+    }
+}
@@ -0,0 +1,331 @@
+//! ## Packages
+//!
+//! Installs the Huff libraries a project depends on.
+//!
+//! Dependencies are declared in a `huff.toml` file at the project root as git repositories,
+//! optionally pinned to a tag:
+//!
+//! ```toml
+//! [dependencies]
+//! huffmate = { git = "https://github.com/huff-language/huffmate", tag = "v0.0.2", src = "src" }
+//! ```
+//!
+//! Installing vendors each dependency under `lib/<name>`, records the resolved commit in
+//! `huff.lock` and adds a `<name>/=lib/<name>/<src>/` remapping to `remappings.txt`, so the
+//! library is included with `#include "huffmate/tokens/ERC20.huff"`.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Component, Path, PathBuf},
+    process::Command,
+};
+
+/// The name of the project package manifest
+pub const PACKAGE_MANIFEST: &str = "huff.toml";
+
+/// The name of the file recording installed dependencies
+pub const LOCK_FILE: &str = "huff.lock";
+
+/// The directory dependencies are vendored in
+pub const LIB_DIR: &str = "lib";
+
+/// The name of the remappings file, read by the compiler to resolve includes
+pub const REMAPPINGS_FILE: &str = "remappings.txt";
+
+/// A package error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageError(pub String);
+
+/// fmt::Display implementation for `PackageError`
+impl fmt::Display for PackageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Package Error: {}", self.0)
+    }
+}
+
+/// Convert a `std::io::Error` to a `PackageError`
+impl From<std::io::Error> for PackageError {
+    fn from(e: std::io::Error) -> Self {
+        PackageError(e.to_string())
+    }
+}
+
+/// Convert a `toml::de::Error` to a `PackageError`
+impl From<toml::de::Error> for PackageError {
+    fn from(e: toml::de::Error) -> Self {
+        PackageError(e.to_string())
+    }
+}
+
+/// Convert a `toml::ser::Error` to a `PackageError`
+impl From<toml::ser::Error> for PackageError {
+    fn from(e: toml::ser::Error) -> Self {
+        PackageError(e.to_string())
+    }
+}
+
+/// A dependency declared in the package manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dependency {
+    /// The url of the git repository
+    pub git: String,
+    /// The tag to check out, defaults to the repository's default branch
+    pub tag: Option<String>,
+    /// The directory of the repository includes are remapped to, defaults to its root
+    pub src: Option<String>,
+}
+
+/// The package manifest, read from `huff.toml`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct PackageManifest {
+    /// Dependencies, keyed by the name they're installed and remapped as
+    pub dependencies: BTreeMap<String, Dependency>,
+}
+
+impl PackageManifest {
+    /// Parses a package manifest from a toml string
+    pub fn from_toml(source: &str) -> Result<Self, PackageError> {
+        let manifest: Self = toml::from_str(source)?;
+        if let Some(name) = manifest.dependencies.keys().find(|name| !is_valid_name(name)) {
+            return Err(PackageError(format!("Invalid dependency name \"{name}\"")))
+        }
+        if let Some(dependency) = manifest.dependencies.values().find(|d| !is_valid_url(&d.git)) {
+            return Err(PackageError(format!("Invalid dependency url \"{}\"", dependency.git)))
+        }
+        let mut sources = manifest.dependencies.values().filter_map(|d| d.src.as_deref());
+        if let Some(src) = sources.find(|src| !is_valid_src(src)) {
+            return Err(PackageError(format!("Invalid dependency src \"{src}\"")))
+        }
+        Ok(manifest)
+    }
+
+    /// Reads the package manifest in the given directory
+    pub fn discover(root: &Path) -> Result<Self, PackageError> {
+        let path = root.join(PACKAGE_MANIFEST);
+        match fs::read_to_string(&path) {
+            Ok(source) => Self::from_toml(&source),
+            Err(_) => {
+                Err(PackageError(format!("No {PACKAGE_MANIFEST} found in \"{}\"", root.display())))
+            }
+        }
+    }
+}
+
+/// An installed dependency, recorded in `huff.lock`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    /// The url of the git repository
+    pub git: String,
+    /// The checked out tag
+    pub tag: Option<String>,
+    /// The commit the dependency was installed at
+    pub rev: String,
+}
+
+/// The installed dependencies, read from `huff.lock`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PackageLock {
+    /// Installed dependencies, keyed by name
+    pub packages: BTreeMap<String, LockedPackage>,
+}
+
+impl PackageLock {
+    /// Reads the lock file in the given directory, empty if there is none
+    pub fn discover(root: &Path) -> Result<Self, PackageError> {
+        match fs::read_to_string(root.join(LOCK_FILE)) {
+            Ok(source) => Ok(toml::from_str(&source)?),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Writes the lock file to the given directory
+    pub fn write(&self, root: &Path) -> Result<(), PackageError> {
+        fs::write(root.join(LOCK_FILE), toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// What installing a dependency did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallStatus {
+    /// The dependency was fetched
+    Installed,
+    /// The dependency was already installed at the declared tag
+    UpToDate,
+}
+
+/// The result of installing a dependency
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledPackage {
+    /// The dependency name
+    pub name: String,
+    /// The commit the dependency is installed at
+    pub rev: String,
+    /// What installing the dependency did
+    pub status: InstallStatus,
+}
+
+/// Installs the dependencies declared in a project's package manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Installer {
+    /// The project root, holding `huff.toml`
+    pub root: PathBuf,
+    /// Whether to fetch dependencies that are already installed
+    pub force: bool,
+}
+
+impl Installer {
+    /// Public associated function to instantiate a new Installer.
+    pub fn new(root: impl Into<PathBuf>, force: bool) -> Self {
+        Self { root: root.into(), force }
+    }
+
+    /// Installs every declared dependency
+    ///
+    /// 1. Fetches dependencies that aren't installed at their declared tag into `lib/<name>`.
+    /// 2. Records the installed commits in `huff.lock`.
+    /// 3. Adds a remapping for each dependency to `remappings.txt`.
+    pub fn install(&self) -> Result<Vec<InstalledPackage>, PackageError> {
+        let manifest = PackageManifest::discover(&self.root)?;
+        let mut lock = PackageLock::discover(&self.root)?;
+
+        let mut installed = vec![];
+        for (name, dependency) in &manifest.dependencies {
+            let dest = self.root.join(LIB_DIR).join(name);
+            let locked = lock
+                .packages
+                .get(name)
+                .filter(|l| l.git == dependency.git && l.tag == dependency.tag && dest.is_dir());
+            let package = match locked {
+                Some(l) if !self.force => InstalledPackage {
+                    name: name.clone(),
+                    rev: l.rev.clone(),
+                    status: InstallStatus::UpToDate,
+                },
+                _ => {
+                    tracing::info!(target: "core", "INSTALLING \"{}\" FROM {}", name, dependency.git);
+                    let rev = fetch(dependency, &dest)?;
+                    InstalledPackage { name: name.clone(), rev, status: InstallStatus::Installed }
+                }
+            };
+            lock.packages.insert(
+                name.clone(),
+                LockedPackage {
+                    git: dependency.git.clone(),
+                    tag: dependency.tag.clone(),
+                    rev: package.rev.clone(),
+                },
+            );
+            installed.push(package);
+        }
+        lock.packages.retain(|name, _| manifest.dependencies.contains_key(name));
+        lock.write(&self.root)?;
+
+        let remappings_path = self.root.join(REMAPPINGS_FILE);
+        let existing = fs::read_to_string(&remappings_path).unwrap_or_default();
+        fs::write(&remappings_path, merge_remappings(&existing, &manifest))?;
+
+        Ok(installed)
+    }
+}
+
+/// The remapping of a dependency, i.e. `huffmate/=lib/huffmate/src/`
+pub fn remapping(name: &str, dependency: &Dependency) -> String {
+    match dependency.src.as_deref().map(|s| s.trim_matches('/')) {
+        Some(src) if !src.is_empty() => format!("{name}/={LIB_DIR}/{name}/{src}/"),
+        _ => format!("{name}/={LIB_DIR}/{name}/"),
+    }
+}
+
+/// Adds the remappings of the manifest's dependencies to the contents of a remappings file
+///
+/// Existing remappings of other prefixes are kept as is, and those of a dependency's prefix are
+/// replaced.
+pub fn merge_remappings(existing: &str, manifest: &PackageManifest) -> String {
+    let prefixes =
+        manifest.dependencies.keys().map(|name| format!("{name}/")).collect::<Vec<String>>();
+    let mut lines = existing
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter(|line| match line.split_once('=') {
+            Some((from, _)) => !prefixes.iter().any(|p| p == from.trim()),
+            None => true,
+        })
+        .map(String::from)
+        .collect::<Vec<String>>();
+    lines.extend(manifest.dependencies.iter().map(|(name, d)| remapping(name, d)));
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+/// Whether a dependency name is usable as a directory and remapping prefix
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() &&
+        name != "std" &&
+        name != "." &&
+        name != ".." &&
+        name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+/// Whether a dependency source directory stays within the vendored dependency
+fn is_valid_src(src: &str) -> bool {
+    Path::new(src).components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// Whether a dependency url is cloned over https, ssh or from the filesystem
+///
+/// Urls can't be mistaken for options of `git clone`, and other transports, i.e. `ext::`, which
+/// runs a command, are rejected.
+fn is_valid_url(url: &str) -> bool {
+    if url.is_empty() || url.starts_with('-') {
+        return false
+    }
+    let scheme = match (url.find("://"), url.find("::")) {
+        (Some(i), _) | (None, Some(i)) => &url[..i],
+        // Paths, and scp-like ssh urls, i.e. `git@github.com:huff-language/huffmate`
+        (None, None) => return true,
+    };
+    matches!(scheme, "https" | "ssh" | "file")
+}
+
+/// Fetches a dependency into a directory, returning the fetched commit
+///
+/// The repository's git metadata is removed, leaving only the vendored sources.
+fn fetch(dependency: &Dependency, dest: &Path) -> Result<String, PackageError> {
+    if dest.exists() {
+        fs::remove_dir_all(dest)?;
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut clone = Command::new("git");
+    clone.args(["clone", "--quiet", "--depth", "1"]);
+    if let Some(tag) = &dependency.tag {
+        clone.args(["--branch", tag]);
+    }
+    clone.arg("--").arg(&dependency.git).arg(dest);
+    git(&mut clone, &dependency.git)?;
+
+    let rev =
+        git(Command::new("git").arg("-C").arg(dest).args(["rev-parse", "HEAD"]), &dependency.git)?;
+    fs::remove_dir_all(dest.join(".git"))?;
+    Ok(rev)
+}
+
+/// Runs a git command, returning its trimmed stdout
+fn git(command: &mut Command, url: &str) -> Result<String, PackageError> {
+    let output = command
+        .output()
+        .map_err(|e| PackageError(format!("Failed to run git, is it installed? {e}")))?;
+    if !output.status.success() {
+        return Err(PackageError(format!(
+            "Failed to fetch \"{url}\": {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
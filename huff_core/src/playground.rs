@@ -0,0 +1,265 @@
+//! ## Playground
+//!
+//! Compiles a single source string in one call, for the web playground and language bindings.
+//!
+//! [compile_str] reports every failure as a diagnostic of the [PlaygroundOutput], along with the
+//! warnings of the analysis and the time spent in each stage of the compilation.
+//!
+//! Errors in the source are returned by each stage rather than panicking. Panics of an internal
+//! compiler error are caught as a safety net and reported as a diagnostic too, which only works
+//! when panics unwind: the workspace `release` profile aborts on panic, so bindings are built with
+//! the `bindings` profile instead.
+//!
+//! ```rust
+//! use huff_core::playground::{compile_str, PlaygroundSettings};
+//!
+//! let output = compile_str(
+//!     "#define macro MAIN() = takes(0) returns(0) { 0x01 0x00 mstore }",
+//!     &PlaygroundSettings::default(),
+//! );
+//! assert!(output.errors.is_empty());
+//! assert_eq!(output.runtime.as_deref(), Some("6001600052"));
+//! ```
+
+use crate::Compiler;
+use huff_codegen::{Codegen, DEFAULT_MACRO_NESTING_LIMIT};
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::{abi::Abi, files::Remapper, prelude::*, time::get_current_millis};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::Arc,
+};
+
+/// The path the source of a playground compilation is compiled as
+pub const PLAYGROUND_FILE: &str = "playground.huff";
+
+/// The settings of a playground compilation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaygroundSettings {
+    /// The EVM version to target
+    #[serde(rename = "evmVersion")]
+    pub evm_version: String,
+    /// The language edition of the source, unless it declares its own
+    pub edition: String,
+    /// The macro compiled as the runtime bytecode, instead of MAIN
+    #[serde(rename = "mainMacro")]
+    pub main_macro: Option<String>,
+    /// The macro compiled as the constructor, instead of CONSTRUCTOR
+    #[serde(rename = "constructorMacro")]
+    pub constructor_macro: Option<String>,
+    /// The constructor arguments
+    #[serde(rename = "constructorArgs")]
+    pub constructor_args: Option<Vec<String>>,
+    /// The hex encoded values of overridden constants, by name
+    #[serde(rename = "constantOverrides")]
+    pub constant_overrides: BTreeMap<String, String>,
+    /// The maximum number of macros that may be expanded within one another
+    #[serde(rename = "macroNestingLimit")]
+    pub macro_nesting_limit: usize,
+}
+
+impl Default for PlaygroundSettings {
+    fn default() -> Self {
+        Self {
+            evm_version: EVMVersion::default().to_string(),
+            edition: Edition::default().to_string(),
+            main_macro: None,
+            constructor_macro: None,
+            constructor_args: None,
+            constant_overrides: BTreeMap::new(),
+            macro_nesting_limit: DEFAULT_MACRO_NESTING_LIMIT,
+        }
+    }
+}
+
+/// An error or warning of a playground compilation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// The rendered message
+    pub message: String,
+    /// The byte ranges of the source the diagnostic is about, excluding included files
+    pub spans: Vec<(usize, usize)>,
+}
+
+impl Diagnostic {
+    /// A diagnostic without a location in the source
+    pub fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into(), spans: vec![] }
+    }
+
+    /// A diagnostic about spans, keeping those in the playground source.
+    fn with_spans<'s>(message: String, spans: impl IntoIterator<Item = &'s Span>) -> Self {
+        let spans = spans
+            .into_iter()
+            .filter(|s| s.file.as_ref().map(|f| f.path == PLAYGROUND_FILE).unwrap_or(true))
+            .map(|s| (s.start, s.end))
+            .collect();
+        Self { message, spans }
+    }
+
+    /// The diagnostics of a compiler error, one per failed compilation.
+    fn from_error(error: &CompilerError) -> Vec<Self> {
+        let message = error.to_string();
+        match error {
+            CompilerError::LexicalError(e) => vec![Self::with_spans(message, [&e.span])],
+            CompilerError::ParserError(e) => vec![Self::with_spans(message, &e.spans.0)],
+            CompilerError::CodegenError(e) => vec![Self::with_spans(message, &e.span.0)],
+            CompilerError::IncompatiblePragma(_, span) |
+            CompilerError::InvalidAbiImport(_, span) |
+            CompilerError::InvalidMerkleLeaves(_, span) => vec![Self::with_spans(message, &span.0)],
+            CompilerError::FailedCompiles(errors) => {
+                errors.iter().flat_map(Self::from_error).collect()
+            }
+            _ => vec![Self::new(message)],
+        }
+    }
+}
+
+/// The milliseconds spent in each stage of a playground compilation
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageTimings {
+    /// Reading the source and resolving its includes
+    pub resolving: f64,
+    /// Lexing the flattened source
+    pub lexing: f64,
+    /// Parsing the tokens and analyzing the contract
+    pub parsing: f64,
+    /// Generating the bytecode
+    pub codegen: f64,
+}
+
+/// The output of a playground compilation
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaygroundOutput {
+    /// The creation bytecode, unless compilation failed
+    pub bytecode: Option<String>,
+    /// The runtime bytecode, unless compilation failed
+    pub runtime: Option<String>,
+    /// The ABI, unless compilation failed
+    pub abi: Option<Abi>,
+    /// The errors, empty if compilation succeeded
+    pub errors: Vec<Diagnostic>,
+    /// The warnings of lexing and analyzing the contract
+    pub warnings: Vec<Diagnostic>,
+    /// The time spent in each stage
+    pub timings: StageTimings,
+}
+
+/// Compile a source string into its bytecode, runtime bytecode and ABI.
+///
+/// The source may include the standard library, but no other files. Only the top level
+/// definitions are compiled, not the contracts of `#define contract` blocks.
+pub fn compile_str(source: &str, settings: &PlaygroundSettings) -> PlaygroundOutput {
+    let mut output = PlaygroundOutput::default();
+    let res = catch_unwind(AssertUnwindSafe(|| compile(source, settings, &mut output)));
+    match res {
+        Ok(Ok(artifact)) => {
+            output.bytecode = Some(artifact.bytecode);
+            output.runtime = Some(artifact.runtime);
+            output.abi = artifact.abi;
+        }
+        Ok(Err(errors)) => output.errors = errors,
+        Err(panic) => {
+            let reason = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            output.errors = vec![Diagnostic::new(format!("Internal compiler error: {reason}"))];
+        }
+    }
+    output
+}
+
+/// Run each stage of the compilation, recording its timings and warnings in the output
+fn compile(
+    source: &str,
+    settings: &PlaygroundSettings,
+    output: &mut PlaygroundOutput,
+) -> Result<Artifact, Vec<Diagnostic>> {
+    let evm_version =
+        settings.evm_version.parse::<EVMVersion>().map_err(|e| vec![Diagnostic::new(e)])?;
+    let edition = settings.edition.parse::<Edition>().map_err(|e| vec![Diagnostic::new(e)])?;
+    let mut constant_overrides = BTreeMap::new();
+    for (name, value) in &settings.constant_overrides {
+        let hex = value.strip_prefix("0x").unwrap_or(value);
+        if hex.is_empty() || hex.len() > 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(vec![Diagnostic::new(format!(
+                "Invalid constant override: \"{name}\" = \"{value}\""
+            ))])
+        }
+        constant_overrides.insert(name.as_str(), str_to_bytes32(hex));
+    }
+
+    let mut compiler = Compiler::new_in_memory(
+        Arc::new(vec![PLAYGROUND_FILE.to_string()]),
+        HashMap::from([(PLAYGROUND_FILE.to_string(), source.to_string())]),
+        settings.main_macro.clone(),
+        settings.constructor_macro.clone(),
+        settings.constructor_args.clone(),
+        None,
+        false,
+    );
+    compiler.evm_version = evm_version;
+    compiler.edition = edition;
+    compiler.macro_nesting_limit = settings.macro_nesting_limit;
+    compiler.constant_overrides = Some(constant_overrides);
+    let errors = |e: &CompilerError| Diagnostic::from_error(e);
+
+    // Read the source and resolve its includes
+    let start = get_current_millis();
+    let paths =
+        compiler.file_provider.transform_paths(&compiler.sources).map_err(|e| errors(&e))?;
+    let file = Compiler::fetch_sources(paths, compiler.file_provider.clone())
+        .into_iter()
+        .next()
+        .ok_or_else(|| vec![Diagnostic::new("Missing playground source")])?
+        .map_err(|e| errors(&e))?;
+    let file = Compiler::recurse_deps(file, &Remapper::new("./"), compiler.dependency_provider())
+        .map_err(|e| errors(&e))?;
+    output.timings.resolving = get_current_millis() - start;
+
+    // Lex the flattened source
+    let start = get_current_millis();
+    let flattened = FileSource::fully_flatten(Arc::clone(&file));
+    let full_source =
+        FullFileSource { source: &flattened.0, file: Some(Arc::clone(&file)), spans: flattened.1 };
+    let mut lexer = Lexer::new(full_source);
+    lexer.edition = edition;
+    let tokens = lexer
+        .by_ref()
+        .collect::<Result<Vec<Token>, LexicalError>>()
+        .map_err(|e| errors(&CompilerError::LexicalError(e)))?;
+    output.timings.lexing = get_current_millis() - start;
+
+    // Parse and analyze the contract
+    let start = get_current_millis();
+    let mut parser = Parser::new(tokens, Some(file.path.clone()));
+    parser.edition = edition;
+    let contract = parser.parse().map_err(|e| errors(&CompilerError::ParserError(e)))?;
+    compiler.check_pragmas(&contract).map_err(|e| errors(&e))?;
+    for warning in
+        lexer.warnings.iter().filter(|w| !contract.suppressions.iter().any(|s| s.silences(w)))
+    {
+        output.warnings.push(Diagnostic::with_spans(warning.kind.to_string(), &warning.span.0));
+    }
+    let main = compiler.alternative_main.as_deref().unwrap_or("MAIN");
+    for warning in Codegen::analyze_with_main(&contract, main) {
+        output.warnings.push(Diagnostic::with_spans(warning.kind.to_string(), &warning.span.0));
+    }
+    output.timings.parsing = get_current_millis() - start;
+
+    // Generate the bytecode
+    let start = get_current_millis();
+    let args = compiler.constructor_args().map_err(|e| errors(&e))?;
+    let artifact = compiler
+        .gen_contract_artifact(file, &contract, None, &args, &mut vec![])
+        .map_err(|e| errors(&e))?;
+    output.timings.codegen = get_current_millis() - start;
+
+    Ok(artifact)
+}
@@ -0,0 +1,186 @@
+//! ## Proxies
+//!
+//! Generates proxies delegating calls to an implementation contract, either an
+//! [ERC-1167](https://eips.ethereum.org/EIPS/eip-1167) minimal proxy or an
+//! [EIP-1967](https://eips.ethereum.org/EIPS/eip-1967) transparent upgradeable proxy.
+//!
+//! The implementation, and the admin of a transparent proxy, are either set when generating the
+//! proxy or left to be appended to the creation code as ABI-encoded constructor arguments.
+//!
+//! ```rust
+//! use huff_core::proxy::{Proxy, ProxyKind};
+//!
+//! let proxy = Proxy::new(ProxyKind::Minimal, Some("0xbebebebebebebebebebebebebebebebebebebebe"), None);
+//! let artifact = proxy.artifact().unwrap();
+//! assert_eq!(
+//!     artifact.runtime,
+//!     "363d3d373d3d3d363d73bebebebebebebebebebebebebebebebebebebebe5af43d82803e903d91602b57fd5bf3"
+//! );
+//! ```
+
+use crate::Compiler;
+use huff_utils::prelude::*;
+use std::{collections::HashMap, fmt, str::FromStr, sync::Arc};
+
+/// The runtime code of an ERC-1167 minimal proxy preceding the implementation address
+pub const MINIMAL_PROXY_PREFIX: &str = "363d3d373d3d3d363d73";
+
+/// The runtime code of an ERC-1167 minimal proxy following the implementation address
+pub const MINIMAL_PROXY_SUFFIX: &str = "5af43d82803e903d91602b57fd5bf3";
+
+/// Copies the 45 byte runtime code following it to memory and returns it
+const MINIMAL_PROXY_INITCODE: &str = "3d602d80600a3d3981f3";
+
+/// Reads the implementation from the last word of the code, then returns the runtime code built
+/// from it in memory
+const MINIMAL_PROXY_ARG_INITCODE: &str = "602080380360003960005169363d3d373d3d3d363d7360a01b177f5af43d82803e903d91602b57fd5bf30000000000000000000000000000000000602052600052602d6002f3";
+
+/// The transparent proxy template, taking the implementation and admin as constructor arguments
+pub const TRANSPARENT_PROXY_SOURCE: &str = include_str!("../proxies/transparent_proxy.huff");
+
+/// The path the transparent proxy template is compiled as
+pub const TRANSPARENT_PROXY_PATH: &str = "proxies/transparent_proxy.huff";
+
+/// The path of generated minimal proxy artifacts
+pub const MINIMAL_PROXY_PATH: &str = "proxies/minimal_proxy";
+
+/// The kind of proxy to generate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyKind {
+    /// An ERC-1167 minimal proxy, delegating every call to a fixed implementation
+    Minimal,
+    /// An EIP-1967 transparent proxy, whose implementation is upgradeable by its admin
+    Transparent,
+}
+
+impl FromStr for ProxyKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "minimal" | "erc1167" => Ok(ProxyKind::Minimal),
+            "transparent" | "eip1967" => Ok(ProxyKind::Transparent),
+            _ => {
+                Err(format!("Unknown proxy kind \"{s}\", expected \"minimal\" or \"transparent\""))
+            }
+        }
+    }
+}
+
+impl fmt::Display for ProxyKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProxyKind::Minimal => write!(f, "minimal"),
+            ProxyKind::Transparent => write!(f, "transparent"),
+        }
+    }
+}
+
+/// A proxy of an implementation contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proxy {
+    /// The kind of proxy
+    pub kind: ProxyKind,
+    /// The implementation address, a constructor argument if unset
+    pub implementation: Option<String>,
+    /// The admin address of a transparent proxy, a constructor argument if unset
+    pub admin: Option<String>,
+}
+
+impl Proxy {
+    /// Public associated function to instantiate a new Proxy.
+    pub fn new(kind: ProxyKind, implementation: Option<&str>, admin: Option<&str>) -> Self {
+        Self {
+            kind,
+            implementation: implementation.map(String::from),
+            admin: admin.map(String::from),
+        }
+    }
+
+    /// Generates the proxy's artifact
+    ///
+    /// Proxies whose addresses are constructor arguments take them ABI-encoded after the creation
+    /// code, and the runtime code of such a minimal proxy is only known once it's deployed.
+    pub fn artifact(&self) -> Result<Artifact, CompilerError<'static>> {
+        let implementation = self.implementation.as_deref().map(parse_address).transpose()?;
+        let admin = self.admin.as_deref().map(parse_address).transpose()?;
+        match self.kind {
+            ProxyKind::Minimal => {
+                if admin.is_some() {
+                    return Err(CompilerError::InvalidProxy(
+                        "Minimal proxies have no admin".to_string(),
+                    ))
+                }
+                Ok(minimal_proxy(implementation.as_deref()))
+            }
+            ProxyKind::Transparent => match (implementation, admin) {
+                (Some(implementation), Some(admin)) => {
+                    transparent_proxy(Some(vec![implementation, admin]))
+                }
+                (None, None) => transparent_proxy(None),
+                _ => Err(CompilerError::InvalidProxy(
+                    "Transparent proxies take both an implementation and an admin, or neither"
+                        .to_string(),
+                )),
+            },
+        }
+    }
+}
+
+/// Validates a hex encoded address, returning it without its `0x` prefix
+fn parse_address(address: &str) -> Result<String, CompilerError<'static>> {
+    let hex = address.strip_prefix("0x").unwrap_or(address);
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(CompilerError::InvalidProxy(format!("Invalid address \"{address}\"")))
+    }
+    Ok(hex.to_lowercase())
+}
+
+/// Generates an ERC-1167 minimal proxy of the implementation, or of the constructor argument
+fn minimal_proxy(implementation: Option<&str>) -> Artifact {
+    let mut abi = Abi { fallback: true, ..Default::default() };
+    let (bytecode, runtime) = match implementation {
+        Some(implementation) => {
+            let runtime = format!("{MINIMAL_PROXY_PREFIX}{implementation}{MINIMAL_PROXY_SUFFIX}");
+            (format!("{MINIMAL_PROXY_INITCODE}{runtime}"), runtime)
+        }
+        None => {
+            abi.constructor = Some(Constructor {
+                inputs: vec![FunctionParam {
+                    name: "implementation".to_string(),
+                    kind: FunctionParamType::Address,
+                    internal_type: None,
+                }],
+            });
+            (MINIMAL_PROXY_ARG_INITCODE.to_string(), String::new())
+        }
+    };
+    let mut artifact = Artifact {
+        file: Arc::new(FileSource { path: MINIMAL_PROXY_PATH.to_string(), ..Default::default() }),
+        bytecode,
+        runtime,
+        ..Default::default()
+    };
+    artifact.set_abi(abi);
+    artifact
+}
+
+/// Compiles the transparent proxy template, with the implementation and admin appended to its
+/// creation code if set
+fn transparent_proxy(
+    construct_args: Option<Vec<String>>,
+) -> Result<Artifact, CompilerError<'static>> {
+    let file_sources =
+        HashMap::from([(TRANSPARENT_PROXY_PATH.to_string(), TRANSPARENT_PROXY_SOURCE.to_string())]);
+    let compiler = Compiler::new_in_memory(
+        Arc::new(vec![TRANSPARENT_PROXY_PATH.to_string()]),
+        file_sources,
+        None,
+        None,
+        construct_args,
+        None,
+        false,
+    );
+    let artifacts = compiler.execute().map_err(|e| e.as_ref().clone())?;
+    Ok(artifacts[0].as_ref().clone())
+}
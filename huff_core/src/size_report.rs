@@ -0,0 +1,125 @@
+//! ## Size Reports
+//!
+//! Attributes the bytes of each contract's runtime bytecode to the macros they were generated
+//! by, to show what takes up the code size limit of [EIP-170](https://eips.ethereum.org/EIPS/eip-170).
+//!
+//! The bytes of a macro invocation are attributed to the invoked macro, except for the bytes of
+//! the macros it invokes in turn, using the symbols of the artifact. Bytes outside of any
+//! invocation, i.e. appended tables, are reported on their own.
+//!
+//! ```text
+//! src/ERC20.huff: 1234 bytes, 5.02% of the 24576 byte limit
+//!     TRANSFER_FROM    412   33.39%  (1 invocation)
+//!     MAIN             301   24.39%  (1 invocation)
+//!     <tables>          96    7.78%
+//! ```
+
+use huff_utils::prelude::Artifact;
+use std::{collections::BTreeMap, fmt};
+
+/// The maximum size of runtime bytecode, set by EIP-170
+pub const MAX_RUNTIME_SIZE: usize = 24576;
+
+/// The name bytes outside of any macro invocation are reported under
+pub const UNATTRIBUTED: &str = "<tables>";
+
+/// The bytes of a runtime bytecode generated by a macro
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MacroSize {
+    /// The macro, or [UNATTRIBUTED] for the bytes outside of any invocation
+    pub name: String,
+    /// The bytes generated by its invocations, excluding the bytes of the macros they invoke
+    pub size: usize,
+    /// The number of invocations
+    pub invocations: usize,
+}
+
+/// The breakdown of the runtime bytecode of a contract by macro
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SizeReport {
+    /// The artifact identifier of the contract
+    pub contract: String,
+    /// The size of the runtime bytecode in bytes
+    pub runtime: usize,
+    /// The bytes generated by each macro, largest first
+    pub macros: Vec<MacroSize>,
+}
+
+impl SizeReport {
+    /// Break the runtime bytecode of an artifact down by the macros of its symbols.
+    pub fn new(artifact: &Artifact) -> Self {
+        let runtime = artifact.runtime.len() / 2;
+
+        // Symbols are ordered parents first, so the bytes of an invocation are the bytes of its
+        // range without those of the invocations nested directly within it
+        let symbols = &artifact.symbols;
+        let mut sizes = symbols.iter().map(|s| s.end - s.start).collect::<Vec<usize>>();
+        for (i, s) in symbols.iter().enumerate() {
+            if let Some(parent) =
+                symbols[..i].iter().rposition(|p| p.start <= s.start && s.end <= p.end)
+            {
+                sizes[parent] = sizes[parent].saturating_sub(s.end - s.start);
+            }
+        }
+
+        let mut macros: BTreeMap<&str, MacroSize> = BTreeMap::new();
+        for (s, size) in symbols.iter().zip(sizes) {
+            let entry = macros
+                .entry(&s.name)
+                .or_insert_with(|| MacroSize { name: s.name.clone(), ..Default::default() });
+            entry.size += size;
+            entry.invocations += 1;
+        }
+
+        // Only top level invocations cover bytes that aren't covered by others
+        let attributed = symbols
+            .iter()
+            .enumerate()
+            .filter(|(i, s)| !symbols[..*i].iter().any(|p| p.start <= s.start && s.end <= p.end))
+            .map(|(_, s)| s.end - s.start)
+            .sum::<usize>();
+        let mut macros = macros.into_values().collect::<Vec<MacroSize>>();
+        if runtime > attributed {
+            macros.push(MacroSize {
+                name: UNATTRIBUTED.to_string(),
+                size: runtime - attributed,
+                invocations: 0,
+            });
+        }
+        macros.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+        Self { contract: artifact.identifier(), runtime, macros }
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = |size: usize, of: usize| match of {
+            0 => 0.0,
+            _ => size as f64 * 100.0 / of as f64,
+        };
+        writeln!(
+            f,
+            "{}: {} bytes, {:.2}% of the {MAX_RUNTIME_SIZE} byte limit",
+            self.contract,
+            self.runtime,
+            percent(self.runtime, MAX_RUNTIME_SIZE)
+        )?;
+        let width = self.macros.iter().map(|m| m.name.len()).max().unwrap_or_default();
+        for m in &self.macros {
+            write!(
+                f,
+                "    {:width$}  {:>5}  {:>6.2}%",
+                m.name,
+                m.size,
+                percent(m.size, self.runtime)
+            )?;
+            match m.invocations {
+                0 => writeln!(f)?,
+                1 => writeln!(f, "  (1 invocation)")?,
+                n => writeln!(f, "  ({n} invocations)")?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+//! ## Snapshots
+//!
+//! Records the size and keccak256 hash of each contract's runtime bytecode, along with the bytes
+//! each macro emits into it, to catch unexpected changes of the generated code.
+//!
+//! A snapshot file holds a line per contract and per macro, with its size in bytes and its hash:
+//!
+//! ```text
+//! src/ERC20.huff 1234 0x4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45
+//! src/ERC20.huff::MAIN 96 0x0b7ac5c2c1e8e2a5a5f4a1b2e0e37d9bd3c2a2f6f4fb2f0e3c1e1cd6a0f2b1a9
+//! ```
+//!
+//! The bytes of a macro are the instructions generated from its statements, with the offsets of
+//! jump labels and tables left unresolved, so moving a macro doesn't change the others.
+
+use ethers_core::utils::{hex, keccak256};
+use huff_codegen::Codegen;
+use huff_utils::prelude::*;
+use std::{collections::BTreeMap, fmt, str::FromStr, sync::Arc};
+
+/// The default path of the snapshot file
+pub const DEFAULT_SNAPSHOT_PATH: &str = ".huff-snapshot";
+
+/// The size and keccak256 hash of bytecode
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BytecodeSnapshot {
+    /// The size in bytes
+    pub size: usize,
+    /// The hex encoded keccak256 hash, prefixed with 0x
+    pub hash: String,
+}
+
+impl BytecodeSnapshot {
+    /// Snapshot bytes.
+    pub fn new(bytes: &[u8], size: usize) -> Self {
+        Self { size, hash: format!("0x{}", hex::encode(keccak256(bytes))) }
+    }
+}
+
+/// The snapshot of a contract's runtime bytecode
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContractSnapshot {
+    /// The runtime bytecode
+    pub runtime: BytecodeSnapshot,
+    /// The bytes emitted by each macro of the runtime bytecode
+    pub macros: BTreeMap<String, BytecodeSnapshot>,
+}
+
+/// Snapshots of contracts, by artifact identifier
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// The contracts, by artifact identifier
+    pub contracts: BTreeMap<String, ContractSnapshot>,
+}
+
+impl Snapshot {
+    /// Snapshot the runtime bytecode of artifacts, with the bytes emitted by the macros of their
+    /// main macro found in the parsed contracts.
+    pub fn new(
+        artifacts: &[Arc<Artifact>],
+        contracts: &[Contract],
+        evm_version: &EVMVersion,
+        main: &str,
+        nesting_limit: usize,
+    ) -> Self {
+        let mut snapshot = Snapshot::default();
+        for artifact in artifacts {
+            let runtime = hex::decode(&artifact.runtime).unwrap_or_default();
+            let macros = contracts
+                .iter()
+                .find(|c| c.macros.iter().any(|m| span_start(&m.span).0 == artifact.file.path))
+                .and_then(|c| match &artifact.contract_name {
+                    Some(name) => c.find_contract_by_name(name).map(|mut c| {
+                        c.derive_storage_pointers();
+                        c
+                    }),
+                    None => Some(c.clone()),
+                })
+                .map(|c| macro_snapshots(&c, evm_version, main, nesting_limit))
+                .unwrap_or_default();
+            snapshot.contracts.insert(
+                artifact.identifier(),
+                ContractSnapshot {
+                    runtime: BytecodeSnapshot::new(&runtime, runtime.len()),
+                    macros,
+                },
+            );
+        }
+        snapshot
+    }
+
+    /// The changes from this snapshot to another, with each changed contract followed by its
+    /// changed macros.
+    pub fn diff(&self, other: &Snapshot) -> Vec<SnapshotDiff> {
+        let mut diffs = vec![];
+        let mut names = self.contracts.keys().chain(other.contracts.keys()).collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        for name in names {
+            let (old, new) = (self.contracts.get(name), other.contracts.get(name));
+            if old == new {
+                continue
+            }
+            diffs.push(SnapshotDiff {
+                contract: name.clone(),
+                macro_name: None,
+                old: old.map(|c| c.runtime.clone()),
+                new: new.map(|c| c.runtime.clone()),
+            });
+
+            let (old, new) = (old.cloned().unwrap_or_default(), new.cloned().unwrap_or_default());
+            let mut macros = old.macros.keys().chain(new.macros.keys()).collect::<Vec<_>>();
+            macros.sort();
+            macros.dedup();
+            for macro_name in macros {
+                let (old, new) = (old.macros.get(macro_name), new.macros.get(macro_name));
+                if old != new {
+                    diffs.push(SnapshotDiff {
+                        contract: name.clone(),
+                        macro_name: Some(macro_name.clone()),
+                        old: old.cloned(),
+                        new: new.cloned(),
+                    });
+                }
+            }
+        }
+        diffs
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, contract) in &self.contracts {
+            writeln!(f, "{name} {} {}", contract.runtime.size, contract.runtime.hash)?;
+            for (macro_name, bytes) in &contract.macros {
+                writeln!(f, "{name}::{macro_name} {} {}", bytes.size, bytes.hash)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Snapshot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut snapshot = Snapshot::default();
+        for (i, line) in s.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let invalid = || format!("Invalid snapshot line {}: \"{line}\"", i + 1);
+            let mut parts = line.trim().rsplitn(3, ' ');
+            let (hash, size, name) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(hash), Some(size), Some(name)) => (hash, size, name),
+                _ => return Err(invalid()),
+            };
+            let bytes = BytecodeSnapshot {
+                size: size.parse().map_err(|_| invalid())?,
+                hash: hash.to_string(),
+            };
+            match name.rsplit_once("::") {
+                Some((contract, macro_name)) => {
+                    snapshot
+                        .contracts
+                        .entry(contract.to_string())
+                        .or_default()
+                        .macros
+                        .insert(macro_name.to_string(), bytes);
+                }
+                None => snapshot.contracts.entry(name.to_string()).or_default().runtime = bytes,
+            }
+        }
+        Ok(snapshot)
+    }
+}
+
+/// A changed contract or macro between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    /// The artifact identifier of the contract
+    pub contract: String,
+    /// The macro, if the change is to the bytes emitted by a macro of the contract
+    pub macro_name: Option<String>,
+    /// The previous snapshot, if the contract or macro existed
+    pub old: Option<BytecodeSnapshot>,
+    /// The new snapshot, if the contract or macro still exists
+    pub new: Option<BytecodeSnapshot>,
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.macro_name {
+            Some(macro_name) => write!(f, "    {macro_name}: ")?,
+            None => write!(f, "{}: ", self.contract)?,
+        }
+        match (&self.old, &self.new) {
+            (None, Some(new)) => write!(f, "added ({} bytes)", new.size),
+            (Some(old), None) => write!(f, "removed ({} bytes)", old.size),
+            (Some(old), Some(new)) if old.size == new.size => {
+                write!(f, "bytecode changed ({} bytes)", new.size)
+            }
+            (Some(old), Some(new)) => write!(
+                f,
+                "{} -> {} bytes ({:+})",
+                old.size,
+                new.size,
+                new.size as i64 - old.size as i64
+            ),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// The file path and offset a span starts at
+fn span_start(span: &AstSpan) -> (String, usize) {
+    span.0.iter().find_map(|s| Some((s.file.as_ref()?.path.clone(), s.start))).unwrap_or_default()
+}
+
+/// Snapshot the bytes each macro emits into the main macro of a contract, attributing
+/// instructions to the macro defining the statement they were generated from.
+fn macro_snapshots(
+    contract: &Contract,
+    evm_version: &EVMVersion,
+    main: &str,
+    nesting_limit: usize,
+) -> BTreeMap<String, BytecodeSnapshot> {
+    let main_macro = match contract.find_macro_by_name(main) {
+        Some(m) => m,
+        None => return BTreeMap::default(),
+    };
+    let res = match Codegen::macro_to_bytecode(
+        evm_version,
+        main_macro,
+        contract,
+        &mut vec![main_macro],
+        0,
+        &mut Vec::default(),
+        false,
+        None,
+        nesting_limit,
+    ) {
+        Ok(res) => res,
+        Err(_) => return BTreeMap::default(),
+    };
+
+    // The file and source range of each macro
+    let ranges = contract
+        .macros
+        .iter()
+        .filter_map(|m| {
+            let spans = m.span.0.iter().filter(|s| s.file.is_some()).collect::<Vec<_>>();
+            let path = spans.first()?.file.as_ref()?.path.clone();
+            let start = spans.iter().map(|s| s.start).min()?;
+            let end = spans.iter().map(|s| s.end).max()?;
+            Some((m.name.clone(), path, start, end))
+        })
+        .collect::<Vec<_>>();
+
+    let mut emitted: BTreeMap<String, (String, usize)> = BTreeMap::new();
+    for (_, instruction) in &res.instructions {
+        let (path, start) = span_start(&instruction.source_span);
+        if let Some((name, ..)) =
+            ranges.iter().find(|(_, p, s, e)| *p == path && (*s..=*e).contains(&start))
+        {
+            let (bytes, size) = emitted.entry(name.clone()).or_default();
+            bytes.push_str(&instruction.to_string());
+            *size += instruction.len();
+        }
+    }
+    emitted
+        .into_iter()
+        .map(|(name, (bytes, size))| (name, BytecodeSnapshot::new(bytes.as_bytes(), size)))
+        .collect()
+}
@@ -0,0 +1,331 @@
+//! ## Solidity Interfaces
+//!
+//! Reads the `interface` blocks of Solidity sources imported with `#include sol "IERC20.sol"`
+//! into the ABI of each interface.
+//!
+//! Only signatures are parsed: the functions, events and errors of each interface, along with the
+//! structs, enums and user defined value types of the file their parameters use. Contracts,
+//! interfaces and libraries of the file used as parameter types are encoded as addresses, and an
+//! interface inherits the entries of the interfaces of the file it extends.
+
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// The ABI of each interface of a Solidity source, in the standard JSON ABI format, by the name
+/// of the interface
+pub fn interfaces(source: &str) -> Result<Vec<(String, Value)>, String> {
+    let tokens = tokenize(source);
+    let types = Types::collect(&tokens)?;
+
+    // The parents and own entries of each interface
+    let mut declared: Vec<(String, Vec<String>, Vec<Value>)> = vec![];
+    let mut i = 0;
+    while i < tokens.len() {
+        if tokens[i] != "interface" {
+            i += 1;
+            continue
+        }
+        let name = token(&tokens, i + 1).to_string();
+        let open = match tokens[i..].iter().position(|t| t == "{") {
+            Some(open) => i + open,
+            None => return Err(format!("interface \"{name}\" has no body")),
+        };
+        // `interface IERC20Metadata is IERC20, IERC165 {`
+        let parents = tokens[i + 2..open]
+            .iter()
+            .filter(|t| !matches!(t.as_str(), "is" | ","))
+            .cloned()
+            .collect::<Vec<String>>();
+        let close = closing(&tokens, open)?;
+        let entries = types
+            .entries(&tokens[open + 1..close])
+            .map_err(|e| format!("{e} in interface \"{name}\""))?;
+        declared.push((name, parents, entries));
+        i = close + 1;
+    }
+
+    fn inherited(declared: &[(String, Vec<String>, Vec<Value>)], name: &str, abi: &mut Vec<Value>) {
+        if let Some((_, parents, entries)) = declared.iter().find(|(n, _, _)| n == name) {
+            parents.iter().for_each(|parent| inherited(declared, parent, abi));
+            abi.extend(entries.iter().filter(|e| !abi.contains(e)).cloned().collect::<Vec<_>>());
+        }
+    }
+    Ok(declared
+        .iter()
+        .map(|(name, _, _)| {
+            let mut abi = vec![];
+            inherited(&declared, name, &mut abi);
+            (name.clone(), Value::Array(abi))
+        })
+        .collect())
+}
+
+/// The user defined types of a source
+#[derive(Default)]
+struct Types {
+    /// The type and name tokens of the fields of each struct
+    structs: BTreeMap<String, Vec<Vec<String>>>,
+    /// The elementary type of each enum and user defined value type
+    aliases: BTreeMap<String, String>,
+    /// The contracts, interfaces and libraries, encoded as addresses
+    contracts: Vec<String>,
+}
+
+impl Types {
+    /// Collects the types defined anywhere in the source
+    fn collect(tokens: &[String]) -> Result<Self, String> {
+        let mut types = Types::default();
+        for (i, t) in tokens.iter().enumerate() {
+            let name = token(tokens, i + 1).to_string();
+            match t.as_str() {
+                // `struct Order { address maker; uint256 amount; }`
+                "struct" if token(tokens, i + 2) == "{" => {
+                    let fields = tokens[i + 3..closing(tokens, i + 2)?]
+                        .split(|t| t == ";")
+                        .filter(|f| !f.is_empty())
+                        .map(|f| f.to_vec())
+                        .collect();
+                    types.structs.insert(name, fields);
+                }
+                // Enums are encoded as their index
+                "enum" => {
+                    types.aliases.insert(name, "uint8".to_string());
+                }
+                // `type Price is uint128;`
+                "type" if token(tokens, i + 2) == "is" => {
+                    types.aliases.insert(name, token(tokens, i + 3).to_string());
+                }
+                "interface" | "contract" | "library" => types.contracts.push(name),
+                _ => {}
+            }
+        }
+        Ok(types)
+    }
+
+    /// The ABI entries of the functions, events and errors declared in the body of an interface
+    fn entries(&self, body: &[String]) -> Result<Vec<Value>, String> {
+        let mut entries = vec![];
+        let mut i = 0;
+        while i < body.len() {
+            let kind = body[i].as_str();
+            let name = token(body, i + 1);
+            match kind {
+                "function" | "event" | "error" => {
+                    let open = i + 2;
+                    if token(body, open) != "(" {
+                        return Err(format!("expected the parameters of {kind} \"{name}\""))
+                    }
+                    let close = closing(body, open)?;
+                    let end = body[close..]
+                        .iter()
+                        .position(|t| t == ";")
+                        .map_or(body.len(), |e| close + e);
+                    let modifiers = &body[close + 1..end];
+                    let inputs = self.params(&body[open + 1..close], kind == "event")?;
+                    entries.push(match kind {
+                        "function" => {
+                            let outputs = match modifiers.iter().position(|t| t == "returns") {
+                                Some(r) if token(modifiers, r + 1) == "(" => {
+                                    let close = closing(modifiers, r + 1)?;
+                                    self.params(&modifiers[r + 2..close], false)?
+                                }
+                                _ => vec![],
+                            };
+                            let mutability = ["pure", "view", "payable"]
+                                .into_iter()
+                                .find(|m| modifiers.iter().any(|t| t == m))
+                                .unwrap_or("nonpayable");
+                            json!({
+                                "type": "function",
+                                "name": name,
+                                "inputs": inputs,
+                                "outputs": outputs,
+                                "stateMutability": mutability,
+                            })
+                        }
+                        "event" => json!({
+                            "type": "event",
+                            "name": name,
+                            "inputs": inputs,
+                            "anonymous": modifiers.iter().any(|t| t == "anonymous"),
+                        }),
+                        _ => json!({ "type": "error", "name": name, "inputs": inputs }),
+                    });
+                    i = end + 1;
+                }
+                // Types were already collected
+                "struct" | "enum" => {
+                    i = match body[i..].iter().position(|t| t == "{") {
+                        Some(open) => closing(body, i + open)? + 1,
+                        None => body.len(),
+                    };
+                }
+                _ => {
+                    i += body[i..].iter().position(|t| t == ";").unwrap_or(body.len()) + 1;
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// The ABI parameters of a parameter list, i.e. `address indexed from, uint256 amount`
+    fn params(&self, list: &[String], indexed: bool) -> Result<Vec<Value>, String> {
+        let mut params = vec![];
+        let mut depth = 0;
+        let mut start = 0;
+        for (i, t) in list.iter().enumerate() {
+            match t.as_str() {
+                "(" | "[" => depth += 1,
+                ")" | "]" => depth -= 1,
+                "," if depth == 0 => {
+                    params.push(self.param(&list[start..i], indexed, &mut vec![])?);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        if start < list.len() {
+            params.push(self.param(&list[start..], indexed, &mut vec![])?);
+        }
+        Ok(params)
+    }
+
+    /// The ABI parameter of a parameter's tokens, i.e. `uint256[] calldata amounts`
+    ///
+    /// `structs` holds the structs being expanded, to catch recursive structs.
+    fn param(
+        &self,
+        tokens: &[String],
+        indexed: bool,
+        structs: &mut Vec<String>,
+    ) -> Result<Value, String> {
+        // The last part of a qualified type, i.e. `IERC20.Permit`
+        let mut i = 1;
+        let mut base = token(tokens, 0);
+        while token(tokens, i) == "." {
+            base = token(tokens, i + 1);
+            i += 2;
+        }
+        if token(tokens, i) == "payable" {
+            i += 1;
+        }
+        let mut suffix = String::new();
+        while token(tokens, i) == "[" {
+            let close = closing(tokens, i)?;
+            suffix.push_str(&format!("[{}]", tokens[i + 1..close].concat()));
+            i = close + 1;
+        }
+        let rest = &tokens[i.min(tokens.len())..];
+        let name = rest
+            .iter()
+            .rfind(|t| !matches!(t.as_str(), "memory" | "calldata" | "storage" | "indexed"))
+            .map_or("", |t| t.as_str());
+
+        let mut param = if let Some(fields) = self.structs.get(base) {
+            if structs.iter().any(|s| s == base) {
+                return Err(format!("recursive struct \"{base}\""))
+            }
+            structs.push(base.to_string());
+            let components = fields
+                .iter()
+                .map(|f| self.param(f, false, structs))
+                .collect::<Result<Vec<Value>, String>>()?;
+            structs.pop();
+            json!({ "name": name, "type": format!("tuple{suffix}"), "components": components })
+        } else {
+            let kind = match self.aliases.get(base) {
+                Some(alias) => elementary(alias),
+                None if self.contracts.iter().any(|c| c == base) => Some("address".to_string()),
+                None => elementary(base),
+            };
+            match kind {
+                Some(kind) => json!({ "name": name, "type": format!("{kind}{suffix}") }),
+                None => return Err(format!("unsupported type \"{base}\"")),
+            }
+        };
+        if indexed {
+            param["indexed"] = json!(rest.iter().any(|t| t == "indexed"));
+        }
+        Ok(param)
+    }
+}
+
+/// The canonical name of an elementary type, i.e. `uint256` for `uint`
+fn elementary(kind: &str) -> Option<String> {
+    let sized = |prefix: &str| {
+        kind.strip_prefix(prefix).is_some_and(|size| size.chars().all(|c| c.is_ascii_digit()))
+    };
+    match kind {
+        "uint" | "int" => Some(format!("{kind}256")),
+        "byte" => Some("bytes1".to_string()),
+        "address" | "bool" | "string" | "bytes" => Some(kind.to_string()),
+        _ if sized("uint") || sized("int") || sized("bytes") => Some(kind.to_string()),
+        _ => None,
+    }
+}
+
+/// Splits a source into identifiers, numbers and punctuation, dropping comments and strings
+fn tokenize(source: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                chars.by_ref().find(|c| *c == '\n');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break
+                    }
+                    previous = c;
+                }
+            }
+            '"' | '\'' => {
+                let mut escaped = false;
+                for n in chars.by_ref() {
+                    if n == c && !escaped {
+                        break
+                    }
+                    escaped = n == '\\' && !escaped;
+                }
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '$' => {
+                let mut word = c.to_string();
+                while let Some(n) = chars.next_if(|n| n.is_alphanumeric() || *n == '_' || *n == '$')
+                {
+                    word.push(n);
+                }
+                tokens.push(word);
+            }
+            c if c.is_whitespace() => {}
+            c => tokens.push(c.to_string()),
+        }
+    }
+    tokens
+}
+
+/// The token at the index, or an empty string past the end
+fn token(tokens: &[String], i: usize) -> &str {
+    tokens.get(i).map_or("", |t| t.as_str())
+}
+
+/// The index of the bracket closing the one opened at the index
+fn closing(tokens: &[String], open: usize) -> Result<usize, String> {
+    let mut depth = 0;
+    for (i, t) in tokens.iter().enumerate().skip(open) {
+        match t.as_str() {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(i)
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(format!("unclosed \"{}\"", token(tokens, open)))
+}
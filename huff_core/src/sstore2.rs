@@ -0,0 +1,130 @@
+//! ## SSTORE2 Data Contracts
+//!
+//! Generates the creation code of [SSTORE2](https://github.com/transmissions11/solmate/blob/main/src/utils/SSTORE2.sol)
+//! data contracts, storing bytes as the runtime code of a contract so they can be read back with
+//! `extcodecopy` for much less gas than from storage.
+//!
+//! The runtime code is the stored bytes prefixed with a `stop`, so calling the data contract
+//! never executes them. The bytes are either those of a data section, i.e.
+//! `#define data PALETTE = 0x...`, or of a file. The address a data contract is deployed at by
+//! `CREATE2` can be derived along with its creation code.
+//!
+//! ```rust
+//! use huff_core::sstore2::DataContract;
+//!
+//! let data = DataContract::new("PALETTE", vec![0xde, 0xad, 0xbe, 0xef]);
+//! assert_eq!(data.runtime(), "00deadbeef");
+//! assert_eq!(data.creation_code(), "600b5981380380925939f300deadbeef");
+//! ```
+
+use crate::size_report::MAX_RUNTIME_SIZE;
+use ethers_core::{
+    types::Address,
+    utils::{get_create2_address, hex, to_checksum},
+};
+use huff_utils::prelude::*;
+use std::sync::Arc;
+
+/// Copies the code following it to memory and returns it, whatever its size
+pub const SSTORE2_INITCODE: &str = "600b5981380380925939f3";
+
+/// Prefixes the stored bytes, so calling the data contract returns right away
+pub const DATA_PREFIX: &str = "00";
+
+/// A data contract storing bytes as its runtime code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataContract {
+    /// The name of the data section or the path of the file the bytes are read from
+    pub name: String,
+    /// The stored bytes
+    pub data: Vec<u8>,
+}
+
+impl DataContract {
+    /// Public associated function to instantiate a new DataContract.
+    pub fn new(name: impl Into<String>, data: Vec<u8>) -> Self {
+        Self { name: name.into(), data }
+    }
+
+    /// A data contract storing the bytes of the data section of a contract
+    pub fn from_section(contract: &Contract, name: &str) -> Result<Self, CompilerError<'static>> {
+        let section = contract
+            .tables
+            .iter()
+            .find(|t| t.name == name && t.kind == TableKind::Data)
+            .ok_or_else(|| {
+                CompilerError::InvalidDataContract(format!("Missing data section \"{name}\""))
+            })?;
+        let mut data = vec![];
+        for s in &section.statements {
+            if let StatementType::Code(code) = &s.ty {
+                let bytes = hex::decode(code).map_err(|_| {
+                    CompilerError::InvalidDataContract(format!(
+                        "Data section \"{name}\" isn't a whole number of bytes"
+                    ))
+                })?;
+                data.extend(bytes);
+            }
+        }
+        Ok(Self::new(name, data))
+    }
+
+    /// The runtime code of the data contract, the stored bytes prefixed with a `stop`
+    pub fn runtime(&self) -> String {
+        format!("{DATA_PREFIX}{}", hex::encode(&self.data))
+    }
+
+    /// The creation code of the data contract, returning its runtime code
+    pub fn creation_code(&self) -> String {
+        format!("{SSTORE2_INITCODE}{}", self.runtime())
+    }
+
+    /// Generates the data contract's artifact
+    ///
+    /// The runtime code, including the `stop` prefixing the bytes, must fit in the code size
+    /// limit of EIP-170.
+    pub fn artifact(&self) -> Result<Artifact, CompilerError<'static>> {
+        let size = self.data.len() + DATA_PREFIX.len() / 2;
+        if size > MAX_RUNTIME_SIZE {
+            return Err(CompilerError::InvalidDataContract(format!(
+                "\"{}\" is {} bytes, more than the {} bytes a contract can store",
+                self.name,
+                self.data.len(),
+                MAX_RUNTIME_SIZE - DATA_PREFIX.len() / 2
+            )))
+        }
+        Ok(Artifact {
+            file: Arc::new(FileSource { path: self.name.clone(), ..Default::default() }),
+            bytecode: self.creation_code(),
+            runtime: self.runtime(),
+            ..Default::default()
+        })
+    }
+
+    /// The checksummed address the data contract is deployed at with `CREATE2` by the deployer,
+    /// using the salt, a hex encoded word left padded with zeros
+    pub fn create2_address(
+        &self,
+        deployer: &str,
+        salt: &str,
+    ) -> Result<String, CompilerError<'static>> {
+        let hex_deployer = deployer.strip_prefix("0x").unwrap_or(deployer);
+        if hex_deployer.len() != 40 || !hex_deployer.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(CompilerError::InvalidDataContract(format!(
+                "Invalid deployer address \"{deployer}\""
+            )))
+        }
+        let hex_salt = salt.strip_prefix("0x").unwrap_or(salt);
+        if hex_salt.is_empty() ||
+            hex_salt.len() > 64 ||
+            !hex_salt.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            return Err(CompilerError::InvalidDataContract(format!("Invalid salt \"{salt}\"")))
+        }
+
+        let deployer = Address::from_slice(&hex::decode(hex_deployer).unwrap_or_default());
+        let salt = str_to_bytes32(hex_salt);
+        let creation_code = hex::decode(self.creation_code()).unwrap_or_default();
+        Ok(to_checksum(&get_create2_address(deployer, salt.to_vec(), creation_code), None))
+    }
+}
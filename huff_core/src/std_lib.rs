@@ -0,0 +1,157 @@
+//! ## Standard Library
+//!
+//! A versioned library of common macros embedded in the compiler, included with
+//! `#include "std/<file>.huff"`.
+//!
+//! Includes are read from the sources embedded in the compiler, or from a directory overriding
+//! them. A contract may pin the library version it was written against, which fails compilation
+//! if the library in use is a different version.
+
+use huff_utils::{
+    file_provider::FileProvider,
+    prelude::{CompilerError, FileSource, UnpackError},
+    time,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use uuid::Uuid;
+
+/// The version of the embedded standard library
+pub const STD_VERSION: &str = "0.1.0";
+
+/// The include path prefix resolved from the standard library
+pub const STD_PREFIX: &str = "std/";
+
+/// The file holding the version of a standard library directory
+pub const STD_VERSION_FILE: &str = "VERSION";
+
+/// The embedded standard library sources, by path relative to the library root
+pub const STD_SOURCES: [(&str, &str); 5] = [
+    ("erc20.huff", include_str!("../std/erc20.huff")),
+    ("math.huff", include_str!("../std/math.huff")),
+    ("ownable.huff", include_str!("../std/ownable.huff")),
+    ("reentrancy_guard.huff", include_str!("../std/reentrancy_guard.huff")),
+    ("safe_transfer_lib.huff", include_str!("../std/safe_transfer_lib.huff")),
+];
+
+/// Where standard library includes are read from
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StdLib {
+    /// A directory overriding the embedded sources, laid out like the embedded library with a
+    /// [STD_VERSION_FILE] at its root
+    pub path: Option<String>,
+    /// The library version to compile against
+    pub version: Option<String>,
+}
+
+impl StdLib {
+    /// Public associated function to instantiate a new StdLib.
+    pub fn new(path: Option<String>, version: Option<String>) -> Self {
+        Self { path, version }
+    }
+
+    /// Whether an include path refers to the standard library
+    ///
+    /// Only an unprefixed `std/` path does, as relative includes are localized to `./` paths.
+    pub fn is_std_import(import: &str) -> bool {
+        import.starts_with(STD_PREFIX)
+    }
+
+    /// The version of the library includes are read from
+    pub fn resolved_version(&self) -> Result<String, String> {
+        match &self.path {
+            Some(path) => {
+                let version_file = Path::new(path).join(STD_VERSION_FILE);
+                fs::read_to_string(&version_file).map(|v| v.trim().to_string()).map_err(|_| {
+                    format!(
+                        "Standard library override \"{path}\" is missing a {STD_VERSION_FILE} file"
+                    )
+                })
+            }
+            None => Ok(STD_VERSION.to_string()),
+        }
+    }
+
+    /// Checks that the library in use is the pinned version, if any
+    pub fn check_version(&self) -> Result<(), String> {
+        let pinned = match &self.version {
+            Some(v) => v.trim().trim_start_matches('v'),
+            None => return Ok(()),
+        };
+        let resolved = self.resolved_version()?;
+        if pinned == resolved.trim_start_matches('v') {
+            return Ok(())
+        }
+        Err(match &self.path {
+            Some(path) => format!(
+                "Standard library version {pinned} is pinned, but \"{path}\" is version {resolved}"
+            ),
+            None => format!(
+                "Standard library version {pinned} is pinned, but the compiler ships version {resolved}. Override the library with a directory holding version {pinned}"
+            ),
+        })
+    }
+
+    /// Reads a standard library source by its include path
+    pub fn read(&self, import: &str) -> Option<String> {
+        let relative = import.strip_prefix(STD_PREFIX)?;
+        match &self.path {
+            Some(path) => fs::read_to_string(Path::new(path).join(relative)).ok(),
+            None => STD_SOURCES.iter().find(|(p, _)| *p == relative).map(|(_, s)| s.to_string()),
+        }
+    }
+}
+
+/// A FileProvider reading standard library includes from a [StdLib], and any other file from
+/// the wrapped provider.
+#[derive(Debug)]
+pub struct StdFileProvider<'a> {
+    /// The provider of files outside the standard library
+    pub inner: Arc<dyn FileProvider<'a>>,
+    /// Where standard library includes are read from
+    pub std_lib: StdLib,
+}
+
+impl<'a> StdFileProvider<'a> {
+    /// Public associated function to instantiate a new StdFileProvider.
+    pub fn new(inner: Arc<dyn FileProvider<'a>>, std_lib: StdLib) -> Self {
+        Self { inner, std_lib }
+    }
+}
+
+impl<'a> FileProvider<'a> for StdFileProvider<'a> {
+    fn read_file(&self, pb: PathBuf) -> Result<Arc<FileSource>, CompilerError<'a>> {
+        let path = pb.to_string_lossy().to_string();
+        if !StdLib::is_std_import(&path) {
+            return self.inner.read_file(pb)
+        }
+        match self.std_lib.read(&path) {
+            Some(source) => Ok(Arc::new(FileSource {
+                id: Uuid::new_v4(),
+                path,
+                source: Some(source),
+                access: Some(time::get_current_time()),
+                dependencies: None,
+            })),
+            None => {
+                tracing::error!(target: "core", "STANDARD LIBRARY FILE NOT FOUND: \"{}\"!", path);
+                Err(CompilerError::FileUnpackError(UnpackError::MissingFile(path)))
+            }
+        }
+    }
+
+    fn transform_paths(&self, sources: &[String]) -> Result<Vec<PathBuf>, CompilerError<'a>> {
+        let mut paths = vec![];
+        for f in sources {
+            if StdLib::is_std_import(f) {
+                paths.push(PathBuf::from(f))
+            } else {
+                paths.extend(self.inner.transform_paths(std::slice::from_ref(f))?)
+            }
+        }
+        Ok(paths)
+    }
+}
@@ -0,0 +1,180 @@
+//! ## Verification
+//!
+//! Generates the payload block explorers verify contracts with, following the Etherscan
+//! `verifysourcecode` API: the flattened source of the contract, the compiler version, the
+//! settings it was compiled with and its ABI-encoded constructor arguments.
+//!
+//! Explorers supporting Huff recompile the source and compare it with the deployed code, so the
+//! payload is built from the artifact of the deployed contract. It may also be submitted to an
+//! explorer with an API key.
+
+use crate::VERSION;
+use huff_utils::prelude::{Artifact, CompilerSettings, FileSource};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fmt,
+    io::Write,
+    process::{Command, Output, Stdio},
+    sync::Arc,
+};
+
+/// The Etherscan API endpoint payloads are submitted to by default
+pub const DEFAULT_VERIFY_API_URL: &str = "https://api.etherscan.io/api";
+
+/// The code format of the source of a verification payload
+pub const HUFF_CODE_FORMAT: &str = "huff-single-file";
+
+/// A verification error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError(pub String);
+
+/// fmt::Display implementation for `VerifyError`
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Verification Error: {}", self.0)
+    }
+}
+
+/// The payload of a contract verification request
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VerifyPayload {
+    /// The API module, always `contract`
+    pub module: String,
+    /// The API action, always `verifysourcecode`
+    pub action: String,
+    /// The address of the deployed contract
+    #[serde(rename = "contractaddress", skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<String>,
+    /// The source of the contract, flattened with its includes
+    #[serde(rename = "sourceCode")]
+    pub source_code: String,
+    /// The format of the source, always [HUFF_CODE_FORMAT]
+    #[serde(rename = "codeformat")]
+    pub code_format: String,
+    /// The name of the contract
+    #[serde(rename = "contractname")]
+    pub contract_name: String,
+    /// The compiler version, i.e. `huffc-v0.3.1`
+    #[serde(rename = "compilerversion")]
+    pub compiler_version: String,
+    /// Whether compilation was optimized, either `0` or `1`
+    #[serde(rename = "optimizationUsed")]
+    pub optimization_used: String,
+    /// The targeted EVM version
+    #[serde(rename = "evmversion")]
+    pub evm_version: String,
+    /// The hex encoded constructor arguments appended to the creation code, without a `0x` prefix
+    ///
+    /// The misspelling is the Etherscan API's.
+    #[serde(rename = "constructorArguements")]
+    pub constructor_arguments: String,
+    /// The settings the contract was compiled with, i.e. the main and constructor macros
+    #[serde(rename = "compilerSettings")]
+    pub compiler_settings: CompilerSettings,
+}
+
+impl VerifyPayload {
+    /// The verification payload of a compiled contract
+    ///
+    /// `#include` directives of the flattened source are commented out, as the included files are
+    /// already part of it.
+    pub fn new(artifact: &Artifact, contract_address: Option<String>) -> Self {
+        let (flattened, _) = FileSource::fully_flatten(Arc::clone(&artifact.file));
+        let source_code = flattened
+            .lines()
+            .map(|line| {
+                let directive = line.trim_start();
+                match directive.starts_with("#include") {
+                    true => format!("{}// {directive}", &line[..line.len() - directive.len()]),
+                    false => line.to_string(),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        // The encoded arguments are appended to the creation code after the runtime code
+        let constructor_arguments = artifact
+            .bytecode
+            .rfind(&artifact.runtime)
+            .map(|i| artifact.bytecode[i + artifact.runtime.len()..].to_string())
+            .unwrap_or_default();
+
+        let compiler_settings =
+            artifact.metadata.as_ref().map(|m| m.settings.clone()).unwrap_or_default();
+        Self {
+            module: "contract".to_string(),
+            action: "verifysourcecode".to_string(),
+            contract_address,
+            source_code,
+            code_format: HUFF_CODE_FORMAT.to_string(),
+            contract_name: artifact.default_name(),
+            compiler_version: format!("huffc-v{VERSION}"),
+            optimization_used: if compiler_settings.optimize { "1" } else { "0" }.to_string(),
+            evm_version: compiler_settings.evm_version.clone(),
+            constructor_arguments,
+            compiler_settings,
+        }
+    }
+
+    /// The form fields of the payload, with the values that aren't strings serialized as json
+    pub fn form_fields(&self) -> Vec<(String, String)> {
+        let value = serde_json::to_value(self).unwrap_or_default();
+        value
+            .as_object()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .map(|(k, v)| match v {
+                        Value::String(s) => (k.clone(), s.clone()),
+                        v => (k.clone(), v.to_string()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Submits the payload to an explorer's API, returning the GUID of the verification request
+    ///
+    /// The request is sent with `curl`, which must be installed. The API key is passed to it
+    /// through stdin, keeping it out of the process list.
+    pub fn submit(&self, api_url: &str, api_key: &str) -> Result<String, VerifyError> {
+        if self.contract_address.is_none() {
+            return Err(VerifyError("The contract address is required to submit".to_string()))
+        }
+
+        let mut curl = Command::new("curl");
+        curl.args(["--silent", "--show-error", "--request", "POST", api_url]);
+        curl.args(["--data-urlencode", "apikey@-"]);
+        for (key, value) in self.form_fields() {
+            curl.arg("--data-urlencode").arg(format!("{key}={value}"));
+        }
+        let run = |curl: &mut Command| -> std::io::Result<Output> {
+            let mut child =
+                curl.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+            child.stdin.take().map(|mut stdin| stdin.write_all(api_key.as_bytes())).transpose()?;
+            child.wait_with_output()
+        };
+        let output = run(&mut curl)
+            .map_err(|e| VerifyError(format!("Failed to run curl, is it installed? {e}")))?;
+        if !output.status.success() {
+            return Err(VerifyError(format!(
+                "Failed to submit to \"{api_url}\": {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+
+        // Responses are `{ "status": "1", "message": "OK", "result": "<guid>" }`
+        let response: Value = serde_json::from_slice(&output.stdout).map_err(|_| {
+            VerifyError(format!(
+                "Unexpected response from \"{api_url}\": {}",
+                String::from_utf8_lossy(&output.stdout).trim()
+            ))
+        })?;
+        let result = response["result"].as_str().unwrap_or_default().to_string();
+        match response["status"].as_str() {
+            Some("1") => Ok(result),
+            _ => Err(VerifyError(format!("Verification request rejected: {result}"))),
+        }
+    }
+}
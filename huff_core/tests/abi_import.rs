@@ -0,0 +1,115 @@
+mod common;
+
+use common::{compile, first_error};
+use huff_utils::prelude::*;
+
+const IERC20: &str = r#"[
+    {
+        "type": "function",
+        "name": "transfer",
+        "inputs": [{ "name": "to", "type": "address" }, { "name": "amount", "type": "uint256" }],
+        "outputs": [{ "name": "", "type": "bool" }],
+        "stateMutability": "nonpayable"
+    },
+    {
+        "type": "event",
+        "name": "Transfer",
+        "inputs": [
+            { "name": "from", "type": "address", "indexed": true },
+            { "name": "to", "type": "address", "indexed": true },
+            { "name": "amount", "type": "uint256", "indexed": false }
+        ],
+        "anonymous": false
+    },
+    {
+        "type": "error",
+        "name": "InsufficientBalance",
+        "inputs": [{ "name": "available", "type": "uint256" }]
+    }
+]"#;
+
+#[test]
+fn test_abi_import_declares_entries() {
+    let qualified = r#"
+        #include abi "IERC20.json"
+
+        #define macro MAIN() = takes (0) returns (0) {
+            __FUNC_SIG(IERC20.transfer) __EVENT_HASH(IERC20.Transfer) __ERROR(IERC20.InsufficientBalance)
+        }
+    "#;
+    let literal = r#"
+        #define error InsufficientBalance(uint256)
+
+        #define macro MAIN() = takes (0) returns (0) {
+            __FUNC_SIG("transfer(address,uint256)") __EVENT_HASH("Transfer(address,address,uint256)")
+            __ERROR(InsufficientBalance)
+        }
+    "#;
+    let imported = compile(&[("main.huff", qualified), ("IERC20.json", IERC20)]).unwrap();
+    let declared = compile(&[("main.huff", literal)]).unwrap();
+    assert_eq!(imported[0].runtime, declared[0].runtime);
+    assert!(imported[0].runtime.starts_with("63a9059cbb7fddf252ad"), "{}", imported[0].runtime);
+
+    // The imported entries declare another contract's interface, not the contract's own ABI
+    let abi = imported[0].abi.as_ref().unwrap();
+    assert!(abi.functions.is_empty() && abi.events.is_empty() && abi.errors.is_empty());
+}
+
+#[test]
+fn test_abi_import_of_artifact() {
+    // Foundry and Hardhat artifacts hold the ABI as `abi`, and paths are relative to the includer
+    let source = r#"
+        #include "lib/token.huff"
+
+        #define macro MAIN() = takes (0) returns (0) {
+            TRANSFER_SIG()
+        }
+    "#;
+    let lib = r#"
+        #include abi "../out/IERC20.json"
+
+        #define macro TRANSFER_SIG() = takes (0) returns (1) {
+            __FUNC_SIG(IERC20.transfer)
+        }
+    "#;
+    let artifact = format!(r#"{{ "abi": {IERC20}, "bytecode": "0x" }}"#);
+    let artifacts =
+        compile(&[("main.huff", source), ("lib/token.huff", lib), ("out/IERC20.json", &artifact)])
+            .unwrap();
+    assert_eq!(artifacts[0].runtime, "63a9059cbb");
+}
+
+#[test]
+fn test_abi_import_errors() {
+    let source = |body: &str| {
+        format!(
+            "#include abi \"IERC20.json\"\n#define macro MAIN() = takes (0) returns (0) {{ {body} }}"
+        )
+    };
+
+    // An entry the ABI doesn't declare
+    let e =
+        compile(&[("main.huff", &source("__FUNC_SIG(IERC20.approve)")), ("IERC20.json", IERC20)])
+            .unwrap_err();
+    assert!(matches!(
+        first_error(&e),
+        CompilerError::CodegenError(CodegenError {
+            kind: CodegenErrorKind::UndeclaredFunction(name, _),
+            ..
+        }) if name == "IERC20.approve"
+    ));
+
+    // A missing or invalid ABI file
+    for (files, reason) in [
+        (vec![], "\"./IERC20.json\" can't be read"),
+        (vec![("IERC20.json", "{}")], "missing the \"abi\" field of the artifact"),
+    ] {
+        let main = source("stop");
+        let files = [vec![("main.huff", main.as_str())], files].concat();
+        let e = compile(&files).unwrap_err();
+        match first_error(&e) {
+            CompilerError::InvalidAbiImport(msg, _) => assert_eq!(msg, reason),
+            e => panic!("unexpected error: {e:?}"),
+        }
+    }
+}
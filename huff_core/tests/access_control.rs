@@ -0,0 +1,86 @@
+mod common;
+
+use common::main_bytecode;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_only_caller_constant_address() {
+    let source = r#"
+        #define constant ADMIN = 0xbebebebebebebebebebebebebebebebebebebebe
+
+        #[only_caller(ADMIN)]
+        #define macro WITHDRAW() = takes (0) returns (0) {
+            selfbalance caller gas call
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            WITHDRAW() stop
+        }
+    "#;
+    // 73 bebe.. 33 14 61001f 57 6000 80 fd 5b: revert unless the caller is the admin
+    assert_eq!(
+        main_bytecode(source).unwrap(),
+        "73bebebebebebebebebebebebebebebebebebebebe331461001f57600080fd5b47335af100"
+    );
+}
+
+#[test]
+fn test_only_caller_repeated_macro() {
+    let source = r#"
+        #define constant ADMIN = 0xbebebebebebebebebebebebebebebebebebebebe
+
+        #[only_caller(ADMIN)]
+        #define macro WITHDRAW() = takes (0) returns (0) {
+            selfbalance
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            WITHDRAW() WITHDRAW() stop
+        }
+    "#;
+    // Each check jumps over its own revert, to 0x1f and 0x40
+    assert_eq!(
+        main_bytecode(source).unwrap(),
+        "73bebebebebebebebebebebebebebebebebebebebe331461001f57600080fd5b47\
+         73bebebebebebebebebebebebebebebebebebebebe331461004057600080fd5b4700"
+    );
+}
+
+#[test]
+fn test_only_caller_storage_pointer_with_error() {
+    let source = r#"
+        #define error Unauthorized()
+        #define constant OWNER = FREE_STORAGE_POINTER()
+
+        #[only_caller(OWNER, Unauthorized)]
+        #define macro SET_OWNER() = takes (0) returns (0) {
+            0x04 calldataload [OWNER] sstore
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            SET_OWNER() stop
+        }
+    "#;
+    // 6000 54 33 14 610016 57: load the owner from its slot
+    // 63 82b42900 6000 52 6004 601c fd 5b: revert with `Unauthorized()`
+    assert_eq!(
+        main_bytecode(source).unwrap(),
+        "6000543314610016576382b429006000526004601cfd5b60043560005500"
+    );
+}
+
+#[test]
+fn test_only_caller_missing_error() {
+    let source = r#"
+        #define constant OWNER = FREE_STORAGE_POINTER()
+
+        #[only_caller(OWNER, Unauthorized)]
+        #define macro MAIN() = takes (0) returns (0) {
+            stop
+        }
+    "#;
+    assert_eq!(
+        main_bytecode(source).unwrap_err().kind,
+        CodegenErrorKind::MissingErrorDefinition("Unauthorized".to_string())
+    );
+}
@@ -27,7 +27,11 @@ fn test_alternative_constructor_macro_provided() {
     let alternative_constructor_label = Some(String::from("ALT_CONSTRUCTOR"));
 
     // Create constructor bytecode
-    match Codegen::generate_constructor_bytecode(&contract, alternative_constructor_label) {
+    match Codegen::generate_constructor_bytecode(
+        &EVMVersion::default(),
+        &contract,
+        alternative_constructor_label,
+    ) {
         Ok((mb, _)) => assert_eq!(mb, "6004356000602435".to_string()),
         Err(_) => panic!("moose"),
     }
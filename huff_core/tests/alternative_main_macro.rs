@@ -1,7 +1,9 @@
 use huff_codegen::*;
+use huff_core::Compiler;
 use huff_lexer::*;
 use huff_parser::*;
 use huff_utils::prelude::*;
+use std::{collections::HashMap, sync::Arc};
 
 #[test]
 fn test_alternative_main_macro_provided() {
@@ -27,8 +29,76 @@ fn test_alternative_main_macro_provided() {
     let alternative_main = Some(String::from("MINT"));
 
     // Createconstructor bytecode
-    match Codegen::generate_main_bytecode(&contract, alternative_main) {
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, alternative_main) {
         Ok(mb) => assert_eq!(mb, "6004356000602435".to_string()),
         Err(_) => panic!("moose"),
     }
 }
+
+const ENTRY_POINTS: &str = r#"
+    #define function transfer(address,uint256) nonpayable returns ()
+
+    #define constant BUFFER = FREE_MEMORY_POINTER()
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x01
+    }
+
+    #define macro RUNTIME_V2() = takes(0) returns(0) {
+        0x00 calldataload 0xE0 shr
+        __FUNC_SIG(transfer) eq transfer jumpi
+
+        transfer:
+            [BUFFER] mload
+    }
+
+    #define macro DEPLOY() = takes(0) returns(0) {
+        0x02
+    }
+"#;
+
+fn compiler(main: Option<&str>, constructor: Option<&str>) -> Compiler<'static> {
+    Compiler::new_in_memory(
+        Arc::new(vec!["main.huff".to_string()]),
+        HashMap::from([("main.huff".to_string(), ENTRY_POINTS.to_string())]),
+        main.map(String::from),
+        constructor.map(String::from),
+        None,
+        None,
+        false,
+    )
+}
+
+#[test]
+fn test_alternative_entry_points_compile() {
+    let artifact = compiler(Some("RUNTIME_V2"), Some("DEPLOY")).execute().unwrap().remove(0);
+
+    // 6000 35 60e0 1c 63a9059cbb 14 610010 57 5b 6000 51
+    assert_eq!(artifact.runtime, "60003560e01c63a9059cbb14610010575b600051");
+    assert!(artifact.bytecode.starts_with("6002"));
+
+    let settings = artifact.metadata.as_ref().unwrap().settings.clone();
+    assert_eq!(settings.main_macro, "RUNTIME_V2");
+    assert_eq!(settings.constructor_macro, "DEPLOY");
+}
+
+#[test]
+fn test_alternative_main_derives_memory_pointers() {
+    let full_source = FullFileSource { source: ENTRY_POINTS, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut contract = Parser::new(tokens, Some("".to_string())).parse().unwrap();
+    contract.derive_storage_pointers_from("RUNTIME_V2", "DEPLOY");
+
+    assert_eq!(contract.memory_layout.len(), 1);
+    assert_eq!(contract.memory_layout[0].name, "BUFFER");
+}
+
+#[test]
+fn test_alternative_main_is_analyzed() {
+    assert!(compiler(None, None).analyze().unwrap().is_empty());
+
+    let warnings = compiler(Some("RUNTIME_V2"), None).analyze().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::MissingFallthrough("RUNTIME_V2".to_string()));
+}
@@ -0,0 +1,87 @@
+use huff_codegen::Codegen;
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::*;
+
+fn compile_main(source: &str) -> Result<String, CodegenError> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut contract = Parser::new(tokens, None).parse().unwrap();
+    contract.derive_storage_pointers();
+    Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None)
+}
+
+#[test]
+fn test_asm_block() {
+    let source = r#"
+        #define macro ROUTINE() = takes(0) returns(0) {
+            asm {
+                0x60 0x00 0x35      // push1 0x00 calldataload
+                @offset(0x06)
+                0x5b
+            }
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x01 pop
+            ROUTINE()
+            asm { 0x0000 @offset(9) }
+            stop
+        }
+    "#;
+
+    // 6001 50 | 600035 | 5b | 0000 | 00
+    assert_eq!(compile_main(source).unwrap(), "6001506000355b000000");
+}
+
+#[test]
+fn test_asm_offset_mismatch() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            0x01
+            asm { 0x50 @offset(0x02) 0x00 }
+        }
+    "#;
+
+    let err = compile_main(source).unwrap_err();
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::AssertionFailed("expected offset 0x2, found 0x3".to_string())
+    );
+}
+
+#[test]
+fn test_asm_offset_mismatch_in_repeated_macro() {
+    let source = r#"
+        #define macro ALIGNED() = takes(0) returns(0) {
+            asm { @offset(0x00) 0x5b }
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            ALIGNED()
+            ALIGNED()
+        }
+    "#;
+
+    // The assertion holds in the first expansion only
+    let err = compile_main(source).unwrap_err();
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::AssertionFailed("expected offset 0x0, found 0x1".to_string())
+    );
+}
+
+#[test]
+fn test_asm_invalid_token() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            asm { 0x60 [VALUE] }
+        }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let err = Parser::new(tokens, None).parse().unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::InvalidTokenInMacroBody(TokenKind::OpenBracket));
+}
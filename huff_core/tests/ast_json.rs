@@ -0,0 +1,56 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+use std::sync::Arc;
+
+#[test]
+fn test_ast_json_roundtrip() {
+    let source = r#"
+    #define function transfer(address,uint256) nonpayable returns ()
+    #define constant OWNER = FREE_STORAGE_POINTER()
+    #define constant LIMIT: uint8 = 0xff
+
+    #define macro INNER(op) = takes(2) returns(1) {
+        <op>
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        __FUNC_SIG(transfer) [OWNER] sload
+        INNER(add)
+        end:
+            0x00 0x00 return
+    }
+    "#;
+
+    let file = Arc::new(FileSource {
+        path: "contracts/Main.huff".to_string(),
+        source: Some(source.to_string()),
+        ..Default::default()
+    });
+    let flattened = FileSource::fully_flatten(Arc::clone(&file));
+    let full_source =
+        FullFileSource { source: &flattened.0, file: Some(Arc::clone(&file)), spans: flattened.1 };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some(file.path.clone()));
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    let json = serde_json::to_string(&contract).unwrap();
+
+    // Spans reference their file by path rather than embedding its source
+    assert!(json.contains("\"file\":\"contracts/Main.huff\""));
+    assert!(!json.contains("#define"));
+
+    // Deserializing and re-serializing yields the same AST
+    let deserialized: Contract = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.macros.len(), 2);
+    assert_eq!(deserialized.macros[1].name, "MAIN");
+    assert_eq!(serde_json::to_string(&deserialized).unwrap(), json);
+}
+
+#[test]
+fn test_opcodes_serialize_lowercase() {
+    assert_eq!(serde_json::to_string(&Opcode::TLoad).unwrap(), "\"tload\"");
+    assert_eq!(serde_json::from_str::<Opcode>("\"calldataload\"").unwrap(), Opcode::Calldataload);
+}
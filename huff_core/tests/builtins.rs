@@ -42,7 +42,7 @@ fn test_codesize_builtin() {
 
     // Have the Codegen create the constructor bytecode
     let (cbytes, custom_bootstrap) =
-        Codegen::generate_constructor_bytecode(&contract, None).unwrap();
+        Codegen::generate_constructor_bytecode(&EVMVersion::default(), &contract, None).unwrap();
     assert_eq!(cbytes, String::from("6004"));
     assert!(!custom_bootstrap);
 }
@@ -78,8 +78,9 @@ fn test_dyn_constructor_arg_builtin() {
 
     // Have the Codegen create the constructor bytecode
     let (constructor_code, has_custom_bootstrap) =
-        Codegen::generate_constructor_bytecode(&contract, None).unwrap();
-    let main_code = Codegen::generate_main_bytecode(&contract, None).unwrap();
+        Codegen::generate_constructor_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    let main_code =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
 
     let args = Codegen::encode_constructor_args(vec![String::from("testing")]);
     let final_bytecode = cg.churn(
@@ -166,7 +167,7 @@ fn test_tablesize_builtin() {
     assert!(cg.artifact.is_none());
 
     // Have the Codegen create the constructor bytecode
-    let mbytes = Codegen::generate_main_bytecode(&contract, None).unwrap();
+    let mbytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
     assert_eq!(mbytes, String::from("6008608061002c60003960205b60006000f35b60006000f35b60006000f35b60006000f3000c00120018001e000000000000000000000000000000000000000000000000000000000000000c00000000000000000000000000000000000000000000000000000000000000120000000000000000000000000000000000000000000000000000000000000018000000000000000000000000000000000000000000000000000000000000001eDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEF"));
 }
 
@@ -231,7 +232,7 @@ fn test_tablestart_builtin() {
 
     // Have the Codegen create the constructor bytecode
     let (cbytes, custom_bootstrap) =
-        Codegen::generate_constructor_bytecode(&contract, None).unwrap();
+        Codegen::generate_constructor_bytecode(&EVMVersion::default(), &contract, None).unwrap();
     assert_eq!(cbytes, String::from("61001e6100265b60006000f35b60006000f35b60006000f35b60006000f30006000c001200180000000000000000000000000000000000000000000000000000000000000006000000000000000000000000000000000000000000000000000000000000000c00000000000000000000000000000000000000000000000000000000000000120000000000000000000000000000000000000000000000000000000000000018"));
     assert!(custom_bootstrap);
 }
@@ -295,7 +296,7 @@ fn test_jump_table_exhaustive_usage() {
     assert!(cg.artifact.is_none());
 
     // Have the Codegen create the constructor bytecode
-    let mbytes = Codegen::generate_main_bytecode(&contract, None).unwrap();
+    let mbytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
     assert_eq!(mbytes, String::from("608061004060003960003560e01c8063a9059cbb14610019575b60208703516202ffe016806020015b60206020015b60206020015b60206020015b60206020010000000000000000000000000000000000000000000000000000000000000028000000000000000000000000000000000000000000000000000000000000002e0000000000000000000000000000000000000000000000000000000000000034000000000000000000000000000000000000000000000000000000000000003a"));
 }
 
@@ -355,7 +356,7 @@ fn test_jump_table_packed_exhaustive_usage() {
     assert!(cg.artifact.is_none());
 
     // Have the Codegen create the main macro bytecode
-    let mbytes = Codegen::generate_main_bytecode(&contract, None).unwrap();
+    let mbytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
     assert_eq!(mbytes, String::from("600861004060003960003560e01c8063a9059cbb14610019575b60208703516202ffe016806020015b60206020015b60206020015b60206020015b60206020010028002e0034003a"));
 }
 
@@ -422,7 +423,7 @@ fn test_label_clashing() {
     assert!(cg.artifact.is_none());
 
     // Have the Codegen create the main macro bytecode
-    let mbytes = Codegen::generate_main_bytecode(&contract, None).unwrap();
+    let mbytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
     assert_eq!(mbytes, String::from("6008610048600039608061005060003960003560e01c8063a9059cbb14610021575b60208703516202ffe016806020015b60206020015b60206020015b60206020015b602060200100300036003c004200000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000000000000000000000000000000000000036000000000000000000000000000000000000000000000000000000000000003c0000000000000000000000000000000000000000000000000000000000000042"));
 }
 
@@ -466,7 +467,7 @@ fn test_func_sig_builtin() {
     assert!(cg.artifact.is_none());
 
     // Have the Codegen create the constructor bytecode
-    let cbytes = Codegen::generate_main_bytecode(&contract, None).unwrap();
+    let cbytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
     // `transfer(address,uint256) signature = 0xa9059cbb
     assert_eq!(&cbytes[16..24], "a9059cbb");
     assert_eq!(&cbytes[38..46], "a9059cbb");
@@ -479,6 +480,37 @@ fn test_func_sig_builtin() {
     );
 }
 
+#[test]
+fn test_func_sig_builtin_undeclared() {
+    let source = r#"
+        #define function transfer(address,uint256) nonpayable returns ()
+        #define function approve(address,uint256) nonpayable returns ()
+
+        #define macro MAIN() = takes (0) returns (0) {
+            __FUNC_SIG(transferFrom)
+        }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+
+    let err = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap_err();
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::UndeclaredFunction(
+            "transferFrom".to_string(),
+            vec!["transfer".to_string(), "approve".to_string()]
+        )
+    );
+
+    // The error lists the declared functions and hints at the string form
+    let message = CompilerError::CodegenError(err).to_string();
+    assert!(message.contains("Declared functions: transfer, approve."), "{message}");
+    assert!(message.contains("__FUNC_SIG(\"transferFrom(uint256)\")"), "{message}");
+}
+
 #[test]
 fn test_event_hash_builtin() {
     let source: &str = r#"
@@ -511,7 +543,7 @@ fn test_event_hash_builtin() {
     assert!(cg.artifact.is_none());
 
     // Have the Codegen create the constructor bytecode
-    let cbytes = Codegen::generate_main_bytecode(&contract, None).unwrap();
+    let cbytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
     // `transfer(address,address,uint256) signature =
     // 0xbeabacc8ffedac16e9a60acdb2ca743d80c2ebb44977a93fa8e483c74d2b35a8
     assert_eq!(&cbytes[2..66], "beabacc8ffedac16e9a60acdb2ca743d80c2ebb44977a93fa8e483c74d2b35a8");
@@ -586,7 +618,7 @@ fn test_error_selector_builtin() {
     assert!(cg.artifact.is_none());
 
     // Have Codegen create the runtime bytecode
-    let r_bytes = Codegen::generate_main_bytecode(&contract, None).unwrap();
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
     assert_eq!(&r_bytes[2..66], "be20788c00000000000000000000000000000000000000000000000000000000");
     assert_eq!(
         &r_bytes[98..162],
@@ -629,7 +661,7 @@ fn test_rightpad_builtin() {
     assert!(cg.artifact.is_none());
 
     // Have Codegen create the runtime bytecode
-    let r_bytes = Codegen::generate_main_bytecode(&contract, None).unwrap();
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
     assert_eq!(&r_bytes[2..66], "a57b000000000000000000000000000000000000000000000000000000000000");
     assert_eq!(
         &r_bytes[68..132],
@@ -647,3 +679,869 @@ fn test_rightpad_builtin() {
         )
     );
 }
+
+#[test]
+fn test_calldata_load_arg_builtin() {
+    let source: &str = r#"
+        #define macro MAIN() = takes (0) returns (0) {
+            __CALLDATA_LOAD_ARG(0, uint256)
+            __CALLDATA_LOAD_ARG(0x01, address)
+            __CALLDATA_LOAD_ARG(2, uint8)
+            __CALLDATA_LOAD_ARG(3, int16)
+            __CALLDATA_LOAD_ARG(4, bytes4)
+            __CALLDATA_LOAD_ARG(5, bool)
+            __CALLDATA_LOAD_ARG(6, bytes)
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    // Have Codegen create the runtime bytecode
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(
+        r_bytes,
+        [
+            // uint256: 0x04 calldataload
+            "600435",
+            // address: 0x24 calldataload 0xff..ff and
+            "602435",
+            "73ffffffffffffffffffffffffffffffffffffffff16",
+            // uint8: 0x44 calldataload 0xff and
+            "60443560ff16",
+            // int16: 0x64 calldataload 0x01 signextend
+            "60643560010b",
+            // bytes4: 0x84 calldataload 0xff..ff not and
+            "608435",
+            "7bffffffffffffffffffffffffffffffffffffffffffffffffffffffff1916",
+            // bool: 0xa4 calldataload iszero iszero
+            "60a4351515",
+            // bytes: 0xc4 calldataload 0x04 add
+            "60c435600401",
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn test_calldata_load_arg_builtin_invalid_type() {
+    for source in [
+        "#define macro MAIN() = takes (0) returns (0) { __CALLDATA_LOAD_ARG(0, uint7) }",
+        "#define macro MAIN() = takes (0) returns (0) { __CALLDATA_LOAD_ARG(0, bytes33) }",
+        "#define macro MAIN() = takes (0) returns (0) { __CALLDATA_LOAD_ARG(0, foo) }",
+        "#define macro MAIN() = takes (0) returns (0) { __CALLDATA_LOAD_ARG(0) }",
+    ] {
+        let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+        let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        let contract = parser.parse().unwrap();
+
+        let err =
+            Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap_err();
+        assert!(matches!(err.kind, CodegenErrorKind::InvalidArguments(_)), "{source}");
+    }
+}
+
+#[test]
+fn test_storage_slot_builtins() {
+    let source: &str = r#"
+        #define macro MAIN() = takes (0) returns (0) {
+            __EIP1967_IMPL_SLOT()
+            __EIP1967_ADMIN_SLOT()
+            __EIP1967_BEACON_SLOT()
+            __ERC7201("example.main")
+        }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(
+        r_bytes,
+        [
+            "7f360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc",
+            "7fb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103",
+            "7fa3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50",
+            "7f183a6125c38840424c4a85fa12bab2ab606c4b6d0e7cc73c0c06ba5300eab500",
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn test_storage_slot_builtins_as_constants() {
+    let source: &str = r#"
+        #define constant IMPLEMENTATION = STORAGE_SLOT(__EIP1967_IMPL_SLOT())
+        #define constant MAIN_STORAGE = STORAGE_SLOT(__ERC7201("example.main"))
+        #define constant OWNER = FREE_STORAGE_POINTER()
+
+        #define macro MAIN() = takes (0) returns (0) {
+            [IMPLEMENTATION] sload [MAIN_STORAGE] sload [OWNER] sload
+        }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+    assert_eq!(contract.storage_layout.len(), 3);
+    assert!(contract.check_storage_collisions().is_ok());
+
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(
+        r_bytes,
+        [
+            "7f360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc54",
+            "7f183a6125c38840424c4a85fa12bab2ab606c4b6d0e7cc73c0c06ba5300eab50054",
+            "600054",
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn test_storage_slot_builtins_invalid_arguments() {
+    for source in [
+        r#"#define macro MAIN() = takes (0) returns (0) { __ERC7201() }"#,
+        r#"#define macro MAIN() = takes (0) returns (0) { __ERC7201("a", "b") }"#,
+        r#"#define macro MAIN() = takes (0) returns (0) { __EIP1967_IMPL_SLOT("a") }"#,
+    ] {
+        let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+        let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        let contract = parser.parse().unwrap();
+
+        let err =
+            Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap_err();
+        assert!(matches!(err.kind, CodegenErrorKind::InvalidArguments(_)), "{source}");
+    }
+
+    for source in [
+        "#define constant SLOT = STORAGE_SLOT(__ERC7201())",
+        "#define constant SLOT = STORAGE_SLOT(__FUNC_SIG(\"transfer(address,uint256)\"))",
+    ] {
+        let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+        let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        let err = parser.parse().unwrap_err();
+        assert!(
+            matches!(
+                err.kind,
+                ParserErrorKind::InvalidArgs(_) | ParserErrorKind::InvalidConstantValue(_)
+            ),
+            "{source}: {err:?}"
+        );
+    }
+}
+
+#[test]
+fn test_assert_builtin() {
+    let compile = |body: &str| {
+        let source = format!(
+            r#"
+            #define constant SLOT_A = FREE_STORAGE_POINTER()
+            #define constant SLOT_B = FREE_STORAGE_POINTER()
+            #define constant SIZE = 0x20
+
+            #define macro MAIN() = takes (0) returns (0) {{
+                #define constant LOCAL = 0x40
+                {body}
+                stop
+            }}
+        "#
+        );
+        let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut contract = Parser::new(tokens, None).parse().unwrap();
+        contract.derive_storage_pointers();
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None)
+    };
+
+    // Assertions that hold generate no code
+    let bytecode = compile(
+        r#"
+        __ASSERT([SLOT_A] != [SLOT_B], "slots collide")
+        __ASSERT([SIZE] * 2 == [LOCAL])
+        __ASSERT([SIZE] < [LOCAL])
+        __ASSERT(0x01)
+        "#,
+    );
+    assert_eq!(bytecode.unwrap(), "00");
+
+    let err = compile(r#"__ASSERT([SLOT_A] == [SLOT_B], "slots differ")"#).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::AssertionFailed("slots differ".to_string()));
+
+    // The expression is the message by default
+    let err = compile("__ASSERT([SIZE] >= [LOCAL])").unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::AssertionFailed("[SIZE] >= [LOCAL]".to_string()));
+
+    for body in ["__ASSERT([SIZE] - [LOCAL])", "__ASSERT([SIZE] / 0)", "__ASSERT([MISSING])"] {
+        let err = compile(body).unwrap_err();
+        assert!(matches!(err.kind, CodegenErrorKind::InvalidConstantExpression(_)), "{body}");
+    }
+}
+
+#[test]
+fn test_gas_limit_builtin() {
+    let compile = |limit: &str| {
+        let source = format!(
+            r#"
+            #define macro STORE() = takes (0) returns (0) {{
+                0x01 0x00 sstore
+            }}
+
+            #define macro MAIN() = takes (0) returns (0) {{
+                __GAS_LIMIT(STORE, {limit})
+                STORE()
+            }}
+        "#
+        );
+        let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let contract = Parser::new(tokens, None).parse().unwrap();
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None)
+    };
+
+    // Two pushes and a cold sstore setting a zero slot, the budget generating no code
+    assert_eq!(compile("22106").unwrap(), "6001600055");
+    assert_eq!(compile("0x5666").unwrap(), "6001600055");
+
+    let err = compile("22105").unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::GasLimitExceeded("STORE".to_string(), 22106, 22105));
+
+    let err = compile("MISSING").unwrap_err();
+    assert!(matches!(err.kind, CodegenErrorKind::InvalidArguments(_)));
+}
+
+#[test]
+fn test_str_lit_builtin() {
+    let source: &str = r#"
+        #define macro MAIN() = takes (0) returns (0) {
+            __STR_LIT("ok")
+            __STR_LIT("TKN", left)
+            __STR_LIT("TKN", right)
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // Left aligned strings are pushed as `bytes32`, right aligned strings as their bytes
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(
+        r_bytes,
+        String::from(
+            "7f6f6b0000000000000000000000000000000000000000000000000000000000007f544b4e000000000000000000000000000000000000000000000000000000000062544b4e"
+        )
+    );
+}
+
+#[test]
+fn test_str_lit_builtin_errors() {
+    for (source, message) in [
+        (format!("__STR_LIT(\"{}\")", "a".repeat(33)), "must be at most 32 ASCII characters"),
+        ("__STR_LIT(\"ok\", center)".to_string(), "Invalid alignment passed to __STR_LIT"),
+        ("__STR_LIT()".to_string(), "Incorrect number of arguments passed to __STR_LIT"),
+        ("__STR_LIT(0x01)".to_string(), "__STR_LIT expects a string literal, got \"01\""),
+        ("__STR_LIT(ok)".to_string(), "__STR_LIT expects a string literal, got \"ok\""),
+    ] {
+        let source = format!("#define macro MAIN() = takes (0) returns (0) {{ {source} }}");
+        let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let lexer = Lexer::new(flattened_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        let contract = parser.parse().unwrap();
+
+        match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+            Err(CodegenError { kind: CodegenErrorKind::InvalidArguments(msg), .. }) => {
+                assert!(msg.contains(message), "{msg}")
+            }
+            res => panic!("expected an error, got {res:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_pc_and_offset_builtins() {
+    let source: &str = r#"
+        #define macro COPY_DATA() = takes (0) returns (0) {
+            __PC() pop
+            // Copy the code between the labels to memory
+            __OFFSET(data_start) __OFFSET(data_end) sub
+            __OFFSET(data_start)
+            0x00 codecopy
+            done jump
+            data_start:
+                __VERBATIM(0xdeadbeef)
+            data_end:
+            done:
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x00 pop
+            COPY_DATA()
+            __PC()
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // 6000 50 | 610003 50 | 610018 61001d 03 610018 6000 39 61001e 56
+    // 5b deadbeef | 5b | 5b | 61001f
+    // Offsets are pushed as 2 bytes wherever they're expanded, labels at their JUMPDEST
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(
+        r_bytes,
+        String::from("6000506100035061001861001d0361001860003961001e565bdeadbeef5b5b61001f")
+    );
+}
+
+#[test]
+fn test_pc_builtin_in_repeated_macro() {
+    let source: &str = r#"
+        #define macro P() = takes (0) returns (1) {
+            __PC()
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            P() P() 0x01 P()
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Parse the AST
+    let contract = parser.parse().unwrap();
+
+    // Every expansion pushes its own offset: 610000 | 610003 | 6001 | 610008
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(r_bytes, String::from("6100006100036001610008"));
+}
+
+#[test]
+fn test_pc_and_offset_builtin_errors() {
+    for (source, message) in [
+        ("__PC(label)", "Incorrect number of arguments passed to __PC"),
+        ("__OFFSET()", "Incorrect number of arguments passed to __OFFSET"),
+    ] {
+        let source = format!("#define macro MAIN() = takes (0) returns (0) {{ {source} label: }}");
+        let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let lexer = Lexer::new(flattened_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        let contract = parser.parse().unwrap();
+
+        match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+            Err(CodegenError { kind: CodegenErrorKind::InvalidArguments(msg), .. }) => {
+                assert!(msg.contains(message), "{msg}")
+            }
+            res => panic!("expected an error, got {res:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_code_slice_builtin() {
+    let source: &str = r#"
+        #define macro MAIN() = takes (0) returns (0) {
+            __CODE_SLICE(blob_start, blob_end) 0x00 codecopy
+            __CODE_SLICE(blob_start, blob_end) pop 0x00 return
+            blob_start:
+                __VERBATIM(0xdeadbeef)
+            blob_end:
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // 610005 610013 6000 39 | 610005 610013 50 6000 f3 | 5b deadbeef | 5b
+    // The blob is the 5 bytes from its JUMPDEST up to the one of the end label
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(r_bytes, String::from("610005610013600039610005610013506000f35bdeadbeef5b"));
+}
+
+#[test]
+fn test_code_slice_builtin_errors() {
+    for (source, message) in [
+        ("__CODE_SLICE(a)", "Incorrect number of arguments passed to __CODE_SLICE"),
+        ("__CODE_SLICE(b, a)", "precedes the start of the slice"),
+    ] {
+        let source = format!("#define macro MAIN() = takes (0) returns (0) {{ {source} a: b: }}");
+        let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let lexer = Lexer::new(flattened_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        let contract = parser.parse().unwrap();
+
+        match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+            Err(CodegenError {
+                kind:
+                    CodegenErrorKind::InvalidArguments(msg) |
+                    CodegenErrorKind::InvalidRelocation(_, msg),
+                ..
+            }) => assert!(msg.contains(message), "{msg}"),
+            res => panic!("expected an error, got {res:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_data_builtins() {
+    let source: &str = r#"
+        #define data BLOB = 0xdeadbeef
+        #define data UNUSED = 0xff
+
+        #define macro MAIN() = takes (0) returns (0) {
+            __DATA_SIZE(BLOB) __DATA_OFFSET(BLOB) 0x00 codecopy
+            __DATA_SIZE(BLOB) 0x00 return
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // 6004 61000d 6000 39 | 6004 6000 f3 | deadbeef
+    // Only the referenced section is appended, after the 13 bytes of code
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(r_bytes, String::from("600461000d60003960046000f3deadbeef"));
+}
+
+#[test]
+fn test_data_builtin_errors() {
+    for (source, kind) in [
+        (
+            "__DATA_OFFSET()",
+            CodegenErrorKind::InvalidArguments(
+                "Incorrect number of arguments passed to __DATA_OFFSET, should be 1: 0".to_string(),
+            ),
+        ),
+        ("__DATA_SIZE(MISSING)", CodegenErrorKind::MissingDataDefinition("MISSING".to_string())),
+        ("__DATA_OFFSET(TABLE)", CodegenErrorKind::MissingDataDefinition("TABLE".to_string())),
+    ] {
+        let source = format!(
+            "#define table TABLE {{ 0x00 }}\n#define macro MAIN() = takes (0) returns (0) {{ {source} }}"
+        );
+        let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let lexer = Lexer::new(flattened_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        let contract = parser.parse().unwrap();
+
+        let err =
+            Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap_err();
+        assert_eq!(err.kind, kind);
+    }
+}
+
+#[test]
+fn test_calldata_check_builtin() {
+    let source: &str = r#"
+        #define function transfer(address,uint256) nonpayable returns ()
+        #define function setName(string) nonpayable returns ()
+
+        #define macro MAIN() = takes (0) returns (0) {
+            __CALLDATA_CHECK(transfer)
+            __CALLDATA_CHECK(setName)
+            __CALLDATA_CHECK(setName, 0x44)
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // 6043 36 11 61000c 57 6000 80 fd 5b | 6023 36 11 610019 57 6000 80 fd 5b
+    // 6043 36 11 610026 57 6000 80 fd 5b
+    // Calls with less calldata than the selector and the head of the arguments revert
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(
+        r_bytes,
+        String::from(
+            "6043361161000c57600080fd5b6023361161001957600080fd5b6043361161002657600080fd5b"
+        )
+    );
+
+    // EOF code jumps over the revert with a relative jump instead
+    let r_bytes =
+        Codegen::generate_main_bytecode(&EVMVersion::default().with_eof(true), &contract, None)
+            .unwrap();
+    assert!(r_bytes.starts_with("60433611e10004600080fd6023"), "{r_bytes}");
+}
+
+#[test]
+fn test_calldata_check_builtin_in_repeated_macro() {
+    let source: &str = r#"
+        #define function transfer(address,uint256) nonpayable returns ()
+
+        #define macro CHECK() = takes (0) returns (0) {
+            __CALLDATA_CHECK(transfer)
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            CHECK()
+            CHECK()
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Parse the AST
+    let contract = parser.parse().unwrap();
+
+    // 6043 36 11 61000c 57 6000 80 fd 5b | 6043 36 11 610019 57 6000 80 fd 5b
+    // Each expansion jumps over its own revert
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(r_bytes, String::from("6043361161000c57600080fd5b6043361161001957600080fd5b"));
+}
+
+#[test]
+fn test_calldata_check_builtin_errors() {
+    for (source, kind) in [
+        (
+            "__CALLDATA_CHECK()",
+            CodegenErrorKind::InvalidArguments(
+                "Incorrect number of arguments passed to __CALLDATA_CHECK, should be 1 or 2: 0"
+                    .to_string(),
+            ),
+        ),
+        ("__CALLDATA_CHECK(mint)", CodegenErrorKind::MissingFunctionInterface("mint".to_string())),
+        (
+            "__CALLDATA_CHECK(transfer, 0x00)",
+            CodegenErrorKind::InvalidArguments(
+                "Invalid minimum size passed to __CALLDATA_CHECK: 00".to_string(),
+            ),
+        ),
+    ] {
+        let source = format!(
+            "#define function transfer(address,uint256) nonpayable returns ()\n#define macro MAIN() = takes (0) returns (0) {{ {source} }}"
+        );
+        let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let lexer = Lexer::new(flattened_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        let contract = parser.parse().unwrap();
+
+        let err =
+            Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap_err();
+        assert_eq!(err.kind, kind);
+    }
+}
+
+#[test]
+fn test_eip712_builtins() {
+    let source: &str = r#"
+        #define struct Order(address maker, uint256 amount, bytes data)
+
+        #define macro MAIN() = takes (0) returns (0) {
+            __TYPEHASH("Mail(Person from,Person to,string contents)Person(string name,address wallet)")
+            __TYPEHASH(Order)
+            __DOMAIN_SEPARATOR("Ether Mail", "1")
+            __DOMAIN_SEPARATOR("Ether Mail", "1", 0x80)
+        }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+
+    let mut order = [0u8; 32];
+    hash_bytes(&mut order, &"Order(address maker,uint256 amount,bytes data)".to_string());
+
+    // The domain type hash, the hashes of the name and version, `chainid` and `address`, are
+    // stored at the memory offset and hashed
+    let typehash = "7f8b73c3c69bb8fe3d512ecc4cf759cc79239f7b179b0ffacaa9a75d522b39400f";
+    let name = "7fc70ef06638535b4881fafcac8287e210e3769ff1a8e91f1b95d6246e61e4d3c6";
+    let version = "7fc89efdaa54c0f20c7adf612882df0950f5a951637e0307cdcb4c672f298b8bc6";
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(
+        r_bytes,
+        [
+            "7fa0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2",
+            &format!("7f{}", bytes32_to_string(&order, false)),
+            typehash,
+            "600052",
+            name,
+            "602052",
+            version,
+            "604052466060523060805260a0600020",
+            typehash,
+            "608052",
+            name,
+            "60a052",
+            version,
+            "60c0524660e052306101005260a0608020",
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn test_eip712_builtin_errors() {
+    for (source, reason) in [
+        ("__TYPEHASH()", "Incorrect number of arguments passed to __TYPEHASH, should be 1: 0"),
+        (
+            "__TYPEHASH(\"Permit(uint value)\")",
+            "Type \"uint\" of struct \"Permit\" is neither a canonical elementary type nor a struct of the EIP-712 type",
+        ),
+        (
+            "__DOMAIN_SEPARATOR(\"Token\")",
+            "Incorrect number of arguments passed to __DOMAIN_SEPARATOR, should be 2 or 3: 1",
+        ),
+    ] {
+        let source = format!("#define macro MAIN() = takes (0) returns (0) {{ {source} }}");
+        let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        let contract = parser.parse().unwrap();
+
+        let err =
+            Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap_err();
+        assert_eq!(err.kind, CodegenErrorKind::InvalidArguments(reason.to_string()));
+    }
+}
+
+#[test]
+fn test_precompile_builtins() {
+    let compile = |body: &str, version: SupportedEVMVersions| {
+        let source = format!("#define macro MAIN() = takes (0) returns (0) {{ {body} }}");
+        let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let contract = Parser::new(tokens, None).parse().unwrap();
+        Codegen::generate_main_bytecode(&EVMVersion::new(version), &contract, None)
+    };
+    let cancun = SupportedEVMVersions::Cancun;
+
+    // The inputs are stored at the memory offset, and the call is given the gas it costs
+    assert_eq!(
+        compile("__ECADD(0x40)", cancun).unwrap(),
+        "60405260605260805260a052604060406080604060066096fa61002157600080fd5b606051604051"
+    );
+    assert_eq!(
+        compile("__MODEXP()", cancun).unwrap(),
+        "60605260805260a052602080806000526020526040526020600060c060006005610550fa61002c57600080fd5b600051"
+    );
+    // An invalid signature returns no data, and pushes zero rather than reverting
+    assert_eq!(
+        compile("__ECRECOVER()", cancun).unwrap(),
+        "60005260205260405260605260206000608060006001610bb8fa5060203d0460005102"
+    );
+
+    // The gas of sha256 and the pairing check grows with their input
+    assert_eq!(
+        compile("__SHA256()", cancun).unwrap(),
+        "6020600091909290600282601f0160209004600c02603c01fa61002157600080fd5b600051"
+    );
+    assert_eq!(
+        compile("__ECPAIRING()", cancun).unwrap(),
+        "602060009190929060088260c090046184d00261afc801fa61002057600080fd5b600051"
+    );
+
+    // Each call of a repeated macro jumps over its own revert, to 0x21 and 0x49
+    let source = r#"
+        #define macro ADD() = takes (4) returns (2) {
+            __ECADD()
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            ADD() ADD()
+        }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).parse().unwrap();
+    assert_eq!(
+        Codegen::generate_main_bytecode(&EVMVersion::new(cancun), &contract, None).unwrap(),
+        "600052602052604052606052604060006080600060066096fa61002157600080fd5b602051600051\
+         600052602052604052606052604060006080600060066096fa61004957600080fd5b602051600051"
+    );
+
+    // Calls are repriced by hard fork, and unavailable before the precompile is introduced
+    assert!(compile("__ECMUL()", SupportedEVMVersions::Byzantium).unwrap().contains("619c40fa"));
+    assert!(compile("__ECMUL()", SupportedEVMVersions::Istanbul).unwrap().contains("611770fa"));
+    assert!(compile("__SHA256()", SupportedEVMVersions::Byzantium).is_ok());
+    assert_eq!(
+        compile("__ECRECOVER()", SupportedEVMVersions::Homestead).unwrap_err().kind,
+        CodegenErrorKind::UnsupportedOpcode(
+            Opcode::Staticcall,
+            EVMVersion::new(SupportedEVMVersions::Homestead)
+        )
+    );
+    assert_eq!(
+        compile("__POINT_EVALUATION()", SupportedEVMVersions::Shanghai).unwrap_err().kind,
+        CodegenErrorKind::UnsupportedPrecompile(
+            Precompile::PointEvaluation,
+            EVMVersion::new(SupportedEVMVersions::Shanghai)
+        )
+    );
+    assert_eq!(
+        compile("__ECADD()", SupportedEVMVersions::Homestead).unwrap_err().kind,
+        CodegenErrorKind::UnsupportedPrecompile(
+            Precompile::EcAdd,
+            EVMVersion::new(SupportedEVMVersions::Homestead)
+        )
+    );
+
+    for (body, reason) in [
+        (
+            "__SHA256(0x00, 0x20)",
+            "Incorrect number of arguments passed to __SHA256, should be 0 or 1: 2",
+        ),
+        ("__ECRECOVER(0x0100000000)", "Invalid memory offset passed to __ECRECOVER: 0100000000"),
+    ] {
+        assert_eq!(
+            compile(body, cancun).unwrap_err().kind,
+            CodegenErrorKind::InvalidArguments(reason.to_string())
+        );
+    }
+}
+
+#[test]
+fn test_builtins_in_repeated_macro() {
+    let compile = |main: &str, sample: &str| {
+        let source = format!(
+            r#"
+            #define function transfer(address,uint256) nonpayable returns ()
+            #define event Transfer(address,address,uint256)
+            #define error Unauthorized()
+            #define struct Order(address maker, uint256 amount)
+            #define constant SIZE = 0x20
+            #define data BLOB = 0xdeadbeef
+
+            #define table CODE_TABLE {{
+                0xc0ffee
+            }}
+
+            #define macro STORE() = takes (0) returns (0) {{
+                0x01 0x00 sstore
+            }}
+
+            #define macro SAMPLE() = takes (0) returns (0) {{
+                {sample}
+            }}
+
+            #define macro MAIN() = takes (0) returns (0) {{
+                {main}
+                start:
+                end:
+            }}
+        "#
+        );
+        let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let contract = Parser::new(tokens, None).parse().unwrap();
+        let evm_version = EVMVersion::new(SupportedEVMVersions::Cancun);
+        Codegen::generate_main_bytecode(&evm_version, &contract, None).unwrap()
+    };
+
+    // A macro invoked twice generates the same code as its body written out twice, whether or not
+    // its expansion is reused
+    for kind in BuiltinFunctionKind::ALL {
+        let sample = match kind {
+            BuiltinFunctionKind::Tablesize => "__tablesize(CODE_TABLE)",
+            BuiltinFunctionKind::Codesize => "__codesize(STORE)",
+            BuiltinFunctionKind::Tablestart => "__tablestart(CODE_TABLE)",
+            BuiltinFunctionKind::FunctionSignature => "__FUNC_SIG(transfer)",
+            BuiltinFunctionKind::EventHash => "__EVENT_HASH(Transfer)",
+            BuiltinFunctionKind::Error => "__ERROR(Unauthorized)",
+            BuiltinFunctionKind::RightPad => "__RIGHTPAD(0xa57b)",
+            BuiltinFunctionKind::DynConstructorArg => "__CODECOPY_DYN_ARG(0x00, 0x20)",
+            BuiltinFunctionKind::Verbatim => "__VERBATIM(0xdeadbeef)",
+            BuiltinFunctionKind::CalldataLoadArg => "__CALLDATA_LOAD_ARG(0, uint256)",
+            BuiltinFunctionKind::Erc7201 => r#"__ERC7201("example.main")"#,
+            BuiltinFunctionKind::Eip1967ImplSlot => "__EIP1967_IMPL_SLOT()",
+            BuiltinFunctionKind::Eip1967AdminSlot => "__EIP1967_ADMIN_SLOT()",
+            BuiltinFunctionKind::Eip1967BeaconSlot => "__EIP1967_BEACON_SLOT()",
+            BuiltinFunctionKind::Assert => "__ASSERT([SIZE] == 0x20)",
+            BuiltinFunctionKind::GasLimit => "__GAS_LIMIT(STORE, 30000)",
+            BuiltinFunctionKind::StrLit => r#"__STR_LIT("ok")"#,
+            BuiltinFunctionKind::Pc => "__PC()",
+            BuiltinFunctionKind::Offset => "__OFFSET(end)",
+            BuiltinFunctionKind::CodeSlice => "__CODE_SLICE(start, end)",
+            BuiltinFunctionKind::DataOffset => "__DATA_OFFSET(BLOB)",
+            BuiltinFunctionKind::DataSize => "__DATA_SIZE(BLOB)",
+            BuiltinFunctionKind::CalldataCheck => "__CALLDATA_CHECK(transfer)",
+            BuiltinFunctionKind::TypeHash => "__TYPEHASH(Order)",
+            BuiltinFunctionKind::DomainSeparator => r#"__DOMAIN_SEPARATOR("Mail", "1")"#,
+            BuiltinFunctionKind::Ecrecover => "__ECRECOVER()",
+            BuiltinFunctionKind::Sha256 => "__SHA256()",
+            BuiltinFunctionKind::Modexp => "__MODEXP()",
+            BuiltinFunctionKind::EcAdd => "__ECADD()",
+            BuiltinFunctionKind::EcMul => "__ECMUL()",
+            BuiltinFunctionKind::EcPairing => "__ECPAIRING()",
+            BuiltinFunctionKind::PointEvaluation => "__POINT_EVALUATION()",
+        };
+        assert_eq!(
+            compile("SAMPLE() SAMPLE()", sample),
+            compile(&format!("{sample} {sample}"), sample),
+            "{}",
+            kind.name()
+        );
+    }
+}
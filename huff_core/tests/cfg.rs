@@ -0,0 +1,81 @@
+use huff_codegen::{analysis::prelude::*, *};
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::*;
+
+fn cfg(source: &str) -> ControlFlowGraph {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut contract = Parser::new(tokens, None).parse().unwrap();
+    contract.derive_storage_pointers();
+    Codegen::generate_main_cfg(&EVMVersion::default(), &contract, None, 1024).unwrap()
+}
+
+#[test]
+fn test_cfg_blocks_and_edges() {
+    let source = r#"
+        #define macro CHECK() = takes(1) returns(0) {
+            iszero fail jumpi
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x00 calldataload CHECK()
+            done jump
+            fail:
+                0x00 0x00 revert
+            done:
+                stop
+        }
+    "#;
+    let cfg = cfg(source);
+
+    // PUSH1 CALLDATALOAD ISZERO PUSH2 JUMPI | PUSH2 JUMP | JUMPDEST PUSH1 PUSH1 REVERT |
+    // JUMPDEST STOP
+    let starts = cfg.blocks.iter().map(|b| b.start).collect::<Vec<usize>>();
+    assert_eq!(starts, vec![0, 8, 12, 18]);
+    assert!(cfg.blocks.iter().all(|b| !b.dynamic_jump));
+    assert_eq!(
+        cfg.edges,
+        vec![
+            Edge { from: 0, to: 12, kind: EdgeKind::Branch },
+            Edge { from: 0, to: 8, kind: EdgeKind::Fallthrough },
+            Edge { from: 8, to: 18, kind: EdgeKind::Jump },
+        ]
+    );
+
+    let dot = cfg.to_dot();
+    assert!(dot.starts_with("digraph \"MAIN\" {"));
+    assert!(dot.contains("b0 -> b12 [color=\"darkgreen\", label=\"jumpi\"];"));
+    assert!(dot.contains("b0 -> b8 [style=dashed];"));
+    assert!(dot.contains("b8 -> b18;"));
+    assert!(dot.contains("label=\"fail:\\l0x000c jumpdest\\l"));
+    assert!(dot.contains("0x0008 push2 0x0012\\l"));
+    // CHECK doesn't start a block of its own, so only MAIN is drawn as a cluster
+    assert!(dot.contains("label=\"MAIN\";"));
+    assert!(!dot.contains("label=\"CHECK\";"));
+}
+
+#[test]
+fn test_cfg_dynamic_jumps_and_macro_clusters() {
+    let source = r#"
+        #define macro RETURN_TO() = takes(1) returns(0) {
+            back:
+                jump
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x00 calldataload
+            RETURN_TO()
+        }
+    "#;
+    let cfg = cfg(source);
+    assert_eq!(cfg.blocks.len(), 2);
+    assert!(cfg.blocks[1].dynamic_jump);
+    assert_eq!(cfg.edges, vec![Edge { from: 0, to: 3, kind: EdgeKind::Fallthrough }]);
+
+    let dot = cfg.to_dot();
+    let main = dot.find("label=\"MAIN\";").unwrap();
+    let nested = dot.find("label=\"RETURN_TO\";").unwrap();
+    assert!(main < nested);
+    assert!(dot.contains("b3 [label=\"back:\\l0x0003 jumpdest\\l0x0004 jump\\l\", color=\"red\"];"));
+}
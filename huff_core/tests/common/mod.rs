@@ -0,0 +1,55 @@
+//! Helpers shared by the integration tests, each of which only uses some of them
+#![allow(dead_code)]
+
+use huff_codegen::Codegen;
+use huff_core::Compiler;
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::*;
+use std::{collections::HashMap, sync::Arc};
+
+/// An in-memory compiler of the first file, which may include the other files
+pub fn compiler(files: &[(&str, &str)]) -> Compiler<'static> {
+    Compiler::new_in_memory(
+        Arc::new(vec![files[0].0.to_string()]),
+        files
+            .iter()
+            .map(|(path, source)| (path.to_string(), source.to_string()))
+            .collect::<HashMap<_, _>>(),
+        None,
+        None,
+        None,
+        None,
+        false,
+    )
+}
+
+pub fn compile(files: &[(&str, &str)]) -> Result<Vec<Arc<Artifact>>, Arc<CompilerError<'static>>> {
+    compiler(files).execute()
+}
+
+/// The artifact of `source`, compiled as `main.huff`
+pub fn artifact(source: &str) -> Arc<Artifact> {
+    compile(&[("main.huff", source)]).unwrap().remove(0)
+}
+
+/// The error of the first file that failed to compile
+pub fn first_error<'a>(e: &'a CompilerError<'static>) -> &'a CompilerError<'static> {
+    match e {
+        CompilerError::FailedCompiles(errors) => first_error(&errors[0]),
+        e => e,
+    }
+}
+
+pub fn parse(source: &str) -> Contract {
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(full_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+    contract
+}
+
+pub fn main_bytecode(source: &str) -> Result<String, CodegenError> {
+    Codegen::generate_main_bytecode(&EVMVersion::default(), &parse(source), None)
+}
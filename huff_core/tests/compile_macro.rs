@@ -0,0 +1,74 @@
+mod common;
+
+use common::compiler;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+#define macro LOOP() = takes (1) returns (0) {
+    top:
+        0x01 swap1 sub
+        dup1 top jumpi
+        pop
+}
+
+#define macro TABLE() = takes (0) returns (0) {
+    __tablesize(JUMPS) __tablestart(JUMPS) 0x00 codecopy
+    a: b:
+}
+
+#define jumptable__packed JUMPS {
+    a b
+}
+
+#define macro WITH_ARGS(arg) = takes (0) returns (0) {
+    <arg>
+}
+
+#define macro MAIN() = takes (0) returns (0) {
+    0x0a LOOP()
+    a: b:
+}
+"#;
+
+#[test]
+fn test_compile_macro_resolves_jumps() {
+    let compiled = compiler(&[("main.huff", SOURCE)]).compile_macro("LOOP").unwrap();
+    // top: 5b | 6001 90 03 | 80 610000 57 | 50
+    assert_eq!(compiled["main.huff"], "5b600190038061000057 50".replace(' ', ""));
+}
+
+#[test]
+fn test_compile_macro_appends_tables() {
+    let compiled = compiler(&[("main.huff", SOURCE)]).compile_macro("TABLE").unwrap();
+    // 6004 61000a 6000 39 5b 5b | 0008 0009
+    assert_eq!(compiled["main.huff"], "600461000a6000395b5b00080009");
+}
+
+#[test]
+fn test_compile_macro_of_an_import() {
+    let main =
+        "#include \"./loop.huff\"\n#define macro MAIN() = takes (0) returns (0) { 0x0a LOOP() }";
+    let compiled =
+        compiler(&[("main.huff", main), ("loop.huff", &SOURCE.replace("MAIN", "OTHER_MAIN"))])
+            .compile_macro("LOOP")
+            .unwrap();
+    assert_eq!(compiled.len(), 1);
+    assert!(compiled.keys().next().unwrap().ends_with("loop.huff"));
+}
+
+#[test]
+fn test_compile_missing_macro() {
+    let err = compiler(&[("main.huff", SOURCE)]).compile_macro("MISSING").unwrap_err();
+    assert!(matches!(
+        err.as_ref(),
+        CompilerError::CodegenError(CodegenError {
+            kind: CodegenErrorKind::MissingMacroDefinition(name),
+            ..
+        }) if name == "MISSING"
+    ));
+}
+
+#[test]
+fn test_compile_macro_with_args() {
+    assert!(compiler(&[("main.huff", SOURCE)]).compile_macro("WITH_ARGS").is_err());
+}
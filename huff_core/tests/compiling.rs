@@ -57,7 +57,7 @@ fn compiles_constructor_bytecode() {
 
     // Have the Codegen create the constructor bytecode
     let (cbytes, custom_bootstrap) =
-        Codegen::generate_constructor_bytecode(&contract, None).unwrap();
+        Codegen::generate_constructor_bytecode(&EVMVersion::default(), &contract, None).unwrap();
     println!("Constructor Bytecode Result: {cbytes:?}");
     assert_eq!(cbytes, String::from("33600055"));
     assert!(!custom_bootstrap);
@@ -83,7 +83,8 @@ fn compiles_runtime_bytecode() {
     assert!(cg.artifact.is_none());
 
     // Have the Codegen create the constructor bytecode
-    let (cbytes, cbootstrap) = Codegen::generate_constructor_bytecode(&contract, None).unwrap();
+    let (cbytes, cbootstrap) =
+        Codegen::generate_constructor_bytecode(&EVMVersion::default(), &contract, None).unwrap();
     assert_eq!(cbytes, String::from("33600055"));
     assert!(!cbootstrap);
 
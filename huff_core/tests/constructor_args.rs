@@ -0,0 +1,61 @@
+mod common;
+
+use huff_utils::prelude::*;
+use std::sync::Arc;
+
+const SOURCE: &str = r#"
+    #define function constructor(address, uint256[]) nonpayable returns ()
+
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {}
+    #define macro MAIN() = takes(0) returns (0) {}
+"#;
+
+fn compile(args: &str) -> Result<Vec<Arc<Artifact>>, Arc<CompilerError<'static>>> {
+    let path = std::env::temp_dir().join(format!("huff-args-{}.json", uuid::Uuid::new_v4()));
+    std::fs::write(&path, args).unwrap();
+
+    let mut compiler = common::compiler(&[("main.huff", SOURCE)]);
+    compiler.constructor_args_path = Some(path.to_string_lossy().to_string());
+    let res = compiler.execute();
+    std::fs::remove_file(path).unwrap();
+    res
+}
+
+#[test]
+fn test_constructor_args_file() {
+    let artifacts =
+        compile(r#"["0x646dB8ffC21e7ddc2B6327448dd9Fa560Df41087", [1, "0x02"]]"#).unwrap();
+    let encoded = concat!(
+        "000000000000000000000000646db8ffc21e7ddc2b6327448dd9fa560df41087",
+        "0000000000000000000000000000000000000000000000000000000000000040",
+        "0000000000000000000000000000000000000000000000000000000000000002",
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "0000000000000000000000000000000000000000000000000000000000000002",
+    );
+    // Dynamic arguments are appended first, without their pointer and length
+    assert!(artifacts[0].bytecode.ends_with(concat!(
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "0000000000000000000000000000000000000000000000000000000000000002",
+        "000000000000000000000000646db8ffc21e7ddc2b6327448dd9fa560df41087",
+    )));
+
+    // The same arguments, already ABI-encoded
+    let encoded_artifacts = compile(&format!("0x{encoded}")).unwrap();
+    assert_eq!(encoded_artifacts[0].bytecode, artifacts[0].bytecode);
+}
+
+#[test]
+fn test_mistyped_constructor_args_file() {
+    let err = compile(r#"["0x646dB8ffC21e7ddc2B6327448dd9Fa560Df41087", [1, true]]"#).unwrap_err();
+    match &*err {
+        CompilerError::FailedCompiles(errors) => match &errors[0] {
+            CompilerError::CodegenError(e) => assert!(matches!(
+                &e.kind,
+                CodegenErrorKind::InvalidConstructorArgs(msg)
+                    if msg.starts_with("Invalid constructor argument true for param 1[1] of type uint256 at line 1, column 52")
+            )),
+            e => panic!("unexpected error: {e:?}"),
+        },
+        e => panic!("unexpected error: {e:?}"),
+    }
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use ethers_core::{
+    abi::{decode, ParamType, Token},
+    utils::{hex, keccak256},
+};
+use huff_utils::prelude::*;
+use std::sync::Arc;
+
+const SOURCE: &str = r#"
+    #define function value() view returns (uint256)
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x2a 0x00 mstore
+        0x20 0x00 return
+    }
+"#;
+
+const CONTRACTS: &str = r#"
+    #define contract Token {
+        #define macro MAIN() = takes(0) returns(0) { 0x01 }
+    }
+
+    #define contract Vault {
+        #define macro MAIN() = takes(0) returns(0) { 0x02 }
+    }
+"#;
+
+fn compile(source: &str, name: Option<&str>) -> Vec<Arc<Artifact>> {
+    let mut compiler = common::compiler(&[("src/Value.huff", source)]);
+    compiler.name = name.map(String::from);
+    compiler.execute().unwrap()
+}
+
+#[test]
+fn test_content_hash() {
+    let artifact = &compile(SOURCE, None)[0];
+    let abi = r#"[{"inputs":[],"name":"value","outputs":[{"name":"","type":"uint256"}],"stateMutability":"view","type":"function"}]"#;
+    let abi_hash = keccak256(abi);
+    let runtime_hash = keccak256(hex::decode(&artifact.runtime).unwrap());
+    let content_hash = keccak256([abi_hash, runtime_hash].concat());
+    assert_eq!(artifact.abi_hash, Some(format!("0x{}", hex::encode(abi_hash))));
+    assert_eq!(artifact.content_hash, Some(format!("0x{}", hex::encode(content_hash))));
+
+    // The name doesn't change the content hash, the code does
+    let labeled = &compile(SOURCE, Some("Value"))[0];
+    assert_eq!(labeled.content_hash, artifact.content_hash);
+    let changed = &compile(&SOURCE.replace("0x2a", "0x2b"), None)[0];
+    assert_eq!(changed.abi_hash, artifact.abi_hash);
+    assert_ne!(changed.content_hash, artifact.content_hash);
+}
+
+#[test]
+fn test_name_labels_artifacts() {
+    let artifact = &compile(SOURCE, None)[0];
+    assert_eq!(artifact.name, None);
+    assert_eq!(artifact.label(), "Value");
+    assert_eq!(compile(SOURCE, Some("Answer"))[0].label(), "Answer");
+
+    // Multiple contracts are labeled with their contract names
+    let mut names =
+        compile(CONTRACTS, Some("Protocol")).iter().map(|a| a.label()).collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, vec!["Protocol.Token", "Protocol.Vault"]);
+}
+
+#[test]
+fn test_registry_calldata() {
+    let artifact = &compile(SOURCE, Some("Answer"))[0];
+    let calldata = hex::decode(&artifact.registry_calldata().unwrap()[2..]).unwrap();
+    assert_eq!(calldata[..4], keccak256("register(bytes32,bytes32,string)")[..4]);
+
+    let args = decode(
+        &[ParamType::FixedBytes(32), ParamType::FixedBytes(32), ParamType::String],
+        &calldata[4..],
+    )
+    .unwrap();
+    let word = |hash: &Option<String>| {
+        Token::FixedBytes(hex::decode(&hash.as_ref().unwrap()[2..]).unwrap())
+    };
+    assert_eq!(
+        args,
+        vec![
+            word(&artifact.content_hash),
+            word(&artifact.abi_hash),
+            Token::String("Answer".to_string())
+        ]
+    );
+}
@@ -0,0 +1,176 @@
+use huff_codegen::Codegen;
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::*;
+
+fn compile_main(source: &str) -> String {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut contract = Parser::new(tokens, None).parse().unwrap();
+    contract.derive_storage_pointers();
+    Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap()
+}
+
+#[test]
+fn test_if_else() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            0x00 calldataload
+            if { 0x02 } else { 0x03 }
+            0x00 mstore
+        }
+    "#;
+
+    // 6000 35 | 15 61000e 57 6002 610011 56 | 5b 6003 | 5b 6000 52
+    assert_eq!(compile_main(source), "6000351561000e576002610011565b60035b600052");
+}
+
+#[test]
+fn test_loop_in_invoked_macros() {
+    let source = r#"
+        #define macro COUNTDOWN() = takes(1) returns(1) {
+            loop {
+                dup1 iszero if { break }
+                0x01 swap1 sub
+            }
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x03 COUNTDOWN()
+            COUNTDOWN()
+        }
+    "#;
+
+    // Each expansion jumps to its own labels
+    // 6003 | 5b 80 15 15 61000e 57 610017 56 5b 6001 90 03 610002 56 5b
+    //      | 5b 80 15 15 610024 57 61002d 56 5b 6001 90 03 610018 56 5b
+    assert_eq!(
+        compile_main(source),
+        concat!(
+            "6003",
+            "5b80151561000e57610017565b60019003610002565b",
+            "5b8015156100245761002d565b60019003610018565b"
+        )
+    );
+}
+
+#[test]
+fn test_switch_over_selector() {
+    let source = r#"
+        #define function transfer(address,uint256) nonpayable returns ()
+        #define constant BALANCE_OF_SIG = 0x70a08231
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x00 calldataload 0xe0 shr
+            switch {
+                case __FUNC_SIG(transfer) { 0x01 }
+                case [BALANCE_OF_SIG] { 0x02 }
+                default { 0x03 }
+            }
+            stop
+        }
+    "#;
+
+    // 6000 35 60e0 1c
+    // 80 63a9059cbb 14 610023 57 | 80 6370a08231 14 61002b 57 | 50 6003 61002f 56
+    // 5b 50 6001 61002f 56 | 5b 50 6002 | 5b 00
+    assert_eq!(
+        compile_main(source),
+        concat!(
+            "60003560e01c",
+            "8063a9059cbb1461002357",
+            "806370a082311461002b57",
+            "50600361002f56",
+            "5b50600161002f56",
+            "5b506002",
+            "5b00"
+        )
+    );
+}
+
+#[test]
+fn test_switch_table_over_selector() {
+    let source = r#"
+        #define function transfer(address,uint256) nonpayable returns ()
+        #define function balanceOf(address) view returns (uint256)
+        #define constant THREE = 0x03
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x00 calldataload 0xe0 shr
+            switch table {
+                case __FUNC_SIG(transfer) { 0x01 }
+                case __FUNC_SIG(balanceOf) { 0x02 }
+                case [THREE] { 0x07 }
+                default { 0x03 }
+            }
+            stop
+        }
+    "#;
+
+    // 6000 35 60e0 1c
+    // 6005 81 06 80 01 61005a 01 6002 90 601e 39 6000 51 61ffff 16 56
+    // 5b 50 6003 610058 56
+    // 5b 80 63a9059cbb 03 61001e 57 50 6001 610058 56
+    // 5b 80 6370a08231 03 61001e 57 50 6002 610058 56
+    // 5b 80 6003 03 61001e 57 50 6007 | 5b 00
+    // 001e 0039 0026 004c 001e
+    // The selectors are 2, 0 and 3 modulo 5, the other entries jump to the default
+    assert_eq!(
+        compile_main(source),
+        concat!(
+            "60003560e01c",
+            "60058106800161005a01600290601e3960005161ffff1656",
+            "5b50600361005856",
+            "5b8063a9059cbb0361001e5750600161005856",
+            "5b806370a082310361001e5750600261005856",
+            "5b8060030361001e57506007",
+            "5b00",
+            "001e00390026004c001e"
+        )
+    );
+}
+
+#[test]
+fn test_switch_trie_over_selector() {
+    let source = r#"
+        #define function transfer(address,uint256) nonpayable returns ()
+        #define function balanceOf(address) view returns (uint256)
+        #define function approve(address,uint256) nonpayable returns (bool)
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x00 calldataload 0xe0 shr
+            switch trie {
+                case __FUNC_SIG(transfer) { 0x01 }
+                case __FUNC_SIG(balanceOf) { 0x02 }
+                case __FUNC_SIG(approve) { 0x03 }
+                default { 0x04 }
+            }
+            stop
+        }
+    "#;
+
+    // 6000 35 60e0 1c | 80 601c 1a
+    // 6070 81 10 61003b 57 60a9 81 10 61002a 57
+    // 50 80 63a9059cbb 14 610050 57 610048 56
+    // 5b 50 80 6370a08231 14 610058 57 610048 56
+    // 5b 50 80 63095ea7b3 14 610060 57
+    // 5b 50 6004 610064 56
+    // 5b 50 6001 610064 56 | 5b 50 6002 610064 56 | 5b 50 6003 | 5b 00
+    // The first bytes 0x09, 0x70 and 0xa9 are searched for before comparing the full selector
+    assert_eq!(
+        compile_main(source),
+        concat!(
+            "60003560e01c80601c1a",
+            "6070811061003b5760a9811061002a57",
+            "508063a9059cbb146100505761004856",
+            "5b50806370a08231146100585761004856",
+            "5b508063095ea7b31461006057",
+            "5b50600461006456",
+            "5b50600161006456",
+            "5b50600261006456",
+            "5b506003",
+            "5b00"
+        )
+    );
+}
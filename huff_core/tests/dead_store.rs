@@ -0,0 +1,84 @@
+use huff_codegen::Codegen;
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::*;
+
+fn warnings(source: &str) -> Vec<CompilerWarning> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut contract = Parser::new(tokens, None).parse().unwrap();
+    contract.derive_storage_pointers();
+    Codegen::analyze(&contract)
+}
+
+#[test]
+fn test_dead_memory_store() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            0x01 0x00 mstore
+            0x02 0x00 mstore
+            0x20 0x00 return
+        }
+    "#;
+    let warnings = warnings(source);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].kind,
+        WarningKind::DeadStore("MAIN".to_string(), "mstore To 0x00".to_string())
+    );
+    // The span is the first store's, from its offset to the opcode
+    let spans = &warnings[0].span.0;
+    assert_eq!(&source[spans[0].start..spans[1].end], "00 mstore");
+    assert!(spans[1].end < source.find("0x02").unwrap());
+}
+
+#[test]
+fn test_dead_storage_store() {
+    let source = r#"
+        #define constant SLOT = FREE_STORAGE_POINTER()
+        #define constant OTHER = 0x01
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x01 [SLOT] sstore
+            0x01 [OTHER] sstore
+            0x02 [SLOT] sstore
+        }
+    "#;
+    let warnings = warnings(source);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].kind,
+        WarningKind::DeadStore("MAIN".to_string(), "sstore To 0x00".to_string())
+    );
+}
+
+#[test]
+fn test_stores_read_in_between() {
+    let source = r#"
+        #define macro STORE() = takes(0) returns(0) {
+            0x01 0x00 mstore
+            0x00 mload pop
+            0x02 0x00 mstore
+            0x01 0x00 sstore
+            0x00 sload pop
+            0x02 0x00 sstore
+            0x01 0x20 mstore
+            0x20 0x00 sha3 pop
+            0x02 0x20 mstore
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x01 0x00 mstore
+            STORE()
+            0x02 0x00 mstore
+            0x01 0x40 mstore
+            0x01 0x60 mstore
+            label jump
+            label:
+                0x02 0x40 mstore
+                dup1 0x60 mstore
+                0x00 0x00 return
+        }
+    "#;
+    assert!(warnings(source).is_empty());
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use huff_utils::prelude::*;
+use std::sync::Arc;
+
+fn compile(
+    source: &str,
+    constructor: Option<&str>,
+    args: Option<Vec<String>>,
+) -> Result<Vec<Arc<Artifact>>, Arc<CompilerError<'static>>> {
+    let mut compiler = common::compiler(&[("main.huff", source)]);
+    compiler.alternative_constructor = constructor.map(String::from);
+    compiler.construct_args = args;
+    compiler.execute()
+}
+
+#[test]
+fn test_missing_constructor_deploys_runtime() {
+    let source = "#define macro MAIN() = takes (0) returns (0) { 0x01 0x02 add }";
+    let artifact = compile(source, None, None).unwrap().remove(0);
+
+    // 6005 80 6009 3d 39 3d f3 | 6001 6002 01
+    assert_eq!(artifact.bytecode, "60058060093d393df36001600201");
+    assert_eq!(artifact.runtime, "6001600201");
+    assert!(artifact.default_constructor);
+
+    let json = serde_json::to_value(artifact.as_ref()).unwrap();
+    assert_eq!(json["defaultConstructor"], serde_json::json!(true));
+}
+
+#[test]
+fn test_constructor_is_not_default() {
+    let source = r#"
+        #define macro CONSTRUCTOR() = takes (0) returns (0) { 0x01 0x00 sstore }
+        #define macro MAIN() = takes (0) returns (0) { 0x01 0x02 add }
+    "#;
+    let artifact = compile(source, None, None).unwrap().remove(0);
+    assert!(artifact.bytecode.starts_with("6001600055"));
+    assert!(!artifact.default_constructor);
+
+    let json = serde_json::to_value(artifact.as_ref()).unwrap();
+    assert!(json.get("defaultConstructor").is_none());
+}
+
+#[test]
+fn test_missing_constructor_with_args_errors() {
+    let source = "#define macro MAIN() = takes (0) returns (0) { 0x01 0x02 add }";
+    let err = compile(source, None, Some(vec!["1".to_string()])).unwrap_err();
+    let errors = match err.as_ref() {
+        CompilerError::FailedCompiles(errors) => errors,
+        e => panic!("unexpected error: {e:?}"),
+    };
+    assert!(matches!(
+        &errors[0],
+        CompilerError::CodegenError(CodegenError {
+            kind: CodegenErrorKind::MissingMacroDefinition(name),
+            ..
+        }) if name == "CONSTRUCTOR"
+    ));
+}
+
+#[test]
+fn test_missing_alternative_constructor_errors() {
+    let source = "#define macro MAIN() = takes (0) returns (0) { 0x01 0x02 add }";
+    assert!(compile(source, Some("DEPLOY"), None).is_err());
+}
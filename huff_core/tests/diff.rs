@@ -0,0 +1,63 @@
+mod common;
+
+use common::artifact;
+use huff_core::diff::{BytecodeDiff, DiffTag};
+
+const OLD: &str = r#"
+    #define macro CHECK() = takes (1) returns (0) {
+        iszero fail jumpi
+    }
+
+    #define macro MAIN() = takes (0) returns (0) {
+        0x00 calldataload CHECK()
+        0x01 0x00 sstore
+        stop
+        fail:
+            0x00 0x00 revert
+    }
+"#;
+
+#[test]
+fn test_identical_bytecode() {
+    assert!(BytecodeDiff::new(&artifact(OLD), &artifact(OLD)).unwrap().is_empty());
+}
+
+#[test]
+fn test_diff_aligned_by_macro_and_label() {
+    let new = OLD.replace("0x01 0x00 sstore", "0x02 0x00 sstore caller pop");
+    let diff = BytecodeDiff::new(&artifact(OLD), &artifact(&new)).unwrap();
+
+    // The label moved, but the jump to it in CHECK and the code after it didn't change
+    assert_eq!(diff.sections.len(), 1);
+    let section = &diff.sections[0];
+    assert_eq!(section.section, "MAIN");
+    let changes = section
+        .lines
+        .iter()
+        .filter(|l| l.tag != DiffTag::Unchanged)
+        .map(|l| (l.tag, l.instruction.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        changes,
+        vec![
+            (DiffTag::Removed, "push1 0x01"),
+            (DiffTag::Added, "push1 0x02"),
+            (DiffTag::Added, "caller"),
+            (DiffTag::Added, "pop")
+        ]
+    );
+
+    let rendered = diff.to_string();
+    assert!(rendered.starts_with("@@ MAIN @@\n"));
+    assert!(rendered.contains("- 0x0008        push1 0x01\n"));
+    assert!(rendered.contains("+        0x0008 push1 0x02\n"));
+}
+
+#[test]
+fn test_diff_of_changed_label() {
+    let new = OLD.replace("0x00 0x00 revert", "0x20 0x00 revert");
+    let diff = BytecodeDiff::new(&artifact(OLD), &artifact(&new)).unwrap();
+    assert_eq!(diff.sections.len(), 1);
+    assert_eq!(diff.sections[0].section, "MAIN::fail");
+    assert!(diff.sections[0].lines.iter().any(|l| l.instruction == "jumpdest"));
+}
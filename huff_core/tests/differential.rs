@@ -0,0 +1,61 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use huff_core::Compiler;
+use walkdir::WalkDir;
+
+/// The corpus checked when `HUFF_DIFFERENTIAL_CORPUS` is not set.
+const DEFAULT_CORPUS: &str = "tests/differential";
+
+/// Collects every contract in the corpus that has an expected bytecode fixture next to it.
+fn corpus(dir: &Path) -> Vec<(PathBuf, PathBuf)> {
+    let mut cases = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .map(|e| e.into_path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "huff"))
+        .filter_map(|p| {
+            let fixture = p.with_extension("bin");
+            fixture.exists().then_some((p, fixture))
+        })
+        .collect::<Vec<_>>();
+    cases.sort();
+    cases
+}
+
+#[test]
+fn test_differential_corpus() {
+    let dir = env::var("HUFF_DIFFERENTIAL_CORPUS").unwrap_or_else(|_| DEFAULT_CORPUS.to_string());
+    let cases = corpus(Path::new(&dir));
+    assert!(!cases.is_empty(), "No contracts with `.bin` fixtures found in {dir}");
+
+    let mut mismatches = vec![];
+    for (contract, fixture) in &cases {
+        let expected = fs::read_to_string(fixture).unwrap().trim().to_lowercase();
+        let path = contract.to_string_lossy().to_string();
+
+        let compiler =
+            Compiler::new(Arc::new(vec![path.clone()]), None, None, None, None, None, false, false);
+        match compiler.execute() {
+            Ok(artifacts) => {
+                let runtime = artifacts[0].runtime.to_lowercase();
+                if runtime != expected {
+                    mismatches
+                        .push(format!("{path}\n  expected: {expected}\n  actual:   {runtime}"));
+                }
+            }
+            Err(e) => mismatches.push(format!("{path}\n  failed to compile: {e}")),
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "{} of {} contracts differ from the reference bytecode:\n{}",
+        mismatches.len(),
+        cases.len(),
+        mismatches.join("\n")
+    );
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use huff_codegen::{analysis::prelude::validate_dispatch, Codegen};
+use huff_utils::prelude::*;
+use std::sync::Arc;
+
+fn compile(source: &str) -> Result<Vec<Arc<Artifact>>, Arc<CompilerError<'static>>> {
+    let mut compiler = common::compiler(&[("main.huff", source)]);
+    compiler.check_dispatch = true;
+    compiler.execute()
+}
+
+fn dispatch_error(e: &CompilerError) -> Option<CodegenErrorKind> {
+    match e {
+        CompilerError::FailedCompiles(errors) => errors.iter().find_map(dispatch_error),
+        CompilerError::CodegenError(CodegenError { kind, .. }) => Some(kind.clone()),
+        _ => None,
+    }
+}
+
+fn dispatched_bodies(source: &str) -> Result<Vec<(String, usize)>, CodegenError> {
+    let contract = common::parse(source);
+    let bytecode = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None)?;
+    validate_dispatch(&contract, &bytecode)
+}
+
+const INTERFACE: &str = r#"
+    #define function get() view returns (uint256)
+    #define function set(uint256) nonpayable returns ()
+"#;
+
+#[test]
+fn test_dispatch_reaches_each_body() {
+    let source = format!(
+        r#"{INTERFACE}
+        #define macro MAIN() = takes (0) returns (0) {{
+            0x00 calldataload 0xe0 shr
+            dup1 __FUNC_SIG(get) eq get jumpi
+            dup1 __FUNC_SIG(set) eq set jumpi
+            0x00 dup1 revert
+
+            get:
+                0x00 sload 0x00 mstore 0x20 0x00 return
+            set:
+                0x04 calldataload 0x00 sstore stop
+        }}
+    "#
+    );
+    // 60003560e01c | 80636d4ce63c1461002057 | 806360fe47b11461002c57 | 600080fd | 5b
+    assert_eq!(
+        dispatched_bodies(&source).unwrap(),
+        vec![("get".to_string(), 0x20), ("set".to_string(), 0x2c)]
+    );
+    assert!(compile(&source).is_ok());
+}
+
+#[test]
+fn test_dispatch_with_checks_and_inline_bodies() {
+    // Calls with value revert ahead of the dispatch, and bodies follow the `jumpi` skipping them
+    let source = format!(
+        r#"{INTERFACE}
+        #define macro MAIN() = takes (0) returns (0) {{
+            callvalue iszero dispatch jumpi
+            0x00 dup1 revert
+            dispatch:
+                0x00 calldataload 0xe0 shr
+                dup1 __FUNC_SIG(get) eq iszero not_get jumpi
+                0x00 sload 0x00 mstore 0x20 0x00 return
+            not_get:
+                dup1 __FUNC_SIG(set) eq iszero not_set jumpi
+                0x04 calldataload 0x00 sstore stop
+            not_set:
+                0x00 dup1 revert
+        }}
+    "#
+    );
+    let bodies = dispatched_bodies(&source).unwrap();
+    assert_eq!(bodies.len(), 2);
+    assert!(bodies[0].1 < bodies[1].1);
+}
+
+#[test]
+fn test_dispatch_missing_function() {
+    // `set` falls through into the revert, or into `get` without one
+    for fallthrough in ["0x00 dup1 revert", ""] {
+        let source = format!(
+            r#"{INTERFACE}
+            #define macro MAIN() = takes (0) returns (0) {{
+                0x00 calldataload 0xe0 shr
+                dup1 __FUNC_SIG(get) eq get jumpi
+                {fallthrough}
+
+                get:
+                    0x00 sload 0x00 mstore 0x20 0x00 return
+            }}
+        "#
+        );
+        let e = compile(&source).unwrap_err();
+        assert_eq!(
+            dispatch_error(&e),
+            Some(CodegenErrorKind::UnreachableFunction("set".to_string())),
+            "{source}"
+        );
+    }
+}
+
+#[test]
+fn test_dispatch_collision() {
+    let source = format!(
+        r#"{INTERFACE}
+        #define macro MAIN() = takes (0) returns (0) {{
+            0x00 calldataload 0xe0 shr
+            dup1 __FUNC_SIG(get) eq get jumpi
+            dup1 __FUNC_SIG(set) eq get jumpi
+            0x00 dup1 revert
+
+            get:
+                0x00 sload 0x00 mstore 0x20 0x00 return
+            set:
+                0x04 calldataload 0x00 sstore stop
+        }}
+    "#
+    );
+    let e = compile(&source).unwrap_err();
+    assert_eq!(
+        dispatch_error(&e),
+        Some(CodegenErrorKind::DispatchCollision("get".to_string(), "set".to_string(), 0x20))
+    );
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use common::parse;
+use huff_codegen::{analysis::prelude::insert_fallthrough_revert, *};
+use huff_utils::prelude::*;
+
+fn compile(source: &str, auto_revert: bool) -> String {
+    let mut compiler = common::compiler(&[("main.huff", source)]);
+    compiler.auto_revert = auto_revert;
+    compiler.execute().unwrap()[0].runtime.clone()
+}
+
+const FALLTHROUGH: &str = r#"
+    #define function transfer(address,uint256) nonpayable returns ()
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 calldataload 0xE0 shr
+        dup1 __FUNC_SIG(transfer) eq transfer jumpi
+
+        transfer:
+            0x00 0x00 return
+    }
+"#;
+
+#[test]
+fn test_dispatch_missing_fallthrough() {
+    let source = r#"
+    #define function transfer(address,uint256) nonpayable returns ()
+    #define function mint(address,uint256) nonpayable returns ()
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 calldataload 0xE0 shr
+        dup1 __FUNC_SIG(transfer) eq transfer jumpi
+        dup1 __FUNC_SIG(mint) eq mint jumpi
+
+        transfer:
+            0x00 0x00 return
+        mint:
+            0x00 0x00 return
+    }
+    "#;
+
+    let contract = parse(source);
+    let warnings = Codegen::analyze(&contract);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::MissingFallthrough("MAIN".to_string()));
+}
+
+#[test]
+fn test_dispatch_with_terminating_revert() {
+    let source = r#"
+    #define function transfer(address,uint256) nonpayable returns ()
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 calldataload 0xE0 shr
+        dup1 __FUNC_SIG(transfer) eq transfer jumpi
+        0x00 0x00 revert
+
+        transfer:
+            0x00 0x00 return
+    }
+    "#;
+
+    let contract = parse(source);
+    assert!(Codegen::analyze(&contract).is_empty());
+}
+
+#[test]
+fn test_dispatch_with_terminating_macro() {
+    let source = r#"
+    #define function transfer(address,uint256) nonpayable returns ()
+
+    #define macro FALLBACK() = takes(0) returns(0) {
+        0x00 0x00 revert
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 calldataload 0xE0 shr
+        dup1 __FUNC_SIG(transfer) eq transfer jumpi
+        FALLBACK()
+
+        transfer:
+            0x00 0x00 return
+    }
+    "#;
+
+    let contract = parse(source);
+    assert!(Codegen::analyze(&contract).is_empty());
+}
+
+#[test]
+fn test_no_dispatch_no_warning() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        0x01 0x02 add
+        end:
+            stop
+    }
+    "#;
+
+    let contract = parse(source);
+    assert!(Codegen::analyze(&contract).is_empty());
+}
+
+#[test]
+fn test_insert_fallthrough_revert() {
+    let mut contract = parse(FALLTHROUGH);
+    assert!(insert_fallthrough_revert(&mut contract, "MAIN"));
+    assert!(Codegen::analyze(&contract).is_empty());
+    let statements = contract.macros[0].statements.clone();
+    assert_eq!(statements[10].ty, StatementType::Opcode(Opcode::Dup1));
+    assert_eq!(statements[11].ty, StatementType::Opcode(Opcode::Revert));
+    assert!(matches!(statements[12].ty, StatementType::Label(_)));
+
+    // The dispatch now reverts, so the revert isn't inserted twice
+    assert!(!insert_fallthrough_revert(&mut contract, "MAIN"));
+    assert_eq!(contract.macros[0].statements.len(), statements.len());
+    assert!(!insert_fallthrough_revert(&mut contract, "MISSING"));
+}
+
+#[test]
+fn test_auto_revert() {
+    let legacy = compile(FALLTHROUGH, false);
+    assert_eq!(legacy, "60003560e01c8063a9059cbb14610011575b60006000f3");
+
+    let reverting = "60003560e01c8063a9059cbb1461001557600080fd5b60006000f3";
+    assert_eq!(compile(FALLTHROUGH, true), reverting);
+
+    // The pragma enables it for the contract, without the compiler option
+    let source =
+        FALLTHROUGH.replace("#define macro MAIN", "#pragma auto-revert\n    #define macro MAIN");
+    assert_eq!(compile(&source, false), reverting);
+
+    // Dispatches already reverting are left as they are
+    let source = FALLTHROUGH.replace("jumpi\n", "jumpi\n        0x00 0x00 revert\n");
+    assert_eq!(compile(&source, true), compile(&source, false));
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use huff_utils::prelude::*;
+use std::sync::Arc;
+
+fn execute(
+    sources: &[(&str, &str)],
+    edition: Edition,
+) -> Result<Vec<Arc<Artifact>>, Arc<CompilerError<'static>>> {
+    let mut compiler = common::compiler(sources);
+    compiler.edition = edition;
+    compiler.execute()
+}
+
+fn unsupported(err: &CompilerError) -> Option<Feature> {
+    match err {
+        CompilerError::ParserError(e) => match e.kind {
+            ParserErrorKind::UnsupportedFeature(feature) => Some(feature),
+            _ => None,
+        },
+        CompilerError::FailedCompiles(errors) => errors.iter().find_map(unsupported),
+        _ => None,
+    }
+}
+
+const LEGACY: &str = r#"
+#define macro MAIN() = takes(0) returns(0) {
+    stop jump
+    stop:
+        0x00 dup1 return
+}
+"#;
+
+#[test]
+fn test_compiler_edition() {
+    // As before the opcode name checks, references to the label are compiled as the opcode
+    let artifacts = execute(&[("main.huff", LEGACY)], Edition::E2022).unwrap();
+    assert_eq!(artifacts[0].runtime, "00565b600080f3");
+    assert_eq!(artifacts[0].metadata.as_ref().unwrap().settings.edition, "2022");
+
+    assert!(execute(&[("main.huff", LEGACY)], Edition::E2024).is_err());
+}
+
+#[test]
+fn test_edition_pragma_applies_to_its_file() {
+    // Legacy files can be included by files of the latest edition, and the other way around
+    let legacy = format!("#pragma edition \"2022\"\n{LEGACY}");
+    let main = r#"
+    #include "./legacy.huff"
+    #define constant ONE = -0x01
+    "#;
+    let sources = [("main.huff", main), ("legacy.huff", legacy.as_str())];
+    assert!(execute(&sources, Edition::E2024).is_ok());
+
+    let sources = [("main.huff", main), ("legacy.huff", LEGACY)];
+    assert!(execute(&sources, Edition::E2022).is_err());
+    let err =
+        execute(&[("main.huff", main), ("legacy.huff", &legacy)], Edition::E2022).unwrap_err();
+    assert_eq!(unsupported(&err), Some(Feature::NegativeLiterals));
+}
@@ -0,0 +1,190 @@
+mod common;
+
+use ethers_core::utils::hex;
+use huff_utils::prelude::*;
+use std::sync::Arc;
+
+fn compile(source: &str, eof: bool) -> Result<Vec<Arc<Artifact>>, Arc<CompilerError<'static>>> {
+    let mut compiler = common::compiler(&[("main.huff", source)]);
+    compiler.eof = eof;
+    compiler.execute()
+}
+
+fn invalid_container(e: &CompilerError) -> Option<String> {
+    match e {
+        CompilerError::FailedCompiles(errors) => errors.iter().find_map(invalid_container),
+        CompilerError::CodegenError(CodegenError {
+            kind: CodegenErrorKind::InvalidEofContainer(reason),
+            ..
+        }) => Some(reason.clone()),
+        _ => None,
+    }
+}
+
+const TABLE: &str = r#"
+    #define table CODE {
+        0xdeadbeef
+    }
+
+    #define macro MAIN() = takes (0) returns (0) {
+        __tablesize(CODE) __tablestart(CODE) 0x00 codecopy
+        __tablesize(CODE) 0x00 return
+    }
+"#;
+
+#[test]
+fn test_eof_containers() {
+    let legacy = compile(TABLE, false).unwrap().remove(0);
+    assert_eq!(legacy.runtime, "600461000d60003960046000f3deadbeef");
+
+    // The table follows the header and the 13 bytes of code
+    let artifact = compile(TABLE, true).unwrap().remove(0);
+    let runtime = EofContainer::decode(&hex::decode(&artifact.runtime).unwrap()).unwrap();
+    assert_eq!(runtime.code, hex::decode("600461001760003960046000f3").unwrap());
+    assert_eq!(runtime.data, vec![0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(artifact.runtime, "ef000101000d02000400600461001760003960046000f3deadbeef");
+
+    // The creation code returns the runtime container, which is its data
+    let creation = EofContainer::decode(&hex::decode(&artifact.bytecode).unwrap()).unwrap();
+    assert_eq!(creation.code, hex::decode("61001b806100153d393df3").unwrap());
+    assert_eq!(creation.data, hex::decode(&artifact.runtime).unwrap());
+    assert!(artifact.metadata.as_ref().unwrap().settings.eof);
+}
+
+#[test]
+fn test_eof_without_tables() {
+    let source = r#"
+        #define macro CONSTRUCTOR() = takes (0) returns (0) {
+            0x01 0x00 sstore
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x00 dup1 return
+        }
+    "#;
+    let artifact = compile(source, true).unwrap().remove(0);
+    assert_eq!(artifact.runtime, "ef000101000400600080f3");
+    assert_eq!(
+        artifact.bytecode,
+        format!("ef000101001002000b00600160005561000b8061001a3d393df3{}", artifact.runtime)
+    );
+}
+
+#[test]
+fn test_eof_unsupported() {
+    let sources = [
+        (
+            "#define macro MAIN() = takes (0) returns (0) { __VERBATIM(0x0c) }",
+            "runtime container: undefined instruction 0x0c at offset 0x0",
+        ),
+        (
+            "#define macro MAIN() = takes (0) returns (0) { __VERBATIM(0x61ff) }",
+            "runtime container: truncated push2 at offset 0x0",
+        ),
+        (
+            r#"
+            #define macro CONSTRUCTOR() = takes (0) returns (0) { 0x00 dup1 return }
+            #define macro MAIN() = takes (0) returns (0) { stop }
+            "#,
+            "constructors returning their own runtime code are unsupported",
+        ),
+        (
+            r#"
+            #define table CODE { 0xdeadbeef }
+            #define macro CONSTRUCTOR() = takes (0) returns (0) { __tablestart(CODE) pop }
+            #define macro MAIN() = takes (0) returns (0) { stop }
+            "#,
+            "tables used by the constructor are unsupported",
+        ),
+    ];
+    for (source, reason) in sources {
+        let e = compile(source, true).unwrap_err();
+        assert_eq!(invalid_container(&e).as_deref(), Some(reason), "{source}");
+    }
+}
+
+#[test]
+fn test_eof_relative_jumps() {
+    let source = r#"
+        #define macro MAIN() = takes (0) returns (0) {
+            0x00 calldataload skip jumpi
+            0x01 0x00 sstore
+            skip:
+                stop
+        }
+    "#;
+    let legacy = compile(source, false).unwrap().remove(0);
+    assert_eq!(legacy.runtime, "60003561000c5760016000555b00");
+
+    // The `push2` of the label is folded into an `rjumpi` relative to the end of its immediate
+    let artifact = compile(source, true).unwrap().remove(0);
+    let runtime = EofContainer::decode(&hex::decode(&artifact.runtime).unwrap()).unwrap();
+    assert_eq!(runtime.code, hex::decode("600035e1000560016000555b00").unwrap());
+    assert!(runtime.functions.is_empty());
+}
+
+#[test]
+fn test_eof_functions() {
+    let source = r#"
+        #define fn ADD_ONE() = takes (1) returns (1) {
+            0x01 add
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x00 calldataload ADD_ONE() ADD_ONE()
+            0x00 mstore 0x20 0x00 return
+        }
+    "#;
+    let artifact = compile(source, true).unwrap().remove(0);
+    let runtime = EofContainer::decode(&hex::decode(&artifact.runtime).unwrap()).unwrap();
+    assert_eq!(runtime.code, hex::decode("600035e30001e3000160005260206000f3").unwrap());
+    assert_eq!(
+        runtime.functions,
+        vec![EofFunction { inputs: 1, outputs: 1, code: hex::decode("600101e4").unwrap() }]
+    );
+    assert!(runtime.validate().is_ok());
+}
+
+#[test]
+fn test_eof_function_jumps() {
+    // Jumps within a function are relative to its own code section
+    let source = r#"
+        #define fn CLAMP() = takes (1) returns (1) {
+            dup1 0xff lt done jumpi
+            pop 0xff
+            done:
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x00 calldataload CLAMP() 0x00 mstore 0x20 0x00 return
+        }
+    "#;
+    let artifact = compile(source, true).unwrap().remove(0);
+    let runtime = EofContainer::decode(&hex::decode(&artifact.runtime).unwrap()).unwrap();
+    assert_eq!(runtime.functions[0].code, hex::decode("8060ff10e100035060ff5be4").unwrap());
+    assert!(runtime.validate().is_ok());
+
+    // Functions can't jump to labels of the code calling them
+    let source = r#"
+        #define fn ESCAPE() = takes (0) returns (0) {
+            done jump
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            ESCAPE()
+            done:
+                stop
+        }
+    "#;
+    let e = compile(source, true).unwrap_err();
+    assert!(matches!(
+        &*e,
+        CompilerError::FailedCompiles(errors) if matches!(
+            errors.first(),
+            Some(CompilerError::CodegenError(CodegenError {
+                kind: CodegenErrorKind::UnmatchedJumpLabel,
+                ..
+            }))
+        )
+    ));
+}
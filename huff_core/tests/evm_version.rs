@@ -0,0 +1,162 @@
+use huff_codegen::*;
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+use std::str::FromStr;
+
+#[test]
+fn test_cancun_opcodes() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 tload 0x00 tstore
+        0x20 0x00 0x00 mcopy
+        0x00 blobhash blobbasefee
+    }
+    "#;
+
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    let evm_version = EVMVersion::new(SupportedEVMVersions::Cancun);
+    match Codegen::generate_main_bytecode(&evm_version, &contract, None) {
+        Ok(mb) => assert_eq!(mb, "60005c60005d6020600060005e6000494a".to_string()),
+        Err(_) => panic!("moose"),
+    }
+}
+
+#[test]
+fn test_cancun_opcodes_unavailable_before_cancun() {
+    let source = r#"
+    #define macro TRANSIENT() = takes(0) returns(0) {
+        0x00 tload
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        TRANSIENT()
+    }
+    "#;
+
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    let evm_version = EVMVersion::from_str("shanghai").unwrap();
+    match Codegen::generate_main_bytecode(&evm_version, &contract, None) {
+        Err(CodegenError { kind: CodegenErrorKind::UnsupportedOpcode(op, version), .. }) => {
+            assert_eq!(op, Opcode::TLoad);
+            assert_eq!(version, evm_version);
+        }
+        _ => panic!("moose"),
+    }
+}
+
+#[test]
+fn test_opcode_macro_arg_unavailable() {
+    let source = r#"
+    #define macro APPLY(op) = takes(0) returns(0) {
+        0x00 <op>
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        APPLY(blobhash)
+    }
+    "#;
+
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    let evm_version = EVMVersion::new(SupportedEVMVersions::Paris);
+    match Codegen::generate_main_bytecode(&evm_version, &contract, None) {
+        Err(CodegenError { kind: CodegenErrorKind::UnsupportedOpcode(op, _), .. }) => {
+            assert_eq!(op, Opcode::Blobhash)
+        }
+        _ => panic!("moose"),
+    }
+}
+
+#[test]
+fn test_parse_evm_version() {
+    assert_eq!(EVMVersion::default().version(), SupportedEVMVersions::Cancun);
+    assert_eq!(
+        EVMVersion::from_str("Paris").unwrap(),
+        EVMVersion::new(SupportedEVMVersions::Paris)
+    );
+    assert!(EVMVersion::from_str("frontier2").is_err());
+}
+
+#[test]
+fn test_opcode_availability_per_fork() {
+    let cases = [
+        ("push0", SupportedEVMVersions::Paris, SupportedEVMVersions::Shanghai),
+        ("basefee", SupportedEVMVersions::Berlin, SupportedEVMVersions::London),
+        ("chainid", SupportedEVMVersions::Petersburg, SupportedEVMVersions::Istanbul),
+        ("0x01 0x01 shl", SupportedEVMVersions::Byzantium, SupportedEVMVersions::Constantinople),
+        ("returndatasize", SupportedEVMVersions::Homestead, SupportedEVMVersions::Byzantium),
+    ];
+
+    for (body, target, required) in cases {
+        let source = &format!(
+            r#"
+            #define macro MAIN() = takes(0) returns(0) {{
+                {body}
+            }}
+            "#
+        );
+
+        let full_source = FullFileSource { source, file: None, spans: vec![] };
+        let lexer = Lexer::new(full_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, Some("".to_string()));
+        let mut contract = parser.parse().unwrap();
+        contract.derive_storage_pointers();
+
+        // Unavailable in the fork preceding its introduction
+        match Codegen::generate_main_bytecode(&EVMVersion::new(target), &contract, None) {
+            Err(CodegenError {
+                kind: CodegenErrorKind::UnsupportedOpcode(op, version), ..
+            }) => {
+                assert_eq!(op.introduced_in(), required);
+                assert_eq!(version.version(), target);
+            }
+            _ => panic!("moose"),
+        }
+
+        // Available from the fork it was introduced in
+        assert!(
+            Codegen::generate_main_bytecode(&EVMVersion::new(required), &contract, None).is_ok()
+        );
+    }
+}
+
+#[test]
+fn test_push0() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        push0 push0 return
+    }
+    "#;
+
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    let evm_version = EVMVersion::new(SupportedEVMVersions::Shanghai);
+    match Codegen::generate_main_bytecode(&evm_version, &contract, None) {
+        Ok(mb) => assert_eq!(mb, "5f5ff3".to_string()),
+        Err(_) => panic!("moose"),
+    }
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use huff_core::fix::{Fix, Migration};
+use huff_utils::prelude::*;
+use std::sync::Arc;
+
+fn compile(source: &str, edition: Edition) -> Arc<Artifact> {
+    let mut compiler = common::compiler(&[("main.huff", source)]);
+    compiler.edition = edition;
+    compiler.execute().unwrap().remove(0)
+}
+
+fn fix(source: &str) -> Fix {
+    Fix::new("main.huff", source, &EVMVersion::default()).unwrap()
+}
+
+const LEGACY: &str = r#"#pragma edition "2022"
+
+// The owner
+#define constant caller = 0x01
+
+#define jumptable__packed TARGETS() = {
+    stop
+}
+
+#define macro REQUIRE(address) = takes(1) returns(0) {
+    0x01 eq stop jumpi
+    0x00 dup1 revert
+    stop:
+}
+
+#define macro MAIN() = takes(0) returns(0) {
+    difficulty pop
+    0x02 REQUIRE(0x02)
+    __tablestart(TARGETS) pop
+}
+"#;
+
+#[test]
+fn test_fix_keeps_bytecode() {
+    let fix = fix(LEGACY);
+    assert_eq!(
+        fix.migrations(),
+        vec![
+            Migration::DeprecatedOpcodes,
+            Migration::TableSyntax,
+            Migration::OpcodeNames,
+            Migration::Edition
+        ]
+    );
+    assert_eq!(
+        fix.fixed,
+        r#"#pragma edition "2024"
+
+// The owner
+#define constant CALLER = 0x01
+
+#define jumptable__packed TARGETS {
+    stop_
+}
+
+#define macro REQUIRE(address_) = takes(1) returns(0) {
+    0x01 eq stop jumpi
+    0x00 dup1 revert
+    stop_:
+}
+
+#define macro MAIN() = takes(0) returns(0) {
+    prevrandao pop
+    0x02 REQUIRE(0x02)
+    __tablestart(TARGETS) pop
+}
+"#
+    );
+
+    let (legacy, fixed) = (compile(LEGACY, Edition::E2024), compile(&fix.fixed, Edition::E2024));
+    assert_eq!(legacy.bytecode, fixed.bytecode);
+
+    // Fixing is idempotent
+    assert!(self::fix(&fix.fixed).is_empty());
+}
+
+#[test]
+fn test_fix_targets_evm_version() {
+    let source = "#define macro MAIN() = takes(0) returns(0) { difficulty }";
+    let london = Fix::new("main.huff", source, &"london".parse().unwrap()).unwrap();
+    assert!(london.is_empty());
+    assert_eq!(fix(source).fixed, "#define macro MAIN() = takes(0) returns(0) { prevrandao }");
+}
+
+#[test]
+fn test_fix_diff() {
+    let source = "#pragma edition \"2022\"\n\n#define table T() = {\n    0x01\n}\n";
+    assert_eq!(
+        fix(source).diff(),
+        r#"--- main.huff
++++ main.huff
+@@ -1 +1 @@
+-#pragma edition "2022"
++#pragma edition "2024"
+@@ -3 +3 @@
+-#define table T() = {
++#define table T {
+"#
+    );
+}
@@ -103,3 +103,41 @@ fn test_missing_constructor_with_inputs() {
         }
     }
 }
+
+#[test]
+fn test_method_identifiers() {
+    let source = r#"
+    #define function transfer(address, uint256) nonpayable returns (bool)
+    #define function balanceOf(address) view returns (uint256)
+    #define function constructor(address, uint256) nonpayable returns ()
+
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {}
+    #define macro MAIN() = takes(0) returns (0) {
+        0x00 calldataload 0xE0 shr
+        __FUNC_SIG(transfer) eq transfer jumpi
+        0x00 dup1 revert
+        transfer:
+            stop
+    }
+    "#;
+
+    let full_source = FileSource {
+        source: Some(source.to_string()),
+        id: uuid::Uuid::new_v4(),
+        path: "".to_string(),
+        access: None,
+        dependencies: None,
+    };
+    let compiler = Compiler::new(Arc::new(vec![]), None, None, None, None, None, false, false);
+    let artifact = compiler.gen_artifact(Arc::new(full_source)).unwrap();
+
+    assert_eq!(artifact.method_identifiers.len(), 2);
+    assert_eq!(artifact.method_identifiers["transfer(address,uint256)"], "a9059cbb");
+    assert_eq!(artifact.method_identifiers["balanceOf(address)"], "70a08231");
+    assert!(artifact.runtime.contains("63a9059cbb"));
+    assert_eq!(artifact.constructor_inputs, vec!["address".to_string(), "uint256".to_string()]);
+
+    let json = serde_json::to_value(&artifact).unwrap();
+    assert_eq!(json["methodIdentifiers"]["balanceOf(address)"], "70a08231");
+    assert_eq!(json["constructorInputs"][0], "address");
+}
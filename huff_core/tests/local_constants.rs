@@ -0,0 +1,71 @@
+use huff_codegen::*;
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn compile_main(source: &str) -> Result<String, CodegenError> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut contract = Parser::new(tokens, None).parse().unwrap();
+    contract.derive_storage_pointers();
+    Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None)
+}
+
+#[test]
+fn test_local_constants_shadow_global_constants() {
+    let source: &str = r#"
+        #define constant VALUE = 0x01
+        #define constant SLOT = FREE_STORAGE_POINTER()
+
+        #define macro TAKES_ARG(arg) = takes(0) returns(0) {
+            <arg>
+        }
+
+        #define macro GLOBAL() = takes(0) returns(0) {
+            [VALUE]
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            #define constant VALUE = 0x02
+            #define constant SLOT = 0x05
+            [VALUE] GLOBAL() TAKES_ARG(VALUE) [SLOT]
+        }
+    "#;
+
+    // Local constants are used within the macro declaring them, including as macro arguments,
+    // and the global constants everywhere else
+    assert_eq!(compile_main(source).unwrap(), "6002600160026005");
+}
+
+#[test]
+fn test_local_constants_only_resolvable_within_their_macro() {
+    let source: &str = r#"
+        #define macro OTHER() = takes(0) returns(0) {
+            [LOCAL]
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            #define constant LOCAL = 0x01
+            [LOCAL] OTHER()
+        }
+    "#;
+
+    let err = compile_main(source).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::MissingConstantDefinition("LOCAL".to_string()));
+}
+
+#[test]
+fn test_local_constants_in_switch_cases() {
+    let source: &str = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            #define constant ONE = 0x01
+            0x01 switch table {
+                case [ONE] { 0x0a }
+                default { 0x0b }
+            }
+            stop
+        }
+    "#;
+
+    assert!(compile_main(source).is_ok());
+}
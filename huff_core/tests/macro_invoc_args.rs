@@ -26,7 +26,8 @@ fn test_opcode_macro_args() {
     contract.derive_storage_pointers();
 
     // Create main and constructor bytecode
-    let main_bytecode = Codegen::generate_main_bytecode(&contract, None).unwrap();
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
 
     // Full expected bytecode output (generated from huffc) (placed here as a reference)
     let expected_bytecode = "60088060093d393df360ff3d5260203df3";
@@ -62,7 +63,8 @@ fn test_all_opcodes_in_macro_args() {
         contract.derive_storage_pointers();
 
         // Create main and constructor bytecode
-        let main_bytecode = Codegen::generate_main_bytecode(&contract, None).unwrap();
+        let main_bytecode =
+            Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
 
         // Full expected bytecode output (generated from huffc) (placed here as a reference)
         let expected_bytecode = format!("60088060093d393df360ff{}", Opcode::from_str(o).unwrap());
@@ -98,7 +100,8 @@ fn test_constant_macro_arg() {
     contract.derive_storage_pointers();
 
     // Create main and constructor bytecode
-    let main_bytecode = Codegen::generate_main_bytecode(&contract, None).unwrap();
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
 
     // Full expected bytecode output (generated from huffc) (placed here as a reference)
     let expected_bytecode = "60088060093d393df360ff6002";
@@ -136,7 +139,8 @@ fn test_bubbled_label_call_macro_arg() {
     contract.derive_storage_pointers();
 
     // Create main and constructor bytecode
-    let main_bytecode = Codegen::generate_main_bytecode(&contract, None).unwrap();
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
 
     // Full expected bytecode output (generated from huffc) (placed here as a reference)
     let expected_bytecode = "60088060093d393df360ff5b610000";
@@ -173,7 +177,8 @@ fn test_bubbled_literal_macro_arg() {
     contract.derive_storage_pointers();
 
     // Create main and constructor bytecode
-    let main_bytecode = Codegen::generate_main_bytecode(&contract, None).unwrap();
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
 
     // Full expected bytecode output (generated from huffc) (placed here as a reference)
     let expected_bytecode = "60088060093d393df360ff610420";
@@ -210,7 +215,8 @@ fn test_bubbled_opcode_macro_arg() {
     contract.derive_storage_pointers();
 
     // Create main and constructor bytecode
-    let main_bytecode = Codegen::generate_main_bytecode(&contract, None).unwrap();
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
 
     // Full expected bytecode output (generated from huffc) (placed here as a reference)
     let expected_bytecode = "60088060093d393df360ff3d";
@@ -249,7 +255,8 @@ fn test_bubbled_constant_macro_arg() {
     contract.derive_storage_pointers();
 
     // Create main and constructor bytecode
-    let main_bytecode = Codegen::generate_main_bytecode(&contract, None).unwrap();
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
 
     // Full expected bytecode output (generated from huffc) (placed here as a reference)
     let expected_bytecode = "60088060093d393df360ff6002";
@@ -260,3 +267,228 @@ fn test_bubbled_constant_macro_arg() {
     // Check the bytecode
     assert_eq!(bytecode.to_lowercase(), expected_bytecode.to_lowercase());
 }
+
+fn try_compile_main(source: &str) -> Result<String, CodegenError> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut contract = Parser::new(tokens, None).parse().unwrap();
+    contract.derive_storage_pointers();
+    Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None)
+}
+
+fn compile_main(source: &str) -> String {
+    try_compile_main(source).unwrap()
+}
+
+#[test]
+fn test_label_macro_arg_jumps_to_caller_label() {
+    let source = r#"
+        #define macro REQUIRE(err) = takes(1) returns(0) {
+            <err> jumpi
+            err:
+                0x02
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x01 REQUIRE(err)
+            err:
+                0x00
+        }
+    "#;
+
+    // The jump skips the label of the same name defined by REQUIRE
+    // 6001 610009 57 5b 6002 5b 6000
+    assert_eq!(compile_main(source), "6001610009575b60025b6000");
+
+    // Also when the caller's label is defined before the invocation
+    let source = r#"
+        #define macro REQUIRE(err) = takes(1) returns(0) {
+            <err> jumpi
+            err:
+                0x02
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            err:
+                0x00
+            0x01 REQUIRE(err)
+        }
+    "#;
+    // 5b 6000 6001 610000 57 5b 6002
+    assert_eq!(compile_main(source), "5b60006001610000575b6002");
+}
+
+#[test]
+fn test_label_macro_arg_passed_through_multiple_macros() {
+    let source = r#"
+        #define macro REQUIRE(err) = takes(1) returns(0) {
+            <err> jumpi
+            err:
+                0x03
+        }
+
+        #define macro CHECK(err) = takes(1) returns(0) {
+            REQUIRE(<err>)
+            err:
+                0x02
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x01 CHECK(fail)
+            0x01 CHECK(done)
+            fail:
+                0x00
+            done:
+                0x01
+        }
+    "#;
+
+    // 6001 610018 57 5b 6003 5b 6002 | 6001 61001b 57 5b 6003 5b 6002 | 5b 6000 5b 6001
+    assert_eq!(
+        compile_main(source),
+        "6001610018575b60035b6002600161001b575b60035b60025b60005b6001"
+    );
+}
+
+#[test]
+fn test_bracketed_constant_macro_arg() {
+    let source = r#"
+        #define constant VALUE = 0x2a
+
+        #define macro PUSH_ARG(value) = takes(0) returns(1) {
+            <value>
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            PUSH_ARG([VALUE])
+        }
+    "#;
+    assert_eq!(compile_main(source), "602a");
+}
+
+#[test]
+fn test_arg_call_passed_under_another_name() {
+    let source = r#"
+        #define macro INNER(b) = takes(0) returns(1) {
+            <b>
+        }
+
+        #define macro OUTER(a) = takes(0) returns(1) {
+            INNER(<a>)
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            OUTER(0x42)
+        }
+    "#;
+    assert_eq!(compile_main(source), "6042");
+}
+
+#[test]
+fn test_macro_invocation_macro_arg() {
+    let source = r#"
+        #define constant VALUE = 0x2a
+
+        #define macro PUSH_TWO(x, y) = takes(0) returns(2) {
+            <x> <y>
+        }
+
+        #define macro APPLY(f) = takes(0) returns(0) {
+            0x01 <f> pop
+        }
+
+        #define macro FORWARD(v) = takes(0) returns(0) {
+            APPLY(PUSH_TWO(<v>, VALUE))
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            FORWARD(0x07)
+        }
+    "#;
+
+    // The arg call passed to PUSH_TWO resolves in FORWARD, where the invocation was written
+    // 6001 | 6007 602a | 50
+    assert_eq!(compile_main(source), "60016007602a50");
+}
+
+#[test]
+fn test_macro_invocation_macro_arg_expanded_at_each_use() {
+    let source = r#"
+        #define macro PUSH_TWO(x, y) = takes(0) returns(2) {
+            <x> <y>
+        }
+
+        #define macro APPLY(f) = takes(0) returns(0) {
+            0x01 <f> pop
+        }
+
+        #define macro TWICE(g) = takes(0) returns(0) {
+            <g> <g>
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            TWICE(APPLY(PUSH_TWO(0x03, 0x04)))
+        }
+    "#;
+
+    // 6001 6003 6004 50 | 6001 6003 6004 50
+    assert_eq!(compile_main(source), "6001600360045060016003600450");
+}
+
+#[test]
+fn test_too_few_macro_args() {
+    let source = r#"
+        #define macro PUSH_TWO(x, y) = takes(0) returns(2) {
+            <x> <y>
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            PUSH_TWO(0x01)
+        }
+    "#;
+
+    let err = try_compile_main(source).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::ArgumentCountMismatch("PUSH_TWO".to_string(), 2, 1));
+    let start = source.find("PUSH_TWO(0x01)").unwrap();
+    assert_eq!(err.span.0[0].start, start);
+}
+
+#[test]
+fn test_too_many_macro_args() {
+    let source = r#"
+        #define macro PUSH_ONE(x) = takes(0) returns(1) {
+            <x>
+        }
+
+        #define macro APPLY(f) = takes(0) returns(0) {
+            <f>
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            APPLY(PUSH_ONE(0x01, 0x02))
+        }
+    "#;
+
+    // Macro invocations passed as arguments are checked where they're expanded
+    let err = try_compile_main(source).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::ArgumentCountMismatch("PUSH_ONE".to_string(), 1, 2));
+}
+
+#[test]
+fn test_opcode_macro_arg_after_macro_invocation_arg() {
+    let source = r#"
+        #define macro PUSH_ONE(x) = takes(0) returns(1) {
+            <x>
+        }
+
+        #define macro APPLY_TWICE(f, op) = takes(0) returns(1) {
+            <f> <f> <op>
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            APPLY_TWICE(PUSH_ONE(0x01), add)
+        }
+    "#;
+    assert_eq!(compile_main(source), "6001600101");
+}
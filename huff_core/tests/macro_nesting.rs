@@ -0,0 +1,73 @@
+use huff_codegen::*;
+use huff_utils::prelude::*;
+
+/// Builds a contract where `MAIN` invokes a chain of `depth` macros, each invoking the next and
+/// passing `value` along as an argument call. The innermost macro pushes the value.
+fn nested_contract(depth: usize) -> Contract {
+    let mut builder = ContractBuilder::new();
+    for i in 0..depth {
+        let nested = if i + 1 == depth {
+            MacroDefinitionBuilder::new(format!("NESTED_{i}")).arg_call("value")
+        } else {
+            MacroDefinitionBuilder::new(format!("NESTED_{i}"))
+                .invoke(format!("NESTED_{}", i + 1), vec![MacroArg::ArgCall("value".to_string())])
+        };
+        builder = builder.macro_definition(nested.parameter("value").returns(1).build());
+    }
+    builder
+        .macro_definition(
+            MacroDefinitionBuilder::new("MAIN")
+                .invoke("NESTED_0", vec![MacroArg::Literal(str_to_bytes32("2a"))])
+                .build(),
+        )
+        .build()
+}
+
+#[test]
+fn test_deeply_nested_macros() {
+    let contract = nested_contract(5000);
+
+    let main_bytecode =
+        Codegen::generate_main_bytecode_with_limit(&EVMVersion::default(), &contract, None, 8192)
+            .unwrap();
+    assert_eq!(main_bytecode, "602a");
+}
+
+#[test]
+fn test_macro_nesting_too_deep() {
+    let contract = nested_contract(DEFAULT_MACRO_NESTING_LIMIT);
+
+    // MAIN itself counts towards the limit
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Err(CodegenError { kind: CodegenErrorKind::MacroNestingTooDeep(limit, name), .. }) => {
+            assert_eq!(limit, DEFAULT_MACRO_NESTING_LIMIT);
+            assert_eq!(name, format!("NESTED_{}", DEFAULT_MACRO_NESTING_LIMIT - 1));
+        }
+        res => panic!("Expected a MacroNestingTooDeep error, got {res:?}"),
+    }
+
+    let main_bytecode = Codegen::generate_main_bytecode_with_limit(
+        &EVMVersion::default(),
+        &contract,
+        None,
+        DEFAULT_MACRO_NESTING_LIMIT + 1,
+    )
+    .unwrap();
+    assert_eq!(main_bytecode, "602a");
+}
+
+#[test]
+fn test_self_invoking_macro() {
+    let contract = ContractBuilder::new()
+        .macro_definition(MacroDefinitionBuilder::new("LOOP").invoke("LOOP", vec![]).build())
+        .macro_definition(MacroDefinitionBuilder::new("MAIN").invoke("LOOP", vec![]).build())
+        .build();
+
+    match Codegen::generate_main_bytecode_with_limit(&EVMVersion::default(), &contract, None, 64) {
+        Err(CodegenError { kind: CodegenErrorKind::MacroNestingTooDeep(limit, name), .. }) => {
+            assert_eq!(limit, 64);
+            assert_eq!(name, "LOOP");
+        }
+        res => panic!("Expected a MacroNestingTooDeep error, got {res:?}"),
+    }
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use common::parse;
+use huff_codegen::*;
+use huff_utils::prelude::*;
+
+/// Compiles `MAIN`, which invokes macros repeatedly, and `INLINED`, which contains the same code
+/// written out by hand, asserting that both generate the same bytecode.
+fn assert_same_as_inlined(source: &str) {
+    let contract = parse(source);
+    let evm_version = EVMVersion::default();
+    let main = Codegen::generate_main_bytecode(&evm_version, &contract, None).unwrap();
+    let inlined =
+        Codegen::generate_main_bytecode(&evm_version, &contract, Some("INLINED".to_string()))
+            .unwrap();
+    assert_eq!(main, inlined);
+}
+
+#[test]
+fn test_reused_macro_with_jumps() {
+    let source = r#"
+    #define constant VALUE = 0x2a
+
+    #define macro CHECK() = takes(1) returns(0) {
+        [VALUE] eq done jumpi
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 CHECK()
+        0x01 CHECK()
+        0x02 0x03 pop CHECK()
+        done:
+            stop
+    }
+
+    #define macro INLINED() = takes(0) returns(0) {
+        0x00 [VALUE] eq done jumpi
+        0x01 [VALUE] eq done jumpi
+        0x02 0x03 pop [VALUE] eq done jumpi
+        done:
+            stop
+    }
+    "#;
+    assert_same_as_inlined(source);
+}
+
+#[test]
+fn test_reused_macro_with_literal_args() {
+    let source = r#"
+    #define macro ADD_TO(value) = takes(1) returns(1) {
+        <value> add
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 ADD_TO(0x01) ADD_TO(0x0200) ADD_TO(0x01) pop
+    }
+
+    #define macro INLINED() = takes(0) returns(0) {
+        0x00 0x01 add 0x0200 add 0x01 add pop
+    }
+    "#;
+    assert_same_as_inlined(source);
+}
+
+#[test]
+fn test_reused_nested_macros_with_tables() {
+    let source = r#"
+    #define jumptable TABLE {
+        first second
+    }
+
+    #define macro LOAD_TABLE() = takes(0) returns(0) {
+        __tablesize(TABLE) __tablestart(TABLE) 0x00 codecopy
+    }
+
+    #define macro OUTER() = takes(0) returns(0) {
+        LOAD_TABLE() first jump LOAD_TABLE()
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        OUTER() OUTER()
+        first:
+            0x01
+        second:
+            stop
+    }
+
+    // Tables are appended for every invocation using them, so distinct copies of LOAD_TABLE are
+    // invoked to generate the same bytecode without reusing any expansion
+    #define macro LOAD_TABLE_0() = takes(0) returns(0) {
+        __tablesize(TABLE) __tablestart(TABLE) 0x00 codecopy
+    }
+    #define macro LOAD_TABLE_1() = takes(0) returns(0) {
+        __tablesize(TABLE) __tablestart(TABLE) 0x00 codecopy
+    }
+    #define macro LOAD_TABLE_2() = takes(0) returns(0) {
+        __tablesize(TABLE) __tablestart(TABLE) 0x00 codecopy
+    }
+    #define macro LOAD_TABLE_3() = takes(0) returns(0) {
+        __tablesize(TABLE) __tablestart(TABLE) 0x00 codecopy
+    }
+
+    #define macro INLINED() = takes(0) returns(0) {
+        LOAD_TABLE_0() first jump LOAD_TABLE_1()
+        LOAD_TABLE_2() first jump LOAD_TABLE_3()
+        first:
+            0x01
+        second:
+            stop
+    }
+    "#;
+    assert_same_as_inlined(source);
+}
+
+#[test]
+fn test_macros_with_labels_are_expanded_each_time() {
+    let source = r#"
+    #define macro SKIP() = takes(0) returns(0) {
+        skip jump
+        0x00 0x00 revert
+        skip:
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        SKIP() SKIP()
+    }
+
+    #define macro INLINED() = takes(0) returns(0) {
+        skip_one jump
+        0x00 0x00 revert
+        skip_one:
+        skip_two jump
+        0x00 0x00 revert
+        skip_two:
+    }
+    "#;
+    assert_same_as_inlined(source);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::artifact;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_artifact_reports_memory_layout() {
+    let artifact = artifact(
+        r#"
+        #define constant SCRATCH = FREE_MEMORY_POINTER(0x40)
+        #define constant RESULT = FREE_MEMORY_POINTER()
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x04 calldataload [SCRATCH] mstore
+            0x24 calldataload [SCRATCH] 0x20 add mstore
+            0x40 [SCRATCH] sha3 [RESULT] mstore
+            0x20 [RESULT] return
+        }
+    "#,
+    );
+
+    assert_eq!(
+        artifact.memory_layout,
+        vec![
+            MemorySlot { name: "SCRATCH".to_string(), offset: 0x00, size: 0x40 },
+            MemorySlot { name: "RESULT".to_string(), offset: 0x40, size: 0x20 },
+        ]
+    );
+    // SCRATCH is pushed as 0x00 and RESULT as 0x40
+    assert!(artifact.runtime.starts_with("600435600052"), "{}", artifact.runtime);
+    assert!(artifact.runtime.ends_with("604060002060405260206040f3"), "{}", artifact.runtime);
+
+    let json = serde_json::to_value(&*artifact).unwrap();
+    assert_eq!(json["memoryLayout"][1]["name"], "RESULT");
+    assert_eq!(json["memoryLayout"][1]["offset"], 0x40);
+}
+
+#[test]
+fn test_memory_layout_is_omitted_without_memory_pointers() {
+    let artifact = artifact("#define macro MAIN() = takes(0) returns(0) { 0x00 mload }");
+    assert!(artifact.memory_layout.is_empty());
+    assert!(serde_json::to_value(&*artifact).unwrap().get("memoryLayout").is_none());
+}
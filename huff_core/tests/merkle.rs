@@ -0,0 +1,80 @@
+mod common;
+
+use common::{compile, first_error};
+use huff_utils::prelude::*;
+
+/// The leaves `0x0101..01` to `0x0505..05`
+fn leaves(count: u8) -> String {
+    let leaves = (1..=count).map(|i| format!("\"0x{}\"", format!("{i:02x}").repeat(32)));
+    format!("[{}]", leaves.collect::<Vec<_>>().join(", "))
+}
+
+/// Returns whether the leaf at `0x00` is proven by the proof following it in calldata
+fn verifier(tree: &str, fixed: bool) -> String {
+    format!(
+        r#"
+        #define merkle {tree} = "leaves.json"
+
+        #define macro MAIN() = takes (0) returns (0) {{
+            [AIRDROP_ROOT] {} 0x00 calldataload AIRDROP_VERIFY()
+            0x00 mstore 0x20 0x00 return
+        }}
+    "#,
+        if fixed { "0x20" } else { "0x20 calldataload 0x40" }
+    )
+}
+
+#[test]
+fn test_merkle_verifiers() {
+    // The proof of a sha256 tree is hashed with the precompile, reverting if it fails
+    let main = verifier("AIRDROP(sha256)", false);
+    let artifacts = compile(&[("main.huff", &main), ("leaves.json", &leaves(5))]).unwrap();
+    assert_eq!(
+        artifacts[0].runtime,
+        "7fca16fd5ea02f6789de83d3c6e4a5a895eaa0f0b599391d40551e6a0089ea86a860203560406000359160051b810191610063565b600080fd5b813580821160051b9182529060201852602060006040600060025afa156100345760005190602001905b828210610039579150501460005260206000f3"
+    );
+
+    // Proofs of a fixed depth are unrolled
+    let main = verifier("AIRDROP(keccak256, 2)", true);
+    let artifacts = compile(&[("main.huff", &main), ("leaves.json", &leaves(4))]).unwrap();
+    let keccak = MerkleHash::Keccak256;
+    let l: Vec<[u8; 32]> = (1..=4).map(|i| [i; 32]).collect();
+    let root = keccak.hash_pair(&keccak.hash_pair(&l[3], &l[2]), &keccak.hash_pair(&l[1], &l[0]));
+    assert_eq!(
+        artifacts[0].runtime,
+        format!(
+            "7f{}6020600035813580821160051b918252906020185260406000208160200135\
+             80821160051b91825290602018526040600020905014{}",
+            bytes32_to_string(&root, false),
+            "60005260206000f3"
+        )
+    );
+}
+
+#[test]
+fn test_invalid_merkle_leaves() {
+    let main = verifier("AIRDROP(keccak256, 2)", true);
+    for (files, reason) in [
+        (vec![("main.huff", main.as_str())], "\"./leaves.json\" can't be read"),
+        (vec![("main.huff", &main), ("leaves.json", "[]")], "the tree has no leaves"),
+        (
+            vec![("main.huff", &main), ("leaves.json", &leaves(3))],
+            "a tree of depth 2 has 4 leaves, not 3",
+        ),
+    ] {
+        let e = compile(&files).unwrap_err();
+        match first_error(&e) {
+            CompilerError::InvalidMerkleLeaves(r, _) => assert_eq!(r, reason),
+            e => panic!("Expected invalid merkle leaves, got {e:?}"),
+        }
+    }
+
+    let main = format!("#define constant AIRDROP_ROOT = 0x01\n{main}");
+    let e = compile(&[("main.huff", &main), ("leaves.json", &leaves(4))]).unwrap_err();
+    match first_error(&e) {
+        CompilerError::InvalidMerkleLeaves(r, _) => {
+            assert_eq!(r, "the constant \"AIRDROP_ROOT\" is already defined")
+        }
+        e => panic!("Expected invalid merkle leaves, got {e:?}"),
+    }
+}
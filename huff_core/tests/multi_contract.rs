@@ -0,0 +1,197 @@
+mod common;
+
+use std::sync::Arc;
+
+use huff_core::{output::*, Compiler};
+use huff_utils::prelude::*;
+
+const FACTORY: &str = r#"
+    #define function deploy() nonpayable returns (address)
+    #define constant OWNER_SLOT = FREE_STORAGE_POINTER()
+
+    #define macro SET_OWNER() = takes(0) returns(0) {
+        caller [OWNER_SLOT] sstore
+    }
+
+    #define contract Child {
+        #define function value() view returns (uint256)
+        #define constant VALUE_SLOT = FREE_STORAGE_POINTER()
+
+        #define macro CONSTRUCTOR() = takes(0) returns(0) {
+            SET_OWNER()
+            0x2a [VALUE_SLOT] sstore
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [VALUE_SLOT] sload 0x00 mstore
+            0x20 0x00 return
+        }
+    }
+
+    #define contract Factory {
+        #define macro CONSTRUCTOR() = takes(0) returns(0) {
+            SET_OWNER()
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            __tablesize(Child) dup1 __tablestart(Child) 0x00 codecopy
+            0x00 0x00 create
+            0x00 mstore 0x20 0x00 return
+        }
+    }
+"#;
+
+fn compiler(source: &str) -> Compiler<'static> {
+    common::compiler(&[("./contracts/Factory.huff", source)])
+}
+
+fn find<'a>(artifacts: &'a [Arc<Artifact>], name: Option<&str>) -> &'a Artifact {
+    artifacts.iter().find(|a| a.contract_name.as_deref() == name).unwrap()
+}
+
+#[test]
+fn test_compiles_an_artifact_per_contract() {
+    let artifacts = compiler(FACTORY).execute().unwrap();
+
+    // There is no top level MAIN macro, so only the named contracts are compiled
+    assert_eq!(artifacts.len(), 2);
+    let child = find(&artifacts, Some("Child"));
+    let factory = find(&artifacts, Some("Factory"));
+    assert_eq!(child.identifier(), "contracts/Factory.huff:Child");
+    assert_eq!(factory.identifier(), "contracts/Factory.huff:Factory");
+
+    // Each contract has its own ABI, along with the top level definitions
+    let child_abi = child.abi.as_ref().unwrap();
+    assert!(child_abi.functions.contains_key("value"));
+    assert!(child_abi.functions.contains_key("deploy"));
+    let factory_abi = factory.abi.as_ref().unwrap();
+    assert!(!factory_abi.functions.contains_key("value"));
+    assert!(factory_abi.functions.contains_key("deploy"));
+
+    // The factory embeds the child's creation code after its runtime code
+    assert!(factory.runtime.contains(&child.bytecode));
+    assert!(!child.runtime.contains(&factory.bytecode));
+}
+
+#[test]
+fn test_derives_storage_pointers_per_contract() {
+    let artifacts = compiler(FACTORY).execute().unwrap();
+
+    // The shared OWNER_SLOT is the first pointer of both contracts, and the child's own
+    // VALUE_SLOT follows it
+    let child = find(&artifacts, Some("Child"));
+    assert!(child.bytecode.starts_with("33600055602a600155"), "{}", child.bytecode);
+    assert!(child.runtime.starts_with("600154"), "{}", child.runtime);
+    let factory = find(&artifacts, Some("Factory"));
+    assert!(factory.bytecode.starts_with("33600055"), "{}", factory.bytecode);
+}
+
+#[test]
+fn test_compiles_top_level_contract() {
+    let source = format!(
+        "{FACTORY}
+        #define macro MAIN() = takes(0) returns(0) {{
+            __tablesize(Factory) __tablestart(Factory)
+        }}"
+    );
+    let artifacts = compiler(&source).execute().unwrap();
+    assert_eq!(artifacts.len(), 3);
+
+    let top = find(&artifacts, None);
+    assert_eq!(top.identifier(), "contracts/Factory.huff");
+    assert!(top.runtime.contains(&find(&artifacts, Some("Factory")).bytecode));
+
+    // Only the top level artifact is compiled as the file's artifact
+    let file = Arc::new(FileSource {
+        path: "./contracts/Factory.huff".to_string(),
+        source: Some(source),
+        ..Default::default()
+    });
+    let artifact = compiler("").gen_artifact(file).unwrap();
+    assert_eq!(artifact.contract_name, None);
+    assert_eq!(artifact.runtime, top.runtime);
+}
+
+#[test]
+fn test_circular_contract_reference() {
+    let source = r#"
+        #define contract A {
+            #define macro MAIN() = takes(0) returns(0) { __tablestart(B) }
+        }
+        #define contract B {
+            #define macro MAIN() = takes(0) returns(0) { __tablesize(A) }
+        }
+    "#;
+    match compiler(source).execute() {
+        Err(e) => match &*e {
+            CompilerError::FailedCompiles(errors) => match &errors[0] {
+                CompilerError::CodegenError(ce) => assert_eq!(
+                    ce.kind,
+                    CodegenErrorKind::CircularContractReference(vec![
+                        "B".to_string(),
+                        "A".to_string(),
+                        "B".to_string()
+                    ])
+                ),
+                e => panic!("unexpected error: {e:?}"),
+            },
+            e => panic!("unexpected error: {e:?}"),
+        },
+        Ok(_) => panic!("expected a circular contract reference error"),
+    }
+}
+
+#[test]
+fn test_tables_shadow_contracts() {
+    let source = r#"
+        #define contract Child {
+            #define macro MAIN() = takes(0) returns(0) { 0x01 }
+        }
+        #define contract Factory {
+            #define table Child { 0xc0de }
+            #define macro MAIN() = takes(0) returns(0) { __tablesize(Child) }
+        }
+    "#;
+    let artifacts = compiler(source).execute().unwrap();
+    assert_eq!(find(&artifacts, Some("Factory")).runtime, "6002c0de");
+}
+
+#[test]
+fn test_exports_and_caches_an_artifact_per_contract() {
+    let out = std::env::temp_dir().join(format!("huff-multi-{}", uuid::Uuid::new_v4()));
+    let output = out.to_string_lossy().to_string();
+
+    let mut compiler = compiler(FACTORY);
+    compiler.output = Some(output.clone());
+    compiler.output_layout = OutputLayout::Mirror;
+    let artifacts = compiler.execute().unwrap();
+
+    let manager = OutputManager::new(OutputLocation(output), OutputLayout::Mirror);
+    let child = out.join(manager.relative_path(find(&artifacts, Some("Child"))));
+    assert!(child.ends_with("contracts/Factory.huff.Child.json"));
+    assert!(out.join("contracts/Factory.huff.Factory.json").exists());
+    let written: Artifact =
+        serde_json::from_str(&std::fs::read_to_string(&child).unwrap()).unwrap();
+    assert_eq!(written.contract_name.as_deref(), Some("Child"));
+
+    // Cached artifacts of every contract are resolved from their source
+    let mut tampered = written.clone();
+    tampered.runtime = "cached".to_string();
+    std::fs::write(&child, serde_json::to_string(&tampered).unwrap()).unwrap();
+    compiler.cached = true;
+    let cached = compiler.execute().unwrap();
+    assert_eq!(cached.len(), 2);
+    assert_eq!(find(&cached, Some("Child")).runtime, "cached");
+    assert!(cached.iter().any(|a| a.contract_name.as_deref() == Some("Factory")));
+
+    std::fs::remove_dir_all(out).unwrap();
+}
+
+#[test]
+fn test_contract_name_is_only_serialized_when_set() {
+    let artifact = Artifact::default();
+    assert!(!serde_json::to_string(&artifact).unwrap().contains("contractName"));
+
+    let named = Artifact { contract_name: Some("Child".to_string()), ..Default::default() };
+    assert!(serde_json::to_string(&named).unwrap().contains("\"contractName\":\"Child\""));
+}
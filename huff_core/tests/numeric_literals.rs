@@ -0,0 +1,194 @@
+mod common;
+
+use huff_codegen::Codegen;
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_readable_numeric_constants() {
+    let source: &str = r#"
+        #define constant WAD = 1e18
+        #define constant SUPPLY: uint32 = 1_000_000
+        #define constant MAGIC = 0xDEAD_BEEF
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [WAD] [SUPPLY] [MAGIC]
+        }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    // Decimal constants are normalized to literals
+    assert_eq!(
+        contract.constants.lock().unwrap()[0].value,
+        ConstVal::Literal(str_to_bytes32("0de0b6b3a7640000"))
+    );
+
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(main_bytecode, "670de0b6b3a7640000620f424063deadbeef");
+}
+
+#[test]
+fn test_readable_numeric_pushes() {
+    let source: &str = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            1e18 1_000_000 25e1_7
+        }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+
+    // Separators and exponents are normalized to literals in macro bodies too
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(main_bytecode, "670de0b6b3a7640000620f42406722b1c8c1227a0000");
+}
+
+#[test]
+fn test_oversized_stack_counts() {
+    for source in [
+        "#define macro MAIN() = takes(1e30) returns(0) {}",
+        "#define macro MAIN() = takes(0) returns(1_000_000_000_000_000_000_000) {}",
+    ] {
+        let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+        let lexer = Lexer::new(flattened_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let err = Parser::new(tokens, None).parse().unwrap_err();
+        assert!(matches!(err.kind, ParserErrorKind::StackCountTooLarge(_)));
+    }
+
+    // The count is reported in decimal
+    let source = "#define macro MAIN() = takes(1e30) returns(0) {}";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let err = Parser::new(tokens, None).parse().unwrap_err();
+    let message = CompilerError::ParserError(err).to_string();
+    assert!(message.contains("Stack Item Count Too Large: \"1000000000000000000000000000000\""));
+}
+
+#[test]
+fn test_decimal_pushes() {
+    let source: &str = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            1000 100000000000000000000000
+        }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+
+    // Decimals are pushed like hex literals, whatever their size
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(main_bytecode, "6103e869152d02c7e14af6800000");
+}
+
+#[test]
+fn test_negative_literals() {
+    let source: &str = r#"
+        #define constant MINUS_ONE = -1
+        #define constant MIN: int8 = -0x80
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [MINUS_ONE] [MIN] -0x20
+        }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    // Negative literals are pushed as 32 byte two's complement values
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    let ones = "ff".repeat(31);
+    assert_eq!(main_bytecode, format!("7f{ones}ff7f{ones}807f{ones}e0"));
+}
+
+#[test]
+fn test_signed_literal_bounds() {
+    let source: &str = r#"
+        #define constant MIN: int16 = -0x8000
+        #define constant MAX: int16 = 0x7fff
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [MIN] [MAX]
+        }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(main_bytecode, format!("7f{}8000617fff", "ff".repeat(30)));
+}
+
+#[test]
+fn test_invalid_negative_literals() {
+    let parse = |source: &str| {
+        let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+        let lexer = Lexer::new(flattened_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        Parser::new(tokens, None).parse().unwrap_err().kind
+    };
+
+    // Out of the range of an int256
+    let source = format!("#define constant C = -0x81{}", "00".repeat(31));
+    assert!(matches!(
+        parse(&source),
+        ParserErrorKind::InvalidNegativeLiteral(TokenKind::Literal(_))
+    ));
+
+    // Out of the range of the type, on either side
+    for (source, ty) in [
+        ("#define constant C: int8 = -0x81", PrimitiveEVMType::Int(8)),
+        ("#define constant C: int8 = 0x80", PrimitiveEVMType::Int(8)),
+        ("#define constant C: int16 = -0x8001", PrimitiveEVMType::Int(16)),
+        ("#define constant C: int16 = 0x8000", PrimitiveEVMType::Int(16)),
+    ] {
+        assert_eq!(parse(source), ParserErrorKind::ConstantTypeMismatch("C".to_string(), ty));
+    }
+
+    // Not a literal
+    assert_eq!(
+        parse("#define macro MAIN() = takes(0) returns(0) { -[C] }"),
+        ParserErrorKind::InvalidNegativeLiteral(TokenKind::OpenBracket)
+    );
+}
+
+#[test]
+fn test_overflowing_literal_fails_compilation() {
+    let source = format!("#define macro MAIN() = takes(0) returns(0) {{ 0x{} }}", "ff".repeat(34));
+    let compiler = common::compiler(&[("main.huff", &source)]);
+
+    // The lexical error is returned instead of panicking
+    let err = compiler.execute().unwrap_err();
+    let CompilerError::FailedCompiles(errors) = &*err else { panic!("unexpected error: {err:?}") };
+    assert!(matches!(
+        &errors[0],
+        CompilerError::LexicalError(e)
+            if matches!(e.kind, LexicalErrorKind::OverflowingLiteral(_))
+    ));
+}
@@ -0,0 +1,175 @@
+use huff_core::output::*;
+use huff_utils::prelude::*;
+use std::{path::PathBuf, sync::Arc};
+
+fn artifact(path: &str) -> Arc<Artifact> {
+    Arc::new(Artifact {
+        file: Arc::new(FileSource { path: path.to_string(), ..Default::default() }),
+        bytecode: "6000".to_string(),
+        ..Default::default()
+    })
+}
+
+fn output_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("huff-output-{}", uuid::Uuid::new_v4()))
+}
+
+#[test]
+fn test_layout_from_str() {
+    assert_eq!("uppercase".parse::<OutputLayout>(), Ok(OutputLayout::Uppercase));
+    assert_eq!("Mirror".parse::<OutputLayout>(), Ok(OutputLayout::Mirror));
+    assert!("flat".parse::<OutputLayout>().is_err());
+}
+
+#[test]
+fn test_relative_paths() {
+    let erc20 = artifact("./src/tokens/ERC20.huff");
+    let outside = artifact("../lib/Owned.huff");
+
+    let uppercase = OutputManager::new(OutputLocation::default(), OutputLayout::Uppercase);
+    assert_eq!(uppercase.relative_path(&erc20), PathBuf::from("SRC/TOKENS/ERC20.HUFF.json"));
+    assert_eq!(uppercase.relative_path(&outside), PathBuf::from("LIB/OWNED.HUFF.json"));
+
+    let mirror = OutputManager::new(OutputLocation::default(), OutputLayout::Mirror);
+    assert_eq!(mirror.relative_path(&erc20), PathBuf::from("src/tokens/ERC20.huff.json"));
+    assert_eq!(mirror.relative_path(&outside), PathBuf::from("lib/Owned.huff.json"));
+}
+
+#[test]
+fn test_export_mirrors_source_tree() {
+    let out = output_dir();
+    let artifacts = vec![artifact("./src/tokens/ERC20.huff"), artifact("./src/Owned.huff")];
+    let manager =
+        OutputManager::new(OutputLocation(out.to_string_lossy().to_string()), OutputLayout::Mirror);
+
+    let manifest = manager.export(&artifacts);
+    assert_eq!(
+        manifest.artifacts,
+        vec![
+            ManifestEntry {
+                source: "./src/Owned.huff".to_string(),
+                artifact: "src/Owned.huff.json".to_string()
+            },
+            ManifestEntry {
+                source: "./src/tokens/ERC20.huff".to_string(),
+                artifact: "src/tokens/ERC20.huff.json".to_string()
+            },
+        ]
+    );
+
+    for entry in &manifest.artifacts {
+        let written: Artifact =
+            serde_json::from_str(&std::fs::read_to_string(out.join(&entry.artifact)).unwrap())
+                .unwrap();
+        assert_eq!(written.file.path, entry.source);
+    }
+    let written: Manifest =
+        serde_json::from_str(&std::fs::read_to_string(out.join(MANIFEST_FILE)).unwrap()).unwrap();
+    assert_eq!(written, manifest);
+
+    std::fs::remove_dir_all(out).unwrap();
+}
+
+#[test]
+fn test_export_multiple_artifacts_to_file() {
+    let out = output_dir();
+    let file = out.join("artifact.json");
+    let artifacts = vec![artifact("./A.huff"), artifact("./B.huff")];
+    let manager = OutputManager::new(
+        OutputLocation(file.to_string_lossy().to_string()),
+        OutputLayout::Uppercase,
+    );
+
+    // Artifacts are laid out in the directory of the output file instead of overwriting it
+    let manifest = manager.export(&artifacts);
+    assert_eq!(manifest.artifacts.len(), 2);
+    assert!(!file.exists());
+    assert!(out.join("A.HUFF.json").exists());
+    assert!(out.join("B.HUFF.json").exists());
+    assert!(out.join(MANIFEST_FILE).exists());
+
+    std::fs::remove_dir_all(out).unwrap();
+}
+
+#[test]
+fn test_format_from_str() {
+    assert_eq!("hardhat".parse::<OutputFormat>(), Ok(OutputFormat::Hardhat));
+    assert_eq!("Foundry".parse::<OutputFormat>(), Ok(OutputFormat::Foundry));
+    assert!("truffle".parse::<OutputFormat>().is_err());
+}
+
+#[test]
+fn test_hardhat_and_foundry_paths() {
+    let erc20 = artifact("./src/tokens/ERC20.huff");
+    let child = Arc::new(Artifact {
+        contract_name: Some("Child".to_string()),
+        ..(*artifact("./src/Factory.huff")).clone()
+    });
+
+    let hardhat = OutputManager {
+        format: OutputFormat::Hardhat,
+        ..OutputManager::new(OutputLocation::default(), OutputLayout::Uppercase)
+    };
+    assert_eq!(hardhat.relative_path(&erc20), PathBuf::from("src/tokens/ERC20.huff/ERC20.json"));
+    assert_eq!(hardhat.relative_path(&child), PathBuf::from("src/Factory.huff/Child.json"));
+
+    let foundry = OutputManager { format: OutputFormat::Foundry, ..hardhat };
+    assert_eq!(foundry.relative_path(&erc20), PathBuf::from("ERC20.huff/ERC20.json"));
+    assert_eq!(foundry.relative_path(&child), PathBuf::from("Factory.huff/Child.json"));
+}
+
+#[test]
+fn test_hardhat_and_foundry_artifacts() {
+    let mut abi = huff_utils::abi::Abi::new();
+    abi.errors.insert(
+        "Unauthorized".to_string(),
+        huff_utils::abi::Error { name: "Unauthorized".to_string(), inputs: vec![] },
+    );
+    let erc20 = Artifact {
+        runtime: "00".to_string(),
+        abi: Some(abi),
+        method_identifiers: [("totalSupply()".to_string(), "18160ddd".to_string())].into(),
+        ..(*artifact("./src/ERC20.huff")).clone()
+    };
+    let abi = serde_json::json!([{ "type": "error", "name": "Unauthorized", "inputs": [] }]);
+
+    let hardhat = hardhat_artifact(&erc20);
+    assert_eq!(hardhat["_format"], "hh-sol-artifact-1");
+    assert_eq!(hardhat["contractName"], "ERC20");
+    assert_eq!(hardhat["sourceName"], "src/ERC20.huff");
+    assert_eq!(hardhat["abi"], abi);
+    assert_eq!(hardhat["bytecode"], "0x6000");
+    assert_eq!(hardhat["deployedBytecode"], "0x00");
+    assert_eq!(hardhat["linkReferences"], serde_json::json!({}));
+
+    let foundry = foundry_artifact(&erc20);
+    assert_eq!(foundry["abi"], abi);
+    assert_eq!(foundry["bytecode"]["object"], "0x6000");
+    assert_eq!(foundry["deployedBytecode"]["object"], "0x00");
+    assert_eq!(foundry["methodIdentifiers"]["totalSupply()"], "18160ddd");
+}
+
+#[test]
+fn test_export_foundry_artifacts() {
+    let out = output_dir();
+    let artifacts = vec![artifact("./src/ERC20.huff"), artifact("./src/Owned.huff")];
+    let manager = OutputManager {
+        format: OutputFormat::Foundry,
+        ..OutputManager::new(
+            OutputLocation(out.to_string_lossy().to_string()),
+            OutputLayout::Uppercase,
+        )
+    };
+
+    let manifest = manager.export(&artifacts);
+    assert_eq!(manifest.artifacts.len(), 2);
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(out.join("ERC20.huff/ERC20.json")).unwrap())
+            .unwrap();
+    assert_eq!(written["bytecode"]["object"], "0x6000");
+    assert!(out.join("Owned.huff/Owned.json").exists());
+    // Foundry reads every json file of its out directory
+    assert!(!out.join(MANIFEST_FILE).exists());
+
+    std::fs::remove_dir_all(out).unwrap();
+}
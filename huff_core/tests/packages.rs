@@ -0,0 +1,158 @@
+use huff_core::packages::*;
+use std::{fs, path::Path, process::Command};
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("huff-{name}-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=huff", "-c", "user.email=huff@huff.sh"])
+        .args(args)
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success(), "git {args:?} failed");
+}
+
+/// Creates a git repository with a library tagged `v1` and `v2`
+fn library_repo() -> std::path::PathBuf {
+    let repo = temp_dir("lib-repo");
+    fs::create_dir_all(repo.join("src")).unwrap();
+    git(&repo, &["init", "--quiet"]);
+    for version in ["v1", "v2"] {
+        fs::write(
+            repo.join("src/Answer.huff"),
+            format!("// {version}\n#define macro ANSWER() = takes(0) returns(1) {{ 0x2a }}\n"),
+        )
+        .unwrap();
+        git(&repo, &["add", "-A"]);
+        git(&repo, &["commit", "--quiet", "-m", version]);
+        git(&repo, &["tag", version]);
+    }
+    repo
+}
+
+#[test]
+fn test_parse_manifest() {
+    let manifest = PackageManifest::from_toml(
+        r#"
+        [dependencies]
+        huffmate = { git = "https://github.com/huff-language/huffmate", tag = "v0.0.2", src = "src" }
+        local = { git = "../local" }
+        "#,
+    )
+    .unwrap();
+    assert_eq!(manifest.dependencies.len(), 2);
+    assert_eq!(manifest.dependencies["huffmate"].tag.as_deref(), Some("v0.0.2"));
+    assert_eq!(
+        remapping("huffmate", &manifest.dependencies["huffmate"]),
+        "huffmate/=lib/huffmate/src/"
+    );
+    assert_eq!(remapping("local", &manifest.dependencies["local"]), "local/=lib/local/");
+
+    assert!(PackageManifest::from_toml("[dependencies]\nstd = { git = \"x\" }").is_err());
+    assert!(PackageManifest::from_toml("[dependencies]\n\"../up\" = { git = \"x\" }").is_err());
+    assert!(PackageManifest::from_toml("[dependencies]\nmissing = { tag = \"v1\" }").is_err());
+
+    // Urls can't pass options to git, or use transports other than https, ssh and files
+    for url in [
+        "https://github.com/huff-language/huffmate",
+        "ssh://git@github.com/huff-language/huffmate",
+        "git@github.com:huff-language/huffmate",
+        "file:///tmp/huffmate",
+        "/tmp/huffmate",
+    ] {
+        let manifest = format!("[dependencies]\nlib = {{ git = \"{url}\" }}");
+        assert!(PackageManifest::from_toml(&manifest).is_ok(), "{url}");
+    }
+    for url in
+        ["--upload-pack=touch /tmp/pwned", "-u", "ext::sh -c touch% /tmp/pwned", "git://x/y", ""]
+    {
+        let manifest = format!("[dependencies]\nlib = {{ git = \"{url}\" }}");
+        assert_eq!(
+            PackageManifest::from_toml(&manifest).unwrap_err().0,
+            format!("Invalid dependency url \"{url}\""),
+        );
+    }
+
+    // Sources are directories within the dependency
+    for src in ["src", "./src/", "src/tokens"] {
+        let manifest = format!("[dependencies]\nlib = {{ git = \"x\", src = \"{src}\" }}");
+        assert!(PackageManifest::from_toml(&manifest).is_ok(), "{src}");
+    }
+    for src in ["/etc", "..", "../other", "src/../../other"] {
+        let manifest = format!("[dependencies]\nlib = {{ git = \"x\", src = \"{src}\" }}");
+        assert_eq!(
+            PackageManifest::from_toml(&manifest).unwrap_err().0,
+            format!("Invalid dependency src \"{src}\""),
+        );
+    }
+}
+
+#[test]
+fn test_merge_remappings() {
+    let manifest =
+        PackageManifest::from_toml("[dependencies]\nhuffmate = { git = \"x\", src = \"src\" }")
+            .unwrap();
+    let existing = "solmate/=lib/solmate/src/\nhuffmate/=lib/old/\n";
+    assert_eq!(
+        merge_remappings(existing, &manifest),
+        "solmate/=lib/solmate/src/\nhuffmate/=lib/huffmate/src/\n"
+    );
+}
+
+#[test]
+fn test_install() {
+    let repo = library_repo();
+    let root = temp_dir("project");
+    let manifest = |tag: &str| {
+        format!(
+            "[dependencies]\nanswer = {{ git = \"{}\", tag = \"{tag}\", src = \"src\" }}\n",
+            repo.display()
+        )
+    };
+    fs::write(root.join(PACKAGE_MANIFEST), manifest("v1")).unwrap();
+
+    let installed = Installer::new(&root, false).install().unwrap();
+    assert_eq!(installed.len(), 1);
+    assert_eq!(installed[0].status, InstallStatus::Installed);
+    let vendored = root.join("lib/answer/src/Answer.huff");
+    assert!(fs::read_to_string(&vendored).unwrap().starts_with("// v1"));
+    assert!(!root.join("lib/answer/.git").exists());
+    assert_eq!(
+        fs::read_to_string(root.join(REMAPPINGS_FILE)).unwrap(),
+        "answer/=lib/answer/src/\n"
+    );
+    let lock = PackageLock::discover(&root).unwrap();
+    assert_eq!(lock.packages["answer"].tag.as_deref(), Some("v1"));
+    assert_eq!(lock.packages["answer"].rev, installed[0].rev);
+
+    // Installed dependencies aren't fetched again
+    let installed = Installer::new(&root, false).install().unwrap();
+    assert_eq!(installed[0].status, InstallStatus::UpToDate);
+
+    // Changing the tag fetches the new version
+    fs::write(root.join(PACKAGE_MANIFEST), manifest("v2")).unwrap();
+    let installed = Installer::new(&root, false).install().unwrap();
+    assert_eq!(installed[0].status, InstallStatus::Installed);
+    assert!(fs::read_to_string(&vendored).unwrap().starts_with("// v2"));
+
+    // Unknown tags fail
+    fs::write(root.join(PACKAGE_MANIFEST), manifest("v3")).unwrap();
+    assert!(Installer::new(&root, false).install().is_err());
+
+    fs::remove_dir_all(root).unwrap();
+    fs::remove_dir_all(repo).unwrap();
+}
+
+#[test]
+fn test_install_without_manifest() {
+    let root = temp_dir("empty");
+    assert!(Installer::new(&root, false).install().is_err());
+    fs::remove_dir_all(root).unwrap();
+}
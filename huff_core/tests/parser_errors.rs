@@ -95,7 +95,7 @@ fn test_invalid_definition() {
                 ParserError {
                     kind: ParserErrorKind::InvalidDefinition(TokenKind::Ident("invalid".to_string())),
                     hint: Some(
-                        "Definition must be one of: `function`, `event`, `constant`, `error`, `macro`, `fn`, or `test`."
+                        "Definition must be one of: `function`, `event`, `constant`, `error`, `struct`, `merkle`, `macro`, `fn`, `test`, or `contract`."
                             .to_string()
                     ),
                     spans: AstSpan(vec![Span {
@@ -120,7 +120,6 @@ fn test_invalid_constant_value() {
         (":", TokenKind::Colon),
         (",", TokenKind::Comma),
         ("+", TokenKind::Add),
-        ("-", TokenKind::Sub),
     ];
 
     for (value, kind) in invalid_constant_values {
@@ -139,7 +138,7 @@ fn test_invalid_constant_value() {
                     ParserError {
                         kind: ParserErrorKind::InvalidConstantValue(kind),
                         hint: Some(
-                            "Expected constant value to be a literal or `FREE_STORAGE_POINTER()`"
+                            "Expected constant value to be a literal, `FREE_STORAGE_POINTER()`, `STORAGE_SLOT()` or `FREE_MEMORY_POINTER()`"
                                 .to_string()
                         ),
                         spans: AstSpan(vec![Span {
@@ -154,6 +153,26 @@ fn test_invalid_constant_value() {
     }
 }
 
+#[test]
+fn test_invalid_negative_literal() {
+    // A minus sign starts a negative literal, in constants and macro bodies
+    for (source, kind) in [
+        ("#define constant CONSTANT = -", TokenKind::Eof),
+        ("#define macro CONSTANT() = takes (0) returns (0) { - }", TokenKind::CloseBrace),
+        ("#define macro CONSTANT() = takes (0) returns (0) { lab: - }", TokenKind::CloseBrace),
+    ] {
+        let full_source = FullFileSource { source, file: None, spans: vec![] };
+        let lexer = Lexer::new(full_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, Some("".to_string()));
+
+        match parser.parse() {
+            Ok(_) => panic!("moose"),
+            Err(e) => assert_eq!(e.kind, ParserErrorKind::InvalidNegativeLiteral(kind)),
+        }
+    }
+}
+
 #[test]
 fn test_invalid_token_in_macro_body() {
     let invalids = vec![
@@ -162,7 +181,6 @@ fn test_invalid_token_in_macro_body() {
         (":", TokenKind::Colon),
         (",", TokenKind::Comma),
         ("+", TokenKind::Add),
-        ("-", TokenKind::Sub),
         ("/", TokenKind::Div),
     ];
 
@@ -206,7 +224,6 @@ fn test_invalid_token_in_label_definition() {
         (":", TokenKind::Colon),
         (",", TokenKind::Comma),
         ("+", TokenKind::Add),
-        ("-", TokenKind::Sub),
         ("/", TokenKind::Div),
     ];
 
@@ -0,0 +1,137 @@
+use huff_core::{compile_str, playground::PlaygroundSettings};
+use std::collections::BTreeMap;
+
+#[test]
+fn test_compile_str() {
+    let source = r#"
+        #define function get() view returns (uint256)
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x2a 0x00 mstore
+            0x20 0x00 return
+        }
+    "#;
+    let output = compile_str(source, &PlaygroundSettings::default());
+
+    assert!(output.errors.is_empty());
+    assert!(output.warnings.is_empty());
+    assert_eq!(output.runtime.as_deref(), Some("602a60005260206000f3"));
+    assert!(output.bytecode.unwrap().ends_with("602a60005260206000f3"));
+    assert_eq!(output.abi.unwrap().functions["get"].name, "get");
+    assert!(output.timings.lexing >= 0.0 && output.timings.codegen >= 0.0);
+}
+
+#[test]
+fn test_compile_str_settings() {
+    let source = r#"
+        #define constant VALUE = 0x01
+
+        #define macro MAIN() = takes(0) returns(0) { [VALUE] }
+        #define macro OTHER() = takes(0) returns(0) { [VALUE] push0 }
+    "#;
+    let settings = PlaygroundSettings {
+        main_macro: Some("OTHER".to_string()),
+        constant_overrides: BTreeMap::from([("VALUE".to_string(), "0x02".to_string())]),
+        ..Default::default()
+    };
+    assert_eq!(compile_str(source, &settings).runtime.as_deref(), Some("60025f"));
+
+    // PUSH0 isn't available before shanghai
+    let settings = PlaygroundSettings { evm_version: "paris".to_string(), ..settings };
+    let output = compile_str(source, &settings);
+    assert_eq!(output.runtime, None);
+    assert_eq!(output.errors.len(), 1);
+
+    let settings = PlaygroundSettings { evm_version: "frontier2".to_string(), ..settings };
+    let output = compile_str(source, &settings);
+    assert_eq!(output.errors[0].message, "Unsupported EVM version: \"frontier2\"");
+    assert!(output.errors[0].spans.is_empty());
+}
+
+#[test]
+fn test_compile_str_settings_from_json() {
+    let settings: PlaygroundSettings =
+        serde_json::from_str(r#"{ "mainMacro": "OTHER", "evmVersion": "paris" }"#).unwrap();
+    assert_eq!(settings.main_macro.as_deref(), Some("OTHER"));
+    assert_eq!(settings.evm_version, "paris");
+    assert_eq!(settings.macro_nesting_limit, PlaygroundSettings::default().macro_nesting_limit);
+}
+
+#[test]
+fn test_compile_str_errors() {
+    // Lexing errors are reported instead of panicking
+    let output =
+        compile_str("#define macro MAIN() = takes(0) returns(0) { ~ }", &Default::default());
+    assert_eq!(output.errors.len(), 1);
+    assert_eq!(output.errors[0].spans, vec![(45, 46)]);
+    assert_eq!(output.bytecode, None);
+
+    // Parsing errors
+    let output = compile_str("#define macro MAIN() = takes(0) {", &Default::default());
+    assert_eq!(output.errors.len(), 1);
+    assert!(!output.errors[0].spans.is_empty());
+
+    // Codegen errors
+    let source = "#define macro MAIN() = takes(0) returns(0) { MISSING() }";
+    let output = compile_str(source, &Default::default());
+    assert_eq!(output.errors.len(), 1);
+    assert!(output.errors[0].message.contains("MISSING"));
+    assert_eq!(output.errors[0].spans[0], (45, 52));
+
+    // Input that used to panic in the lexer is reported as an error of the source
+    for source in [
+        "#define function f(uint256[99999999999999999999999]) view returns ()",
+        "#define macro MAIN() = takes(0) returns(0) {} 0",
+    ] {
+        let output = compile_str(source, &Default::default());
+        assert_eq!(output.errors.len(), 1, "{source}");
+        assert!(!output.errors[0].message.contains("Internal compiler error"), "{source}");
+    }
+}
+
+#[test]
+fn test_compile_str_warnings() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            0x00 0x00 revert
+            0x01
+        }
+    "#;
+    let output = compile_str(source, &Default::default());
+    assert!(output.errors.is_empty());
+    assert_eq!(output.warnings.len(), 1);
+    assert!(output.warnings[0].message.contains("MAIN"));
+
+    // Lexing warnings
+    let source = r#"
+        #define constant SLOT = STORAGE_SLOT(0100)
+        #define macro MAIN() = takes(0) returns(0) { [SLOT] sload pop }
+    "#;
+    let output = compile_str(source, &Default::default());
+    assert!(output.errors.is_empty());
+    assert_eq!(output.warnings.len(), 1);
+    assert!(output.warnings[0].message.contains("\"0100\""));
+    assert_eq!(output.warnings[0].spans, vec![(46, 50)]);
+
+    // Overflowing literals are errors
+    let source = format!("#define macro MAIN() = takes(0) returns(0) {{ 0x1{} }}", "0".repeat(64));
+    let output = compile_str(&source, &Default::default());
+    assert_eq!(output.errors.len(), 1);
+    assert!(output.errors[0].message.contains("Exceeds 32 Bytes"));
+}
+
+#[test]
+fn test_compile_str_std_includes() {
+    let source = r#"
+        #include "std/math.huff"
+
+        #define macro MAIN() = takes(0) returns(0) { stop }
+    "#;
+    let output = compile_str(source, &Default::default());
+    assert!(output.errors.is_empty(), "{:?}", output.errors);
+    assert_eq!(output.runtime.as_deref(), Some("00"));
+
+    // Other files can't be included
+    let output = compile_str(r#"#include "./other.huff""#, &Default::default());
+    assert_eq!(output.errors.len(), 1);
+}
@@ -0,0 +1,234 @@
+mod common;
+
+use common::{main_bytecode, parse};
+use huff_codegen::*;
+use huff_core::VERSION;
+use huff_utils::prelude::*;
+use std::{str::FromStr, sync::Arc};
+
+#[test]
+fn test_no_inline_pragma_outlines_macro() {
+    let outlined = main_bytecode(
+        r#"
+        #pragma no-inline
+        #define macro DOUBLE() = takes(1) returns(1) {
+            dup1 add
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x01 DOUBLE() DOUBLE() pop
+        }
+        "#,
+    )
+    .unwrap();
+    let function = main_bytecode(
+        r#"
+        #define fn DOUBLE() = takes(1) returns(1) {
+            dup1 add
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x01 DOUBLE() DOUBLE() pop
+        }
+        "#,
+    )
+    .unwrap();
+    assert_eq!(outlined, function);
+}
+
+#[test]
+fn test_hot_pragma_inlines_function() {
+    let inlined = main_bytecode(
+        r#"
+        #pragma hot
+        #define fn DOUBLE() = takes(1) returns(1) {
+            dup1 add
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x01 DOUBLE() pop
+        }
+        "#,
+    )
+    .unwrap();
+    assert_eq!(inlined, "6001800150");
+}
+
+#[test]
+fn test_unsafe_stack_pragma_skips_stack_check() {
+    let source = |pragma: &str| {
+        format!(
+            r#"
+            #define macro PUSH_EIGHT() = takes(0) returns(8) {{
+                0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01
+            }}
+
+            {pragma}
+            #define macro PUSH_MANY() = takes(0) returns(1032) {{
+                {}
+            }}
+
+            #define macro MAIN() = takes(0) returns(0) {{
+                PUSH_MANY()
+            }}
+            "#,
+            "PUSH_EIGHT() ".repeat(129)
+        )
+    };
+
+    let contract = parse(&source(""));
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Err(CodegenError { kind: CodegenErrorKind::StackTooDeep(..), .. }) => {}
+        _ => panic!("expected the stack check to fail"),
+    }
+
+    let contract = parse(&source("#pragma unsafe-stack"));
+    assert!(Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).is_ok());
+}
+
+#[test]
+fn test_unsafe_stack_pragma_skips_output_analysis() {
+    let source = |pragma: &str| {
+        format!(
+            r#"
+            {pragma}
+            #define macro DIVMOD() = takes(2) returns(quotient, remainder) {{
+                div
+            }}
+            "#
+        )
+    };
+
+    let warnings = Codegen::analyze(&parse(&source("")));
+    assert!(warnings.iter().any(|w| matches!(w.kind, WarningKind::OutputMismatch(..))));
+
+    let warnings = Codegen::analyze(&parse(&source("#pragma unsafe-stack")));
+    assert!(!warnings.iter().any(|w| matches!(w.kind, WarningKind::OutputMismatch(..))));
+}
+
+const NONREENTRANT: &str = r#"
+    #define constant OWNER = FREE_STORAGE_POINTER()
+
+    #pragma nonreentrant
+    #define macro WITHDRAW() = takes(0) returns(0) {
+        [OWNER] sload iszero done jumpi
+        0x00 0x00 return
+        done:
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        WITHDRAW() stop
+    }
+"#;
+
+#[test]
+fn test_nonreentrant_pragma_guards_macro() {
+    // The lock takes the slot after the declared ones
+    let contract = parse(NONREENTRANT);
+    assert_eq!(
+        contract.storage_layout.iter().map(|s| (s.name.as_str(), s.slot[31])).collect::<Vec<_>>(),
+        vec![("OWNER", 0), (REENTRANCY_LOCK, 1)]
+    );
+
+    // 6001 5c 15 61000c 57 6000 80 fd 5b 6001 6001 5d: revert if locked, then lock
+    // 6000 54 15 610024 57 | 6000 6000 6000 6001 5d f3: unlock right before returning
+    // 5b 6000 6001 5d | 00: unlock once done, not before halting outside of the macro
+    assert_eq!(
+        main_bytecode(NONREENTRANT).unwrap(),
+        "60015c1561000c57600080fd5b600160015d600054156100245760006000600060015df35b600060015d00"
+    );
+
+    // Hard forks without transient storage keep the lock in storage
+    let paris = EVMVersion::from_str("paris").unwrap();
+    let bytecode = Codegen::generate_main_bytecode(&paris, &parse(NONREENTRANT), None).unwrap();
+    assert_eq!(
+        bytecode,
+        "6001541561000c57600080fd5b60016001556000541561002457600060006000600155f35b600060015500"
+    );
+}
+
+#[test]
+fn test_nonreentrant_pragma_repeated_macro() {
+    let source = r#"
+        #pragma nonreentrant
+        #define macro DEPOSIT() = takes(0) returns(0) {
+            callvalue
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            DEPOSIT() DEPOSIT() stop
+        }
+    "#;
+
+    // Each lock jumps over its own revert, to 0x0c and 0x24
+    let paris = EVMVersion::from_str("paris").unwrap();
+    assert_eq!(
+        Codegen::generate_main_bytecode(&paris, &parse(source), None).unwrap(),
+        "6000541561000c57600080fd5b6001600055346000600055\
+         6000541561002457600080fd5b6001600055346000600055\
+         00"
+    );
+}
+
+fn execute(
+    source: &str,
+    evm_version: &str,
+) -> Result<Vec<Arc<Artifact>>, Arc<CompilerError<'static>>> {
+    let mut compiler = common::compiler(&[("main.huff", source)]);
+    compiler.evm_version = evm_version.parse().unwrap();
+    compiler.execute()
+}
+
+fn incompatible_pragma(err: &CompilerError) -> Option<String> {
+    match err {
+        CompilerError::IncompatiblePragma(msg, _) => Some(msg.clone()),
+        CompilerError::FailedCompiles(errors) => errors.iter().find_map(incompatible_pragma),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_evm_version_pragma() {
+    let source = r#"
+    #pragma evm-version "shanghai"
+    #define macro MAIN() = takes(0) returns(0) { push0 }
+    "#;
+
+    // Later hard forks than the required one are compatible
+    assert!(execute(source, "shanghai").is_ok());
+    assert!(execute(source, "cancun").is_ok());
+
+    let err = execute(source, "paris").unwrap_err();
+    assert_eq!(
+        incompatible_pragma(&err).unwrap(),
+        "Source requires EVM version shanghai or later, but paris is targeted. Target it with `--evm-version shanghai`"
+    );
+
+    // Unknown hard forks are rejected
+    let err = execute(&source.replace("\"shanghai\"", "prague_x"), "cancun").unwrap_err();
+    assert_eq!(incompatible_pragma(&err).unwrap(), "Unsupported EVM version: \"prague_x\"");
+}
+
+#[test]
+fn test_huff_version_pragma() {
+    let source = |requirement: &str| {
+        format!(
+            r#"
+            #pragma huff-version "{requirement}"
+            #define macro MAIN() = takes(0) returns(0) {{}}
+            "#
+        )
+    };
+
+    assert!(execute(&source(&format!("={VERSION}")), "cancun").is_ok());
+    assert!(execute(&source(&format!(">={VERSION}, <100")), "cancun").is_ok());
+
+    let err = execute(&source(">=100.0.0"), "cancun").unwrap_err();
+    assert_eq!(
+        incompatible_pragma(&err).unwrap(),
+        format!("Source requires huff version >=100.0.0, but the compiler is version {VERSION}")
+    );
+
+    let err = execute(&source("not a version"), "cancun").unwrap_err();
+    assert!(incompatible_pragma(&err).unwrap().starts_with("Invalid huff version requirement"));
+}
@@ -0,0 +1,89 @@
+use huff_core::proxy::*;
+use huff_utils::prelude::*;
+
+const IMPLEMENTATION: &str = "0xbebebebebebebebebebebebebebebebebebebebe";
+const ADMIN: &str = "0xadadadadadadadadadadadadadadadadadadadad";
+
+#[test]
+fn test_parses_proxy_kinds() {
+    assert_eq!("minimal".parse::<ProxyKind>(), Ok(ProxyKind::Minimal));
+    assert_eq!("ERC1167".parse::<ProxyKind>(), Ok(ProxyKind::Minimal));
+    assert_eq!("transparent".parse::<ProxyKind>(), Ok(ProxyKind::Transparent));
+    assert_eq!("eip1967".parse::<ProxyKind>(), Ok(ProxyKind::Transparent));
+    assert!("beacon".parse::<ProxyKind>().is_err());
+    assert_eq!(ProxyKind::Transparent.to_string(), "transparent");
+}
+
+#[test]
+fn test_minimal_proxy_of_implementation() {
+    let artifact = Proxy::new(ProxyKind::Minimal, Some(IMPLEMENTATION), None).artifact().unwrap();
+    let runtime =
+        format!("363d3d373d3d3d363d73{}5af43d82803e903d91602b57fd5bf3", &IMPLEMENTATION[2..]);
+    assert_eq!(artifact.runtime, runtime);
+    assert_eq!(artifact.bytecode, format!("3d602d80600a3d3981f3{runtime}"));
+    assert!(artifact.constructor_inputs.is_empty());
+    assert!(artifact.abi.unwrap().fallback);
+}
+
+#[test]
+fn test_minimal_proxy_of_constructor_argument() {
+    let artifact = Proxy::new(ProxyKind::Minimal, None, None).artifact().unwrap();
+
+    // The runtime is built from the implementation appended to the creation code
+    assert!(artifact.runtime.is_empty());
+    assert!(artifact.bytecode.contains(MINIMAL_PROXY_PREFIX));
+    assert!(artifact.bytecode.contains(MINIMAL_PROXY_SUFFIX));
+    assert_eq!(artifact.constructor_inputs, vec!["address".to_string()]);
+}
+
+#[test]
+fn test_transparent_proxy_of_implementation() {
+    let artifact =
+        Proxy::new(ProxyKind::Transparent, Some(IMPLEMENTATION), Some(ADMIN)).artifact().unwrap();
+
+    // The implementation and admin are appended to the creation code
+    assert!(artifact.bytecode.ends_with(&format!(
+        "000000000000000000000000{}000000000000000000000000{}",
+        &IMPLEMENTATION[2..],
+        &ADMIN[2..]
+    )));
+    assert_eq!(artifact.file.path, TRANSPARENT_PROXY_PATH);
+    assert_eq!(artifact.constructor_inputs, vec!["address".to_string(), "address".to_string()]);
+    assert_eq!(
+        artifact.method_identifiers.get("upgradeToAndCall(address,bytes)").map(String::as_str),
+        Some("4f1ef286")
+    );
+
+    // The implementation and admin are read from the EIP-1967 slots
+    let impl_slot = bytes32_to_string(&eip1967_slot("eip1967.proxy.implementation"), false);
+    let admin_slot = bytes32_to_string(&eip1967_slot("eip1967.proxy.admin"), false);
+    assert!(artifact.runtime.contains(&impl_slot));
+    assert!(artifact.runtime.contains(&admin_slot));
+}
+
+#[test]
+fn test_transparent_proxy_of_constructor_arguments() {
+    let artifact = Proxy::new(ProxyKind::Transparent, None, None).artifact().unwrap();
+    let with_args =
+        Proxy::new(ProxyKind::Transparent, Some(IMPLEMENTATION), Some(ADMIN)).artifact().unwrap();
+    assert_eq!(artifact.runtime, with_args.runtime);
+    assert_eq!(artifact.bytecode.len() + 128, with_args.bytecode.len());
+}
+
+#[test]
+fn test_invalid_proxies() {
+    let invalid = |proxy: Proxy| match proxy.artifact() {
+        Err(CompilerError::InvalidProxy(msg)) => msg,
+        r => panic!("expected an invalid proxy, got {r:?}"),
+    };
+    assert_eq!(
+        invalid(Proxy::new(ProxyKind::Minimal, Some("0xbebe"), None)),
+        "Invalid address \"0xbebe\""
+    );
+    assert_eq!(
+        invalid(Proxy::new(ProxyKind::Minimal, Some(IMPLEMENTATION), Some(ADMIN))),
+        "Minimal proxies have no admin"
+    );
+    assert!(invalid(Proxy::new(ProxyKind::Transparent, Some(IMPLEMENTATION), None))
+        .starts_with("Transparent proxies take both"));
+}
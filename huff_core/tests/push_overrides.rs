@@ -32,7 +32,7 @@ fn test_gracefully_pads_push_override() {
 
     // Have the Codegen create the constructor bytecode
     let (cbytes, has_custom_bootstrap) =
-        Codegen::generate_constructor_bytecode(&contract, None).unwrap();
+        Codegen::generate_constructor_bytecode(&EVMVersion::default(), &contract, None).unwrap();
     assert_eq!(
         cbytes,
         String::from("7f0000000000000000000000000000000000000000000000000000000000000234")
@@ -69,7 +69,7 @@ fn test_constructs_exact_push_override() {
 
     // Have the Codegen create the constructor bytecode
     let (cbytes, has_custom_bootstrap) =
-        Codegen::generate_constructor_bytecode(&contract, None).unwrap();
+        Codegen::generate_constructor_bytecode(&EVMVersion::default(), &contract, None).unwrap();
     assert_eq!(cbytes, String::from("6034"));
     assert!(!has_custom_bootstrap);
 }
@@ -90,3 +90,29 @@ fn test_fails_on_push_underflow() {
     let mut parser = Parser::new(tokens, None);
     parser.parse().unwrap();
 }
+
+#[test]
+fn test_literals_use_minimal_push() {
+    // Create the raw source
+    const MINIMAL_PUSH: &str = r#"
+        #define constant PADDED = 0x0000002a
+        #define macro MAIN() = {
+            0x0001 [PADDED] push2 0x0001 __VERBATIM(0x61002a)
+        }
+    "#;
+
+    // Lex and Parse the source code
+    let flattened_source = FullFileSource { source: MINIMAL_PUSH, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Grab the first macro
+    let mut contract = parser.parse().unwrap();
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // Leading zero bytes are stripped unless an exact push or `__VERBATIM` is used
+    let mbytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(mbytes, String::from("6001602a61000161002a"));
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use ethers_core::utils::{hex, keccak256};
+use huff_core::{Compiler, VERSION};
+use huff_utils::prelude::*;
+
+const MAIN: &str = r#"
+#include "./utils.huff"
+
+#define constant FEE = 0x01
+
+#define macro MAIN() = takes (0) returns (0) {
+    [FEE] ADD_ONE()
+    0x00 mstore
+    0x20 0x00 return
+}
+"#;
+
+const UTILS: &str = r#"
+#define macro ADD_ONE() = takes (1) returns (1) {
+    0x01 add
+}
+"#;
+
+fn compiler() -> Compiler<'static> {
+    common::compiler(&[("main.huff", MAIN), ("utils.huff", UTILS)])
+}
+
+#[test]
+fn test_identical_inputs_compile_to_identical_artifacts() {
+    let first = serde_json::to_string_pretty(compiler().execute().unwrap()[0].as_ref()).unwrap();
+    let second = serde_json::to_string_pretty(compiler().execute().unwrap()[0].as_ref()).unwrap();
+    assert_eq!(first, second);
+
+    // The time the sources were read isn't recorded
+    assert!(!first.contains("access"));
+}
+
+#[test]
+fn test_artifact_metadata() {
+    let artifact = compiler().execute().unwrap().remove(0);
+    let metadata = artifact.metadata.as_ref().unwrap();
+
+    assert_eq!(metadata.version, VERSION);
+    assert_eq!(
+        metadata.settings,
+        CompilerSettings {
+            evm_version: EVMVersion::default().to_string(),
+            edition: Edition::default().to_string(),
+            optimize: false,
+            eof: false,
+            auto_revert: false,
+            main_macro: "MAIN".to_string(),
+            constructor_macro: "CONSTRUCTOR".to_string(),
+            constant_overrides: Default::default(),
+            macro_nesting_limit: 1024,
+        }
+    );
+
+    let hash = |source: &str| format!("0x{}", hex::encode(keccak256(source)));
+    assert_eq!(metadata.sources.len(), 2);
+    assert_eq!(metadata.sources["main.huff"], hash(MAIN));
+    assert_eq!(
+        metadata.sources.iter().find(|(p, _)| p.ends_with("utils.huff")).unwrap().1,
+        &hash(UTILS)
+    );
+}
+
+#[test]
+fn test_artifact_metadata_records_settings() {
+    let mut compiler = compiler();
+    compiler.evm_version = "paris".parse().unwrap();
+    compiler.constant_overrides = Some([("FEE", str_to_bytes32("02"))].into_iter().collect());
+    let artifact = compiler.execute().unwrap().remove(0);
+    let settings = &artifact.metadata.as_ref().unwrap().settings;
+
+    assert_eq!(settings.evm_version, "paris");
+    assert_eq!(settings.constant_overrides["FEE"], "0x02");
+
+    let json = serde_json::to_value(artifact.as_ref()).unwrap();
+    assert_eq!(json["metadata"]["settings"]["evmVersion"], "paris");
+    assert_eq!(json["metadata"]["settings"]["constantOverrides"]["FEE"], "0x02");
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::artifact;
+use huff_core::size_report::{MacroSize, SizeReport, UNATTRIBUTED};
+
+fn size(name: &str, size: usize, invocations: usize) -> MacroSize {
+    MacroSize { name: name.to_string(), size, invocations }
+}
+
+#[test]
+fn test_size_report_attributes_bytes_to_macros() {
+    let source = r#"
+        #define macro LOOP() = takes (0) returns (0) {
+            top:
+                0x01 top jumpi
+        }
+
+        #define macro ADD() = takes (2) returns (1) {
+            add
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x01 0x02 ADD()
+            LOOP()
+            0x03 0x04 ADD()
+            done jump
+            done:
+                stop
+        }
+    "#;
+    let report = SizeReport::new(&artifact(source));
+
+    // MAIN generates 23 bytes, of which LOOP generates 7 and each ADD 1
+    assert_eq!(report.contract, "main.huff");
+    assert_eq!(report.runtime, 23);
+    assert_eq!(report.macros, vec![size("MAIN", 14, 1), size("LOOP", 7, 1), size("ADD", 2, 2)]);
+
+    let rendered = report.to_string();
+    assert!(rendered.starts_with("main.huff: 23 bytes, 0.09% of the 24576 byte limit\n"));
+    assert!(rendered.contains("    MAIN     14   60.87%  (1 invocation)\n"));
+    assert!(rendered.contains("    ADD       2    8.70%  (2 invocations)\n"));
+}
+
+#[test]
+fn test_size_report_tables() {
+    let source = r#"
+        #define table TABLE {
+            0x0000000000000000000000000000000000000000000000000000000000000001
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            __tablesize(TABLE) __tablestart(TABLE) 0x00 codecopy
+        }
+    "#;
+    let report = SizeReport::new(&artifact(source));
+    assert_eq!(report.runtime, 40);
+    assert_eq!(report.macros, vec![size(UNATTRIBUTED, 32, 0), size("MAIN", 8, 1)]);
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use huff_core::snapshot::Snapshot;
+
+const SOURCE: &str = r#"
+#define macro ADD() = takes (2) returns (1) {
+    add
+}
+
+#define macro STORE() = takes (1) returns (0) {
+    0x00 sstore
+}
+
+#define macro MAIN() = takes (0) returns (0) {
+    0x01 0x02 ADD()
+    STORE()
+    done jump
+    done:
+        stop
+}
+"#;
+
+fn snapshot(source: &str) -> Snapshot {
+    let compiler = common::compiler(&[("main.huff", source)]);
+    compiler.snapshot().unwrap()
+}
+
+#[test]
+fn test_snapshot_records_runtime_and_macros() {
+    let snapshot = snapshot(SOURCE);
+    let contract = &snapshot.contracts["main.huff"];
+
+    // 6001 6002 01 6000 55 61000a 56 5b 00
+    assert_eq!(contract.runtime.size, 14);
+    assert_eq!(contract.runtime.hash.len(), 66);
+    assert_eq!(contract.macros.keys().collect::<Vec<_>>(), vec!["ADD", "MAIN", "STORE"]);
+    assert_eq!(contract.macros["ADD"].size, 1);
+    assert_eq!(contract.macros["STORE"].size, 3);
+    assert_eq!(contract.macros["MAIN"].size, 10);
+}
+
+#[test]
+fn test_snapshot_round_trip() {
+    let snapshot = snapshot(SOURCE);
+    let text = snapshot.to_string();
+    assert_eq!(text.lines().count(), 4);
+    assert!(text.starts_with("main.huff 14 0x"));
+    assert_eq!(text.parse::<Snapshot>().unwrap(), snapshot);
+
+    assert!("main.huff 13".parse::<Snapshot>().is_err());
+    assert!("main.huff thirteen 0x00".parse::<Snapshot>().is_err());
+}
+
+#[test]
+fn test_snapshot_diff() {
+    let old = snapshot(SOURCE);
+    assert!(old.diff(&old).is_empty());
+
+    let new = snapshot(&SOURCE.replace("0x00 sstore", "0x0100 sstore"));
+    let diffs = new.diff(&old);
+    assert_eq!(diffs.len(), 2);
+    assert_eq!(diffs[0].macro_name, None);
+    assert_eq!(diffs[0].to_string(), "main.huff: 15 -> 14 bytes (-1)");
+    assert_eq!(diffs[1].macro_name.as_deref(), Some("STORE"));
+    assert_eq!(diffs[1].to_string(), "    STORE: 4 -> 3 bytes (-1)");
+
+    // A change to the jump offsets doesn't change the bytes of the macro
+    assert!(diffs.iter().all(|d| d.macro_name.as_deref() != Some("MAIN")));
+
+    let removed = old.diff(&Snapshot::default());
+    assert_eq!(removed.len(), 4);
+    assert_eq!(removed[0].to_string(), "main.huff: removed (14 bytes)");
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "solidity")]
+
+mod common;
+
+use common::compile;
+use huff_core::solidity;
+use huff_utils::prelude::*;
+
+const INTERFACES: &str = r#"
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+import "./Other.sol";
+
+type Price is uint128;
+
+struct Permit {
+    address owner;
+    uint value;
+    Side side;
+}
+
+enum Side { Buy, Sell }
+
+/// @notice A token, with a `function` mentioned in a comment
+interface IERC20 {
+    event Transfer(address indexed from, address indexed to, uint256 amount);
+
+    error InsufficientBalance(uint256 available, string reason);
+
+    function transfer(address to, uint256 amount) external returns (bool);
+
+    /* function mint(address to) external; */
+    function balanceOf(address owner) external view returns (uint256);
+}
+
+interface IMarket is IERC20 {
+    function permit(Permit calldata permit, bytes memory signature) external payable;
+
+    function setPrice(Price price, IERC20 token, Side[] calldata sides) external;
+}
+"#;
+
+#[test]
+fn test_sol_interfaces() {
+    let interfaces = solidity::interfaces(INTERFACES).unwrap();
+    assert_eq!(
+        interfaces.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(),
+        ["IERC20", "IMarket"]
+    );
+
+    // Interfaces inherit the entries of the interfaces they extend
+    let (_, market) = &interfaces[1];
+    assert_eq!(market.as_array().unwrap().len(), 6);
+    assert_eq!(
+        market[4],
+        serde_json::json!({
+            "type": "function",
+            "name": "permit",
+            "inputs": [
+                {
+                    "name": "permit",
+                    "type": "tuple",
+                    "components": [
+                        { "name": "owner", "type": "address" },
+                        { "name": "value", "type": "uint256" },
+                        { "name": "side", "type": "uint8" },
+                    ],
+                },
+                { "name": "signature", "type": "bytes" },
+            ],
+            "outputs": [],
+            "stateMutability": "payable",
+        })
+    );
+    assert_eq!(market[0]["inputs"][0]["indexed"], true);
+    assert_eq!(market[0]["inputs"][2]["indexed"], false);
+}
+
+#[test]
+fn test_sol_import_declares_entries() {
+    let qualified = r#"
+        #include sol "interfaces/IMarket.sol"
+
+        #define macro MAIN() = takes (0) returns (0) {
+            __FUNC_SIG(IERC20.transfer) __FUNC_SIG(IMarket.balanceOf)
+            __FUNC_SIG(IMarket.permit) __FUNC_SIG(IMarket.setPrice)
+            __EVENT_HASH(IERC20.Transfer) __ERROR(IERC20.InsufficientBalance)
+        }
+    "#;
+    let literal = r#"
+        #define error InsufficientBalance(uint256, string)
+
+        #define macro MAIN() = takes (0) returns (0) {
+            __FUNC_SIG("transfer(address,uint256)") __FUNC_SIG("balanceOf(address)")
+            __FUNC_SIG("permit((address,uint256,uint8),bytes)")
+            __FUNC_SIG("setPrice(uint128,address,uint8[])")
+            __EVENT_HASH("Transfer(address,address,uint256)") __ERROR(InsufficientBalance)
+        }
+    "#;
+    let imported =
+        compile(&[("main.huff", qualified), ("interfaces/IMarket.sol", INTERFACES)]).unwrap();
+    let declared = compile(&[("main.huff", literal)]).unwrap();
+    assert_eq!(imported[0].runtime, declared[0].runtime);
+}
+
+#[test]
+fn test_sol_import_errors() {
+    let main = "#include sol \"I.sol\"\n#define macro MAIN() = takes (0) returns (0) { stop }";
+    for (source, reason) in [
+        ("contract Token {}", "no interfaces are declared"),
+        (
+            "interface I { function f(Unknown u) external; }",
+            "unsupported type \"Unknown\" in interface \"I\"",
+        ),
+        (
+            "struct S { S[] children; } interface I { function f(S memory s) external; }",
+            "recursive struct \"S\" in interface \"I\"",
+        ),
+    ] {
+        let e = compile(&[("main.huff", main), ("I.sol", source)]).unwrap_err();
+        let msg = match &*e {
+            CompilerError::FailedCompiles(errors) => match &errors[0] {
+                CompilerError::InvalidAbiImport(msg, _) => msg.clone(),
+                e => panic!("unexpected error: {e:?}"),
+            },
+            e => panic!("unexpected error: {e:?}"),
+        };
+        assert_eq!(msg, reason);
+    }
+}
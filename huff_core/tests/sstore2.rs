@@ -0,0 +1,62 @@
+mod common;
+
+use common::parse;
+use huff_core::{size_report::MAX_RUNTIME_SIZE, sstore2::*};
+use huff_utils::prelude::*;
+
+const DEPLOYER: &str = "0xbebebebebebebebebebebebebebebebebebebebe";
+
+#[test]
+fn test_data_contract_of_section() {
+    let contract = parse("#define data PALETTE = 0xdeadbeef\n#define table CODE { 0x00 }");
+    let data = DataContract::from_section(&contract, "PALETTE").unwrap();
+    assert_eq!(data.data, vec![0xde, 0xad, 0xbe, 0xef]);
+
+    // The bytes follow a `stop`, after the code returning them
+    let artifact = data.artifact().unwrap();
+    assert_eq!(artifact.runtime, "00deadbeef");
+    assert_eq!(artifact.bytecode, format!("{SSTORE2_INITCODE}00deadbeef"));
+    assert_eq!(artifact.file.path, "PALETTE");
+
+    // Code tables aren't data sections
+    for name in ["CODE", "MISSING"] {
+        assert_eq!(
+            DataContract::from_section(&contract, name).unwrap_err(),
+            CompilerError::InvalidDataContract(format!("Missing data section \"{name}\""))
+        );
+    }
+}
+
+#[test]
+fn test_data_contract_size_limit() {
+    let data = DataContract::new("art.bin", vec![0xff; MAX_RUNTIME_SIZE - 1]);
+    assert_eq!(data.artifact().unwrap().runtime.len(), MAX_RUNTIME_SIZE * 2);
+
+    let data = DataContract::new("art.bin", vec![0xff; MAX_RUNTIME_SIZE]);
+    assert_eq!(
+        data.artifact().unwrap_err(),
+        CompilerError::InvalidDataContract(
+            "\"art.bin\" is 24576 bytes, more than the 24575 bytes a contract can store"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn test_data_contract_create2_address() {
+    let data = DataContract::new("PALETTE", vec![0xde, 0xad, 0xbe, 0xef]);
+    let address = "0xba6118CAee8b1905cEeb228eAbc5b76872D5bD33";
+    assert_eq!(data.create2_address(DEPLOYER, "0x0123").unwrap(), address);
+    assert_eq!(data.create2_address(&DEPLOYER[2..], &format!("{:0>64}", "0123")).unwrap(), address);
+
+    for (deployer, salt, message) in [
+        ("0xbebe", "0x00", "Invalid deployer address \"0xbebe\""),
+        (DEPLOYER, "0x", "Invalid salt \"0x\""),
+        (DEPLOYER, "0xzz", "Invalid salt \"0xzz\""),
+    ] {
+        assert_eq!(
+            data.create2_address(deployer, salt).unwrap_err(),
+            CompilerError::InvalidDataContract(message.to_string())
+        );
+    }
+}
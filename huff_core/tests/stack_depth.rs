@@ -0,0 +1,283 @@
+mod common;
+
+use common::parse;
+use huff_codegen::{analysis::prelude::*, *};
+use huff_utils::prelude::*;
+
+#[test]
+fn test_stack_height_through_macros() {
+    let source = r#"
+    #define macro PUSH_TWO() = takes(0) returns(2) {
+        0x01 0x02
+    }
+
+    #define macro APPLY(op) = takes(2) returns(1) {
+        <op>
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        PUSH_TWO()
+        PUSH_TWO()
+        APPLY(add)
+        APPLY(add)
+        pop pop
+    }
+    "#;
+
+    let contract = parse(source);
+    let main = contract.find_macro_by_name("MAIN").unwrap();
+    let res = stack_height(&contract, main);
+    assert_eq!(res.max, 4);
+    assert_eq!(res.chain, vec!["MAIN".to_string(), "PUSH_TWO".to_string()]);
+}
+
+#[test]
+fn test_stack_height_outlined_function() {
+    let source = r#"
+    #define fn PUSH_MANY() = takes(1) returns(1) {
+        0x01 0x02 0x03 pop pop pop
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 PUSH_MANY() pop
+    }
+    "#;
+
+    let contract = parse(source);
+    let main = contract.find_macro_by_name("MAIN").unwrap();
+    let res = stack_height(&contract, main);
+    assert_eq!(res.max, 4);
+    assert_eq!(res.chain, vec!["MAIN".to_string(), "PUSH_MANY".to_string()]);
+}
+
+#[test]
+fn test_stack_too_deep() {
+    let source = r#"
+    #define macro PUSH_EIGHT() = takes(0) returns(8) {
+        0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01
+    }
+
+    #define macro PUSH_SIXTY_FOUR() = takes(0) returns(64) {
+        PUSH_EIGHT() PUSH_EIGHT() PUSH_EIGHT() PUSH_EIGHT()
+        PUSH_EIGHT() PUSH_EIGHT() PUSH_EIGHT() PUSH_EIGHT()
+    }
+
+    #define macro PUSH_FIVE_TWELVE() = takes(0) returns(512) {
+        PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR()
+        PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR()
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        PUSH_FIVE_TWELVE()
+        PUSH_FIVE_TWELVE()
+        0x01
+    }
+    "#;
+
+    let contract = parse(source);
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Err(CodegenError { kind: CodegenErrorKind::StackTooDeep(height, chain), span, .. }) => {
+            assert_eq!(height, STACK_LIMIT + 1);
+            assert_eq!(chain, "MAIN".to_string());
+            assert_eq!(span.0.len(), 1);
+        }
+        _ => panic!("moose"),
+    }
+}
+
+#[test]
+fn test_stack_too_deep_points_at_macro_chain() {
+    let source = r#"
+    #define macro PUSH_EIGHT() = takes(0) returns(8) {
+        0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01
+    }
+
+    #define macro PUSH_SIXTY_FOUR() = takes(0) returns(64) {
+        PUSH_EIGHT() PUSH_EIGHT() PUSH_EIGHT() PUSH_EIGHT()
+        PUSH_EIGHT() PUSH_EIGHT() PUSH_EIGHT() PUSH_EIGHT()
+    }
+
+    #define macro PUSH_FIVE_TWELVE() = takes(0) returns(512) {
+        PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR()
+        PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR()
+    }
+
+    #define macro MAIN() = takes(1) returns(0) {
+        PUSH_FIVE_TWELVE()
+        PUSH_FIVE_TWELVE()
+    }
+    "#;
+
+    let contract = parse(source);
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Err(CodegenError { kind: CodegenErrorKind::StackTooDeep(height, chain), span, .. }) => {
+            assert_eq!(height, STACK_LIMIT + 1);
+            assert_eq!(chain, "MAIN -> PUSH_FIVE_TWELVE -> PUSH_SIXTY_FOUR -> PUSH_EIGHT");
+            // Spans cover each invocation in the chain, followed by the offending push
+            assert!(span.0.len() > 4);
+        }
+        _ => panic!("moose"),
+    }
+}
+
+#[test]
+fn test_opcode_stack_inputs_and_outputs() {
+    let expected = [
+        (Opcode::Stop, 0, 0),
+        (Opcode::Add, 2, 1),
+        (Opcode::Addmod, 3, 1),
+        (Opcode::Calldataload, 1, 1),
+        (Opcode::Push0, 0, 1),
+        (Opcode::Push32, 0, 1),
+        (Opcode::Dup1, 1, 2),
+        (Opcode::Dup16, 16, 17),
+        (Opcode::Swap1, 2, 2),
+        (Opcode::Swap16, 17, 17),
+        (Opcode::Log0, 2, 0),
+        (Opcode::Log4, 6, 0),
+        (Opcode::Mstore, 2, 0),
+        (Opcode::Jumpi, 2, 0),
+        (Opcode::Call, 7, 1),
+        (Opcode::Staticcall, 6, 1),
+        (Opcode::Create2, 4, 1),
+        (Opcode::Return, 2, 0),
+    ];
+    for (opcode, inputs, outputs) in expected {
+        assert_eq!(opcode.stack_inputs(), inputs, "{opcode:?}");
+        assert_eq!(opcode.stack_outputs(), outputs, "{opcode:?}");
+    }
+}
+
+#[test]
+fn test_named_outputs_mismatch() {
+    let source = r#"
+    #define macro DIVMOD() = takes(2) returns(quotient, remainder) {
+        dup2 dup2 mod swap2 div
+    }
+
+    #define macro DOUBLE() = takes(2) returns(x, y) {
+        dup2 dup2 __ECADD()
+    }
+
+    #define macro ONLY_QUOTIENT() = takes(2) returns(quotient, remainder) {
+        div
+    }
+
+    #define macro BRANCHING() = takes(1) returns(result) {
+        skip jumpi
+        0x01
+        skip:
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x02 0x01 DIVMOD() ONLY_QUOTIENT() BRANCHING()
+    }
+    "#;
+
+    let contract = parse(source);
+    let divmod = contract.find_macro_by_name("DIVMOD").unwrap();
+    assert_eq!(mismatched_outputs(&contract, divmod), None);
+
+    // Precompile calls consume their inputs
+    let double = contract.find_macro_by_name("DOUBLE").unwrap();
+    assert_eq!(mismatched_outputs(&contract, double), None);
+
+    // Macros with jumps aren't checked, their height depends on the path taken
+    let branching = contract.find_macro_by_name("BRANCHING").unwrap();
+    assert_eq!(mismatched_outputs(&contract, branching), None);
+
+    let warnings = Codegen::analyze(&contract);
+    let only_quotient = contract.find_macro_by_name("ONLY_QUOTIENT").unwrap();
+    assert_eq!(
+        warnings,
+        vec![CompilerWarning::new(
+            WarningKind::OutputMismatch(
+                "ONLY_QUOTIENT".to_string(),
+                vec!["quotient".to_string(), "remainder".to_string()],
+                1
+            ),
+            only_quotient.span.clone()
+        )]
+    );
+    assert_eq!(
+        warnings[0].kind.to_string(),
+        "Macro \"ONLY_QUOTIENT\" Leaves 1 Stack Item(s) But Returns 2 (quotient, remainder)"
+    );
+}
+
+#[test]
+fn test_growing_loop() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 calldataload
+        grow:
+            0x01 swap1
+            0x01 swap1 sub
+            dup1 grow jumpi
+        pop
+        0x0a
+        steady:
+            0x01 swap1 sub
+            dup1 steady jumpi
+        stop
+    }
+    "#;
+
+    let contract = parse(source);
+    let main = contract.find_macro_by_name("MAIN").unwrap();
+
+    // Only the loop leaving an item behind on every iteration grows the stack
+    let res = stack_height(&contract, main);
+    assert_eq!(res.growing_loops.len(), 1);
+    assert_eq!(res.growing_loops[0].0, "grow");
+    assert_eq!(res.growing_loops[0].1, 1);
+
+    let warnings = Codegen::analyze(&contract);
+    assert_eq!(
+        warnings.iter().map(|w| w.kind.clone()).collect::<Vec<WarningKind>>(),
+        vec![WarningKind::GrowingLoop("MAIN".to_string(), "grow".to_string(), 1)]
+    );
+    assert_eq!(
+        warnings[0].kind.to_string(),
+        "Loop At Label \"grow\" In Macro \"MAIN\" Adds 1 Stack Item(s) Per Iteration"
+    );
+
+    // The loop can't be proven to overflow, so it doesn't fail compilation
+    assert!(Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).is_ok());
+}
+
+#[test]
+fn test_exclusive_branches_are_not_added_up() {
+    let source = r#"
+    #define macro PUSH_EIGHT() = takes(0) returns(8) {
+        0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01
+    }
+
+    #define macro PUSH_SIXTY_FOUR() = takes(0) returns(64) {
+        PUSH_EIGHT() PUSH_EIGHT() PUSH_EIGHT() PUSH_EIGHT()
+        PUSH_EIGHT() PUSH_EIGHT() PUSH_EIGHT() PUSH_EIGHT()
+    }
+
+    #define macro PUSH_FIVE_TWELVE() = takes(0) returns(512) {
+        PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR()
+        PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR() PUSH_SIXTY_FOUR()
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 calldataload other jumpi
+        PUSH_FIVE_TWELVE() PUSH_SIXTY_FOUR()
+        0x00 0x00 return
+        other:
+            PUSH_FIVE_TWELVE() PUSH_SIXTY_FOUR()
+            0x00 0x00 revert
+    }
+    "#;
+
+    let contract = parse(source);
+    let main = contract.find_macro_by_name("MAIN").unwrap();
+
+    // Each branch starts from the height of the jump, rather than where the other one ended
+    let res = stack_height(&contract, main);
+    assert_eq!(res.max, 578);
+    assert!(Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).is_ok());
+}
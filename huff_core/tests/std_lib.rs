@@ -0,0 +1,141 @@
+mod common;
+
+use huff_core::{std_lib::*, Compiler};
+use huff_utils::prelude::*;
+
+const TOKEN: &str = r#"
+    #include "std/erc20.huff"
+    #include "std/ownable.huff"
+
+    #define function mint(address,uint256) nonpayable returns ()
+
+    #define macro CONSTRUCTOR() = takes(0) returns(0) {
+        OWNABLE_CONSTRUCTOR()
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 calldataload 0xe0 shr
+        dup1 __FUNC_SIG(mint) eq mint jumpi
+        ERC20_MAIN()
+        OWNABLE_MAIN()
+        0x00 dup1 revert
+
+        mint:
+            ONLY_OWNER()
+            __CALLDATA_LOAD_ARG(1, uint256) __CALLDATA_LOAD_ARG(0, address)
+            ERC20_MINT()
+            stop
+    }
+"#;
+
+fn compiler(source: &str, std_lib: StdLib) -> Compiler<'static> {
+    let mut compiler = common::compiler(&[("contracts/main.huff", source)]);
+    compiler.std_lib = std_lib;
+    compiler
+}
+
+#[test]
+fn test_std_version_file_matches() {
+    assert_eq!(include_str!("../std/VERSION").trim(), STD_VERSION);
+}
+
+#[test]
+fn test_std_includes_compile() {
+    let artifacts = compiler(TOKEN, StdLib::default()).execute().unwrap();
+    let abi = artifacts[0].abi.as_ref().unwrap();
+    for function in ["transfer", "transferFrom", "approve", "owner", "transferOwnership", "mint"] {
+        assert!(abi.functions.contains_key(function), "missing {function}");
+    }
+    assert!(abi.events.contains_key("Transfer"));
+    assert!(abi.events.contains_key("OwnershipTransferred"));
+}
+
+#[test]
+fn test_std_library_macros_compile() {
+    let source = r#"
+        #include "std/math.huff"
+        #include "std/reentrancy_guard.huff"
+        #include "std/safe_transfer_lib.huff"
+
+        #define macro MAIN() = takes(0) returns(0) {
+            REENTRANCY_GUARD_LOCK()
+            0x04 calldataload 0x24 calldataload SAFE_ADD()
+            0x44 calldataload SAFE_SUB() 0x02 SAFE_MUL() 0x02 SAFE_DIV()
+            0x01 MIN() 0x01 MAX()
+            0x64 calldataload caller 0x84 calldataload SAFE_TRANSFER()
+            0x64 calldataload address caller 0x84 calldataload SAFE_TRANSFER_FROM()
+            0x64 calldataload caller 0x84 calldataload SAFE_APPROVE()
+            caller SAFE_TRANSFER_ETH()
+            REENTRANCY_GUARD_UNLOCK()
+        }
+    "#;
+    assert!(compiler(source, StdLib::default()).execute().is_ok());
+}
+
+#[test]
+fn test_missing_std_file() {
+    let source = r#"
+        #include "std/missing.huff"
+
+        #define macro MAIN() = takes(0) returns(0) {}
+    "#;
+    match compiler(source, StdLib::default()).execute() {
+        Err(e) => assert_eq!(
+            *e,
+            CompilerError::FileUnpackError(UnpackError::MissingFile(
+                "std/missing.huff".to_string()
+            ))
+        ),
+        Ok(_) => panic!("expected a missing file error"),
+    }
+}
+
+#[test]
+fn test_std_version_pin() {
+    let pinned = StdLib::new(None, Some(STD_VERSION.to_string()));
+    assert!(compiler(TOKEN, pinned).execute().is_ok());
+
+    let mismatched = StdLib::new(None, Some("9.9.9".to_string()));
+    match compiler(TOKEN, mismatched).execute() {
+        Err(e) => match &*e {
+            CompilerError::StdLibError(msg) => assert!(msg.contains("9.9.9"), "{msg}"),
+            e => panic!("unexpected error: {e:?}"),
+        },
+        Ok(_) => panic!("expected a pinned version error"),
+    }
+}
+
+#[test]
+fn test_std_override_path() {
+    let dir = std::env::temp_dir().join(format!("huff-std-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(STD_VERSION_FILE), "0.2.0\n").unwrap();
+    std::fs::write(
+        dir.join("answer.huff"),
+        "#define macro ANSWER() = takes(0) returns(1) { 0x2a }",
+    )
+    .unwrap();
+    let source = r#"
+        #include "std/answer.huff"
+
+        #define macro MAIN() = takes(0) returns(0) {
+            ANSWER()
+        }
+    "#;
+    let path = Some(dir.to_string_lossy().to_string());
+
+    let artifacts =
+        compiler(source, StdLib::new(path.clone(), Some("0.2.0".to_string()))).execute().unwrap();
+    assert_eq!(artifacts[0].runtime, "602a");
+
+    // The override replaces the embedded library
+    assert!(compiler(TOKEN, StdLib::new(path.clone(), None)).execute().is_err());
+
+    // Pins are checked against the override's version
+    match compiler(source, StdLib::new(path, Some(STD_VERSION.to_string()))).execute() {
+        Err(e) => assert!(matches!(&*e, CompilerError::StdLibError(_))),
+        Ok(_) => panic!("expected a pinned version error"),
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,217 @@
+mod common;
+
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+    #include "./Storage.huff"
+
+    #define constant OWNER = FREE_STORAGE_POINTER(slot=0)
+    #define constant BALANCE = FREE_STORAGE_POINTER()
+
+    #define macro MAIN() = takes(0) returns(0) {
+        [BALANCE] sload [OWNER] sload [TOTAL] sload
+    }
+"#;
+
+fn compiler(source: &str, storage: &str) -> Compiler<'static> {
+    common::compiler(&[("main.huff", source), ("Storage.huff", storage)])
+}
+
+fn layout_file(contents: &str) -> String {
+    let path = std::env::temp_dir().join(format!("huff-layout-{}.json", uuid::Uuid::new_v4()));
+    std::fs::write(&path, contents).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+fn check_errors(compiler: &Compiler) -> Vec<String> {
+    match compiler.execute() {
+        Err(e) => match &*e {
+            CompilerError::IncompatibleStorageLayout(incompatibilities) => {
+                incompatibilities.clone()
+            }
+            e => panic!("unexpected error: {e:?}"),
+        },
+        Ok(_) => vec![],
+    }
+}
+
+#[test]
+fn test_artifact_reports_storage_layout() {
+    let storage = "#define constant TOTAL = FREE_STORAGE_POINTER()";
+    let artifact = compiler(SOURCE, storage).execute().unwrap().remove(0);
+
+    // Included constants are declared after those of the including file, around the pinned slot
+    let slots = artifact
+        .storage_layout
+        .iter()
+        .map(|s| (s.name.as_str(), bytes32_to_string(&s.slot, true)))
+        .collect::<Vec<(&str, String)>>();
+    assert_eq!(
+        slots,
+        [
+            ("OWNER", "0x00".to_string()),
+            ("BALANCE", "0x01".to_string()),
+            ("TOTAL", "0x02".to_string())
+        ]
+    );
+    assert_eq!(artifact.runtime, "600154600054600254");
+
+    let json = serde_json::to_value(&*artifact).unwrap();
+    assert_eq!(json["storageLayout"][2], serde_json::json!({ "name": "TOTAL", "slot": "0x02" }));
+
+    // The layout round trips through the artifact
+    let parsed: Artifact = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.storage_layout, artifact.storage_layout);
+}
+
+#[test]
+fn test_storage_check_accepts_compatible_layouts() {
+    let storage = "#define constant TOTAL = FREE_STORAGE_POINTER()";
+    let previous = compiler(SOURCE, storage).execute().unwrap().remove(0);
+
+    // New variables may be appended, and the previous artifact itself is a valid layout
+    let appended = format!("{storage}\n#define constant EXTRA = FREE_STORAGE_POINTER(slot=0x10)");
+    let mut compiler = compiler(SOURCE, &appended);
+    compiler.storage_check_path = Some(layout_file(&serde_json::to_string(&*previous).unwrap()));
+    assert!(check_errors(&compiler).is_empty());
+
+    // Removing a variable leaves its slot unused
+    compiler.storage_check_path = Some(layout_file(
+        r#"[{ "name": "OWNER", "slot": "0x00" }, { "name": "GONE", "slot": "0x05" }]"#,
+    ));
+    assert!(check_errors(&compiler).is_empty());
+}
+
+#[test]
+fn test_storage_check_rejects_incompatible_layouts() {
+    let previous = layout_file(
+        r#"[
+            { "name": "OWNER", "slot": "0x00" },
+            { "name": "BALANCE", "slot": "0x01" },
+            { "name": "SUPPLY", "slot": "0x02" },
+            { "name": "TOTAL", "slot": "0x03" }
+        ]"#,
+    );
+
+    // A new variable declared in place of a removed one takes over its slot
+    let replaced = r#"
+        #define constant PAUSED = FREE_STORAGE_POINTER()
+        #define constant TOTAL = FREE_STORAGE_POINTER()
+    "#;
+    let mut compiler = compiler(SOURCE, replaced);
+    compiler.storage_check_path = Some(previous.clone());
+    assert_eq!(
+        check_errors(&compiler),
+        ["main.huff: \"PAUSED\" reuses slot 0x02 of the removed \"SUPPLY\""]
+    );
+
+    // Removing a variable shifts the slots of those declared after it
+    let mut compiler = self::compiler(SOURCE, "#define constant TOTAL = FREE_STORAGE_POINTER()");
+    compiler.storage_check_path = Some(previous.clone());
+    let out = std::env::temp_dir().join(format!("huff-layout-{}", uuid::Uuid::new_v4()));
+    compiler.output = Some(out.to_string_lossy().to_string());
+    assert_eq!(check_errors(&compiler), ["main.huff: \"TOTAL\" moved from slot 0x03 to slot 0x02"]);
+
+    // Nothing is exported for an incompatible layout
+    assert!(!out.exists());
+
+    // Pinning the slot of a new variable keeps the layout compatible
+    let pinned = r#"
+        #define constant PAUSED = FREE_STORAGE_POINTER(slot=0x20)
+        #define constant SUPPLY = FREE_STORAGE_POINTER()
+        #define constant TOTAL = FREE_STORAGE_POINTER()
+    "#;
+    let mut compiler = self::compiler(SOURCE, pinned);
+    compiler.storage_check_path = Some(previous);
+    assert!(check_errors(&compiler).is_empty());
+}
+
+#[test]
+fn test_storage_check_of_named_contracts() {
+    let source = r#"
+        #define contract Vault {
+            #define constant SHARES = FREE_STORAGE_POINTER()
+            #define macro MAIN() = takes(0) returns(0) { [SHARES] sload }
+        }
+        #define contract Token {
+            #define constant SUPPLY = FREE_STORAGE_POINTER()
+            #define constant SHARES = FREE_STORAGE_POINTER()
+            #define macro MAIN() = takes(0) returns(0) { [SHARES] sload }
+        }
+    "#;
+
+    // The layout of a named contract's artifact is only checked against that contract
+    let mut compiler = compiler(source, "");
+    compiler.storage_check_path = Some(layout_file(
+        r#"{ "contractName": "Vault", "storageLayout": [{ "name": "SHARES", "slot": "0x00" }] }"#,
+    ));
+    assert!(check_errors(&compiler).is_empty());
+
+    compiler.storage_check_path = Some(layout_file(r#"[{ "name": "SHARES", "slot": "0x00" }]"#));
+    assert_eq!(
+        check_errors(&compiler),
+        ["main.huff:Token: \"SHARES\" moved from slot 0x00 to slot 0x01"]
+    );
+}
+
+#[test]
+fn test_storage_check_errors() {
+    let storage = "#define constant TOTAL = FREE_STORAGE_POINTER()";
+    let mut compiler = compiler(SOURCE, storage);
+
+    compiler.storage_check_path = Some(layout_file(r#"[{ "name": "OWNER", "slot": "zz" }]"#));
+    let errors = check_errors(&compiler);
+    assert!(errors[0].starts_with("Invalid storage layout"), "{errors:?}");
+
+    compiler.storage_check_path = Some("./missing-layout.json".to_string());
+    match compiler.execute() {
+        Err(e) => assert!(matches!(
+            &*e,
+            CompilerError::FileUnpackError(UnpackError::MissingFile(p)) if p == "./missing-layout.json"
+        )),
+        Ok(_) => panic!("expected the missing layout file to fail"),
+    }
+}
+
+#[test]
+fn test_storage_slot_collisions() {
+    let source = r#"
+        #define constant IMPLEMENTATION = STORAGE_SLOT(0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc)
+        #define constant ADMIN = STORAGE_SLOT(0xb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103)
+        #define constant OWNER = FREE_STORAGE_POINTER()
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [IMPLEMENTATION] sload [OWNER] sload
+        }
+    "#;
+    let artifact = compiler(source, "").execute().unwrap().remove(0);
+    assert_eq!(
+        artifact.runtime,
+        "7f360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc54600054"
+    );
+
+    // Pinning another constant to a used slot fails compilation
+    let colliding =
+        format!("{source}\n#define constant BEACON = FREE_STORAGE_POINTER(slot=0xb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103)");
+    match compiler(&colliding, "").execute() {
+        Err(e) => match &*e {
+            CompilerError::FailedCompiles(errors) => match &errors[0] {
+                CompilerError::CodegenError(ce) => {
+                    assert_eq!(
+                        ce.kind,
+                        CodegenErrorKind::StorageSlotCollision(
+                            vec!["ADMIN".to_string(), "BEACON".to_string()],
+                            "0xb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103"
+                                .to_string()
+                        )
+                    );
+                    assert!(ce.span.0.iter().all(|s| s.file.is_some()));
+                }
+                e => panic!("unexpected error: {e:?}"),
+            },
+            e => panic!("unexpected error: {e:?}"),
+        },
+        Ok(_) => panic!("expected a storage slot collision"),
+    }
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use huff_utils::prelude::*;
+use std::sync::Arc;
+
+fn compile(source: &str) -> Result<Vec<Arc<Artifact>>, Arc<CompilerError<'static>>> {
+    let mut compiler = common::compiler(&[("main.huff", source)]);
+    compiler.strict_jumps = true;
+    compiler.execute()
+}
+
+fn dynamic_jump(e: &CompilerError) -> Option<String> {
+    match e {
+        CompilerError::FailedCompiles(errors) => errors.iter().find_map(dynamic_jump),
+        CompilerError::CodegenError(CodegenError {
+            kind: CodegenErrorKind::DynamicJump(name),
+            ..
+        }) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_jumps_to_labels() {
+    let source = r#"
+        #define macro JUMP_TO(dest) = takes (0) returns (0) {
+            <dest> jump
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x00 calldataload done jumpi
+            JUMP_TO(done)
+            done:
+                stop
+        }
+    "#;
+    assert!(compile(source).is_ok());
+}
+
+#[test]
+fn test_dynamic_jumps() {
+    // The destination is computed at runtime
+    let source = r#"
+        #define macro RETURN() = takes (1) returns (0) {
+            jump
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x00 calldataload RETURN()
+        }
+    "#;
+    let e = compile(source).unwrap_err();
+    assert_eq!(dynamic_jump(&e).as_deref(), Some("RETURN"));
+
+    // Jumps to literal offsets
+    let source = r#"
+        #define macro MAIN() = takes (0) returns (0) {
+            0x04 jump
+            jumpdest stop
+        }
+    "#;
+    let e = compile(source).unwrap_err();
+    assert_eq!(dynamic_jump(&e).as_deref(), Some("MAIN"));
+
+    // Constructors are checked too
+    let source = r#"
+        #define macro CONSTRUCTOR() = takes (0) returns (0) {
+            0x00 calldataload jump
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            stop
+        }
+    "#;
+    let e = compile(source).unwrap_err();
+    assert_eq!(dynamic_jump(&e).as_deref(), Some("CONSTRUCTOR"));
+}
@@ -0,0 +1,67 @@
+mod common;
+
+fn compile(source: &str) -> String {
+    let compiler = common::compiler(&[("main.huff", source)]);
+    compiler.execute().unwrap()[0].runtime.clone()
+}
+
+#[test]
+fn test_struct_encoding() {
+    // Returns `abi.encode(address(0xbeef), bytes("abc"))`
+    let source = r#"
+        #define struct Order(address maker, bytes data)
+
+        #define macro MAIN() = takes (0) returns (0) {
+            // The order at 0x00, its data at 0x40
+            0xbeef 0x00 mstore
+            0x40 0x20 mstore
+            0x03 0x40 mstore
+            0x616263 0xe8 shl 0x60 mstore
+
+            0x80 0x00 Order_ENCODE()    // [end]
+            0x80 swap1 sub 0x80 return
+        }
+    "#;
+    let runtime = compile(source);
+
+    // The head holds the maker and the offset of the data, then the data is copied to the tail a
+    // word at a time, and the bytes following "abc" in its last word are cleared
+    assert_eq!(
+        runtime,
+        [
+            "61beef600052604060205260036040526261626360e81b60605260806000",
+            "816040018151835282810383602001528160200151805160",
+            "1f0160051c60051b60200160005b8281015184820152602001818110610043575081516000",
+            "03601f1660031b6020828501038051821c821b905250905001915050",
+            "608090036080f3",
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn test_struct_decoding() {
+    // Decodes the calldata as a struct at 0x400, returning its amount
+    let source = r#"
+        #define struct Order(address maker, uint256 amount, bytes data)
+
+        #define macro MAIN() = takes (0) returns (0) {
+            calldatasize 0x00 0x00 calldatacopy
+            0x400 0x00 Order_DECODE()
+            0x420 mload 0x00 mstore
+            0x20 0x00 return
+        }
+    "#;
+    let runtime = compile(source);
+
+    // The maker and amount are copied, and the data points into the encoding at its offset
+    assert_eq!(
+        runtime,
+        [
+            "366000600037610400600080518252806020015182602001528080604001510182604001525050",
+            "61042051600052602060",
+            "00f3"
+        ]
+        .concat()
+    );
+}
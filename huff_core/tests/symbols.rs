@@ -0,0 +1,83 @@
+mod common;
+
+use common::artifact;
+use huff_utils::prelude::*;
+
+fn activation(name: &str, start: usize, end: usize, labels: &[(&str, usize)]) -> MacroActivation {
+    MacroActivation {
+        name: name.to_string(),
+        start,
+        end,
+        labels: labels.iter().map(|(l, o)| (l.to_string(), *o)).collect(),
+    }
+}
+
+#[test]
+fn test_symbols_of_nested_macros_and_labels() {
+    let source = r#"
+        #define macro LOOP() = takes (0) returns (0) {
+            top:
+                0x01 top jumpi
+        }
+
+        #define macro ADD() = takes (2) returns (1) {
+            add
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x01 0x02 ADD()
+            LOOP()
+            0x03 0x04 ADD()
+            done jump
+            done:
+                stop
+        }
+    "#;
+    let artifact = artifact(source);
+
+    // 6001 6002 01 | 5b 6001 610005 57 | 6003 6004 01 | 610015 56 5b 00
+    assert_eq!(artifact.runtime, "60016002015b6001610005576003600401610015565b00");
+    assert_eq!(
+        artifact.symbols,
+        vec![
+            activation("MAIN", 0, 23, &[("done", 21)]),
+            activation("ADD", 4, 5, &[]),
+            activation("LOOP", 5, 12, &[("top", 5)]),
+            activation("ADD", 16, 17, &[]),
+        ]
+    );
+}
+
+#[test]
+fn test_symbols_of_outlined_functions() {
+    let source = r#"
+        #define fn DOUBLE() = takes (1) returns (1) {
+            dup1 add
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x01 DOUBLE()
+            stop
+        }
+    "#;
+    let artifact = artifact(source);
+    let names = artifact.symbols.iter().map(|a| a.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["MAIN", "DOUBLE"]);
+
+    // The function's activation starts after its JUMPDEST, at the end of MAIN
+    let main = &artifact.symbols[0];
+    let double = &artifact.symbols[1];
+    assert_eq!(double.start, main.end + 1);
+    assert_eq!(double.end, double.start + 2);
+    assert_eq!(&artifact.runtime[double.start * 2..double.end * 2], "8001");
+}
+
+#[test]
+fn test_symbols_are_serialized() {
+    let artifact = artifact("#define macro MAIN() = takes (0) returns (0) { stop }");
+    let json = serde_json::to_value(artifact.as_ref()).unwrap();
+    assert_eq!(json["symbols"], serde_json::json!([{ "macro": "MAIN", "start": 0, "end": 1 }]));
+
+    let parsed: Artifact = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.symbols, artifact.symbols);
+}
@@ -3,12 +3,14 @@ use huff_lexer::Lexer;
 use huff_parser::Parser;
 use huff_utils::{
     error::CodegenErrorKind,
-    prelude::{FullFileSource, Token},
+    prelude::{EVMVersion, FullFileSource, Token},
 };
 
 #[test]
 fn test_invocation_should_fail() {
     let source: &str = r#"
+        #define function test1() nonpayable returns ()
+
         #define test MY_TEST() = takes (0) returns (0) {
             0x00 0x01 eq
         }
@@ -42,7 +44,7 @@ fn test_invocation_should_fail() {
 
     // Have the Codegen create the runtime bytecode. Should throw an error because test
     // invocation is not allowed.
-    match Codegen::generate_main_bytecode(&contract, None) {
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
         Ok(_) => panic!("Expected an error"),
         Err(e) => {
             assert_eq!(
@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+
+use huff_codegen::*;
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_typed_constants_codegen() {
+    let source: &str = r#"
+        #define constant OWNER: address = 0x000000000000000000000000000000000000dEaD
+        #define constant SELECTOR: bytes4 = 0xa9059cbb
+        #define constant SMALL: uint8 = 0x05
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [OWNER] [SELECTOR] [SMALL]
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // Create main bytecode
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+
+    // Typed constants are pushed with the minimal push size
+    assert_eq!(main_bytecode, "61dead63a9059cbb6005");
+}
+
+#[test]
+fn test_typed_constant_override_mismatch() {
+    let source: &str = r#"
+        #define constant SMALL: uint8 = 0x05
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [SMALL]
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Override the constant with a value that does not fit its type
+    let mut overrides = BTreeMap::new();
+    overrides.insert("SMALL", str_to_bytes32("0100"));
+    contract.add_override_constants(&Some(overrides));
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // Create main bytecode
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Err(CodegenError { kind: CodegenErrorKind::ConstantTypeMismatch(name), .. }) => {
+            assert_eq!(name, "SMALL")
+        }
+        _ => panic!("moose"),
+    }
+}
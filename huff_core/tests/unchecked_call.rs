@@ -0,0 +1,137 @@
+use huff_codegen::Codegen;
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::*;
+
+fn warnings(source: &str) -> Vec<CompilerWarning> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut contract = Parser::new(tokens, None).parse().unwrap();
+    contract.derive_storage_pointers();
+    Codegen::analyze(&contract)
+}
+
+#[test]
+fn test_popped_call_flag() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            0x00 0x00 0x00 0x00 selfbalance caller gas call
+            pop
+            stop
+        }
+    "#;
+    let warnings = warnings(source);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].kind,
+        WarningKind::UncheckedCall("MAIN".to_string(), "call".to_string())
+    );
+    // The span is the call's, followed by the pop's
+    let spans = &warnings[0].span.0;
+    assert_eq!(spans.len(), 2);
+    assert_eq!(&source[spans[0].start..spans[0].end], "call");
+    assert_eq!(&source[spans[1].start..spans[1].end], "pop");
+}
+
+#[test]
+fn test_popped_call_flag_through_swaps() {
+    // The flag is swapped below the address, then popped after it
+    let source = r#"
+        #define macro MAIN() = takes(1) returns(0) {
+            0x00 0x00 0x00 0x00 dup5 gas staticcall
+            swap1 pop pop
+            stop
+        }
+    "#;
+    assert_eq!(
+        warnings(source).iter().map(|w| w.kind.clone()).collect::<Vec<_>>(),
+        vec![WarningKind::UncheckedCall("MAIN".to_string(), "staticcall".to_string())]
+    );
+}
+
+#[test]
+fn test_checked_call_flags() {
+    let sources = [
+        // Reverting unless the call succeeds
+        r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            0x00 0x00 0x00 0x00 0x00 caller gas call
+            success jumpi
+            0x00 dup1 revert
+            success:
+                stop
+        }
+        "#,
+        // Checking a copy of the flag
+        r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            0x00 0x00 0x00 0x00 caller gas delegatecall
+            dup1 iszero pop pop
+            stop
+        }
+        "#,
+        // Returning the flag
+        r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            0x00 0x00 0x00 0x00 caller gas delegatecall
+            0x00 mstore 0x20 0x00 return
+        }
+        "#,
+        // Leaving the flag to the invoked macro
+        r#"
+        #define macro CHECK() = takes(1) returns(0) {
+            iszero fail jumpi
+            stop
+            fail:
+                0x00 dup1 revert
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x00 0x00 0x00 0x00 caller gas delegatecall
+            CHECK()
+        }
+        "#,
+    ];
+    for source in sources {
+        assert!(warnings(source).is_empty(), "{source}");
+    }
+}
+
+#[test]
+fn test_suppressed_call_flag() {
+    for comment in [
+        "call // huff-ignore: unchecked-call\n pop",
+        "call pop // huff-ignore: unchecked-call",
+        "call pop /* huff-ignore: dead-store, unchecked-call */",
+    ] {
+        let source = format!(
+            "#define macro MAIN() = takes(0) returns(0) {{\n 0x00 0x00 0x00 0x00 0x00 caller gas {comment}\n stop\n}}"
+        );
+        assert!(warnings(&source).is_empty(), "{source}");
+    }
+
+    // Comments silence only the warnings they name, of the code they follow
+    for comment in
+        ["call pop // huff-ignore: dead-store", "call pop stop // huff-ignore: unchecked-call"]
+    {
+        let source = format!(
+            "#define macro MAIN() = takes(0) returns(0) {{\n 0x00 0x00 0x00 0x00 0x00 caller gas {comment}\n}}"
+        );
+        assert_eq!(warnings(&source).len(), 1, "{source}");
+    }
+}
+
+#[test]
+fn test_suppression_comments() {
+    let span = Span::new(0..4, None);
+    assert_eq!(
+        Suppression::from_comment("// huff-ignore: unchecked-call, dead-store", span.clone()),
+        Some(Suppression {
+            warnings: vec!["unchecked-call".to_string(), "dead-store".to_string()],
+            span: span.clone()
+        })
+    );
+    for comment in ["// call the owner", "// huff-ignore:", "/* huff-ignore: unchecked-call"] {
+        assert_eq!(Suppression::from_comment(comment, span.clone()), None, "{comment}");
+    }
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use common::parse;
+use huff_codegen::*;
+use huff_utils::prelude::*;
+use std::sync::Arc;
+
+#[test]
+fn test_unreachable_after_terminator() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 0x00 revert
+        0x01 0x02
+    }
+    "#;
+
+    let contract = parse(source);
+    let warnings = Codegen::analyze(&contract);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::UnreachableCode("MAIN".to_string()));
+    assert_eq!(warnings[0].span.0.len(), 2);
+}
+
+#[test]
+fn test_unreachable_within_label() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 calldataload one jumpi
+        0x00 0x00 revert
+
+        one:
+            0x00 0x00 return
+            0x01 0x02 add
+        two:
+            stop
+    }
+    "#;
+
+    let contract = parse(source);
+    let warnings = Codegen::analyze(&contract);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::UnreachableCode("MAIN".to_string()));
+    assert_eq!(warnings[0].span.0.len(), 3);
+}
+
+#[test]
+fn test_labels_after_terminator_are_reachable() {
+    let source = r#"
+    #define macro CONTINUE() = takes(0) returns(0) {
+        continue:
+            0x01
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        end jump
+        end:
+            stop
+    }
+
+    #define macro OTHER() = takes(0) returns(0) {
+        continue jump
+        CONTINUE()
+    }
+    "#;
+
+    let contract = parse(source);
+    assert!(Codegen::analyze(&contract).is_empty());
+}
+
+#[test]
+fn test_compiler_analyze_deduplicates_imported_macros() {
+    let source_main = r#"
+    #include "../lib/halt.huff"
+
+    #define macro MAIN() = takes(0) returns(0) {
+        HALT()
+    }
+    "#;
+
+    let source_halt = r#"
+    #define macro HALT() = takes(0) returns(0) {
+        stop
+        0x01
+    }
+    "#;
+
+    // Both files are compiled, so HALT is analyzed once through each of them
+    let files = [("contracts/main.huff", source_main), ("lib/halt.huff", source_halt)];
+    let mut compiler = common::compiler(&files);
+    compiler.sources = Arc::new(files.iter().map(|(path, _)| path.to_string()).collect());
+
+    let warnings = compiler.analyze().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::UnreachableCode("HALT".to_string()));
+}
@@ -19,7 +19,7 @@ fn test_verbatim() {
     contract.derive_storage_pointers();
 
     // Get main bytecode with verbatim
-    match Codegen::generate_main_bytecode(&contract, None) {
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
         Ok(mb) => assert_eq!(mb, "1234567890abcdef".to_string()),
         Err(_) => panic!("moose"),
     }
@@ -41,5 +41,74 @@ fn test_verbatim_invalid_hex() {
     contract.derive_storage_pointers();
 
     // Expect failure to generate bytecode with verbatim
-    assert!(Codegen::generate_main_bytecode(&contract, None).is_err());
+    assert!(Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).is_err());
+}
+
+#[test]
+fn test_verbatim_string_with_prefix() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        __VERBATIM("0x0060ff")
+    }
+    "#;
+
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    // Leading zero bytes are preserved when passed as a string
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Ok(mb) => assert_eq!(mb, "0060ff".to_string()),
+        Err(_) => panic!("moose"),
+    }
+}
+
+#[test]
+fn test_verbatim_odd_length_hex() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        __VERBATIM("60f")
+    }
+    "#;
+
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    // Odd length hex would have to be modified to be injected
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Err(CodegenError { kind: CodegenErrorKind::InvalidHex(h), .. }) => assert_eq!(h, "60f"),
+        _ => panic!("moose"),
+    }
+}
+
+#[test]
+fn test_verbatim_offset_accounting() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        __VERBATIM("5f5f5f")
+        label jump
+        label:
+            stop
+    }
+    "#;
+
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    // The label offset accounts for the injected bytes
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Ok(mb) => assert_eq!(mb, "5f5f5f610007565b00".to_string()),
+        Err(_) => panic!("moose"),
+    }
 }
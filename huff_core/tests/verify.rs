@@ -0,0 +1,80 @@
+mod common;
+
+use huff_core::{
+    compile_str,
+    playground::PlaygroundSettings,
+    verify::{VerifyPayload, HUFF_CODE_FORMAT},
+    VERSION,
+};
+use std::{collections::HashMap, sync::Arc};
+
+const SOURCE: &str = r#"
+    #include "../lib/owner.huff"
+
+    #define function constructor(uint256) nonpayable returns ()
+
+    #define macro CONSTRUCTOR() = takes(0) returns(0) {
+        SET_OWNER()
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 sload 0x00 mstore
+        0x20 0x00 return
+    }
+"#;
+
+const OWNER: &str = r#"
+    #define macro SET_OWNER() = takes(0) returns(0) {
+        caller 0x00 sstore
+    }
+"#;
+
+fn compile() -> Arc<huff_utils::prelude::Artifact> {
+    let mut compiler =
+        common::compiler(&[("contracts/Owned.huff", SOURCE), ("lib/owner.huff", OWNER)]);
+    compiler.construct_args = Some(vec!["42".to_string()]);
+    compiler.execute().unwrap().remove(0)
+}
+
+#[test]
+fn test_verify_payload() {
+    let artifact = compile();
+    let address = "0x646dB8ffC21e7ddc2B6327448dd9Fa560Df41087".to_string();
+    let payload = VerifyPayload::new(&artifact, Some(address.clone()));
+
+    assert_eq!(payload.contract_address, Some(address));
+    assert_eq!(payload.contract_name, "Owned");
+    assert_eq!(payload.code_format, HUFF_CODE_FORMAT);
+    assert_eq!(payload.compiler_version, format!("huffc-v{VERSION}"));
+    assert_eq!(payload.optimization_used, "0");
+    assert_eq!(payload.evm_version, "cancun");
+    assert_eq!(payload.constructor_arguments, format!("{:064x}", 42));
+
+    // The includes are flattened into the source
+    assert!(payload.source_code.contains(r#"// #include "../lib/owner.huff""#));
+    assert!(payload.source_code.contains("caller 0x00 sstore"));
+
+    // Recompiling the flattened source yields the same code
+    let recompiled = compile_str(&payload.source_code, &PlaygroundSettings::default());
+    assert!(recompiled.errors.is_empty(), "{:?}", recompiled.errors);
+    assert_eq!(recompiled.runtime.as_ref(), Some(&artifact.runtime));
+    assert_eq!(
+        format!("{}{}", recompiled.bytecode.unwrap(), payload.constructor_arguments),
+        artifact.bytecode
+    );
+}
+
+#[test]
+fn test_verify_payload_fields() {
+    let payload = VerifyPayload::new(&compile(), None);
+    let fields = payload.form_fields().into_iter().collect::<HashMap<String, String>>();
+    assert_eq!(fields["module"], "contract");
+    assert_eq!(fields["action"], "verifysourcecode");
+    assert_eq!(fields["constructorArguements"], format!("{:064x}", 42));
+    assert!(!fields.contains_key("contractaddress"));
+    assert!(fields["compilerSettings"].starts_with('{'));
+
+    // Submitting requires the deployed address
+    let err = payload.submit("http://127.0.0.1:0", "KEY").unwrap_err();
+    assert_eq!(err.0, "The contract address is required to submit");
+}
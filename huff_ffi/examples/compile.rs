@@ -0,0 +1,14 @@
+//! Compiles the source given as the first argument through the C interface, printing the output
+
+use huff_ffi::{huff_compile, huff_string_free};
+use std::ffi::{CStr, CString};
+
+fn main() {
+    let source = CString::new(std::env::args().nth(1).unwrap_or_default()).unwrap();
+    // SAFETY: the source is a nul terminated string, and the output is freed once
+    unsafe {
+        let output = huff_compile(source.as_ptr(), std::ptr::null());
+        println!("{}", CStr::from_ptr(output).to_string_lossy());
+        huff_string_free(output);
+    }
+}
@@ -0,0 +1,39 @@
+/*
+ * C interface to the Huff compiler, see huff_ffi/README.md.
+ *
+ * Strings returned by huff_compile are owned by the caller and must be freed with
+ * huff_string_free.
+ */
+
+#ifndef HUFF_H
+#define HUFF_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The version of this interface, incremented on any breaking change */
+#define HUFF_FFI_ABI_VERSION 1
+
+/*
+ * Compiles a Huff source string with settings given as a JSON object, or NULL for the defaults.
+ * Returns the JSON encoded output, with the errors of the compilation.
+ */
+char *huff_compile(const char *source, const char *settings_json);
+
+/* Frees a string returned by huff_compile, does nothing if NULL */
+void huff_string_free(char *s);
+
+/* The version of the compiler, a static string that must not be freed */
+const char *huff_version(void);
+
+/* The version of the interface the library implements, to check against HUFF_FFI_ABI_VERSION */
+uint32_t huff_abi_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* HUFF_H */
@@ -0,0 +1,108 @@
+#![doc = include_str!("../README.md")]
+#![warn(missing_docs)]
+#![warn(unused_extern_crates)]
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use huff_core::{
+    compile_str,
+    playground::{Diagnostic, PlaygroundOutput, PlaygroundSettings},
+    VERSION,
+};
+use lazy_static::lazy_static;
+use std::ffi::{c_char, CStr, CString};
+
+/// The version of the C interface, incremented on any breaking change to the exported functions
+/// or their JSON formats
+pub const HUFF_FFI_ABI_VERSION: u32 = 1;
+
+lazy_static! {
+    /// The version of the compiler, nul terminated
+    static ref VERSION_CSTR: CString = CString::new(VERSION).unwrap_or_default();
+}
+
+/// Reads a string argument, `None` if the pointer is null.
+///
+/// # Safety
+///
+/// The pointer must be null or point to a nul terminated string.
+unsafe fn read_str<'s>(ptr: *const c_char, name: &str) -> Result<Option<&'s str>, String> {
+    if ptr.is_null() {
+        return Ok(None)
+    }
+    // SAFETY: the caller guarantees the pointer is a nul terminated string
+    let s = unsafe { CStr::from_ptr(ptr) };
+    s.to_str().map(Some).map_err(|e| format!("The {name} isn't valid UTF-8: {e}"))
+}
+
+/// Hands a string over to the caller, to be freed with [huff_string_free]
+fn into_raw(s: String) -> *mut c_char {
+    // JSON escapes nul characters, so the string has none
+    CString::new(s).map(CString::into_raw).unwrap_or(std::ptr::null_mut())
+}
+
+/// Compiles a Huff source string.
+///
+/// `settings_json` is a JSON object of the settings, e.g. `{"evmVersion": "shanghai"}`, or null
+/// for the default settings. Returns a JSON object of the creation bytecode, runtime bytecode,
+/// ABI, errors, warnings and the milliseconds spent in each stage of the compilation, in the
+/// format of [PlaygroundOutput]. Invalid arguments are reported as errors of the output.
+///
+/// The returned string is owned by the caller and must be freed with [huff_string_free]. It is
+/// only null if the output can't be serialized.
+///
+/// # Safety
+///
+/// `source` and `settings_json` must be null or point to nul terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn huff_compile(
+    source: *const c_char,
+    settings_json: *const c_char,
+) -> *mut c_char {
+    // SAFETY: the caller guarantees both pointers are null or nul terminated strings
+    let args = unsafe { (read_str(source, "source"), read_str(settings_json, "settings")) };
+    let output = match args {
+        (Ok(Some(source)), Ok(settings)) => {
+            match settings.map(serde_json::from_str::<PlaygroundSettings>).transpose() {
+                Ok(settings) => compile_str(source, &settings.unwrap_or_default()),
+                Err(e) => errors(format!("Invalid settings: {e}")),
+            }
+        }
+        (Ok(None), _) => errors("The source is null".to_string()),
+        (Err(e), _) | (_, Err(e)) => errors(e),
+    };
+    match serde_json::to_string(&output) {
+        Ok(json) => into_raw(json),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// An output of a single error
+fn errors(message: String) -> PlaygroundOutput {
+    PlaygroundOutput { errors: vec![Diagnostic::new(message)], ..Default::default() }
+}
+
+/// Frees a string returned by the compiler. Does nothing if the pointer is null.
+///
+/// # Safety
+///
+/// The pointer must be null or a string returned by this library that wasn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn huff_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: the caller guarantees the string was returned by `into_raw` and is only freed
+        // once
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// The version of the compiler, a static string that must not be freed.
+#[no_mangle]
+pub extern "C" fn huff_version() -> *const c_char {
+    VERSION_CSTR.as_ptr()
+}
+
+/// The version of the C interface, see [HUFF_FFI_ABI_VERSION].
+#[no_mangle]
+pub extern "C" fn huff_abi_version() -> u32 {
+    HUFF_FFI_ABI_VERSION
+}
@@ -0,0 +1,74 @@
+use huff_ffi::{
+    huff_abi_version, huff_compile, huff_string_free, huff_version, HUFF_FFI_ABI_VERSION,
+};
+use serde_json::Value;
+use std::ffi::{CStr, CString};
+
+/// Compiles through the C interface, returning the parsed output
+fn compile(source: Option<&str>, settings: Option<&str>) -> Value {
+    let source = source.map(|s| CString::new(s).unwrap());
+    let settings = settings.map(|s| CString::new(s).unwrap());
+    let output = unsafe {
+        huff_compile(
+            source.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+            settings.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+        )
+    };
+    assert!(!output.is_null());
+    let json = unsafe { CStr::from_ptr(output) }.to_str().unwrap().to_string();
+    unsafe { huff_string_free(output) };
+    serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn test_compile() {
+    let source = "#define macro MAIN() = takes(0) returns(0) { 0x2a 0x00 mstore 0x20 0x00 return }";
+    let output = compile(Some(source), None);
+    assert_eq!(output["runtime"], "602a60005260206000f3");
+    assert_eq!(output["errors"], Value::Array(vec![]));
+
+    let output = compile(Some("#define macro MAIN() = takes(0) returns(0) { push0 }"), None);
+    assert_eq!(output["runtime"], "5f");
+    let output = compile(
+        Some("#define macro MAIN() = takes(0) returns(0) { push0 }"),
+        Some(r#"{"evmVersion": "paris"}"#),
+    );
+    assert_eq!(output["runtime"], Value::Null);
+    assert_eq!(output["errors"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_invalid_arguments() {
+    let output = compile(None, None);
+    assert_eq!(output["errors"][0]["message"], "The source is null");
+
+    let output = compile(Some(""), Some("{"));
+    assert!(output["errors"][0]["message"].as_str().unwrap().starts_with("Invalid settings"));
+
+    // Freeing null is a no-op
+    unsafe { huff_string_free(std::ptr::null_mut()) };
+}
+
+#[test]
+fn test_versions() {
+    let version = unsafe { CStr::from_ptr(huff_version()) }.to_str().unwrap();
+    assert_eq!(version, huff_core::VERSION);
+    assert_eq!(huff_abi_version(), HUFF_FFI_ABI_VERSION);
+}
+
+#[test]
+fn test_internal_errors_in_bindings_build() {
+    // Builtins without arguments are an internal compiler error, which panics in codegen. The
+    // `release` profile aborts on panic, the `bindings` profile the library is built with unwinds,
+    // so the panic is reported as an error instead of killing the host process.
+    let source = "#define macro MAIN() = takes(0) returns(0) { __tablesize() }";
+    let output = std::process::Command::new(env!("CARGO"))
+        .args(["run", "--quiet", "--profile", "bindings", "--package", "huff_ffi"])
+        .args(["--example", "compile", "--", source])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let output: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let message = output["errors"][0]["message"].as_str().unwrap();
+    assert!(message.starts_with("Internal compiler error"), "{message}");
+}
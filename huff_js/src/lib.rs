@@ -4,11 +4,14 @@
 #![forbid(unsafe_code)]
 #![forbid(where_clauses_object_safety)]
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
 use wasm_bindgen::prelude::*;
 
-use huff_core::Compiler;
+use huff_core::{playground::PlaygroundSettings, Compiler};
 use huff_utils::{abi::Abi, artifact::Artifact, error::CompilerError};
 use serde::{Deserialize, Serialize};
 
@@ -32,6 +35,10 @@ struct CompilerArtifact {
     bytecode: String,
     runtime: String,
     abi: Option<Abi>,
+    #[serde(rename = "methodIdentifiers")]
+    method_identifiers: BTreeMap<String, String>,
+    #[serde(rename = "constructorInputs")]
+    constructor_inputs: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -60,11 +67,13 @@ pub fn compile(input: JsValue) -> Result<JsValue, JsValue> {
 
     res.into_iter().for_each(|artifact| {
         contracts.insert(
-            artifact.file.path.clone(),
+            artifact.identifier(),
             CompilerArtifact {
                 bytecode: artifact.bytecode.clone(),
                 runtime: artifact.runtime.clone(),
                 abi: artifact.abi.clone(),
+                method_identifiers: artifact.method_identifiers.clone(),
+                constructor_inputs: artifact.constructor_inputs.clone(),
             },
         );
     });
@@ -73,3 +82,16 @@ pub fn compile(input: JsValue) -> Result<JsValue, JsValue> {
 
     serde_wasm_bindgen::to_value(&output).map_err(|_| JsValue::NULL)
 }
+
+/// Compiles a single source string, returning its bytecode, ABI, diagnostics and the time spent
+/// in each stage of the compilation
+#[wasm_bindgen]
+pub fn compile_str(source: &str, settings: JsValue) -> Result<JsValue, JsValue> {
+    let settings: PlaygroundSettings = if settings.is_undefined() || settings.is_null() {
+        PlaygroundSettings::default()
+    } else {
+        serde_wasm_bindgen::from_value(settings)?
+    };
+    let output = huff_core::compile_str(source, &settings);
+    serde_wasm_bindgen::to_value(&output).map_err(|_| JsValue::NULL)
+}
@@ -9,8 +9,9 @@ use huff_utils::prelude::*;
 use regex::Regex;
 use std::{
     cell::{Ref, RefCell, RefMut},
+    collections::BTreeMap,
     iter::Peekable,
-    str::Chars,
+    str::{Chars, FromStr},
 };
 
 /// Defines a context in which the lexing happens.
@@ -34,6 +35,10 @@ pub enum Context {
     Constant,
     /// Code table context
     CodeTableBody,
+    /// The bytes of a data section
+    DataBody,
+    /// The raw bytes of an `asm` block in a macro body
+    AsmBody,
 }
 
 /// ## Lexer
@@ -58,6 +63,17 @@ pub struct Lexer<'a> {
     pub eof_returned: bool,
     /// Current context.
     pub context: Context,
+    /// Depth of the parentheses nested within macro arguments, i.e. of macro invocations passed
+    /// as arguments.
+    pub args_depth: usize,
+    /// Depth of the control flow blocks nested within a macro body.
+    pub block_depth: usize,
+    /// The warnings about the lexed source, i.e. decimal literals that look like hex.
+    pub warnings: Vec<CompilerWarning>,
+    /// The edition of files not declaring their own.
+    pub edition: Edition,
+    /// The editions declared with `#pragma edition`, by file path.
+    pub file_editions: BTreeMap<Option<String>, Edition>,
 }
 
 impl<'a> Lexer<'a> {
@@ -72,9 +88,36 @@ impl<'a> Lexer<'a> {
             eof: false,
             eof_returned: false,
             context: Context::Global,
+            args_depth: 0,
+            block_depth: 0,
+            warnings: vec![],
+            edition: Edition::default(),
+            file_editions: BTreeMap::new(),
         }
     }
 
+    /// The path of the file being lexed, if the source has files
+    fn current_file(&self) -> Option<String> {
+        self.source.relative_span(self.current_span()).and_then(|s| s.file).map(|f| f.path.clone())
+    }
+
+    /// The edition of the file being lexed
+    pub fn current_edition(&self) -> Edition {
+        self.file_editions.get(&self.current_file()).copied().unwrap_or(self.edition)
+    }
+
+    /// Errors if the file being lexed is of an edition without the given feature
+    fn require(&self, feature: Feature) -> Result<(), LexicalError<'a>> {
+        if self.current_edition().supports(feature) {
+            return Ok(())
+        }
+        tracing::error!(target: "lexer", "{} REQUIRE EDITION {}", feature.to_string().to_uppercase(), feature.edition());
+        Err(LexicalError::new(
+            LexicalErrorKind::UnsupportedFeature(feature),
+            self.current_span().clone(),
+        ))
+    }
+
     /// Lex all imports
     /// Example import: `// #include "./Utils.huff"`
     pub fn lex_imports(source: &str) -> Vec<String> {
@@ -221,6 +264,12 @@ impl<'a> Lexer<'a> {
         self.source.source[self.current_span().range().unwrap()].to_string()
     }
 
+    /// Gets the current slice of the source code covered by span, borrowed from the source
+    pub fn source_slice(&self) -> &'a str {
+        let source: &'a str = self.source.source;
+        &source[self.current_span().range().unwrap()]
+    }
+
     /// Consumes the characters
     pub fn consume(&mut self) -> Option<char> {
         self.chars.next().map(|x| {
@@ -268,9 +317,11 @@ impl<'a> Lexer<'a> {
     /// `TokenKind::Ident`.
     ///
     /// Rules:
-    /// - The `macro`, `fn`, `test`, `function`, `constant`, `event`, `jumptable`,
-    ///   `jumptable__packed`, and `table` keywords must be preceded by a `#define` keyword.
-    /// - The `takes` keyword must be preceded by an assignment operator: `=`.
+    /// - The `macro`, `fn`, `test`, `contract`, `function`, `constant`, `event`, `jumptable`,
+    ///   `jumptable__packed`, `table`, `data`, `struct`, and `merkle` keywords must be preceded by
+    ///   a `#define` keyword.
+    /// - The `takes` keyword must be preceded by an assignment operator: `=`, or by a close paren
+    ///   and succeeded by an open parenthesis when it follows the `returns` clause.
     /// - The `nonpayable`, `payable`, `view`, and `pure` keywords must be preceeded by one of these
     ///   keywords or a close paren.
     /// - The `returns` keyword must be succeeded by an open parenthesis and must *not* be succeeded
@@ -280,13 +331,17 @@ impl<'a> Lexer<'a> {
             Some(TokenKind::Macro) |
             Some(TokenKind::Fn) |
             Some(TokenKind::Test) |
+            Some(TokenKind::Contract) |
             Some(TokenKind::Function) |
             Some(TokenKind::Constant) |
             Some(TokenKind::Error) |
             Some(TokenKind::Event) |
             Some(TokenKind::JumpTable) |
             Some(TokenKind::JumpTablePacked) |
-            Some(TokenKind::CodeTable) => self.checked_lookback(TokenKind::Define),
+            Some(TokenKind::CodeTable) |
+            Some(TokenKind::Data) |
+            Some(TokenKind::Struct) |
+            Some(TokenKind::Merkle) => self.checked_lookback(TokenKind::Define),
             Some(TokenKind::NonPayable) |
             Some(TokenKind::Payable) |
             Some(TokenKind::View) |
@@ -305,7 +360,12 @@ impl<'a> Lexer<'a> {
                 }
                 false
             }
-            Some(TokenKind::Takes) => self.checked_lookback(TokenKind::Assign),
+            Some(TokenKind::Takes) => {
+                // `takes` may also follow the `returns` clause
+                self.checked_lookback(TokenKind::Assign) ||
+                    (self.checked_lookback(TokenKind::CloseParen) &&
+                        self.checked_lookforward('('))
+            }
             Some(TokenKind::Returns) => {
                 let cur_span_end = self.current_span().end;
                 // Allow for loose and tight syntax (e.g. `returns   (0)`, `returns(0)`, ...)
@@ -325,6 +385,7 @@ impl<'a> Iterator for Lexer<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         self.reset();
         if let Some(ch) = self.consume() {
+            let mut warning = None;
             let kind = match ch {
                 // Comments
                 '/' => {
@@ -352,8 +413,7 @@ impl<'a> Iterator for Lexer<'a> {
                 '#' => {
                     let mut found_kind: Option<TokenKind> = None;
 
-                    let keys = [TokenKind::Define, TokenKind::Include];
-                    for kind in keys.into_iter() {
+                    for kind in DIRECTIVES.into_iter() {
                         let key = kind.to_string();
                         let token_length = key.len() - 1;
                         let peeked = self.peek_n_chars(token_length);
@@ -365,9 +425,28 @@ impl<'a> Iterator for Lexer<'a> {
                         }
                     }
 
+                    // The edition declared by a file applies to the tokens following it
+                    if found_kind == Some(TokenKind::Pragma) {
+                        let line = self.dyn_peek(|c| *c != '\n');
+                        let declared = line
+                            .strip_prefix("#pragma")
+                            .map(|rest| rest.trim_start())
+                            .and_then(|rest| rest.strip_prefix("edition"))
+                            .map(|value| value.trim().trim_matches(|c| c == '"' || c == '\''));
+                        if let Some(edition) = declared.and_then(|v| Edition::from_str(v).ok()) {
+                            self.file_editions.insert(self.current_file(), edition);
+                        }
+                    }
+
                     if let Some(kind) = &found_kind {
                         kind.clone()
-                    } else if self.context == Context::Global && &self.peek_n_chars(1) == "#[" {
+                    } else if matches!(
+                        self.context,
+                        Context::Global | Context::Abi | Context::Constant
+                    ) && &self.peek_n_chars(1) == "#["
+                    {
+                        // Constant and ABI definitions end without a closing token, so a
+                        // decorator may follow them
                         TokenKind::Pound
                     } else {
                         // Otherwise we don't support # prefixed indentifiers
@@ -378,32 +457,18 @@ impl<'a> Iterator for Lexer<'a> {
                         )))
                     }
                 }
+                // Pragma names, i.e. `no-inline`, may contain dashes
+                ch if (ch.is_alphabetic() || ch.eq(&'_')) &&
+                    self.checked_lookback(TokenKind::Pragma) =>
+                {
+                    self.dyn_consume(|c| c.is_alphanumeric() || *c == '_' || *c == '-');
+                    TokenKind::Ident(self.slice())
+                }
                 // Alphabetical characters
                 ch if ch.is_alphabetic() || ch.eq(&'_') => {
                     let mut found_kind: Option<TokenKind> = None;
 
-                    let keys = [
-                        TokenKind::Macro,
-                        TokenKind::Fn,
-                        TokenKind::Test,
-                        TokenKind::Function,
-                        TokenKind::Constant,
-                        TokenKind::Error,
-                        TokenKind::Takes,
-                        TokenKind::Returns,
-                        TokenKind::Event,
-                        TokenKind::NonPayable,
-                        TokenKind::Payable,
-                        TokenKind::Indexed,
-                        TokenKind::View,
-                        TokenKind::Pure,
-                        // First check for packed jump table
-                        TokenKind::JumpTablePacked,
-                        // Match with jump table if not
-                        TokenKind::JumpTable,
-                        TokenKind::CodeTable,
-                    ];
-                    for kind in keys.into_iter() {
+                    for kind in KEYWORDS.into_iter() {
                         if self.context == Context::MacroBody {
                             break
                         }
@@ -430,11 +495,17 @@ impl<'a> Iterator for Lexer<'a> {
                             TokenKind::Macro | TokenKind::Fn | TokenKind::Test => {
                                 self.context = Context::MacroDefinition
                             }
-                            TokenKind::Function | TokenKind::Event | TokenKind::Error => {
-                                self.context = Context::Abi
-                            }
+                            TokenKind::Function |
+                            TokenKind::Event |
+                            TokenKind::Error |
+                            TokenKind::Struct => self.context = Context::Abi,
                             TokenKind::Constant => self.context = Context::Constant,
+                            // Merkle trees end without a closing token, their depth is a count
+                            // rather than a value
+                            TokenKind::Merkle => self.context = Context::Global,
                             TokenKind::CodeTable => self.context = Context::CodeTableBody,
+                            TokenKind::Data => self.context = Context::DataBody,
+                            TokenKind::Contract => self.context = Context::Global,
                             _ => (),
                         }
                     }
@@ -445,21 +516,41 @@ impl<'a> Iterator for Lexer<'a> {
                     let peeked = self.peek_n_chars(token_length);
                     if fsp == peeked {
                         self.nconsume(token_length);
-                        // Consume the parenthesis following the FREE_STORAGE_POINTER
-                        // Note: This will consume `FREE_STORAGE_POINTER)` or
-                        // `FREE_STORAGE_POINTER(` as well
-                        if let Some('(') = self.peek() {
-                            self.consume();
-                        }
-                        if let Some(')') = self.peek() {
-                            self.consume();
+                        // Consume empty parenthesis following the FREE_STORAGE_POINTER, an explicit
+                        // slot, i.e. `FREE_STORAGE_POINTER(slot=3)`, is lexed as separate tokens
+                        if self.peek_n_chars(2).ends_with("()") {
+                            self.nconsume(2);
                         }
                         found_kind = Some(TokenKind::FreeStoragePointer);
                     }
 
+                    // Check for free memory pointer builtin
+                    let fmp = "FREE_MEMORY_POINTER";
+                    let token_length = fmp.len() - 1;
+                    let peeked = self.peek_n_chars(token_length);
+                    if fmp == peeked {
+                        self.nconsume(token_length);
+                        // Consume empty parenthesis following the FREE_MEMORY_POINTER, a region
+                        // size, i.e. `FREE_MEMORY_POINTER(0x40)`, is lexed as a literal
+                        if self.peek_n_chars(2).ends_with("()") {
+                            self.nconsume(2);
+                        }
+                        found_kind = Some(TokenKind::FreeMemoryPointer);
+                    }
+
+                    // Check for the storage slot builtin of constants, leaving its slot, i.e.
+                    // `STORAGE_SLOT(0x02)`, to be lexed as separate tokens
+                    let storage_slot = "STORAGE_SLOT(";
+                    if self.context == Context::Constant &&
+                        self.peek_n_chars(storage_slot.len() - 1) == storage_slot
+                    {
+                        self.nconsume(storage_slot.len() - 2);
+                        found_kind = Some(TokenKind::StorageSlot);
+                    }
+
                     let potential_label: String =
                         self.dyn_peek(|c| c.is_alphanumeric() || c == &'_' || c == &':');
-                    if let true = potential_label.ends_with(':') {
+                    if self.context != Context::Constant && potential_label.ends_with(':') {
                         self.dyn_consume(|c| c.is_alphanumeric() || c == &'_');
                         let label = self.slice();
                         if let Some(l) = label.get(0..label.len()) {
@@ -505,12 +596,10 @@ impl<'a> Iterator for Lexer<'a> {
                             let raw_type: String = self.slice();
 
                             // Check if calldata, memory, or storage
-                            if raw_type == TokenKind::Calldata.to_string() {
-                                found_kind = Some(TokenKind::Calldata);
-                            } else if raw_type == TokenKind::Memory.to_string() {
-                                found_kind = Some(TokenKind::Memory);
-                            } else if raw_type == TokenKind::Storage.to_string() {
-                                found_kind = Some(TokenKind::Storage);
+                            if let Some(location) =
+                                DATA_LOCATIONS.into_iter().find(|l| raw_type == l.to_string())
+                            {
+                                found_kind = Some(location);
                             } else if EVM_TYPE_ARRAY_REGEX.is_match(&raw_type) {
                                 // split to get array size and type
                                 // TODO: support multi-dimensional arrays
@@ -526,19 +615,16 @@ impl<'a> Iterator for Lexer<'a> {
                                     match size.is_empty() {
                                         true => size_vec.push(0),
                                         false => {
-                                            let arr_size: usize = size
-                                                .parse::<usize>()
-                                                .map_err(|_| {
-                                                    let err = LexicalError {
-                                                        kind: LexicalErrorKind::InvalidArraySize(
-                                                            &words[1],
-                                                        ),
-                                                        span: self.current_span().clone(),
-                                                    };
-                                                    tracing::error!(target: "lexer", "{}", format!("{err:?}"));
-                                                    err
-                                                })
-                                                .unwrap();
+                                            let Ok(arr_size) = size.parse::<usize>() else {
+                                                let err = LexicalError {
+                                                    kind: LexicalErrorKind::InvalidArraySize(
+                                                        words[1].clone().into(),
+                                                    ),
+                                                    span: self.current_span().clone(),
+                                                };
+                                                tracing::error!(target: "lexer", "{}", format!("{err:?}"));
+                                                return Some(Err(err))
+                                            };
                                             size_vec.push(arr_size);
                                         }
                                     }
@@ -548,7 +634,9 @@ impl<'a> Iterator for Lexer<'a> {
                                     found_kind = Some(TokenKind::ArrayType(primitive, size_vec));
                                 } else {
                                     let err = LexicalError {
-                                        kind: LexicalErrorKind::InvalidPrimitiveType(&words[0]),
+                                        kind: LexicalErrorKind::InvalidPrimitiveType(
+                                            words[0].as_str().into(),
+                                        ),
                                         span: self.current_span().clone(),
                                     };
                                     tracing::error!(target: "lexer", "{}", format!("{err:?}"));
@@ -564,10 +652,26 @@ impl<'a> Iterator for Lexer<'a> {
                         }
                     }
 
+                    // Constants may be annotated with a primitive type, i.e.
+                    // `#define constant OWNER: address = 0x...`
+                    if self.context == Context::Constant &&
+                        found_kind.is_none() &&
+                        self.checked_lookback(TokenKind::Colon)
+                    {
+                        self.dyn_consume(|c| c.is_alphanumeric());
+                        if let Ok(primitive) = PrimitiveEVMType::try_from(self.slice()) {
+                            found_kind = Some(TokenKind::PrimitiveType(primitive));
+                        }
+                    }
+
                     if let Some(kind) = &found_kind {
                         kind.clone()
                     } else {
-                        self.dyn_consume(|c| c.is_alphanumeric() || c.eq(&'_'));
+                        // Arguments may name entries of an imported ABI, i.e. `IERC20.transfer`
+                        let qualified = self.context == Context::MacroArgs;
+                        self.dyn_consume(move |c| {
+                            c.is_alphanumeric() || c.eq(&'_') || (qualified && c.eq(&'.'))
+                        });
 
                         let slice = self.slice();
                         // Check for built-in function calls
@@ -581,23 +685,41 @@ impl<'a> Iterator for Lexer<'a> {
                     }
                 }
                 // If it's the start of a hex literal
-                ch if ch == '0' && self.peek().unwrap() == 'x' => {
+                ch if ch == '0' && self.peek() == Some('x') => {
                     self.consume(); // Consume the 'x' after '0' (separated from the `dyn_consume` so we don't have
                                     // to match `x` in the actual hex)
                     self.dyn_consume(|c| {
                         c.is_numeric() ||
                             // Match a-f & A-F
-                            matches!(c, '\u{0041}'..='\u{0046}' | '\u{0061}'..='\u{0066}')
+                            matches!(c, '\u{0041}'..='\u{0046}' | '\u{0061}'..='\u{0066}') ||
+                            // Match digit separators, i.e. 0xDEAD_BEEF
+                            *c == '_'
                     });
                     self.current_span_mut().start += 2; // Ignore the "0x"
-
-                    if self.context == Context::CodeTableBody {
-                        // In codetables, the bytecode provided is of arbitrary length. We pass
-                        // the code as an Ident, and it is appended to the end of the runtime
-                        // bytecode in codegen.
-                        TokenKind::Ident(self.slice())
+                    if self.slice().contains('_') {
+                        if let Err(e) = self.require(Feature::DigitSeparators) {
+                            return Some(Err(e))
+                        }
+                    }
+                    let hex = self.slice().replace('_', "");
+
+                    if matches!(self.context, Context::CodeTableBody | Context::AsmBody) {
+                        // In codetables and asm blocks, the bytecode provided is of arbitrary
+                        // length. We pass the code as an Ident, and it is appended to the end of
+                        // the runtime bytecode, or emitted as is, in codegen.
+                        TokenKind::Ident(hex)
+                    } else if self.context == Context::DataBody {
+                        // Data sections hold a single blob of bytes, appended like code tables
+                        self.context = Context::Global;
+                        TokenKind::Ident(hex)
+                    } else if hex.len() > 64 {
+                        tracing::error!(target: "lexer", "OVERFLOWING LITERAL 0x{}", self.slice());
+                        return Some(Err(LexicalError::new(
+                            LexicalErrorKind::OverflowingLiteral(self.source_slice().into()),
+                            self.current_span().clone(),
+                        )))
                     } else {
-                        TokenKind::Literal(str_to_bytes32(self.slice().as_ref()))
+                        TokenKind::Literal(str_to_bytes32(&hex))
                     }
                 }
                 '=' => TokenKind::Assign,
@@ -605,6 +727,7 @@ impl<'a> Iterator for Lexer<'a> {
                     match self.context {
                         Context::Abi => self.context = Context::AbiArgs,
                         Context::MacroBody => self.context = Context::MacroArgs,
+                        Context::MacroArgs => self.args_depth += 1,
                         _ => {}
                     }
                     TokenKind::OpenParen
@@ -612,6 +735,7 @@ impl<'a> Iterator for Lexer<'a> {
                 ')' => {
                     match self.context {
                         Context::AbiArgs => self.context = Context::Abi,
+                        Context::MacroArgs if self.args_depth > 0 => self.args_depth -= 1,
                         Context::MacroArgs => self.context = Context::MacroBody,
                         _ => {}
                     }
@@ -620,20 +744,34 @@ impl<'a> Iterator for Lexer<'a> {
                 '[' => TokenKind::OpenBracket,
                 ']' => TokenKind::CloseBracket,
                 '{' => {
-                    if self.context == Context::MacroDefinition {
-                        self.context = Context::MacroBody;
+                    match self.context {
+                        Context::MacroDefinition => self.context = Context::MacroBody,
+                        Context::MacroBody
+                            if self.checked_lookback(TokenKind::Ident("asm".to_string())) =>
+                        {
+                            self.context = Context::AsmBody
+                        }
+                        Context::MacroBody => self.block_depth += 1,
+                        _ => {}
                     }
                     TokenKind::OpenBrace
                 }
                 '}' => {
-                    if matches!(self.context, Context::MacroBody | Context::CodeTableBody) {
-                        self.context = Context::Global;
+                    match self.context {
+                        Context::MacroBody if self.block_depth > 0 => self.block_depth -= 1,
+                        Context::AsmBody => self.context = Context::MacroBody,
+                        Context::MacroBody | Context::CodeTableBody => {
+                            self.context = Context::Global
+                        }
+                        _ => {}
                     }
                     TokenKind::CloseBrace
                 }
                 '+' => TokenKind::Add,
                 '-' => TokenKind::Sub,
                 '*' => TokenKind::Mul,
+                '!' => TokenKind::Bang,
+                '@' => TokenKind::At,
                 '<' => TokenKind::LeftAngle,
                 '>' => TokenKind::RightAngle,
                 // NOTE: TokenKind::Div is lexed further up since it overlaps with comment
@@ -641,8 +779,57 @@ impl<'a> Iterator for Lexer<'a> {
                 // identifiers
                 ',' => TokenKind::Comma,
                 '0'..='9' => {
-                    self.dyn_consume(char::is_ascii_digit);
-                    TokenKind::Num(self.slice().parse().unwrap())
+                    let digits = |c: &char| c.is_ascii_digit() || *c == '_';
+                    self.dyn_consume(digits);
+                    // Scientific notation, i.e. 1e18
+                    let exponent = self.peek() == Some('e') &&
+                        self.chars.clone().nth(1).map(|c| c.is_ascii_digit()).unwrap_or(false);
+                    if exponent {
+                        self.consume();
+                        self.dyn_consume(digits);
+                    }
+                    let slice = self.slice();
+                    let feature = match (slice.contains('_'), exponent) {
+                        (true, _) => Some(Feature::DigitSeparators),
+                        (_, true) => Some(Feature::ScientificNotation),
+                        _ => None,
+                    };
+                    if let Some(Err(e)) = feature.map(|f| self.require(f)) {
+                        return Some(Err(e))
+                    }
+                    let number = slice.replace('_', "");
+                    // Leading zeros and the lengths of addresses and words hint at a missing "0x"
+                    if !exponent &&
+                        ((number.len() > 1 && number.starts_with('0')) ||
+                            matches!(number.len(), 40 | 64))
+                    {
+                        tracing::warn!(target: "lexer", "DECIMAL LITERAL LOOKS LIKE HEX: {}", slice);
+                        warning = Some(WarningKind::DecimalLiteral(slice.clone()));
+                    }
+                    match dec_str_to_bytes32(&number) {
+                        // Where a value is pushed, decimals are literals whatever their size
+                        Some(literal)
+                            if matches!(
+                                self.context,
+                                Context::MacroBody | Context::MacroArgs | Context::Constant
+                            ) =>
+                        {
+                            TokenKind::Literal(literal)
+                        }
+                        // Elsewhere they are counts, sizes or offsets. Those too large for a usize
+                        // are left to the parser to reject.
+                        Some(literal) => match bytes32_to_usize(&literal) {
+                            Some(n) => TokenKind::Num(n),
+                            None => TokenKind::Literal(literal),
+                        },
+                        None => {
+                            tracing::error!(target: "lexer", "OVERFLOWING LITERAL {}", slice);
+                            return Some(Err(LexicalError::new(
+                                LexicalErrorKind::OverflowingLiteral(self.source_slice().into()),
+                                self.current_span().clone(),
+                            )))
+                        }
+                    }
                 }
                 // Lexes Spaces and Newlines as Whitespace
                 ch if ch.is_ascii_whitespace() => {
@@ -718,6 +905,9 @@ impl<'a> Iterator for Lexer<'a> {
                     self.current_span().clone()
                 }
             };
+            if let Some(kind) = warning {
+                self.warnings.push(CompilerWarning::new(kind, AstSpan(vec![new_span.clone()])));
+            }
             let token = Token { kind, span: new_span };
             if token.kind != TokenKind::Whitespace {
                 self.lookback = Some(token.clone());
@@ -52,3 +52,49 @@ fn macro_context() {
         .collect::<Vec<Token>>();
     assert_eq!(tokens.get(tokens.len() - 3).unwrap().kind, TokenKind::Opcode(Opcode::Byte));
 }
+
+/// Arguments following a macro invocation passed as an argument are still arguments
+#[test]
+fn nested_macro_args_context() {
+    let source = "#define macro TEST() = takes (0) returns (0) {APPLY(PUSH(0x01), add) add}";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer
+        .into_iter()
+        .map(|x| x.unwrap())
+        .filter(|x| !matches!(x.kind, TokenKind::Whitespace))
+        .collect::<Vec<Token>>();
+    assert_eq!(tokens.get(tokens.len() - 5).unwrap().kind, TokenKind::Ident("add".to_string()));
+    assert_eq!(tokens.get(tokens.len() - 3).unwrap().kind, TokenKind::Opcode(Opcode::Add));
+}
+
+#[test]
+fn asm_context() {
+    let source = "#define macro MAIN() = { asm { 0x6000 @offset(0x02) } add }";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer
+        .into_iter()
+        .map(|x| x.unwrap().kind)
+        .filter(|x| !matches!(x, TokenKind::Whitespace))
+        .collect::<Vec<TokenKind>>();
+
+    // Hex keeps its leading zeros, and the block ends back in the macro body
+    assert_eq!(
+        tokens[7..],
+        [
+            TokenKind::Ident("asm".to_string()),
+            TokenKind::OpenBrace,
+            TokenKind::Ident("6000".to_string()),
+            TokenKind::At,
+            TokenKind::Ident("offset".to_string()),
+            TokenKind::OpenParen,
+            TokenKind::Ident("02".to_string()),
+            TokenKind::CloseParen,
+            TokenKind::CloseBrace,
+            TokenKind::Opcode(Opcode::Add),
+            TokenKind::CloseBrace,
+            TokenKind::Eof,
+        ]
+    );
+}
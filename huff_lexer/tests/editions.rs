@@ -0,0 +1,45 @@
+use huff_lexer::Lexer;
+use huff_utils::prelude::{Edition, Feature, FullFileSource, LexicalErrorKind, Token};
+
+fn lex(source: &str, edition: Edition) -> Result<Vec<Token>, LexicalErrorKind<'_>> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+    lexer.edition = edition;
+    lexer.collect::<Result<Vec<Token>, _>>().map_err(|e| e.kind)
+}
+
+#[test]
+fn lexes_newer_literals_in_latest_edition() {
+    assert!(lex("#define constant C = 1_000", Edition::default()).is_ok());
+    assert!(lex("#define constant C = 0xff_ff", Edition::default()).is_ok());
+    assert!(lex("#define constant C = 1e18", Edition::default()).is_ok());
+}
+
+#[test]
+fn rejects_newer_literals_in_legacy_edition() {
+    assert_eq!(
+        lex("#define constant C = 1_000", Edition::E2022).unwrap_err(),
+        LexicalErrorKind::UnsupportedFeature(Feature::DigitSeparators)
+    );
+    assert_eq!(
+        lex("#define constant C = 0xff_ff", Edition::E2022).unwrap_err(),
+        LexicalErrorKind::UnsupportedFeature(Feature::DigitSeparators)
+    );
+    assert_eq!(
+        lex("#define constant C = 1e18", Edition::E2022).unwrap_err(),
+        LexicalErrorKind::UnsupportedFeature(Feature::ScientificNotation)
+    );
+    assert!(lex("#define constant C = 0xffff", Edition::E2022).is_ok());
+}
+
+#[test]
+fn files_declare_their_edition() {
+    let source = "#pragma edition \"2022\"\n#define constant C = 1e18";
+    assert_eq!(
+        lex(source, Edition::E2024).unwrap_err(),
+        LexicalErrorKind::UnsupportedFeature(Feature::ScientificNotation)
+    );
+
+    let source = "#pragma edition \"2024\"\n#define constant C = 1e18";
+    assert!(lex(source, Edition::E2022).is_ok());
+}
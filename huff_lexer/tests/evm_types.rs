@@ -103,3 +103,12 @@ fn multidim_array_parsing() {
         assert_eq!(tokens.get(4).unwrap().kind, evm_type_enum);
     }
 }
+
+#[test]
+fn oversized_array_errors() {
+    let source = "#define function test(uint256[99999999999999999999999]) view returns (uint256)";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let err = lexer.into_iter().find_map(|x| x.err()).unwrap();
+    assert_eq!(err.kind, LexicalErrorKind::InvalidArraySize("99999999999999999999999".into()));
+}
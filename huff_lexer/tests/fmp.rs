@@ -0,0 +1,48 @@
+use huff_lexer::*;
+use huff_utils::prelude::*;
+/// Tests lexing the Free Memory Pointer Keyword
+use std::ops::Deref;
+
+#[test]
+fn free_memory_pointer() {
+    let source = "FREE_MEMORY_POINTER() ";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source.clone());
+    assert_eq!(lexer.source, flattened_source);
+
+    // The first token should be the fmp
+    let tok = lexer.next().unwrap().unwrap();
+    assert_eq!(tok, Token::new(TokenKind::FreeMemoryPointer, Span::new(0..21, None)));
+    assert_eq!(lexer.current_span().deref(), &Span::new(0..21, None));
+
+    // Eats the whitespace
+    let _ = lexer.next();
+
+    // We should have reached EOF now
+    assert_eq!(lexer.current_span().end, source.len());
+    assert!(lexer.eof);
+}
+
+#[test]
+fn free_memory_pointer_with_size() {
+    let source = "#define constant BUF = FREE_MEMORY_POINTER(0x40)";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer
+        .into_iter()
+        .map(|x| x.unwrap().kind)
+        .filter(|k| !matches!(k, TokenKind::Whitespace))
+        .collect::<Vec<TokenKind>>();
+
+    // The size is lexed as a literal between parenthesis
+    assert_eq!(
+        tokens[4..],
+        [
+            TokenKind::FreeMemoryPointer,
+            TokenKind::OpenParen,
+            TokenKind::Literal(str_to_bytes32("40")),
+            TokenKind::CloseParen,
+            TokenKind::Eof
+        ]
+    );
+}
@@ -22,3 +22,59 @@ fn free_storage_pointer() {
     assert_eq!(lexer.current_span().end, source.len());
     assert!(lexer.eof);
 }
+
+#[test]
+fn free_storage_pointer_with_slot() {
+    let source = "#define constant OWNER = FREE_STORAGE_POINTER(slot=3)";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer
+        .into_iter()
+        .map(|x| x.unwrap().kind)
+        .filter(|k| !matches!(k, TokenKind::Whitespace))
+        .collect::<Vec<TokenKind>>();
+
+    // The explicit slot is lexed between parenthesis
+    assert_eq!(
+        tokens[4..],
+        [
+            TokenKind::FreeStoragePointer,
+            TokenKind::OpenParen,
+            TokenKind::Ident("slot".to_string()),
+            TokenKind::Assign,
+            TokenKind::Literal(str_to_bytes32("3")),
+            TokenKind::CloseParen,
+            TokenKind::Eof
+        ]
+    );
+}
+
+#[test]
+fn storage_slot() {
+    let lex = |source: &str| {
+        let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+        Lexer::new(flattened_source)
+            .map(|x| x.unwrap().kind)
+            .filter(|k| !matches!(k, TokenKind::Whitespace))
+            .collect::<Vec<TokenKind>>()
+    };
+
+    let tokens = lex("#define constant IMPLEMENTATION = STORAGE_SLOT(0x02)");
+    assert_eq!(
+        tokens[4..],
+        [
+            TokenKind::StorageSlot,
+            TokenKind::OpenParen,
+            TokenKind::Literal(str_to_bytes32("02")),
+            TokenKind::CloseParen,
+            TokenKind::Eof
+        ]
+    );
+
+    // Without the slot, or outside of constant definitions, it's an identifier
+    let tokens = lex("#define constant STORAGE_SLOT = 0x01");
+    assert_eq!(tokens[2], TokenKind::Ident("STORAGE_SLOT".to_string()));
+    let tokens = lex("#define macro MAIN() = takes(0) returns(0) { STORAGE_SLOT(0x01) }");
+    assert!(tokens.contains(&TokenKind::Ident("STORAGE_SLOT".to_string())));
+    assert!(!tokens.contains(&TokenKind::StorageSlot));
+}
@@ -76,3 +76,17 @@ fn converts_literal_to_hex_string() {
         assert_eq!(format!("0x{source}"), bytes32_to_string(&str_to_bytes32(source), true));
     }
 }
+
+#[test]
+fn parses_hex_with_separators() {
+    let source = "0xDEAD_BEEF";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+
+    let tok = lexer.next().unwrap().unwrap();
+    assert_eq!(
+        tok,
+        Token::new(TokenKind::Literal(str_to_bytes32("DEADBEEF")), Span::new(2..11, None))
+    );
+    assert!(lexer.eof);
+}
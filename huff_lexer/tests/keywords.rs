@@ -336,6 +336,7 @@ fn parses_function_definition_with_keyword_name() {
         "macro",
         "fn",
         "test",
+        "contract",
         "function",
         "constant",
         "error",
@@ -401,6 +402,7 @@ fn parses_label_with_keyword_name() {
         "macro",
         "fn",
         "test",
+        "contract",
         "function",
         "constant",
         "error",
@@ -461,6 +463,7 @@ fn parses_function_with_keyword_name() {
         "macro",
         "fn",
         "test",
+        "contract",
         "function",
         "constant",
         "error",
@@ -512,6 +515,7 @@ fn parses_function_with_keyword_name_in_macro() {
         "macro",
         "fn",
         "test",
+        "contract",
         "function",
         "constant",
         "error",
@@ -728,3 +732,26 @@ fn parses_include_with_extra_suffix() {
     assert_eq!(unwrapped, Token::new(TokenKind::Include, span.clone()));
     assert_eq!(lexer.current_span().deref(), &span);
 }
+
+#[test]
+fn parses_takes_after_returns() {
+    let source = "#define macro TEST() = returns (1) takes(0) { takes }";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let kinds = lexer
+        .into_iter()
+        .map(|t| t.unwrap().kind)
+        .filter(|k| matches!(k, TokenKind::Takes | TokenKind::Returns | TokenKind::Ident(_)))
+        .collect::<Vec<_>>();
+
+    // A `takes` that isn't a clause is still an identifier
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::Ident("TEST".to_string()),
+            TokenKind::Returns,
+            TokenKind::Takes,
+            TokenKind::Ident("takes".to_string()),
+        ]
+    );
+}
@@ -18,6 +18,19 @@ fn lexes_zero_prefixed_numbers() {
     assert!(lexer.eof);
 }
 
+#[test]
+fn lexes_zero_at_end_of_source() {
+    let source = "0x01 0";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer
+        .into_iter()
+        .map(|x| x.unwrap())
+        .filter(|x| !matches!(x.kind, TokenKind::Whitespace | TokenKind::Eof))
+        .collect::<Vec<Token>>();
+    assert_eq!(tokens.last().unwrap(), &Token::new(TokenKind::Num(0), Span::new(5..6, None)));
+}
+
 #[test]
 fn lexes_large_numbers() {
     let source = &format!("{}", usize::MAX);
@@ -33,3 +46,143 @@ fn lexes_large_numbers() {
     assert_eq!(lexer.current_span().end, source.len());
     assert!(lexer.eof);
 }
+
+#[test]
+fn lexes_numbers_larger_than_usize_as_literals() {
+    let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+    let flattened_source = FullFileSource { source: max, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+
+    // Numbers that don't fit in a usize are lexed as 32 byte literals
+    let tok = lexer.next().unwrap().unwrap();
+    assert_eq!(tok, Token::new(TokenKind::Literal([0xff; 32]), Span::new(0..max.len(), None)));
+    assert!(lexer.eof);
+}
+
+#[test]
+fn lexes_pushed_numbers_as_literals() {
+    let source = "#define macro MAIN() = takes(1) returns(0) { 1000 100000000000000000000000 }";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.map(|x| x.unwrap().kind).collect::<Vec<TokenKind>>();
+
+    // Stack counts stay numbers, while pushed numbers are literals whatever their size
+    assert!(tokens.contains(&TokenKind::Num(1)));
+    assert!(tokens.contains(&TokenKind::Literal(str_to_bytes32("3e8"))));
+    assert!(tokens.contains(&TokenKind::Literal(str_to_bytes32("152d02c7e14af6800000"))));
+}
+
+#[test]
+fn errors_on_overflowing_numbers() {
+    let source = "115792089237316195423570985008687907853269984665640564039457584007913129639936";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+
+    let err = lexer.next().unwrap().unwrap_err();
+    assert_eq!(
+        err,
+        LexicalError::new(
+            LexicalErrorKind::OverflowingLiteral(source.into()),
+            Span::new(0..source.len(), None)
+        )
+    );
+}
+
+#[test]
+fn errors_on_overflowing_hex_literals() {
+    let source = &format!("0x1{}", "0".repeat(64));
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+
+    let err = lexer.next().unwrap().unwrap_err();
+    assert_eq!(
+        err,
+        LexicalError::new(
+            LexicalErrorKind::OverflowingLiteral(source[2..].into()),
+            Span::new(2..source.len(), None)
+        )
+    );
+}
+
+#[test]
+fn warns_about_decimals_that_look_like_hex() {
+    let source = "0100 100 00";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+    let tokens = lexer.by_ref().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    assert_eq!(tokens[0].kind, TokenKind::Num(100));
+
+    // Only the zero prefixed numbers are warned about
+    assert_eq!(
+        lexer.warnings,
+        vec![
+            CompilerWarning::new(
+                WarningKind::DecimalLiteral("0100".to_string()),
+                AstSpan(vec![Span::new(0..4, None)])
+            ),
+            CompilerWarning::new(
+                WarningKind::DecimalLiteral("00".to_string()),
+                AstSpan(vec![Span::new(9..11, None)])
+            ),
+        ]
+    );
+}
+
+#[test]
+fn lexes_numbers_with_separators() {
+    let source = "1_000_000";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+
+    let tok = lexer.next().unwrap().unwrap();
+    assert_eq!(tok, Token::new(TokenKind::Num(1_000_000), Span::new(0..source.len(), None)));
+    assert!(lexer.eof);
+    assert!(lexer.warnings.is_empty());
+}
+
+#[test]
+fn lexes_numbers_in_scientific_notation() {
+    let source = "1e18 25e1_7 1e30";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+    let mut tokens = lexer.by_ref().map(|x| x.unwrap()).filter(|t| t.kind != TokenKind::Whitespace);
+
+    assert_eq!(
+        tokens.next().unwrap(),
+        Token::new(TokenKind::Num(10usize.pow(18)), Span::new(0..4, None))
+    );
+    assert_eq!(
+        tokens.next().unwrap(),
+        Token::new(TokenKind::Num(25 * 10usize.pow(17)), Span::new(5..11, None))
+    );
+
+    // Numbers too large for a usize are lexed as 32 byte literals
+    assert_eq!(
+        tokens.next().unwrap(),
+        Token::new(
+            TokenKind::Literal(str_to_bytes32("c9f2c9cd04674edea40000000")),
+            Span::new(12..16, None)
+        )
+    );
+}
+
+#[test]
+fn errors_on_overflowing_scientific_notation() {
+    let source = "1e78";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+
+    let err = lexer.next().unwrap().unwrap_err();
+    assert_eq!(err.kind, LexicalErrorKind::OverflowingLiteral(source.into()));
+}
+
+#[test]
+fn lexes_exponent_less_numbers_before_identifiers() {
+    // An `e` not followed by digits isn't an exponent
+    let source = "1e";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+
+    let tok = lexer.next().unwrap().unwrap();
+    assert_eq!(tok, Token::new(TokenKind::Num(1), Span::new(0..1, None)));
+}
@@ -0,0 +1,31 @@
+use huff_lexer::Lexer;
+use huff_utils::prelude::{FullFileSource, Span, Token, TokenKind};
+
+#[test]
+fn lexes_pragma_names_with_dashes() {
+    let source = "#pragma unsafe-stack\n#define macro MAIN() = {}";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source)
+        .map(|x| x.unwrap())
+        .filter(|t| t.kind != TokenKind::Whitespace)
+        .collect::<Vec<Token>>();
+
+    assert_eq!(tokens[0], Token::new(TokenKind::Pragma, Span::new(0..7, None)));
+    assert_eq!(
+        tokens[1],
+        Token::new(TokenKind::Ident("unsafe-stack".to_string()), Span::new(8..20, None))
+    );
+    assert_eq!(tokens[2].kind, TokenKind::Define);
+    assert_eq!(tokens[3].kind, TokenKind::Macro);
+}
+
+#[test]
+fn lexes_dashes_as_subtraction_outside_of_pragmas() {
+    let source = "#define constant C = 0x02 - 0x01";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let kinds = Lexer::new(flattened_source)
+        .map(|x| x.unwrap().kind)
+        .filter(|k| *k != TokenKind::Whitespace)
+        .collect::<Vec<TokenKind>>();
+    assert!(kinds.contains(&TokenKind::Sub));
+}
@@ -54,3 +54,27 @@ fn parses_code_table() {
     assert_eq!(tokens.get(3).unwrap().kind, TokenKind::OpenParen);
     assert_eq!(tokens.get(4).unwrap().kind, TokenKind::CloseParen);
 }
+
+#[test]
+fn parses_data_section() {
+    let bytes = "00".repeat(40);
+    let source = &format!("#define data BLOB = 0x{bytes}\n#define constant C = 0x01");
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer
+        .into_iter()
+        .map(|x| x.unwrap())
+        .filter(|x| !matches!(x.kind, TokenKind::Whitespace))
+        .collect::<Vec<Token>>();
+
+    // The bytes of the section may be longer than a literal
+    assert_eq!(tokens.first().unwrap().kind, TokenKind::Define);
+    assert_eq!(tokens.get(1).unwrap().kind, TokenKind::Data);
+    assert_eq!(tokens.get(2).unwrap().kind, TokenKind::Ident(String::from("BLOB")));
+    assert_eq!(tokens.get(3).unwrap().kind, TokenKind::Assign);
+    assert_eq!(tokens.get(4).unwrap().kind, TokenKind::Ident(bytes));
+
+    // Hex following the section is lexed as literals again
+    assert_eq!(tokens.get(8).unwrap().kind, TokenKind::Assign);
+    assert_eq!(tokens.get(9).unwrap().kind, TokenKind::Literal(str_to_bytes32("01")));
+}
@@ -0,0 +1,40 @@
+use huff_lexer::*;
+use huff_utils::prelude::*;
+use std::sync::Arc;
+
+#[test]
+fn test_token_stream_json() {
+    let source = "#define macro MAIN() = takes(0) returns(0) { 0x01 add }";
+    let file = Arc::new(FileSource {
+        path: "contracts/Main.huff".to_string(),
+        source: Some(source.to_string()),
+        ..Default::default()
+    });
+    let flattened = FileSource::fully_flatten(Arc::clone(&file));
+    let full_source =
+        FullFileSource { source: &flattened.0, file: Some(Arc::clone(&file)), spans: flattened.1 };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+
+    // Every token, including those at the start and end of the file, references the file
+    assert!(tokens.iter().filter(|t| t.kind != TokenKind::Eof).all(|t| t
+        .span
+        .file
+        .as_ref()
+        .map(|f| f.path.as_str()) ==
+        Some("contracts/Main.huff")));
+
+    let json = serde_json::to_string(&tokens).unwrap();
+    assert!(json.starts_with(
+        r#"[{"kind":"Define","span":{"start":0,"end":7,"file":"contracts/Main.huff"}}"#
+    ));
+    assert!(json.contains(
+        r#"{"kind":{"Opcode":"add"},"span":{"start":50,"end":53,"file":"contracts/Main.huff"}}"#
+    ));
+
+    let deserialized: Vec<Token> = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        deserialized.iter().map(|t| &t.kind).collect::<Vec<_>>(),
+        tokens.iter().map(|t| &t.kind).collect::<Vec<_>>()
+    );
+}
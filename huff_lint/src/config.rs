@@ -0,0 +1,64 @@
+use crate::{errors::LintError, types::Level};
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// The name of the project lint configuration file
+pub const CONFIG_FILE: &str = "huff-lint.toml";
+
+/// Project Lint Configuration
+///
+/// Read from a `huff-lint.toml` file, i.e.
+///
+/// ```toml
+/// max-macro-size = 128
+/// magic-number-limit = 32
+///
+/// [rules]
+/// magic-numbers = "allow"
+/// shadowing = "deny"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct LintConfig {
+    /// Level overrides, keyed by rule name
+    pub rules: BTreeMap<String, Level>,
+    /// The maximum number of statements in a macro before it is flagged as oversized
+    pub max_macro_size: usize,
+    /// The largest literal that may be used in a macro body without a named constant
+    pub magic_number_limit: u64,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self { rules: BTreeMap::new(), max_macro_size: 64, magic_number_limit: 0xff }
+    }
+}
+
+impl LintConfig {
+    /// Parses a lint configuration from a toml string
+    pub fn from_toml(source: &str) -> Result<Self, LintError> {
+        Ok(toml::from_str(source)?)
+    }
+
+    /// Reads a lint configuration from the file at the given path
+    pub fn from_file(path: &Path) -> Result<Self, LintError> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Reads the `huff-lint.toml` file in the given directory, falling back to the default
+    /// configuration if there is none.
+    pub fn discover(dir: &Path) -> Result<Self, LintError> {
+        let path = dir.join(CONFIG_FILE);
+        if path.is_file() {
+            tracing::info!(target: "lint", "USING LINT CONFIG \"{}\"", path.display());
+            Self::from_file(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// The configured level for a rule, if it was overridden
+    pub fn level(&self, rule: &str) -> Option<Level> {
+        self.rules.get(rule).copied()
+    }
+}
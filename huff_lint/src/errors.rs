@@ -0,0 +1,26 @@
+use std::fmt;
+
+/// A Lint error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintError(pub String);
+
+/// fmt::Display implementation for `LintError`
+impl fmt::Display for LintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Lint Error: {}", self.0)
+    }
+}
+
+/// Convert a `std::io::Error` to a `LintError`
+impl From<std::io::Error> for LintError {
+    fn from(e: std::io::Error) -> Self {
+        LintError(e.to_string())
+    }
+}
+
+/// Convert a `toml::de::Error` to a `LintError`
+impl From<toml::de::Error> for LintError {
+    fn from(e: toml::de::Error) -> Self {
+        LintError(e.to_string())
+    }
+}
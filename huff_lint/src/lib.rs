@@ -0,0 +1,72 @@
+#![doc = include_str!("../README.md")]
+#![warn(missing_docs)]
+#![warn(unused_extern_crates)]
+#![forbid(unsafe_code)]
+
+use crate::{config::LintConfig, rules::Rule, types::Lint};
+use huff_utils::prelude::Contract;
+
+/// The config module
+pub mod config;
+
+/// The rules module
+pub mod rules;
+
+/// The types module
+pub mod types;
+
+/// The errors module
+pub mod errors;
+
+/// Prelude wraps all modules within the crate
+pub mod prelude {
+    pub use crate::{config::*, errors::*, rules::*, types::*};
+}
+
+/// The core struct of the huff-lint crate.
+///
+/// A `Linter` is instantiated with a [LintConfig](config::LintConfig) and all built-in rules.
+/// Additional rules can be registered with [add_rule](Linter::add_rule) before checking
+/// contracts with [lint](Linter::lint).
+pub struct Linter {
+    /// The lint configuration
+    pub config: LintConfig,
+
+    /// The rules to check
+    pub rules: Vec<Box<dyn Rule>>,
+}
+
+impl Linter {
+    /// Create a new instance of `Linter` with the built-in rules.
+    pub fn new(config: LintConfig) -> Self {
+        Self { config, rules: rules::builtin_rules() }
+    }
+
+    /// Registers an additional rule.
+    pub fn add_rule(&mut self, rule: Box<dyn Rule>) {
+        self.rules.push(rule);
+    }
+
+    /// Checks a contract against every enabled rule.
+    pub fn lint(&self, contract: &Contract) -> Vec<Lint> {
+        self.rules
+            .iter()
+            .flat_map(|rule| {
+                let level = self.config.level(rule.name()).unwrap_or_else(|| rule.default_level());
+                if level == types::Level::Allow {
+                    return vec![]
+                }
+                rule.check(contract, &self.config)
+                    .into_iter()
+                    .map(|(message, span)| Lint {
+                        rule: rule.name(),
+                        code: rule.code(),
+                        level,
+                        message,
+                        span,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{
+    config::LintConfig,
+    rules::{macro_statements, Rule},
+};
+use huff_utils::prelude::{AstSpan, Contract};
+
+/// Macros with more statements than the configured maximum should be split up
+pub struct MacroSize;
+
+impl Rule for MacroSize {
+    fn name(&self) -> &'static str {
+        "macro-size"
+    }
+
+    fn check(&self, contract: &Contract, config: &LintConfig) -> Vec<(String, AstSpan)> {
+        contract
+            .macros
+            .iter()
+            .filter_map(|m| {
+                let size = macro_statements(m).len();
+                (size > config.max_macro_size).then(|| {
+                    (
+                        format!(
+                            "Macro \"{}\" has {size} statements, exceeding the maximum of {}",
+                            m.name, config.max_macro_size
+                        ),
+                        m.span.clone(),
+                    )
+                })
+            })
+            .collect()
+    }
+}
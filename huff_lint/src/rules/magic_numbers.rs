@@ -0,0 +1,46 @@
+use crate::{
+    config::LintConfig,
+    rules::{macro_statements, Rule},
+};
+use huff_utils::prelude::{bytes32_to_string, AstSpan, Contract, Literal, StatementType};
+
+/// Literals larger than the configured limit should be named with a constant
+pub struct MagicNumbers;
+
+impl Rule for MagicNumbers {
+    fn name(&self) -> &'static str {
+        "magic-numbers"
+    }
+
+    fn check(&self, contract: &Contract, config: &LintConfig) -> Vec<(String, AstSpan)> {
+        contract
+            .macros
+            .iter()
+            .flat_map(|m| {
+                macro_statements(m)
+                    .into_iter()
+                    .filter_map(|s| match &s.ty {
+                        StatementType::Literal(l) if exceeds(l, config.magic_number_limit) => {
+                            Some((
+                                format!(
+                                "Magic number \"{}\" in macro \"{}\" should be a named constant",
+                                bytes32_to_string(l, true),
+                                m.name
+                            ),
+                                s.span.clone(),
+                            ))
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// Whether a literal is larger than the limit
+fn exceeds(literal: &Literal, limit: u64) -> bool {
+    let mut low = [0u8; 8];
+    low.copy_from_slice(&literal[24..]);
+    literal[..24].iter().any(|b| *b != 0) || u64::from_be_bytes(low) > limit
+}
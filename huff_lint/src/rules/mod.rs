@@ -0,0 +1,68 @@
+use crate::{config::LintConfig, types::Level};
+use huff_utils::prelude::{walk_statement, AstSpan, Contract, MacroDefinition, Statement, Visitor};
+
+/// Naming convention rules
+pub mod naming;
+
+/// Magic number rule
+pub mod magic_numbers;
+
+/// Oversized macro rule
+pub mod macro_size;
+
+/// Shadowing rule
+pub mod shadowing;
+
+/// Security rules
+pub mod security;
+
+/// A lint rule, checked against the AST of a contract
+pub trait Rule: Send + Sync {
+    /// The name the rule is configured by
+    fn name(&self) -> &'static str;
+
+    /// The code identifying the rule in reports, if it has one
+    fn code(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// The level violations are reported at unless configured otherwise
+    fn default_level(&self) -> Level {
+        Level::Warn
+    }
+
+    /// Checks a contract, returning a message and span for every violation
+    fn check(&self, contract: &Contract, config: &LintConfig) -> Vec<(String, AstSpan)>;
+}
+
+/// Every built-in rule
+pub fn builtin_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(naming::MacroNaming),
+        Box::new(naming::ConstantNaming),
+        Box::new(magic_numbers::MagicNumbers),
+        Box::new(macro_size::MacroSize),
+        Box::new(shadowing::Shadowing),
+        Box::new(security::ReachableSelfdestruct),
+        Box::new(security::CalldataDelegatecall),
+        Box::new(security::TxOriginAuth),
+        Box::new(security::ConstructorDelegatecall),
+    ]
+}
+
+/// Collects the statements of a macro body, including those nested within labels
+pub(crate) fn macro_statements(macro_def: &MacroDefinition) -> Vec<&Statement> {
+    let mut collector = StatementCollector(vec![]);
+    collector.visit_macro(macro_def);
+    collector.0
+}
+
+/// Visitor collecting every statement it walks
+struct StatementCollector<'ast>(Vec<&'ast Statement>);
+
+impl<'ast> Visitor<'ast> for StatementCollector<'ast> {
+    fn visit_statement(&mut self, statement: &'ast Statement) {
+        self.0.push(statement);
+        walk_statement(self, statement);
+    }
+}
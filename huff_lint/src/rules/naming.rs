@@ -0,0 +1,53 @@
+use crate::{config::LintConfig, rules::Rule};
+use huff_utils::prelude::{AstSpan, Contract};
+
+/// Whether a name is written in SCREAMING_SNAKE_CASE
+pub fn is_screaming_snake_case(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_uppercase() || c == '_') &&
+        name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Macros, functions and tests should be named in SCREAMING_SNAKE_CASE
+pub struct MacroNaming;
+
+impl Rule for MacroNaming {
+    fn name(&self) -> &'static str {
+        "macro-naming"
+    }
+
+    fn check(&self, contract: &Contract, _: &LintConfig) -> Vec<(String, AstSpan)> {
+        contract
+            .macros
+            .iter()
+            .filter(|m| !is_screaming_snake_case(&m.name))
+            .map(|m| {
+                (format!("Macro \"{}\" should be SCREAMING_SNAKE_CASE", m.name), m.span.clone())
+            })
+            .collect()
+    }
+}
+
+/// Constants should be named in SCREAMING_SNAKE_CASE
+pub struct ConstantNaming;
+
+impl Rule for ConstantNaming {
+    fn name(&self) -> &'static str {
+        "constant-naming"
+    }
+
+    fn check(&self, contract: &Contract, _: &LintConfig) -> Vec<(String, AstSpan)> {
+        match contract.constants.lock() {
+            Ok(constants) => constants
+                .iter()
+                .filter(|c| !is_screaming_snake_case(&c.name))
+                .map(|c| {
+                    (
+                        format!("Constant \"{}\" should be SCREAMING_SNAKE_CASE", c.name),
+                        c.span.clone(),
+                    )
+                })
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+}
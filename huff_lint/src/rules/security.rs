@@ -0,0 +1,267 @@
+use crate::{
+    config::LintConfig,
+    rules::{macro_statements, Rule},
+    types::Level,
+};
+use huff_utils::prelude::{
+    walk_macro_invocation, AstSpan, BuiltinFunctionKind, Contract, MacroDefinition,
+    MacroInvocation, Opcode, Statement, StatementType, Visitor,
+};
+
+/// `selfdestruct` should not be reachable from the dispatch of the `MAIN` macro
+pub struct ReachableSelfdestruct;
+
+impl Rule for ReachableSelfdestruct {
+    fn name(&self) -> &'static str {
+        "reachable-selfdestruct"
+    }
+
+    fn code(&self) -> Option<&'static str> {
+        Some("S001")
+    }
+
+    fn check(&self, contract: &Contract, _: &LintConfig) -> Vec<(String, AstSpan)> {
+        opcodes_reachable_from(contract, "MAIN", Opcode::Selfdestruct)
+            .into_iter()
+            .map(|(m, s)| {
+                (
+                    format!("Selfdestruct in macro \"{}\" is reachable from \"MAIN\"", m.name),
+                    s.span.clone(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// The address `delegatecall` runs the code of should not be read from calldata
+///
+/// Follows the values of `calldataload` and `__CALLDATALOAD_ARG` through the stack within a
+/// block of statements, flagging a `delegatecall` to an address derived from one.
+pub struct CalldataDelegatecall;
+
+impl Rule for CalldataDelegatecall {
+    fn name(&self) -> &'static str {
+        "calldata-delegatecall"
+    }
+
+    fn code(&self) -> Option<&'static str> {
+        Some("S002")
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Deny
+    }
+
+    fn check(&self, contract: &Contract, _: &LintConfig) -> Vec<(String, AstSpan)> {
+        let mut lints = vec![];
+        for m in &contract.macros {
+            follow_sources(&m.statements, &[Opcode::Calldataload], &mut |s, o, inputs| {
+                // The address is the second input, below the gas
+                if o == Opcode::Delegatecall && inputs.get(1).copied().flatten().is_some() {
+                    lints.push((
+                        format!(
+                            "Delegatecall in macro \"{}\" runs the code of an address read from calldata",
+                            m.name
+                        ),
+                        s.span.clone(),
+                    ));
+                }
+            });
+        }
+        lints
+    }
+}
+
+/// `origin` should not be compared against to authorize a caller
+///
+/// Flags an `eq` of a value derived from `origin` with anything but a value derived from
+/// `caller`, as any contract the origin calls can act on its behalf. Comparing the origin with
+/// the caller to check that the caller isn't a contract isn't flagged.
+pub struct TxOriginAuth;
+
+impl Rule for TxOriginAuth {
+    fn name(&self) -> &'static str {
+        "tx-origin-auth"
+    }
+
+    fn code(&self) -> Option<&'static str> {
+        Some("S003")
+    }
+
+    fn check(&self, contract: &Contract, _: &LintConfig) -> Vec<(String, AstSpan)> {
+        let mut lints = vec![];
+        for m in &contract.macros {
+            follow_sources(
+                &m.statements,
+                &[Opcode::Origin, Opcode::Caller],
+                &mut |s, o, inputs| {
+                    let compared = match (o, inputs) {
+                        (Opcode::Eq, [a, b]) => [*a, *b],
+                        _ => return,
+                    };
+                    if compared.contains(&Some(Opcode::Origin)) &&
+                        !compared.contains(&Some(Opcode::Caller))
+                    {
+                        lints.push((
+                            format!(
+                                "Macro \"{}\" authorizes the caller by comparing \"origin\"",
+                                m.name
+                            ),
+                            s.span.clone(),
+                        ));
+                    }
+                },
+            );
+        }
+        lints
+    }
+}
+
+/// `delegatecall` should not be reachable from the `CONSTRUCTOR` macro, running the code of
+/// another contract against the storage of the one being deployed
+pub struct ConstructorDelegatecall;
+
+impl Rule for ConstructorDelegatecall {
+    fn name(&self) -> &'static str {
+        "constructor-delegatecall"
+    }
+
+    fn code(&self) -> Option<&'static str> {
+        Some("S004")
+    }
+
+    fn check(&self, contract: &Contract, _: &LintConfig) -> Vec<(String, AstSpan)> {
+        opcodes_reachable_from(contract, "CONSTRUCTOR", Opcode::Delegatecall)
+            .into_iter()
+            .map(|(m, s)| {
+                (
+                    format!(
+                        "Delegatecall in macro \"{}\" is reachable from \"CONSTRUCTOR\"",
+                        m.name
+                    ),
+                    s.span.clone(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// The statements of an opcode in the macros reachable from the entry point through invocations,
+/// along with the macro of each
+fn opcodes_reachable_from<'a>(
+    contract: &'a Contract,
+    entry: &str,
+    opcode: Opcode,
+) -> Vec<(&'a MacroDefinition, &'a Statement)> {
+    let mut reachable: Vec<&MacroDefinition> = vec![];
+    let mut pending = vec![entry.to_string()];
+    while let Some(name) = pending.pop() {
+        let m = match contract.macros.iter().find(|m| m.name == name) {
+            Some(m) if !reachable.iter().any(|r| r.name == name) => m,
+            _ => continue,
+        };
+        let mut invocations = InvocationCollector(vec![]);
+        invocations.visit_macro(m);
+        pending.extend(invocations.0);
+        reachable.push(m);
+    }
+
+    reachable
+        .into_iter()
+        .flat_map(|m| {
+            macro_statements(m)
+                .into_iter()
+                .filter(move |s| matches!(s.ty, StatementType::Opcode(o) if o == opcode))
+                .map(move |s| (m, s))
+        })
+        .collect()
+}
+
+/// Visitor collecting the names of the macros invoked, including those passed as arguments
+struct InvocationCollector(Vec<String>);
+
+impl<'ast> Visitor<'ast> for InvocationCollector {
+    fn visit_macro_invocation(&mut self, invocation: &'ast MacroInvocation) {
+        self.0.push(invocation.macro_name.clone());
+        walk_macro_invocation(self, invocation);
+    }
+}
+
+/// Follows the stack items pushed by the source opcodes through a block of statements, calling
+/// `sink` with every opcode but `dup`s and `swap`s and the source each of its inputs derives
+/// from, top first.
+///
+/// An opcode's outputs derive from the source of its first input deriving from one. Labels,
+/// macro invocations and arg calls end the block, as the stack items can't be followed through
+/// them.
+fn follow_sources(
+    statements: &[Statement],
+    sources: &[Opcode],
+    sink: &mut impl FnMut(&Statement, Opcode, &[Option<Opcode>]),
+) {
+    // The source of each stack item, from the bottom of the followed stack
+    let mut stack: Vec<Option<Opcode>> = vec![];
+    for s in statements {
+        match &s.ty {
+            StatementType::Opcode(o) => {
+                let inputs = o.stack_inputs();
+                // Items below the followed stack were pushed before the block
+                while stack.len() < inputs {
+                    stack.insert(0, None);
+                }
+                match o.byte() {
+                    // DUP1 to DUP16 copy the nth item
+                    0x80..=0x8f => stack.push(stack[stack.len() - inputs]),
+                    // SWAP1 to SWAP16 exchange the top item with the one below the nth
+                    0x90..=0x9f => {
+                        let top = stack.len() - 1;
+                        stack.swap(top, top + 1 - inputs);
+                    }
+                    _ => {
+                        let mut consumed = stack.split_off(stack.len() - inputs);
+                        consumed.reverse();
+                        sink(s, *o, &consumed);
+                        let source = match sources.contains(o) {
+                            true => Some(*o),
+                            false => consumed.into_iter().flatten().next(),
+                        };
+                        stack.resize(stack.len() + o.stack_outputs(), source);
+                    }
+                }
+            }
+            StatementType::BuiltinFunctionCall(bf) => match bf.kind {
+                BuiltinFunctionKind::CalldataLoadArg if sources.contains(&Opcode::Calldataload) => {
+                    stack.push(Some(Opcode::Calldataload))
+                }
+                BuiltinFunctionKind::Verbatim => stack.clear(),
+                BuiltinFunctionKind::DynConstructorArg |
+                BuiltinFunctionKind::GasLimit |
+                BuiltinFunctionKind::CalldataCheck => {}
+                BuiltinFunctionKind::CodeSlice => stack.extend([None, None]),
+                // The outputs of a precompile call derive from its inputs, like an opcode's
+                _ => match bf.kind.precompile() {
+                    Some(p) => {
+                        while stack.len() < p.stack_inputs() {
+                            stack.insert(0, None);
+                        }
+                        let consumed = stack.split_off(stack.len() - p.stack_inputs());
+                        let source = consumed.into_iter().rev().flatten().next();
+                        stack.resize(stack.len() + p.stack_outputs(), source);
+                    }
+                    None => stack.push(None),
+                },
+            },
+            StatementType::Literal(_) |
+            StatementType::Constant(_) |
+            StatementType::LabelCall(_) => stack.push(None),
+            StatementType::Label(l) => {
+                stack.clear();
+                follow_sources(&l.inner, sources, sink);
+            }
+            StatementType::MacroInvocation(_) | StatementType::ArgCall(_) => stack.clear(),
+            StatementType::Code(_) |
+            StatementType::Assertion(_) |
+            StatementType::OffsetAssertion(_) => {}
+        }
+    }
+}
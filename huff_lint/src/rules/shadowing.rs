@@ -0,0 +1,63 @@
+use crate::{
+    config::LintConfig,
+    rules::{macro_statements, Rule},
+};
+use huff_utils::prelude::{AstSpan, Contract, StatementType};
+
+/// Names should not be reused within a macro
+///
+/// Flags macro parameters that share a name with a constant or macro, and labels defined more
+/// than once within the same macro.
+pub struct Shadowing;
+
+impl Rule for Shadowing {
+    fn name(&self) -> &'static str {
+        "shadowing"
+    }
+
+    fn check(&self, contract: &Contract, _: &LintConfig) -> Vec<(String, AstSpan)> {
+        let constants: Vec<String> = match contract.constants.lock() {
+            Ok(constants) => constants.iter().map(|c| c.name.clone()).collect(),
+            Err(_) => vec![],
+        };
+
+        let mut lints = vec![];
+        for m in &contract.macros {
+            for param in &m.parameters {
+                let name = match &param.name {
+                    Some(n) => n,
+                    None => continue,
+                };
+                if constants.contains(name) {
+                    lints.push((
+                        format!("Parameter \"{name}\" of macro \"{}\" shadows a constant", m.name),
+                        param.span.clone(),
+                    ));
+                }
+                if contract.macros.iter().any(|other| other.name.eq(name)) {
+                    lints.push((
+                        format!("Parameter \"{name}\" of macro \"{}\" shadows a macro", m.name),
+                        param.span.clone(),
+                    ));
+                }
+            }
+
+            let mut labels: Vec<&String> = vec![];
+            for s in macro_statements(m) {
+                if let StatementType::Label(l) = &s.ty {
+                    if labels.contains(&&l.name) {
+                        lints.push((
+                            format!(
+                                "Label \"{}\" is defined more than once in macro \"{}\"",
+                                l.name, m.name
+                            ),
+                            l.span.clone(),
+                        ));
+                    }
+                    labels.push(&l.name);
+                }
+            }
+        }
+        lints
+    }
+}
@@ -0,0 +1,50 @@
+use huff_utils::prelude::AstSpan;
+use serde::Deserialize;
+use std::fmt;
+
+/// The level a lint is reported at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    /// The rule is disabled
+    Allow,
+    /// Violations are reported as warnings
+    Warn,
+    /// Violations are reported as errors
+    Deny,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Level::Allow => write!(f, "Allowed"),
+            Level::Warn => write!(f, "Warning"),
+            Level::Deny => write!(f, "Error"),
+        }
+    }
+}
+
+/// A single rule violation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    /// The name of the violated rule
+    pub rule: &'static str,
+    /// The code of the violated rule, if it has one
+    pub code: Option<&'static str>,
+    /// The level the violation is reported at
+    pub level: Level,
+    /// A description of the violation
+    pub message: String,
+    /// The span of the offending code
+    pub span: AstSpan,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rule = match self.code {
+            Some(code) => format!("{code} {}", self.rule),
+            None => self.rule.to_string(),
+        };
+        write!(f, "\n{}: {} [{rule}]\n{}\n", self.level, self.message, self.span.error(None))
+    }
+}
@@ -0,0 +1,48 @@
+use huff_lint::prelude::*;
+use std::{collections::BTreeMap, path::Path};
+
+#[test]
+fn test_default_config() {
+    let config = LintConfig::from_toml("").unwrap();
+    assert_eq!(config, LintConfig::default());
+    assert_eq!(config.max_macro_size, 64);
+    assert_eq!(config.magic_number_limit, 0xff);
+}
+
+#[test]
+fn test_parse_config() {
+    let config = LintConfig::from_toml(
+        r#"
+        max-macro-size = 128
+        magic-number-limit = 32
+
+        [rules]
+        magic-numbers = "allow"
+        shadowing = "deny"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.max_macro_size, 128);
+    assert_eq!(config.magic_number_limit, 32);
+    assert_eq!(
+        config.rules,
+        BTreeMap::from([
+            ("magic-numbers".to_string(), Level::Allow),
+            ("shadowing".to_string(), Level::Deny)
+        ])
+    );
+    assert_eq!(config.level("shadowing"), Some(Level::Deny));
+    assert_eq!(config.level("macro-size"), None);
+}
+
+#[test]
+fn test_invalid_config() {
+    assert!(LintConfig::from_toml("[rules]\nshadowing = \"sometimes\"").is_err());
+}
+
+#[test]
+fn test_discover_missing_config() {
+    let config = LintConfig::discover(Path::new("./does/not/exist")).unwrap();
+    assert_eq!(config, LintConfig::default());
+}
@@ -0,0 +1,264 @@
+use huff_lexer::*;
+use huff_lint::{prelude::*, Linter};
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn parse(source: &str) -> Contract {
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+    contract
+}
+
+fn rules_of(lints: &[Lint]) -> Vec<&'static str> {
+    lints.iter().map(|l| l.rule).collect()
+}
+
+#[test]
+fn test_clean_contract() {
+    let source = r#"
+    #define constant OWNER_SLOT = FREE_STORAGE_POINTER()
+
+    #define macro MAIN() = takes(0) returns(0) {
+        [OWNER_SLOT] sload 0x00 mstore
+        0x20 0x00 return
+    }
+    "#;
+
+    let lints = Linter::new(LintConfig::default()).lint(&parse(source));
+    assert!(lints.is_empty());
+}
+
+#[test]
+fn test_naming_conventions() {
+    let source = r#"
+    #define constant ownerSlot = 0x00
+
+    #define macro doThing() = takes(0) returns(0) {
+        [ownerSlot] sload
+    }
+    "#;
+
+    let lints = Linter::new(LintConfig::default()).lint(&parse(source));
+    assert_eq!(rules_of(&lints), vec!["macro-naming", "constant-naming"]);
+    assert!(lints.iter().all(|l| l.level == Level::Warn));
+}
+
+#[test]
+fn test_magic_numbers() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 calldataload 0xE0 shr
+        0x40c10f19 eq
+    }
+    "#;
+
+    let lints = Linter::new(LintConfig::default()).lint(&parse(source));
+    assert_eq!(rules_of(&lints), vec!["magic-numbers"]);
+    assert!(lints[0].message.contains("0x40c10f19"));
+
+    let config = LintConfig { magic_number_limit: 0x10, ..Default::default() };
+    let lints = Linter::new(config).lint(&parse(source));
+    assert_eq!(rules_of(&lints), vec!["magic-numbers", "magic-numbers"]);
+}
+
+#[test]
+fn test_macro_size() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        0x01 0x02 add
+        end:
+            pop stop
+    }
+    "#;
+
+    let config = LintConfig { max_macro_size: 5, ..Default::default() };
+    let lints = Linter::new(config).lint(&parse(source));
+    assert_eq!(rules_of(&lints), vec!["macro-size"]);
+    assert!(lints[0].message.contains("has 6 statements"));
+}
+
+#[test]
+fn test_shadowing() {
+    let source = r#"
+    #define constant VALUE = 0x01
+
+    #define macro INNER() = takes(0) returns(0) {}
+
+    #define macro OUTER(VALUE, INNER) = takes(0) returns(0) {
+        <VALUE> <INNER>
+        end:
+            stop
+        end:
+            stop
+    }
+    "#;
+
+    let lints = Linter::new(LintConfig::default()).lint(&parse(source));
+    assert_eq!(rules_of(&lints), vec!["shadowing", "shadowing", "shadowing"]);
+    assert!(lints[0].message.contains("shadows a constant"));
+    assert!(lints[1].message.contains("shadows a macro"));
+    assert!(lints[2].message.contains("defined more than once"));
+}
+
+#[test]
+fn test_configured_levels() {
+    let source = r#"
+    #define constant ownerSlot = 0x00
+
+    #define macro doThing() = takes(0) returns(0) {
+        [ownerSlot] sload
+    }
+    "#;
+
+    let config = LintConfig::from_toml(
+        r#"
+        [rules]
+        macro-naming = "allow"
+        constant-naming = "deny"
+        "#,
+    )
+    .unwrap();
+    let lints = Linter::new(config).lint(&parse(source));
+    assert_eq!(rules_of(&lints), vec!["constant-naming"]);
+    assert_eq!(lints[0].level, Level::Deny);
+}
+
+struct NoTests;
+
+impl Rule for NoTests {
+    fn name(&self) -> &'static str {
+        "no-tests"
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Deny
+    }
+
+    fn check(&self, contract: &Contract, _: &LintConfig) -> Vec<(String, AstSpan)> {
+        contract
+            .macros
+            .iter()
+            .filter(|m| m.test)
+            .map(|m| (format!("Test \"{}\" found", m.name), m.span.clone()))
+            .collect()
+    }
+}
+
+#[test]
+fn test_custom_rule() {
+    let source = r#"
+    #define test MY_TEST() = takes(0) returns(0) {
+        0x01 pop
+    }
+    "#;
+
+    let mut linter = Linter::new(LintConfig::default());
+    linter.add_rule(Box::new(NoTests));
+    let lints = linter.lint(&parse(source));
+    assert_eq!(rules_of(&lints), vec!["no-tests"]);
+    assert_eq!(lints[0].level, Level::Deny);
+}
+
+#[test]
+fn test_reachable_selfdestruct() {
+    let source = r#"
+    #define macro KILL() = takes(0) returns(0) {
+        caller selfdestruct
+    }
+
+    #define macro UNUSED() = takes(0) returns(0) {
+        caller selfdestruct
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 calldataload 0xe0 shr
+        dup1 __FUNC_SIG("kill()") eq kill jumpi
+        0x00 dup1 revert
+        kill:
+            KILL()
+    }
+    "#;
+    let lints = Linter::new(LintConfig::default()).lint(&parse(source));
+    assert_eq!(rules_of(&lints), vec!["reachable-selfdestruct"]);
+    assert_eq!(lints[0].code, Some("S001"));
+    assert_eq!(lints[0].level, Level::Warn);
+    assert!(lints[0].message.contains("\"KILL\""));
+    assert!(lints[0].to_string().contains("[S001 reachable-selfdestruct]"));
+}
+
+#[test]
+fn test_calldata_delegatecall() {
+    let source = r#"
+    #define constant IMPLEMENTATION = FREE_STORAGE_POINTER()
+
+    #define macro FORWARD() = takes(0) returns(0) {
+        0x00 0x00 0x00 0x00 0x04 calldataload gas delegatecall
+        pop
+        0x00 0x00 0x00 0x00 [IMPLEMENTATION] sload gas delegatecall
+        pop
+    }
+    "#;
+    let lints = Linter::new(LintConfig::default()).lint(&parse(source));
+    assert_eq!(rules_of(&lints), vec!["calldata-delegatecall"]);
+    assert_eq!(lints[0].code, Some("S002"));
+    assert_eq!(lints[0].level, Level::Deny);
+
+    // The address is followed through masking and swaps
+    let source = r#"
+    #define macro FORWARD() = takes(0) returns(0) {
+        0x04 calldataload 0xffffffffffffffffffffffffffffffffffffffff and
+        0x00 0x00 0x00 0x00 swap4 gas delegatecall
+        pop
+    }
+    "#;
+    let lints = Linter::new(LintConfig::default()).lint(&parse(source));
+    assert_eq!(rules_of(&lints).iter().filter(|r| **r == "calldata-delegatecall").count(), 1);
+}
+
+#[test]
+fn test_tx_origin_auth() {
+    let source = r#"
+    #define constant OWNER = FREE_STORAGE_POINTER()
+
+    #define macro ONLY_OWNER() = takes(0) returns(0) {
+        [OWNER] sload origin eq authorized jumpi
+        0x00 dup1 revert
+        authorized:
+    }
+
+    #define macro NOT_CONTRACT() = takes(0) returns(0) {
+        caller origin eq eoa jumpi
+        0x00 dup1 revert
+        eoa:
+    }
+    "#;
+    let lints = Linter::new(LintConfig::default()).lint(&parse(source));
+    assert_eq!(rules_of(&lints), vec!["tx-origin-auth"]);
+    assert_eq!(lints[0].code, Some("S003"));
+    assert!(lints[0].message.contains("\"ONLY_OWNER\""));
+}
+
+#[test]
+fn test_constructor_delegatecall() {
+    let source = r#"
+    #define constant INITIALIZER = 0x00
+
+    #define macro INITIALIZE() = takes(0) returns(0) {
+        0x00 0x00 0x00 0x00 [INITIALIZER] gas delegatecall
+        success jumpi
+        0x00 dup1 revert
+        success:
+    }
+
+    #define macro CONSTRUCTOR() = takes(0) returns(0) {
+        INITIALIZE()
+    }
+    "#;
+    let lints = Linter::new(LintConfig::default()).lint(&parse(source));
+    assert_eq!(rules_of(&lints), vec!["constructor-delegatecall"]);
+    assert_eq!(lints[0].code, Some("S004"));
+}
@@ -0,0 +1,193 @@
+//! ## Struct Codecs
+//!
+//! Generates the macros converting a struct defined with
+//! `#define struct Order(address maker, uint256 amount, bytes data)` to and from its ABI encoding.
+//!
+//! In memory, a struct is laid out like a Solidity memory struct: a word for each field, holding
+//! the value of a value type, or a pointer to the length of a dynamic field followed by its
+//! contents, i.e. the bytes of a `bytes` or `string`, or the elements of an array.
+//!
+//! ```text
+//! Order_ENCODE()  takes (2) returns (1)  // [ptr, dest] -> [end]
+//! Order_DECODE()  takes (2) returns (0)  // [src, dest] -> []
+//! ```
+//!
+//! `Order_ENCODE` writes the encoding of the fields of the struct at `ptr` to `dest`, the head
+//! holding the value types and the offsets of the dynamic fields, which follow in the tail padded
+//! to a multiple of 32 bytes. It returns the end of the encoding, such that `end - dest` is its
+//! size. As for `abi.encode(order.maker, order.amount, order.data)`, the encoding isn't preceded
+//! by the offset of the struct itself.
+//!
+//! `Order_DECODE` lays out the encoding at `src` as a struct at `dest`. Dynamic fields point into
+//! the encoding rather than being copied, as a dynamic field is encoded as its length followed by
+//! its contents, the way it's laid out in memory. Neither the offsets nor the values are
+//! validated, so an untrusted encoding, i.e. copied from calldata, must be checked first.
+
+use huff_utils::prelude::{
+    str_to_bytes32, Literal, MacroDefinition, MacroDefinitionBuilder, Opcode, PrimitiveEVMType,
+    StructDefinition,
+};
+
+/// How a field of a struct is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    /// A value type, encoded in the head
+    Value,
+    /// `bytes` or `string`, encoded in the tail as their length followed by their bytes
+    Bytes,
+    /// A dynamic array of a value type, i.e. `uint256[]`, encoded in the tail as its length
+    /// followed by its elements
+    Array,
+}
+
+impl FieldKind {
+    /// The kind of a field of the given type, if its encoding can be generated
+    pub fn of(ty: &str) -> Option<Self> {
+        let value = |ty: &str| {
+            PrimitiveEVMType::try_from(ty.to_string()).ok().and_then(|t| t.size()).is_some()
+        };
+        match ty.strip_suffix("[]") {
+            Some(element) => value(element).then_some(FieldKind::Array),
+            None => match PrimitiveEVMType::try_from(ty.to_string()) {
+                Ok(PrimitiveEVMType::String | PrimitiveEVMType::DynBytes) => Some(FieldKind::Bytes),
+                _ => value(ty).then_some(FieldKind::Value),
+            },
+        }
+    }
+}
+
+/// The name of the macro encoding a struct
+pub fn encoder_name(name: &str) -> String {
+    format!("{name}_ENCODE")
+}
+
+/// The name of the macro decoding a struct
+pub fn decoder_name(name: &str) -> String {
+    format!("{name}_DECODE")
+}
+
+/// Generates the macro ABI-encoding the struct at the pointer on top of the stack to the
+/// destination below it, returning the end of the encoding.
+///
+/// The fields must have been checked to be of a [FieldKind].
+pub fn encoder(def: &StructDefinition) -> MacroDefinition {
+    // The tail follows the head, a word for each field
+    let mut m = MacroDefinitionBuilder::new(encoder_name(&def.name))
+        .takes(2)
+        .outputs(["end"])
+        .opcode(Opcode::Dup2)
+        .literal(word(def.fields.len() * 32))
+        .opcode(Opcode::Add); // [tail, ptr, dest]
+    for (i, field) in def.fields.iter().enumerate() {
+        let kind = field.arg_type.as_deref().and_then(FieldKind::of).unwrap_or(FieldKind::Value);
+        if kind == FieldKind::Value {
+            m = add(m.opcode(Opcode::Dup2), i * 32).opcode(Opcode::Mload);
+            m = add(m.opcode(Opcode::Dup4), i * 32).opcode(Opcode::Mstore);
+            continue
+        }
+
+        // The head holds the offset of the field from the start of the encoding
+        m = m.opcode(Opcode::Dup3).opcode(Opcode::Dup2).opcode(Opcode::Sub);
+        m = add(m.opcode(Opcode::Dup4), i * 32).opcode(Opcode::Mstore);
+
+        // The size of the length and contents, padded to a multiple of 32 bytes
+        m = add(m.opcode(Opcode::Dup2), i * 32).opcode(Opcode::Mload); // [p, tail, ptr, dest]
+        m = m.opcode(Opcode::Dup1).opcode(Opcode::Mload);
+        if kind == FieldKind::Bytes {
+            m = m.literal(word(0x1f)).opcode(Opcode::Add).literal(word(5)).opcode(Opcode::Shr);
+        }
+        m = m.literal(word(5)).opcode(Opcode::Shl).literal(word(0x20)).opcode(Opcode::Add);
+
+        // Copy them to the tail a word at a time
+        let copy = format!("copy{i}");
+        m = m
+            .literal(word(0)) // [i, size, p, tail, ptr, dest]
+            .label(copy.clone())
+            .opcode(Opcode::Dup3)
+            .opcode(Opcode::Dup2)
+            .opcode(Opcode::Add)
+            .opcode(Opcode::Mload)
+            .opcode(Opcode::Dup5)
+            .opcode(Opcode::Dup3)
+            .opcode(Opcode::Add)
+            .opcode(Opcode::Mstore)
+            .literal(word(0x20))
+            .opcode(Opcode::Add)
+            .opcode(Opcode::Dup2)
+            .opcode(Opcode::Dup2)
+            .opcode(Opcode::Lt)
+            .label_call(copy)
+            .opcode(Opcode::Jumpi)
+            .opcode(Opcode::Pop); // [size, p, tail, ptr, dest]
+
+        if kind == FieldKind::Bytes {
+            // Clear the bytes following the contents in their last word, copied along with it
+            m = m
+                .opcode(Opcode::Dup2)
+                .opcode(Opcode::Mload)
+                .literal(word(0))
+                .opcode(Opcode::Sub)
+                .literal(word(0x1f))
+                .opcode(Opcode::And)
+                .literal(word(3))
+                .opcode(Opcode::Shl) // [bits, size, p, tail, ptr, dest]
+                .literal(word(0x20))
+                .opcode(Opcode::Dup3)
+                .opcode(Opcode::Dup6)
+                .opcode(Opcode::Add)
+                .opcode(Opcode::Sub) // [last, bits, size, p, tail, ptr, dest]
+                .opcode(Opcode::Dup1)
+                .opcode(Opcode::Mload)
+                .opcode(Opcode::Dup3)
+                .opcode(Opcode::Shr)
+                .opcode(Opcode::Dup3)
+                .opcode(Opcode::Shl)
+                .opcode(Opcode::Swap1)
+                .opcode(Opcode::Mstore)
+                .opcode(Opcode::Pop);
+        }
+
+        // The next dynamic field follows in the tail
+        m = m.opcode(Opcode::Swap1).opcode(Opcode::Pop).opcode(Opcode::Add); // [tail, ptr, dest]
+    }
+    let mut macro_def = m.opcode(Opcode::Swap2).opcode(Opcode::Pop).opcode(Opcode::Pop).build();
+    macro_def.span = def.span.clone();
+    macro_def
+}
+
+/// Generates the macro laying out the ABI encoding at the pointer on top of the stack as a struct
+/// at the destination below it.
+///
+/// The fields must have been checked to be of a [FieldKind].
+pub fn decoder(def: &StructDefinition) -> MacroDefinition {
+    let mut m = MacroDefinitionBuilder::new(decoder_name(&def.name)).takes(2);
+    for (i, field) in def.fields.iter().enumerate() {
+        let kind = field.arg_type.as_deref().and_then(FieldKind::of).unwrap_or(FieldKind::Value);
+        m = match kind {
+            FieldKind::Value => add(m.opcode(Opcode::Dup1), i * 32).opcode(Opcode::Mload),
+            // Dynamic fields point to their encoding, at their offset from the start of the head
+            FieldKind::Bytes | FieldKind::Array => {
+                add(m.opcode(Opcode::Dup1).opcode(Opcode::Dup1), i * 32)
+                    .opcode(Opcode::Mload)
+                    .opcode(Opcode::Add)
+            }
+        };
+        m = add(m.opcode(Opcode::Dup3), i * 32).opcode(Opcode::Mstore); // [src, dest]
+    }
+    let mut macro_def = m.opcode(Opcode::Pop).opcode(Opcode::Pop).build();
+    macro_def.span = def.span.clone();
+    macro_def
+}
+
+/// Adds the offset to the word on top of the stack
+fn add(m: MacroDefinitionBuilder, offset: usize) -> MacroDefinitionBuilder {
+    match offset {
+        0 => m,
+        offset => m.literal(word(offset)).opcode(Opcode::Add),
+    }
+}
+
+/// A literal of the value
+fn word(value: usize) -> Literal {
+    str_to_bytes32(&format!("{value:02x}"))
+}
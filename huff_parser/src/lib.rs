@@ -8,11 +8,21 @@ use huff_utils::{
     ast::*,
     error::*,
     files,
-    prelude::{bytes32_to_string, hash_bytes, str_to_bytes32, Span},
+    prelude::{
+        bytes32_to_string, bytes32_to_usize, hash_bytes, negate_bytes32, str_to_bytes32, Edition, Feature,
+        MerkleHash, Opcode, Span, OPCODES_MAP,
+    },
     token::{Token, TokenKind},
     types::*,
 };
 use regex::Regex;
+use std::{collections::BTreeMap, str::FromStr};
+
+pub mod codec;
+pub mod merkle;
+pub mod trie;
+use codec::FieldKind;
+use trie::SelectorTrie;
 
 /// The Parser
 #[derive(Debug, Clone)]
@@ -29,6 +39,52 @@ pub struct Parser {
     pub spans: Vec<Span>,
     /// Our remapper
     pub remapper: files::Remapper,
+    /// The labels `continue` and `break` jump to in the loops enclosing the current statement
+    pub loops: Vec<(String, String)>,
+    /// The number of control flow blocks parsed in the current macro, numbering their labels
+    pub blocks: usize,
+    /// The switches dispatching through a jump table or a selector trie, generated once the
+    /// contract is parsed
+    pub dispatches: Vec<Dispatch>,
+    /// The constants declared in the body of the macro being parsed
+    pub local_constants: Vec<ConstantDefinition>,
+    /// The edition of files not declaring their own
+    pub edition: Edition,
+    /// The editions declared with `#pragma edition`, by file path
+    pub file_editions: BTreeMap<Option<String>, Edition>,
+}
+
+/// How a [Dispatch] routes its value to the matching case
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchKind {
+    /// Through a jump table indexed by the value modulo the table size
+    Table,
+    /// Through a [SelectorTrie] branching on the bytes of the selector
+    Trie,
+}
+
+/// A switch dispatching through a jump table indexed by its value modulo the table size, or
+/// through a selector trie
+///
+/// The lookup code refers to the table and its size, or to an invocation standing in for the
+/// trie, which are only generated once the values of the cases are known.
+#[derive(Debug, Clone)]
+pub struct Dispatch {
+    /// How the switch routes its value
+    pub kind: DispatchKind,
+    /// The contract block the switch is defined in, if any
+    pub contract: Option<String>,
+    /// The name of the jump table, also naming the constant holding its size, or of the
+    /// invocation replaced by the trie
+    pub table: String,
+    /// The statement pushing the value of each case, with the label it jumps to
+    pub cases: Vec<(Statement, String)>,
+    /// The label jumped to when no case matches
+    pub default: String,
+    /// The span of the switch
+    pub span: AstSpan,
+    /// The constants declared in the macro the switch is in, which cases may refer to
+    pub constants: Vec<ConstantDefinition>,
 }
 
 impl Parser {
@@ -36,7 +92,20 @@ impl Parser {
     pub fn new(tokens: Vec<Token>, base: Option<String>) -> Self {
         let initial_token = tokens.get(0).unwrap().clone();
         let remapper = files::Remapper::new("./");
-        Self { tokens, cursor: 0, current_token: initial_token, base, spans: vec![], remapper }
+        Self {
+            tokens,
+            cursor: 0,
+            current_token: initial_token,
+            base,
+            spans: vec![],
+            remapper,
+            loops: vec![],
+            blocks: 0,
+            dispatches: vec![],
+            local_constants: vec![],
+            edition: Edition::default(),
+            file_editions: BTreeMap::new(),
+        }
     }
 
     /// Resets the current token and cursor to the first token in the parser's token vec
@@ -49,6 +118,23 @@ impl Parser {
 
     /// Parse
     pub fn parse(&mut self) -> Result<Contract, ParserError> {
+        // Suppression comments silence the warnings of the token preceding them
+        let mut suppressions = vec![];
+        let mut preceding: Option<&Token> = None;
+        for token in &self.tokens {
+            match &token.kind {
+                TokenKind::Whitespace => {}
+                TokenKind::Comment(comment) => {
+                    if let Some(s) =
+                        preceding.and_then(|t| Suppression::from_comment(comment, t.span.clone()))
+                    {
+                        suppressions.push(s);
+                    }
+                }
+                _ => preceding = Some(token),
+            }
+        }
+
         // Remove all whitespaces, newlines, and comments first
         self.tokens
             .retain(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment(_)));
@@ -56,20 +142,45 @@ impl Parser {
         // Reset the initial token
         self.reset();
 
+        // Files may declare their edition, which the whole file is parsed in
+        self.file_editions = self
+            .tokens
+            .windows(3)
+            .filter_map(|w| match (&w[0].kind, &w[1].kind, &w[2].kind) {
+                (TokenKind::Pragma, TokenKind::Ident(name), value) if name == "edition" => {
+                    let edition = match value {
+                        TokenKind::Str(v) => Edition::from_str(v).ok()?,
+                        TokenKind::Num(n) => Edition::from_str(&n.to_string()).ok()?,
+                        _ => return None,
+                    };
+                    Some((Parser::file_of(&w[0]), edition))
+                }
+                _ => None,
+            })
+            .collect();
+
         // Initialize an empty Contract
-        let mut contract = Contract::default();
+        let mut contract = Contract { suppressions, ..Default::default() };
 
         // Iterate over tokens and construct the Contract aka AST
         while !self.check(TokenKind::Eof) {
             // Reset our spans
             self.spans = vec![];
 
+            // Check for ABI imports with the "#include abi" or "#include sol" keywords
+            if self.check(TokenKind::Include) && self.peek_abi_import() {
+                contract.abi_imports.push(self.parse_abi_import()?);
+            }
             // Check for imports with the "#include" keyword
-            if self.check(TokenKind::Include) {
+            else if self.check(TokenKind::Include) {
                 contract.imports.push(self.parse_imports()?);
             }
-            // Check for a decorator above a test macro
-            else if self.check(TokenKind::Pound) {
+            // Check for a pragma declaring a requirement of the source
+            else if self.check(TokenKind::Pragma) && self.peek_source_pragma() {
+                contract.pragmas.push(self.parse_source_pragma()?);
+            }
+            // Check for a decorator above a test macro, or pragmas above a macro
+            else if self.check(TokenKind::Pound) || self.check(TokenKind::Pragma) {
                 let m = self.parse_macro()?;
                 tracing::info!(target: "parser", "SUCCESSFULLY PARSED MACRO {}", m.name);
                 contract.macros.push(m);
@@ -79,49 +190,22 @@ impl Parser {
                 // Consume the definition token
                 self.match_kind(TokenKind::Define)?;
 
-                // match to fucntion, constant, macro, or event
-                match self.current_token.kind {
-                    TokenKind::Function => {
-                        let func = self.parse_function()?;
-                        tracing::info!(target: "parser", "SUCCESSFULLY PARSED FUNCTION {}", func.name);
-                        contract.functions.push(func);
-                    }
-                    TokenKind::Event => {
-                        let ev = self.parse_event()?;
-                        tracing::info!(target: "parser", "SUCCESSFULLY PARSED EVENT {}", ev.name);
-                        contract.events.push(ev);
-                    }
-                    TokenKind::Constant => {
-                        let c = self.parse_constant()?;
-                        tracing::info!(target: "parser", "SUCCESSFULLY PARSED CONSTANT {}", c.name);
-                        contract.constants.lock().unwrap().push(c);
-                    }
-                    TokenKind::Error => {
-                        let e = self.parse_custom_error()?;
-                        tracing::info!(target: "parser", "SUCCESSFULLY PARSED ERROR {}", e.name);
-                        contract.errors.push(e);
-                    }
-                    TokenKind::Macro | TokenKind::Fn | TokenKind::Test => {
-                        let m = self.parse_macro()?;
-                        tracing::info!(target: "parser", "SUCCESSFULLY PARSED MACRO {}", m.name);
-                        contract.macros.push(m);
-                    }
-                    TokenKind::JumpTable | TokenKind::JumpTablePacked | TokenKind::CodeTable => {
-                        contract.tables.push(self.parse_table()?);
-                    }
-                    _ => {
-                        tracing::error!(
-                            target: "parser",
-                            "Invalid definition. Must be a function, event, constant, error, or macro. Got: {}",
-                            self.current_token.kind
-                        );
+                // Contract blocks can only be defined at the top level of a file
+                if self.check(TokenKind::Contract) {
+                    let c = self.parse_contract()?;
+                    if contract.contracts.iter().any(|d| d.name == c.name) {
+                        tracing::error!(target: "parser", "DUPLICATE CONTRACT DEFINITION: {}", c.name);
                         return Err(ParserError {
-                            kind: ParserErrorKind::InvalidDefinition(self.current_token.kind.clone()),
-                            hint: Some("Definition must be one of: `function`, `event`, `constant`, `error`, `macro`, `fn`, or `test`.".to_string()),
-                            spans: AstSpan(vec![self.current_token.span.clone()]),
-                        });
+                            kind: ParserErrorKind::DuplicateContract(c.name),
+                            hint: Some("Contract names must be unique within a file.".to_string()),
+                            spans: c.span,
+                        })
                     }
-                };
+                    tracing::info!(target: "parser", "SUCCESSFULLY PARSED CONTRACT {}", c.name);
+                    contract.contracts.push(c);
+                } else {
+                    self.parse_definition(&mut contract)?;
+                }
             } else {
                 // If we don't have an "#include" or "#define" keyword, we have an invalid token
                 return Err(ParserError {
@@ -136,9 +220,127 @@ impl Parser {
             }
         }
 
+        self.generate_dispatch_tables(&mut contract)?;
+        Parser::generate_struct_codecs(&mut contract)?;
+        Parser::generate_merkle_verifiers(&mut contract)?;
         Ok(contract)
     }
 
+    /// Parses a definition following a `#define` keyword into the contract
+    pub fn parse_definition(&mut self, contract: &mut Contract) -> Result<(), ParserError> {
+        // match to fucntion, constant, macro, event, error, or table
+        match self.current_token.kind {
+            TokenKind::Function => {
+                let func = self.parse_function()?;
+                tracing::info!(target: "parser", "SUCCESSFULLY PARSED FUNCTION {}", func.name);
+                contract.functions.push(func);
+            }
+            TokenKind::Event => {
+                let ev = self.parse_event()?;
+                tracing::info!(target: "parser", "SUCCESSFULLY PARSED EVENT {}", ev.name);
+                contract.events.push(ev);
+            }
+            TokenKind::Constant => {
+                let c = self.parse_constant()?;
+                tracing::info!(target: "parser", "SUCCESSFULLY PARSED CONSTANT {}", c.name);
+                contract.constants.lock().unwrap().push(c);
+            }
+            TokenKind::Error => {
+                let e = self.parse_custom_error()?;
+                tracing::info!(target: "parser", "SUCCESSFULLY PARSED ERROR {}", e.name);
+                contract.errors.push(e);
+            }
+            TokenKind::Macro | TokenKind::Fn | TokenKind::Test => {
+                let m = self.parse_macro()?;
+                tracing::info!(target: "parser", "SUCCESSFULLY PARSED MACRO {}", m.name);
+                contract.macros.push(m);
+            }
+            TokenKind::JumpTable | TokenKind::JumpTablePacked | TokenKind::CodeTable => {
+                contract.tables.push(self.parse_table()?);
+            }
+            TokenKind::Data => {
+                let d = self.parse_data()?;
+                tracing::info!(target: "parser", "SUCCESSFULLY PARSED DATA SECTION {}", d.name);
+                contract.tables.push(d);
+            }
+            TokenKind::Struct => {
+                let s = self.parse_struct()?;
+                tracing::info!(target: "parser", "SUCCESSFULLY PARSED STRUCT {}", s.name);
+                contract.structs.push(s);
+            }
+            TokenKind::Merkle => {
+                let t = self.parse_merkle_tree()?;
+                tracing::info!(target: "parser", "SUCCESSFULLY PARSED MERKLE TREE {}", t.name);
+                contract.merkle_trees.push(t);
+            }
+            _ => {
+                tracing::error!(
+                    target: "parser",
+                    "Invalid definition. Must be a function, event, constant, error, or macro. Got: {}",
+                    self.current_token.kind
+                );
+                return Err(ParserError {
+                    kind: ParserErrorKind::InvalidDefinition(self.current_token.kind.clone()),
+                    hint: Some("Definition must be one of: `function`, `event`, `constant`, `error`, `struct`, `merkle`, `macro`, `fn`, `test`, or `contract`.".to_string()),
+                    spans: AstSpan(vec![self.current_token.span.clone()]),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parses a contract definition, a named contract scope holding its own definitions.
+    ///
+    /// It should parse the following : contract CONTRACT_NAME { ... }
+    pub fn parse_contract(&mut self) -> Result<ContractDefinition, ParserError> {
+        self.match_kind(TokenKind::Contract)?;
+        let name = self.match_kind(TokenKind::Ident("CONTRACT_NAME".to_string()))?.to_string();
+        let span = AstSpan(self.spans.clone());
+        self.match_kind(TokenKind::OpenBrace)?;
+        let first_dispatch = self.dispatches.len();
+
+        let mut contract = Contract::default();
+        while !self.check(TokenKind::CloseBrace) {
+            // Reset our spans
+            self.spans = vec![];
+
+            // Check for a decorator above a test macro, or pragmas above a macro
+            if self.check(TokenKind::Pound) || self.check(TokenKind::Pragma) {
+                let m = self.parse_macro()?;
+                tracing::info!(target: "parser", "SUCCESSFULLY PARSED MACRO {}", m.name);
+                contract.macros.push(m);
+            } else if self.check(TokenKind::Define) {
+                self.match_kind(TokenKind::Define)?;
+                if self.check(TokenKind::Contract) {
+                    tracing::error!(target: "parser", "NESTED CONTRACT DEFINITION IN CONTRACT {}", name);
+                    return Err(ParserError {
+                        kind: ParserErrorKind::InvalidDefinition(self.current_token.kind.clone()),
+                        hint: Some(
+                            "Contracts can't be defined inside of another contract.".to_string(),
+                        ),
+                        spans: AstSpan(vec![self.current_token.span.clone()]),
+                    })
+                }
+                self.parse_definition(&mut contract)?;
+            } else {
+                // Includes are shared by the whole file and must be at its top level
+                return Err(ParserError {
+                    kind: ParserErrorKind::UnexpectedType(self.current_token.kind.clone()),
+                    hint: Some(format!(
+                        "Expected either \"{}\" or \"}}\" in contract \"{name}\"",
+                        TokenKind::Define
+                    )),
+                    spans: AstSpan(vec![self.current_token.span.clone()]),
+                })
+            }
+        }
+        self.match_kind(TokenKind::CloseBrace)?;
+        self.dispatches[first_dispatch..].iter_mut().for_each(|d| d.contract = Some(name.clone()));
+
+        Ok(ContractDefinition { name, contract, span })
+    }
+
     /// Parses Contract Imports
     pub fn parse_imports(&mut self) -> Result<FilePath, ParserError> {
         // First token should be keyword "#include"
@@ -164,6 +366,41 @@ impl Parser {
         Ok(std::path::PathBuf::from(p))
     }
 
+    /// Checks if the current include imports an ABI or Solidity file
+    fn peek_abi_import(&mut self) -> bool {
+        match self.peek().map(|t| t.kind) {
+            Some(TokenKind::Ident(kind)) => AbiImportKind::try_from(kind.as_str()).is_ok(),
+            _ => false,
+        }
+    }
+
+    /// Parses an ABI or Solidity import, whose entries are declared once the compiler reads the
+    /// file.
+    ///
+    /// It should parse the following : #include abi "IERC20.json" or #include sol "IERC20.sol"
+    pub fn parse_abi_import(&mut self) -> Result<AbiImport, ParserError> {
+        self.match_kind(TokenKind::Include)?;
+        let kind = self.match_kind(TokenKind::Ident("abi".to_string()))?.to_string();
+        let kind = AbiImportKind::try_from(kind.as_str()).unwrap_or_default();
+        let path = self.match_kind(TokenKind::Str("x".to_string()))?.to_string();
+        let import =
+            AbiImport::new(std::path::PathBuf::from(path), kind, AstSpan(self.spans.clone()));
+        // Solidity imports are named after their interfaces instead
+        if kind == AbiImportKind::Abi && (import.name.is_empty() || import.name.contains('.')) {
+            tracing::error!(target: "parser", "INVALID ABI IMPORT NAME: {}", import.name);
+            return Err(ParserError {
+                kind: ParserErrorKind::InvalidName(self.peek_behind().unwrap().kind),
+                hint: Some(
+                    "The stem of an ABI file names its entries, i.e. \"IERC20\" of \"IERC20.json\", and can't contain dots."
+                        .to_string(),
+                ),
+                spans: import.span,
+            })
+        }
+        tracing::info!(target: "parser", "SUCCESSFULLY PARSED ABI IMPORT {}", import.name);
+        Ok(import)
+    }
+
     /// Match current token to a type.
     pub fn match_kind(&mut self, kind: TokenKind) -> Result<TokenKind, ParserError> {
         if std::mem::discriminant(&self.current_token.kind) == std::mem::discriminant(&kind) {
@@ -322,29 +559,94 @@ impl Parser {
 
         // Parse the constant name
         self.match_kind(TokenKind::Ident("x".to_string()))?;
-        let tok = self.peek_behind().unwrap().kind;
-        let name = match tok {
-            TokenKind::Ident(const_name) => const_name,
-            _ => {
-                tracing::error!(target: "parser", "TOKEN MISMATCH - EXPECTED IDENT, GOT: {}", tok);
+        let tok = self.peek_behind().unwrap();
+        let name = match tok.kind {
+            TokenKind::Ident(const_name) => {
+                self.check_opcode_name(&const_name, "constant", AstSpan(vec![tok.span]))?;
+                const_name
+            }
+            kind => {
+                tracing::error!(target: "parser", "TOKEN MISMATCH - EXPECTED IDENT, GOT: {}", kind);
                 return Err(ParserError {
-                    kind: ParserErrorKind::UnexpectedType(tok),
+                    kind: ParserErrorKind::UnexpectedType(kind),
                     hint: Some("Expected constant name.".to_string()),
                     spans: AstSpan(self.spans.clone()),
                 })
             }
         };
 
+        // Parse the optional type annotation
+        let ty = if self.check(TokenKind::Colon) {
+            self.consume();
+            match self.current_token.kind.clone() {
+                TokenKind::PrimitiveType(ty) if ty.size().is_some() => {
+                    self.consume();
+                    Some(ty)
+                }
+                kind => {
+                    tracing::error!(target: "parser", "INVALID CONSTANT TYPE: {}", kind);
+                    return Err(ParserError {
+                        kind: ParserErrorKind::InvalidConstantType(kind),
+                        hint: Some(
+                            "Expected a statically sized type, e.g. `uint8`, `address` or `bytes4`"
+                                .to_string(),
+                        ),
+                        spans: AstSpan(vec![self.current_token.span.clone()]),
+                    })
+                }
+            }
+        } else {
+            None
+        };
+
         // We must assign a value to the constant
         self.match_kind(TokenKind::Assign)?;
 
-        let value: ConstVal = match self.current_token.kind.clone() {
+        // Decimal numbers are placed as hex like literals
+        let kind = match self.current_token.kind.clone() {
+            TokenKind::Num(n) => TokenKind::Literal(str_to_bytes32(&format!("{n:x}"))),
+            kind => kind,
+        };
+        let value: ConstVal = match kind {
             TokenKind::FreeStoragePointer => {
                 self.consume();
-                ConstVal::FreeStoragePointer(FreeStoragePointer {})
+                ConstVal::FreeStoragePointer(FreeStoragePointer {
+                    slot: self.parse_storage_slot()?,
+                })
             }
-            TokenKind::Literal(l) => {
+            TokenKind::StorageSlot => {
+                self.consume();
+                self.match_kind(TokenKind::OpenParen)?;
+                let slot = self.parse_slot()?;
+                self.match_kind(TokenKind::CloseParen)?;
+                ConstVal::FreeStoragePointer(FreeStoragePointer { slot: Some(slot) })
+            }
+            TokenKind::FreeMemoryPointer => {
                 self.consume();
+                ConstVal::FreeMemoryPointer(FreeMemoryPointer { size: self.parse_memory_size()? })
+            }
+            TokenKind::Literal(_) | TokenKind::Sub => {
+                let (l, spans) = match kind {
+                    TokenKind::Literal(l) => {
+                        let spans = vec![self.current_token.span.clone()];
+                        self.consume();
+                        (l, spans)
+                    }
+                    // Negative numbers are placed in two's complement
+                    _ => self.parse_negative_literal()?,
+                };
+                // Validate the literal against the type annotation, if any
+                if let Some(ty) = ty.filter(|ty| !ty.fits_literal(&l)) {
+                    tracing::error!(target: "parser", "CONSTANT \"{}\" DOES NOT FIT TYPE \"{}\"", name, ty);
+                    return Err(ParserError {
+                        kind: ParserErrorKind::ConstantTypeMismatch(name, ty),
+                        hint: Some(format!(
+                            "Expected a value of at most {} byte(s)",
+                            ty.size().unwrap_or_default()
+                        )),
+                        spans: AstSpan(spans),
+                    })
+                }
                 ConstVal::Literal(l)
             }
             kind => {
@@ -352,7 +654,7 @@ impl Parser {
                 return Err(ParserError {
                     kind: ParserErrorKind::InvalidConstantValue(kind),
                     hint: Some(
-                        "Expected constant value to be a literal or `FREE_STORAGE_POINTER()`"
+                        "Expected constant value to be a literal, `FREE_STORAGE_POINTER()`, `STORAGE_SLOT()` or `FREE_MEMORY_POINTER()`"
                             .to_string(),
                     ),
                     spans: AstSpan(vec![self.current_token.span.clone()]),
@@ -365,7 +667,161 @@ impl Parser {
         self.spans = vec![];
 
         // Return the Constant Definition
-        Ok(ConstantDefinition { name, value, span: AstSpan(new_spans) })
+        Ok(ConstantDefinition { name, ty, value, span: AstSpan(new_spans) })
+    }
+
+    /// Parses the optional explicit slot of a free storage pointer, i.e. `(slot=3)`.
+    pub fn parse_storage_slot(&mut self) -> Result<Option<Literal>, ParserError> {
+        if !self.check(TokenKind::OpenParen) {
+            return Ok(None)
+        }
+        self.consume();
+        if !matches!(&self.current_token.kind, TokenKind::Ident(key) if key == "slot") {
+            return Err(self.invalid_slot())
+        }
+        self.consume();
+        self.match_kind(TokenKind::Assign)?;
+        let slot = self.parse_slot()?;
+        self.match_kind(TokenKind::CloseParen)?;
+        Ok(Some(slot))
+    }
+
+    /// Parses a storage slot, either a hex literal, a decimal number or a storage slot builtin,
+    /// i.e. `__ERC7201("example.main")`.
+    pub fn parse_slot(&mut self) -> Result<Literal, ParserError> {
+        let slot = match self.current_token.kind.clone() {
+            TokenKind::Literal(l) => l,
+            TokenKind::Num(n) => str_to_bytes32(&format!("{n:x}")),
+            TokenKind::Ident(name) => {
+                let kind = match BuiltinFunctionKind::try_from(&name) {
+                    Ok(kind) if kind.storage_slot(&[]).is_some() => kind,
+                    _ => return Err(self.invalid_slot()),
+                };
+                let mut spans = vec![self.current_token.span.clone()];
+                self.consume();
+                let args = self.parse_args(true, false, false, true)?;
+                args.iter().for_each(|a| spans.extend_from_slice(&a.span.0));
+                let args = args
+                    .iter()
+                    .map(|a| a.name.as_deref().unwrap_or_default())
+                    .collect::<Vec<&str>>();
+                return match kind.storage_slot(&args) {
+                    Some(Ok(slot)) => Ok(slot),
+                    res => {
+                        tracing::error!(target: "parser", "INVALID ARGUMENTS PASSED TO {}", name);
+                        Err(ParserError {
+                            kind: ParserErrorKind::InvalidArgs(TokenKind::Ident(name)),
+                            hint: res.and_then(Result::err),
+                            spans: AstSpan(spans),
+                        })
+                    }
+                }
+            }
+            _ => return Err(self.invalid_slot()),
+        };
+        self.consume();
+        Ok(slot)
+    }
+
+    /// The path of the file a token was lexed from, if the source has files
+    fn file_of(token: &Token) -> Option<String> {
+        token.span.file.as_ref().map(|f| f.path.clone())
+    }
+
+    /// The edition of the file being parsed
+    pub fn current_edition(&self) -> Edition {
+        let file = Parser::file_of(&self.current_token);
+        self.file_editions.get(&file).copied().unwrap_or(self.edition)
+    }
+
+    /// Errors if the file being parsed is of an edition without the given feature
+    fn require(&self, feature: Feature, spans: Vec<Span>) -> Result<(), ParserError> {
+        if self.current_edition().supports(feature) {
+            return Ok(())
+        }
+        tracing::error!(target: "parser", "{} REQUIRE EDITION {}", feature.to_string().to_uppercase(), feature.edition());
+        Err(ParserError {
+            kind: ParserErrorKind::UnsupportedFeature(feature),
+            hint: Some(format!(
+                "The file is edition {}. Declare `#pragma edition \"{}\"` as its first line, or compile with `--edition {}`.",
+                self.current_edition(),
+                feature.edition(),
+                feature.edition()
+            )),
+            spans: AstSpan(spans),
+        })
+    }
+
+    /// Errors if a label, constant or macro argument is named after an opcode, as references to
+    /// it would be lexed as the opcode and silently miscompile.
+    ///
+    /// The 2022 edition allows these names.
+    fn check_opcode_name(
+        &self,
+        name: &str,
+        definition: &str,
+        spans: AstSpan,
+    ) -> Result<(), ParserError> {
+        if !OPCODES_MAP.contains_key(name) ||
+            !self.current_edition().supports(Feature::OpcodeNameCollisions)
+        {
+            return Ok(())
+        }
+        tracing::error!(target: "parser", "{} \"{}\" COLLIDES WITH AN OPCODE", definition.to_uppercase(), name);
+        let suggestion =
+            if definition == "constant" { name.to_uppercase() } else { format!("{name}_") };
+        Err(ParserError {
+            kind: ParserErrorKind::OpcodeNameCollision(name.to_string()),
+            hint: Some(format!(
+                "The {definition} \"{name}\" is named after an opcode, so references to it compile to the opcode. Rename it, i.e. to \"{suggestion}\"."
+            )),
+            spans,
+        })
+    }
+
+    fn invalid_slot(&self) -> ParserError {
+        tracing::error!(target: "parser", "INVALID STORAGE SLOT: {}", self.current_token.kind);
+        ParserError {
+            kind: ParserErrorKind::InvalidConstantValue(self.current_token.kind.clone()),
+            hint: Some(
+                "Expected an explicit storage slot, i.e. `STORAGE_SLOT(0x02)` or `FREE_STORAGE_POINTER(slot=2)`"
+                    .to_string(),
+            ),
+            spans: AstSpan(vec![self.current_token.span.clone()]),
+        }
+    }
+
+    /// Parses the optional size of a free memory pointer's region, i.e. `(0x40)`.
+    ///
+    /// The size is rounded up to a multiple of 32 bytes, defaulting to a single word.
+    pub fn parse_memory_size(&mut self) -> Result<usize, ParserError> {
+        if !self.check(TokenKind::OpenParen) {
+            return Ok(32)
+        }
+        self.consume();
+        let size = match self.current_token.kind.clone() {
+            TokenKind::Literal(l) => usize::from_str_radix(&bytes32_to_string(&l, false), 16)
+                .ok()
+                .filter(|size| (1..=u32::MAX as usize).contains(size)),
+            _ => None,
+        };
+        let size = match size {
+            Some(size) => size,
+            None => {
+                tracing::error!(target: "parser", "INVALID FREE MEMORY POINTER SIZE: {}", self.current_token.kind);
+                return Err(ParserError {
+                    kind: ParserErrorKind::InvalidConstantValue(self.current_token.kind.clone()),
+                    hint: Some(
+                        "Expected the size of the memory region in bytes, i.e. `FREE_MEMORY_POINTER(0x40)`"
+                            .to_string(),
+                    ),
+                    spans: AstSpan(vec![self.current_token.span.clone()]),
+                })
+            }
+        };
+        self.consume();
+        self.match_kind(TokenKind::CloseParen)?;
+        Ok(size.div_ceil(32) * 32)
     }
 
     /// Parse a custom error definition.
@@ -403,6 +859,111 @@ impl Parser {
         Ok(ErrorDefinition { name, selector, parameters, span: AstSpan(new_spans) })
     }
 
+    /// Parses a struct definition, i.e. `struct Order(address maker, uint256 amount, bytes data)`.
+    ///
+    /// Fields must be named, and be value types, `bytes`, `string`, or dynamic arrays of value
+    /// types, for the codec macros of the struct to be generated.
+    pub fn parse_struct(&mut self) -> Result<StructDefinition, ParserError> {
+        self.require(Feature::StructDefinitions, vec![self.current_token.span.clone()])?;
+        self.match_kind(TokenKind::Struct)?;
+        let name = self.match_kind(TokenKind::Ident("STRUCT_NAME".to_string()))?.to_string();
+        let fields = self.parse_args(true, true, false, false)?;
+        for field in &fields {
+            let ty = field.arg_type.clone().unwrap_or_default();
+            let reason = match (&field.name, FieldKind::of(&ty)) {
+                (None, _) => format!("A field of struct \"{name}\" of type \"{ty}\" is unnamed"),
+                (Some(field), None) => {
+                    format!(
+                        "Field \"{field}\" of struct \"{name}\" is of unsupported type \"{ty}\""
+                    )
+                }
+                _ => continue,
+            };
+            tracing::error!(target: "parser", "INVALID STRUCT FIELD: {}", reason);
+            return Err(ParserError {
+                kind: ParserErrorKind::InvalidStruct(reason),
+                hint: Some(
+                    "Struct fields must be named value types, `bytes`, `string`, or dynamic arrays of value types, i.e. `uint256[] amounts`".to_string(),
+                ),
+                spans: field.span.clone(),
+            })
+        }
+
+        let span = AstSpan(std::mem::take(&mut self.spans));
+        Ok(StructDefinition { name, fields, span })
+    }
+
+    /// Parses a merkle tree definition, i.e. `merkle AIRDROP(keccak256, 0x10) = "airdrop.json"`.
+    ///
+    /// The hash function is `keccak256` or `sha256`, followed by the fixed depth of the tree's
+    /// proofs if the verification is unrolled, and the json file of the tree's leaves is optional.
+    pub fn parse_merkle_tree(&mut self) -> Result<MerkleTreeDefinition, ParserError> {
+        self.require(Feature::MerkleTrees, vec![self.current_token.span.clone()])?;
+        self.match_kind(TokenKind::Merkle)?;
+        let name = self.match_kind(TokenKind::Ident("MERKLE_NAME".to_string()))?.to_string();
+        self.match_kind(TokenKind::OpenParen)?;
+
+        let invalid = |kind: &TokenKind, span: &Span, hint: &str| {
+            tracing::error!(target: "parser", "INVALID MERKLE TREE ARGUMENT: {}", kind);
+            ParserError {
+                kind: ParserErrorKind::InvalidMerkleTree(format!(
+                    "Invalid argument \"{kind}\" of merkle tree \"{name}\""
+                )),
+                hint: Some(hint.to_string()),
+                spans: AstSpan(vec![span.clone()]),
+            }
+        };
+        let hash = match &self.current_token.kind {
+            TokenKind::Ident(hash) => MerkleHash::try_from(hash.as_str()).ok(),
+            _ => None,
+        };
+        let hash = match hash {
+            Some(hash) => hash,
+            None => {
+                return Err(invalid(
+                    &self.current_token.kind,
+                    &self.current_token.span,
+                    "The hash function of a merkle tree is either `keccak256` or `sha256`",
+                ))
+            }
+        };
+        self.consume();
+
+        let mut depth = None;
+        if self.check(TokenKind::Comma) {
+            self.consume();
+            let value = match &self.current_token.kind {
+                TokenKind::Literal(l) => {
+                    usize::from_str_radix(&bytes32_to_string(l, false), 16).ok()
+                }
+                TokenKind::Num(n) => Some(*n),
+                _ => None,
+            };
+            match value {
+                Some(value) if (1..=64).contains(&value) => depth = Some(value),
+                _ => {
+                    return Err(invalid(
+                        &self.current_token.kind,
+                        &self.current_token.span,
+                        "The fixed depth of a merkle tree's proofs is between 1 and 64",
+                    ))
+                }
+            }
+            self.consume();
+        }
+        self.match_kind(TokenKind::CloseParen)?;
+
+        let mut leaves = None;
+        if self.check(TokenKind::Assign) {
+            self.consume();
+            let path = self.match_kind(TokenKind::Str(String::default()))?.to_string();
+            leaves = Some(FilePath::from(path));
+        }
+
+        let span = AstSpan(std::mem::take(&mut self.spans));
+        Ok(MerkleTreeDefinition { name, hash, depth, leaves, span })
+    }
+
     /// Parses a decorator.
     ///
     /// Decorators are currently used to add additional flags to a test.
@@ -450,6 +1011,53 @@ impl Parser {
                             })
                         }
                     }
+                    // The fuzz flag accepts the function signature of the fuzzed calldata
+                    Ok(DecoratorFlag::Fuzz(_)) => match self.current_token.kind.clone() {
+                        TokenKind::Str(sig) if sig.contains('(') && sig.ends_with(')') => {
+                            self.consume();
+                            flags.push(DecoratorFlag::Fuzz(sig));
+                        }
+                        kind => {
+                            return Err(ParserError {
+                                kind: ParserErrorKind::InvalidDecoratorFlagArg(kind),
+                                hint: Some(format!(
+                                    "Expected function signature for decorator flag: {s}, i.e. `fuzz(\"add(uint256,uint256)\")`"
+                                )),
+                                spans: AstSpan(vec![self.current_token.span.clone()]),
+                            })
+                        }
+                    },
+                    // The runs and seed flags accept a single literal or number as an argument
+                    Ok(DecoratorFlag::Runs(_)) => {
+                        flags.push(DecoratorFlag::Runs(self.parse_decorator_literal(&s)?))
+                    }
+                    Ok(DecoratorFlag::Seed(_)) => {
+                        flags.push(DecoratorFlag::Seed(self.parse_decorator_literal(&s)?))
+                    }
+                    // The only_caller flag accepts the constant the caller is checked against,
+                    // optionally followed by the custom error to revert with
+                    Ok(DecoratorFlag::OnlyCaller(..)) => {
+                        let constant = match self.current_token.kind.clone() {
+                            TokenKind::Ident(constant) => constant,
+                            kind => {
+                                return Err(ParserError {
+                                    kind: ParserErrorKind::InvalidDecoratorFlagArg(kind),
+                                    hint: Some(format!(
+                                        "Expected a constant for decorator flag: {s}, i.e. `only_caller(OWNER, Unauthorized)`"
+                                    )),
+                                    spans: AstSpan(vec![self.current_token.span.clone()]),
+                                })
+                            }
+                        };
+                        self.consume();
+                        let error = if self.check(TokenKind::Comma) {
+                            self.consume();
+                            Some(self.match_kind(TokenKind::Ident("ERROR".to_string()))?.to_string())
+                        } else {
+                            None
+                        };
+                        flags.push(DecoratorFlag::OnlyCaller(constant, error));
+                    }
                     Err(_) => {
                         tracing::error!(target: "parser", "DECORATOR FLAG NOT FOUND: {}", s);
                         return Err(ParserError {
@@ -484,16 +1092,79 @@ impl Parser {
         Ok(Decorator { flags })
     }
 
+    /// Parses the literal or number argument of a decorator flag.
+    fn parse_decorator_literal(&mut self, flag: &str) -> Result<Literal, ParserError> {
+        let literal = match self.current_token.kind {
+            TokenKind::Literal(l) => l,
+            TokenKind::Num(n) => str_to_bytes32(&format!("{n:x}")),
+            _ => {
+                return Err(ParserError {
+                    kind: ParserErrorKind::InvalidDecoratorFlagArg(self.current_token.kind.clone()),
+                    hint: Some(format!("Expected literal for decorator flag: {flag}")),
+                    spans: AstSpan(vec![self.current_token.span.clone()]),
+                })
+            }
+        };
+        self.consume();
+        Ok(literal)
+    }
+
+    /// Parses a negative literal, i.e. `-1` or `-0x20`, into its two's complement. Returns the
+    /// literal along with the spans of the sign and the number.
+    ///
+    /// Negative literals are pushed with all 32 bytes. Small values are cheaper to push sign
+    /// extended, i.e. `0xe0 0x00 signextend` for `-0x20`.
+    pub fn parse_negative_literal(&mut self) -> Result<(Literal, Vec<Span>), ParserError> {
+        let mut curr_spans = vec![self.current_token.span.clone()];
+        self.require(Feature::NegativeLiterals, curr_spans.clone())?;
+        self.match_kind(TokenKind::Sub)?;
+        curr_spans.push(self.current_token.span.clone());
+        let kind = self.current_token.kind.clone();
+        let negated = match &kind {
+            TokenKind::Literal(l) => negate_bytes32(l),
+            TokenKind::Num(n) => negate_bytes32(&str_to_bytes32(&format!("{n:x}"))),
+            _ => None,
+        };
+        match negated {
+            Some(literal) => {
+                self.consume();
+                Ok((literal, curr_spans))
+            }
+            None => {
+                tracing::error!(target: "parser", "INVALID NEGATIVE LITERAL: -{}", kind);
+                Err(ParserError {
+                    kind: ParserErrorKind::InvalidNegativeLiteral(kind),
+                    hint: Some(
+                        "Expected a literal of at most 2**255, the minimum of an int256"
+                            .to_string(),
+                    ),
+                    spans: AstSpan(curr_spans),
+                })
+            }
+        }
+    }
+
     /// Parses a macro.
     ///
     /// It should parse the following : macro MACRO_NAME(args...) = takes (x) returns (n) {...}
+    ///
+    /// The `takes` and `returns` clauses may be omitted, defaulting to 0, or swapped. The outputs
+    /// may be named instead of counted, i.e. `returns (quotient, remainder)`.
     pub fn parse_macro(&mut self) -> Result<MacroDefinition, ParserError> {
+        let mut pragmas = vec![];
+        while self.check(TokenKind::Pragma) {
+            self.require(Feature::MacroPragmas, vec![self.current_token.span.clone()])?;
+            let pragma = self.parse_pragma()?;
+            if !pragmas.contains(&pragma) {
+                pragmas.push(pragma);
+            }
+        }
         let mut decorator: Option<Decorator> = None;
         if self.check(TokenKind::Pound) {
             decorator = Some(self.parse_decorator()?);
-
-            // Consume the `#define` keyword
-            self.consume();
+        }
+        if !pragmas.is_empty() || decorator.is_some() {
+            self.match_kind(TokenKind::Define)?;
         }
 
         let outlined = self.check(TokenKind::Fn);
@@ -512,26 +1183,354 @@ impl Parser {
         tracing::info!(target: "parser", "PARSING MACRO: \"{}\"", macro_name);
 
         let macro_arguments = self.parse_args(true, false, false, false)?;
+        for arg in &macro_arguments {
+            if let Some(name) = &arg.name {
+                self.check_opcode_name(name, "macro argument", arg.span.clone())?;
+            }
+        }
         self.match_kind(TokenKind::Assign)?;
-
-        let macro_takes =
-            self.match_kind(TokenKind::Takes).map_or(Ok(0), |_| self.parse_single_arg())?;
-        let macro_returns =
-            self.match_kind(TokenKind::Returns).map_or(Ok(0), |_| self.parse_single_arg())?;
+        self.blocks = 0;
+        self.local_constants.clear();
+        let dispatches = self.dispatches.len();
+
+        // `takes` and `returns` are optional, default to 0 and may be given in either order
+        let (mut macro_takes, mut macro_returns) = (None, None);
+        let mut macro_outputs = vec![];
+        while self.check(TokenKind::Takes) || self.check(TokenKind::Returns) {
+            let clause = self.current_token.kind.clone();
+            let value = match clause {
+                TokenKind::Takes => &mut macro_takes,
+                _ => &mut macro_returns,
+            };
+            if value.is_some() {
+                tracing::error!(target: "parser", "DUPLICATE \"{}\" IN MACRO \"{}\"", clause, macro_name);
+                return Err(ParserError {
+                    kind: ParserErrorKind::UnexpectedType(clause.clone()),
+                    hint: Some(format!("\"{clause}\" may only be specified once per macro")),
+                    spans: AstSpan(vec![self.current_token.span.clone()]),
+                })
+            }
+            self.consume();
+            *value = Some(match clause {
+                TokenKind::Takes => self.parse_single_arg()?,
+                _ => {
+                    let (returns, outputs) = self.parse_outputs()?;
+                    macro_outputs = outputs;
+                    returns
+                }
+            });
+        }
+        if !self.check(TokenKind::OpenBrace) {
+            return Err(ParserError {
+                kind: ParserErrorKind::UnexpectedType(self.current_token.kind.clone()),
+                hint: Some("Expected \"takes\", \"returns\" or the macro body".to_string()),
+                spans: AstSpan(vec![self.current_token.span.clone()]),
+            })
+        }
+        let (macro_takes, macro_returns) = (macro_takes.unwrap_or(0), macro_returns.unwrap_or(0));
 
         let macro_statements: Vec<Statement> = self.parse_body()?;
 
-        Ok(MacroDefinition::new(
-            macro_name,
-            decorator,
-            macro_arguments,
-            macro_statements,
-            macro_takes,
-            macro_returns,
-            self.spans.clone(),
-            outlined,
-            test,
-        ))
+        let mut macro_def = MacroDefinition::new(
+            macro_name,
+            decorator,
+            macro_arguments,
+            macro_statements,
+            macro_takes,
+            macro_returns,
+            self.spans.clone(),
+            outlined,
+            test,
+        );
+        macro_def.constants = std::mem::take(&mut self.local_constants);
+        macro_def.outputs = macro_outputs;
+        macro_def.pragmas = pragmas;
+        for dispatch in &mut self.dispatches[dispatches..] {
+            dispatch.constants = macro_def.constants.clone();
+        }
+        Ok(macro_def)
+    }
+
+    /// Parses a macro pragma, i.e. `#pragma no-inline`.
+    pub fn parse_pragma(&mut self) -> Result<MacroPragma, ParserError> {
+        self.match_kind(TokenKind::Pragma)?;
+        let span = self.current_token.span.clone();
+        let name = self.match_kind(TokenKind::Ident("PRAGMA".to_string()))?.to_string();
+        MacroPragma::try_from(name.as_str()).map_err(|_| {
+            tracing::error!(target: "parser", "UNKNOWN PRAGMA: {}", name);
+            let hint = match SourcePragmaKind::try_from(name.as_str()) {
+                Ok(_) => format!("\"{name}\" must be declared at the top level of a file"),
+                Err(_) => format!(
+                    "Expected one of: {}",
+                    MacroPragma::ALL
+                        .iter()
+                        .map(|p| format!("`{p}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            };
+            ParserError {
+                kind: ParserErrorKind::InvalidPragma(name),
+                hint: Some(hint),
+                spans: AstSpan(vec![span]),
+            }
+        })
+    }
+
+    /// Whether the current `#pragma` declares a requirement of the source rather than annotating
+    /// a macro
+    fn peek_source_pragma(&mut self) -> bool {
+        match self.peek().map(|t| t.kind) {
+            Some(TokenKind::Ident(name)) => SourcePragmaKind::try_from(name.as_str()).is_ok(),
+            _ => false,
+        }
+    }
+
+    /// Parses a pragma declaring a requirement of the source, i.e. `#pragma evm-version "paris"`.
+    ///
+    /// The required version is a string, or an identifier for EVM versions.
+    pub fn parse_source_pragma(&mut self) -> Result<SourcePragma, ParserError> {
+        let mut spans = vec![self.current_token.span.clone()];
+        self.match_kind(TokenKind::Pragma)?;
+        let name = self.match_kind(TokenKind::Ident("PRAGMA".to_string()))?.to_string();
+        let kind = SourcePragmaKind::try_from(name.as_str()).map_err(|_| ParserError {
+            kind: ParserErrorKind::InvalidPragma(name.clone()),
+            hint: None,
+            spans: AstSpan(spans.clone()),
+        })?;
+        spans.push(self.current_token.span.clone());
+        let value = match self.current_token.kind.clone() {
+            TokenKind::Str(v) => v,
+            TokenKind::Ident(v) if kind == SourcePragmaKind::EvmVersion => v,
+            TokenKind::Num(n) if kind == SourcePragmaKind::Edition => n.to_string(),
+            t => {
+                tracing::error!(target: "parser", "INVALID \"{}\" PRAGMA VALUE: {}", kind, t);
+                let example = match kind {
+                    SourcePragmaKind::EvmVersion => "shanghai",
+                    SourcePragmaKind::HuffVersion => "^0.3.1",
+                    SourcePragmaKind::Edition => "2024",
+                };
+                return Err(ParserError {
+                    kind: ParserErrorKind::UnexpectedType(t),
+                    hint: Some(format!("Expected a version, i.e. `#pragma {kind} \"{example}\"`")),
+                    spans: AstSpan(spans),
+                })
+            }
+        };
+        self.consume();
+        if kind == SourcePragmaKind::Edition {
+            // The edition applies to the whole file, so it must be declared before anything else
+            let file = spans[0].file.as_ref().map(|f| f.path.clone());
+            let first = self.tokens.iter().position(|t| Parser::file_of(t) == file);
+            if let Err(e) = Edition::from_str(&value) {
+                return Err(ParserError {
+                    kind: ParserErrorKind::InvalidPragma(format!("{kind} {value}")),
+                    hint: Some(e),
+                    spans: AstSpan(spans),
+                })
+            }
+            if first.map(|i| i + 3 != self.cursor).unwrap_or(false) {
+                return Err(ParserError {
+                    kind: ParserErrorKind::InvalidPragma(kind.to_string()),
+                    hint: Some("`#pragma edition` must be the first line of the file".to_string()),
+                    spans: AstSpan(spans),
+                })
+            }
+        }
+        Ok(SourcePragma { kind, value, span: AstSpan(spans) })
+    }
+
+    /// Parses a compile time assertion, i.e. `__ASSERT([SLOT_A] != [SLOT_B], "slots collide")`.
+    ///
+    /// The message is optional.
+    pub fn parse_assertion(&mut self) -> Result<Statement, ParserError> {
+        let mut curr_spans = vec![self.current_token.span.clone()];
+        self.match_kind(TokenKind::BuiltinFunction(String::default()))?;
+        self.match_kind(TokenKind::OpenParen)?;
+        let condition = self.parse_const_expr()?;
+        let message = if self.check(TokenKind::Comma) {
+            self.consume();
+            match self.current_token.kind.clone() {
+                TokenKind::Str(message) => {
+                    self.consume();
+                    Some(message)
+                }
+                kind => {
+                    tracing::error!(target: "parser", "INVALID ASSERTION MESSAGE: {}", kind);
+                    return Err(ParserError {
+                        kind: ParserErrorKind::UnexpectedType(kind),
+                        hint: Some(
+                            "Expected the message of the assertion, i.e. \"message\"".to_string(),
+                        ),
+                        spans: AstSpan(vec![self.current_token.span.clone()]),
+                    })
+                }
+            }
+        } else {
+            None
+        };
+        curr_spans.push(self.current_token.span.clone());
+        self.match_kind(TokenKind::CloseParen)?;
+        tracing::info!(target: "parser", "PARSED ASSERTION: {}", condition);
+
+        let span = AstSpan(curr_spans);
+        Ok(Statement {
+            ty: StatementType::Assertion(Assertion { condition, message, span: span.clone() }),
+            span,
+        })
+    }
+
+    /// Parses a constant expression of literals, constants, and arithmetic or comparison
+    /// operators, i.e. `[SIZE] * 0x20 <= 0x0400`.
+    ///
+    /// Comparisons bind the loosest, then `+` and `-`, then `*` and `/`. Parentheses group
+    /// sub-expressions.
+    pub fn parse_const_expr(&mut self) -> Result<ConstExpr, ParserError> {
+        let lhs = self.parse_const_terms(&[ConstOperator::Add, ConstOperator::Sub])?;
+        let op = match (self.current_token.kind.clone(), self.peek().map(|t| t.kind)) {
+            (TokenKind::Assign, Some(TokenKind::Assign)) => ConstOperator::Eq,
+            (TokenKind::Bang, Some(TokenKind::Assign)) => ConstOperator::Neq,
+            (TokenKind::LeftAngle, Some(TokenKind::Assign)) => ConstOperator::Le,
+            (TokenKind::RightAngle, Some(TokenKind::Assign)) => ConstOperator::Ge,
+            (TokenKind::LeftAngle, _) => ConstOperator::Lt,
+            (TokenKind::RightAngle, _) => ConstOperator::Gt,
+            _ => return Ok(lhs),
+        };
+        self.consume();
+        if matches!(
+            op,
+            ConstOperator::Eq | ConstOperator::Neq | ConstOperator::Le | ConstOperator::Ge
+        ) {
+            self.consume();
+        }
+        let rhs = self.parse_const_terms(&[ConstOperator::Add, ConstOperator::Sub])?;
+        Ok(ConstExpr::Binary(Box::new(lhs), op, Box::new(rhs)))
+    }
+
+    /// Parses the operands of a constant expression joined by left associative operators of
+    /// the same precedence, either `+` and `-`, or `*` and `/`.
+    fn parse_const_terms(&mut self, ops: &[ConstOperator]) -> Result<ConstExpr, ParserError> {
+        let operand = |parser: &mut Parser| match ops.contains(&ConstOperator::Add) {
+            true => parser.parse_const_terms(&[ConstOperator::Mul, ConstOperator::Div]),
+            false => parser.parse_const_operand(),
+        };
+        let mut expr = operand(self)?;
+        loop {
+            let op = match self.current_token.kind {
+                TokenKind::Add => ConstOperator::Add,
+                TokenKind::Sub => ConstOperator::Sub,
+                TokenKind::Mul => ConstOperator::Mul,
+                TokenKind::Div => ConstOperator::Div,
+                _ => return Ok(expr),
+            };
+            if !ops.contains(&op) {
+                return Ok(expr)
+            }
+            self.consume();
+            expr = ConstExpr::Binary(Box::new(expr), op, Box::new(operand(self)?));
+        }
+    }
+
+    /// Parses an operand of a constant expression: a literal, a constant or a parenthesized
+    /// expression.
+    fn parse_const_operand(&mut self) -> Result<ConstExpr, ParserError> {
+        match self.current_token.kind.clone() {
+            TokenKind::Literal(l) => {
+                self.consume();
+                Ok(ConstExpr::Literal(l))
+            }
+            TokenKind::Num(n) => {
+                self.consume();
+                Ok(ConstExpr::Literal(str_to_bytes32(&format!("{n:x}"))))
+            }
+            TokenKind::OpenBracket => {
+                let (constant, _) = self.parse_constant_push()?;
+                Ok(ConstExpr::Constant(constant))
+            }
+            TokenKind::OpenParen => {
+                self.consume();
+                let expr = self.parse_const_expr()?;
+                self.match_kind(TokenKind::CloseParen)?;
+                Ok(expr)
+            }
+            kind => {
+                tracing::error!(target: "parser", "INVALID CONSTANT EXPRESSION OPERAND: {}", kind);
+                Err(ParserError {
+                    kind: ParserErrorKind::UnexpectedType(kind),
+                    hint: Some(
+                        "Expected a literal, a constant, i.e. `[SLOT]`, or a parenthesized expression"
+                            .to_string(),
+                    ),
+                    spans: AstSpan(vec![self.current_token.span.clone()]),
+                })
+            }
+        }
+    }
+
+    /// Parses a constant declared in a macro body, i.e. `#define constant NAME = 0x01`.
+    ///
+    /// Local constants may only be assigned a literal. They are only resolvable within the macro
+    /// declaring them, taking precedence over the global constants of the same name.
+    pub fn parse_local_constant(&mut self) -> Result<(), ParserError> {
+        let mut curr_spans = vec![self.current_token.span.clone()];
+        self.match_kind(TokenKind::Define)?;
+
+        // Keywords aren't lexed in macro bodies, so `constant` is an identifier
+        if !matches!(&self.current_token.kind, TokenKind::Ident(keyword) if keyword == "constant") {
+            tracing::error!(target: "parser", "INVALID DEFINITION IN MACRO BODY: {}", self.current_token.kind);
+            return Err(ParserError {
+                kind: ParserErrorKind::InvalidDefinition(self.current_token.kind.clone()),
+                hint: Some("Only constants may be defined in a macro body".to_string()),
+                spans: AstSpan(vec![self.current_token.span.clone()]),
+            })
+        }
+        self.consume();
+
+        let name_span = self.current_token.span.clone();
+        // Opcodes are lexed as such in macro bodies, so the name can't collide with one
+        let name = self.match_kind(TokenKind::Ident("x".to_string()))?.to_string();
+        curr_spans.push(name_span.clone());
+        if self.local_constants.iter().any(|c| c.name == name) {
+            tracing::error!(target: "parser", "DUPLICATE LOCAL CONSTANT: {}", name);
+            return Err(ParserError {
+                kind: ParserErrorKind::DuplicateConstant(name),
+                hint: Some("A constant may only be declared once per macro".to_string()),
+                spans: AstSpan(vec![name_span]),
+            })
+        }
+
+        self.match_kind(TokenKind::Assign)?;
+        let value = match self.current_token.kind.clone() {
+            TokenKind::Literal(l) => {
+                curr_spans.push(self.current_token.span.clone());
+                self.consume();
+                ConstVal::Literal(l)
+            }
+            TokenKind::Sub => {
+                let (l, spans) = self.parse_negative_literal()?;
+                curr_spans.extend(spans);
+                ConstVal::Literal(l)
+            }
+            kind => {
+                tracing::error!(target: "parser", "INVALID LOCAL CONSTANT VALUE: {}", kind);
+                return Err(ParserError {
+                    kind: ParserErrorKind::InvalidConstantValue(kind),
+                    hint: Some(
+                        "Expected the value of a local constant to be a literal".to_string(),
+                    ),
+                    spans: AstSpan(vec![self.current_token.span.clone()]),
+                })
+            }
+        };
+
+        tracing::info!(target: "parser", "PARSED LOCAL CONSTANT: {}", name);
+        self.local_constants.push(ConstantDefinition {
+            name,
+            ty: None,
+            value,
+            span: AstSpan(curr_spans),
+        });
+        Ok(())
     }
 
     /// Parse the body of a macro.
@@ -552,6 +1551,14 @@ impl Parser {
                         span: AstSpan(curr_spans),
                     });
                 }
+                TokenKind::Sub => {
+                    let (val, curr_spans) = self.parse_negative_literal()?;
+                    tracing::info!(target: "parser", "PARSING MACRO BODY: [NEGATIVE LITERAL: {}]", hex::encode(val));
+                    statements.push(Statement {
+                        ty: StatementType::Literal(val),
+                        span: AstSpan(curr_spans),
+                    });
+                }
                 TokenKind::Opcode(o) => {
                     let curr_spans = vec![self.current_token.span.clone()];
                     tracing::info!(target: "parser", "PARSING MACRO BODY: [OPCODE: {}]", o);
@@ -603,6 +1610,14 @@ impl Parser {
                     let mut curr_spans = vec![self.current_token.span.clone()];
                     tracing::info!(target: "parser", "PARSING MACRO BODY: [IDENT: {}]", ident_str);
                     self.match_kind(TokenKind::Ident("MACRO_NAME".to_string()))?;
+                    if ident_str == "asm" && self.check(TokenKind::OpenBrace) {
+                        statements.append(&mut self.parse_asm_block(&curr_spans)?);
+                        continue
+                    }
+                    if let Some(mut lowered) = self.parse_control_flow(&ident_str, &curr_spans)? {
+                        statements.append(&mut lowered);
+                        continue
+                    }
                     // Can be a macro call or label call
                     match self.current_token.kind.clone() {
                         TokenKind::OpenParen => {
@@ -631,6 +1646,11 @@ impl Parser {
                     }
                 }
                 TokenKind::Label(l) => {
+                    self.check_opcode_name(
+                        &l,
+                        "label",
+                        AstSpan(vec![self.current_token.span.clone()]),
+                    )?;
                     let mut curr_spans = vec![self.current_token.span.clone()];
                     self.consume();
                     let inner_statements: Vec<Statement> = self.parse_label()?;
@@ -661,6 +1681,10 @@ impl Parser {
                         span: AstSpan(vec![arg_span]),
                     });
                 }
+                TokenKind::Define => self.parse_local_constant()?,
+                TokenKind::BuiltinFunction(f) if f == BuiltinFunctionKind::Assert.name() => {
+                    statements.push(self.parse_assertion()?);
+                }
                 TokenKind::BuiltinFunction(f) => {
                     let mut curr_spans = vec![self.current_token.span.clone()];
                     self.match_kind(TokenKind::BuiltinFunction(String::default()))?;
@@ -718,6 +1742,14 @@ impl Parser {
                         span: AstSpan(curr_spans),
                     });
                 }
+                TokenKind::Sub => {
+                    let (val, curr_spans) = self.parse_negative_literal()?;
+                    tracing::info!(target: "parser", "PARSING LABEL BODY: [NEGATIVE LITERAL: {}]", hex::encode(val));
+                    statements.push(Statement {
+                        ty: StatementType::Literal(val),
+                        span: AstSpan(curr_spans),
+                    });
+                }
                 TokenKind::Opcode(o) => {
                     let curr_spans = vec![self.current_token.span.clone()];
                     tracing::info!(target: "parser", "PARSING LABEL BODY: [OPCODE: {}]", o);
@@ -731,6 +1763,14 @@ impl Parser {
                     let mut curr_spans = vec![self.current_token.span.clone()];
                     tracing::info!(target: "parser", "PARSING LABEL BODY: [IDENT: {}]", ident_str);
                     self.match_kind(TokenKind::Ident("MACRO_NAME".to_string()))?;
+                    if ident_str == "asm" && self.check(TokenKind::OpenBrace) {
+                        statements.append(&mut self.parse_asm_block(&curr_spans)?);
+                        continue
+                    }
+                    if let Some(mut lowered) = self.parse_control_flow(&ident_str, &curr_spans)? {
+                        statements.append(&mut lowered);
+                        continue
+                    }
                     // Can be a macro call or label call
                     match self.current_token.kind.clone() {
                         TokenKind::OpenParen => {
@@ -774,6 +1814,10 @@ impl Parser {
                         span: AstSpan(vec![arg_span]),
                     });
                 }
+                TokenKind::Define => self.parse_local_constant()?,
+                TokenKind::BuiltinFunction(f) if f == BuiltinFunctionKind::Assert.name() => {
+                    statements.push(self.parse_assertion()?);
+                }
                 TokenKind::BuiltinFunction(f) => {
                     let mut curr_spans = vec![self.current_token.span.clone()];
                     self.match_kind(TokenKind::BuiltinFunction(String::default()))?;
@@ -802,6 +1846,706 @@ impl Parser {
         Ok(statements)
     }
 
+    /// Parse a control flow block, if the identifier just consumed starts one, lowered to
+    /// uniquely named labels and jumps.
+    ///
+    /// ## Examples
+    ///
+    /// `if` consumes the condition on top of the stack, `break` and `continue` jump out of and
+    /// back to the start of the innermost `loop`:
+    ///
+    /// ```huff
+    /// loop {
+    ///     dup1 iszero if { break }
+    ///     0x01 swap1 sub
+    /// }
+    /// ```
+    ///
+    /// `switch` compares the value on top of the stack, i.e. the function selector, to each
+    /// case and pops it before running the matching case or the default:
+    ///
+    /// ```huff
+    /// 0x00 calldataload 0xe0 shr
+    /// switch {
+    ///     case __FUNC_SIG(transfer) { TRANSFER() }
+    ///     case [BALANCE_OF_SIG] { BALANCE_OF() }
+    ///     default { 0x00 dup1 revert }
+    /// }
+    /// ```
+    ///
+    /// `switch table` instead jumps through a jump table indexed by the value modulo the table
+    /// size, in constant gas whatever the number of cases. The case values must be known at
+    /// compile time, the lookup overwrites memory `0x1e..0x20`, and like jump tables, the macro
+    /// should only be expanded once.
+    ///
+    /// `switch trie` is an experimental dispatcher branching on the bytes of the selector, in gas
+    /// logarithmic in the number of cases, without tables or memory. The case values must be
+    /// known at compile time and fit in 4 bytes. See [trie] for the gas it uses.
+    ///
+    /// Without a following brace, `if`, `loop` and `switch` are label calls, as are `break` and
+    /// `continue` outside of a loop.
+    pub fn parse_control_flow(
+        &mut self,
+        ident: &str,
+        spans: &[Span],
+    ) -> Result<Option<Vec<Statement>>, ParserError> {
+        if matches!(ident, "if" | "loop" | "switch") &&
+            (self.check(TokenKind::OpenBrace) ||
+                (ident == "switch" &&
+                    (self.check_switch_modifier("table") ||
+                        self.check_switch_modifier("trie"))))
+        {
+            self.require(Feature::ControlFlowBlocks, spans.to_vec())?;
+        }
+        let span = AstSpan(spans.to_vec());
+        let op = |o: Opcode| Statement { ty: StatementType::Opcode(o), span: span.clone() };
+        let label_call =
+            |l: &String| Statement { ty: StatementType::LabelCall(l.clone()), span: span.clone() };
+        let label = |l: String| Statement {
+            ty: StatementType::Label(Label { name: l, inner: vec![], span: span.clone() }),
+            span: span.clone(),
+        };
+
+        let lowered = match ident {
+            "if" if self.check(TokenKind::OpenBrace) => {
+                let id = self.blocks;
+                self.blocks += 1;
+                let then = self.parse_body()?;
+                let end = format!("__if{id}_end");
+                tracing::info!(target: "parser", "PARSED IF BLOCK \"{}\" WITH {} STATEMENTS", end, then.len());
+
+                let else_follows = matches!(&self.current_token.kind, TokenKind::Ident(i) if i == "else") &&
+                    matches!(self.peek(), Some(t) if t.kind == TokenKind::OpenBrace);
+                if else_follows {
+                    self.consume();
+                    let otherwise = self.parse_body()?;
+                    let else_label = format!("__if{id}_else");
+                    [op(Opcode::Iszero), label_call(&else_label), op(Opcode::Jumpi)]
+                        .into_iter()
+                        .chain(then)
+                        .chain([label_call(&end), op(Opcode::Jump), label(else_label)])
+                        .chain(otherwise)
+                        .chain([label(end)])
+                        .collect()
+                } else {
+                    [op(Opcode::Iszero), label_call(&end), op(Opcode::Jumpi)]
+                        .into_iter()
+                        .chain(then)
+                        .chain([label(end)])
+                        .collect()
+                }
+            }
+            "loop" if self.check(TokenKind::OpenBrace) => {
+                let id = self.blocks;
+                self.blocks += 1;
+                let (start, end) = (format!("__loop{id}_start"), format!("__loop{id}_end"));
+                self.loops.push((start.clone(), end.clone()));
+                let body = self.parse_body();
+                self.loops.pop();
+                let body = body?;
+                tracing::info!(target: "parser", "PARSED LOOP BLOCK \"{}\" WITH {} STATEMENTS", start, body.len());
+
+                [label(start.clone())]
+                    .into_iter()
+                    .chain(body)
+                    .chain([label_call(&start), op(Opcode::Jump), label(end)])
+                    .collect()
+            }
+            "switch" if self.check_switch_modifier("table") => {
+                self.consume();
+                let n = self.dispatches.len();
+                let (cases, default) = self.parse_switch_cases()?;
+                let table = format!("__DISPATCH{n}");
+                let (default_label, end) =
+                    (format!("__dispatch{n}_default"), format!("__dispatch{n}_end"));
+                let case_label = |i: usize| format!("__dispatch{n}_case{i}");
+                tracing::info!(target: "parser", "PARSED SWITCH TABLE \"{}\" WITH {} CASES", table, cases.len());
+
+                // Jump to the table entry at the index of the value, i.e. the selector, modulo
+                // the table size: `__tablestart(table) + 2 * (value % size)`
+                let tablestart = Statement {
+                    ty: StatementType::BuiltinFunctionCall(BuiltinFunctionCall {
+                        kind: BuiltinFunctionKind::Tablestart,
+                        args: vec![Argument {
+                            name: Some(table.clone()),
+                            arg_type: None,
+                            arg_location: None,
+                            indexed: false,
+                            span: span.clone(),
+                        }],
+                        span: span.clone(),
+                    }),
+                    span: span.clone(),
+                };
+                let literal = |v: &str| Statement {
+                    ty: StatementType::Literal(str_to_bytes32(v)),
+                    span: span.clone(),
+                };
+                let mut lowered = vec![
+                    Statement {
+                        ty: StatementType::Constant(format!("{table}_SIZE")),
+                        span: span.clone(),
+                    },
+                    op(Opcode::Dup2),
+                    op(Opcode::Mod),
+                    op(Opcode::Dup1),
+                    op(Opcode::Add),
+                    tablestart,
+                    op(Opcode::Add),
+                    // Copy the 2 byte entry to the end of the first word of memory
+                    literal("02"),
+                    op(Opcode::Swap1),
+                    literal("1e"),
+                    op(Opcode::Codecopy),
+                    literal("00"),
+                    op(Opcode::Mload),
+                    literal("ffff"),
+                    op(Opcode::And),
+                    op(Opcode::Jump),
+                    label(default_label.clone()),
+                    op(Opcode::Pop),
+                ];
+                lowered.extend(default.unwrap_or_default());
+
+                // Entries of values colliding modulo the table size jump to the wrong case, so
+                // each case checks the value
+                let mut dispatch_cases = vec![];
+                for (i, (_, value, body)) in cases.into_iter().enumerate() {
+                    lowered.extend([label_call(&end), op(Opcode::Jump), label(case_label(i))]);
+                    lowered.extend([op(Opcode::Dup1), value.clone(), op(Opcode::Sub)]);
+                    lowered.extend([
+                        label_call(&default_label),
+                        op(Opcode::Jumpi),
+                        op(Opcode::Pop),
+                    ]);
+                    lowered.extend(body);
+                    dispatch_cases.push((value, case_label(i)));
+                }
+                lowered.push(label(end));
+
+                self.dispatches.push(Dispatch {
+                    kind: DispatchKind::Table,
+                    contract: None,
+                    table,
+                    cases: dispatch_cases,
+                    default: default_label,
+                    span: span.clone(),
+                    constants: vec![],
+                });
+                lowered
+            }
+            "switch" if self.check_switch_modifier("trie") => {
+                self.consume();
+                let n = self.dispatches.len();
+                let (cases, default) = self.parse_switch_cases()?;
+                let trie = format!("__DISPATCH{n}");
+                let end = format!("__dispatch{n}_end");
+                let case_label = |i: usize| format!("__dispatch{n}_case{i}");
+                tracing::info!(target: "parser", "PARSED SWITCH TRIE \"{}\" WITH {} CASES", trie, cases.len());
+
+                // The invocation is replaced by the trie once the selectors of the cases are
+                // known, jumping to the matching case or falling through to the default
+                let mut lowered = vec![
+                    Statement {
+                        ty: StatementType::MacroInvocation(MacroInvocation {
+                            macro_name: trie.clone(),
+                            args: vec![],
+                            span: span.clone(),
+                        }),
+                        span: span.clone(),
+                    },
+                    op(Opcode::Pop),
+                ];
+                lowered.extend(default.unwrap_or_default());
+                let mut dispatch_cases = vec![];
+                for (i, (_, value, body)) in cases.into_iter().enumerate() {
+                    lowered.extend([label_call(&end), op(Opcode::Jump)]);
+                    lowered.extend([label(case_label(i)), op(Opcode::Pop)]);
+                    lowered.extend(body);
+                    dispatch_cases.push((value, case_label(i)));
+                }
+                lowered.push(label(end));
+
+                self.dispatches.push(Dispatch {
+                    kind: DispatchKind::Trie,
+                    contract: None,
+                    default: format!("{trie}_miss"),
+                    table: trie,
+                    cases: dispatch_cases,
+                    span: span.clone(),
+                    constants: vec![],
+                });
+                lowered
+            }
+            "switch" if self.check(TokenKind::OpenBrace) => {
+                let id = self.blocks;
+                self.blocks += 1;
+                let (cases, default) = self.parse_switch_cases()?;
+                let end = format!("__switch{id}_end");
+                let case_label = |i: usize| format!("__switch{id}_case{i}");
+                tracing::info!(target: "parser", "PARSED SWITCH \"{}\" WITH {} CASES", end, cases.len());
+
+                // Compare the value on top of the stack to each case, popping it before running
+                // the matching case, or the default if none match. Cases never fall through.
+                let mut lowered = vec![];
+                for (i, (_, value, _)) in cases.iter().enumerate() {
+                    lowered.extend([op(Opcode::Dup1), value.clone(), op(Opcode::Eq)]);
+                    lowered.extend([label_call(&case_label(i)), op(Opcode::Jumpi)]);
+                }
+                lowered.push(op(Opcode::Pop));
+                lowered.extend(default.unwrap_or_default());
+                let last = cases.len();
+                for (i, (_, _, body)) in cases.into_iter().enumerate() {
+                    lowered.extend([label_call(&end), op(Opcode::Jump)]);
+                    lowered.extend([label(case_label(i)), op(Opcode::Pop)]);
+                    lowered.extend(body);
+                    if i + 1 == last {
+                        lowered.push(label(end.clone()));
+                    }
+                }
+                lowered
+            }
+            "break" | "continue" if !self.check(TokenKind::OpenParen) => {
+                let (start, end) = match self.loops.last() {
+                    Some(labels) => labels,
+                    None => return Ok(None),
+                };
+                let target = if ident == "break" { end } else { start };
+                vec![label_call(target), op(Opcode::Jump)]
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(lowered))
+    }
+
+    /// Parse the raw bytes of an `asm` block, emitted as they are, i.e.
+    /// `asm { 0x60 0x00 0x35 @offset(0x03) 0x5b }`.
+    ///
+    /// Bytes are written in hex, any number of them per literal, and leading zeros are kept. An
+    /// `@offset(n)` fails compilation unless the byte following it is generated at offset `n` of
+    /// the bytecode, in every expansion of the macro. The bytes are opaque to the analyses of the
+    /// macro, i.e. the stack checks.
+    pub fn parse_asm_block(&mut self, spans: &[Span]) -> Result<Vec<Statement>, ParserError> {
+        self.require(Feature::AsmBlocks, spans.to_vec())?;
+        self.match_kind(TokenKind::OpenBrace)?;
+        let invalid = |kind: TokenKind, span: &Span| ParserError {
+            kind: ParserErrorKind::InvalidTokenInMacroBody(kind),
+            hint: Some("Expected hex bytes or `@offset(<offset>)` in the asm block".to_string()),
+            spans: AstSpan(vec![span.clone()]),
+        };
+
+        let mut statements = vec![];
+        let (mut code, mut code_spans) = (String::new(), vec![]);
+        loop {
+            let span = self.current_token.span.clone();
+            match self.current_token.kind.clone() {
+                TokenKind::CloseBrace => {
+                    self.consume();
+                    break
+                }
+                // The lexer passes the hex of asm blocks as is, like the code of code tables
+                TokenKind::Ident(hex) if hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+                    self.consume();
+                    code.push_str(&hex);
+                    code_spans.push(span);
+                }
+                TokenKind::At => {
+                    self.consume();
+                    let mut assertion_spans = vec![span];
+                    match self.current_token.kind.clone() {
+                        TokenKind::Ident(i) if i == "offset" => self.consume(),
+                        kind => return Err(invalid(kind, &self.current_token.span)),
+                    }
+                    self.match_kind(TokenKind::OpenParen)?;
+                    let offset = match self.current_token.kind.clone() {
+                        TokenKind::Num(n) => Some(n),
+                        TokenKind::Ident(hex) => usize::from_str_radix(&hex, 16).ok(),
+                        _ => None,
+                    };
+                    let offset = match offset {
+                        Some(o) => o,
+                        None => {
+                            return Err(invalid(
+                                self.current_token.kind.clone(),
+                                &self.current_token.span,
+                            ))
+                        }
+                    };
+                    assertion_spans.push(self.current_token.span.clone());
+                    self.consume();
+                    self.match_kind(TokenKind::CloseParen)?;
+
+                    if !code.is_empty() {
+                        statements.push(Statement {
+                            ty: StatementType::Code(std::mem::take(&mut code)),
+                            span: AstSpan(std::mem::take(&mut code_spans)),
+                        });
+                    }
+                    statements.push(Statement {
+                        ty: StatementType::OffsetAssertion(offset),
+                        span: AstSpan(assertion_spans),
+                    });
+                }
+                kind => return Err(invalid(kind, &span)),
+            }
+        }
+        if !code.is_empty() {
+            statements.push(Statement { ty: StatementType::Code(code), span: AstSpan(code_spans) });
+        }
+        tracing::info!(target: "parser", "PARSED ASM BLOCK WITH {} STATEMENTS", statements.len());
+        Ok(statements)
+    }
+
+    /// Whether a switch is followed by a modifier and its cases, i.e. `table` to dispatch through
+    /// a jump table or `trie` to branch on the bytes of the selector, rather than comparing the
+    /// value to each case.
+    fn check_switch_modifier(&mut self, modifier: &str) -> bool {
+        matches!(&self.current_token.kind, TokenKind::Ident(t) if t == modifier) &&
+            matches!(self.peek(), Some(t) if t.kind == TokenKind::OpenBrace)
+    }
+
+    /// Generate the jump tables of the switches dispatching through one, along with the
+    /// constants holding their sizes.
+    ///
+    /// The table size is the smallest one, from the number of cases up, with no two case values
+    /// at the same index. Every other entry jumps to the default.
+    pub fn generate_dispatch_tables(&mut self, contract: &mut Contract) -> Result<(), ParserError> {
+        for dispatch in std::mem::take(&mut self.dispatches) {
+            let modifier = match dispatch.kind {
+                DispatchKind::Table => "table",
+                DispatchKind::Trie => "trie",
+            };
+            let invalid = |reason: String| {
+                ParserError {
+                kind: ParserErrorKind::InvalidDispatchTable(reason),
+                hint: Some(format!(
+                    "Switch {modifier} cases must be literals, literal constants or __FUNC_SIG calls"
+                )),
+                spans: dispatch.span.clone(),
+            }
+            };
+            let scope = match &dispatch.contract {
+                Some(name) => contract
+                    .find_contract_by_name(name)
+                    .ok_or_else(|| invalid(format!("Missing contract \"{name}\"")))?,
+                None => Contract {
+                    constants: contract.constants.clone(),
+                    functions: contract.functions.clone(),
+                    errors: contract.errors.clone(),
+                    ..Default::default()
+                },
+            };
+
+            let mut values = vec![];
+            for (value, _) in &dispatch.cases {
+                values.push(Parser::case_value(value, &dispatch.constants, &scope).ok_or_else(
+                    || invalid(format!("The value of a case can't be resolved: {}", value.ty)),
+                )?);
+            }
+            if dispatch.kind == DispatchKind::Trie {
+                let mut cases: Vec<(u32, String)> = vec![];
+                for (value, (_, label)) in values.iter().zip(&dispatch.cases) {
+                    if value[..28].iter().any(|b| *b != 0) {
+                        return Err(invalid(format!(
+                            "The value of a case is wider than a selector: {}",
+                            bytes32_to_string(value, true)
+                        )))
+                    }
+                    let selector = u32::from_be_bytes([value[28], value[29], value[30], value[31]]);
+                    if cases.iter().any(|(s, _)| *s == selector) {
+                        return Err(invalid(format!(
+                            "Two cases have the same selector: {selector:#010x}"
+                        )))
+                    }
+                    cases.push((selector, label.clone()));
+                }
+                let trie = SelectorTrie::new(dispatch.table.clone(), cases);
+                tracing::info!(target: "parser", "GENERATED SELECTOR TRIE {}", trie);
+
+                // Replace the invocation standing in for the trie in the macro of the switch
+                let statements = trie.statements(&dispatch.span);
+                let target = match &dispatch.contract {
+                    Some(name) => match contract.contracts.iter_mut().find(|c| &c.name == name) {
+                        Some(c) => &mut c.contract,
+                        None => return Err(invalid(format!("Missing contract \"{name}\""))),
+                    },
+                    None => &mut *contract,
+                };
+                for m in target.macros.iter_mut() {
+                    let replaced = m.statements.iter().any(|s| {
+                        matches!(&s.ty, StatementType::MacroInvocation(mi) if mi.macro_name == dispatch.table)
+                    });
+                    if replaced {
+                        m.statements = std::mem::take(&mut m.statements)
+                            .into_iter()
+                            .flat_map(|s| match &s.ty {
+                                StatementType::MacroInvocation(mi)
+                                    if mi.macro_name == dispatch.table =>
+                                {
+                                    statements.clone()
+                                }
+                                _ => vec![s],
+                            })
+                            .collect();
+                    }
+                }
+                continue
+            }
+            let size = (values.len().max(1)..=MAX_DISPATCH_TABLE_SIZE)
+                .find(|size| {
+                    let mut indices = values.iter().map(|v| modulo(v, *size)).collect::<Vec<_>>();
+                    indices.sort_unstable();
+                    indices.windows(2).all(|w| w[0] != w[1])
+                })
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "No table of up to {MAX_DISPATCH_TABLE_SIZE} entries maps each case to its own entry"
+                    ))
+                })?;
+            tracing::info!(target: "parser", "GENERATED DISPATCH TABLE \"{}\" OF SIZE {}", dispatch.table, size);
+
+            let mut entries = vec![dispatch.default.clone(); size];
+            for (value, (_, label)) in values.iter().zip(&dispatch.cases) {
+                entries[modulo(value, size)] = label.clone();
+            }
+            let table = TableDefinition::new(
+                dispatch.table.clone(),
+                TableKind::JumpTablePacked,
+                entries
+                    .into_iter()
+                    .map(|l| Statement {
+                        ty: StatementType::LabelCall(l),
+                        span: dispatch.span.clone(),
+                    })
+                    .collect(),
+                str_to_bytes32(format!("{:02x}", size * 0x02).as_str()),
+                dispatch.span.clone(),
+            );
+            let constant = ConstantDefinition {
+                name: format!("{}_SIZE", dispatch.table),
+                ty: None,
+                value: ConstVal::Literal(str_to_bytes32(format!("{size:02x}").as_str())),
+                span: dispatch.span.clone(),
+            };
+
+            let target = match &dispatch.contract {
+                Some(name) => match contract.contracts.iter_mut().find(|c| &c.name == name) {
+                    Some(c) => &mut c.contract,
+                    None => return Err(invalid(format!("Missing contract \"{name}\""))),
+                },
+                None => &mut *contract,
+            };
+            target.tables.push(table);
+            target.constants.lock().unwrap().push(constant);
+        }
+        Ok(())
+    }
+
+    /// Generate the `_ENCODE` and `_DECODE` macros of the structs of the contract and of its
+    /// contract blocks.
+    pub fn generate_struct_codecs(contract: &mut Contract) -> Result<(), ParserError> {
+        for def in contract.contracts.iter_mut() {
+            Parser::generate_struct_codecs(&mut def.contract)?;
+        }
+        for (i, def) in contract.structs.iter().enumerate() {
+            let invalid = |reason: String| ParserError {
+                kind: ParserErrorKind::InvalidStruct(reason),
+                hint: Some(format!(
+                    "The macros \"{}\" and \"{}\" are generated for struct \"{}\"",
+                    codec::encoder_name(&def.name),
+                    codec::decoder_name(&def.name),
+                    def.name
+                )),
+                spans: def.span.clone(),
+            };
+            if contract.structs[..i].iter().any(|s| s.name == def.name) {
+                return Err(invalid(format!("Struct \"{}\" is defined more than once", def.name)))
+            }
+            for m in [codec::encoder(def), codec::decoder(def)] {
+                if contract.macros.iter().any(|d| d.name == m.name) {
+                    return Err(invalid(format!("Macro \"{}\" is already defined", m.name)))
+                }
+                tracing::info!(target: "parser", "GENERATED MACRO {} OF STRUCT {}", m.name, def.name);
+                contract.macros.push(m);
+            }
+        }
+        Ok(())
+    }
+
+    /// Generate the `_VERIFY` macros of the merkle trees of the contract and of its contract
+    /// blocks.
+    pub fn generate_merkle_verifiers(contract: &mut Contract) -> Result<(), ParserError> {
+        for def in contract.contracts.iter_mut() {
+            Parser::generate_merkle_verifiers(&mut def.contract)?;
+        }
+        for (i, def) in contract.merkle_trees.iter().enumerate() {
+            let invalid = |reason: String| ParserError {
+                kind: ParserErrorKind::InvalidMerkleTree(reason),
+                hint: Some(format!(
+                    "The macro \"{}\" is generated for merkle tree \"{}\"",
+                    merkle::verifier_name(&def.name),
+                    def.name
+                )),
+                spans: def.span.clone(),
+            };
+            if contract.merkle_trees[..i].iter().any(|t| t.name == def.name) {
+                return Err(invalid(format!(
+                    "Merkle tree \"{}\" is defined more than once",
+                    def.name
+                )))
+            }
+            let m = merkle::verifier(def);
+            if contract.macros.iter().any(|d| d.name == m.name) {
+                return Err(invalid(format!("Macro \"{}\" is already defined", m.name)))
+            }
+            tracing::info!(target: "parser", "GENERATED MACRO {} OF MERKLE TREE {}", m.name, def.name);
+            contract.macros.push(m);
+        }
+        Ok(())
+    }
+
+    /// The value of a switch case, if known without generating code
+    ///
+    /// Constants are looked up in the local constants of the switch's macro first.
+    fn case_value(
+        value: &Statement,
+        locals: &[ConstantDefinition],
+        contract: &Contract,
+    ) -> Option<Literal> {
+        match &value.ty {
+            StatementType::Literal(l) => Some(*l),
+            StatementType::Constant(name) => {
+                if let Some(local) = locals.iter().find(|c| &c.name == name) {
+                    return match local.value {
+                        ConstVal::Literal(l) => Some(l),
+                        _ => None,
+                    }
+                }
+                match &contract.constants.lock().ok()?.iter().find(|c| &c.name == name)?.value {
+                    ConstVal::Literal(l) => Some(*l),
+                    _ => None,
+                }
+            }
+            StatementType::BuiltinFunctionCall(bf)
+                if bf.kind == BuiltinFunctionKind::FunctionSignature && bf.args.len() == 1 =>
+            {
+                let name = bf.args[0].name.as_ref()?;
+                let selector = if let Some(f) = contract.find_function(name) {
+                    f.signature
+                } else if let Some(e) = contract.find_error(name) {
+                    e.selector
+                } else if name.contains('(') {
+                    let mut signature = [0u8; 4];
+                    hash_bytes(&mut signature, name);
+                    signature
+                } else {
+                    // Undeclared names, including those of ABI imports read after parsing
+                    return None
+                };
+                let mut l = [0u8; 32];
+                l[28..].copy_from_slice(&selector);
+                Some(l)
+            }
+            _ => None,
+        }
+    }
+
+    /// Parse the cases and default of a switch, identifying each case by its value.
+    #[allow(clippy::type_complexity)]
+    pub fn parse_switch_cases(
+        &mut self,
+    ) -> Result<(Vec<(String, Statement, Vec<Statement>)>, Option<Vec<Statement>>), ParserError>
+    {
+        let mut cases: Vec<(String, Statement, Vec<Statement>)> = vec![];
+        let mut default = None;
+        self.match_kind(TokenKind::OpenBrace)?;
+        while !self.check(TokenKind::CloseBrace) {
+            let clause_span = self.current_token.span.clone();
+            match self.current_token.kind.clone() {
+                TokenKind::Ident(i) if i == "case" => {
+                    self.consume();
+                    let (key, value) = self.parse_case_value()?;
+                    if cases.iter().any(|(k, ..)| k == &key) {
+                        tracing::error!(target: "parser", "DUPLICATE SWITCH CASE: {}", key);
+                        return Err(ParserError {
+                            kind: ParserErrorKind::DuplicateCase(key),
+                            hint: Some("Each value may only be matched by one case".to_string()),
+                            spans: value.span,
+                        })
+                    }
+                    cases.push((key, value, self.parse_body()?));
+                }
+                TokenKind::Ident(i) if i == "default" && default.is_none() => {
+                    self.consume();
+                    default = Some(self.parse_body()?);
+                }
+                kind => {
+                    return Err(ParserError {
+                        kind: ParserErrorKind::UnexpectedType(kind),
+                        hint: Some(
+                            "Expected \"case\" or a single \"default\" within the switch"
+                                .to_string(),
+                        ),
+                        spans: AstSpan(vec![clause_span]),
+                    })
+                }
+            }
+        }
+        self.match_kind(TokenKind::CloseBrace)?;
+        Ok((cases, default))
+    }
+
+    /// Parse the value of a switch case: a literal, a constant or a builtin function call such
+    /// as `__FUNC_SIG`. Returns the statement pushing it, along with its source text to compare
+    /// cases by.
+    pub fn parse_case_value(&mut self) -> Result<(String, Statement), ParserError> {
+        let mut curr_spans = vec![self.current_token.span.clone()];
+        match self.current_token.kind.clone() {
+            TokenKind::Literal(val) => {
+                self.consume();
+                Ok((
+                    bytes32_to_string(&val, true),
+                    Statement { ty: StatementType::Literal(val), span: AstSpan(curr_spans) },
+                ))
+            }
+            TokenKind::OpenBracket => {
+                let (constant, const_span) = self.parse_constant_push()?;
+                Ok((
+                    format!("[{constant}]"),
+                    Statement {
+                        ty: StatementType::Constant(constant),
+                        span: AstSpan(vec![const_span]),
+                    },
+                ))
+            }
+            TokenKind::BuiltinFunction(f) => {
+                self.consume();
+                let args = self.parse_args(true, false, false, true)?;
+                args.iter().for_each(|a| curr_spans.extend_from_slice(&a.span.0));
+                let names = args.iter().filter_map(|a| a.name.clone()).collect::<Vec<_>>();
+                Ok((
+                    format!("{f}({})", names.join(", ")),
+                    Statement {
+                        ty: StatementType::BuiltinFunctionCall(BuiltinFunctionCall {
+                            kind: BuiltinFunctionKind::from(f),
+                            args,
+                            span: AstSpan(curr_spans.clone()),
+                        }),
+                        span: AstSpan(curr_spans),
+                    },
+                ))
+            }
+            kind => Err(ParserError {
+                kind: ParserErrorKind::UnexpectedType(kind),
+                hint: Some(
+                    "Expected a literal, constant or builtin function as the case value"
+                        .to_string(),
+                ),
+                spans: AstSpan(curr_spans),
+            }),
+        }
+    }
+
     /// Parse new lines.
     ///
     /// No-return since newlines are non-essential.
@@ -835,7 +2579,8 @@ impl Parser {
                 if let TokenKind::Str(s) = &self.current_token.kind {
                     args.push(Argument {
                         name: Some(s.to_owned()), // Place the string in the "name" field
-                        arg_type: None,
+                        // Tell strings apart from literals and identifiers, i.e. for `__STR_LIT`
+                        arg_type: Some(String::from("string")),
                         indexed: false,
                         span: AstSpan(vec![self.current_token.span.clone()]),
                         arg_location: None,
@@ -850,11 +2595,16 @@ impl Parser {
                     continue
                 }
 
-                // Check for literals
-                if let TokenKind::Literal(l) = &self.current_token.kind {
+                // Check for literals, with decimal numbers placed as hex like literals
+                let literal = match &self.current_token.kind {
+                    TokenKind::Literal(l) => Some(*l),
+                    TokenKind::Num(n) => Some(str_to_bytes32(&format!("{n:x}"))),
+                    _ => None,
+                };
+                if let Some(l) = literal {
                     args.push(Argument {
                         // Place literal in the "name" field
-                        name: Some(bytes32_to_string(l, false)),
+                        name: Some(bytes32_to_string(&l, false)),
                         arg_location: None,
                         arg_type: None,
                         indexed: false,
@@ -975,8 +2725,19 @@ impl Parser {
     pub fn parse_single_arg(&mut self) -> Result<usize, ParserError> {
         self.match_kind(TokenKind::OpenParen)?;
         let single_arg_span = vec![self.current_token.span.clone()];
-        let value: usize = match self.match_kind(TokenKind::Num(0)) {
-            Ok(TokenKind::Num(value)) => value,
+        let value: usize = match self.current_token.kind {
+            TokenKind::Num(value) => {
+                self.consume();
+                value
+            }
+            // Decimals too large for a usize are lexed as literals
+            TokenKind::Literal(count) if bytes32_to_usize(&count).is_none() => {
+                return Err(ParserError {
+                    kind: ParserErrorKind::StackCountTooLarge(count),
+                    hint: Some(format!("Stack item counts can't exceed {}.", usize::MAX)),
+                    spans: AstSpan(single_arg_span),
+                })
+            }
             _ => {
                 return Err(ParserError {
                     kind: ParserErrorKind::InvalidSingleArg(self.current_token.kind.clone()),
@@ -989,6 +2750,27 @@ impl Parser {
         Ok(value)
     }
 
+    /// Parses the outputs of a macro, either counted or named: (n) or (a, b)
+    ///
+    /// Returns the number of outputs along with their names, if named.
+    pub fn parse_outputs(&mut self) -> Result<(usize, Vec<String>), ParserError> {
+        if !matches!(self.peek().map(|t| t.kind), Some(TokenKind::Ident(_))) {
+            return Ok((self.parse_single_arg()?, vec![]))
+        }
+        self.require(Feature::NamedOutputs, vec![self.current_token.span.clone()])?;
+        self.match_kind(TokenKind::OpenParen)?;
+        let mut outputs = vec![];
+        loop {
+            outputs.push(self.match_kind(TokenKind::Ident("x".to_string()))?.to_string());
+            if !self.check(TokenKind::Comma) {
+                break
+            }
+            self.consume();
+        }
+        self.match_kind(TokenKind::CloseParen)?;
+        Ok((outputs.len(), outputs))
+    }
+
     /// Parse call to a macro.
     pub fn parse_macro_call(&mut self) -> Result<Vec<MacroArg>, ParserError> {
         self.parse_macro_call_args()
@@ -1006,8 +2788,28 @@ impl Parser {
                     self.consume();
                 }
                 TokenKind::Ident(ident) => {
-                    args.push(MacroArg::Ident(ident));
+                    let curr_spans = vec![self.current_token.span.clone()];
+                    self.consume();
+                    // A macro invocation, expanded where the argument is used
+                    if self.check(TokenKind::OpenParen) {
+                        let invoc_args = self.parse_macro_call_args()?;
+                        args.push(MacroArg::MacroInvocation(MacroInvocation {
+                            macro_name: ident,
+                            args: invoc_args,
+                            span: AstSpan(curr_spans),
+                        }));
+                    } else {
+                        args.push(MacroArg::Ident(ident));
+                    }
+                }
+                TokenKind::OpenBracket => {
+                    // Constants may be passed like they're referenced in macro bodies:
+                    // STORE_ELEMENT([OWNER_SLOT])
                     self.consume();
+                    let const_name =
+                        self.match_kind(TokenKind::Ident("CONSTANT".to_string()))?.to_string();
+                    args.push(MacroArg::Ident(const_name));
+                    self.match_kind(TokenKind::CloseBracket)?;
                 }
                 TokenKind::Calldata => {
                     args.push(MacroArg::Ident("calldata".to_string()));
@@ -1033,7 +2835,7 @@ impl Parser {
                     return Err(ParserError {
                         kind: ParserErrorKind::InvalidMacroArgs(arg),
                         hint: Some(
-                            "Expected literal, identifier (string), or an argument call"
+                            "Expected literal, identifier (string), constant, argument call or macro invocation"
                                 .to_string(),
                         ),
                         spans: AstSpan(new_spans),
@@ -1068,7 +2870,7 @@ impl Parser {
         let size = match kind {
             TableKind::JumpTablePacked => table_statements.len() * 0x02,
             TableKind::JumpTable => table_statements.len() * 0x20,
-            TableKind::CodeTable => {
+            TableKind::CodeTable | TableKind::Data => {
                 table_statements
                     .iter()
                     .map(|s| {
@@ -1098,6 +2900,44 @@ impl Parser {
         ))
     }
 
+    /// Parses a data section, i.e. `data NAME = 0x...`.
+    ///
+    /// The bytes are appended to the runtime bytecode like a code table, after the code of the
+    /// contract so they're never executed, and are referenced with `__DATA_OFFSET(NAME)` and
+    /// `__DATA_SIZE(NAME)`.
+    pub fn parse_data(&mut self) -> Result<TableDefinition, ParserError> {
+        self.require(Feature::DataSections, vec![self.current_token.span.clone()])?;
+        self.match_kind(TokenKind::Data)?;
+        let name = self.match_kind(TokenKind::Ident("DATA_NAME".to_string()))?.to_string();
+        self.match_kind(TokenKind::Assign)?;
+
+        let span = self.current_token.span.clone();
+        let code = match &self.current_token.kind {
+            TokenKind::Ident(hex) if hex.chars().all(|c| c.is_ascii_hexdigit()) => hex.clone(),
+            kind => {
+                tracing::error!(target: "parser", "INVALID DATA SECTION BYTES: {}", kind);
+                return Err(ParserError {
+                    kind: ParserErrorKind::InvalidTableBodyToken(kind.clone()),
+                    hint: Some(
+                        "Expected the hex bytes of the data section, i.e. `0xdeadbeef`."
+                            .to_string(),
+                    ),
+                    spans: AstSpan(vec![span]),
+                })
+            }
+        };
+        self.consume();
+
+        let size = code.len() / 2;
+        Ok(TableDefinition::new(
+            name,
+            TableKind::Data,
+            vec![Statement { ty: StatementType::Code(code), span: AstSpan(vec![span]) }],
+            str_to_bytes32(format!("{size:02x}").as_str()),
+            AstSpan(self.spans.clone()),
+        ))
+    }
+
     /// Parse the body of a table.
     ///
     /// Only `LabelCall` and `Code` Statements should be authorized.
@@ -1278,3 +3118,11 @@ impl Parser {
         }
     }
 }
+
+/// The largest jump table generated for a switch
+pub const MAX_DISPATCH_TABLE_SIZE: usize = 1024;
+
+/// A 32 byte big-endian value modulo a table size
+fn modulo(value: &Literal, size: usize) -> usize {
+    value.iter().fold(0, |acc, b| (acc * 256 + *b as usize) % size)
+}
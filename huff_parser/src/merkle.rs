@@ -0,0 +1,140 @@
+//! ## Merkle Proof Verification
+//!
+//! Generates the macro verifying merkle proofs of a tree defined with
+//! `#define merkle AIRDROP(keccak256)`.
+//!
+//! Proofs are read from calldata, a word for each node, i.e. the elements of a `bytes32[]`
+//! argument. The two children of a node are hashed sorted, like OpenZeppelin's `MerkleProof`, so
+//! proofs don't hold the side of each sibling.
+//!
+//! ```text
+//! AIRDROP_VERIFY()  takes (4) returns (1)  // [leaf, proof, length, root] -> [valid]
+//! AIRDROP_VERIFY()  takes (3) returns (1)  // [leaf, proof, root] -> [valid], for a fixed depth
+//! ```
+//!
+//! `proof` is the calldata offset of the first node of the proof, and `length` the number of its
+//! nodes. Trees of a fixed depth, i.e. `#define merkle AIRDROP(keccak256, 0x10)`, take proofs of
+//! exactly that many nodes, and unroll the verification instead of looping over the proof.
+//!
+//! Nodes are hashed in the first 64 bytes of memory, with the `sha3` opcode for keccak256 trees,
+//! or a `staticcall` to the sha256 precompile, reverting if the call fails.
+
+use huff_utils::prelude::{
+    str_to_bytes32, Literal, MacroDefinition, MacroDefinitionBuilder, MerkleHash,
+    MerkleTreeDefinition, Opcode,
+};
+
+/// The name of the macro verifying the proofs of a merkle tree
+pub fn verifier_name(name: &str) -> String {
+    format!("{name}_VERIFY")
+}
+
+/// The name of the constant of the root of a merkle tree's leaves
+pub fn root_name(name: &str) -> String {
+    format!("{name}_ROOT")
+}
+
+/// Generates the macro verifying a proof of the leaf on top of the stack against a root.
+pub fn verifier(def: &MerkleTreeDefinition) -> MacroDefinition {
+    let m = MacroDefinitionBuilder::new(verifier_name(&def.name)).outputs(["valid"]);
+    let mut m = match def.depth {
+        Some(depth) => {
+            let mut m = m.takes(3); // [leaf, proof, root]
+            for i in 0..depth {
+                m = step(add(m.opcode(Opcode::Dup2), i * 32), def.hash);
+            }
+            m.opcode(Opcode::Swap1).opcode(Opcode::Pop).opcode(Opcode::Eq)
+        }
+        None => {
+            // The proof ends `length` words past its start
+            let mut m = m
+                .takes(4) // [leaf, proof, length, root]
+                .opcode(Opcode::Swap2)
+                .literal(word(5))
+                .opcode(Opcode::Shl)
+                .opcode(Opcode::Dup2)
+                .opcode(Opcode::Add)
+                .opcode(Opcode::Swap2) // [leaf, proof, end, root]
+                .label_call("check")
+                .opcode(Opcode::Jump);
+            if def.hash == MerkleHash::Sha256 {
+                m = revert(m.label("failed"));
+            }
+            m = step(m.label("loop").opcode(Opcode::Dup2), def.hash)
+                .opcode(Opcode::Swap1)
+                .literal(word(0x20))
+                .opcode(Opcode::Add)
+                .opcode(Opcode::Swap1);
+            m.label("check")
+                .opcode(Opcode::Dup3)
+                .opcode(Opcode::Dup3)
+                .opcode(Opcode::Lt)
+                .label_call("loop")
+                .opcode(Opcode::Jumpi)
+                .opcode(Opcode::Swap2)
+                .opcode(Opcode::Pop)
+                .opcode(Opcode::Pop)
+                .opcode(Opcode::Eq)
+        }
+    };
+    if def.depth.is_some() && def.hash == MerkleHash::Sha256 {
+        m = revert(m.label_call("done").opcode(Opcode::Jump).label("failed")).label("done");
+    }
+    let mut macro_def = m.build();
+    macro_def.span = def.span.clone();
+    macro_def
+}
+
+/// Hashes the node at the calldata offset on top of the stack with the node below it, the
+/// smaller one first: `[offset, node, ...] -> [parent, ...]`
+fn step(m: MacroDefinitionBuilder, hash: MerkleHash) -> MacroDefinitionBuilder {
+    // The larger node is stored in the second word, at `(node > sibling) << 5` for the node
+    let m = m
+        .opcode(Opcode::Calldataload) // [sibling, node]
+        .opcode(Opcode::Dup1)
+        .opcode(Opcode::Dup3)
+        .opcode(Opcode::Gt)
+        .literal(word(5))
+        .opcode(Opcode::Shl) // [at, sibling, node]
+        .opcode(Opcode::Swap2)
+        .opcode(Opcode::Dup3)
+        .opcode(Opcode::Mstore) // [sibling, at]
+        .opcode(Opcode::Swap1)
+        .literal(word(0x20))
+        .opcode(Opcode::Xor)
+        .opcode(Opcode::Mstore);
+    match hash {
+        MerkleHash::Keccak256 => m.literal(word(0x40)).literal(word(0)).opcode(Opcode::Sha3),
+        MerkleHash::Sha256 => m
+            .literal(word(0x20))
+            .literal(word(0))
+            .literal(word(0x40))
+            .literal(word(0))
+            .literal(word(2))
+            .opcode(Opcode::Gas)
+            .opcode(Opcode::Staticcall)
+            .opcode(Opcode::Iszero)
+            .label_call("failed")
+            .opcode(Opcode::Jumpi)
+            .literal(word(0))
+            .opcode(Opcode::Mload),
+    }
+}
+
+/// Reverts without data
+fn revert(m: MacroDefinitionBuilder) -> MacroDefinitionBuilder {
+    m.literal(word(0)).opcode(Opcode::Dup1).opcode(Opcode::Revert)
+}
+
+/// Adds the offset to the word on top of the stack
+fn add(m: MacroDefinitionBuilder, offset: usize) -> MacroDefinitionBuilder {
+    match offset {
+        0 => m,
+        offset => m.literal(word(offset)).opcode(Opcode::Add),
+    }
+}
+
+/// A literal of the value
+fn word(value: usize) -> Literal {
+    str_to_bytes32(&format!("{value:02x}"))
+}
@@ -17,7 +17,8 @@ fn test_parses_free_storage_pointer_constant() {
         fsp_constant,
         ConstantDefinition {
             name: "FSP_LOCATION".to_string(),
-            value: ConstVal::FreeStoragePointer(FreeStoragePointer {}),
+            ty: None,
+            value: ConstVal::FreeStoragePointer(FreeStoragePointer::default()),
             span: AstSpan(vec![
                 Span { start: 0, end: 7, file: None },
                 Span { start: 8, end: 16, file: None },
@@ -49,6 +50,7 @@ fn test_parses_literal_constant() {
         fsp_constant,
         ConstantDefinition {
             name: "LITERAL".to_string(),
+            ty: None,
             value: ConstVal::Literal(arr),
             span: AstSpan(vec![
                 Span { start: 0, end: 7, file: None },
@@ -60,3 +62,131 @@ fn test_parses_literal_constant() {
         }
     );
 }
+
+#[test]
+fn test_parses_typed_constant() {
+    let source = "#define constant OWNER: address = 0xdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    let constant = contract.constants.lock().unwrap()[0].clone();
+    assert_eq!(constant.name, "OWNER");
+    assert_eq!(constant.ty, Some(PrimitiveEVMType::Address));
+    assert_eq!(
+        constant.value,
+        ConstVal::Literal(str_to_bytes32("deadbeefdeadbeefdeadbeefdeadbeefdeadbeef"))
+    );
+}
+
+#[test]
+fn test_typed_constant_overflow() {
+    let sources = [
+        ("#define constant SMALL: uint8 = 0x0100", PrimitiveEVMType::Uint(8)),
+        ("#define constant SEL: bytes4 = 0x0102030405", PrimitiveEVMType::Bytes(4)),
+        ("#define constant FLAG: bool = 0x02", PrimitiveEVMType::Bool),
+        ("#define constant MAX: int8 = 0x80", PrimitiveEVMType::Int(8)),
+        ("#define constant MAX: int16 = 0x8000", PrimitiveEVMType::Int(16)),
+    ];
+
+    for (source, ty) in sources {
+        let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+        let lexer = Lexer::new(flattened_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        match parser.parse() {
+            Err(ParserError { kind: ParserErrorKind::ConstantTypeMismatch(_, t), .. }) => {
+                assert_eq!(t, ty)
+            }
+            _ => panic!("moose"),
+        }
+    }
+}
+
+#[test]
+fn test_signed_constant_range() {
+    // Positive literals of signed types leave the sign bit clear
+    for source in ["#define constant MAX: int8 = 0x7f", "#define constant MAX: int16 = 0x7fff"] {
+        let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+        let lexer = Lexer::new(flattened_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        assert!(parser.parse().is_ok(), "{source}");
+    }
+}
+
+#[test]
+fn test_invalid_constant_type() {
+    let source = "#define constant NAME: string = 0x01";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    match parser.parse() {
+        Err(ParserError { kind: ParserErrorKind::InvalidConstantType(_), .. }) => {}
+        _ => panic!("moose"),
+    }
+}
+
+#[test]
+fn test_parse_local_constants() {
+    let source = r#"
+        #define constant VALUE = 0x01
+
+        #define macro MAIN() = takes(0) returns(0) {
+            #define constant VALUE = 0x02
+            [VALUE]
+            label:
+                #define constant OTHER = 0x03
+                [OTHER]
+        }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    // Local constants don't pollute the global namespace
+    let constants = contract.constants.lock().unwrap().clone();
+    assert_eq!(constants.len(), 1);
+    assert_eq!(constants[0].value, ConstVal::Literal(str_to_bytes32("01")));
+
+    let main = contract.find_macro_by_name("MAIN").unwrap();
+    assert_eq!(main.constants.len(), 2);
+    assert_eq!(main.find_constant("VALUE").unwrap().value, ConstVal::Literal(str_to_bytes32("02")));
+    assert_eq!(main.find_constant("OTHER").unwrap().value, ConstVal::Literal(str_to_bytes32("03")));
+    assert_eq!(main.statements.len(), 2);
+}
+
+#[test]
+fn test_invalid_local_constants() {
+    let sources = [
+        (
+            "#define macro MAIN() = takes(0) returns(0) { #define constant A = 0x01 #define constant A = 0x02 }",
+            ParserErrorKind::DuplicateConstant("A".to_string()),
+        ),
+        (
+            "#define macro MAIN() = takes(0) returns(0) { #define constant A = FREE_STORAGE_POINTER() }",
+            ParserErrorKind::InvalidConstantValue(TokenKind::FreeStoragePointer),
+        ),
+        (
+            "#define macro MAIN() = takes(0) returns(0) { #define macro A() = takes(0) returns(0) {} }",
+            ParserErrorKind::InvalidDefinition(TokenKind::Ident("macro".to_string())),
+        ),
+        (
+            "#define macro MAIN() = takes(0) returns(0) { #define constant add = 0x01 }",
+            ParserErrorKind::UnexpectedType(TokenKind::Opcode(Opcode::Add)),
+        ),
+    ];
+
+    for (source, kind) in sources {
+        let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+        let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        assert_eq!(parser.parse().unwrap_err().kind, kind);
+    }
+}
@@ -0,0 +1,146 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn parse(source: &str) -> Result<Contract, ParserError> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    Parser::new(tokens, None).parse()
+}
+
+#[test]
+fn parses_contract_definitions() {
+    let source = r#"
+        #define constant SHARED = 0x01
+        #define macro HELPER() = takes(0) returns(1) { [SHARED] }
+
+        #define contract Child {
+            #define function value() view returns (uint256)
+            #define constant VALUE = 0x2a
+
+            #define macro MAIN() = takes(0) returns(0) {
+                [VALUE] HELPER() add
+            }
+        }
+
+        #define contract Factory {
+            #define table DATA { 0xdeadbeef }
+
+            #[calldata("0x")]
+            #define test TEST_DEPLOY() = {}
+
+            #define macro MAIN() = takes(0) returns(0) {
+                __tablesize(Child) __tablestart(Child)
+            }
+        }
+    "#;
+    let contract = parse(source).unwrap();
+
+    // Top level definitions stay at the top level
+    assert_eq!(contract.macros.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), ["HELPER"]);
+    assert_eq!(contract.constants.lock().unwrap().len(), 1);
+
+    assert_eq!(contract.contracts.len(), 2);
+    let child = &contract.contracts[0];
+    assert_eq!(child.name, "Child");
+    assert_eq!(child.contract.functions[0].name, "value");
+    assert_eq!(child.contract.constants.lock().unwrap()[0].name, "VALUE");
+    assert_eq!(child.contract.macros[0].name, "MAIN");
+    assert_eq!(child.span.0.len(), 3);
+
+    let factory = &contract.contracts[1];
+    assert_eq!(factory.name, "Factory");
+    assert_eq!(factory.contract.tables[0].name, "DATA");
+    assert_eq!(
+        factory.contract.macros.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(),
+        ["TEST_DEPLOY", "MAIN"]
+    );
+    assert!(factory.contract.macros[0].decorator.is_some());
+}
+
+#[test]
+fn merges_shared_definitions_into_contracts() {
+    let source = r#"
+        #define constant VALUE = 0x01
+        #define macro MAIN() = takes(0) returns(0) { [VALUE] }
+        #define macro HELPER() = takes(0) returns(0) {}
+
+        #define contract Child {
+            #define constant VALUE = 0x02
+            #define macro MAIN() = takes(0) returns(0) { HELPER() [VALUE] }
+        }
+    "#;
+    let contract = parse(source).unwrap();
+    assert!(contract.find_contract_by_name("Missing").is_none());
+
+    let child = contract.find_contract_by_name("Child").unwrap();
+    assert_eq!(
+        child.macros.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(),
+        ["MAIN", "HELPER"]
+    );
+    assert_eq!(child.find_macro_by_name("MAIN").unwrap().statements.len(), 2);
+
+    // The contract's own constant shadows the shared one, in its own copy of the constants
+    let constants = child.constants.lock().unwrap();
+    assert_eq!(constants.len(), 1);
+    assert_eq!(constants[0].value, ConstVal::Literal(str_to_bytes32("02")));
+    assert!(!std::sync::Arc::ptr_eq(&child.constants, &contract.constants));
+}
+
+#[test]
+fn contract_is_not_a_keyword_outside_definitions() {
+    let source = r#"
+        #define function contract() view returns (address)
+
+        #define macro MAIN() = takes(0) returns(0) {
+            contract jump
+            contract:
+        }
+    "#;
+    let contract = parse(source).unwrap();
+    assert_eq!(contract.functions[0].name, "contract");
+    assert!(contract.contracts.is_empty());
+}
+
+#[test]
+fn rejects_duplicate_contracts() {
+    let source = r#"
+        #define contract Child {}
+        #define contract Child {}
+    "#;
+    let err = parse(source).unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::DuplicateContract("Child".to_string()));
+}
+
+#[test]
+fn rejects_nested_contracts() {
+    let source = r#"
+        #define contract Outer {
+            #define contract Inner {}
+        }
+    "#;
+    let err = parse(source).unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::InvalidDefinition(TokenKind::Contract));
+}
+
+#[test]
+fn rejects_includes_in_contracts() {
+    let source = r#"
+        #define contract Child {
+            #include "./Other.huff"
+        }
+    "#;
+    let err = parse(source).unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::UnexpectedType(TokenKind::Include));
+}
+
+#[test]
+fn rejects_unclosed_contracts() {
+    let source = r#"
+        #define contract Child {
+            #define macro MAIN() = takes(0) returns(0) {}
+    "#;
+    let err = parse(source).unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::UnexpectedType(TokenKind::Eof));
+}
@@ -0,0 +1,374 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::{evm::Opcode, prelude::*};
+
+fn parse_statements(body: &str) -> Result<Vec<StatementType>, ParserError> {
+    let source = format!("#define macro MAIN() = takes(0) returns(0) {{ {body} }}");
+    let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse()?;
+    Ok(contract.macros[0].statements.iter().map(|s| s.ty.clone()).collect())
+}
+
+fn label(name: &str) -> StatementType {
+    match parse_statements(&format!("{name}:")).unwrap().remove(0) {
+        StatementType::Label(l) => {
+            StatementType::Label(Label { name: l.name, inner: vec![], span: AstSpan(vec![]) })
+        }
+        ty => panic!("Expected a label, got {ty}"),
+    }
+}
+
+fn call(name: &str) -> StatementType {
+    StatementType::LabelCall(name.to_string())
+}
+
+/// Strips the spans of lowered labels to compare statements
+fn without_spans(statements: Vec<StatementType>) -> Vec<StatementType> {
+    statements
+        .into_iter()
+        .map(|ty| match ty {
+            StatementType::Label(l) if l.inner.is_empty() => label(&l.name),
+            ty => ty,
+        })
+        .collect()
+}
+
+#[test]
+fn lowers_if() {
+    let statements = without_spans(parse_statements("0x01 if { 0x02 } stop").unwrap());
+    assert_eq!(
+        statements,
+        vec![
+            StatementType::Literal(str_to_bytes32("01")),
+            StatementType::Opcode(Opcode::Iszero),
+            call("__if0_end"),
+            StatementType::Opcode(Opcode::Jumpi),
+            StatementType::Literal(str_to_bytes32("02")),
+            label("__if0_end"),
+            StatementType::Opcode(Opcode::Stop),
+        ]
+    );
+}
+
+#[test]
+fn lowers_if_else() {
+    let statements = without_spans(parse_statements("if { 0x02 } else { 0x03 }").unwrap());
+    assert_eq!(
+        statements,
+        vec![
+            StatementType::Opcode(Opcode::Iszero),
+            call("__if0_else"),
+            StatementType::Opcode(Opcode::Jumpi),
+            StatementType::Literal(str_to_bytes32("02")),
+            call("__if0_end"),
+            StatementType::Opcode(Opcode::Jump),
+            label("__if0_else"),
+            StatementType::Literal(str_to_bytes32("03")),
+            label("__if0_end"),
+        ]
+    );
+}
+
+#[test]
+fn lowers_nested_loops() {
+    let statements = without_spans(parse_statements("loop { loop { break } continue }").unwrap());
+    assert_eq!(
+        statements,
+        vec![
+            label("__loop0_start"),
+            label("__loop1_start"),
+            call("__loop1_end"),
+            StatementType::Opcode(Opcode::Jump),
+            call("__loop1_start"),
+            StatementType::Opcode(Opcode::Jump),
+            label("__loop1_end"),
+            call("__loop0_start"),
+            StatementType::Opcode(Opcode::Jump),
+            call("__loop0_start"),
+            StatementType::Opcode(Opcode::Jump),
+            label("__loop0_end"),
+        ]
+    );
+}
+
+#[test]
+fn control_flow_within_label() {
+    let statements = parse_statements("done: if { stop }").unwrap();
+    assert_eq!(statements.len(), 1);
+    let inner = match &statements[0] {
+        StatementType::Label(l) => l.inner.iter().map(|s| s.ty.clone()).collect::<Vec<_>>(),
+        ty => panic!("Expected a label, got {ty}"),
+    };
+    assert_eq!(
+        without_spans(inner),
+        vec![
+            StatementType::Opcode(Opcode::Iszero),
+            call("__if0_end"),
+            StatementType::Opcode(Opcode::Jumpi),
+            StatementType::Opcode(Opcode::Stop),
+            label("__if0_end"),
+        ]
+    );
+}
+
+#[test]
+fn keywords_without_blocks_are_label_calls() {
+    let statements = parse_statements("loop: loop jump if continue break else").unwrap();
+    let inner = match &statements[0] {
+        StatementType::Label(l) if l.name == "loop" => {
+            l.inner.iter().map(|s| s.ty.clone()).collect::<Vec<_>>()
+        }
+        ty => panic!("Expected the \"loop\" label, got {ty}"),
+    };
+    assert_eq!(
+        inner,
+        vec![
+            call("loop"),
+            StatementType::Opcode(Opcode::Jump),
+            call("if"),
+            call("continue"),
+            call("break"),
+            call("else"),
+        ]
+    );
+}
+
+#[test]
+fn unclosed_block() {
+    assert!(parse_statements("loop { 0x01").is_err());
+}
+
+#[test]
+fn lowers_switch() {
+    let statements = without_spans(
+        parse_statements("switch { case 0x01 { 0x0a } case [TWO] { 0x0b } default { 0x0c } }")
+            .unwrap(),
+    );
+    assert_eq!(
+        statements,
+        vec![
+            StatementType::Opcode(Opcode::Dup1),
+            StatementType::Literal(str_to_bytes32("01")),
+            StatementType::Opcode(Opcode::Eq),
+            call("__switch0_case0"),
+            StatementType::Opcode(Opcode::Jumpi),
+            StatementType::Opcode(Opcode::Dup1),
+            StatementType::Constant("TWO".to_string()),
+            StatementType::Opcode(Opcode::Eq),
+            call("__switch0_case1"),
+            StatementType::Opcode(Opcode::Jumpi),
+            StatementType::Opcode(Opcode::Pop),
+            StatementType::Literal(str_to_bytes32("0c")),
+            call("__switch0_end"),
+            StatementType::Opcode(Opcode::Jump),
+            label("__switch0_case0"),
+            StatementType::Opcode(Opcode::Pop),
+            StatementType::Literal(str_to_bytes32("0a")),
+            call("__switch0_end"),
+            StatementType::Opcode(Opcode::Jump),
+            label("__switch0_case1"),
+            StatementType::Opcode(Opcode::Pop),
+            StatementType::Literal(str_to_bytes32("0b")),
+            label("__switch0_end"),
+        ]
+    );
+}
+
+#[test]
+fn switch_case_on_function_selector() {
+    let statements = parse_statements("switch { case __FUNC_SIG(transfer) { stop } }").unwrap();
+    match &statements[1] {
+        StatementType::BuiltinFunctionCall(bf) => {
+            assert_eq!(bf.kind, BuiltinFunctionKind::FunctionSignature);
+            assert_eq!(bf.args[0].name.as_deref(), Some("transfer"));
+        }
+        ty => panic!("Expected a builtin function call, got {ty}"),
+    }
+}
+
+#[test]
+fn rejects_duplicate_switch_cases() {
+    let err = parse_statements("switch { case 0x01 { } case 0x0001 { } }").unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::DuplicateCase("0x01".to_string()));
+
+    let err = parse_statements("switch { case [ONE] { } case [ONE] { } }").unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::DuplicateCase("[ONE]".to_string()));
+}
+
+#[test]
+fn rejects_invalid_switch_clauses() {
+    let err = parse_statements("switch { default { } default { } }").unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::UnexpectedType(TokenKind::Ident("default".to_string())));
+
+    let err = parse_statements("switch { case add { } }").unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::UnexpectedType(TokenKind::Opcode(Opcode::Add)));
+}
+
+#[test]
+fn generates_switch_table() {
+    let source = r#"
+        #define function transfer(address,uint256) nonpayable returns ()
+        #define constant THREE = 0x03
+        #define macro MAIN() = takes(0) returns(0) {
+            switch table {
+                case __FUNC_SIG(transfer) { 0x01 }
+                case [THREE] { 0x02 }
+                default { 0x03 }
+            }
+        }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).parse().unwrap();
+
+    // 0xa9059cbb % 2 and 0x03 % 2 collide, 0xa9059cbb % 3 == 1 and 0x03 % 3 == 0
+    let constants = contract.constants.lock().unwrap();
+    let size = constants.iter().find(|c| c.name == "__DISPATCH0_SIZE").unwrap();
+    assert_eq!(size.value, ConstVal::Literal(str_to_bytes32("03")));
+
+    let table = &contract.tables[0];
+    assert_eq!(table.name, "__DISPATCH0");
+    assert_eq!(table.kind, TableKind::JumpTablePacked);
+    assert_eq!(table.size, str_to_bytes32("06"));
+    assert_eq!(
+        table.statements.iter().map(|s| s.ty.clone()).collect::<Vec<_>>(),
+        vec![call("__dispatch0_case1"), call("__dispatch0_case0"), call("__dispatch0_default")]
+    );
+
+    let statements =
+        without_spans(contract.macros[0].statements.iter().map(|s| s.ty.clone()).collect());
+    assert_eq!(statements[0], StatementType::Constant("__DISPATCH0_SIZE".to_string()));
+    assert_eq!(statements.last(), Some(&label("__dispatch0_end")));
+}
+
+#[test]
+fn rejects_unresolved_switch_table_cases() {
+    let err = parse_statements("switch table { case [MISSING] { } }").unwrap_err();
+    assert!(matches!(err.kind, ParserErrorKind::InvalidDispatchTable(_)));
+
+    // Without cases, `table` is a label call
+    let statements = parse_statements("switch table").unwrap();
+    assert_eq!(statements, vec![call("switch"), call("table")]);
+}
+
+#[test]
+fn generates_switch_trie() {
+    let source = r#"
+        #define function transfer(address,uint256) nonpayable returns ()
+        #define constant APPROVE = 0x095ea7b3
+        #define macro MAIN() = takes(0) returns(0) {
+            switch trie {
+                case __FUNC_SIG(transfer) { 0x01 }
+                case [APPROVE] { 0x02 }
+                default { 0x03 }
+            }
+        }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).parse().unwrap();
+    assert!(contract.tables.is_empty());
+
+    // The first bytes differ, so the trie compares the selector once it found the first byte
+    let statements =
+        without_spans(contract.macros[0].statements.iter().map(|s| s.ty.clone()).collect());
+    let lit = |v: &str| StatementType::Literal(str_to_bytes32(v));
+    let op = StatementType::Opcode;
+    assert_eq!(
+        statements[..20],
+        [
+            op(Opcode::Dup1),
+            lit("1c"),
+            op(Opcode::Byte),
+            lit("a9"),
+            op(Opcode::Dup2),
+            op(Opcode::Lt),
+            call("__DISPATCH0_branch0"),
+            op(Opcode::Jumpi),
+            op(Opcode::Pop),
+            op(Opcode::Dup1),
+            lit("a9059cbb"),
+            op(Opcode::Eq),
+            call("__dispatch0_case0"),
+            op(Opcode::Jumpi),
+            call("__DISPATCH0_miss"),
+            op(Opcode::Jump),
+            label("__DISPATCH0_branch0"),
+            op(Opcode::Pop),
+            op(Opcode::Dup1),
+            lit("095ea7b3"),
+        ]
+    );
+    assert_eq!(
+        statements[20..25],
+        [
+            op(Opcode::Eq),
+            call("__dispatch0_case1"),
+            op(Opcode::Jumpi),
+            label("__DISPATCH0_miss"),
+            op(Opcode::Pop),
+        ]
+    );
+    assert_eq!(statements.last(), Some(&label("__dispatch0_end")));
+}
+
+#[test]
+fn routes_selectors_through_trie() {
+    let selectors = [
+        0x06fdde03, 0x095ea7b3, 0x18160ddd, 0x23b872dd, 0x313ce567, 0x70a08231, 0x95d89b41,
+        0xa9059cbb, 0xdd62ed3e, 0xd505accf, 0x7ecebe00, 0x3644e515, 0x40c10f19, 0x42966c68,
+        0x39509351, 0xa457c2d7,
+    ];
+    let cases = selectors.iter().enumerate().map(|(i, s)| (*s, format!("case{i}"))).collect();
+    let trie = trie::SelectorTrie::new("TRIE".to_string(), cases);
+
+    for (i, selector) in selectors.iter().enumerate() {
+        assert_eq!(trie.route(*selector).0, Some(format!("case{i}")));
+    }
+    assert_eq!(trie.route(0xa9059cbc).0, None);
+    assert_eq!(trie.route(0x00000000).0, None);
+
+    // Selectors sharing their first byte are told apart by the second one
+    let shared = trie::SelectorTrie::new(
+        "TRIE".to_string(),
+        vec![(0xa9059cbb, "a".to_string()), (0xa9ff0000, "b".to_string())],
+    );
+    assert_eq!(shared.route(0xa9ff0000).0, Some("b".to_string()));
+    assert_eq!(shared.route(0xa9000000).0, None);
+
+    // Four levels of 22 gas binary searching the first byte, then the comparison of the case,
+    // while the chain compares every case before the last one
+    assert_eq!(trie.route(0xa457c2d7).1, 123);
+    assert_eq!(trie.chain_gas(0xa457c2d7), 16 * trie::COMPARISON_GAS);
+    assert!(trie.to_string().starts_with(
+        "TRIE: 16 cases, 123 gas on average, 187 for a comparison chain\n    0x06fdde03  case0 "
+    ));
+}
+
+#[test]
+fn rejects_invalid_switch_trie_cases() {
+    let err = parse_statements("switch trie { case 0x0100000000 { } }").unwrap_err();
+    assert!(matches!(err.kind, ParserErrorKind::InvalidDispatchTable(_)));
+
+    let source = r#"
+        #define constant ONE = 0x01
+        #define macro MAIN() = takes(0) returns(0) {
+            switch trie { case 0x01 { } case [ONE] { } }
+        }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let err = Parser::new(tokens, None).parse().unwrap_err();
+    assert_eq!(
+        err.kind,
+        ParserErrorKind::InvalidDispatchTable(
+            "Two cases have the same selector: 0x00000001".to_string()
+        )
+    );
+}
@@ -0,0 +1,82 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn parse(source: &str, edition: Edition) -> Result<Contract, ParserError> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    parser.edition = edition;
+    parser.parse()
+}
+
+fn unsupported(source: &str) -> Feature {
+    parse(source, Edition::E2024).unwrap();
+    match parse(source, Edition::E2022).unwrap_err().kind {
+        ParserErrorKind::UnsupportedFeature(feature) => feature,
+        kind => panic!("Expected an unsupported feature, got {kind:?}"),
+    }
+}
+
+#[test]
+fn gates_newer_syntax_by_edition() {
+    assert_eq!(unsupported("#define constant C = -0x01"), Feature::NegativeLiterals);
+    assert_eq!(
+        unsupported("#define macro A() = takes(0) returns(amount) { 0x01 }"),
+        Feature::NamedOutputs
+    );
+    assert_eq!(
+        unsupported("#pragma no-inline\n#define macro A() = takes(0) returns(0) {}"),
+        Feature::MacroPragmas
+    );
+    assert_eq!(
+        unsupported("#define macro A() = takes(1) returns(0) { if { 0x01 pop } }"),
+        Feature::ControlFlowBlocks
+    );
+    assert_eq!(unsupported("#define data BLOB = 0xdeadbeef"), Feature::DataSections);
+    assert_eq!(unsupported("#define struct Pair(uint256 a, bytes b)"), Feature::StructDefinitions);
+    assert_eq!(unsupported("#define merkle TREE(keccak256)"), Feature::MerkleTrees);
+}
+
+#[test]
+fn legacy_edition_allows_opcode_names() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        stop jump
+        stop:
+            0x00 dup1 revert
+    }
+    "#;
+    assert_eq!(
+        parse(source, Edition::E2024).unwrap_err().kind,
+        ParserErrorKind::OpcodeNameCollision("stop".to_string())
+    );
+    assert!(parse(source, Edition::E2022).is_ok());
+}
+
+#[test]
+fn files_declare_their_edition() {
+    let source = "#pragma edition \"2022\"\n#define constant C = -0x01";
+    let err = parse(source, Edition::E2024).unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::UnsupportedFeature(Feature::NegativeLiterals));
+    assert_eq!(
+        err.hint.unwrap(),
+        "The file is edition 2022. Declare `#pragma edition \"2024\"` as its first line, or compile with `--edition 2024`."
+    );
+
+    let source = "#pragma edition 2024\n#define constant C = -0x01";
+    let contract = parse(source, Edition::E2022).unwrap();
+    assert_eq!(contract.pragmas[0].kind, SourcePragmaKind::Edition);
+    assert_eq!(contract.pragmas[0].value, "2024");
+}
+
+#[test]
+fn rejects_invalid_edition_pragmas() {
+    let err = parse("#pragma edition \"2023\"", Edition::E2024).unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::InvalidPragma("edition 2023".to_string()));
+    assert_eq!(err.hint.unwrap(), "Unsupported edition: \"2023\", expected one of 2022, 2024");
+
+    let source = "#define constant C = 0x01\n#pragma edition \"2022\"";
+    let err = parse(source, Edition::E2024).unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::InvalidPragma("edition".to_string()));
+}
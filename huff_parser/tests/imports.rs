@@ -34,3 +34,51 @@ fn parses_deep_imports() {
     let import_path = contract.imports[0].clone();
     assert_eq!(import_path.to_str().unwrap(), "../huff-examples/erc20/contracts/ERC20.huff");
 }
+
+#[test]
+fn parses_abi_import() {
+    let source = r#"
+        #include abi "../out/IERC20.sol/IERC20.json"
+        #include "./Ownable.huff"
+        #define macro MAIN() = takes (0) returns (0) { __FUNC_SIG(IERC20.transfer) }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+
+    // The stem of the file names the entries, which are declared once the file is read
+    assert_eq!(contract.imports.len(), 1);
+    let import = &contract.abi_imports[0];
+    assert_eq!(import.name, "IERC20");
+    assert_eq!(import.path.to_str().unwrap(), "../out/IERC20.sol/IERC20.json");
+    assert!(import.functions.is_empty());
+    assert_eq!(
+        contract.macros[0].statements[0].ty,
+        StatementType::BuiltinFunctionCall(BuiltinFunctionCall {
+            kind: BuiltinFunctionKind::FunctionSignature,
+            args: vec![Argument {
+                name: Some("IERC20.transfer".to_string()),
+                span: AstSpan(vec![Span::new(154..169, None)]),
+                ..Default::default()
+            }],
+            span: AstSpan(vec![Span::new(143..153, None), Span::new(154..169, None)]),
+        })
+    );
+}
+
+#[test]
+fn parses_sol_import() {
+    let source = r#"#include sol "./interfaces/tokens.v2.sol""#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+
+    // Solidity imports are named after their interfaces once read, so the stem may hold dots
+    let import = &contract.abi_imports[0];
+    assert_eq!(import.kind, AbiImportKind::Sol);
+    assert_eq!(import.path.to_str().unwrap(), "./interfaces/tokens.v2.sol");
+}
@@ -163,6 +163,9 @@ fn multiline_labels() {
         ]),
         outlined: false,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition.name, md_expected.name);
     assert_eq!(macro_definition.parameters, md_expected.parameters);
@@ -419,6 +422,9 @@ pub fn builtins_under_labels() {
         ]),
         outlined: false,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition.name, md_expected.name);
     assert_eq!(macro_definition.parameters, md_expected.parameters);
@@ -39,6 +39,9 @@ fn empty_macro() {
         ]),
         outlined: false,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -73,6 +76,9 @@ fn empty_macro_without_takes_returns() {
         ]),
         outlined: false,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -111,6 +117,9 @@ fn empty_macro_only_takes() {
         ]),
         outlined: false,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -149,6 +158,9 @@ fn empty_macro_only_returns() {
         ]),
         outlined: false,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -218,6 +230,9 @@ fn macro_with_simple_body() {
         ]),
         outlined: false,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -400,6 +415,9 @@ fn macro_with_arg_calls() {
         ]),
         outlined: false,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -576,6 +594,9 @@ fn macro_labels() {
         ]),
         outlined: false,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -722,6 +743,9 @@ fn macro_invocation_with_arg_call() {
         ]),
         outlined: false,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -791,6 +815,9 @@ fn test_macro_opcode_arguments() {
         ]),
         outlined: false,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -863,6 +890,9 @@ fn macro_with_builtin_fn_call() {
         ]),
         outlined: false,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -907,6 +937,9 @@ fn empty_outlined_macro() {
         ]),
         outlined: true,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -975,6 +1008,9 @@ fn outlined_macro_with_simple_body() {
         ]),
         outlined: true,
         test: false,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -1017,6 +1053,9 @@ fn empty_test() {
         ]),
         outlined: false,
         test: true,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -1103,6 +1142,9 @@ fn test_with_simple_body() {
         ]),
         outlined: false,
         test: true,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -1160,6 +1202,9 @@ fn empty_test_with_decorator() {
         ]),
         outlined: false,
         test: true,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
@@ -1225,7 +1270,276 @@ fn empty_test_with_multi_flag_decorator() {
         ]),
         outlined: false,
         test: true,
+        constants: vec![],
+        outputs: vec![],
+        pragmas: vec![],
     };
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
 }
+
+#[test]
+fn empty_test_with_fuzz_decorator() {
+    let source = r#"
+    #[fuzz("add(uint256,uint256)"), runs(0x0a), seed(42)]
+    #define test MY_TEST() = takes(0) returns(0) {}
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Grab the first macro
+    let macro_definition = parser.parse().unwrap().macros[0].clone();
+    assert_eq!(
+        macro_definition.decorator,
+        Some(Decorator {
+            flags: vec![
+                DecoratorFlag::Fuzz(String::from("add(uint256,uint256)")),
+                DecoratorFlag::Runs(str_to_bytes32("0a")),
+                DecoratorFlag::Seed(str_to_bytes32("2a")),
+            ],
+        })
+    );
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+}
+
+#[test]
+fn macro_with_only_caller_decorator() {
+    let source = r#"
+    #[only_caller(OWNER, Unauthorized), only_caller(ADMIN)]
+    #define macro WITHDRAW() = takes(0) returns(0) {}
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    let macro_definition = parser.parse().unwrap().macros[0].clone();
+    assert_eq!(
+        macro_definition.decorator,
+        Some(Decorator {
+            flags: vec![
+                DecoratorFlag::OnlyCaller(
+                    String::from("OWNER"),
+                    Some(String::from("Unauthorized"))
+                ),
+                DecoratorFlag::OnlyCaller(String::from("ADMIN"), None),
+            ],
+        })
+    );
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    let source = r#"
+    #[only_caller(0x01)]
+    #define macro WITHDRAW() = takes(0) returns(0) {}
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let err = Parser::new(tokens, None).parse().unwrap_err();
+    assert!(matches!(err.kind, ParserErrorKind::InvalidDecoratorFlagArg(_)));
+}
+
+#[test]
+fn test_with_invalid_fuzz_signature() {
+    let source = r#"
+    #[fuzz("add")]
+    #define test MY_TEST() = takes(0) returns(0) {}
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    let err = parser.parse().unwrap_err();
+    assert_eq!(
+        err.kind,
+        ParserErrorKind::InvalidDecoratorFlagArg(TokenKind::Str("add".to_string()))
+    );
+}
+
+#[test]
+fn macro_invocation_with_constant_and_macro_args() {
+    let source = r#"
+    #define macro HIGHER_ORDER(value) = takes(0) returns(0) {
+        APPLY([OWNER_SLOT], HASH(<value>, 0x20), DOUBLE())
+    }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    let macro_definition = parser.parse().unwrap().macros[0].clone();
+    let args = match &macro_definition.statements[0].ty {
+        StatementType::MacroInvocation(mi) => mi.args.clone(),
+        ty => panic!("Expected a macro invocation, got {ty}"),
+    };
+    assert_eq!(args.len(), 3);
+    assert_eq!(args[0], MacroArg::Ident("OWNER_SLOT".to_string()));
+    match (&args[1], &args[2]) {
+        (MacroArg::MacroInvocation(hash), MacroArg::MacroInvocation(double)) => {
+            assert_eq!(hash.macro_name, "HASH");
+            assert_eq!(
+                hash.args,
+                vec![
+                    MacroArg::ArgCall("value".to_string()),
+                    MacroArg::Literal(str_to_bytes32("20"))
+                ]
+            );
+            assert_eq!(hash.span, AstSpan(vec![Span { start: 91, end: 95, file: None }]));
+            assert_eq!(double.macro_name, "DOUBLE");
+            assert!(double.args.is_empty());
+        }
+        args => panic!("Expected macro invocations, got {args:?}"),
+    }
+}
+
+#[test]
+fn macro_invocation_with_invalid_arg() {
+    let source = r#"
+    #define macro INVALID() = takes(0) returns(0) {
+        APPLY(=)
+    }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    let err = parser.parse().unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::InvalidMacroArgs(TokenKind::Assign));
+}
+
+#[test]
+fn macro_takes_and_returns_are_optional_and_unordered() {
+    let parse = |source: &str| {
+        let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+        let lexer = Lexer::new(flattened_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        parser.parse().map(|c| (c.macros[0].takes, c.macros[0].returns))
+    };
+
+    assert_eq!(parse("#define macro FOO() = {}").unwrap(), (0, 0));
+    assert_eq!(parse("#define macro FOO() = takes(2) {}").unwrap(), (2, 0));
+    assert_eq!(parse("#define macro FOO() = returns(1) {}").unwrap(), (0, 1));
+    assert_eq!(parse("#define macro FOO() = returns(1) takes(2) {}").unwrap(), (2, 1));
+    assert_eq!(parse("#define fn FOO() = returns (3) takes (1) { add }").unwrap(), (1, 3));
+
+    let err = parse("#define macro FOO() = takes(1) returns(1) takes(2) {}").unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::UnexpectedType(TokenKind::Takes));
+    assert_eq!(err.hint.unwrap(), "\"takes\" may only be specified once per macro");
+
+    let err = parse("#define macro FOO() = take(1) {}").unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::UnexpectedType(TokenKind::Ident("take".to_string())));
+    assert_eq!(err.hint.unwrap(), "Expected \"takes\", \"returns\" or the macro body");
+}
+
+#[test]
+fn macro_with_assertions() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            __ASSERT([SLOT_A] != [SLOT_B], "slots collide")
+            __ASSERT(([SIZE] + 1) * 0x20 <= 0x0400)
+        }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+
+    let assertions = contract.macros[0]
+        .statements
+        .iter()
+        .map(|s| match &s.ty {
+            StatementType::Assertion(a) => a.clone(),
+            ty => panic!("Expected an assertion, got {ty}"),
+        })
+        .collect::<Vec<Assertion>>();
+    assert_eq!(
+        assertions[0].condition,
+        ConstExpr::Binary(
+            Box::new(ConstExpr::Constant("SLOT_A".to_string())),
+            ConstOperator::Neq,
+            Box::new(ConstExpr::Constant("SLOT_B".to_string())),
+        )
+    );
+    assert_eq!(assertions[0].message.as_deref(), Some("slots collide"));
+    assert_eq!(assertions[1].condition.to_string(), "(([SIZE] + 0x01) * 0x20) <= 0x0400");
+    assert_eq!(assertions[1].message, None);
+
+    let source = "#define macro MAIN() = takes(0) returns(0) { __ASSERT(add) }";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let err = Parser::new(tokens, None).parse().unwrap_err();
+    assert!(matches!(err.kind, ParserErrorKind::UnexpectedType(_)));
+}
+
+#[test]
+fn macro_with_named_outputs() {
+    let source = "#define macro DIVMOD() = takes(2) returns(quotient, remainder) {}";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Named outputs are counted as the macro's returns
+    let macro_definition = parser.parse().unwrap().macros[0].clone();
+    assert_eq!(macro_definition.takes, 2);
+    assert_eq!(macro_definition.returns, 2);
+    assert_eq!(macro_definition.outputs, vec!["quotient".to_string(), "remainder".to_string()]);
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    // Outputs are either counted or named
+    let source = "#define macro DIVMOD() = takes(2) returns(quotient, 1) {}";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    assert!(parser.parse().is_err());
+}
+
+#[test]
+fn macro_with_pragmas() {
+    let source = r#"
+    #pragma no-inline
+    #pragma unsafe-stack
+    #pragma no-inline
+    #define macro HELPER() = takes(0) returns(0) {}
+
+    #pragma hot
+    #[calldata("0x01")]
+    #define test MY_TEST() = {}
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Duplicate pragmas are only stored once
+    let contract = parser.parse().unwrap();
+    assert_eq!(contract.macros[0].pragmas, vec![MacroPragma::NoInline, MacroPragma::UnsafeStack]);
+    assert!(contract.macros[0].is_outlined());
+    assert_eq!(contract.macros[1].pragmas, vec![MacroPragma::Hot]);
+    assert!(contract.macros[1].test);
+    assert!(contract.macros[1].decorator.is_some());
+
+    // Unknown pragmas are rejected
+    let source = "#pragma inline\n#define macro MAIN() = {}";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let err = Parser::new(tokens, None).parse().unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::InvalidPragma("inline".to_string()));
+    assert_eq!(err.spans.0[0].start, 8);
+    assert_eq!(err.spans.0[0].end, 14);
+
+    // Macro pragmas must be followed by a macro
+    let source = "#pragma hot\n#define constant C = 0x01";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let err = Parser::new(tokens, None).parse().unwrap_err();
+    assert!(matches!(err.kind, ParserErrorKind::UnexpectedType(TokenKind::Constant)));
+}
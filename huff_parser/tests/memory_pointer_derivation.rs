@@ -0,0 +1,88 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn parse(source: &str) -> Result<Contract, ParserError> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    Parser::new(tokens, None).parse()
+}
+
+#[test]
+fn parses_memory_pointer_sizes() {
+    let source = r#"
+        #define constant WORD = FREE_MEMORY_POINTER()
+        #define constant BUFFER = FREE_MEMORY_POINTER(0x40)
+        #define constant UNALIGNED = FREE_MEMORY_POINTER(0x21)
+    "#;
+    let contract = parse(source).unwrap();
+    let sizes = contract
+        .constants
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|c| match &c.value {
+            ConstVal::FreeMemoryPointer(fmp) => fmp.size,
+            v => panic!("unexpected constant value: {v:?}"),
+        })
+        .collect::<Vec<usize>>();
+
+    // Sizes are rounded up to whole words
+    assert_eq!(sizes, [0x20, 0x40, 0x40]);
+}
+
+#[test]
+fn rejects_invalid_memory_pointer_sizes() {
+    for size in ["0x00", "0x0100000000", "SIZE"] {
+        let source = format!("#define constant BUF = FREE_MEMORY_POINTER({size})");
+        let err = parse(&source).unwrap_err();
+        assert!(matches!(err.kind, ParserErrorKind::InvalidConstantValue(_)), "{size}: {err:?}");
+    }
+}
+
+#[test]
+fn derives_memory_pointers() {
+    let source = r#"
+        #define constant UNUSED = FREE_MEMORY_POINTER()
+        #define constant BUFFER = FREE_MEMORY_POINTER(0x40)
+        #define constant SLOT = FREE_STORAGE_POINTER()
+        #define constant WORD = FREE_MEMORY_POINTER()
+        #define constant LAST = FREE_MEMORY_POINTER()
+
+        #define macro CONSTRUCTOR() = takes(0) returns(0) {
+            [WORD] mload
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [BUFFER] mload [SLOT] sload
+            USE_LAST()
+        }
+
+        #define macro USE_LAST() = takes(0) returns(0) {
+            [LAST] mload [WORD] mload
+        }
+    "#;
+    let mut contract = parse(source).unwrap();
+    contract.derive_storage_pointers();
+
+    // Regions are laid out contiguously in the order they're first used, and don't take up
+    // storage slots
+    assert_eq!(
+        contract.memory_layout,
+        vec![
+            MemorySlot { name: "WORD".to_string(), offset: 0x00, size: 0x20 },
+            MemorySlot { name: "BUFFER".to_string(), offset: 0x20, size: 0x40 },
+            MemorySlot { name: "LAST".to_string(), offset: 0x60, size: 0x20 },
+        ]
+    );
+    let constants = contract.constants.lock().unwrap();
+    let value = |name: &str| constants.iter().find(|c| c.name == name).unwrap().value.clone();
+    assert_eq!(value("WORD"), ConstVal::Literal(str_to_bytes32("00")));
+    assert_eq!(value("BUFFER"), ConstVal::Literal(str_to_bytes32("20")));
+    assert_eq!(value("LAST"), ConstVal::Literal(str_to_bytes32("60")));
+    assert_eq!(value("SLOT"), ConstVal::Literal(str_to_bytes32("00")));
+
+    // Unused pointers aren't reserved
+    assert_eq!(value("UNUSED"), ConstVal::FreeMemoryPointer(FreeMemoryPointer { size: 0x20 }));
+}
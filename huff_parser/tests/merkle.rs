@@ -0,0 +1,102 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn parse(source: &str) -> Result<Contract, ParserError> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    Parser::new(tokens, None).parse()
+}
+
+#[test]
+fn parses_merkle_trees() {
+    let source = r#"
+        #define merkle AIRDROP(keccak256) = "leaves.json"
+        #define merkle ALLOWLIST(sha256, 0x10)
+
+        #define contract Vault {
+            #define merkle CLAIMS(keccak256, 3)
+        }
+    "#;
+    let contract = parse(source).unwrap();
+
+    let trees = contract.merkle_trees.iter().map(|t| (t.name.as_str(), t.hash, t.depth));
+    assert_eq!(
+        trees.collect::<Vec<_>>(),
+        [("AIRDROP", MerkleHash::Keccak256, None), ("ALLOWLIST", MerkleHash::Sha256, Some(16))]
+    );
+    assert_eq!(contract.merkle_trees[0].leaves, Some(FilePath::from("leaves.json")));
+    assert_eq!(contract.merkle_trees[1].leaves, None);
+
+    // The verifiers are generated in the scope of their tree, and fixed depths don't take a length
+    let macros = contract.macros.iter().map(|m| (m.name.as_str(), m.takes, m.returns));
+    assert_eq!(macros.collect::<Vec<_>>(), [("AIRDROP_VERIFY", 4, 1), ("ALLOWLIST_VERIFY", 3, 1)]);
+    let vault = &contract.contracts[0].contract;
+    assert_eq!(vault.merkle_trees[0].depth, Some(3));
+    assert_eq!(vault.macros.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), ["CLAIMS_VERIFY"]);
+}
+
+#[test]
+fn generates_unrolled_verifier() {
+    let contract = parse("#define merkle TREE(keccak256, 2)").unwrap();
+    let verifier = contract.find_macro_by_name("TREE_VERIFY").unwrap();
+
+    // Each level hashes the next node of the proof with the node below it, the smaller one first
+    let word = |value: &str| StatementType::Literal(str_to_bytes32(value));
+    let step = |offset: Option<&str>| {
+        let mut step = vec![StatementType::Opcode(Opcode::Dup2)];
+        if let Some(offset) = offset {
+            step.extend([word(offset), StatementType::Opcode(Opcode::Add)]);
+        }
+        step.extend([
+            StatementType::Opcode(Opcode::Calldataload),
+            StatementType::Opcode(Opcode::Dup1),
+            StatementType::Opcode(Opcode::Dup3),
+            StatementType::Opcode(Opcode::Gt),
+            word("05"),
+            StatementType::Opcode(Opcode::Shl),
+            StatementType::Opcode(Opcode::Swap2),
+            StatementType::Opcode(Opcode::Dup3),
+            StatementType::Opcode(Opcode::Mstore),
+            StatementType::Opcode(Opcode::Swap1),
+            word("20"),
+            StatementType::Opcode(Opcode::Xor),
+            StatementType::Opcode(Opcode::Mstore),
+            word("40"),
+            word("00"),
+            StatementType::Opcode(Opcode::Sha3),
+        ]);
+        step
+    };
+    let mut expected = step(None);
+    expected.extend(step(Some("20")));
+    expected.extend([
+        StatementType::Opcode(Opcode::Swap1),
+        StatementType::Opcode(Opcode::Pop),
+        StatementType::Opcode(Opcode::Eq),
+    ]);
+    assert_eq!(verifier.statements.iter().map(|s| s.ty.clone()).collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn rejects_invalid_merkle_trees() {
+    for (source, reason) in [
+        ("#define merkle TREE(blake2)", "Invalid argument \"blake2\" of merkle tree \"TREE\""),
+        ("#define merkle TREE(keccak256, 0)", "Invalid argument \"0\" of merkle tree \"TREE\""),
+        ("#define merkle TREE(sha256, 65)", "Invalid argument \"65\" of merkle tree \"TREE\""),
+        (
+            "#define merkle TREE(keccak256)\n#define merkle TREE(sha256)",
+            "Merkle tree \"TREE\" is defined more than once",
+        ),
+        (
+            "#define merkle TREE(keccak256)\n#define macro TREE_VERIFY() = takes(0) returns(0) {}",
+            "Macro \"TREE_VERIFY\" is already defined",
+        ),
+    ] {
+        match parse(source).unwrap_err().kind {
+            ParserErrorKind::InvalidMerkleTree(r) => assert_eq!(r, reason),
+            kind => panic!("Expected an invalid merkle tree, got {kind:?}"),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn parse(source: &str) -> Result<Contract, ParserError> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    Parser::new(tokens, None).parse()
+}
+
+#[test]
+fn rejects_label_named_after_opcode() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            0x01 balance jumpi
+            balance:
+                stop
+        }
+    "#;
+    let err = parse(source).unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::OpcodeNameCollision("balance".to_string()));
+    assert_eq!(&source[err.spans.0[0].start..err.spans.0[0].end], "balance");
+    assert!(err.hint.unwrap().contains("\"balance_\""));
+}
+
+#[test]
+fn rejects_constant_named_after_opcode() {
+    let err = parse("#define constant caller = 0x01").unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::OpcodeNameCollision("caller".to_string()));
+    assert!(err.hint.unwrap().contains("\"CALLER\""));
+}
+
+#[test]
+fn rejects_macro_argument_named_after_opcode() {
+    let source = r#"
+        #define macro TRANSFER(to, value) = takes(0) returns(0) {
+            <to> <value>
+        }
+    "#;
+    assert!(parse(source).is_ok());
+
+    let err = parse(&source.replace("value", "callvalue")).unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::OpcodeNameCollision("callvalue".to_string()));
+}
+
+#[test]
+fn accepts_names_containing_opcodes() {
+    let source = r#"
+        #define constant CALLER = 0x01
+        #define macro MAIN(add_) = takes(0) returns(0) {
+            [CALLER] balance_ jumpi
+            balance_:
+                <add_> stop
+        }
+    "#;
+    assert!(parse(source).is_ok());
+}
@@ -0,0 +1,47 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn parse(source: &str) -> Result<Contract, ParserError> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    Parser::new(tokens, None).parse()
+}
+
+#[test]
+fn parses_source_pragmas() {
+    let source = r#"
+    #pragma huff-version "^0.3.1"
+    #pragma evm-version shanghai
+
+    #pragma no-inline
+    #define macro MAIN() = takes(0) returns(0) {}
+    "#;
+    let contract = parse(source).unwrap();
+
+    let pragmas = contract.pragmas.iter().map(|p| (p.kind, p.value.as_str())).collect::<Vec<_>>();
+    assert_eq!(
+        pragmas,
+        vec![(SourcePragmaKind::HuffVersion, "^0.3.1"), (SourcePragmaKind::EvmVersion, "shanghai")]
+    );
+    assert_eq!(contract.pragmas[0].span.0.len(), 2);
+    assert_eq!(contract.macros[0].pragmas, vec![MacroPragma::NoInline]);
+}
+
+#[test]
+fn rejects_invalid_source_pragmas() {
+    // Compiler versions are strings
+    let err = parse("#pragma huff-version 0x01").unwrap_err();
+    assert!(matches!(err.kind, ParserErrorKind::UnexpectedType(TokenKind::Literal(_))));
+
+    // Source pragmas can't annotate the macros of contract blocks
+    let source = r#"
+    #define contract TOKEN {
+        #pragma evm-version "paris"
+        #define macro MAIN() = {}
+    }
+    "#;
+    let err = parse(source).unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::InvalidPragma("evm-version".to_string()));
+    assert_eq!(err.hint.unwrap(), "\"evm-version\" must be declared at the top level of a file");
+}
@@ -20,7 +20,8 @@ fn derives_storage_pointers() {
         fsp_constant,
         ConstantDefinition {
             name: "FSP_LOCATION".to_string(),
-            value: ConstVal::FreeStoragePointer(FreeStoragePointer {}),
+            ty: None,
+            value: ConstVal::FreeStoragePointer(FreeStoragePointer::default()),
             span: AstSpan(vec![
                 Span { start: 0, end: 7, file: None },
                 Span { start: 8, end: 16, file: None },
@@ -36,7 +37,8 @@ fn derives_storage_pointers() {
         fsp_constant,
         ConstantDefinition {
             name: "FSP_LOCATION_2".to_string(),
-            value: ConstVal::FreeStoragePointer(FreeStoragePointer {}),
+            ty: None,
+            value: ConstVal::FreeStoragePointer(FreeStoragePointer::default()),
             span: AstSpan(vec![
                 Span { start: 55, end: 62, file: None },
                 Span { start: 63, end: 71, file: None },
@@ -52,6 +54,7 @@ fn derives_storage_pointers() {
         num_constant,
         ConstantDefinition {
             name: "NUM".to_string(),
+            ty: None,
             value: ConstVal::Literal(str_to_bytes32("a57B")),
             span: AstSpan(vec![
                 Span { start: 112, end: 119, file: None },
@@ -66,17 +69,173 @@ fn derives_storage_pointers() {
     // Derive the AST's free storage pointers
     contract.derive_storage_pointers();
 
-    // Ensure that the storage pointers were set for the FSP constants in the AST
+    // Ensure that the storage pointers were set for the FSP constants in the AST, in declaration
+    // order even though they're unused
     assert_eq!(
         contract.constants.lock().unwrap()[0].value,
-        ConstVal::FreeStoragePointer(FreeStoragePointer)
+        ConstVal::Literal(str_to_bytes32("00"))
     );
     assert_eq!(
         contract.constants.lock().unwrap()[1].value,
-        ConstVal::FreeStoragePointer(FreeStoragePointer)
+        ConstVal::Literal(str_to_bytes32("01"))
     );
     assert_eq!(
         contract.constants.lock().unwrap()[2].value,
         ConstVal::Literal(str_to_bytes32("a57B"))
     );
 }
+
+fn parse(source: &str) -> Result<Contract, ParserError> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    Parser::new(tokens, None).parse()
+}
+
+#[test]
+fn parses_explicit_storage_slots() {
+    let source = r#"
+        #define constant DECIMAL = FREE_STORAGE_POINTER(slot=10)
+        #define constant HEX = FREE_STORAGE_POINTER(slot=0x0b)
+        #define constant FREE = FREE_STORAGE_POINTER()
+    "#;
+    let contract = parse(source).unwrap();
+    let slots = contract
+        .constants
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|c| match &c.value {
+            ConstVal::FreeStoragePointer(fsp) => fsp.slot,
+            v => panic!("unexpected constant value: {v:?}"),
+        })
+        .collect::<Vec<Option<Literal>>>();
+    assert_eq!(slots, [Some(str_to_bytes32("0a")), Some(str_to_bytes32("0b")), None]);
+}
+
+#[test]
+fn rejects_invalid_storage_slots() {
+    for slot in ["(index=1)", "(slot=)", "(slot=OTHER)", "(slot=1"] {
+        let source = format!("#define constant SLOT = FREE_STORAGE_POINTER{slot}\n");
+        assert!(parse(&source).is_err(), "{slot}");
+    }
+    let err = parse("#define constant SLOT = FREE_STORAGE_POINTER(slot=)").unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::InvalidConstantValue(TokenKind::CloseParen));
+}
+
+#[test]
+fn derives_storage_pointers_in_declaration_order() {
+    let source = r#"
+        #define constant FIRST = FREE_STORAGE_POINTER()
+        #define constant PINNED = FREE_STORAGE_POINTER(slot=1)
+        #define constant SECOND = FREE_STORAGE_POINTER()
+        #define constant MEMORY = FREE_MEMORY_POINTER()
+        #define constant THIRD = FREE_STORAGE_POINTER()
+        #define constant FAR = FREE_STORAGE_POINTER(slot=0x10)
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [THIRD] sload [SECOND] sload [FIRST] sload
+        }
+    "#;
+    let mut contract = parse(source).unwrap();
+    contract.derive_storage_pointers();
+
+    // Slots follow the declarations rather than their use, skipping the pinned slots
+    let slot =
+        |name: &str, slot: &str| StorageSlot { name: name.to_string(), slot: str_to_bytes32(slot) };
+    assert_eq!(
+        contract.storage_layout,
+        vec![
+            slot("FIRST", "00"),
+            slot("PINNED", "01"),
+            slot("SECOND", "02"),
+            slot("THIRD", "03"),
+            slot("FAR", "10")
+        ]
+    );
+    let constants = contract.constants.lock().unwrap();
+    let value = |name: &str| constants.iter().find(|c| c.name == name).unwrap().value.clone();
+    assert_eq!(value("THIRD"), ConstVal::Literal(str_to_bytes32("03")));
+    assert_eq!(value("FAR"), ConstVal::Literal(str_to_bytes32("10")));
+}
+
+#[test]
+fn derives_hex_storage_slots() {
+    let source = (0..12)
+        .map(|i| format!("#define constant SLOT_{i} = FREE_STORAGE_POINTER()\n"))
+        .collect::<String>();
+    let mut contract = parse(&source).unwrap();
+    contract.derive_storage_pointers();
+
+    // The eleventh slot is 0x0a, not 0x10
+    assert_eq!(contract.storage_layout[10].slot, str_to_bytes32("0a"));
+    assert_eq!(contract.storage_layout[11].slot, str_to_bytes32("0b"));
+}
+
+#[test]
+fn derives_storage_slots() {
+    let source = r#"
+        #define constant IMPLEMENTATION = STORAGE_SLOT(0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc)
+        #define constant FIRST = FREE_STORAGE_POINTER()
+        #define constant PINNED = STORAGE_SLOT(0)
+        #define constant SECOND = FREE_STORAGE_POINTER()
+    "#;
+    let mut contract = parse(source).unwrap();
+    assert_eq!(
+        contract.constants.lock().unwrap()[2].value,
+        ConstVal::FreeStoragePointer(FreeStoragePointer { slot: Some(str_to_bytes32("00")) })
+    );
+    contract.derive_storage_pointers();
+
+    // Derived slots skip the explicit slots
+    let slots = contract
+        .storage_layout
+        .iter()
+        .map(|s| (s.name.as_str(), bytes32_to_string(&s.slot, true)))
+        .collect::<Vec<(&str, String)>>();
+    assert_eq!(
+        slots,
+        [
+            (
+                "IMPLEMENTATION",
+                "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc".to_string()
+            ),
+            ("FIRST", "0x01".to_string()),
+            ("PINNED", "0x00".to_string()),
+            ("SECOND", "0x02".to_string())
+        ]
+    );
+    assert!(contract.check_storage_collisions().is_ok());
+}
+
+#[test]
+fn detects_storage_slot_collisions() {
+    let source = r#"
+        #define constant OWNER = FREE_STORAGE_POINTER(slot=2)
+        #define constant FREE = FREE_STORAGE_POINTER()
+        #define constant ADMIN = STORAGE_SLOT(0x02)
+    "#;
+    let mut contract = parse(source).unwrap();
+    contract.derive_storage_pointers();
+    let err = contract.check_storage_collisions().unwrap_err();
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::StorageSlotCollision(
+            vec!["OWNER".to_string(), "ADMIN".to_string()],
+            "0x02".to_string()
+        )
+    );
+    // The error points at the second constant
+    assert_eq!(err.span, contract.constants.lock().unwrap()[2].span);
+}
+
+#[test]
+fn rejects_invalid_storage_slot_builtins() {
+    for source in [
+        "#define constant SLOT = STORAGE_SLOT()",
+        "#define constant SLOT = STORAGE_SLOT(slot=1)",
+        "#define constant SLOT = STORAGE_SLOT(0x01",
+    ] {
+        assert!(parse(source).is_err(), "{source}");
+    }
+}
@@ -0,0 +1,113 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn parse(source: &str) -> Result<Contract, ParserError> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    Parser::new(tokens, None).parse()
+}
+
+#[test]
+fn parses_struct_definitions() {
+    let source = r#"
+        #define struct Order(address maker, uint256[] ids, string note)
+
+        #define contract Market {
+            #define struct Bid(uint256 price)
+        }
+    "#;
+    let contract = parse(source).unwrap();
+
+    let order = &contract.structs[0];
+    assert_eq!(order.name, "Order");
+    assert_eq!(
+        order
+            .fields
+            .iter()
+            .map(|f| (f.arg_type.as_deref().unwrap(), f.name.as_deref().unwrap()))
+            .collect::<Vec<_>>(),
+        [("address", "maker"), ("uint256[]", "ids"), ("string", "note")]
+    );
+    assert_eq!(
+        order
+            .fields
+            .iter()
+            .map(|f| codec::FieldKind::of(f.arg_type.as_ref().unwrap()))
+            .collect::<Vec<_>>(),
+        [
+            Some(codec::FieldKind::Value),
+            Some(codec::FieldKind::Array),
+            Some(codec::FieldKind::Bytes)
+        ]
+    );
+
+    // The codec macros are generated in the scope of their struct
+    let macros = contract.macros.iter().map(|m| (m.name.as_str(), m.takes, m.returns));
+    assert_eq!(macros.collect::<Vec<_>>(), [("Order_ENCODE", 2, 1), ("Order_DECODE", 2, 0)]);
+    let market = &contract.contracts[0].contract;
+    assert_eq!(market.structs[0].name, "Bid");
+    assert_eq!(
+        market.macros.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(),
+        ["Bid_ENCODE", "Bid_DECODE"]
+    );
+}
+
+#[test]
+fn generates_struct_decoder() {
+    let contract = parse("#define struct Pair(uint256 a, bytes b)").unwrap();
+    let decoder = contract.find_macro_by_name("Pair_DECODE").unwrap();
+
+    // The value is copied, and the bytes point into the encoding at their offset
+    let word = |value: &str| StatementType::Literal(str_to_bytes32(value));
+    assert_eq!(
+        decoder.statements.iter().map(|s| s.ty.clone()).collect::<Vec<_>>(),
+        [
+            StatementType::Opcode(Opcode::Dup1),
+            StatementType::Opcode(Opcode::Mload),
+            StatementType::Opcode(Opcode::Dup3),
+            StatementType::Opcode(Opcode::Mstore),
+            StatementType::Opcode(Opcode::Dup1),
+            StatementType::Opcode(Opcode::Dup1),
+            word("20"),
+            StatementType::Opcode(Opcode::Add),
+            StatementType::Opcode(Opcode::Mload),
+            StatementType::Opcode(Opcode::Add),
+            StatementType::Opcode(Opcode::Dup3),
+            word("20"),
+            StatementType::Opcode(Opcode::Add),
+            StatementType::Opcode(Opcode::Mstore),
+            StatementType::Opcode(Opcode::Pop),
+            StatementType::Opcode(Opcode::Pop),
+        ]
+    );
+}
+
+#[test]
+fn rejects_invalid_structs() {
+    for (source, reason) in [
+        (
+            "#define struct Order(bytes[] data)",
+            "Field \"data\" of struct \"Order\" is of unsupported type \"bytes[]\"",
+        ),
+        (
+            "#define struct Order(uint256[2] ids)",
+            "Field \"ids\" of struct \"Order\" is of unsupported type \"uint256[2]\"",
+        ),
+        ("#define struct Order(address)", "A field of struct \"Order\" of type \"address\" is unnamed"),
+        (
+            "#define struct Order(address maker)\n#define struct Order(uint256 amount)",
+            "Struct \"Order\" is defined more than once",
+        ),
+        (
+            "#define struct Order(address maker)\n#define macro Order_DECODE() = takes(0) returns(0) {}",
+            "Macro \"Order_DECODE\" is already defined",
+        ),
+    ] {
+        match parse(source).unwrap_err().kind {
+            ParserErrorKind::InvalidStruct(r) => assert_eq!(r, reason),
+            kind => panic!("Expected an invalid struct, got {kind:?}"),
+        }
+    }
+}
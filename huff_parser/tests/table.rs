@@ -112,3 +112,41 @@ fn table_with_body() {
         assert_eq!(parser.current_token.kind, TokenKind::Eof);
     }
 }
+
+#[test]
+fn data_section() {
+    let source = "#define data BLOB = 0xdeadbeef00";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+
+    let mut parser = Parser::new(tokens, None);
+    let data = parser.parse().unwrap().tables[0].clone();
+    assert_eq!(data.name, "BLOB");
+    assert_eq!(data.kind, TableKind::Data);
+    assert_eq!(data.size, str_to_bytes32("05"));
+    assert_eq!(
+        data.statements,
+        vec![Statement {
+            ty: StatementType::Code("deadbeef00".to_string()),
+            span: AstSpan(vec![Span { start: 22, end: 32, file: None }]),
+        }]
+    );
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+}
+
+#[test]
+fn data_section_without_bytes() {
+    let source = "#define data BLOB = FOO";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+
+    let mut parser = Parser::new(tokens, None);
+    let err = parser.parse().unwrap_err();
+    assert_eq!(
+        err.kind,
+        ParserErrorKind::InvalidTableBodyToken(TokenKind::Ident("FOO".to_string()))
+    );
+    assert_eq!(err.spans, AstSpan(vec![Span { start: 20, end: 23, file: None }]));
+}
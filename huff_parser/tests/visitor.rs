@@ -0,0 +1,145 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::{
+    ast::{Event, Function},
+    prelude::*,
+};
+
+/// Records the name of every node it visits
+#[derive(Default)]
+struct Recorder {
+    visited: Vec<String>,
+}
+
+impl<'ast> Visitor<'ast> for Recorder {
+    fn visit_macro(&mut self, macro_def: &'ast MacroDefinition) {
+        self.visited.push(format!("macro {}", macro_def.name));
+        walk_macro(self, macro_def);
+    }
+
+    fn visit_macro_invocation(&mut self, invocation: &'ast MacroInvocation) {
+        self.visited.push(format!("invocation {}", invocation.macro_name));
+        walk_macro_invocation(self, invocation);
+    }
+
+    fn visit_macro_arg(&mut self, arg: &'ast MacroArg) {
+        if let MacroArg::Ident(name) = arg {
+            self.visited.push(format!("arg {name}"));
+        }
+    }
+
+    fn visit_label(&mut self, label: &'ast Label) {
+        self.visited.push(format!("label {}", label.name));
+        walk_label(self, label);
+    }
+
+    fn visit_builtin_function_call(&mut self, call: &'ast BuiltinFunctionCall) {
+        self.visited.push(format!("builtin {:?}", call.kind));
+    }
+
+    fn visit_constant(&mut self, constant: &ConstantDefinition) {
+        self.visited.push(format!("constant {}", constant.name));
+    }
+
+    fn visit_function(&mut self, function: &'ast Function) {
+        self.visited.push(format!("function {}", function.name));
+    }
+
+    fn visit_event(&mut self, event: &'ast Event) {
+        self.visited.push(format!("event {}", event.name));
+    }
+
+    fn visit_error(&mut self, error: &'ast ErrorDefinition) {
+        self.visited.push(format!("error {}", error.name));
+    }
+
+    fn visit_table(&mut self, table: &'ast TableDefinition) {
+        self.visited.push(format!("table {}", table.name));
+        walk_table(self, table);
+    }
+}
+
+#[test]
+fn test_visitor_walks_contract() {
+    let source = r#"
+    #define function transfer(address,uint256) nonpayable returns ()
+    #define event Transfer(address,address,uint256)
+    #define error Unauthorized()
+    #define constant OWNER = FREE_STORAGE_POINTER()
+
+    #define jumptable TABLE {
+        one
+    }
+
+    #define macro INNER(op) = takes(0) returns(0) {
+        <op>
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        __FUNC_SIG(transfer)
+        one:
+            INNER(pop)
+    }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+
+    let mut recorder = Recorder::default();
+    recorder.visit_contract(&contract);
+    assert_eq!(
+        recorder.visited,
+        vec![
+            "constant OWNER",
+            "function transfer",
+            "event Transfer",
+            "error Unauthorized",
+            "table TABLE",
+            "macro INNER",
+            "macro MAIN",
+            "builtin FunctionSignature",
+            "label one",
+            "invocation INNER",
+            "arg pop",
+        ]
+    );
+}
+
+/// Collects references to every opcode statement, relying on the `'ast` lifetime
+#[derive(Default)]
+struct OpcodeCollector<'ast> {
+    opcodes: Vec<&'ast Opcode>,
+}
+
+impl<'ast> Visitor<'ast> for OpcodeCollector<'ast> {
+    fn visit_statement(&mut self, statement: &'ast Statement) {
+        if let StatementType::Opcode(o) = &statement.ty {
+            self.opcodes.push(o);
+        }
+        walk_statement(self, statement);
+    }
+}
+
+#[test]
+fn test_visitor_collects_references() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 calldataload
+        end:
+            pop stop
+    }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+
+    let mut collector = OpcodeCollector::default();
+    collector.visit_contract(&contract);
+    assert_eq!(collector.opcodes, vec![&Opcode::Calldataload, &Opcode::Pop, &Opcode::Stop]);
+}
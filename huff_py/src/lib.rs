@@ -0,0 +1,101 @@
+#![doc = include_str!("../README.md")]
+#![warn(missing_docs)]
+#![warn(unused_extern_crates)]
+#![forbid(unsafe_code)]
+
+use huff_core::{compile_str, playground::PlaygroundSettings};
+use huff_utils::disassembly;
+use pyo3::{
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+    types::{PyDict, PyList},
+};
+use serde_json::Value;
+
+/// Converts a JSON value into the equivalent Python object
+fn to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(u), _) => u.into_py(py),
+            (None, Some(i)) => i.into_py(py),
+            _ => n.as_f64().unwrap_or_default().into_py(py),
+        },
+        Value::String(s) => s.into_py(py),
+        Value::Array(values) => {
+            PyList::new(py, values.iter().map(|v| to_py(py, v)).collect::<PyResult<Vec<_>>>()?)
+                .into()
+        }
+        Value::Object(entries) => {
+            let dict = PyDict::new(py);
+            for (key, value) in entries {
+                dict.set_item(key, to_py(py, value)?)?;
+            }
+            dict.into()
+        }
+    })
+}
+
+/// Compiles a Huff source string.
+///
+/// Returns a dict of the creation bytecode, runtime bytecode and ABI, which are `None` if
+/// compilation failed, the errors and warnings, and the milliseconds spent in each stage of the
+/// compilation. The source may include the standard library, but no other files.
+///
+/// The settings are the keyword arguments `evm_version`, `edition`, `main_macro`,
+/// `constructor_macro`, `constructor_args`, `constant_overrides` and `macro_nesting_limit`.
+#[pyfunction]
+#[pyo3(signature = (source, **settings))]
+fn compile(py: Python<'_>, source: &str, settings: Option<&PyDict>) -> PyResult<PyObject> {
+    let mut playground = PlaygroundSettings::default();
+    for (key, value) in settings.into_iter().flatten() {
+        match key.extract::<&str>()? {
+            "evm_version" => playground.evm_version = value.extract()?,
+            "edition" => playground.edition = value.extract()?,
+            "main_macro" => playground.main_macro = value.extract()?,
+            "constructor_macro" => playground.constructor_macro = value.extract()?,
+            "constructor_args" => playground.constructor_args = value.extract()?,
+            "constant_overrides" => playground.constant_overrides = value.extract()?,
+            "macro_nesting_limit" => playground.macro_nesting_limit = value.extract()?,
+            key => {
+                return Err(PyTypeError::new_err(format!(
+                    "compile() got an unexpected keyword argument '{key}'"
+                )))
+            }
+        }
+    }
+
+    let output = py.allow_threads(|| compile_str(source, &playground));
+    let output = serde_json::to_value(output).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    to_py(py, &output)
+}
+
+/// Disassembles hex encoded bytecode.
+///
+/// Returns a list of dicts of the offset, mnemonic and hex encoded immediate, if any, of each
+/// operation. Raises a `ValueError` if the bytecode isn't valid hex.
+#[pyfunction]
+fn disassemble(py: Python<'_>, bytecode: &str) -> PyResult<PyObject> {
+    let ops = disassembly::disassemble(bytecode).map_err(PyValueError::new_err)?;
+    let list = PyList::empty(py);
+    for op in ops {
+        let dict = PyDict::new(py);
+        dict.set_item("offset", op.offset)?;
+        dict.set_item("mnemonic", op.mnemonic())?;
+        let immediate = (!op.immediate.is_empty()).then(|| {
+            format!("0x{}", op.immediate.iter().map(|b| format!("{b:02x}")).collect::<String>())
+        });
+        dict.set_item("immediate", immediate)?;
+        list.append(dict)?;
+    }
+    Ok(list.into())
+}
+
+/// The Huff compiler
+#[pymodule]
+fn huff(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(compile, m)?)?;
+    m.add_function(wrap_pyfunction!(disassemble, m)?)?;
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use ethers_core::abi::ParamType;
 use phf::phf_map;
 
 /// Map of u32 IDs to cheat codes
@@ -9,3 +10,102 @@ pub const HUFF_CHEATS_MAP: phf::Map<u32, HuffCheatCode> = phf_map! {
 pub enum HuffCheatCode {
     Log,
 }
+
+/// Map of function selectors to the Foundry-style cheatcodes called at the `VM_ADDR`
+pub const VM_CHEATS_MAP: phf::Map<u32, VmCheatCode> = phf_map! {
+    0xca669fa7u32 => VmCheatCode::Prank,
+    0x06447d56u32 => VmCheatCode::StartPrank,
+    0x90c5013bu32 => VmCheatCode::StopPrank,
+    0xc88a5e6du32 => VmCheatCode::Deal,
+    0xe5d6bf02u32 => VmCheatCode::Warp,
+    0x1f7b4f30u32 => VmCheatCode::Roll,
+    0x39b37ab0u32 => VmCheatCode::Fee,
+    0x46cc92d9u32 => VmCheatCode::Difficulty,
+    0xff483c54u32 => VmCheatCode::Coinbase,
+    0x4049ddd2u32 => VmCheatCode::ChainId,
+    0x70ca10bbu32 => VmCheatCode::Store,
+    0x667f9d70u32 => VmCheatCode::Load,
+    0xf4844814u32 => VmCheatCode::ExpectRevert,
+    0xf28dceb3u32 => VmCheatCode::ExpectRevertData,
+    0xc31eb0e0u32 => VmCheatCode::ExpectRevertSelector,
+};
+
+/// Foundry-style cheatcodes, called with the abi encoded calldata of their signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VmCheatCode {
+    /// Sets `msg.sender` until the next call
+    Prank,
+    /// Sets `msg.sender` until `stopPrank()`
+    StartPrank,
+    /// Stops a prank
+    StopPrank,
+    /// Sets the balance of an account
+    Deal,
+    /// Sets `block.timestamp`
+    Warp,
+    /// Sets `block.number`
+    Roll,
+    /// Sets `block.basefee`
+    Fee,
+    /// Sets `block.difficulty`
+    Difficulty,
+    /// Sets `block.coinbase`
+    Coinbase,
+    /// Sets `block.chainid`
+    ChainId,
+    /// Sets a storage slot of an account
+    Store,
+    /// Returns a storage slot of an account
+    Load,
+    /// Expects the next call to revert
+    ExpectRevert,
+    /// Expects the next call to revert with the given data
+    ExpectRevertData,
+    /// Expects the next call to revert with data starting with the given selector
+    ExpectRevertSelector,
+}
+
+impl VmCheatCode {
+    /// The function signature of the cheatcode
+    pub fn signature(&self) -> &'static str {
+        match self {
+            VmCheatCode::Prank => "prank(address)",
+            VmCheatCode::StartPrank => "startPrank(address)",
+            VmCheatCode::StopPrank => "stopPrank()",
+            VmCheatCode::Deal => "deal(address,uint256)",
+            VmCheatCode::Warp => "warp(uint256)",
+            VmCheatCode::Roll => "roll(uint256)",
+            VmCheatCode::Fee => "fee(uint256)",
+            VmCheatCode::Difficulty => "difficulty(uint256)",
+            VmCheatCode::Coinbase => "coinbase(address)",
+            VmCheatCode::ChainId => "chainId(uint256)",
+            VmCheatCode::Store => "store(address,bytes32,bytes32)",
+            VmCheatCode::Load => "load(address,bytes32)",
+            VmCheatCode::ExpectRevert => "expectRevert()",
+            VmCheatCode::ExpectRevertData => "expectRevert(bytes)",
+            VmCheatCode::ExpectRevertSelector => "expectRevert(bytes4)",
+        }
+    }
+
+    /// The types of the cheatcode's arguments
+    pub fn inputs(&self) -> Vec<ParamType> {
+        match self {
+            VmCheatCode::Prank | VmCheatCode::StartPrank | VmCheatCode::Coinbase => {
+                vec![ParamType::Address]
+            }
+            VmCheatCode::StopPrank | VmCheatCode::ExpectRevert => vec![],
+            VmCheatCode::Deal => vec![ParamType::Address, ParamType::Uint(256)],
+            VmCheatCode::Warp |
+            VmCheatCode::Roll |
+            VmCheatCode::Fee |
+            VmCheatCode::Difficulty |
+            VmCheatCode::ChainId => vec![ParamType::Uint(256)],
+            VmCheatCode::Store => {
+                vec![ParamType::Address, ParamType::FixedBytes(32), ParamType::FixedBytes(32)]
+            }
+            VmCheatCode::Load => vec![ParamType::Address, ParamType::FixedBytes(32)],
+            VmCheatCode::ExpectRevertData => vec![ParamType::Bytes],
+            VmCheatCode::ExpectRevertSelector => vec![ParamType::FixedBytes(4)],
+        }
+    }
+}
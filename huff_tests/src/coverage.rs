@@ -0,0 +1,157 @@
+use huff_utils::prelude::{AstSpan, Contract, Instruction, Statement, StatementType};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The lines of a macro's statements
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MacroCoverage {
+    /// The macro name
+    pub name: String,
+    /// The path of the file defining the macro
+    pub path: String,
+    /// The line the macro is defined on
+    pub line: usize,
+    /// The lines of the macro's statements
+    pub lines: BTreeSet<usize>,
+}
+
+/// Line and macro coverage of the tests of a contract
+///
+/// Coverage is tracked for the lines of the statements generating instructions, as macro
+/// invocations and argument calls are covered by the statements they expand to. Test macros
+/// aren't covered themselves.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct CoverageReport {
+    /// The number of times each line was executed, by file path and line
+    pub hits: BTreeMap<String, BTreeMap<usize, u64>>,
+    /// The macros of the contract
+    pub macros: Vec<MacroCoverage>,
+}
+
+impl CoverageReport {
+    /// Create a report with no lines executed from a contract's AST.
+    pub fn new(contract: &Contract) -> Self {
+        let mut report = Self::default();
+        for macro_def in contract.macros.iter().filter(|m| !m.test) {
+            let (path, line) = match span_line(&macro_def.span) {
+                Some(l) => l,
+                None => continue,
+            };
+            let mut lines = BTreeSet::new();
+            collect_lines(&macro_def.statements, &mut lines);
+            let hits = report.hits.entry(path.clone()).or_default();
+            lines.iter().for_each(|l| {
+                hits.entry(*l).or_insert(0);
+            });
+            report.macros.push(MacroCoverage { name: macro_def.name.clone(), path, line, lines });
+        }
+        report
+    }
+
+    /// Record the executions of a test, given its instructions and the number of times each
+    /// program counter was executed.
+    ///
+    /// A line is executed as often as its most executed instruction.
+    pub fn record(
+        &mut self,
+        instructions: &[(usize, Instruction)],
+        executed: &BTreeMap<usize, u64>,
+    ) {
+        let mut lines: BTreeMap<(String, usize), u64> = BTreeMap::new();
+        for (pc, instruction) in instructions {
+            if let (Some(count), Some(line)) =
+                (executed.get(pc), span_line(&instruction.source_span))
+            {
+                let hits = lines.entry(line).or_insert(0);
+                *hits = (*hits).max(*count);
+            }
+        }
+        for ((path, line), count) in lines {
+            if let Some(hits) = self.hits.get_mut(&path).and_then(|h| h.get_mut(&line)) {
+                *hits += count;
+            }
+        }
+    }
+
+    /// Merge the coverage of another report, i.e. of another contract's tests.
+    pub fn merge(&mut self, other: CoverageReport) {
+        for (path, lines) in other.hits {
+            let hits = self.hits.entry(path).or_default();
+            for (line, count) in lines {
+                *hits.entry(line).or_insert(0) += count;
+            }
+        }
+        for macro_cov in other.macros {
+            if !self.macros.contains(&macro_cov) {
+                self.macros.push(macro_cov);
+            }
+        }
+    }
+
+    /// The number of executed lines and the number of lines of a file.
+    pub fn file_lines(&self, path: &str) -> (usize, usize) {
+        self.hits
+            .get(path)
+            .map(|lines| (lines.values().filter(|h| **h > 0).count(), lines.len()))
+            .unwrap_or_default()
+    }
+
+    /// The number of executions of a macro, those of its most executed line.
+    pub fn macro_hits(&self, macro_cov: &MacroCoverage) -> u64 {
+        macro_cov.lines.iter().map(|l| self.line_hits(&macro_cov.path, *l)).max().unwrap_or(0)
+    }
+
+    /// The number of executed lines and the number of lines of a macro.
+    pub fn macro_lines(&self, macro_cov: &MacroCoverage) -> (usize, usize) {
+        let covered =
+            macro_cov.lines.iter().filter(|l| self.line_hits(&macro_cov.path, **l) > 0).count();
+        (covered, macro_cov.lines.len())
+    }
+
+    /// The number of times a line was executed.
+    pub fn line_hits(&self, path: &str, line: usize) -> u64 {
+        self.hits.get(path).and_then(|lines| lines.get(&line)).copied().unwrap_or(0)
+    }
+
+    /// Export the report in the lcov tracefile format.
+    pub fn to_lcov(&self) -> String {
+        let mut lcov = String::new();
+        for (path, lines) in &self.hits {
+            lcov.push_str(&format!("TN:\nSF:{path}\n"));
+            let macros = self.macros.iter().filter(|m| m.path == *path).collect::<Vec<_>>();
+            for m in &macros {
+                lcov.push_str(&format!("FN:{},{}\n", m.line, m.name));
+            }
+            for m in &macros {
+                lcov.push_str(&format!("FNDA:{},{}\n", self.macro_hits(m), m.name));
+            }
+            let macros_hit = macros.iter().filter(|m| self.macro_hits(m) > 0).count();
+            lcov.push_str(&format!("FNF:{}\nFNH:{macros_hit}\n", macros.len()));
+            for (line, hits) in lines {
+                lcov.push_str(&format!("DA:{line},{hits}\n"));
+            }
+            let (covered, total) = self.file_lines(path);
+            lcov.push_str(&format!("LF:{total}\nLH:{covered}\nend_of_record\n"));
+        }
+        lcov
+    }
+}
+
+/// The file path and line a span starts on.
+fn span_line(span: &AstSpan) -> Option<(String, usize)> {
+    span.0.iter().find_map(|s| Some((s.file.as_ref()?.path.clone(), s.line()?)))
+}
+
+/// Collect the lines of the statements generating instructions.
+fn collect_lines(statements: &[Statement], lines: &mut BTreeSet<usize>) {
+    for statement in statements {
+        match &statement.ty {
+            StatementType::MacroInvocation(_) | StatementType::ArgCall(_) => {}
+            StatementType::Label(label) => {
+                lines.extend(span_line(&statement.span).map(|(_, l)| l));
+                collect_lines(&label.inner, lines);
+            }
+            _ => lines.extend(span_line(&statement.span).map(|(_, l)| l)),
+        }
+    }
+}
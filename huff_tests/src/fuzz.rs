@@ -0,0 +1,258 @@
+use crate::errors::RunnerError;
+use ethers_core::{
+    abi::{encode, param_type::Reader, short_signature, ParamType, Token},
+    types::{Address, U256},
+    utils::hex,
+};
+use rand::{rngs::StdRng, Rng};
+
+/// The number of calls of a fuzzed test, unless set with the `runs` decorator flag
+pub const DEFAULT_FUZZ_RUNS: u64 = 256;
+
+/// The maximum number of calls made to shrink the arguments of a failing fuzzed test
+pub const MAX_SHRINK_CALLS: usize = 1024;
+
+/// The configuration of fuzzed tests, overridden by the `runs` and `seed` decorator flags
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzConfig {
+    /// The number of calls of each fuzzed test
+    pub runs: u64,
+    /// The seed of the random arguments, a random seed if unset
+    pub seed: Option<u64>,
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        Self { runs: DEFAULT_FUZZ_RUNS, seed: None }
+    }
+}
+
+/// Generates and shrinks the arguments of a fuzzed test's function signature
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fuzzer {
+    /// The function name
+    pub name: String,
+    /// The types of the function's arguments
+    pub inputs: Vec<ParamType>,
+}
+
+impl Fuzzer {
+    /// Create a fuzzer from a function signature, i.e. `add(uint256,uint256)`.
+    pub fn new(signature: &str) -> Result<Self, RunnerError> {
+        let invalid = || RunnerError(format!("Invalid fuzz signature \"{signature}\""));
+        let open = signature.find('(').ok_or_else(invalid)?;
+        let (name, args) = signature.split_at(open);
+        let inputs = match Reader::read(args) {
+            _ if args == "()" => vec![],
+            Ok(ParamType::Tuple(inputs)) if inputs.iter().all(is_valid) => inputs,
+            _ => return Err(invalid()),
+        };
+        Ok(Self { name: name.trim().to_string(), inputs })
+    }
+
+    /// Generate random arguments, biased towards edge cases.
+    pub fn generate(&self, rng: &mut StdRng) -> Vec<Token> {
+        self.inputs.iter().map(|kind| random_token(kind, rng)).collect()
+    }
+
+    /// Encode the calldata of a call with the arguments, prefixed with the function selector.
+    pub fn calldata(&self, args: &[Token]) -> String {
+        let selector = short_signature(&self.name, &self.inputs);
+        format!("{}{}", hex::encode(selector), hex::encode(encode(args)))
+    }
+
+    /// Shrink the arguments of a failing call, while the call keeps failing.
+    ///
+    /// Each argument is replaced by simpler values in turn, i.e. numbers closer to zero or
+    /// shorter arrays, and the first that still fails is kept, until no simpler arguments fail or
+    /// [MAX_SHRINK_CALLS] calls were made.
+    pub fn shrink(
+        &self,
+        args: Vec<Token>,
+        mut fails: impl FnMut(&[Token]) -> Result<bool, RunnerError>,
+    ) -> Result<Vec<Token>, RunnerError> {
+        let mut args = args;
+        let mut calls = 0;
+        'shrink: while calls < MAX_SHRINK_CALLS {
+            for i in 0..args.len() {
+                for candidate in shrink_token(&args[i]) {
+                    let mut shrunk = args.clone();
+                    shrunk[i] = candidate;
+                    calls += 1;
+                    if fails(&shrunk)? {
+                        args = shrunk;
+                        continue 'shrink;
+                    }
+                    if calls >= MAX_SHRINK_CALLS {
+                        break 'shrink;
+                    }
+                }
+            }
+            break;
+        }
+        Ok(args)
+    }
+
+    /// Format a call with the arguments, i.e. `add(1, 0)`.
+    pub fn format_call(&self, args: &[Token]) -> String {
+        format!("{}({})", self.name, args.iter().map(format_token).collect::<Vec<_>>().join(", "))
+    }
+}
+
+/// Whether a type has a valid size, i.e. `uint256` but not `uint257`
+fn is_valid(kind: &ParamType) -> bool {
+    match kind {
+        ParamType::Uint(bits) | ParamType::Int(bits) => (8..=256).contains(bits) && bits % 8 == 0,
+        ParamType::FixedBytes(size) => (1..=32).contains(size),
+        ParamType::Array(inner) | ParamType::FixedArray(inner, _) => is_valid(inner),
+        ParamType::Tuple(inner) => inner.iter().all(is_valid),
+        _ => true,
+    }
+}
+
+/// The mask of the lowest `bits` bits of a word
+fn mask(bits: usize) -> U256 {
+    match bits {
+        256 => U256::MAX,
+        _ => (U256::one() << bits) - 1,
+    }
+}
+
+/// Sign extends a `bits` wide two's complement value to a word
+fn sign_extend(value: U256, bits: usize) -> U256 {
+    match bits < 256 && value.bit(bits - 1) {
+        true => value | !mask(bits),
+        false => value,
+    }
+}
+
+/// Whether a word is a negative two's complement value
+fn is_negative(value: &U256) -> bool {
+    value.bit(255)
+}
+
+/// A random word of `bits` bits, an edge case one time in four
+fn random_word(rng: &mut StdRng, bits: usize) -> U256 {
+    match rng.gen_range(0..12) {
+        0 => U256::zero(),
+        1 => U256::one(),
+        2 => mask(bits),
+        _ => U256::from_big_endian(&rng.gen::<[u8; 32]>()) & mask(bits),
+    }
+}
+
+fn random_token(kind: &ParamType, rng: &mut StdRng) -> Token {
+    match kind {
+        ParamType::Uint(bits) => Token::Uint(random_word(rng, *bits)),
+        ParamType::Int(bits) => match rng.gen_range(0..8) {
+            // The minimum and maximum values
+            0 => Token::Int(sign_extend(U256::one() << (bits - 1), *bits)),
+            1 => Token::Int(mask(*bits) >> 1),
+            _ => Token::Int(sign_extend(random_word(rng, *bits), *bits)),
+        },
+        ParamType::Address => match rng.gen_range(0..8) {
+            0 => Token::Address(Address::zero()),
+            _ => Token::Address(Address::from(rng.gen::<[u8; 20]>())),
+        },
+        ParamType::Bool => Token::Bool(rng.gen()),
+        ParamType::FixedBytes(size) => Token::FixedBytes((0..*size).map(|_| rng.gen()).collect()),
+        ParamType::Bytes => {
+            let len = rng.gen_range(0..=64);
+            Token::Bytes((0..len).map(|_| rng.gen()).collect())
+        }
+        ParamType::String => {
+            let len = rng.gen_range(0..=32);
+            Token::String((0..len).map(|_| rng.gen_range('a'..='z')).collect())
+        }
+        ParamType::Array(inner) => {
+            let len = rng.gen_range(0..=4);
+            Token::Array((0..len).map(|_| random_token(inner, rng)).collect())
+        }
+        ParamType::FixedArray(inner, size) => {
+            Token::FixedArray((0..*size).map(|_| random_token(inner, rng)).collect())
+        }
+        ParamType::Tuple(inner) => {
+            Token::Tuple(inner.iter().map(|k| random_token(k, rng)).collect())
+        }
+    }
+}
+
+/// Simpler values of a token, simplest first
+fn shrink_token(token: &Token) -> Vec<Token> {
+    match token {
+        Token::Uint(v) => towards_zero(*v).into_iter().map(Token::Uint).collect(),
+        Token::Int(v) if is_negative(v) => {
+            towards_zero(negate(*v)).into_iter().map(|v| Token::Int(negate(v))).collect()
+        }
+        Token::Int(v) => towards_zero(*v).into_iter().map(Token::Int).collect(),
+        Token::Address(a) if !a.is_zero() => vec![Token::Address(Address::zero())],
+        Token::Bool(true) => vec![Token::Bool(false)],
+        Token::FixedBytes(b) if b.iter().any(|b| *b != 0) => {
+            vec![Token::FixedBytes(vec![0; b.len()])]
+        }
+        Token::Bytes(b) if !b.is_empty() => {
+            vec![Token::Bytes(vec![]), Token::Bytes(b[..b.len() / 2].to_vec())]
+        }
+        Token::String(s) if !s.is_empty() => {
+            vec![Token::String(String::new()), Token::String(s[..s.len() / 2].to_string())]
+        }
+        Token::Array(v) if !v.is_empty() => {
+            let mut shrunk = vec![Token::Array(vec![]), Token::Array(v[..v.len() - 1].to_vec())];
+            shrunk.extend(shrink_elements(v).into_iter().map(Token::Array));
+            shrunk
+        }
+        Token::FixedArray(v) => shrink_elements(v).into_iter().map(Token::FixedArray).collect(),
+        Token::Tuple(v) => shrink_elements(v).into_iter().map(Token::Tuple).collect(),
+        _ => vec![],
+    }
+}
+
+/// Numbers between zero and a number, closest to zero first, approaching the number by halving
+/// the distance to it
+fn towards_zero(value: U256) -> Vec<U256> {
+    if value.is_zero() {
+        return vec![];
+    }
+    let mut numbers = vec![U256::zero()];
+    numbers.extend((1..256).map(|k| value >> k).take_while(|d| !d.is_zero()).map(|d| value - d));
+    numbers.dedup();
+    numbers
+}
+
+/// The two's complement negation of a word
+fn negate(value: U256) -> U256 {
+    (!value).overflowing_add(U256::one()).0
+}
+
+/// The elements with one of them shrunk
+fn shrink_elements(elements: &[Token]) -> Vec<Vec<Token>> {
+    elements
+        .iter()
+        .enumerate()
+        .flat_map(|(i, element)| {
+            shrink_token(element).into_iter().map(move |shrunk| {
+                let mut elements = elements.to_vec();
+                elements[i] = shrunk;
+                elements
+            })
+        })
+        .collect()
+}
+
+fn format_token(token: &Token) -> String {
+    match token {
+        Token::Uint(v) => v.to_string(),
+        Token::Int(v) if is_negative(v) => format!("-{}", negate(*v)),
+        Token::Int(v) => v.to_string(),
+        Token::Address(a) => format!("{a:?}"),
+        Token::Bytes(b) | Token::FixedBytes(b) => format!("0x{}", hex::encode(b)),
+        Token::String(s) => format!("{s:?}"),
+        Token::Array(v) | Token::FixedArray(v) => {
+            format!("[{}]", v.iter().map(format_token).collect::<Vec<_>>().join(", "))
+        }
+        Token::Tuple(v) => {
+            format!("({})", v.iter().map(format_token).collect::<Vec<_>>().join(", "))
+        }
+        token => token.to_string(),
+    }
+}
@@ -1,28 +1,244 @@
-use crate::cheats::{HuffCheatCode, HUFF_CHEATS_MAP};
+use crate::{
+    cheats::{HuffCheatCode, VmCheatCode, HUFF_CHEATS_MAP, VM_CHEATS_MAP},
+    repl::MachineState,
+};
 use bytes::Bytes;
-use ethers_core::{types::Address, utils::hex};
+use ethers_core::{
+    abi::{decode, encode, short_signature, ParamType, Token},
+    types::{Address, U256},
+    utils::hex,
+};
 use lazy_static::lazy_static;
 use revm::{
-    interpreter::{CallInputs, CreateInputs, Gas, InstructionResult},
+    interpreter::{CallInputs, CreateInputs, Gas, InstructionResult, Interpreter},
     primitives::B160,
     Database, EVMData, Inspector,
 };
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr};
 
 lazy_static! {
     pub static ref CHEATS_ADDR: Address =
         Address::from_str("00000000000000000000000000000000bEefbabe").unwrap();
+    /// The address of the Foundry-style cheatcodes, `address(uint160(uint256(keccak256("hevm cheat code"))))`
+    pub static ref VM_ADDR: Address =
+        Address::from_str("7109709ECfa91a80626fF3989D68f67F5b1DD12D").unwrap();
+}
+
+/// A prank of `msg.sender`, set by the `prank` and `startPrank` cheatcodes
+#[derive(Debug, Clone)]
+pub struct Prank {
+    /// The pranked `msg.sender`
+    pub caller: B160,
+    /// The contract that started the prank
+    pub pranker: B160,
+    /// The call depth of the contract that started the prank
+    pub depth: u64,
+    /// Whether the prank ends with the next call
+    pub single: bool,
+    /// Whether the prank hasn't ended
+    pub active: bool,
+    /// The `msg.sender` of the contract that started the prank, restored when the prank ends
+    pub original: Option<B160>,
+}
+
+/// A revert expected by the `expectRevert` cheatcodes
+#[derive(Debug, Clone)]
+pub struct ExpectedRevert {
+    /// The expected revert data, any revert data if unset
+    pub data: Option<Vec<u8>>,
+    /// Whether the revert data only has to start with the expected data
+    pub partial: bool,
+    /// The call depth of the contract expecting the revert
+    pub depth: u64,
 }
 
 #[derive(Debug, Default)]
 pub struct CheatsInspector {
     pub logs: Vec<(u32, String)>,
+    /// The contract whose executed program counters are recorded, if collecting coverage
+    pub traced: Option<B160>,
+    /// The number of times each program counter of the traced contract was executed
+    pub executed: BTreeMap<usize, u64>,
+    /// The active prank of `msg.sender`
+    pub prank: Option<Prank>,
+    /// The revert expected of the next call
+    pub expected_revert: Option<ExpectedRevert>,
+    /// The contract whose stack and memory are recorded, if inspecting its state
+    pub inspected: Option<B160>,
+    /// The stack and memory of the inspected contract before its last executed instruction
+    pub state: Option<MachineState>,
+}
+
+impl ExpectedRevert {
+    /// Check the result of a call against the expected revert.
+    pub fn check(&self, status: InstructionResult, out: &Bytes) -> Result<(), String> {
+        if !matches!(status, InstructionResult::Revert) {
+            return Err(String::from("Call did not revert as expected"));
+        }
+        let matched = match &self.data {
+            None => true,
+            Some(data) if self.partial => out.starts_with(data),
+            Some(data) => out[..] == data[..],
+        };
+        match matched {
+            true => Ok(()),
+            false => Err(format!(
+                "Call reverted with 0x{} instead of the expected 0x{}",
+                hex::encode(out),
+                hex::encode(self.data.as_deref().unwrap_or_default())
+            )),
+        }
+    }
+}
+
+/// The revert data of a failed cheatcode or expectation, abi encoded as `Error(string)`
+fn revert_data(msg: &str) -> Bytes {
+    let selector = short_signature("Error", &[ParamType::String]);
+    [selector.to_vec(), encode(&[Token::String(msg.to_string())])].concat().into()
+}
+
+impl CheatsInspector {
+    /// Apply a Foundry-style cheatcode called at the `VM_ADDR`, returning its return data.
+    fn apply_cheatcode<DB: Database>(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &CallInputs,
+    ) -> Result<Bytes, String> {
+        if call.input.len() < 4 {
+            return Err(String::from("Missing cheatcode selector"));
+        }
+        let selector =
+            u32::from_be_bytes([call.input[0], call.input[1], call.input[2], call.input[3]]);
+        let cheat = VM_CHEATS_MAP
+            .get(&selector)
+            .ok_or_else(|| format!("Unknown cheatcode selector 0x{selector:08x}"))?;
+        let args = decode(&cheat.inputs(), &call.input[4..])
+            .map_err(|_| format!("Invalid arguments to cheatcode {}", cheat.signature()))?;
+
+        let address = |i: usize| match &args[i] {
+            Token::Address(a) => B160::from_slice(a.as_bytes()),
+            _ => B160::zero(),
+        };
+        let word = |i: usize| -> revm::primitives::U256 {
+            match &args[i] {
+                Token::Uint(v) => (*v).into(),
+                Token::FixedBytes(b) => U256::from_big_endian(b).into(),
+                _ => revm::primitives::U256::ZERO,
+            }
+        };
+        let depth = data.journaled_state.depth();
+
+        match cheat {
+            VmCheatCode::Prank | VmCheatCode::StartPrank => {
+                self.prank = Some(Prank {
+                    caller: address(0),
+                    pranker: call.context.caller,
+                    depth,
+                    single: *cheat == VmCheatCode::Prank,
+                    active: true,
+                    original: self.prank.as_ref().and_then(|p| p.original),
+                });
+            }
+            VmCheatCode::StopPrank => {
+                if let Some(prank) = &mut self.prank {
+                    prank.active = false;
+                }
+            }
+            VmCheatCode::Deal => {
+                let who = address(0);
+                let (account, _) = data
+                    .journaled_state
+                    .load_account(who, data.db)
+                    .map_err(|_| String::from("Failed to load account"))?;
+                account.info.balance = word(1);
+                data.journaled_state.touch(&who);
+            }
+            VmCheatCode::Warp => data.env.block.timestamp = word(0),
+            VmCheatCode::Roll => data.env.block.number = word(0),
+            VmCheatCode::Fee => data.env.block.basefee = word(0),
+            VmCheatCode::Difficulty => data.env.block.difficulty = word(0),
+            VmCheatCode::Coinbase => data.env.block.coinbase = address(0),
+            VmCheatCode::ChainId => data.env.cfg.chain_id = word(0),
+            VmCheatCode::Store | VmCheatCode::Load => {
+                let who = address(0);
+                data.journaled_state
+                    .load_account(who, data.db)
+                    .map_err(|_| String::from("Failed to load account"))?;
+                if *cheat == VmCheatCode::Store {
+                    data.journaled_state
+                        .sstore(who, word(1), word(2), data.db)
+                        .map_err(|_| String::from("Failed to store slot"))?;
+                } else {
+                    let (value, _) = data
+                        .journaled_state
+                        .sload(who, word(1), data.db)
+                        .map_err(|_| String::from("Failed to load slot"))?;
+                    return Ok(value.to_be_bytes::<32>().to_vec().into());
+                }
+            }
+            VmCheatCode::ExpectRevert |
+            VmCheatCode::ExpectRevertData |
+            VmCheatCode::ExpectRevertSelector => {
+                let expected = match args.first() {
+                    Some(Token::Bytes(b)) | Some(Token::FixedBytes(b)) => Some(b.clone()),
+                    _ => None,
+                };
+                self.expected_revert = Some(ExpectedRevert {
+                    data: expected,
+                    partial: *cheat == VmCheatCode::ExpectRevertSelector,
+                    depth,
+                });
+            }
+        }
+        Ok(Bytes::new())
+    }
 }
 
 impl<DB> Inspector<DB> for CheatsInspector
 where
     DB: Database,
 {
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> InstructionResult {
+        if self.traced == Some(interp.contract.address) {
+            *self.executed.entry(interp.program_counter()).or_insert(0) += 1;
+        }
+        if self.inspected == Some(interp.contract.address) {
+            self.state = Some(MachineState {
+                stack: interp
+                    .stack
+                    .data()
+                    .iter()
+                    .map(|v| U256::from_big_endian(&v.to_be_bytes::<32>()))
+                    .collect(),
+                memory: interp.memory.data().clone(),
+            });
+        }
+
+        // Set the `msg.sender` of the contract that started a prank while it is active, and
+        // restore it once the prank ends
+        if let Some(prank) = &mut self.prank {
+            if interp.contract.address == prank.pranker &&
+                data.journaled_state.depth() == prank.depth
+            {
+                if prank.active {
+                    prank.original.get_or_insert(interp.contract.caller);
+                    interp.contract.caller = prank.caller;
+                } else {
+                    if let Some(original) = prank.original {
+                        interp.contract.caller = original;
+                    }
+                    self.prank = None;
+                }
+            }
+        }
+        InstructionResult::Continue
+    }
+
     fn log(
         &mut self,
         _: &mut EVMData<'_, DB>,
@@ -35,16 +251,38 @@ where
 
     fn call(
         &mut self,
-        _data: &mut EVMData<'_, DB>,
+        data: &mut EVMData<'_, DB>,
         inputs: &mut CallInputs,
         _is_static: bool,
     ) -> (InstructionResult, Gas, Bytes) {
+        if inputs.contract == B160::from_slice(VM_ADDR.as_bytes()) {
+            return match self.apply_cheatcode(data, inputs) {
+                Ok(out) => (InstructionResult::Return, Gas::new(inputs.gas_limit), out),
+                Err(msg) => {
+                    (InstructionResult::Revert, Gas::new(inputs.gas_limit), revert_data(&msg))
+                }
+            };
+        }
+
+        // Make the next call of the contract that started a prank from the pranked address
+        if let Some(prank) = &mut self.prank {
+            if prank.active &&
+                inputs.context.caller == prank.pranker &&
+                data.journaled_state.depth() == prank.depth
+            {
+                inputs.context.caller = prank.caller;
+                inputs.transfer.source = prank.caller;
+                if prank.single {
+                    prank.active = false;
+                }
+            }
+        }
         (InstructionResult::Continue, Gas::new(inputs.gas_limit), Bytes::new())
     }
 
     fn call_end(
         &mut self,
-        _data: &mut EVMData<'_, DB>,
+        data: &mut EVMData<'_, DB>,
         call: &CallInputs,
         remaining_gas: Gas,
         status: InstructionResult,
@@ -52,6 +290,23 @@ where
         _is_static: bool,
     ) -> (InstructionResult, Gas, Bytes) {
         let revm_cheats = revm::primitives::B160::from_slice(CHEATS_ADDR.as_bytes());
+        let revm_vm = B160::from_slice(VM_ADDR.as_bytes());
+
+        // Check an expected revert against the next call of the contract expecting it, or
+        // against the contract itself if it ends first
+        if call.contract != revm_cheats && call.contract != revm_vm {
+            let depth = data.journaled_state.depth();
+            match self.expected_revert.take() {
+                Some(expected) if depth == expected.depth || depth + 1 == expected.depth => {
+                    return match expected.check(status, &out) {
+                        Ok(()) => (InstructionResult::Return, remaining_gas, Bytes::new()),
+                        Err(msg) => (InstructionResult::Revert, remaining_gas, revert_data(&msg)),
+                    }
+                }
+                expected => self.expected_revert = expected,
+            }
+        }
+
         if call.contract == revm_cheats && call.input.len() >= 64 {
             // All cheatcodes calls must include the cheatcode key and the current pc in the first
             // 64 bytes of calldata.
@@ -88,7 +343,7 @@ where
 
                 // Check if we have exactly one 32 byte input
                 if call.input.len() != 96 {
-                    return (InstructionResult::Revert, remaining_gas, out)
+                    return (InstructionResult::Revert, remaining_gas, out);
                 }
 
                 let log_item = hex::encode(&call.input[64..96]);
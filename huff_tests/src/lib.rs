@@ -1,4 +1,4 @@
-use crate::{errors::RunnerError, runner::TestRunner, types::TestResult};
+use crate::{coverage::CoverageReport, errors::RunnerError, runner::TestRunner, types::TestResult};
 use huff_utils::prelude::{Contract, MacroDefinition};
 use std::{borrow::Borrow, rc::Rc};
 
@@ -14,6 +14,18 @@ pub mod inspectors;
 /// The cheats module
 pub mod cheats;
 
+/// The coverage module
+pub mod coverage;
+
+/// The fuzz module
+pub mod fuzz;
+
+/// The snapshot module
+pub mod snapshot;
+
+/// The repl module
+pub mod repl;
+
 /// The types module
 pub mod types;
 
@@ -22,7 +34,10 @@ pub mod errors;
 
 /// Prelude wraps all modules within the crate
 pub mod prelude {
-    pub use crate::{errors::*, inspectors::*, report::*, runner::*, types::*};
+    pub use crate::{
+        coverage::*, errors::*, fuzz::*, inspectors::*, repl::*, report::*, runner::*, snapshot::*,
+        types::*,
+    };
 }
 
 /// A vector of shared references to test macro definitions
@@ -66,14 +81,27 @@ impl<'t> HuffTester<'t> {
 
     /// Execute tests
     pub fn execute(mut self) -> Result<Vec<TestResult>, RunnerError> {
+        self.run()
+    }
+
+    /// Execute tests, along with a report of the lines and macros of the contract they executed
+    pub fn execute_with_coverage(
+        mut self,
+    ) -> Result<(Vec<TestResult>, CoverageReport), RunnerError> {
+        self.runner.coverage = Some(CoverageReport::new(self.ast));
+        let results = self.run()?;
+        Ok((results, self.runner.coverage.take().unwrap_or_default()))
+    }
+
+    fn run(&mut self) -> Result<Vec<TestResult>, RunnerError> {
         // Check if any test macros exist
         if self.macros.is_empty() {
-            return Err(RunnerError(String::from("No test macros found.")))
+            return Err(RunnerError(String::from("No test macros found.")));
         }
 
         // Execute our tests and return a vector of the results
         self.macros
-            .into_iter()
+            .iter()
             .map(|macro_def| self.runner.run_test(macro_def, self.ast))
             .collect::<Result<Vec<TestResult>, RunnerError>>()
     }
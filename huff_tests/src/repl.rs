@@ -0,0 +1,199 @@
+use crate::prelude::{cheats_inspector::CheatsInspector, RunnerError, TestRunner, TestStatus};
+use ethers_core::types::{Address, U256};
+use huff_codegen::{Codegen, DEFAULT_MACRO_NESTING_LIMIT};
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::{CompilerError, Contract, EVMVersion, FullFileSource, Token};
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+/// The name of the macro holding the statements of a REPL session
+pub const REPL_MACRO: &str = "__REPL";
+
+/// The stack and memory of a contract when its execution halted
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MachineState {
+    /// The stack, from the bottom to the top
+    pub stack: Vec<U256>,
+    /// The memory
+    pub memory: Vec<u8>,
+}
+
+/// The result of running the statements of a REPL session
+#[derive(Debug, Clone)]
+pub struct ReplResult {
+    /// Whether the statements ran successfully or reverted
+    pub status: TestStatus,
+    /// The gas used
+    pub gas: u64,
+    /// The returned or revert data, hex encoded
+    pub return_data: Option<String>,
+    /// The stack and memory when execution halted
+    pub state: MachineState,
+}
+
+impl fmt::Display for ReplResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "stack ({} items):", self.state.stack.len())?;
+        for (i, value) in self.state.stack.iter().rev().enumerate() {
+            writeln!(f, "  {i}: {value:#x}")?;
+        }
+        writeln!(f, "memory ({} bytes):", self.state.memory.len())?;
+        for (i, word) in self.state.memory.chunks(32).enumerate() {
+            writeln!(f, "  {:#06x}: 0x{}", i * 32, ethers_core::utils::hex::encode(word))?;
+        }
+        if let Some(data) = &self.return_data {
+            writeln!(f, "returned: 0x{data}")?;
+        }
+        match self.status {
+            TestStatus::Success => write!(f, "gas: {}", self.gas),
+            TestStatus::Revert => write!(f, "reverted, gas: {}", self.gas),
+        }
+    }
+}
+
+/// An interactive session evaluating Huff statements in an in-memory REVM instance.
+///
+/// Statements entered are appended to the body of the session's macro, which is recompiled and
+/// run from scratch on each entry, so each result reflects every statement entered so far.
+/// Definitions, i.e. `#define macro` or `#define constant`, are added to the session's contract
+/// and may be used by later statements.
+///
+/// Entries that fail to compile, or whose statements revert, are discarded.
+#[derive(Debug, Default)]
+pub struct Repl {
+    /// The contract the session starts from, i.e. a file loaded into the session
+    pub contract: Contract,
+    /// The definitions entered
+    pub definitions: Vec<String>,
+    /// The statements entered
+    pub statements: Vec<String>,
+    /// The EVM version the statements are compiled for
+    pub evm_version: EVMVersion,
+}
+
+impl Repl {
+    /// Create a session starting from the definitions of a contract.
+    pub fn new(contract: Contract, evm_version: EVMVersion) -> Self {
+        Self { contract, evm_version, ..Default::default() }
+    }
+
+    /// Evaluate a line of input, either a definition, or statements to run after the statements
+    /// entered so far.
+    ///
+    /// Returns the result of running the session's statements, unless the input is a definition.
+    pub fn eval(&mut self, input: &str) -> Result<Option<ReplResult>, RunnerError> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(None)
+        }
+
+        if input.starts_with('#') {
+            let mut definitions = self.definitions.clone();
+            definitions.push(input.to_string());
+            self.session_contract(&definitions, &self.statements)?;
+            self.definitions = definitions;
+            return Ok(None)
+        }
+
+        let mut statements = self.statements.clone();
+        statements.push(input.to_string());
+        let res = self.run(&statements)?;
+        if let TestStatus::Success = res.status {
+            self.statements = statements;
+        }
+        Ok(Some(res))
+    }
+
+    /// Run the statements entered so far.
+    pub fn current(&self) -> Result<ReplResult, RunnerError> {
+        self.run(&self.statements)
+    }
+
+    /// Discard the last statements entered, returning them.
+    pub fn undo(&mut self) -> Option<String> {
+        self.statements.pop()
+    }
+
+    /// Discard the statements and definitions entered.
+    pub fn reset(&mut self) {
+        self.definitions.clear();
+        self.statements.clear();
+    }
+
+    /// Compile and run statements in a fresh REVM instance.
+    fn run(&self, statements: &[String]) -> Result<ReplResult, RunnerError> {
+        let contract = self.session_contract(&self.definitions, statements)?;
+        let m = contract
+            .find_macro_by_name(REPL_MACRO)
+            .ok_or_else(|| RunnerError(format!("Missing macro \"{REPL_MACRO}\"")))?;
+        let bytecode = Codegen::macro_to_bytecode(
+            &self.evm_version,
+            m,
+            &contract,
+            &mut vec![m],
+            0,
+            &mut Vec::default(),
+            false,
+            None,
+            DEFAULT_MACRO_NESTING_LIMIT,
+        )
+        .and_then(Codegen::gen_table_bytecode)
+        .map_err(CompilerError::CodegenError)?;
+
+        let mut runner = TestRunner::default();
+        let address = runner.deploy_code(bytecode)?;
+        let mut inspector = CheatsInspector {
+            inspected: Some(revm::primitives::B160::from_slice(address.as_bytes())),
+            ..Default::default()
+        };
+        let res = runner.inspect_call(
+            REPL_MACRO.to_string(),
+            Address::zero(),
+            address,
+            U256::zero(),
+            String::default(),
+            &mut inspector,
+        )?;
+        Ok(ReplResult {
+            status: res.status,
+            gas: res.gas,
+            return_data: res.return_data,
+            state: inspector.state.unwrap_or_default(),
+        })
+    }
+
+    /// The session's contract, with the definitions and the macro of the statements
+    fn session_contract(
+        &self,
+        definitions: &[String],
+        statements: &[String],
+    ) -> Result<Contract, RunnerError> {
+        let source = format!(
+            "{}\n#define macro {REPL_MACRO}() = takes(0) returns(0) {{\n{}\n}}",
+            definitions.join("\n"),
+            statements.join("\n")
+        );
+        let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let lexer = Lexer::new(flattened_source);
+        let tokens = lexer
+            .into_iter()
+            .collect::<Result<Vec<Token>, _>>()
+            .map_err(|e| RunnerError::from(CompilerError::LexicalError(e)))?;
+        let parsed = Parser::new(tokens, None).parse().map_err(CompilerError::ParserError)?;
+
+        let mut contract = self.contract.clone();
+        let mut constants = contract.constants.lock().unwrap().clone();
+        constants.extend(parsed.constants.lock().unwrap().iter().cloned());
+        contract.constants = Arc::new(Mutex::new(constants));
+        contract.macros.extend(parsed.macros);
+        contract.tables.extend(parsed.tables);
+        contract.functions.extend(parsed.functions);
+        contract.events.extend(parsed.events);
+        contract.errors.extend(parsed.errors);
+        contract.derive_storage_pointers();
+        Ok(contract)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::prelude::{ReportKind, TestResult, TestStatus};
+use crate::prelude::{CoverageReport, ReportKind, TestResult, TestStatus};
 use comfy_table::{
     modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, Attribute, Cell, Color, ContentArrangement,
     Row, Table,
@@ -47,16 +47,28 @@ pub fn print_test_report(results: Vec<TestResult>, report_kind: ReportKind, star
         }
         ReportKind::List => {
             for result in results {
+                let runs = match &result.fuzz {
+                    Some(fuzz) => format!(" (runs: {}, μ)", fuzz.runs),
+                    None => String::default(),
+                };
                 println!(
                     "[{0}] {1: <15} - {2} {3: <20}",
                     String::from(result.status),
                     result.name,
                     Paint::yellow("Gas used:"),
-                    result.gas
+                    format!("{}{runs}", result.gas)
                 );
 
                 let num_logs = result.logs.len().saturating_sub(1);
 
+                if let Some(fuzz) = &result.fuzz {
+                    if let Some(counterexample) = &fuzz.counterexample {
+                        let last = result.return_data.is_none() && num_logs == 0;
+                        println!("├─ {} (seed: {})", Paint::cyan("COUNTEREXAMPLE"), fuzz.seed);
+                        println!("{} {counterexample}", if last { "╰─" } else { "├─" });
+                    }
+                }
+
                 if let Some(return_data) = result.return_data {
                     println!("├─ {}", Paint::cyan("RETURN DATA"));
                     println!("{} {return_data}", if num_logs == 0 { "╰─" } else { "├─" });
@@ -83,7 +95,7 @@ pub fn print_test_report(results: Vec<TestResult>, report_kind: ReportKind, star
             } else {
                 eprintln!("Error serializing test results into JSON.");
             }
-            return
+            return;
         }
     }
     println!(
@@ -94,3 +106,47 @@ pub fn print_test_report(results: Vec<TestResult>, report_kind: ReportKind, star
         Paint::magenta(format!("{:.4?}", start.elapsed()))
     );
 }
+
+/// Print a table of the line coverage of each file and macro in a coverage report.
+pub fn print_coverage_report(coverage: &CoverageReport) {
+    fn percentage((covered, total): (usize, usize)) -> Cell {
+        let cell = match total {
+            0 => Cell::new(format!("{covered}/{total}")),
+            _ => Cell::new(format!(
+                "{:.2}% ({covered}/{total})",
+                covered as f64 * 100.0 / total as f64
+            )),
+        };
+        match covered == total {
+            true => cell.fg(Color::Green),
+            false => cell.fg(Color::Yellow),
+        }
+    }
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL).apply_modifier(UTF8_ROUND_CORNERS);
+    table.set_header(Row::from(vec![
+        Cell::new("File").fg(Color::Magenta),
+        Cell::new("Macro").fg(Color::Cyan),
+        Cell::new("Lines").fg(Color::Blue),
+    ]));
+    table.set_content_arrangement(ContentArrangement::DynamicFullWidth);
+    table.set_width(120);
+
+    for path in coverage.hits.keys() {
+        table.add_row(Row::from(vec![
+            Cell::new(path).add_attribute(Attribute::Bold),
+            Cell::new(""),
+            percentage(coverage.file_lines(path)),
+        ]));
+        for macro_cov in coverage.macros.iter().filter(|m| m.path == *path) {
+            table.add_row(Row::from(vec![
+                Cell::new(""),
+                Cell::new(&macro_cov.name),
+                percentage(coverage.macro_lines(macro_cov)),
+            ]));
+        }
+    }
+
+    println!("{table}");
+}
@@ -1,14 +1,18 @@
-use crate::prelude::{cheats_inspector::CheatsInspector, RunnerError, TestResult, TestStatus};
+use crate::prelude::{
+    cheats_inspector::CheatsInspector, CoverageReport, FuzzConfig, FuzzResult, Fuzzer, RunnerError,
+    TestResult, TestStatus,
+};
 use bytes::Bytes;
 use ethers_core::{
     types::{Address, U256},
     utils::hex,
 };
-use huff_codegen::Codegen;
+use huff_codegen::{Codegen, DEFAULT_MACRO_NESTING_LIMIT};
 use huff_utils::{
     ast::{DecoratorFlag, MacroDefinition},
-    prelude::{pad_n_bytes, CompilerError, Contract},
+    prelude::{pad_n_bytes, CompilerError, Contract, EVMVersion, Instruction},
 };
+use rand::{rngs::StdRng, SeedableRng};
 use revm::{
     db::DbAccount,
     primitives::{
@@ -23,6 +27,10 @@ use revm::{
 pub struct TestRunner {
     pub database: InMemoryDB,
     pub env: Env,
+    /// The coverage of the tests run, if collecting coverage
+    pub coverage: Option<CoverageReport>,
+    /// The configuration of fuzzed tests
+    pub fuzz: FuzzConfig,
 }
 
 impl TestRunner {
@@ -107,9 +115,21 @@ impl TestRunner {
         address: Address,
         value: U256,
         data: String,
+    ) -> Result<TestResult, RunnerError> {
+        self.inspect_call(name, caller, address, value, data, &mut CheatsInspector::default())
+    }
+
+    /// Perform a call to a deployed contract, inspected by the given inspector
+    pub fn inspect_call(
+        &mut self,
+        name: String,
+        caller: Address,
+        address: Address,
+        value: U256,
+        data: String,
+        inspector: &mut CheatsInspector,
     ) -> Result<TestResult, RunnerError> {
         let mut evm = EVM::new();
-        let mut inspector = CheatsInspector::default();
         self.set_balance(caller, U256::MAX);
         let revm_address = revm::primitives::B160::from_slice(address.as_bytes());
 
@@ -122,7 +142,7 @@ impl TestRunner {
         evm.database(self.db_mut());
 
         // Send our CALL transaction
-        let er = evm.inspect_commit(&mut inspector).map_err(RunnerError::from)?;
+        let er = evm.inspect_commit(&mut *inspector).map_err(RunnerError::from)?;
 
         // Extract execution params
         let gas_used = match er {
@@ -145,7 +165,7 @@ impl TestRunner {
                         Some(hex::encode(b))
                     }
                 } else {
-                    return Err(RunnerError(String::from("Unexpected transaction kind")))
+                    return Err(RunnerError(String::from("Unexpected transaction kind")));
                 }
             }
             ExecutionResult::Revert { output, .. } => {
@@ -161,7 +181,14 @@ impl TestRunner {
         // Return our test result
         // NOTE: We subtract 21000 gas from the gas result to account for the
         // base cost of the CALL.
-        Ok(TestResult { name, return_data, gas: gas_used - 21000, status, logs: inspector.logs })
+        Ok(TestResult {
+            name,
+            return_data,
+            gas: gas_used - 21000,
+            status,
+            logs: std::mem::take(&mut inspector.logs),
+            fuzz: None,
+        })
     }
 
     /// Compile a test macro and run it in an in-memory REVM instance.
@@ -174,49 +201,156 @@ impl TestRunner {
 
         // Compile the passed test macro
         match Codegen::macro_to_bytecode(
-            m.to_owned(),
+            &EVMVersion::default(),
+            m,
             contract,
-            &mut vec![m.to_owned()],
+            &mut vec![m],
             0,
             &mut Vec::default(),
             false,
             None,
+            DEFAULT_MACRO_NESTING_LIMIT,
         ) {
             // Generate table bytecode for compiled test macro
-            Ok(res) => match Codegen::gen_table_bytecode(res) {
-                Ok(bytecode) => {
-                    // Deploy compiled test macro
-                    let address = self.deploy_code(bytecode)?;
-
-                    // Set environment flags passed through the test decorator
-                    let mut data = String::default();
-                    let mut value = U256::zero();
-                    if let Some(decorator) = &m.decorator {
-                        for flag in &decorator.flags {
-                            match flag {
-                                DecoratorFlag::Calldata(s) => {
-                                    // Strip calldata of 0x prefix, if it is present.
-                                    data = if let Some(s) = s.strip_prefix("0x") {
-                                        s.to_owned()
-                                    } else {
-                                        s.to_owned()
-                                    };
+            Ok(res) => {
+                let instructions = self.coverage.is_some().then(|| res.instructions.clone());
+                match Codegen::gen_table_bytecode(res) {
+                    Ok(bytecode) => {
+                        // Deploy compiled test macro
+                        let address = self.deploy_code(bytecode)?;
+
+                        // Set environment flags passed through the test decorator
+                        let mut data = String::default();
+                        let mut value = U256::zero();
+                        let mut fuzzer = None;
+                        let mut fuzz = self.fuzz.clone();
+                        if let Some(decorator) = &m.decorator {
+                            for flag in &decorator.flags {
+                                match flag {
+                                    DecoratorFlag::Calldata(s) => {
+                                        // Strip calldata of 0x prefix, if it is present.
+                                        data = if let Some(s) = s.strip_prefix("0x") {
+                                            s.to_owned()
+                                        } else {
+                                            s.to_owned()
+                                        };
+                                    }
+                                    DecoratorFlag::Value(v) => value = U256::from(v),
+                                    DecoratorFlag::Fuzz(s) => fuzzer = Some(Fuzzer::new(s)?),
+                                    DecoratorFlag::Runs(r) => fuzz.runs = U256::from(r).low_u64(),
+                                    DecoratorFlag::Seed(s) => {
+                                        fuzz.seed = Some(U256::from(s).low_u64())
+                                    }
+                                    // Caller checks are generated in the test's code
+                                    DecoratorFlag::OnlyCaller(..) => {}
                                 }
-                                DecoratorFlag::Value(v) => value = U256::from(v),
                             }
                         }
-                    }
 
-                    // Call the deployed test
-                    let res = self.call(name, Address::zero(), address, value, data)?;
-                    Ok(res)
+                        match fuzzer {
+                            Some(fuzzer) => self.fuzz_test(
+                                name,
+                                address,
+                                value,
+                                &fuzzer,
+                                &fuzz,
+                                instructions.as_deref(),
+                            ),
+                            None => {
+                                self.test_call(name, address, value, data, instructions.as_deref())
+                            }
+                        }
+                    }
+                    Err(e) => Err(CompilerError::CodegenError(e).into()),
                 }
-                Err(e) => Err(CompilerError::CodegenError(e).into()),
-            },
+            }
             Err(e) => Err(CompilerError::CodegenError(e).into()),
         }
     }
 
+    /// Call a deployed test, tracing its execution if collecting coverage
+    fn test_call(
+        &mut self,
+        name: String,
+        address: Address,
+        value: U256,
+        data: String,
+        instructions: Option<&[(usize, Instruction)]>,
+    ) -> Result<TestResult, RunnerError> {
+        let mut inspector = CheatsInspector {
+            traced: instructions
+                .is_some()
+                .then(|| revm::primitives::B160::from_slice(address.as_bytes())),
+            ..Default::default()
+        };
+        let res = self.inspect_call(name, Address::zero(), address, value, data, &mut inspector)?;
+        if let (Some(coverage), Some(instructions)) = (&mut self.coverage, instructions) {
+            coverage.record(instructions, &inspector.executed);
+        }
+        Ok(res)
+    }
+
+    /// Call a deployed test with calldata of random arguments, shrinking the arguments of the
+    /// first failing call.
+    ///
+    /// Each call starts from the state before the test, and the gas used is the mean gas of
+    /// the calls.
+    fn fuzz_test(
+        &mut self,
+        name: String,
+        address: Address,
+        value: U256,
+        fuzzer: &Fuzzer,
+        config: &FuzzConfig,
+        instructions: Option<&[(usize, Instruction)]>,
+    ) -> Result<TestResult, RunnerError> {
+        let seed = config.seed.unwrap_or_else(rand::random);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let snapshot = self.database.clone();
+        let mut gas = 0;
+
+        for run in 1..=config.runs {
+            let args = fuzzer.generate(&mut rng);
+            let res =
+                self.test_call(name.clone(), address, value, fuzzer.calldata(&args), instructions);
+            self.database = snapshot.clone();
+            let res = res?;
+            gas += res.gas;
+
+            if let TestStatus::Revert = res.status {
+                let args = fuzzer.shrink(args, |args| {
+                    let res = self.call(
+                        name.clone(),
+                        Address::zero(),
+                        address,
+                        value,
+                        fuzzer.calldata(args),
+                    );
+                    self.database = snapshot.clone();
+                    Ok(matches!(res?.status, TestStatus::Revert))
+                })?;
+
+                // Report the result of the shrunk call
+                let res = self.call(name, Address::zero(), address, value, fuzzer.calldata(&args));
+                self.database = snapshot;
+                let counterexample = Some(fuzzer.format_call(&args));
+                return Ok(TestResult {
+                    fuzz: Some(FuzzResult { runs: run, seed, counterexample }),
+                    ..res?
+                });
+            }
+        }
+
+        Ok(TestResult {
+            name,
+            return_data: None,
+            gas: gas / config.runs.max(1),
+            status: TestStatus::Success,
+            logs: vec![],
+            fuzz: Some(FuzzResult { runs: config.runs, seed, counterexample: None }),
+        })
+    }
+
     /// Build an EVM transaction environment.
     fn build_env(&self, caller: Address, to: TransactTo, data: Bytes, value: U256) -> Env {
         let revm_address = revm::primitives::B160::from_slice(caller.as_bytes());
@@ -0,0 +1,150 @@
+use crate::types::{TestResult, TestStatus};
+use huff_utils::prelude::Contract;
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+/// The default path of the gas snapshot file
+pub const DEFAULT_GAS_SNAPSHOT_PATH: &str = ".gas-snapshot";
+
+/// The gas used by a test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TestGas {
+    /// The gas used, or the mean gas used by the calls of a fuzzed test
+    pub gas: u64,
+    /// The number of calls of a fuzzed test
+    pub runs: Option<u64>,
+}
+
+impl fmt::Display for TestGas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.runs {
+            Some(runs) => write!(f, "(runs: {runs}, μ: {})", self.gas),
+            None => write!(f, "(gas: {})", self.gas),
+        }
+    }
+}
+
+/// The gas used by each test, in the format of `forge snapshot`
+///
+/// Tests are identified by the path of the file defining them and their name, i.e.
+/// `src/Math.huff:TEST_ADD (gas: 21345)`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GasSnapshot {
+    /// The gas used by each test, by test identifier
+    pub tests: BTreeMap<String, TestGas>,
+}
+
+impl GasSnapshot {
+    /// Record the gas used by the passing tests of a contract.
+    pub fn record(&mut self, contract: &Contract, results: &[TestResult]) {
+        for result in results.iter().filter(|r| matches!(r.status, TestStatus::Success)) {
+            let path = contract
+                .macros
+                .iter()
+                .find(|m| m.test && m.name == result.name)
+                .and_then(|m| m.span.0.iter().find_map(|s| s.file.as_ref()))
+                .map(|f| f.path.clone())
+                .unwrap_or_default();
+            self.tests.insert(
+                format!("{path}:{}", result.name),
+                TestGas { gas: result.gas, runs: result.fuzz.as_ref().map(|f| f.runs) },
+            );
+        }
+    }
+
+    /// The changes of the gas used by the tests from this snapshot to another.
+    pub fn diff(&self, other: &GasSnapshot) -> Vec<GasDiff> {
+        let mut names = self.tests.keys().chain(other.tests.keys()).collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let (old, new) = (self.tests.get(name), other.tests.get(name));
+                (old.map(|t| t.gas) != new.map(|t| t.gas)).then(|| GasDiff {
+                    test: name.clone(),
+                    old: old.map(|t| t.gas),
+                    new: new.map(|t| t.gas),
+                })
+            })
+            .collect()
+    }
+
+    /// The total gas used by the tests
+    pub fn total(&self) -> u64 {
+        self.tests.values().map(|t| t.gas).sum()
+    }
+}
+
+impl fmt::Display for GasSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, gas) in &self.tests {
+            writeln!(f, "{name} {gas}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GasSnapshot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut snapshot = GasSnapshot::default();
+        for (i, line) in s.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let invalid = || format!("Invalid gas snapshot line {}: \"{line}\"", i + 1);
+            let (name, gas) = line.trim().rsplit_once(" (").ok_or_else(invalid)?;
+            let gas = gas.strip_suffix(')').ok_or_else(invalid)?;
+
+            let mut test = TestGas { gas: 0, runs: None };
+            for field in gas.split(", ") {
+                let (key, value) = field.split_once(": ").ok_or_else(invalid)?;
+                let value = value.parse::<u64>().map_err(|_| invalid())?;
+                match key {
+                    "gas" | "μ" => test.gas = value,
+                    "runs" => test.runs = Some(value),
+                    // Ignore fields of other tools, i.e. the median gas of `forge snapshot`
+                    _ => {}
+                }
+            }
+            snapshot.tests.insert(name.to_string(), test);
+        }
+        Ok(snapshot)
+    }
+}
+
+/// A change of the gas used by a test between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasDiff {
+    /// The test identifier
+    pub test: String,
+    /// The previous gas used, if the test existed
+    pub old: Option<u64>,
+    /// The new gas used, if the test still exists
+    pub new: Option<u64>,
+}
+
+impl GasDiff {
+    /// The change of gas used, or zero if the test was added or removed
+    pub fn delta(&self) -> i128 {
+        match (self.old, self.new) {
+            (Some(old), Some(new)) => new as i128 - old as i128,
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Display for GasDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.old, self.new) {
+            (None, Some(new)) => write!(f, "{} (gas: {new}, added)", self.test),
+            (Some(old), None) => write!(f, "{} (gas: {old}, removed)", self.test),
+            (Some(old), Some(_)) => write!(
+                f,
+                "{} (gas: {:+} ({:+.3}%))",
+                self.test,
+                self.delta(),
+                self.delta() as f64 * 100.0 / old.max(1) as f64
+            ),
+            (None, None) => write!(f, "{}", self.test),
+        }
+    }
+}
@@ -10,6 +10,19 @@ pub struct TestResult {
     pub gas: u64,
     pub status: TestStatus,
     pub logs: Vec<(u32, String)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fuzz: Option<FuzzResult>,
+}
+
+/// The result of a fuzzed test
+#[derive(Debug, Clone, Serialize)]
+pub struct FuzzResult {
+    /// The number of calls made before the test failed, or of all calls if it passed
+    pub runs: u64,
+    /// The seed of the random arguments
+    pub seed: u64,
+    /// The shrunk call the test failed with, i.e. `add(1, 0)`
+    pub counterexample: Option<String>,
 }
 
 /// A test status variant
@@ -0,0 +1,162 @@
+use ethers_core::{
+    abi::{param_type::Reader, ParamType},
+    utils::id,
+};
+use huff_core::Compiler;
+use huff_tests::{cheats::VM_CHEATS_MAP, prelude::TestStatus, HuffTester};
+use std::{collections::HashMap, rc::Rc, sync::Arc};
+
+const SOURCE: &str = r#"
+#define constant VM = 0x7109709ECfa91a80626fF3989D68f67F5b1DD12D
+
+// Calls a cheatcode with the calldata in memory, copying its return data to memory
+#define macro VM_CALL(size) = takes (0) returns (0) {
+    0x20 0x00 <size> 0x00 0x00 [VM] gas call pop
+}
+
+#define test TEST_PRANK() = {
+    __FUNC_SIG("startPrank(address)") 0xe0 shl 0x00 mstore
+    0xbeef 0x04 mstore
+    VM_CALL(0x24)
+    caller 0xbeef eq pranked jumpi
+    0x00 dup1 revert
+
+    pranked:
+        __FUNC_SIG("stopPrank()") 0xe0 shl 0x00 mstore
+        VM_CALL(0x04)
+        caller iszero stopped jumpi
+        0x00 dup1 revert
+
+    stopped:
+}
+
+#define test TEST_DEAL() = {
+    __FUNC_SIG("deal(address,uint256)") 0xe0 shl 0x00 mstore
+    0xbeef 0x04 mstore
+    0x1234 0x24 mstore
+    VM_CALL(0x44)
+    0xbeef balance 0x1234 eq success jumpi
+    0x00 dup1 revert
+    success:
+}
+
+#define test TEST_BLOCK() = {
+    __FUNC_SIG("warp(uint256)") 0xe0 shl 0x00 mstore
+    0x64 0x04 mstore
+    VM_CALL(0x24)
+    __FUNC_SIG("roll(uint256)") 0xe0 shl 0x00 mstore
+    0x0a 0x04 mstore
+    VM_CALL(0x24)
+    __FUNC_SIG("chainId(uint256)") 0xe0 shl 0x00 mstore
+    0x05 0x04 mstore
+    VM_CALL(0x24)
+    __FUNC_SIG("coinbase(address)") 0xe0 shl 0x00 mstore
+    0xc0ffee 0x04 mstore
+    VM_CALL(0x24)
+
+    timestamp 0x64 eq
+    number 0x0a eq and
+    chainid 0x05 eq and
+    coinbase 0xc0ffee eq and
+    success jumpi
+    0x00 dup1 revert
+    success:
+}
+
+#define test TEST_STORE_LOAD() = {
+    __FUNC_SIG("store(address,bytes32,bytes32)") 0xe0 shl 0x00 mstore
+    address 0x04 mstore
+    0x01 0x24 mstore
+    0x2a 0x44 mstore
+    VM_CALL(0x64)
+    0x01 sload 0x2a eq stored jumpi
+    0x00 dup1 revert
+
+    stored:
+        __FUNC_SIG("load(address,bytes32)") 0xe0 shl 0x00 mstore
+        address 0x04 mstore
+        0x01 0x24 mstore
+        VM_CALL(0x44)
+        0x00 mload 0x2a eq loaded jumpi
+        0x00 dup1 revert
+
+    loaded:
+}
+
+#define test TEST_EXPECT_REVERT() = {
+    __FUNC_SIG("expectRevert()") 0xe0 shl 0x00 mstore
+    VM_CALL(0x04)
+    0x00 dup1 revert
+}
+
+#define test TEST_EXPECT_REVERT_SELECTOR() = {
+    __FUNC_SIG("expectRevert(bytes4)") 0xe0 shl 0x00 mstore
+    0xdeadbeef 0xe0 shl 0x04 mstore
+    VM_CALL(0x24)
+    0xdeadbeef 0xe0 shl 0x00 mstore
+    0x24 0x00 revert
+}
+
+#define test FAIL_EXPECT_REVERT() = {
+    __FUNC_SIG("expectRevert()") 0xe0 shl 0x00 mstore
+    VM_CALL(0x04)
+    stop
+}
+
+#define test FAIL_EXPECT_REVERT_DATA() = {
+    __FUNC_SIG("expectRevert(bytes)") 0xe0 shl 0x00 mstore
+    0x20 0x04 mstore
+    0x01 0x24 mstore
+    0x01 0xf8 shl 0x44 mstore
+    VM_CALL(0x64)
+    0x00 dup1 revert
+}
+
+#define test TEST_UNKNOWN_CHEATCODE() = {
+    0x12345678 0xe0 shl 0x00 mstore
+    0x00 0x00 0x04 0x00 0x00 [VM] gas call
+    iszero success jumpi
+    0x00 dup1 revert
+    success:
+}
+"#;
+
+#[test]
+fn test_cheatcode_selectors() {
+    for (selector, cheat) in VM_CHEATS_MAP.entries() {
+        let signature = cheat.signature();
+        assert_eq!(*selector, u32::from_be_bytes(id(signature)), "{signature}");
+
+        let inputs = match Reader::read(&signature[signature.find('(').unwrap()..]) {
+            Ok(ParamType::Tuple(inputs)) => inputs,
+            _ => vec![],
+        };
+        assert_eq!(inputs, cheat.inputs(), "{signature}");
+    }
+}
+
+#[test]
+fn test_cheatcodes() {
+    let compiler = Compiler::new_in_memory(
+        Arc::new(vec!["main.huff".to_string()]),
+        HashMap::from([("main.huff".to_string(), SOURCE.to_string())]),
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+    let contract = compiler.grab_contracts().unwrap().remove(0);
+    let results = HuffTester::new(&contract, Rc::new(None)).execute().unwrap();
+
+    for result in results {
+        match result.name.starts_with("FAIL_") {
+            true => {
+                assert!(matches!(result.status, TestStatus::Revert), "{}", result.name);
+                // Failed expectations revert with an `Error(string)`
+                assert!(result.return_data.unwrap().starts_with("08c379a0"));
+            }
+            false => assert!(matches!(result.status, TestStatus::Success), "{}", result.name),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use huff_core::Compiler;
+use huff_tests::{prelude::CoverageReport, HuffTester};
+use huff_utils::prelude::*;
+use std::{collections::HashMap, rc::Rc, sync::Arc};
+
+const SOURCE: &str = r#"
+#define macro ADD() = takes(2) returns(1) {
+    add
+}
+
+#define macro UNUSED() = takes(0) returns(0) {
+    0x00 dup1 revert
+}
+
+#define test TEST_ADD() = {
+    0x01 0x02 ADD()
+    0x03 eq success jumpi
+    0x00 dup1 revert
+    success:
+}
+"#;
+
+fn contract() -> Contract {
+    let compiler = Compiler::new_in_memory(
+        Arc::new(vec!["main.huff".to_string()]),
+        HashMap::from([("main.huff".to_string(), SOURCE.to_string())]),
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+    compiler.grab_contracts().unwrap().remove(0)
+}
+
+#[test]
+fn test_registers_lines_of_macros() {
+    let report = CoverageReport::new(&contract());
+
+    // Test macros aren't covered
+    assert_eq!(
+        report.macros.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(),
+        ["ADD", "UNUSED"]
+    );
+    assert_eq!(report.hits["main.huff"], [(3, 0), (7, 0)].into_iter().collect());
+    assert_eq!(report.file_lines("main.huff"), (0, 2));
+}
+
+#[test]
+fn test_records_executed_lines() {
+    let contract = contract();
+    let tester = HuffTester::new(&contract, Rc::new(None));
+    let (results, report) = tester.execute_with_coverage().unwrap();
+    assert_eq!(results.len(), 1);
+
+    assert_eq!(report.line_hits("main.huff", 3), 1);
+    assert_eq!(report.line_hits("main.huff", 7), 0);
+    assert_eq!(report.macro_lines(&report.macros[0]), (1, 1));
+    assert_eq!(report.macro_lines(&report.macros[1]), (0, 1));
+    assert_eq!(
+        report.to_lcov(),
+        "TN:\nSF:main.huff\nFN:2,ADD\nFN:6,UNUSED\nFNDA:1,ADD\nFNDA:0,UNUSED\nFNF:2\nFNH:1\n\
+         DA:3,1\nDA:7,0\nLF:2\nLH:1\nend_of_record\n"
+    );
+}
+
+#[test]
+fn test_merges_reports() {
+    let mut report = CoverageReport::new(&contract());
+    let mut other = report.clone();
+    other.hits.get_mut("main.huff").unwrap().insert(3, 2);
+    report.merge(other.clone());
+    report.merge(other);
+    assert_eq!(report.line_hits("main.huff", 3), 4);
+    assert_eq!(report.macros.len(), 2);
+}
@@ -0,0 +1,113 @@
+use ethers_core::{abi::Token, types::U256};
+use huff_core::Compiler;
+use huff_tests::{prelude::*, HuffTester};
+use huff_utils::prelude::Contract;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{collections::HashMap, rc::Rc, sync::Arc};
+
+const SOURCE: &str = r#"
+// Fails for arguments over 100
+#[fuzz("check(uint256)"), runs(0x20), seed(0x01)]
+#define test FUZZ_FAILING() = {
+    0x04 calldataload 0x64 lt fail jumpi
+    stop
+    fail:
+        0x00 dup1 revert
+}
+
+// Each call starts from an empty storage slot
+#[fuzz("check(uint8)"), runs(0x10)]
+#define test FUZZ_PASSING() = {
+    0x00 sload fail jumpi
+    0x01 0x00 sstore
+    0x04 calldataload 0xff lt fail jumpi
+    stop
+    fail:
+        0x00 dup1 revert
+}
+"#;
+
+fn contract() -> Contract {
+    let compiler = Compiler::new_in_memory(
+        Arc::new(vec!["main.huff".to_string()]),
+        HashMap::from([("main.huff".to_string(), SOURCE.to_string())]),
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+    compiler.grab_contracts().unwrap().remove(0)
+}
+
+#[test]
+fn test_fuzzer_encodes_calldata() {
+    let fuzzer = Fuzzer::new("add(uint256,uint256)").unwrap();
+    assert_eq!(
+        fuzzer.calldata(&[Token::Uint(U256::from(1)), Token::Uint(U256::from(2))]),
+        format!("771602f7{:0>64}{:0>64}", "1", "2")
+    );
+    assert_eq!(fuzzer.format_call(&[Token::Uint(1.into()), Token::Uint(2.into())]), "add(1, 2)");
+}
+
+#[test]
+fn test_fuzzer_generates_from_seed() {
+    let fuzzer = Fuzzer::new("f(int8,address,bytes,string[],(bool,bytes2))").unwrap();
+    let generate = |seed| fuzzer.generate(&mut StdRng::seed_from_u64(seed));
+    assert_eq!(generate(1), generate(1));
+    assert_eq!(generate(1).len(), 5);
+    assert!(Fuzzer::new("f()").unwrap().inputs.is_empty());
+}
+
+#[test]
+fn test_fuzzer_rejects_invalid_signatures() {
+    assert!(Fuzzer::new("f").is_err());
+    assert!(Fuzzer::new("f(uint257)").is_err());
+    assert!(Fuzzer::new("f(bytes33)").is_err());
+}
+
+#[test]
+fn test_fuzzer_shrinks_arguments() {
+    let fuzzer = Fuzzer::new("f(uint256,int256,bytes)").unwrap();
+    let args =
+        vec![Token::Uint(U256::MAX), Token::Int(U256::MAX - 99), Token::Bytes(vec![0xff; 10])];
+
+    // Fails for uints over 100 and ints under -10, whatever the bytes
+    let shrunk = fuzzer
+        .shrink(args, |args| {
+            let uint = args[0].clone().into_uint().unwrap();
+            let int = args[1].clone().into_int().unwrap();
+            Ok(uint > U256::from(100) && int.bit(255) && int < U256::MAX - 9)
+        })
+        .unwrap();
+    assert_eq!(fuzzer.format_call(&shrunk), "f(101, -11, 0x)");
+}
+
+#[test]
+fn test_runs_fuzzed_tests() {
+    let contract = contract();
+    let results = HuffTester::new(&contract, Rc::new(None)).execute().unwrap();
+
+    // The failing test reports its shrunk arguments and seed
+    let failing = results[0].fuzz.as_ref().unwrap();
+    assert!(matches!(results[0].status, TestStatus::Revert));
+    assert_eq!(failing.seed, 1);
+    assert_eq!(failing.counterexample.as_deref(), Some("check(101)"));
+
+    let passing = results[1].fuzz.as_ref().unwrap();
+    assert!(matches!(results[1].status, TestStatus::Success));
+    assert_eq!(passing.runs, 16);
+    assert_eq!(passing.counterexample, None);
+}
+
+#[test]
+fn test_fuzz_config_of_runner() {
+    let contract = contract();
+    let mut tester = HuffTester::new(&contract, Rc::new(Some("FUZZ_PASSING".to_string())));
+    tester.runner.fuzz = FuzzConfig { runs: 4, seed: Some(7) };
+    let results = tester.execute().unwrap();
+
+    // The runs decorator flag takes precedence over the runner's configuration
+    let fuzz = results[0].fuzz.as_ref().unwrap();
+    assert_eq!((fuzz.runs, fuzz.seed), (16, 7));
+}
@@ -0,0 +1,54 @@
+use ethers_core::types::U256;
+use huff_tests::prelude::{Repl, TestStatus};
+
+#[test]
+fn test_repl_runs_statements_entered_so_far() {
+    let mut repl = Repl::default();
+    let res = repl.eval("0x01 0x02").unwrap().unwrap();
+    assert_eq!(res.state.stack, vec![U256::from(1), U256::from(2)]);
+    assert!(res.state.memory.is_empty());
+    assert_eq!(res.gas, 6);
+
+    let res = repl.eval("add 0x00 mstore").unwrap().unwrap();
+    let mut word = vec![0u8; 32];
+    word[31] = 3;
+    assert!(res.state.stack.is_empty());
+    assert_eq!(res.state.memory, word);
+    assert_eq!(repl.statements, vec!["0x01 0x02", "add 0x00 mstore"]);
+}
+
+#[test]
+fn test_repl_definitions() {
+    let mut repl = Repl::default();
+    assert!(repl.eval("#define constant TWO = 0x02").unwrap().is_none());
+    assert!(repl
+        .eval("#define macro DOUBLE() = takes (1) returns (1) { dup1 add }")
+        .unwrap()
+        .is_none());
+
+    let res = repl.eval("[TWO] DOUBLE()").unwrap().unwrap();
+    assert_eq!(res.state.stack, vec![U256::from(4)]);
+
+    // Invalid definitions are discarded
+    assert!(repl.eval("#define macro BROKEN() = takes (0) returns (0) {").is_err());
+    assert_eq!(repl.definitions.len(), 2);
+}
+
+#[test]
+fn test_repl_discards_failing_entries() {
+    let mut repl = Repl::default();
+    repl.eval("0x01").unwrap();
+
+    assert!(repl.eval("MISSING()").is_err());
+    let res = repl.eval("0x00 0x00 revert").unwrap().unwrap();
+    assert!(matches!(res.status, TestStatus::Revert));
+    assert_eq!(repl.statements, vec!["0x01"]);
+
+    assert_eq!(repl.undo().as_deref(), Some("0x01"));
+    assert!(repl.current().unwrap().state.stack.is_empty());
+
+    repl.eval("#define constant ONE = 0x01").unwrap();
+    repl.eval("[ONE]").unwrap();
+    repl.reset();
+    assert!(repl.definitions.is_empty() && repl.statements.is_empty());
+}
@@ -0,0 +1,90 @@
+use huff_core::Compiler;
+use huff_tests::{prelude::GasSnapshot, HuffTester};
+use huff_utils::prelude::*;
+use std::{collections::HashMap, rc::Rc, sync::Arc};
+
+const SOURCE: &str = r#"
+#define macro ADD() = takes(2) returns(1) {
+    add
+}
+
+#define test TEST_ADD() = {
+    0x01 0x02 ADD()
+    0x03 eq success jumpi
+    0x00 dup1 revert
+    success:
+}
+
+#[fuzz("f(uint256)"), runs(0x10)]
+#define test TEST_FUZZ() = {
+    0x04 calldataload pop
+}
+
+#define test FAIL() = {
+    0x00 dup1 revert
+}
+"#;
+
+fn contract(source: &str) -> Contract {
+    let compiler = Compiler::new_in_memory(
+        Arc::new(vec!["main.huff".to_string()]),
+        HashMap::from([("main.huff".to_string(), source.to_string())]),
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+    compiler.grab_contracts().unwrap().remove(0)
+}
+
+fn snapshot(source: &str) -> GasSnapshot {
+    let contract = contract(source);
+    let results = HuffTester::new(&contract, Rc::new(None)).execute().unwrap();
+    let mut snapshot = GasSnapshot::default();
+    snapshot.record(&contract, &results);
+    snapshot
+}
+
+#[test]
+fn test_gas_snapshot_records_passing_tests() {
+    let snapshot = snapshot(SOURCE);
+    assert_eq!(
+        snapshot.tests.keys().collect::<Vec<_>>(),
+        vec!["main.huff:TEST_ADD", "main.huff:TEST_FUZZ"]
+    );
+    assert_eq!(snapshot.tests["main.huff:TEST_ADD"].runs, None);
+    assert_eq!(snapshot.tests["main.huff:TEST_FUZZ"].runs, Some(16));
+
+    let text = snapshot.to_string();
+    assert!(text.starts_with("main.huff:TEST_ADD (gas: "));
+    assert!(text.contains("main.huff:TEST_FUZZ (runs: 16, μ: "));
+    assert_eq!(text.parse::<GasSnapshot>().unwrap(), snapshot);
+}
+
+#[test]
+fn test_gas_snapshot_parses_forge_snapshots() {
+    let snapshot = "Counter:testIncrement() (gas: 28334)\nCounter:testFuzz(uint256) (runs: 256, μ: 27564, ~: 28343)\n"
+        .parse::<GasSnapshot>()
+        .unwrap();
+    assert_eq!(snapshot.tests["Counter:testIncrement()"].gas, 28334);
+    assert_eq!(snapshot.tests["Counter:testFuzz(uint256)"].gas, 27564);
+    assert_eq!(snapshot.tests["Counter:testFuzz(uint256)"].runs, Some(256));
+
+    assert!("TEST_ADD 21000".parse::<GasSnapshot>().is_err());
+    assert!("TEST_ADD (gas: lots)".parse::<GasSnapshot>().is_err());
+}
+
+#[test]
+fn test_gas_snapshot_diff() {
+    let old = snapshot(SOURCE);
+    assert!(old.diff(&old).is_empty());
+
+    // An extra push and pop costs 5 gas
+    let new = snapshot(&SOURCE.replace("0x01 0x02 ADD()", "0x01 0x02 0x00 pop ADD()"));
+    let diffs = old.diff(&new);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].test, "main.huff:TEST_ADD");
+    assert_eq!(diffs[0].delta(), 5);
+    assert!(diffs[0].to_string().starts_with("main.huff:TEST_ADD (gas: +5 ("));
+}
@@ -18,6 +18,7 @@
 //!     macros: vec![],
 //!     invocations: vec![],
 //!     imports: vec![],
+//!     abi_imports: vec![],
 //!     constants: Arc::new(Mutex::new(vec![])),
 //!     errors: vec![],
 //!     functions: vec![huff_utils::ast::Function {
@@ -29,17 +30,29 @@
 //!         span: AstSpan(vec![]),
 //!     }],
 //!     events: vec![],
+//!     structs: vec![],
+//!     merkle_trees: vec![],
 //!     tables: vec![],
+//!     contracts: vec![],
+//!     memory_layout: vec![],
+//!     storage_layout: vec![],
+//!     pragmas: vec![],
+//!     suppressions: vec![],
 //! };
 //!
 //! // Create an ABI using that generate contract
 //! let abi: Abi = contract.into();
 //! ```
 
+use ethers_core::abi::ParamType;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::{collections::BTreeMap, fmt};
 
-use crate::ast::{self, FunctionType};
+use crate::{
+    ast::{self, FunctionType},
+    bytes_util::hash_bytes,
+};
 
 /// #### Abi
 ///
@@ -65,6 +78,94 @@ impl Abi {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Maps the signature of every function to its hex encoded selector, skipping a function
+    /// overriding the constructor
+    pub fn method_identifiers(&self) -> BTreeMap<String, String> {
+        self.functions
+            .values()
+            .filter(|f| f.name.to_lowercase() != "constructor")
+            .map(|f| {
+                let selector = f.selector().iter().map(|b| format!("{b:02x}")).collect();
+                (f.signature(), selector)
+            })
+            .collect()
+    }
+
+    /// The ABI in the standard JSON ABI format read by solc, Hardhat, Foundry and ethers, i.e.
+    /// `[{"type": "function", "name": "transfer", "inputs": [..], ..}, ..]`
+    pub fn to_json_abi(&self) -> Value {
+        let mut entries = vec![];
+        if let Some(constructor) = &self.constructor {
+            entries.push(json!({
+                "type": "constructor",
+                "inputs": constructor.inputs.iter().map(FunctionParam::to_json_abi).collect::<Vec<_>>(),
+                "stateMutability": "nonpayable",
+            }));
+        }
+        entries.extend(self.functions.values().map(|function| {
+            json!({
+                "type": "function",
+                "name": function.name,
+                "inputs": function.inputs.iter().map(FunctionParam::to_json_abi).collect::<Vec<_>>(),
+                "outputs": function.outputs.iter().map(FunctionParam::to_json_abi).collect::<Vec<_>>(),
+                "stateMutability": function.state_mutability.json_abi_mutability(),
+            })
+        }));
+        entries.extend(self.events.values().map(|event| {
+            let inputs = event
+                .inputs
+                .iter()
+                .map(|param| {
+                    let mut input = param_to_json_abi(&param.name, &param.kind, None);
+                    input["indexed"] = json!(param.indexed);
+                    input
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "type": "event",
+                "name": event.name,
+                "inputs": inputs,
+                "anonymous": event.anonymous,
+            })
+        }));
+        entries.extend(self.errors.values().map(|error| {
+            json!({
+                "type": "error",
+                "name": error.name,
+                "inputs": error.inputs.iter().map(FunctionParam::to_json_abi).collect::<Vec<_>>(),
+            })
+        }));
+        if self.receive {
+            entries.push(json!({ "type": "receive", "stateMutability": "payable" }));
+        }
+        if self.fallback {
+            entries.push(json!({ "type": "fallback", "stateMutability": "payable" }));
+        }
+        Value::Array(entries)
+    }
+}
+
+/// A parameter of the standard JSON ABI, with the components of tuples
+fn param_to_json_abi(name: &str, kind: &FunctionParamType, internal_type: Option<&str>) -> Value {
+    let inner = match kind {
+        FunctionParamType::Array(inner, _) => inner.as_ref(),
+        _ => kind,
+    };
+    let kind = kind.to_string();
+    let mut param = match inner {
+        // Tuples are named `tuple` and list their fields as components, i.e. `tuple[]`
+        FunctionParamType::Tuple(fields) => json!({
+            "name": name,
+            "type": format!("tuple{}", kind.rsplit_once(')').map(|(_, s)| s).unwrap_or_default()),
+            "components": fields.iter().map(|f| param_to_json_abi("", f, None)).collect::<Vec<_>>(),
+        }),
+        _ => json!({ "name": name, "type": kind }),
+    };
+    if let Some(internal_type) = internal_type {
+        param["internalType"] = json!(internal_type);
+    }
+    param
 }
 
 // Allows for simple ABI Generation by directly translating the AST
@@ -213,6 +314,22 @@ pub struct Function {
     pub state_mutability: FunctionType,
 }
 
+impl Function {
+    /// The canonical signature of the function, i.e. `transfer(address,uint256)`
+    pub fn signature(&self) -> String {
+        let inputs =
+            self.inputs.iter().map(|i| i.kind.to_string().replace(' ', "")).collect::<Vec<_>>();
+        format!("{}({})", self.name, inputs.join(","))
+    }
+
+    /// The 4 byte selector of the function
+    pub fn selector(&self) -> [u8; 4] {
+        let mut selector = [0u8; 4];
+        hash_bytes(&mut selector, &self.signature());
+        selector
+    }
+}
+
 /// #### Event
 ///
 /// An Event definition.
@@ -297,6 +414,13 @@ pub enum FunctionParamType {
     Tuple(Vec<FunctionParamType>),
 }
 
+impl FunctionParam {
+    /// The parameter in the standard JSON ABI format
+    pub fn to_json_abi(&self) -> Value {
+        param_to_json_abi(&self.name, &self.kind, self.internal_type.as_deref())
+    }
+}
+
 impl FunctionParamType {
     /// Checks if the param type should be designated as "memory" for solidity interface
     /// generation.
@@ -311,6 +435,34 @@ impl FunctionParamType {
     }
 }
 
+impl FunctionParamType {
+    /// Whether the type is dynamically sized, encoded in the tail of an ABI encoding with its
+    /// offset in the head
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            FunctionParamType::Bytes | FunctionParamType::String => true,
+            FunctionParamType::Array(inner, sizes) => sizes.contains(&0) || inner.is_dynamic(),
+            FunctionParamType::Tuple(inner) => inner.iter().any(|t| t.is_dynamic()),
+            _ => false,
+        }
+    }
+
+    /// The number of bytes the type takes in the head of an ABI encoding, the 32 bytes of its
+    /// offset if it's dynamic
+    pub fn head_size(&self) -> usize {
+        if self.is_dynamic() {
+            return 32
+        }
+        match self {
+            FunctionParamType::Array(inner, sizes) => {
+                sizes.iter().product::<usize>() * inner.head_size()
+            }
+            FunctionParamType::Tuple(inner) => inner.iter().map(|t| t.head_size()).sum(),
+            _ => 32,
+        }
+    }
+}
+
 impl fmt::Debug for FunctionParamType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.display(f)
@@ -412,6 +564,30 @@ impl FunctionParamType {
     }
 }
 
+impl From<&FunctionParamType> for ParamType {
+    fn from(kind: &FunctionParamType) -> Self {
+        match kind {
+            FunctionParamType::Address => ParamType::Address,
+            FunctionParamType::Bytes => ParamType::Bytes,
+            FunctionParamType::Int(size) => ParamType::Int(*size),
+            FunctionParamType::Uint(size) => ParamType::Uint(*size),
+            FunctionParamType::Bool => ParamType::Bool,
+            FunctionParamType::String => ParamType::String,
+            // Sizes are in source order, so the last one is the outermost array
+            FunctionParamType::Array(inner, sizes) => {
+                sizes.iter().fold(ParamType::from(inner.as_ref()), |kind, size| match size {
+                    0 => ParamType::Array(Box::new(kind)),
+                    size => ParamType::FixedArray(Box::new(kind), *size),
+                })
+            }
+            FunctionParamType::FixedBytes(size) => ParamType::FixedBytes(*size),
+            FunctionParamType::Tuple(inner) => {
+                ParamType::Tuple(inner.iter().map(ParamType::from).collect())
+            }
+        }
+    }
+}
+
 impl From<&str> for FunctionParamType {
     fn from(string: &str) -> Self {
         FunctionParamType::convert_string_to_type(string).unwrap()
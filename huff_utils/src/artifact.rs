@@ -2,11 +2,23 @@
 //!
 //! The artifacts generated from codegen.
 
+use ethers_core::{
+    abi::{encode, Token},
+    utils::{hex, keccak256},
+};
 use serde::{Deserialize, Serialize};
-use std::{fs, path::Path, sync::Arc};
+use serde_json::Value;
+use std::{collections::BTreeMap, fs, path::Path, sync::Arc};
 
 pub use crate::abi::Abi;
-use crate::prelude::FileSource;
+use crate::{
+    bytes_util::hash_bytes,
+    prelude::{FileSource, MacroActivation, MemorySlot, StorageSlot},
+};
+
+/// The function registering a contract in an on-chain metadata registry, taking its content hash,
+/// ABI hash and name
+pub const REGISTRY_SIGNATURE: &str = "register(bytes32,bytes32,string)";
 
 /// A Codegen Artifact
 #[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -19,9 +31,162 @@ pub struct Artifact {
     pub runtime: String,
     /// The abi
     pub abi: Option<Abi>,
+    /// Function signatures mapped to their hex encoded selectors
+    #[serde(default, rename = "methodIdentifiers")]
+    pub method_identifiers: BTreeMap<String, String>,
+    /// The types of the constructor's inputs
+    #[serde(default, rename = "constructorInputs")]
+    pub constructor_inputs: Vec<String>,
+    /// Whether the contract has no constructor macro, deploying its runtime bytecode with only
+    /// the bootstrap code
+    #[serde(default, skip_serializing_if = "std::ops::Not::not", rename = "defaultConstructor")]
+    pub default_constructor: bool,
+    /// The name of the contract, for contracts defined with `#define contract` blocks
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "contractName")]
+    pub contract_name: Option<String>,
+    /// The label of the contract, set when compiling
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The hex encoded keccak256 hash of the canonical JSON ABI, with sorted keys and no
+    /// whitespace
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "abiHash")]
+    pub abi_hash: Option<String>,
+    /// The hex encoded keccak256 hash of the ABI hash followed by the hash of the runtime
+    /// bytecode, identifying the contract's interface and code
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "contentHash")]
+    pub content_hash: Option<String>,
+    /// The static memory regions reserved with `FREE_MEMORY_POINTER`
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "memoryLayout")]
+    pub memory_layout: Vec<MemorySlot>,
+    /// The storage slots assigned to `FREE_STORAGE_POINTER` constants
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "storageLayout")]
+    pub storage_layout: Vec<StorageSlot>,
+    /// The runtime bytecode generated by each macro invocation and the labels it defines, for
+    /// debuggers to name program counters
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symbols: Vec<MacroActivation>,
+    /// The compiler and settings the artifact was compiled with, and the hashes of its sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ArtifactMetadata>,
+}
+
+/// The compiler version and settings an artifact was compiled with, along with the hashes of the
+/// sources it was compiled from, to reproduce the build
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ArtifactMetadata {
+    /// The version of huff-rs
+    pub version: String,
+    /// The settings affecting the generated bytecode
+    pub settings: CompilerSettings,
+    /// The hex encoded keccak256 hash of each source file, including imported files, by path
+    pub sources: BTreeMap<String, String>,
+}
+
+impl ArtifactMetadata {
+    /// The metadata of an artifact compiled from a file and its dependencies
+    pub fn new(version: &str, settings: CompilerSettings, file: &FileSource) -> Self {
+        fn hash_sources(file: &FileSource, sources: &mut BTreeMap<String, String>) {
+            let source = file.source.as_deref().unwrap_or_default();
+            sources.insert(file.path.clone(), format!("0x{}", hex::encode(keccak256(source))));
+            for dependency in file.dependencies.iter().flatten() {
+                hash_sources(dependency, sources);
+            }
+        }
+
+        let mut sources = BTreeMap::new();
+        hash_sources(file, &mut sources);
+        Self { version: version.to_string(), settings, sources }
+    }
+}
+
+/// The compiler settings affecting the generated bytecode
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct CompilerSettings {
+    /// The targeted EVM version
+    #[serde(rename = "evmVersion")]
+    pub evm_version: String,
+    /// The language edition of files not declaring their own
+    #[serde(default)]
+    pub edition: String,
+    /// Whether compilation was optimized
+    pub optimize: bool,
+    /// Whether EOF containers were output rather than legacy bytecode
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub eof: bool,
+    /// Whether a revert was appended to the main macro's selector dispatch falling through
+    #[serde(default, skip_serializing_if = "std::ops::Not::not", rename = "autoRevert")]
+    pub auto_revert: bool,
+    /// The macro compiled as the runtime bytecode
+    #[serde(rename = "mainMacro")]
+    pub main_macro: String,
+    /// The macro compiled as the constructor
+    #[serde(rename = "constructorMacro")]
+    pub constructor_macro: String,
+    /// The hex encoded values of overridden constants, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty", rename = "constantOverrides")]
+    pub constant_overrides: BTreeMap<String, String>,
+    /// The maximum number of macros that may be expanded within one another
+    #[serde(rename = "macroNestingLimit")]
+    pub macro_nesting_limit: usize,
 }
 
 impl Artifact {
+    /// The artifact's identifier, its source path followed by the contract name for contracts
+    /// defined with `#define contract` blocks, i.e. `src/Factory.huff:Child`
+    pub fn identifier(&self) -> String {
+        match &self.contract_name {
+            Some(name) => format!("{}:{name}", self.file.path),
+            None => self.file.path.clone(),
+        }
+    }
+
+    /// The name of the contract, i.e. `Child` for a `#define contract Child` block, otherwise the
+    /// stem of its file, i.e. `ERC20` for `./src/ERC20.huff`
+    pub fn default_name(&self) -> String {
+        self.contract_name.clone().unwrap_or_else(|| {
+            Path::new(&self.file.path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+    }
+
+    /// The label of the contract, its name if set, otherwise its [default name](Self::default_name)
+    pub fn label(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.default_name())
+    }
+
+    /// Sets the abi, along with the method identifiers, constructor inputs and content hash
+    /// derived from it
+    pub fn set_abi(&mut self, abi: Abi) {
+        self.method_identifiers = abi.method_identifiers();
+        self.constructor_inputs = abi
+            .constructor
+            .iter()
+            .flat_map(|c| c.inputs.iter().map(|i| i.kind.to_string()))
+            .collect();
+
+        let abi_hash = keccak256(canonical_json(&abi.to_json_abi()));
+        let runtime_hash = keccak256(hex::decode(&self.runtime).unwrap_or_default());
+        let content_hash = keccak256([abi_hash, runtime_hash].concat());
+        self.abi_hash = Some(format!("0x{}", hex::encode(abi_hash)));
+        self.content_hash = Some(format!("0x{}", hex::encode(content_hash)));
+        self.abi = Some(abi);
+    }
+
+    /// The hex encoded calldata of a [REGISTRY_SIGNATURE] call registering the contract's content
+    /// hash, ABI hash and label, unless it has no ABI
+    pub fn registry_calldata(&self) -> Option<String> {
+        let word = |hash: &Option<String>| -> Option<Token> {
+            let bytes = hex::decode(hash.as_deref()?.trim_start_matches("0x")).ok()?;
+            Some(Token::FixedBytes(bytes))
+        };
+        let args = [word(&self.content_hash)?, word(&self.abi_hash)?, Token::String(self.label())];
+        let mut selector = [0u8; 4];
+        hash_bytes(&mut selector, &REGISTRY_SIGNATURE.to_string());
+        Some(format!("0x{}{}", hex::encode(selector), hex::encode(encode(&args))))
+    }
+
     /// Exports an artifact to a json file
     pub fn export(&self, out: &str) -> std::result::Result<(), std::io::Error> {
         let serialized_artifact = serde_json::to_string_pretty(self)?;
@@ -33,3 +198,22 @@ impl Artifact {
         fs::write(file_path, serialized_artifact)
     }
 }
+
+/// Serializes a json value without whitespace and with the keys of objects sorted
+fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<(&String, &Value)>>();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            let entries = entries
+                .into_iter()
+                .map(|(k, v)| format!("{}:{}", Value::String(k.clone()), canonical_json(v)))
+                .collect::<Vec<String>>();
+            format!("{{{}}}", entries.join(","))
+        }
+        Value::Array(items) => {
+            format!("[{}]", items.iter().map(canonical_json).collect::<Vec<String>>().join(","))
+        }
+        value => value.to_string(),
+    }
+}
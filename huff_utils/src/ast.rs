@@ -1,30 +1,40 @@
+use ethers_core::types::U256;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     bytecode::*,
     bytes_util::*,
-    error::CodegenError,
+    edition::Edition,
+    error::{CodegenError, CodegenErrorKind},
     evm::Opcode,
-    prelude::{MacroArg::Ident, Span, TokenKind},
+    evm_version::EVMVersion,
+    merkle::MerkleHash,
+    precompile::Precompile,
+    prelude::{MacroArg::Ident, PrimitiveEVMType, Span, TokenKind},
+    warning::CompilerWarning,
 };
 use std::{
     collections::BTreeMap,
     fmt::{Display, Formatter},
     path::PathBuf,
+    str::FromStr,
     sync::{Arc, Mutex},
 };
 
 /// A contained literal
 pub type Literal = [u8; 32];
 
+/// The constant holding the slot of the reentrancy lock of `#pragma nonreentrant` macros
+pub const REENTRANCY_LOCK: &str = "REENTRANCY_LOCK";
+
 /// A File Path
 ///
 /// Used for parsing the huff imports.
 pub type FilePath = PathBuf;
 
 /// An AST-level Span
-#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct AstSpan(pub Vec<Span>);
 
 impl AstSpan {
@@ -84,7 +94,7 @@ impl AstSpan {
 /// Thus, it is also the root of the AST.
 ///
 /// For examples of Huff contracts, see the [huff-examples repository](https://github.com/huff-language/huff-examples).
-#[derive(Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Contract {
     /// Macro definitions
     pub macros: Vec<MacroDefinition>,
@@ -92,6 +102,9 @@ pub struct Contract {
     pub invocations: Vec<MacroInvocation>,
     /// File Imports
     pub imports: Vec<FilePath>,
+    /// ABI JSON files imported with `#include abi "IERC20.json"`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub abi_imports: Vec<AbiImport>,
     /// Constants
     pub constants: Arc<Mutex<Vec<ConstantDefinition>>>,
     /// Custom Errors
@@ -100,25 +113,125 @@ pub struct Contract {
     pub functions: Vec<Function>,
     /// Events
     pub events: Vec<Event>,
+    /// Structs defined with `#define struct`, ABI-encoded and decoded by generated macros
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub structs: Vec<StructDefinition>,
+    /// Merkle trees defined with `#define merkle`, verified by generated macros
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merkle_trees: Vec<MerkleTreeDefinition>,
     /// Tables
     pub tables: Vec<TableDefinition>,
+    /// Contracts defined with `#define contract` blocks
+    pub contracts: Vec<ContractDefinition>,
+    /// Static memory regions reserved with `FREE_MEMORY_POINTER`, laid out when deriving the
+    /// storage pointers
+    pub memory_layout: Vec<MemorySlot>,
+    /// Storage slots of the `FREE_STORAGE_POINTER` constants, assigned when deriving the storage
+    /// pointers
+    pub storage_layout: Vec<StorageSlot>,
+    /// The requirements declared by the source, i.e. `#pragma huff-version "^0.3.0"`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pragmas: Vec<SourcePragma>,
+    /// The warnings silenced by `// huff-ignore: <warning>` comments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressions: Vec<Suppression>,
 }
 
 impl Contract {
     /// Returns the first macro that matches the provided name
-    pub fn find_macro_by_name(&self, name: &str) -> Option<MacroDefinition> {
+    pub fn find_macro_by_name(&self, name: &str) -> Option<&MacroDefinition> {
         if let Some(m) = self.macros.iter().find(|m| m.name == name) {
-            Some(m.clone())
+            Some(m)
         } else {
             tracing::warn!("Failed to find macro \"{}\" in contract", name);
             None
         }
     }
 
+    /// Returns the contract defined with a `#define contract` block of the provided name, merged
+    /// with the file's top level definitions
+    ///
+    /// Top level definitions are shared by every contract in the file, and are shadowed by the
+    /// contract's own definitions of the same name. The merged contract holds its own copy of the
+    /// constants, so its storage pointers are derived independently of the other contracts. Shared
+    /// constants come before the contract's own, so the storage layout of the top level is a
+    /// prefix of every contract's layout.
+    pub fn find_contract_by_name(&self, name: &str) -> Option<Contract> {
+        let def = self.contracts.iter().find(|c| c.name == name)?;
+        let own = &def.contract;
+
+        fn merge<T: Clone>(own: &[T], shared: &[T], name: impl Fn(&T) -> &str) -> Vec<T> {
+            let mut merged = own.to_vec();
+            merged
+                .extend(shared.iter().filter(|s| !own.iter().any(|o| name(o) == name(s))).cloned());
+            merged
+        }
+
+        let own_constants = own.constants.lock().unwrap();
+        let mut constants = self
+            .constants
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|s| !own_constants.iter().any(|o| o.name == s.name))
+            .cloned()
+            .collect::<Vec<ConstantDefinition>>();
+        constants.extend(own_constants.iter().cloned());
+        Some(Contract {
+            macros: merge(&own.macros, &self.macros, |m| &m.name),
+            invocations: [own.invocations.clone(), self.invocations.clone()].concat(),
+            imports: self.imports.clone(),
+            abi_imports: self.abi_imports.clone(),
+            constants: Arc::new(Mutex::new(constants)),
+            errors: merge(&own.errors, &self.errors, |e| &e.name),
+            functions: merge(&own.functions, &self.functions, |f| &f.name),
+            events: merge(&own.events, &self.events, |e| &e.name),
+            structs: merge(&own.structs, &self.structs, |s| &s.name),
+            merkle_trees: merge(&own.merkle_trees, &self.merkle_trees, |t| &t.name),
+            tables: merge(&own.tables, &self.tables, |t| &t.name),
+            contracts: vec![],
+            memory_layout: vec![],
+            storage_layout: vec![],
+            pragmas: self.pragmas.clone(),
+            suppressions: self.suppressions.clone(),
+        })
+    }
+
+    /// Returns the function of the provided name, declared by the contract or, for a name
+    /// qualified with the name of an ABI import, i.e. `IERC20.transfer`, by the imported ABI
+    pub fn find_function(&self, name: &str) -> Option<&Function> {
+        match name.split_once('.') {
+            Some((abi, name)) => {
+                self.find_abi_import(abi)?.functions.iter().find(|f| f.name == name)
+            }
+            None => self.functions.iter().find(|f| f.name == name),
+        }
+    }
+
+    /// Returns the event of the provided name, declared by the contract or an imported ABI
+    pub fn find_event(&self, name: &str) -> Option<&Event> {
+        match name.split_once('.') {
+            Some((abi, name)) => self.find_abi_import(abi)?.events.iter().find(|e| e.name == name),
+            None => self.events.iter().find(|e| e.name == name),
+        }
+    }
+
+    /// Returns the error of the provided name, declared by the contract or an imported ABI
+    pub fn find_error(&self, name: &str) -> Option<&ErrorDefinition> {
+        match name.split_once('.') {
+            Some((abi, name)) => self.find_abi_import(abi)?.errors.iter().find(|e| e.name == name),
+            None => self.errors.iter().find(|e| e.name == name),
+        }
+    }
+
+    fn find_abi_import(&self, name: &str) -> Option<&AbiImport> {
+        self.abi_imports.iter().find(|i| i.name == name)
+    }
+
     /// Returns the first table that matches the provided name
-    pub fn find_table_by_name(&self, name: &str) -> Option<TableDefinition> {
+    pub fn find_table_by_name(&self, name: &str) -> Option<&TableDefinition> {
         if let Some(t) = self.tables.iter().find(|t| t.name == name) {
-            Some(t.clone())
+            Some(t)
         } else {
             tracing::warn!("Failed to find table \"{}\" in contract", name);
             None
@@ -126,36 +239,115 @@ impl Contract {
     }
 
     /// Derives the FreeStoragePointers into their bytes32 representation
+    ///
+    /// Storage slots are assigned in declaration order, whether or not the constant is used, so
+    /// the layout doesn't shift as macros change. Pointers with an explicit slot, i.e.
+    /// `FREE_STORAGE_POINTER(slot=3)`, are pinned to it, and the other pointers take the lowest
+    /// slots that aren't pinned. The result is recorded in the contract's
+    /// [storage_layout](Contract::storage_layout).
+    ///
+    /// Free memory pointers are derived along with them, reserving contiguous regions of memory
+    /// from offset zero in the order they're first used, recorded in the contract's
+    /// [memory_layout](Contract::memory_layout).
     pub fn derive_storage_pointers(&mut self) {
+        self.derive_storage_pointers_from("MAIN", "CONSTRUCTOR")
+    }
+
+    /// Derives the FreeStoragePointers into their bytes32 representation, walking the macros
+    /// from the given main and constructor entry points.
+    ///
+    /// See [derive_storage_pointers](Contract::derive_storage_pointers).
+    pub fn derive_storage_pointers_from(&mut self, main: &str, constructor: &str) {
         let mut storage_pointers: Vec<(String, [u8; 32])> = Vec::new();
         let mut last_assigned_free_pointer = 0;
 
+        // The lock of `#pragma nonreentrant` macros takes the slot after the declared ones, unless
+        // its constant is declared to pin it
+        if self.macros.iter().any(|m| m.has_pragma(MacroPragma::Nonreentrant)) {
+            let mut constants = self.constants.lock().unwrap();
+            if !constants.iter().any(|c| c.name == REENTRANCY_LOCK) {
+                constants.push(ConstantDefinition {
+                    name: REENTRANCY_LOCK.to_string(),
+                    ty: None,
+                    value: ConstVal::FreeStoragePointer(FreeStoragePointer { slot: None }),
+                    span: AstSpan(vec![]),
+                });
+            }
+        }
+
+        // Assign the storage slots in declaration order
+        let mut storage_layout: Vec<StorageSlot> = vec![];
+        {
+            let constants = self.constants.lock().unwrap();
+            let pinned = constants
+                .iter()
+                .filter_map(|c| match &c.value {
+                    ConstVal::FreeStoragePointer(FreeStoragePointer { slot: Some(slot) }) => {
+                        Some(*slot)
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<Literal>>();
+            for c in constants.iter() {
+                let slot = match &c.value {
+                    ConstVal::FreeStoragePointer(FreeStoragePointer { slot: Some(slot) }) => *slot,
+                    ConstVal::FreeStoragePointer(FreeStoragePointer { slot: None }) => loop {
+                        let slot = str_to_bytes32(&format!("{last_assigned_free_pointer:x}"));
+                        last_assigned_free_pointer += 1;
+                        if !pinned.contains(&slot) {
+                            break slot
+                        }
+                    },
+                    _ => continue,
+                };
+                storage_pointers.push((c.name.clone(), slot));
+                storage_layout.push(StorageSlot { name: c.name.clone(), slot });
+            }
+        }
+        tracing::debug!(target: "ast", "Generated storage layout: {:?}", storage_layout);
+        self.storage_layout = storage_layout;
+
         // Derive Constructor Storage Pointers
-        match self.find_macro_by_name("CONSTRUCTOR") {
+        match self.find_macro_by_name(constructor) {
             Some(m) => self.recurse_ast_constants(
-                &m,
+                m,
                 &mut storage_pointers,
                 &mut last_assigned_free_pointer,
                 false,
             ),
             None => {
                 // The constructor is not required, so we can just warn
-                tracing::warn!(target: "ast", "'CONSTRUCTOR' MACRO NOT FOUND WHILE DERIVING STORAGE POINTERS!")
+                tracing::warn!(target: "ast", "'{}' MACRO NOT FOUND WHILE DERIVING STORAGE POINTERS!", constructor)
             }
         }
 
         // Derive Main Storage Pointers
-        match self.find_macro_by_name("MAIN") {
+        match self.find_macro_by_name(main) {
             Some(m) => self.recurse_ast_constants(
-                &m,
+                m,
                 &mut storage_pointers,
                 &mut last_assigned_free_pointer,
                 false,
             ),
             None => {
-                tracing::error!(target: "ast", "'MAIN' MACRO NOT FOUND WHILE DERIVING STORAGE POINTERS!")
+                tracing::error!(target: "ast", "'{}' MACRO NOT FOUND WHILE DERIVING STORAGE POINTERS!", main)
+            }
+        }
+
+        // Lay out the memory regions of the free memory pointers
+        let mut memory_layout: Vec<MemorySlot> = vec![];
+        for pointer in storage_pointers.iter_mut() {
+            let constants = self.constants.lock().unwrap();
+            if let Some(ConstVal::FreeMemoryPointer(fmp)) =
+                constants.iter().find(|c| c.name == pointer.0).map(|c| &c.value)
+            {
+                let offset = memory_layout.last().map(|s| s.offset + s.size).unwrap_or_default();
+                pointer.1 = str_to_bytes32(&format!("{offset:x}"));
+                memory_layout.push(MemorySlot { name: pointer.0.clone(), offset, size: fmp.size });
             }
         }
+        tracing::debug!(target: "ast", "Generated memory layout: {:?}", memory_layout);
+        self.memory_layout = memory_layout;
 
         tracing::debug!(target: "ast", "Generate Storage pointers: {:?}", storage_pointers);
         tracing::debug!(target: "ast", "ALL AST CONSTANTS: {:?}", storage_pointers);
@@ -171,6 +363,7 @@ impl Contract {
                 Some(p) => {
                     *c = ConstantDefinition {
                         name: c.name.to_string(),
+                        ty: c.ty,
                         value: ConstVal::Literal(p.1),
                         span: c.span.clone(),
                     };
@@ -182,6 +375,35 @@ impl Contract {
         }
     }
 
+    /// Checks that no two constants of the [storage_layout](Contract::storage_layout) are
+    /// assigned the same slot
+    ///
+    /// Only explicit slots can collide, since derived slots skip them.
+    pub fn check_storage_collisions(&self) -> Result<(), CodegenError> {
+        for (i, second) in self.storage_layout.iter().enumerate() {
+            if let Some(first) = self.storage_layout[..i].iter().find(|s| s.slot == second.slot) {
+                tracing::error!(target: "ast", "STORAGE SLOT COLLISION OF \"{}\" AND \"{}\"", first.name, second.name);
+                let span = self
+                    .constants
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .find(|c| c.name == second.name)
+                    .map(|c| c.span.clone())
+                    .unwrap_or_default();
+                return Err(CodegenError {
+                    kind: CodegenErrorKind::StorageSlotCollision(
+                        vec![first.name.clone(), second.name.clone()],
+                        bytes32_to_string(&second.slot, true),
+                    ),
+                    span,
+                    token: None,
+                })
+            }
+        }
+        Ok(())
+    }
+
     /// Recurse down an AST Macro Definition to set Storage Pointers
     ///
     /// ## Overview
@@ -191,8 +413,11 @@ impl Contract {
     ///       updated out `last_p` tracker value
     ///     - If it's a literal constant, we can set the constant value directly to the literal if
     ///       not already set
-    ///     - If it's a macro invocation, look for the macro definition and recurse into that macro
-    ///       definition using `recurse_ast_constants`
+    ///     - If it's a macro invocation, look for the macro definition and walk into that macro
+    ///       definition before moving on to the next statement
+    ///
+    /// Macros are walked depth-first with an explicit stack rather than recursively, so deeply
+    /// nested macros can't overflow the native stack. Circular invocations are only walked once.
     pub fn recurse_ast_constants(
         &self,
         macro_def: &MacroDefinition,
@@ -200,15 +425,25 @@ impl Contract {
         last_p: &mut i32,
         checking_constructor: bool,
     ) {
-        let mut statements = macro_def.statements.clone();
+        let mut stack = vec![(macro_def, macro_def.statements.iter(), checking_constructor)];
 
-        let mut i = 0;
-        loop {
-            if i >= statements.len() {
-                break
-            }
-            match &statements[i].clone().ty {
-                StatementType::Constant(const_name) => {
+        while let Some((macro_def, statements, checking_constructor)) = stack.last_mut() {
+            let (macro_def, checking_constructor) = (*macro_def, *checking_constructor);
+            let statement = match statements.next() {
+                Some(s) => s,
+                None => {
+                    stack.pop();
+                    continue
+                }
+            };
+
+            // Macros referenced by the statement, walked in order
+            let mut referenced: Vec<&MacroDefinition> = vec![];
+            match &statement.ty {
+                // Constants declared in the macro's body are literals, shadowing the contract's
+                StatementType::Constant(const_name)
+                    if macro_def.find_constant(const_name).is_none() =>
+                {
                     self.assign_free_storage_pointers(
                         const_name,
                         &macro_def.name,
@@ -219,11 +454,15 @@ impl Contract {
                 StatementType::MacroInvocation(mi) => {
                     tracing::debug!(target: "ast", "Found macro invocation: \"{}\" in macro def: \"{}\"!", mi.macro_name, macro_def.name);
 
-                    // Check for constant references in macro arguments
+                    // Check for constant references in macro arguments, including the arguments
+                    // of macros passed as arguments
                     let mut constant_args: Vec<String> = Vec::new();
-                    for arg in &mi.args {
+                    for arg in mi.flatten().into_iter().flat_map(|mi| &mi.args) {
                         // check if it is a constant
                         if let Ident(name) = arg {
+                            if macro_def.find_constant(name).is_some() {
+                                continue
+                            }
                             self.constants.lock().unwrap().iter().for_each(|constant| {
                                 if name == &constant.name {
                                     tracing::debug!(target: "ast", "CONSTANT FOUND AS MACRO PARAMETER {}", name);
@@ -242,62 +481,21 @@ impl Contract {
                         );
                     }
 
-                    match self
-                        .macros
-                        .iter()
-                        .filter(|md| md.name.eq(&mi.macro_name))
-                        .collect::<Vec<&MacroDefinition>>()
-                        .get(0)
-                    {
-                        Some(&md) => {
-                            if md.name.eq("CONSTRUCTOR") {
-                                if !checking_constructor {
-                                    self.recurse_ast_constants(md, storage_pointers, last_p, true);
-                                }
-                            } else {
-                                self.recurse_ast_constants(
-                                    md,
-                                    storage_pointers,
-                                    last_p,
-                                    checking_constructor,
-                                );
+                    for mi in mi.flatten() {
+                        match self.macros.iter().find(|md| md.name.eq(&mi.macro_name)) {
+                            Some(md) => referenced.push(md),
+                            None => {
+                                tracing::warn!(target: "ast", "MACRO \"{}\" INVOKED BUT NOT FOUND IN AST!", mi.macro_name)
                             }
                         }
-                        None => {
-                            tracing::warn!(target: "ast", "MACRO \"{}\" INVOKED BUT NOT FOUND IN AST!", mi.macro_name)
-                        }
                     }
                 }
                 StatementType::BuiltinFunctionCall(bfc) => {
                     tracing::debug!(target: "ast", "Deriving Storage Pointers: Found builtin function {:?}", bfc.kind);
                     for a in &bfc.args {
                         if let Some(name) = &a.name {
-                            match self
-                                .macros
-                                .iter()
-                                .filter(|md| md.name.eq(name))
-                                .collect::<Vec<&MacroDefinition>>()
-                                .get(0)
-                            {
-                                Some(&md) => {
-                                    if md.name.eq("CONSTRUCTOR") {
-                                        if !checking_constructor {
-                                            self.recurse_ast_constants(
-                                                md,
-                                                storage_pointers,
-                                                last_p,
-                                                true,
-                                            );
-                                        }
-                                    } else {
-                                        self.recurse_ast_constants(
-                                            md,
-                                            storage_pointers,
-                                            last_p,
-                                            checking_constructor,
-                                        );
-                                    }
-                                }
+                            match self.macros.iter().find(|md| md.name.eq(name)) {
+                                Some(md) => referenced.push(md),
                                 None => {
                                     tracing::warn!(target: "ast", "BUILTIN HAS ARG NAME \"{}\" BUT NOT FOUND IN AST!", name)
                                 }
@@ -306,20 +504,29 @@ impl Contract {
                     }
                 }
                 StatementType::Label(l) => {
-                    for state in l.inner.iter().rev() {
-                        statements.insert(i + 1, state.clone());
-                    }
+                    stack.push((macro_def, l.inner.iter(), checking_constructor));
                 }
                 _ => {}
             }
-            i += 1;
-        }
 
-        // Breadth-first
-        // if !macros_to_recurse.is_empty() {
-        //     let next_md = macros_to_recurse.remove(0);
-        //     self.recurse_ast_constants(next_md, storage_pointers, last_p, macros_to_recurse);
-        // }
+            // Push in reverse so that the first referenced macro is walked first
+            let chain_len = stack.len();
+            for md in referenced.into_iter().rev() {
+                let checking_constructor = if md.name.eq("CONSTRUCTOR") {
+                    if checking_constructor {
+                        continue
+                    }
+                    true
+                } else {
+                    checking_constructor
+                };
+                // Circular invocations are never walked again
+                if stack[..chain_len].iter().any(|(m, ..)| m.name.eq(&md.name)) {
+                    continue
+                }
+                stack.push((md, md.statements.iter(), checking_constructor));
+            }
+        }
     }
 
     fn assign_free_storage_pointers(
@@ -351,11 +558,16 @@ impl Contract {
                 Some(c) => {
                     let new_value = match c.value {
                         ConstVal::Literal(l) => l,
-                        ConstVal::FreeStoragePointer(_) => {
+                        ConstVal::FreeStoragePointer(FreeStoragePointer { slot: Some(slot) }) => {
+                            slot
+                        }
+                        ConstVal::FreeStoragePointer(FreeStoragePointer { slot: None }) => {
                             let old_p = *last_p;
                             *last_p += 1;
-                            str_to_bytes32(&format!("{old_p}"))
+                            str_to_bytes32(&format!("{old_p:x}"))
                         }
+                        // Memory regions are laid out once every pointer is found
+                        ConstVal::FreeMemoryPointer(_) => [0u8; 32],
                     };
                     storage_pointers.push((const_name.to_string(), new_value));
                 }
@@ -381,6 +593,7 @@ impl Contract {
                 } else {
                     constants.push(ConstantDefinition {
                         name: name.to_string(),
+                        ty: None,
                         value: ConstVal::Literal(*value),
                         span: AstSpan::default(),
                     });
@@ -390,6 +603,149 @@ impl Contract {
     }
 }
 
+/// A Contract Definition
+///
+/// A named contract scope in a file, defined with `#define contract <name> { ... }`. Each
+/// contract has its own `MAIN` and `CONSTRUCTOR` macros and compiles into its own artifact.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ContractDefinition {
+    /// The name of the contract
+    pub name: String,
+    /// The definitions in the contract block
+    pub contract: Contract,
+    /// The contract definition span
+    pub span: AstSpan,
+}
+
+/// An ABI JSON file imported with `#include abi "IERC20.json"`, declaring the functions, events
+/// and errors of the ABI under the file's stem, i.e. `__FUNC_SIG(IERC20.transfer)`
+///
+/// The parser records the include, and the compiler reads the file to declare its entries. The
+/// imported entries are only referenced by name, and aren't part of the contract's own ABI.
+///
+/// Solidity files imported with `#include sol "IERC20.sol"` declare an import for each of their
+/// interfaces instead, named after the interface.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AbiImport {
+    /// The name qualifying the imported entries, the stem of the file
+    pub name: String,
+    /// The path of the imported file, as included
+    pub path: FilePath,
+    /// The kind of the imported file
+    #[serde(default)]
+    pub kind: AbiImportKind,
+    /// The functions of the ABI
+    pub functions: Vec<Function>,
+    /// The events of the ABI
+    pub events: Vec<Event>,
+    /// The errors of the ABI
+    pub errors: Vec<ErrorDefinition>,
+    /// The span of the include
+    pub span: AstSpan,
+}
+
+/// The kind of file imported by an [AbiImport]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AbiImportKind {
+    /// An ABI JSON file, imported with `#include abi`
+    #[default]
+    Abi,
+    /// A Solidity file of interfaces, imported with `#include sol`
+    Sol,
+}
+
+impl TryFrom<&str> for AbiImportKind {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "abi" => Ok(AbiImportKind::Abi),
+            "sol" => Ok(AbiImportKind::Sol),
+            _ => Err(()),
+        }
+    }
+}
+
+impl AbiImport {
+    /// Public associated function instantiating an import of the file at the path, without
+    /// declaring its entries
+    pub fn new(path: FilePath, kind: AbiImportKind, span: AstSpan) -> Self {
+        let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+        Self { name, path, kind, span, ..Default::default() }
+    }
+
+    /// Declares the entries of the ABI JSON, either an array of ABI entries or an artifact
+    /// holding it as `abi`, as Foundry and Hardhat output
+    ///
+    /// Overloaded functions share their name, so only the first one is found by name.
+    pub fn declare(&mut self, json: &str) -> Result<(), String> {
+        let entries = match serde_json::from_str(json).map_err(|e| e.to_string())? {
+            serde_json::Value::Object(mut artifact) => {
+                artifact.remove("abi").ok_or("missing the \"abi\" field of the artifact")?
+            }
+            entries => entries,
+        };
+        let abi: ethers_core::abi::Abi =
+            serde_json::from_value(entries).map_err(|e| e.to_string())?;
+
+        let span = &self.span;
+        let argument = |name: &str, kind: &ethers_core::abi::ParamType, indexed: bool| Argument {
+            arg_type: Some(kind.to_string()),
+            arg_location: None,
+            name: (!name.is_empty()).then(|| name.to_string()),
+            indexed,
+            span: span.clone(),
+        };
+        self.functions = abi
+            .functions()
+            .map(|f| Function {
+                name: f.name.clone(),
+                signature: f.short_signature(),
+                inputs: f.inputs.iter().map(|p| argument(&p.name, &p.kind, false)).collect(),
+                fn_type: match f.state_mutability {
+                    ethers_core::abi::StateMutability::Pure => FunctionType::Pure,
+                    ethers_core::abi::StateMutability::View => FunctionType::View,
+                    ethers_core::abi::StateMutability::NonPayable => FunctionType::NonPayable,
+                    ethers_core::abi::StateMutability::Payable => FunctionType::Payable,
+                },
+                outputs: f.outputs.iter().map(|p| argument(&p.name, &p.kind, false)).collect(),
+                span: span.clone(),
+            })
+            .collect();
+        self.events = abi
+            .events()
+            .map(|e| Event {
+                name: e.name.clone(),
+                parameters: e
+                    .inputs
+                    .iter()
+                    .map(|p| argument(&p.name, &p.kind, p.indexed))
+                    .collect(),
+                span: span.clone(),
+                hash: e.signature().0,
+            })
+            .collect();
+        self.errors = abi
+            .errors()
+            .map(|e| {
+                let mut selector = [0u8; 4];
+                selector.copy_from_slice(&e.signature()[..4]);
+                ErrorDefinition {
+                    name: e.name.clone(),
+                    selector,
+                    parameters: e
+                        .inputs
+                        .iter()
+                        .map(|p| argument(&p.name, &p.kind, false))
+                        .collect(),
+                    span: span.clone(),
+                }
+            })
+            .collect();
+        Ok(())
+    }
+}
+
 /// An argument's location
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ArgumentLocation {
@@ -403,7 +759,7 @@ pub enum ArgumentLocation {
 }
 
 /// A function, event, or macro argument
-#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Argument {
     /// Type of the argument
     pub arg_type: Option<String>,
@@ -418,7 +774,7 @@ pub struct Argument {
 }
 
 /// A Function Signature
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Function {
     /// The name of the function
     pub name: String,
@@ -457,10 +813,20 @@ impl FunctionType {
             _ => "", // payable / nonpayable types not valid in Solidity interfaces
         }
     }
+
+    /// Get the `stateMutability` of the function in a JSON ABI.
+    pub fn json_abi_mutability(&self) -> &str {
+        match self {
+            FunctionType::View => "view",
+            FunctionType::Payable => "payable",
+            FunctionType::NonPayable => "nonpayable",
+            FunctionType::Pure => "pure",
+        }
+    }
 }
 
 /// An Event Signature
-#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Event {
     /// The name of the event
     pub name: String,
@@ -473,7 +839,7 @@ pub struct Event {
 }
 
 /// A Table Definition
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TableDefinition {
     /// The name of the table
     pub name: String,
@@ -501,7 +867,7 @@ impl TableDefinition {
 }
 
 /// A Table Kind
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum TableKind {
     /// A regular jump table
     JumpTable,
@@ -509,6 +875,8 @@ pub enum TableKind {
     JumpTablePacked,
     /// A code table
     CodeTable,
+    /// A data section, i.e. `#define data NAME = 0x...`
+    Data,
 }
 
 impl From<TokenKind> for TableKind {
@@ -518,13 +886,14 @@ impl From<TokenKind> for TableKind {
             TokenKind::JumpTable => TableKind::JumpTable,
             TokenKind::JumpTablePacked => TableKind::JumpTablePacked,
             TokenKind::CodeTable => TableKind::CodeTable,
+            TokenKind::Data => TableKind::Data,
             _ => panic!("Invalid Token Kind"), // TODO: Better error handling
         }
     }
 }
 
 /// A Macro Definition
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct MacroDefinition {
     /// The Macro Name
     pub name: String,
@@ -544,11 +913,21 @@ pub struct MacroDefinition {
     pub outlined: bool,
     /// Is the macro a test?
     pub test: bool,
+    /// The constants declared in the macro's body, only resolvable within it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constants: Vec<ConstantDefinition>,
+    /// The names of the stack items the macro returns, top of the stack first, if its `returns`
+    /// names them, i.e. `returns (quotient, remainder)`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<String>,
+    /// The pragmas annotating the macro, i.e. `#pragma no-inline`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pragmas: Vec<MacroPragma>,
 }
 
 impl ToIRBytecode<CodegenError> for MacroDefinition {
     fn to_irbytecode(&self) -> Result<IRBytecode, CodegenError> {
-        let inner_irbytes: Vec<IRBytes> = MacroDefinition::to_irbytes(&self.statements);
+        let inner_irbytes: Vec<IRBytes> = MacroDefinition::to_irbytes(&self.statements)?;
         Ok(IRBytecode(inner_irbytes))
     }
 }
@@ -577,41 +956,52 @@ impl MacroDefinition {
             span: AstSpan(spans),
             outlined,
             test,
+            constants: vec![],
+            outputs: vec![],
+            pragmas: vec![],
         }
     }
 
+    /// Whether the macro is compiled as an outlined function, either defined with `#define fn` or
+    /// annotated with `#pragma no-inline`, and not annotated with `#pragma hot`
+    pub fn is_outlined(&self) -> bool {
+        (self.outlined || self.has_pragma(MacroPragma::NoInline)) &&
+            !self.has_pragma(MacroPragma::Hot)
+    }
+
+    /// Whether the macro is annotated with the given pragma
+    pub fn has_pragma(&self, pragma: MacroPragma) -> bool {
+        self.pragmas.contains(&pragma)
+    }
+
+    /// Returns the constant declared in the macro's body with the given name, if any
+    pub fn find_constant(&self, name: &str) -> Option<&ConstantDefinition> {
+        self.constants.iter().find(|c| c.name == name)
+    }
+
     /// Translate statements into IRBytes
-    pub fn to_irbytes(statements: &[Statement]) -> Vec<IRBytes> {
+    pub fn to_irbytes(statements: &[Statement]) -> Result<Vec<IRBytes>, CodegenError> {
         let mut inner_irbytes: Vec<IRBytes> = vec![];
 
         let mut statement_iter = statements.iter();
         while let Some(statement) = statement_iter.next() {
             match &statement.ty {
                 StatementType::Literal(l) => {
-                    let hex_literal: String = bytes32_to_string(l, false);
-                    let push_bytes = format!("{:02x}{hex_literal}", 95 + hex_literal.len() / 2);
                     inner_irbytes.push(IRBytes {
-                        ty: IRByteType::Bytes(Bytes(push_bytes)),
+                        ty: IRByteType::Instruction(Instruction::push_value(
+                            l,
+                            statement.span.clone(),
+                        )),
                         span: statement.span.clone(),
                     });
                 }
                 StatementType::Opcode(o) => {
-                    let opcode_str = o.string();
-                    inner_irbytes.push(IRBytes {
-                        ty: IRByteType::Bytes(Bytes(opcode_str)),
-                        span: statement.span.clone(),
-                    });
                     // If the opcode is a push, we need to consume the next statement, which must be
                     // a literal as checked in the parser
-                    if o.is_push() {
+                    let immediate = if o.is_push() {
                         match statement_iter.next() {
                             Some(Statement { ty: StatementType::Literal(l), span: _ }) => {
-                                let hex_literal: String = bytes32_to_string(l, false);
-                                let prefixed_hex_literal = o.prefix_push_literal(&hex_literal);
-                                inner_irbytes.push(IRBytes {
-                                    ty: IRByteType::Bytes(Bytes(prefixed_hex_literal)),
-                                    span: statement.span.clone(),
-                                });
+                                l[l.len() - o.immediate_size()..].to_vec()
                             }
                             _ => {
                                 // We have a push without a literal - this should be caught by the
@@ -619,11 +1009,33 @@ impl MacroDefinition {
                                 panic!("Invalid push statement");
                             }
                         }
-                    }
+                    } else {
+                        vec![]
+                    };
+                    inner_irbytes.push(IRBytes {
+                        ty: IRByteType::Instruction(Instruction {
+                            immediate,
+                            ..Instruction::op(*o, statement.span.clone())
+                        }),
+                        span: statement.span.clone(),
+                    });
                 }
                 StatementType::Code(c) => {
+                    if c.len() % 2 == 1 {
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidCodeLength(c.len()),
+                            span: statement.span.clone(),
+                            token: None,
+                        })
+                    }
+                    let code =
+                        Instruction::code(c, statement.span.clone()).ok_or_else(|| CodegenError {
+                            kind: CodegenErrorKind::InvalidHex(c.to_owned()),
+                            span: statement.span.clone(),
+                            token: None,
+                        })?;
                     inner_irbytes.push(IRBytes {
-                        ty: IRByteType::Bytes(Bytes(c.to_owned())),
+                        ty: IRByteType::Instruction(code),
                         span: statement.span.clone(),
                     });
                 }
@@ -671,7 +1083,7 @@ impl MacroDefinition {
                     });
 
                     // Recurse label statements to IRBytes Bytes
-                    inner_irbytes.append(&mut MacroDefinition::to_irbytes(&l.inner));
+                    inner_irbytes.append(&mut MacroDefinition::to_irbytes(&l.inner)?);
                 }
                 StatementType::BuiltinFunctionCall(builtin) => {
                     inner_irbytes.push(IRBytes {
@@ -682,15 +1094,22 @@ impl MacroDefinition {
                         span: statement.span.clone(),
                     });
                 }
+                StatementType::Assertion(_) | StatementType::OffsetAssertion(_) => {
+                    /* Assertions are evaluated in codegen, where constants and offsets are known */
+                    inner_irbytes.push(IRBytes {
+                        ty: IRByteType::Statement(statement.clone()),
+                        span: statement.span.clone(),
+                    });
+                }
             }
         }
 
-        inner_irbytes
+        Ok(inner_irbytes)
     }
 }
 
 /// A Macro Invocation
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct MacroInvocation {
     /// The Macro Name
     pub macro_name: String,
@@ -700,8 +1119,22 @@ pub struct MacroInvocation {
     pub span: AstSpan,
 }
 
+impl MacroInvocation {
+    /// The invocation followed by the invocations passed as its arguments, recursively, in the
+    /// order they appear in the source
+    pub fn flatten(&self) -> Vec<&MacroInvocation> {
+        let mut invocations = vec![self];
+        for arg in &self.args {
+            if let MacroArg::MacroInvocation(mi) = arg {
+                invocations.extend(mi.flatten());
+            }
+        }
+        invocations
+    }
+}
+
 /// An argument passed when invoking a maco
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum MacroArg {
     /// Macro Literal Argument
     Literal(Literal),
@@ -709,34 +1142,207 @@ pub enum MacroArg {
     Ident(String),
     /// An Arg Call
     ArgCall(String),
+    /// A Macro Invocation, expanded where the argument is used
+    MacroInvocation(MacroInvocation),
 }
 
-/// Free Storage Pointer Unit Struct
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct FreeStoragePointer;
+/// Free Storage Pointer
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FreeStoragePointer {
+    /// The explicit slot of the pointer, i.e. `FREE_STORAGE_POINTER(slot=3)`
+    pub slot: Option<Literal>,
+}
+
+/// Free Memory Pointer, reserving a region of static memory
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FreeMemoryPointer {
+    /// The size of the region in bytes, a multiple of 32
+    pub size: usize,
+}
+
+/// A region of static memory reserved with `FREE_MEMORY_POINTER`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MemorySlot {
+    /// The name of the constant holding the region's offset
+    pub name: String,
+    /// The offset of the region
+    pub offset: usize,
+    /// The size of the region in bytes
+    pub size: usize,
+}
+
+/// A storage slot assigned to a `FREE_STORAGE_POINTER` constant
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StorageSlot {
+    /// The name of the constant holding the slot
+    pub name: String,
+    /// The slot, serialized as a hex string
+    #[serde(with = "hex_slot")]
+    pub slot: Literal,
+}
+
+mod hex_slot {
+    use super::Literal;
+    use crate::bytes_util::{bytes32_to_string, str_to_bytes32};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(slot: &Literal, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&bytes32_to_string(slot, true))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Literal, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let hex = s.strip_prefix("0x").unwrap_or(&s);
+        if hex.is_empty() || hex.len() > 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(D::Error::custom(format!("invalid storage slot \"{s}\"")))
+        }
+        Ok(str_to_bytes32(hex))
+    }
+}
 
 /// A Constant Value
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ConstVal {
     /// A literal value for the constant
     Literal(Literal),
     /// A Free Storage Pointer
     FreeStoragePointer(FreeStoragePointer),
+    /// A Free Memory Pointer
+    FreeMemoryPointer(FreeMemoryPointer),
 }
 
 /// A Constant Definition
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ConstantDefinition {
     /// The Constant name
     pub name: String,
+    /// The optional type annotation of the Constant
+    pub ty: Option<PrimitiveEVMType>,
     /// The Constant value
     pub value: ConstVal,
     /// The Span of the Constant Definition
     pub span: AstSpan,
 }
 
+/// An operator of a constant expression
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConstOperator {
+    /// `+`
+    Add,
+    /// `-`
+    Sub,
+    /// `*`
+    Mul,
+    /// `/`
+    Div,
+    /// `==`
+    Eq,
+    /// `!=`
+    Neq,
+    /// `<`
+    Lt,
+    /// `>`
+    Gt,
+    /// `<=`
+    Le,
+    /// `>=`
+    Ge,
+}
+
+impl Display for ConstOperator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            ConstOperator::Add => "+",
+            ConstOperator::Sub => "-",
+            ConstOperator::Mul => "*",
+            ConstOperator::Div => "/",
+            ConstOperator::Eq => "==",
+            ConstOperator::Neq => "!=",
+            ConstOperator::Lt => "<",
+            ConstOperator::Gt => ">",
+            ConstOperator::Le => "<=",
+            ConstOperator::Ge => ">=",
+        };
+        write!(f, "{op}")
+    }
+}
+
+/// An expression of literals and constants, evaluated at compile time
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConstExpr {
+    /// A literal
+    Literal(Literal),
+    /// A reference to a constant, i.e. `[SLOT]`
+    Constant(String),
+    /// A binary operation
+    Binary(Box<ConstExpr>, ConstOperator, Box<ConstExpr>),
+}
+
+impl ConstExpr {
+    /// Evaluates the expression, resolving constants to their values with `resolve`.
+    ///
+    /// Arithmetic is checked, failing on overflow, underflow and division by zero. Comparisons
+    /// evaluate to 1 if true and 0 otherwise.
+    pub fn eval(&self, resolve: &dyn Fn(&str) -> Option<Literal>) -> Result<U256, String> {
+        match self {
+            ConstExpr::Literal(l) => Ok(U256::from_big_endian(l)),
+            ConstExpr::Constant(name) => resolve(name)
+                .map(|l| U256::from_big_endian(&l))
+                .ok_or_else(|| format!("Missing constant \"{name}\"")),
+            ConstExpr::Binary(lhs, op, rhs) => {
+                let (a, b) = (lhs.eval(resolve)?, rhs.eval(resolve)?);
+                let checked = match op {
+                    ConstOperator::Add => a.checked_add(b),
+                    ConstOperator::Sub => a.checked_sub(b),
+                    ConstOperator::Mul => a.checked_mul(b),
+                    ConstOperator::Div => a.checked_div(b),
+                    ConstOperator::Eq => Some(U256::from((a == b) as u8)),
+                    ConstOperator::Neq => Some(U256::from((a != b) as u8)),
+                    ConstOperator::Lt => Some(U256::from((a < b) as u8)),
+                    ConstOperator::Gt => Some(U256::from((a > b) as u8)),
+                    ConstOperator::Le => Some(U256::from((a <= b) as u8)),
+                    ConstOperator::Ge => Some(U256::from((a >= b) as u8)),
+                };
+                checked.ok_or_else(|| format!("Arithmetic error evaluating \"{self}\""))
+            }
+        }
+    }
+}
+
+impl Display for ConstExpr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstExpr::Literal(l) => write!(f, "{}", bytes32_to_string(l, true)),
+            ConstExpr::Constant(name) => write!(f, "[{name}]"),
+            ConstExpr::Binary(lhs, op, rhs) => {
+                for (i, operand) in [lhs, rhs].into_iter().enumerate() {
+                    if i == 1 {
+                        write!(f, " {op} ")?;
+                    }
+                    match operand.as_ref() {
+                        ConstExpr::Binary(..) => write!(f, "({operand})")?,
+                        _ => write!(f, "{operand}")?,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A compile time assertion, i.e. `__ASSERT([SLOT_A] != [SLOT_B], "slots collide")`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Assertion {
+    /// The asserted expression, holding if it evaluates to a non-zero value
+    pub condition: ConstExpr,
+    /// The message compilation fails with if the assertion doesn't hold
+    pub message: Option<String>,
+    /// The span of the assertion
+    pub span: AstSpan,
+}
+
 /// An Error Definition
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ErrorDefinition {
     /// The Error name
     pub name: String,
@@ -748,8 +1354,43 @@ pub struct ErrorDefinition {
     pub span: AstSpan,
 }
 
+/// A Struct Definition, i.e. `#define struct Order(address maker, uint256 amount, bytes data)`
+///
+/// Its fields are laid out in memory like the fields of a Solidity memory struct, and the
+/// `Order_ENCODE` and `Order_DECODE` macros generated for it convert it to and from its ABI
+/// encoding.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StructDefinition {
+    /// The struct name
+    pub name: String,
+    /// The fields of the struct, in order
+    pub fields: Vec<Argument>,
+    /// The span of the struct definition
+    pub span: AstSpan,
+}
+
+/// A Merkle Tree Definition, i.e. `#define merkle AIRDROP(keccak256) = "airdrop.json"`
+///
+/// The `AIRDROP_VERIFY` macro generated for it verifies a proof of a leaf against a root, and
+/// the root of the leaves read from the json file, if one is given, is declared as the
+/// `AIRDROP_ROOT` constant.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MerkleTreeDefinition {
+    /// The tree name
+    pub name: String,
+    /// The function hashing the nodes of the tree
+    pub hash: MerkleHash,
+    /// The number of nodes of every proof, unrolling the verification, or `None` to loop over
+    /// proofs of any length
+    pub depth: Option<usize>,
+    /// The json file of the leaves, as included
+    pub leaves: Option<FilePath>,
+    /// The span of the merkle tree definition
+    pub span: AstSpan,
+}
+
 /// A Jump Destination
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Label {
     /// The JumpDest Name
     pub name: String,
@@ -760,7 +1401,7 @@ pub struct Label {
 }
 
 /// A Builtin Function Call
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct BuiltinFunctionCall {
     /// The Builtin Kind
     pub kind: BuiltinFunctionKind,
@@ -773,7 +1414,7 @@ pub struct BuiltinFunctionCall {
 }
 
 /// A Builtin Function Kind
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum BuiltinFunctionKind {
     /// Table size function
     Tablesize,
@@ -793,23 +1434,178 @@ pub enum BuiltinFunctionKind {
     DynConstructorArg,
     /// Inject Raw Bytes
     Verbatim,
+    /// Load an ABI-encoded argument from calldata
+    CalldataLoadArg,
+    /// ERC-7201 namespaced storage slot
+    Erc7201,
+    /// EIP-1967 implementation slot
+    Eip1967ImplSlot,
+    /// EIP-1967 admin slot
+    Eip1967AdminSlot,
+    /// EIP-1967 beacon slot
+    Eip1967BeaconSlot,
+    /// Compile time assertion
+    Assert,
+    /// Compile time gas budget of a macro
+    GasLimit,
+    /// Short string pushed as a literal
+    StrLit,
+    /// Offset of the expansion point pushed as a literal
+    Pc,
+    /// Offset of a label pushed as a literal
+    Offset,
+    /// Size and offset of the code between two labels
+    CodeSlice,
+    /// Offset of a data section
+    DataOffset,
+    /// Size of a data section
+    DataSize,
+    /// Revert unless the calldata holds the arguments of a function
+    CalldataCheck,
+    /// EIP-712 type hash of a struct
+    TypeHash,
+    /// EIP-712 domain separator of the contract, computed at runtime
+    DomainSeparator,
+    /// Recover the signer of a hash with the ecrecover precompile
+    Ecrecover,
+    /// Hash memory with the sha256 precompile
+    Sha256,
+    /// Modular exponentiation with the modexp precompile
+    Modexp,
+    /// Add alt_bn128 points with the ecadd precompile
+    EcAdd,
+    /// Multiply an alt_bn128 point with the ecmul precompile
+    EcMul,
+    /// Check an alt_bn128 pairing with the ecpairing precompile
+    EcPairing,
+    /// Verify a KZG proof with the point evaluation precompile
+    PointEvaluation,
+}
+
+impl BuiltinFunctionKind {
+    /// Every builtin function, in declaration order.
+    pub const ALL: [BuiltinFunctionKind; 32] = [
+        BuiltinFunctionKind::Tablesize,
+        BuiltinFunctionKind::Codesize,
+        BuiltinFunctionKind::Tablestart,
+        BuiltinFunctionKind::FunctionSignature,
+        BuiltinFunctionKind::EventHash,
+        BuiltinFunctionKind::Error,
+        BuiltinFunctionKind::RightPad,
+        BuiltinFunctionKind::DynConstructorArg,
+        BuiltinFunctionKind::Verbatim,
+        BuiltinFunctionKind::CalldataLoadArg,
+        BuiltinFunctionKind::Erc7201,
+        BuiltinFunctionKind::Eip1967ImplSlot,
+        BuiltinFunctionKind::Eip1967AdminSlot,
+        BuiltinFunctionKind::Eip1967BeaconSlot,
+        BuiltinFunctionKind::Assert,
+        BuiltinFunctionKind::GasLimit,
+        BuiltinFunctionKind::StrLit,
+        BuiltinFunctionKind::Pc,
+        BuiltinFunctionKind::Offset,
+        BuiltinFunctionKind::CodeSlice,
+        BuiltinFunctionKind::DataOffset,
+        BuiltinFunctionKind::DataSize,
+        BuiltinFunctionKind::CalldataCheck,
+        BuiltinFunctionKind::TypeHash,
+        BuiltinFunctionKind::DomainSeparator,
+        BuiltinFunctionKind::Ecrecover,
+        BuiltinFunctionKind::Sha256,
+        BuiltinFunctionKind::Modexp,
+        BuiltinFunctionKind::EcAdd,
+        BuiltinFunctionKind::EcMul,
+        BuiltinFunctionKind::EcPairing,
+        BuiltinFunctionKind::PointEvaluation,
+    ];
+
+    /// The name used to invoke the builtin function in Huff source.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuiltinFunctionKind::Tablesize => "__tablesize",
+            BuiltinFunctionKind::Codesize => "__codesize",
+            BuiltinFunctionKind::Tablestart => "__tablestart",
+            BuiltinFunctionKind::FunctionSignature => "__FUNC_SIG",
+            BuiltinFunctionKind::EventHash => "__EVENT_HASH",
+            BuiltinFunctionKind::Error => "__ERROR",
+            BuiltinFunctionKind::RightPad => "__RIGHTPAD",
+            BuiltinFunctionKind::DynConstructorArg => "__CODECOPY_DYN_ARG",
+            BuiltinFunctionKind::Verbatim => "__VERBATIM",
+            BuiltinFunctionKind::CalldataLoadArg => "__CALLDATA_LOAD_ARG",
+            BuiltinFunctionKind::Erc7201 => "__ERC7201",
+            BuiltinFunctionKind::Eip1967ImplSlot => "__EIP1967_IMPL_SLOT",
+            BuiltinFunctionKind::Eip1967AdminSlot => "__EIP1967_ADMIN_SLOT",
+            BuiltinFunctionKind::Eip1967BeaconSlot => "__EIP1967_BEACON_SLOT",
+            BuiltinFunctionKind::Assert => "__ASSERT",
+            BuiltinFunctionKind::GasLimit => "__GAS_LIMIT",
+            BuiltinFunctionKind::StrLit => "__STR_LIT",
+            BuiltinFunctionKind::Pc => "__PC",
+            BuiltinFunctionKind::Offset => "__OFFSET",
+            BuiltinFunctionKind::CodeSlice => "__CODE_SLICE",
+            BuiltinFunctionKind::DataOffset => "__DATA_OFFSET",
+            BuiltinFunctionKind::DataSize => "__DATA_SIZE",
+            BuiltinFunctionKind::CalldataCheck => "__CALLDATA_CHECK",
+            BuiltinFunctionKind::TypeHash => "__TYPEHASH",
+            BuiltinFunctionKind::DomainSeparator => "__DOMAIN_SEPARATOR",
+            BuiltinFunctionKind::Ecrecover => "__ECRECOVER",
+            BuiltinFunctionKind::Sha256 => "__SHA256",
+            BuiltinFunctionKind::Modexp => "__MODEXP",
+            BuiltinFunctionKind::EcAdd => "__ECADD",
+            BuiltinFunctionKind::EcMul => "__ECMUL",
+            BuiltinFunctionKind::EcPairing => "__ECPAIRING",
+            BuiltinFunctionKind::PointEvaluation => "__POINT_EVALUATION",
+        }
+    }
+
+    /// The precompile called by a precompile builtin, i.e. `__ECRECOVER()`, or `None` if the
+    /// builtin doesn't call one.
+    pub fn precompile(&self) -> Option<Precompile> {
+        match self {
+            BuiltinFunctionKind::Ecrecover => Some(Precompile::Ecrecover),
+            BuiltinFunctionKind::Sha256 => Some(Precompile::Sha256),
+            BuiltinFunctionKind::Modexp => Some(Precompile::Modexp),
+            BuiltinFunctionKind::EcAdd => Some(Precompile::EcAdd),
+            BuiltinFunctionKind::EcMul => Some(Precompile::EcMul),
+            BuiltinFunctionKind::EcPairing => Some(Precompile::EcPairing),
+            BuiltinFunctionKind::PointEvaluation => Some(Precompile::PointEvaluation),
+            _ => None,
+        }
+    }
+
+    /// Computes the storage slot of a storage slot builtin from its arguments, i.e.
+    /// `__ERC7201("example.main")`, or `None` if the builtin doesn't compute a slot.
+    pub fn storage_slot(&self, args: &[&str]) -> Option<Result<Literal, String>> {
+        let expected = match self {
+            BuiltinFunctionKind::Erc7201 => 1,
+            BuiltinFunctionKind::Eip1967ImplSlot |
+            BuiltinFunctionKind::Eip1967AdminSlot |
+            BuiltinFunctionKind::Eip1967BeaconSlot => 0,
+            _ => return None,
+        };
+        if args.len() != expected {
+            return Some(Err(format!(
+                "Incorrect number of arguments passed to {}, should be {}: {}",
+                self.name(),
+                expected,
+                args.len()
+            )))
+        }
+        Some(Ok(match self {
+            BuiltinFunctionKind::Eip1967ImplSlot => eip1967_slot("eip1967.proxy.implementation"),
+            BuiltinFunctionKind::Eip1967AdminSlot => eip1967_slot("eip1967.proxy.admin"),
+            BuiltinFunctionKind::Eip1967BeaconSlot => eip1967_slot("eip1967.proxy.beacon"),
+            _ => erc7201_slot(args[0]),
+        }))
+    }
 }
 
 impl From<String> for BuiltinFunctionKind {
     fn from(value: String) -> Self {
-        match value.as_str() {
-            "__tablesize" => BuiltinFunctionKind::Tablesize,
-            "__codesize" => BuiltinFunctionKind::Codesize,
-            "__tablestart" => BuiltinFunctionKind::Tablestart,
-            "__FUNC_SIG" => BuiltinFunctionKind::FunctionSignature,
-            "__EVENT_HASH" => BuiltinFunctionKind::EventHash,
-            "__ERROR" => BuiltinFunctionKind::Error,
-            "__RIGHTPAD" => BuiltinFunctionKind::RightPad,
-            "__CODECOPY_DYN_ARG" => BuiltinFunctionKind::DynConstructorArg,
-            "__VERBATIM" => BuiltinFunctionKind::Verbatim,
-            _ => panic!("Invalid Builtin Function Kind"), /* This should never be reached,
-                                                           * builtins are validated with a
-                                                           * `try_from` call in the lexer. */
+        match BuiltinFunctionKind::try_from(&value) {
+            Ok(kind) => kind,
+            Err(_) => panic!("Invalid Builtin Function Kind"), /* This should never be reached,
+                                                                * builtins are validated with a
+                                                                * `try_from` call in the lexer. */
         }
     }
 }
@@ -818,23 +1614,12 @@ impl TryFrom<&String> for BuiltinFunctionKind {
     type Error = ();
 
     fn try_from(value: &String) -> Result<Self, <BuiltinFunctionKind as TryFrom<&String>>::Error> {
-        match value.as_str() {
-            "__tablesize" => Ok(BuiltinFunctionKind::Tablesize),
-            "__codesize" => Ok(BuiltinFunctionKind::Codesize),
-            "__tablestart" => Ok(BuiltinFunctionKind::Tablestart),
-            "__FUNC_SIG" => Ok(BuiltinFunctionKind::FunctionSignature),
-            "__EVENT_HASH" => Ok(BuiltinFunctionKind::EventHash),
-            "__ERROR" => Ok(BuiltinFunctionKind::Error),
-            "__RIGHTPAD" => Ok(BuiltinFunctionKind::RightPad),
-            "__CODECOPY_DYN_ARG" => Ok(BuiltinFunctionKind::DynConstructorArg),
-            "__VERBATIM" => Ok(BuiltinFunctionKind::Verbatim),
-            _ => Err(()),
-        }
+        BuiltinFunctionKind::ALL.into_iter().find(|kind| kind.name() == value.as_str()).ok_or(())
     }
 }
 
 /// A Statement
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Statement {
     /// The type of statement
     pub ty: StatementType,
@@ -843,7 +1628,7 @@ pub struct Statement {
 }
 
 /// The Statement Type
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum StatementType {
     /// A Literal Statement
     Literal(Literal),
@@ -863,6 +1648,11 @@ pub enum StatementType {
     LabelCall(String),
     /// A built-in function call
     BuiltinFunctionCall(BuiltinFunctionCall),
+    /// A compile time assertion
+    Assertion(Assertion),
+    /// A compile time assertion of the offset the next instruction is generated at, i.e.
+    /// `@offset(0x20)` in an `asm` block
+    OffsetAssertion(usize),
 }
 
 impl Display for StatementType {
@@ -881,6 +1671,8 @@ impl Display for StatementType {
             StatementType::BuiltinFunctionCall(b) => {
                 write!(f, "BUILTIN FUNCTION CALL: {:?}", b.kind)
             }
+            StatementType::Assertion(a) => write!(f, "ASSERTION: {}", a.condition),
+            StatementType::OffsetAssertion(o) => write!(f, "OFFSET ASSERTION: {o:#x}"),
         }
     }
 }
@@ -890,19 +1682,28 @@ impl Display for StatementType {
 /// At the moment, the decorator tag can only be placed over test definitions. Developers
 /// can use decorators to define environment variables and other metadata for their individual
 /// tests.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Decorator {
     /// Vector of flags passed within the decorator
     pub flags: Vec<DecoratorFlag>,
 }
 
 /// A decorator flag
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum DecoratorFlag {
     /// Sets the calldata of the test call transaction
     Calldata(String),
     /// Sets the value of the test call transaction
     Value(Literal),
+    /// Fuzzes the test with calldata of random arguments to the function signature
+    Fuzz(String),
+    /// Sets the number of calls of a fuzzed test
+    Runs(Literal),
+    /// Sets the seed of the random arguments of a fuzzed test
+    Seed(Literal),
+    /// Reverts unless the caller is the address of a constant, or the address stored in the slot
+    /// of a storage pointer constant, with the custom error if one is named
+    OnlyCaller(String, Option<String>),
 }
 
 impl TryFrom<&String> for DecoratorFlag {
@@ -912,7 +1713,319 @@ impl TryFrom<&String> for DecoratorFlag {
         match value.as_str() {
             "calldata" => Ok(DecoratorFlag::Calldata(String::default())),
             "value" => Ok(DecoratorFlag::Value(Literal::default())),
+            "fuzz" => Ok(DecoratorFlag::Fuzz(String::default())),
+            "runs" => Ok(DecoratorFlag::Runs(Literal::default())),
+            "seed" => Ok(DecoratorFlag::Seed(Literal::default())),
+            "only_caller" => Ok(DecoratorFlag::OnlyCaller(String::default(), None)),
             _ => Err(()),
         }
     }
 }
+
+/// A pragma annotating a macro definition, i.e. `#pragma unsafe-stack`
+///
+/// Pragmas are placed on their own lines above the `#define` of the macro and its decorator.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MacroPragma {
+    /// `no-inline`: compiles the macro as an outlined function, like `#define fn`
+    NoInline,
+    /// `hot`: marks the macro as a hot path, inlining it at every invocation even if it's
+    /// defined as an outlined function
+    Hot,
+    /// `unsafe-stack`: skips the stack depth check and output analysis for the macro's code
+    UnsafeStack,
+    /// `auto-revert`: reverts calls matching none of the selectors of the main macro's dispatch,
+    /// rather than falling through into its first label
+    AutoRevert,
+    /// `nonreentrant`: locks a reentrancy guard while the macro's code executes, reverting if
+    /// it's already locked
+    Nonreentrant,
+}
+
+impl MacroPragma {
+    /// All macro pragmas
+    pub const ALL: [MacroPragma; 5] = [
+        MacroPragma::NoInline,
+        MacroPragma::Hot,
+        MacroPragma::UnsafeStack,
+        MacroPragma::AutoRevert,
+        MacroPragma::Nonreentrant,
+    ];
+}
+
+impl Display for MacroPragma {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MacroPragma::NoInline => write!(f, "no-inline"),
+            MacroPragma::Hot => write!(f, "hot"),
+            MacroPragma::UnsafeStack => write!(f, "unsafe-stack"),
+            MacroPragma::AutoRevert => write!(f, "auto-revert"),
+            MacroPragma::Nonreentrant => write!(f, "nonreentrant"),
+        }
+    }
+}
+
+impl TryFrom<&str> for MacroPragma {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        MacroPragma::ALL.into_iter().find(|p| p.to_string() == value).ok_or(())
+    }
+}
+
+/// The kind of a [SourcePragma]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SourcePragmaKind {
+    /// `evm-version`: the earliest hard fork the source can be compiled for
+    EvmVersion,
+    /// `huff-version`: the semver requirement on the compiler version
+    HuffVersion,
+    /// `edition`: the [Edition] the file is written in, declared as its first line
+    Edition,
+}
+
+impl SourcePragmaKind {
+    /// All source pragma kinds
+    pub const ALL: [SourcePragmaKind; 3] =
+        [SourcePragmaKind::EvmVersion, SourcePragmaKind::HuffVersion, SourcePragmaKind::Edition];
+}
+
+impl Display for SourcePragmaKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourcePragmaKind::EvmVersion => write!(f, "evm-version"),
+            SourcePragmaKind::HuffVersion => write!(f, "huff-version"),
+            SourcePragmaKind::Edition => write!(f, "edition"),
+        }
+    }
+}
+
+impl TryFrom<&str> for SourcePragmaKind {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        SourcePragmaKind::ALL.into_iter().find(|p| p.to_string() == value).ok_or(())
+    }
+}
+
+/// A pragma declaring a requirement of a source file, i.e. `#pragma evm-version "shanghai"` or
+/// `#pragma huff-version ">=0.3.1, <0.4"`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SourcePragma {
+    /// The kind of requirement
+    pub kind: SourcePragmaKind,
+    /// The required version
+    pub value: String,
+    /// The span of the pragma
+    pub span: AstSpan,
+}
+
+impl SourcePragma {
+    /// Checks the requirement against the version of the compiler and the targeted hard fork,
+    /// returning why they're incompatible if they are.
+    pub fn check(&self, huff_version: &str, evm_version: &EVMVersion) -> Result<(), String> {
+        match self.kind {
+            SourcePragmaKind::EvmVersion => {
+                let required = EVMVersion::from_str(&self.value)?;
+                if *evm_version < required {
+                    return Err(format!(
+                        "Source requires EVM version {required} or later, but {evm_version} is targeted. Target it with `--evm-version {required}`"
+                    ))
+                }
+            }
+            SourcePragmaKind::HuffVersion => {
+                let required = semver::VersionReq::parse(&self.value).map_err(|e| {
+                    format!("Invalid huff version requirement \"{}\": {e}", self.value)
+                })?;
+                let version = semver::Version::parse(huff_version)
+                    .map_err(|e| format!("Invalid compiler version \"{huff_version}\": {e}"))?;
+                if !required.matches(&version) {
+                    return Err(format!(
+                        "Source requires huff version {}, but the compiler is version {huff_version}",
+                        self.value
+                    ))
+                }
+            }
+            // Editions are applied while lexing and parsing
+            SourcePragmaKind::Edition => {
+                Edition::from_str(&self.value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A `// huff-ignore: <warning>, ...` comment silencing warnings of the code it follows, i.e.
+/// `call pop // huff-ignore: unchecked-call`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Suppression {
+    /// The names of the silenced warnings
+    pub warnings: Vec<String>,
+    /// The span of the token the comment follows
+    pub span: Span,
+}
+
+impl Suppression {
+    /// The prefix of a suppression comment's text
+    pub const PREFIX: &'static str = "huff-ignore:";
+
+    /// Parses a line or block comment, returning the suppression it declares for the token
+    /// preceding it, if any
+    pub fn from_comment(comment: &str, span: Span) -> Option<Self> {
+        let text = match comment.strip_prefix("//") {
+            Some(text) => text,
+            None => comment.strip_prefix("/*")?.strip_suffix("*/")?,
+        };
+        let warnings = text
+            .trim()
+            .strip_prefix(Self::PREFIX)?
+            .split(',')
+            .map(|w| w.trim().to_string())
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<String>>();
+        (!warnings.is_empty()).then_some(Self { warnings, span })
+    }
+
+    /// Whether the suppression silences the warning
+    pub fn silences(&self, warning: &CompilerWarning) -> bool {
+        self.warnings.iter().any(|w| w == warning.kind.name()) &&
+            warning.span.0.contains(&self.span)
+    }
+}
+
+/// An AST Visitor
+///
+/// Every method defaults to walking the node's children through the matching `walk_*` function,
+/// so implementors only override the nodes they care about. An overriding method can call the
+/// `walk_*` function itself to keep descending.
+///
+/// Constants live behind the [Contract]'s mutex, so they are only borrowed for the duration of
+/// [visit_constant](Visitor::visit_constant).
+///
+/// ```rust
+/// use huff_utils::prelude::*;
+///
+/// #[derive(Default)]
+/// struct LabelCounter(usize);
+///
+/// impl<'ast> Visitor<'ast> for LabelCounter {
+///     fn visit_label(&mut self, label: &'ast Label) {
+///         self.0 += 1;
+///         walk_label(self, label);
+///     }
+/// }
+///
+/// let mut counter = LabelCounter::default();
+/// counter.visit_contract(&Contract::default());
+/// assert_eq!(counter.0, 0);
+/// ```
+pub trait Visitor<'ast> {
+    /// Visits a contract, the root of the AST
+    fn visit_contract(&mut self, contract: &'ast Contract) {
+        walk_contract(self, contract);
+    }
+
+    /// Visits a macro, function or test definition
+    fn visit_macro(&mut self, macro_def: &'ast MacroDefinition) {
+        walk_macro(self, macro_def);
+    }
+
+    /// Visits a statement within a macro, label or table
+    fn visit_statement(&mut self, statement: &'ast Statement) {
+        walk_statement(self, statement);
+    }
+
+    /// Visits a macro invocation
+    fn visit_macro_invocation(&mut self, invocation: &'ast MacroInvocation) {
+        walk_macro_invocation(self, invocation);
+    }
+
+    /// Visits an argument passed to a macro invocation
+    fn visit_macro_arg(&mut self, arg: &'ast MacroArg) {
+        walk_macro_arg(self, arg);
+    }
+
+    /// Visits a label definition
+    fn visit_label(&mut self, label: &'ast Label) {
+        walk_label(self, label);
+    }
+
+    /// Visits a builtin function call
+    fn visit_builtin_function_call(&mut self, _call: &'ast BuiltinFunctionCall) {}
+
+    /// Visits a constant definition
+    fn visit_constant(&mut self, _constant: &ConstantDefinition) {}
+
+    /// Visits a function interface
+    fn visit_function(&mut self, _function: &'ast Function) {}
+
+    /// Visits an event interface
+    fn visit_event(&mut self, _event: &'ast Event) {}
+
+    /// Visits a custom error definition
+    fn visit_error(&mut self, _error: &'ast ErrorDefinition) {}
+
+    /// Visits a table definition
+    fn visit_table(&mut self, table: &'ast TableDefinition) {
+        walk_table(self, table);
+    }
+}
+
+/// Walks every definition within a contract
+pub fn walk_contract<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, contract: &'ast Contract) {
+    if let Ok(constants) = contract.constants.lock() {
+        constants.iter().for_each(|c| visitor.visit_constant(c));
+    }
+    contract.functions.iter().for_each(|f| visitor.visit_function(f));
+    contract.events.iter().for_each(|e| visitor.visit_event(e));
+    contract.errors.iter().for_each(|e| visitor.visit_error(e));
+    contract.tables.iter().for_each(|t| visitor.visit_table(t));
+    contract.macros.iter().for_each(|m| visitor.visit_macro(m));
+    contract.invocations.iter().for_each(|mi| visitor.visit_macro_invocation(mi));
+}
+
+/// Walks the statements of a macro
+pub fn walk_macro<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    macro_def: &'ast MacroDefinition,
+) {
+    macro_def.statements.iter().for_each(|s| visitor.visit_statement(s));
+}
+
+/// Walks the invocation, label or builtin function call within a statement
+pub fn walk_statement<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    statement: &'ast Statement,
+) {
+    match &statement.ty {
+        StatementType::MacroInvocation(mi) => visitor.visit_macro_invocation(mi),
+        StatementType::Label(l) => visitor.visit_label(l),
+        StatementType::BuiltinFunctionCall(bf) => visitor.visit_builtin_function_call(bf),
+        _ => {}
+    }
+}
+
+/// Walks the arguments of a macro invocation
+pub fn walk_macro_invocation<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    invocation: &'ast MacroInvocation,
+) {
+    invocation.args.iter().for_each(|arg| visitor.visit_macro_arg(arg));
+}
+
+/// Walks the macro invocation passed as an argument, if any
+pub fn walk_macro_arg<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, arg: &'ast MacroArg) {
+    if let MacroArg::MacroInvocation(mi) = arg {
+        visitor.visit_macro_invocation(mi);
+    }
+}
+
+/// Walks the statements within a label
+pub fn walk_label<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, label: &'ast Label) {
+    label.inner.iter().for_each(|s| visitor.visit_statement(s));
+}
+
+/// Walks the statements of a table
+pub fn walk_table<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, table: &'ast TableDefinition) {
+    table.statements.iter().for_each(|s| visitor.visit_statement(s));
+}
@@ -0,0 +1,272 @@
+use crate::{
+    ast::{
+        Argument, AstSpan, BuiltinFunctionCall, BuiltinFunctionKind, ConstVal, ConstantDefinition,
+        Contract, Decorator, ErrorDefinition, Event, FilePath, Function, Label, Literal, MacroArg,
+        MacroDefinition, MacroInvocation, MacroPragma, Statement, StatementType, TableDefinition,
+    },
+    evm::Opcode,
+};
+use std::sync::{Arc, Mutex};
+
+/// Builds a [MacroDefinition] without going through the lexer and parser.
+///
+/// Statements added after a [label](MacroDefinitionBuilder::label) are placed inside of it,
+/// the same way the parser nests the statements following a label.
+///
+/// ```
+/// use huff_utils::prelude::*;
+///
+/// let main = MacroDefinitionBuilder::new("MAIN")
+///     .literal(str_to_bytes32("01"))
+///     .label_call("done")
+///     .opcode(Opcode::Jumpi)
+///     .label("done")
+///     .opcode(Opcode::Stop)
+///     .build();
+/// assert_eq!(main.statements.len(), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MacroDefinitionBuilder {
+    name: String,
+    decorator: Option<Decorator>,
+    parameters: Vec<Argument>,
+    statements: Vec<Statement>,
+    takes: usize,
+    returns: usize,
+    outputs: Vec<String>,
+    pragmas: Vec<MacroPragma>,
+    outlined: bool,
+    test: bool,
+}
+
+impl MacroDefinitionBuilder {
+    /// Public associated function to instantiate a new builder for a macro named `name`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            decorator: None,
+            parameters: vec![],
+            statements: vec![],
+            takes: 0,
+            returns: 0,
+            outputs: vec![],
+            pragmas: vec![],
+            outlined: false,
+            test: false,
+        }
+    }
+
+    /// Sets the number of stack items the macro takes.
+    pub fn takes(mut self, takes: usize) -> Self {
+        self.takes = takes;
+        self
+    }
+
+    /// Sets the number of stack items the macro returns.
+    pub fn returns(mut self, returns: usize) -> Self {
+        self.returns = returns;
+        self
+    }
+
+    /// Names the stack items the macro returns, top of the stack first, setting their number.
+    pub fn outputs<S: Into<String>>(mut self, outputs: impl IntoIterator<Item = S>) -> Self {
+        self.outputs = outputs.into_iter().map(Into::into).collect();
+        self.returns = self.outputs.len();
+        self
+    }
+
+    /// Adds a named macro parameter.
+    pub fn parameter(mut self, name: impl Into<String>) -> Self {
+        self.parameters.push(Argument { name: Some(name.into()), ..Default::default() });
+        self
+    }
+
+    /// Sets the macro's decorator.
+    pub fn decorator(mut self, decorator: Decorator) -> Self {
+        self.decorator = Some(decorator);
+        self
+    }
+
+    /// Marks the macro as an outlined function (`#define fn`).
+    pub fn outlined(mut self) -> Self {
+        self.outlined = true;
+        self
+    }
+
+    /// Annotates the macro with a pragma, i.e. `#pragma no-inline`.
+    pub fn pragma(mut self, pragma: MacroPragma) -> Self {
+        if !self.pragmas.contains(&pragma) {
+            self.pragmas.push(pragma);
+        }
+        self
+    }
+
+    /// Marks the macro as a test (`#define test`).
+    pub fn test(mut self) -> Self {
+        self.test = true;
+        self
+    }
+
+    /// Adds a statement, nesting it in the last label if there is one.
+    pub fn statement(mut self, ty: StatementType) -> Self {
+        let statement = Statement { ty, span: AstSpan::default() };
+        match self.statements.last_mut() {
+            Some(Statement { ty: StatementType::Label(label), .. }) => label.inner.push(statement),
+            _ => self.statements.push(statement),
+        }
+        self
+    }
+
+    /// Adds an opcode.
+    pub fn opcode(self, opcode: Opcode) -> Self {
+        self.statement(StatementType::Opcode(opcode))
+    }
+
+    /// Adds a literal, pushed with the smallest push opcode that fits it.
+    pub fn literal(self, literal: Literal) -> Self {
+        self.statement(StatementType::Literal(literal))
+    }
+
+    /// Adds a constant push, `[NAME]`.
+    pub fn constant(self, name: impl Into<String>) -> Self {
+        self.statement(StatementType::Constant(name.into()))
+    }
+
+    /// Adds a macro argument call, `<name>`.
+    pub fn arg_call(self, name: impl Into<String>) -> Self {
+        self.statement(StatementType::ArgCall(name.into()))
+    }
+
+    /// Adds a macro invocation.
+    pub fn invoke(self, name: impl Into<String>, args: Vec<MacroArg>) -> Self {
+        self.statement(StatementType::MacroInvocation(MacroInvocation {
+            macro_name: name.into(),
+            args,
+            span: AstSpan::default(),
+        }))
+    }
+
+    /// Adds a builtin function call.
+    pub fn builtin(self, kind: BuiltinFunctionKind, args: Vec<Argument>) -> Self {
+        self.statement(StatementType::BuiltinFunctionCall(BuiltinFunctionCall {
+            kind,
+            args,
+            span: AstSpan::default(),
+        }))
+    }
+
+    /// Adds a reference to a label, pushing its offset.
+    pub fn label_call(self, name: impl Into<String>) -> Self {
+        self.statement(StatementType::LabelCall(name.into()))
+    }
+
+    /// Starts a label, the statements added after it are placed inside of it.
+    pub fn label(mut self, name: impl Into<String>) -> Self {
+        self.statements.push(Statement {
+            ty: StatementType::Label(Label {
+                name: name.into(),
+                inner: vec![],
+                span: AstSpan::default(),
+            }),
+            span: AstSpan::default(),
+        });
+        self
+    }
+
+    /// Builds the [MacroDefinition].
+    pub fn build(self) -> MacroDefinition {
+        let mut macro_def = MacroDefinition::new(
+            self.name,
+            self.decorator,
+            self.parameters,
+            self.statements,
+            self.takes,
+            self.returns,
+            vec![],
+            self.outlined,
+            self.test,
+        );
+        macro_def.outputs = self.outputs;
+        macro_def.pragmas = self.pragmas;
+        macro_def
+    }
+}
+
+/// Builds a [Contract] without going through the lexer and parser.
+///
+/// ```
+/// use huff_utils::prelude::*;
+///
+/// let contract = ContractBuilder::new()
+///     .constant("OWNER", ConstVal::Literal(str_to_bytes32("dead")))
+///     .macro_definition(MacroDefinitionBuilder::new("MAIN").constant("OWNER").build())
+///     .build();
+/// assert!(contract.find_macro_by_name("MAIN").is_some());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ContractBuilder {
+    contract: Contract,
+    constants: Vec<ConstantDefinition>,
+}
+
+impl ContractBuilder {
+    /// Public associated function to instantiate a new builder for an empty contract.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a macro definition.
+    pub fn macro_definition(mut self, macro_def: MacroDefinition) -> Self {
+        self.contract.macros.push(macro_def);
+        self
+    }
+
+    /// Adds a constant definition.
+    pub fn constant(mut self, name: impl Into<String>, value: ConstVal) -> Self {
+        self.constants.push(ConstantDefinition {
+            name: name.into(),
+            ty: None,
+            value,
+            span: AstSpan::default(),
+        });
+        self
+    }
+
+    /// Adds a function definition.
+    pub fn function(mut self, function: Function) -> Self {
+        self.contract.functions.push(function);
+        self
+    }
+
+    /// Adds an event definition.
+    pub fn event(mut self, event: Event) -> Self {
+        self.contract.events.push(event);
+        self
+    }
+
+    /// Adds a custom error definition.
+    pub fn error(mut self, error: ErrorDefinition) -> Self {
+        self.contract.errors.push(error);
+        self
+    }
+
+    /// Adds a table definition.
+    pub fn table(mut self, table: TableDefinition) -> Self {
+        self.contract.tables.push(table);
+        self
+    }
+
+    /// Adds a file import.
+    pub fn import(mut self, path: impl Into<FilePath>) -> Self {
+        self.contract.imports.push(path.into());
+        self
+    }
+
+    /// Builds the [Contract], deriving its free storage pointers.
+    pub fn build(self) -> Contract {
+        let mut contract = self.contract;
+        contract.constants = Arc::new(Mutex::new(self.constants));
+        contract.derive_storage_pointers();
+        contract
+    }
+}
@@ -2,15 +2,127 @@
 //!
 //! Abstract translating state into bytecode.
 
-use crate::prelude::{AstSpan, Statement, TableDefinition};
+use crate::prelude::{
+    str_to_vec, AstSpan, EofFunction, Opcode, Statement, Symbol, TableDefinition,
+};
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, BTreeSet},
     fmt::{self, Display},
 };
 
-/// A string of Bytes
+/// A single instruction of generated bytecode
+///
+/// Bytecode is generated as a list of instructions and only hex encoded once it's complete, so
+/// sizes and offsets are counted in bytes rather than derived from the length of hex strings.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Bytes(pub String);
+pub struct Instruction {
+    /// The opcode, `None` for raw bytes such as `__VERBATIM` data
+    pub opcode: Option<Opcode>,
+    /// The immediate bytes following the opcode, or the raw bytes if there is no opcode
+    pub immediate: Vec<u8>,
+    /// The number of placeholder bytes preceding the immediate, filled in once their value is
+    /// known
+    pub placeholder: usize,
+    /// The hex raw bytes were written as in the source, emitted in place of the bytes to keep
+    /// the case it was written in
+    pub written: Option<String>,
+    /// The span of the statement the instruction was generated from
+    pub source_span: AstSpan,
+}
+
+impl Instruction {
+    /// An opcode without immediate bytes
+    pub fn op(opcode: Opcode, source_span: AstSpan) -> Self {
+        Self { opcode: Some(opcode), immediate: vec![], placeholder: 0, written: None, source_span }
+    }
+
+    /// A `PUSH` of exactly the given bytes
+    ///
+    /// Falls back to raw bytes if there is no `PUSH` opcode of that size, which the parser
+    /// prevents for literals.
+    pub fn push(immediate: Vec<u8>, source_span: AstSpan) -> Self {
+        match Opcode::push_n(immediate.len()) {
+            Some(opcode) => Self { immediate, ..Self::op(opcode, source_span) },
+            None => Self::data(immediate, source_span),
+        }
+    }
+
+    /// The smallest `PUSH` placing `value` on the stack
+    ///
+    /// Leading zero bytes are stripped, i.e. `0x0001` becomes `PUSH1 0x01`.
+    pub fn push_value(value: &[u8], source_span: AstSpan) -> Self {
+        let start = value.iter().position(|b| *b != 0).unwrap_or(value.len().saturating_sub(1));
+        let immediate = if value.is_empty() { vec![0] } else { value[start..].to_vec() };
+        Self::push(immediate, source_span)
+    }
+
+    /// A `PUSH` of `width` bytes whose value is filled in later
+    pub fn push_placeholder(width: usize, source_span: AstSpan) -> Self {
+        Self {
+            opcode: Opcode::push_n(width),
+            immediate: vec![],
+            placeholder: width,
+            written: None,
+            source_span,
+        }
+    }
+
+    /// Raw bytes, emitted as they are
+    pub fn data(bytes: Vec<u8>, source_span: AstSpan) -> Self {
+        Self { opcode: None, immediate: bytes, placeholder: 0, written: None, source_span }
+    }
+
+    /// Raw bytes written as hex in the source, i.e. code table entries, emitted as they were
+    /// written
+    ///
+    /// Returns `None` if `hex` isn't valid hex.
+    pub fn code(hex: &str, source_span: AstSpan) -> Option<Self> {
+        let bytes = str_to_vec(hex).ok()?;
+        Some(Self { written: Some(hex.to_string()), ..Self::data(bytes, source_span) })
+    }
+
+    /// Raw bytes whose first `width` bytes are filled in later
+    pub fn data_placeholder(width: usize, bytes: Vec<u8>, source_span: AstSpan) -> Self {
+        Self { placeholder: width, ..Self::data(bytes, source_span) }
+    }
+
+    /// The size of the instruction in bytes
+    pub fn len(&self) -> usize {
+        usize::from(self.opcode.is_some()) + self.placeholder + self.immediate.len()
+    }
+
+    /// Whether the instruction doesn't take up any bytes
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Fills the placeholder of the instruction, returning `false` if `value` isn't as wide as the
+    /// placeholder
+    pub fn fill(&mut self, value: &[u8]) -> bool {
+        if self.placeholder == 0 || value.len() != self.placeholder {
+            return false
+        }
+        self.immediate = [value, &self.immediate].concat();
+        self.placeholder = 0;
+        self.written = None;
+        true
+    }
+}
+
+impl Display for Instruction {
+    /// Hex encodes the instruction, with unfilled placeholder bytes encoded as `xx`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let (None, 0, Some(written)) = (self.opcode, self.placeholder, &self.written) {
+            return write!(f, "{written}")
+        }
+        if let Some(opcode) = self.opcode {
+            write!(f, "{opcode}")?;
+        }
+        write!(f, "{}", "xx".repeat(self.placeholder))?;
+        self.immediate.iter().try_for_each(|b| write!(f, "{b:02x}"))
+    }
+}
 
 /// Intermediate Bytecode Representation
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -24,8 +136,8 @@ pub struct IRBytes {
 /// IRBytes Type
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum IRByteType {
-    /// Bytes
-    Bytes(Bytes),
+    /// A generated instruction
+    Instruction(Instruction),
     /// Macro Statement to be expanded
     Statement(Statement),
     /// A Constant to be referenced
@@ -58,26 +170,59 @@ pub trait ToBytecode<'a, E> {
     fn to_bytecode(&self) -> Result<Bytecode, E>;
 }
 
-impl From<Vec<Bytes>> for Bytecode {
-    fn from(b: Vec<Bytes>) -> Self {
-        Bytecode(b.iter().fold("".to_string(), |acc, b| format!("{acc}{}", b.0)))
+impl From<Vec<Instruction>> for Bytecode {
+    fn from(instructions: Vec<Instruction>) -> Self {
+        Bytecode(instructions.iter().map(|i| i.to_string()).collect())
     }
 }
 
 /// Result type for [huff_codegen](../../huff_codegen)'s
-/// [`recurse_bytecode`](../../huff_codegen/src/lib.rs#recurse_bytecode)
+/// [`macro_to_bytecode`](../../huff_codegen/src/lib.rs#macro_to_bytecode)
 #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct BytecodeRes {
-    /// Resulting bytes
-    pub bytes: Vec<(usize, Bytes)>,
+    /// Resulting instructions and their bytecode offsets
+    pub instructions: Vec<(usize, Instruction)>,
     /// Jump Indices
     pub label_indices: LabelIndices,
-    /// Unmatched Jumps
-    pub unmatched_jumps: Jumps,
-    /// Table Instances
-    pub table_instances: Jumps,
+    /// Placeholders to patch with label and table offsets
+    pub relocations: Relocations,
     /// Utilized Tables
     pub utilized_tables: Vec<TableDefinition>,
+    /// The bytecode generated by each macro invocation, children before their parents
+    pub activations: Vec<MacroActivation>,
+    /// The functions called with `callf`, each generated into a code section of its own for EOF
+    /// containers rather than appended to the bytecode
+    pub functions: Vec<FunctionSection>,
+}
+
+/// The bytecode of a function generated into a code section of its own, for EOF containers
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FunctionSection {
+    /// The name of the function
+    pub name: String,
+    /// The number of stack items the function takes
+    pub inputs: usize,
+    /// The number of stack items the function returns
+    pub outputs: usize,
+    /// The bytecode of the function, at offsets relative to the start of its section
+    pub res: BytecodeRes,
+}
+
+impl BytecodeRes {
+    /// The size of the generated bytecode in bytes
+    pub fn size(&self) -> usize {
+        self.instructions.iter().map(|(_, i)| i.len()).sum()
+    }
+
+    /// The sum of the static gas costs of the generated opcodes
+    pub fn static_gas(&self) -> u64 {
+        self.instructions.iter().filter_map(|(_, i)| i.opcode).map(|o| o.static_gas()).sum()
+    }
+
+    /// The jumps to labels that haven't been defined
+    pub fn unmatched_jumps(&self) -> impl Iterator<Item = &Relocation> {
+        self.relocations.iter().filter(|r| r.kind == RelocationKind::Label && r.target.is_none())
+    }
 }
 
 impl Display for BytecodeRes {
@@ -85,38 +230,118 @@ impl Display for BytecodeRes {
         write!(
             f,
             r#"BytecodeRes(
-            bytes: [{}],
+            instructions: [{}],
             label_indices: {:?},
-            unmatched_jumps: {:?}
-            table_instances: {:?}
+            relocations: {:?}
         )"#,
-            self.bytes.iter().fold("".to_string(), |acc, b| format!("{acc}{}", b.0)),
+            self.instructions.iter().map(|(_, i)| i.to_string()).collect::<String>(),
             self.label_indices,
-            self.unmatched_jumps,
-            self.table_instances
+            self.relocations
         )
     }
 }
 
-/// A Jump
+/// Generated bytecode split into its code and the data appended to it, i.e. the tables in use
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BytecodeSections {
+    /// The hex encoded code
+    pub code: String,
+    /// The code sections of the functions called with `callf`, only generated for EOF containers
+    pub functions: Vec<EofFunction>,
+    /// The hex encoded data following the code
+    pub data: String,
+}
+
+impl fmt::Display for BytecodeSections {
+    /// The code followed by the data, as laid out in legacy bytecode, which has no function
+    /// sections
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.code, self.data)
+    }
+}
+
+/// The bytecode generated by an invocation of a macro
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MacroActivation {
+    /// The invoked macro
+    #[serde(rename = "macro")]
+    pub name: String,
+    /// The offset of the first byte generated by the invocation
+    pub start: usize,
+    /// The offset after the last byte generated by the invocation, including the bytes of the
+    /// macros it invokes
+    pub end: usize,
+    /// The labels defined by the invocation, mapped to their offsets
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, usize>,
+}
+
+/// What a [Relocation] refers to
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RelocationKind {
+    /// A jump label, resolved against the labels of the macro it's used in and its parents
+    #[default]
+    Label,
+    /// A table, resolved once the tables are appended to the bytecode
+    Table,
+}
+
+/// A placeholder in the bytecode that is patched with the offset of a label or table
+///
+/// Relocations are collected while generating bytecode, bound to their targets as labels are
+/// resolved, and patched in a final pass over the generated bytes.
 #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Jump {
-    /// Jump's Label
-    pub label: String,
-    /// Index of jump within bytecode
-    pub bytecode_index: usize,
-    /// The Jump Span
+pub struct Relocation {
+    /// What the relocation refers to
+    pub kind: RelocationKind,
+    /// The label or table whose offset is patched in
+    pub label: Symbol,
+    /// The bytecode offset of the placeholder
+    pub offset: usize,
+    /// The width of the placeholder in bytes
+    pub width: usize,
+    /// The offset of the label or table, once resolved
+    pub target: Option<usize>,
+    /// The span of the reference
     pub span: AstSpan,
+    /// The number of macro expansions the jump has to leave before it's bound to a label, for
+    /// labels passed as macro arguments, which belong to the macro passing them
+    pub pending_scopes: usize,
+    /// Whether the offset is subtracted from the one of the other relocation of the placeholder,
+    /// i.e. the start of a `__CODE_SLICE`, rather than patched in
+    pub negated: bool,
+    /// Whether the offset is patched in relative to the end of the placeholder as a signed
+    /// integer, i.e. the destination of an `rjump`
+    pub relative: bool,
 }
 
-/// Type for a vec of `Jump`s
-pub type Jumps = Vec<Jump>;
+impl Relocation {
+    /// A jump to `label`, patched into the immediate of the `PUSH2` at `push_offset`
+    pub fn label(label: impl Into<Symbol>, push_offset: usize, span: AstSpan) -> Self {
+        Self {
+            kind: RelocationKind::Label,
+            label: label.into(),
+            offset: push_offset + 1,
+            width: 2,
+            target: None,
+            span,
+            pending_scopes: 0,
+            negated: false,
+            relative: false,
+        }
+    }
+
+    /// The start of `table`, patched into the immediate of the `PUSH2` at `push_offset`
+    pub fn table(table: impl Into<Symbol>, push_offset: usize, span: AstSpan) -> Self {
+        Self { kind: RelocationKind::Table, ..Relocation::label(table, push_offset, span) }
+    }
+}
 
-/// Type to map `Jump` labels to their bytecode indices
-pub type LabelIndices = BTreeMap<String, usize>;
+/// Type for a vec of `Relocation`s
+pub type Relocations = Vec<Relocation>;
 
-/// Typw to map circular_codesize labels to their bytecode indices
-pub type CircularCodeSizeIndices = BTreeSet<(String, usize)>;
+/// Type to map labels to their bytecode indices
+pub type LabelIndices = BTreeMap<Symbol, usize>;
 
-/// Type for a map of bytecode indexes to `Jumps`. Represents a Jump Table.
-pub type JumpTable = BTreeMap<usize, Jumps>;
+/// Typw to map circular_codesize labels to their bytecode indices
+pub type CircularCodeSizeIndices = BTreeSet<(Symbol, usize)>;
@@ -1,3 +1,4 @@
+use ethers_core::types::U256;
 use std::num::ParseIntError;
 
 use tiny_keccak::{Hasher, Keccak};
@@ -21,6 +22,39 @@ pub fn str_to_bytes32(s: &str) -> [u8; 32] {
     padded
 }
 
+/// Convert a decimal string, optionally in scientific notation i.e. `1e18`, to a `[u8; 32]`
+/// Returns `None` if the string isn't a decimal number or its value doesn't fit in 32 bytes.
+pub fn dec_str_to_bytes32(s: &str) -> Option<[u8; 32]> {
+    let (mantissa, exponent) = match s.split_once('e') {
+        Some((mantissa, exponent)) => (mantissa, U256::from_dec_str(exponent).ok()?),
+        None => (s, U256::zero()),
+    };
+    let value =
+        U256::from_dec_str(mantissa).ok()?.checked_mul(U256::from(10).checked_pow(exponent)?)?;
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    Some(bytes)
+}
+
+/// Convert a `[u8; 32]` to a usize
+/// Returns `None` if its value doesn't fit in a usize.
+pub fn bytes32_to_usize(bytes: &[u8; 32]) -> Option<usize> {
+    let value = U256::from_big_endian(bytes);
+    (value <= U256::from(usize::MAX)).then(|| value.as_usize())
+}
+
+/// Negate a `[u8; 32]` into its two's complement, i.e. 0x01 becomes 0xff..ff
+/// Returns `None` if the negated value is out of the range of an `int256`.
+pub fn negate_bytes32(bytes: &[u8; 32]) -> Option<[u8; 32]> {
+    let value = U256::from_big_endian(bytes);
+    if value > U256::one() << 255 {
+        return None
+    }
+    let mut negated = [0u8; 32];
+    (!value).overflowing_add(U256::one()).0.to_big_endian(&mut negated);
+    Some(negated)
+}
+
 /// Convert a `[u8; 32]` to a bytes string.
 pub fn bytes32_to_string(bytes: &[u8; 32], prefixed: bool) -> String {
     let mut s = String::default();
@@ -31,6 +65,16 @@ pub fn bytes32_to_string(bytes: &[u8; 32], prefixed: bool) -> String {
     format!("{}{s}", if prefixed { "0x" } else { "" })
 }
 
+/// Convert a `[u8; 32]` to the smallest `PUSH` instruction that places it on the stack.
+///
+/// Leading zero bytes are always stripped, i.e. `0x0001` becomes `PUSH1 0x01` (`6001`), since
+/// literals don't keep the width they're written with. Exact widths are emitted with an explicit
+/// `pushN` opcode.
+pub fn bytes32_to_push(bytes: &[u8; 32]) -> String {
+    let hex_literal = bytes32_to_string(bytes, false);
+    format!("{:02x}{hex_literal}", 95 + hex_literal.len() / 2)
+}
+
 /// Wrapper to convert a hex string to a usize.
 pub fn hex_to_usize(s: &str) -> Result<usize, ParseIntError> {
     usize::from_str_radix(s, 16)
@@ -68,3 +112,35 @@ pub fn hash_bytes(dest: &mut [u8], to_hash: &String) {
     hasher.update(to_hash.as_bytes());
     hasher.finalize(dest);
 }
+
+/// Computes the [EIP-1967](https://eips.ethereum.org/EIPS/eip-1967) storage slot of a name,
+/// `keccak256(name) - 1`
+pub fn eip1967_slot(name: &str) -> [u8; 32] {
+    let mut slot = [0u8; 32];
+    hash_bytes(&mut slot, &name.to_string());
+    decrement(&mut slot);
+    slot
+}
+
+/// Computes the [ERC-7201](https://eips.ethereum.org/EIPS/eip-7201) storage slot of a namespace,
+/// `keccak256(keccak256(namespace) - 1) & ~0xff`
+pub fn erc7201_slot(namespace: &str) -> [u8; 32] {
+    let inner = eip1967_slot(namespace);
+    let mut slot = [0u8; 32];
+    let mut hasher = Keccak::v256();
+    hasher.update(&inner);
+    hasher.finalize(&mut slot);
+    slot[31] = 0;
+    slot
+}
+
+/// Subtracts one from a big-endian `[u8; 32]`, wrapping at zero
+fn decrement(bytes: &mut [u8; 32]) {
+    for b in bytes.iter_mut().rev() {
+        let (value, borrow) = b.overflowing_sub(1);
+        *b = value;
+        if !borrow {
+            break
+        }
+    }
+}
@@ -0,0 +1,435 @@
+//! ## Constructor Arguments
+//!
+//! Parses constructor arguments against the input types of a contract's declared constructor.
+//!
+//! Arguments are either values passed as strings on the command line, a json array of values,
+//! or the already ABI-encoded arguments from a constructor arguments file.
+//!
+//! Json values are mapped onto the declared input types, i.e. a nested json array onto an array
+//! or tuple argument, and a value that doesn't match its type is reported along with its line and
+//! column in the json.
+//!
+//! ```rust
+//! use huff_utils::prelude::*;
+//!
+//! let args = ConstructorArgs::from_file_contents(r#"["0x646dB8ffC21e7ddc2B6327448dd9Fa560Df41087", [1, 2]]"#).unwrap();
+//! let inputs = vec![
+//!     FunctionParam { name: "owner".to_string(), kind: "address".into(), internal_type: None },
+//!     FunctionParam { name: "ids".to_string(), kind: "uint256[]".into(), internal_type: None },
+//! ];
+//! assert_eq!(args.tokenize(Some(&inputs)).unwrap().len(), 2);
+//! ```
+
+use crate::{abi::FunctionParam, bytes_util::str_to_vec, types::EToken};
+use ethers_core::abi::{
+    ethereum_types::U256,
+    token::{LenientTokenizer, Tokenizer},
+    ParamType, Token, Tokenizable,
+};
+use serde_json::Value;
+use std::{fmt, mem};
+
+/// Constructor arguments to encode after the deployed bytecode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstructorArgs {
+    /// Argument values, parsed against the constructor's input types
+    Values(Vec<String>),
+    /// A json array with a value per argument, kept as text to locate the values that don't match
+    /// their types
+    Json(String),
+    /// Already ABI-encoded arguments
+    Encoded(Vec<u8>),
+}
+
+impl Default for ConstructorArgs {
+    fn default() -> Self {
+        ConstructorArgs::Values(vec![])
+    }
+}
+
+impl ConstructorArgs {
+    /// Parses the contents of a constructor arguments file
+    ///
+    /// The file either holds a json array with a value per argument, or the hex of the
+    /// ABI-encoded arguments.
+    pub fn from_file_contents(contents: &str) -> Result<Self, String> {
+        let contents = contents.trim();
+        if contents.starts_with('[') {
+            return ConstructorArgs::from_json(contents)
+        }
+        let hex = contents.strip_prefix("0x").unwrap_or(contents);
+        str_to_vec(hex)
+            .map(ConstructorArgs::Encoded)
+            .map_err(|e| format!("Invalid ABI-encoded constructor arguments: {e}"))
+    }
+
+    /// Parses a json array with a value per argument, i.e. `["0xdead...", [1, 2, 3]]`
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        match serde_json::from_str::<Value>(json) {
+            Ok(Value::Array(_)) => Ok(ConstructorArgs::Json(json.to_string())),
+            Ok(_) => Err("Json constructor arguments must be an array of values".to_string()),
+            Err(e) => Err(format!("Invalid json constructor arguments: {e}")),
+        }
+    }
+
+    /// Whether there are no arguments to encode
+    pub fn is_empty(&self) -> bool {
+        match self {
+            ConstructorArgs::Values(values) => values.is_empty(),
+            ConstructorArgs::Json(json) => json_values(json).is_empty(),
+            ConstructorArgs::Encoded(encoded) => encoded.is_empty(),
+        }
+    }
+
+    /// Converts the arguments into tokens
+    ///
+    /// If the contract declares a constructor, the arguments are parsed against its input types.
+    /// Otherwise, the type of each value is inferred from its format.
+    pub fn tokenize(&self, inputs: Option<&[FunctionParam]>) -> Result<Vec<Token>, String> {
+        if self.is_empty() {
+            return Ok(vec![])
+        }
+        match (self, inputs) {
+            (ConstructorArgs::Values(values), Some(inputs)) => {
+                if values.len() != inputs.len() {
+                    return Err(format!(
+                        "Expected {} constructor arguments, got {}",
+                        inputs.len(),
+                        values.len()
+                    ))
+                }
+                values
+                    .iter()
+                    .zip(inputs)
+                    .enumerate()
+                    .map(|(i, (value, input))| {
+                        tokenize_value(&ParamType::from(&input.kind), value).map_err(|e| {
+                            let param = match input.name.is_empty() {
+                                true => format!("param {i}"),
+                                false => format!("param \"{}\"", input.name),
+                            };
+                            format!(
+                                "Invalid constructor argument \"{value}\" for {param} of type {}: {e}",
+                                input.kind
+                            )
+                        })
+                    })
+                    .collect()
+            }
+            (ConstructorArgs::Json(json), Some(inputs)) => {
+                let values = json_values(json);
+                if values.len() != inputs.len() {
+                    return Err(format!(
+                        "Expected {} constructor arguments, got {}",
+                        inputs.len(),
+                        values.len()
+                    ))
+                }
+                values
+                    .iter()
+                    .zip(inputs)
+                    .enumerate()
+                    .map(|(i, (value, input))| {
+                        tokenize_json(&ParamType::from(&input.kind), value).map_err(|e| {
+                            let param = match input.name.is_empty() {
+                                true => format!("param {i}"),
+                                false => format!("param \"{}\"", input.name),
+                            };
+                            e.located(json, i, &param)
+                        })
+                    })
+                    .collect()
+            }
+            (ConstructorArgs::Json(json), None) => json_values(json)
+                .iter()
+                .enumerate()
+                .map(|(i, value)| infer_json(value).map_err(|e| e.located(json, i, "value")))
+                .collect(),
+            (ConstructorArgs::Values(values), None) => values
+                .iter()
+                .map(|value| {
+                    EToken::try_from(value.clone())
+                        .map(|t| t.0)
+                        .map_err(|e| format!("Invalid constructor argument \"{value}\": {e}"))
+                })
+                .collect(),
+            (ConstructorArgs::Encoded(encoded), Some(inputs)) => {
+                let kinds = inputs.iter().map(|i| ParamType::from(&i.kind)).collect::<Vec<_>>();
+                ethers_core::abi::decode(&kinds, encoded).map_err(|e| {
+                    format!(
+                        "ABI-encoded constructor arguments don't match ({}): {}",
+                        inputs.iter().map(|i| i.kind.to_string()).collect::<Vec<_>>().join(", "),
+                        e
+                    )
+                })
+            }
+            (ConstructorArgs::Encoded(_), None) => Err(
+                "ABI-encoded constructor arguments require a declared constructor to decode them"
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+/// Parses a value against a type
+///
+/// Array and tuple values may be json arrays, with each element parsed against the inner type.
+fn tokenize_value(kind: &ParamType, value: &str) -> Result<Token, String> {
+    let elements = match kind {
+        ParamType::Array(_) | ParamType::FixedArray(..) | ParamType::Tuple(_) => {
+            json_elements(value)
+        }
+        _ => None,
+    };
+    match (kind, elements) {
+        (ParamType::Array(inner), Some(elements)) => Ok(Token::Array(
+            elements.iter().map(|e| tokenize_value(inner, e)).collect::<Result<_, _>>()?,
+        )),
+        (ParamType::FixedArray(inner, size), Some(elements)) => {
+            if elements.len() != *size {
+                return Err(format!("expected {size} elements, got {}", elements.len()))
+            }
+            Ok(Token::FixedArray(
+                elements.iter().map(|e| tokenize_value(inner, e)).collect::<Result<_, _>>()?,
+            ))
+        }
+        (ParamType::Tuple(inner), Some(elements)) => {
+            if elements.len() != inner.len() {
+                return Err(format!(
+                    "expected {} tuple members, got {}",
+                    inner.len(),
+                    elements.len()
+                ))
+            }
+            Ok(Token::Tuple(
+                inner
+                    .iter()
+                    .zip(&elements)
+                    .map(|(k, e)| tokenize_value(k, e))
+                    .collect::<Result<_, _>>()?,
+            ))
+        }
+        (ParamType::Uint(size), _) => {
+            let value = value.trim();
+            let uint = match value.strip_prefix("0x") {
+                Some(hex) => U256::from_str_radix(hex, 16).map_err(|e| e.to_string())?,
+                None => LenientTokenizer::tokenize_uint(value).map_err(|e| e.to_string())?.into(),
+            };
+            if uint.bits() > *size {
+                return Err(format!("value doesn't fit in {size} bits"))
+            }
+            Ok(Token::Uint(uint))
+        }
+        _ => LenientTokenizer::tokenize(kind, value.trim()).map_err(|e| e.to_string()),
+    }
+}
+
+/// A json value that doesn't match its type
+#[derive(Debug)]
+pub(crate) struct JsonError {
+    /// The indices of the value in the arrays enclosing it, from the outermost
+    path: Vec<usize>,
+    /// The type the value was parsed against, unless inferred from the value
+    kind: Option<ParamType>,
+    /// The json text of the value
+    value: String,
+    /// Why the value doesn't match its type
+    error: String,
+}
+
+impl JsonError {
+    fn new(kind: Option<&ParamType>, value: &Value, error: impl Into<String>) -> Self {
+        Self { path: vec![], kind: kind.cloned(), value: value.to_string(), error: error.into() }
+    }
+
+    /// The error of the value in the element at the index of the enclosing array
+    fn within(mut self, index: usize) -> Self {
+        self.path.insert(0, index);
+        self
+    }
+
+    /// Describes the error of the argument at the index of the json arguments, with the line and
+    /// column of the value in the json
+    fn located(self, json: &str, index: usize, param: &str) -> String {
+        let offset = locate(json, &[&[index], self.path.as_slice()].concat());
+        let line = json[..offset].matches('\n').count() + 1;
+        let column = json[..offset].rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+        let path = self.path.iter().map(|i| format!("[{i}]")).collect::<String>();
+        let kind = self.kind.map(|k| format!(" of type {k}")).unwrap_or_default();
+        format!(
+            "Invalid constructor argument {} for {param}{path}{kind} at line {line}, column {column}: {}",
+            self.value, self.error
+        )
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.iter().map(|i| format!("[{i}]")).collect::<String>();
+        write!(f, "{}{path}: {}", self.value, self.error)
+    }
+}
+
+/// Maps a json value onto a type
+///
+/// Arrays and tuples are json arrays of their elements, strings are taken as is, and integers are
+/// json numbers or, past 64 bits, strings. Any other type is parsed from a json string.
+fn tokenize_json(kind: &ParamType, value: &Value) -> Result<Token, JsonError> {
+    let elements = |values: &[Value], kinds: &mut dyn Iterator<Item = &ParamType>| {
+        values
+            .iter()
+            .zip(kinds)
+            .enumerate()
+            .map(|(i, (v, k))| tokenize_json(k, v).map_err(|e| e.within(i)))
+            .collect::<Result<Vec<Token>, JsonError>>()
+    };
+    match (kind, value) {
+        (ParamType::Array(inner), Value::Array(values)) => {
+            Ok(Token::Array(elements(values, &mut std::iter::repeat(inner.as_ref()))?))
+        }
+        (ParamType::FixedArray(inner, size), Value::Array(values)) => {
+            if values.len() != *size {
+                return Err(JsonError::new(
+                    Some(kind),
+                    value,
+                    format!("expected {size} elements, got {}", values.len()),
+                ))
+            }
+            Ok(Token::FixedArray(elements(values, &mut std::iter::repeat(inner.as_ref()))?))
+        }
+        (ParamType::Tuple(inner), Value::Array(values)) => {
+            if values.len() != inner.len() {
+                return Err(JsonError::new(
+                    Some(kind),
+                    value,
+                    format!("expected {} tuple members, got {}", inner.len(), values.len()),
+                ))
+            }
+            Ok(Token::Tuple(elements(values, &mut inner.iter())?))
+        }
+        (ParamType::String, Value::String(s)) => Ok(Token::String(s.clone())),
+        (_, Value::String(s)) => {
+            tokenize_value(kind, s).map_err(|e| JsonError::new(Some(kind), value, e))
+        }
+        (ParamType::Uint(_) | ParamType::Int(_), Value::Number(n)) if n.is_f64() => {
+            Err(JsonError::new(
+                Some(kind),
+                value,
+                "not an integer json number, pass large integers as strings",
+            ))
+        }
+        (
+            ParamType::Uint(_) | ParamType::Int(_) | ParamType::Bool,
+            Value::Number(_) | Value::Bool(_),
+        ) => tokenize_value(kind, &value.to_string())
+            .map_err(|e| JsonError::new(Some(kind), value, e)),
+        (ParamType::Array(_) | ParamType::FixedArray(..) | ParamType::Tuple(_), _) => {
+            Err(JsonError::new(Some(kind), value, "expected a json array"))
+        }
+        _ => Err(JsonError::new(Some(kind), value, "expected a json string")),
+    }
+}
+
+/// Infers the type of a json value
+///
+/// Numbers are integers, strings prefixed with `0x` are addresses or bytes, and other strings are
+/// taken as is. A json array is an array if all its elements are of the same kind, and a tuple
+/// otherwise.
+pub(crate) fn infer_json(value: &Value) -> Result<Token, JsonError> {
+    match value {
+        Value::Bool(b) => Ok(Token::Bool(*b)),
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(u), _) => Ok(Token::Uint(U256::from(u))),
+            (None, Some(i)) => Ok((i as i128).into_token()),
+            _ => Err(JsonError::new(
+                None,
+                value,
+                "not an integer json number, pass large integers as strings",
+            )),
+        },
+        Value::String(s) if s.starts_with("0x") => {
+            EToken::try_from(s.clone()).map(|t| t.0).map_err(|e| JsonError::new(None, value, e))
+        }
+        Value::String(s) => Ok(Token::String(s.clone())),
+        Value::Array(values) => {
+            let tokens = values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| infer_json(v).map_err(|e| e.within(i)))
+                .collect::<Result<Vec<Token>, JsonError>>()?;
+            let same =
+                tokens.windows(2).all(|w| mem::discriminant(&w[0]) == mem::discriminant(&w[1]));
+            Ok(if same { Token::Array(tokens) } else { Token::Tuple(tokens) })
+        }
+        Value::Null | Value::Object(_) => {
+            Err(JsonError::new(None, value, "expected a json array, number, string or boolean"))
+        }
+    }
+}
+
+/// The values of a json array, empty if it isn't one
+fn json_values(json: &str) -> Vec<Value> {
+    match serde_json::from_str::<Value>(json) {
+        Ok(Value::Array(values)) => values,
+        _ => vec![],
+    }
+}
+
+/// The offset of the value at the indices of the nested arrays of a json text
+fn locate(json: &str, path: &[usize]) -> usize {
+    let bytes = json.as_bytes();
+    let skip_whitespace = |mut i: usize| {
+        while bytes.get(i).is_some_and(|b| b.is_ascii_whitespace()) {
+            i += 1;
+        }
+        i
+    };
+    // The offset following the value at the offset
+    let skip_value = |mut i: usize| {
+        let (mut depth, mut string, mut escaped) = (0, false, false);
+        while let Some(b) = bytes.get(i) {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' if string => escaped = true,
+                b'"' => string = !string,
+                _ if string => {}
+                b'[' | b'{' => depth += 1,
+                b']' | b'}' if depth == 0 => break,
+                b']' | b'}' => depth -= 1,
+                b',' if depth == 0 => break,
+                _ => {}
+            }
+            i += 1;
+            if depth == 0 && !string && matches!(b, b']' | b'}' | b'"') {
+                break
+            }
+        }
+        i
+    };
+
+    let mut i = skip_whitespace(0);
+    for index in path {
+        // Past the opening bracket, then each preceding element and its comma
+        i = skip_whitespace(i + 1);
+        for _ in 0..*index {
+            i = skip_whitespace(skip_whitespace(skip_value(i)) + 1);
+        }
+    }
+    i.min(json.len())
+}
+
+/// The elements of a json array, strings taken as is and other values as their json text
+fn json_elements(value: &str) -> Option<Vec<String>> {
+    match serde_json::from_str::<Value>(value) {
+        Ok(Value::Array(elements)) => Some(
+            elements
+                .into_iter()
+                .map(|v| match v {
+                    Value::String(s) => s,
+                    v => v.to_string(),
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
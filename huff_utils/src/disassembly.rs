@@ -0,0 +1,97 @@
+use crate::evm::{Opcode, OPCODES, OPCODES_MAP};
+use lazy_static::lazy_static;
+use std::fmt;
+
+lazy_static! {
+    /// Opcodes indexed by their byte, aliased opcodes resolve to their most recent name.
+    static ref OPCODES_BY_BYTE: Vec<Option<(&'static str, Opcode)>> = {
+        let mut table = vec![None; 256];
+        for name in OPCODES {
+            if let Some(opcode) = OPCODES_MAP.get(name) {
+                if let Ok(byte) = u8::from_str_radix(&opcode.string(), 16) {
+                    table[byte as usize] = Some((name, *opcode));
+                }
+            }
+        }
+        table
+    };
+}
+
+impl Opcode {
+    /// The opcode encoded as `byte`, aliased opcodes resolve to their most recent name
+    pub fn from_byte(byte: u8) -> Option<Opcode> {
+        OPCODES_BY_BYTE[byte as usize].map(|(_, o)| o)
+    }
+}
+
+/// A single disassembled operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisassembledOp {
+    /// The byte offset of the operation in the bytecode
+    pub offset: usize,
+    /// The raw opcode byte
+    pub byte: u8,
+    /// The decoded opcode, `None` if the byte isn't a known opcode
+    pub opcode: Option<Opcode>,
+    /// The immediate bytes of a push opcode
+    pub immediate: Vec<u8>,
+}
+
+impl DisassembledOp {
+    /// The mnemonic of the operation, unknown bytes are shown as `unknown(0x..)`
+    pub fn mnemonic(&self) -> String {
+        match OPCODES_BY_BYTE[self.byte as usize] {
+            Some((name, _)) => name.to_string(),
+            None => format!("unknown(0x{:02x})", self.byte),
+        }
+    }
+}
+
+impl fmt::Display for DisassembledOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#06x} {}", self.offset, self.mnemonic())?;
+        if !self.immediate.is_empty() {
+            write!(f, " 0x")?;
+            for b in &self.immediate {
+                write!(f, "{b:02x}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Disassembles hex encoded bytecode into its operations.
+///
+/// A push whose immediate runs past the end of the bytecode keeps the bytes that are present,
+/// matching how the EVM pads truncated immediates.
+pub fn disassemble(bytecode: &str) -> Result<Vec<DisassembledOp>, String> {
+    let bytecode = bytecode.strip_prefix("0x").unwrap_or(bytecode);
+    if bytecode.len() % 2 == 1 {
+        return Err(format!("Bytecode has an odd number of hex characters: {}", bytecode.len()))
+    }
+    let bytes = (0..bytecode.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&bytecode[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|e| format!("Invalid bytecode: {e}"))?;
+
+    let mut ops = vec![];
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let byte = bytes[offset];
+        let opcode = Opcode::from_byte(byte);
+        let immediate_size = match opcode {
+            Some(o) if o.is_push() => (byte - 0x5f) as usize,
+            _ => 0,
+        };
+        let end = (offset + 1 + immediate_size).min(bytes.len());
+        ops.push(DisassembledOp {
+            offset,
+            byte,
+            opcode,
+            immediate: bytes[offset + 1..end].to_vec(),
+        });
+        offset += 1 + immediate_size;
+    }
+    Ok(ops)
+}
@@ -0,0 +1,115 @@
+use std::{fmt, str::FromStr};
+
+/// Huff Language Editions
+///
+/// Syntax that could break existing sources is introduced in a new edition, so sources keep
+/// compiling with the edition they were written for. The edition is selected per project with the
+/// compiler's `--edition`, and per file with `#pragma edition "2022"` as its first line.
+///
+/// Ordered chronologically, such that an edition compares greater than all editions preceding it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Edition {
+    /// The original syntax
+    E2022,
+    /// Adds the [Feature]s of the 2024 edition
+    #[default]
+    E2024,
+}
+
+impl Edition {
+    /// All editions, oldest first
+    pub const ALL: [Edition; 2] = [Edition::E2022, Edition::E2024];
+
+    /// Whether the edition supports the given feature
+    pub fn supports(&self, feature: Feature) -> bool {
+        *self >= feature.edition()
+    }
+}
+
+impl FromStr for Edition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Edition::ALL.into_iter().find(|e| e.to_string() == s).ok_or_else(|| {
+            format!(
+                "Unsupported edition: \"{s}\", expected one of {}",
+                Edition::ALL.map(|e| e.to_string()).join(", ")
+            )
+        })
+    }
+}
+
+impl fmt::Display for Edition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Edition::E2022 => write!(f, "2022"),
+            Edition::E2024 => write!(f, "2024"),
+        }
+    }
+}
+
+/// Edition Gated Language Features
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Feature {
+    /// Underscores separating the digits of numbers, i.e. `1_000` or `0xdead_beef`
+    DigitSeparators,
+    /// Decimal numbers with an exponent, i.e. `1e18`
+    ScientificNotation,
+    /// Negated literals, i.e. `-0x01`
+    NegativeLiterals,
+    /// Named macro outputs, i.e. `returns (quotient, remainder)`
+    NamedOutputs,
+    /// Macro pragmas, i.e. `#pragma no-inline`
+    MacroPragmas,
+    /// `if`, `loop` and `switch` blocks in macro bodies
+    ControlFlowBlocks,
+    /// `asm` blocks of raw bytes in macro bodies
+    AsmBlocks,
+    /// Data sections appended to the runtime bytecode, i.e. `#define data NAME = 0x...`
+    DataSections,
+    /// Struct definitions generating ABI codec macros, i.e. `#define struct Order(address maker)`
+    StructDefinitions,
+    /// Merkle tree definitions generating proof verification macros, i.e.
+    /// `#define merkle AIRDROP(keccak256)`
+    MerkleTrees,
+    /// Rejecting labels, constants and macro arguments named after opcodes, which the 2022
+    /// edition compiles as the opcode at every reference
+    OpcodeNameCollisions,
+}
+
+impl Feature {
+    /// The edition introducing the feature
+    pub fn edition(&self) -> Edition {
+        match self {
+            Feature::DigitSeparators |
+            Feature::ScientificNotation |
+            Feature::NegativeLiterals |
+            Feature::NamedOutputs |
+            Feature::MacroPragmas |
+            Feature::ControlFlowBlocks |
+            Feature::AsmBlocks |
+            Feature::DataSections |
+            Feature::StructDefinitions |
+            Feature::MerkleTrees |
+            Feature::OpcodeNameCollisions => Edition::E2024,
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Feature::DigitSeparators => write!(f, "digit separators"),
+            Feature::ScientificNotation => write!(f, "scientific notation"),
+            Feature::NegativeLiterals => write!(f, "negative literals"),
+            Feature::NamedOutputs => write!(f, "named macro outputs"),
+            Feature::MacroPragmas => write!(f, "macro pragmas"),
+            Feature::ControlFlowBlocks => write!(f, "control flow blocks"),
+            Feature::AsmBlocks => write!(f, "asm blocks"),
+            Feature::DataSections => write!(f, "data sections"),
+            Feature::StructDefinitions => write!(f, "struct definitions"),
+            Feature::MerkleTrees => write!(f, "merkle trees"),
+            Feature::OpcodeNameCollisions => write!(f, "opcode name collision checks"),
+        }
+    }
+}
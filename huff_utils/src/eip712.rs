@@ -0,0 +1,168 @@
+//! ## EIP-712 Typed Data
+//!
+//! Computes the type hashes of [EIP-712](https://eips.ethereum.org/EIPS/eip-712) structs at
+//! compile time, i.e. for `__TYPEHASH("Permit(address owner,uint256 value)")`.
+//!
+//! A type hash is the hash of the struct's encoded type, its name followed by its members, then
+//! the structs it references sorted by name, i.e.
+//! `Mail(Person from,Person to,string contents)Person(string name,address wallet)`. As the hash
+//! of a misspelled type silently fails to verify signatures, the encoded type is checked to be
+//! canonical before it's hashed.
+//!
+//! ```rust
+//! use huff_utils::eip712::type_hash;
+//!
+//! assert!(type_hash("Mail(Person from,Person to,string contents)Person(string name,address wallet)").is_ok());
+//! assert!(type_hash("Permit(address owner, uint256 value)").is_err());
+//! ```
+
+use crate::prelude::{hash_bytes, PrimitiveEVMType, StructDefinition};
+
+/// The encoded type of the domain separated by `__DOMAIN_SEPARATOR`
+pub const DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+/// Computes the type hash of an encoded type, erroring if it isn't canonical
+pub fn type_hash(encode_type: &str) -> Result<[u8; 32], String> {
+    check_encode_type(encode_type)?;
+    let mut hash = [0u8; 32];
+    hash_bytes(&mut hash, &encode_type.to_string());
+    Ok(hash)
+}
+
+/// The encoded type of a struct defined with `#define struct`, its members being of elementary
+/// types or dynamic arrays of them
+pub fn struct_type(def: &StructDefinition) -> String {
+    let members = def
+        .fields
+        .iter()
+        .map(|f| {
+            let ty = f.arg_type.as_deref().unwrap_or_default();
+            let (base, suffix) = match ty.strip_suffix("[]") {
+                Some(base) => (base, "[]"),
+                None => (ty, ""),
+            };
+            let base = PrimitiveEVMType::try_from(base.to_string())
+                .map_or(base.to_string(), |t| t.to_string());
+            format!("{base}{suffix} {}", f.name.as_deref().unwrap_or_default())
+        })
+        .collect::<Vec<String>>();
+    format!("{}({})", def.name, members.join(","))
+}
+
+/// A struct of an encoded type, its name and the types and names of its members
+type EncodedStruct<'a> = (&'a str, Vec<(&'a str, &'a str)>);
+
+/// Checks that an encoded type is canonical: members are separated by commas without spaces, of
+/// elementary types or structs defined in the encoded type, and the structs referenced by the
+/// primary type follow it sorted by name.
+fn check_encode_type(encode_type: &str) -> Result<(), String> {
+    let mut structs: Vec<EncodedStruct> = vec![];
+    let mut rest = encode_type;
+    while structs.is_empty() || !rest.is_empty() {
+        let (name, members) = match rest.split_once('(') {
+            Some((name, tail)) => match tail.split_once(')') {
+                Some((members, tail)) => {
+                    rest = tail;
+                    (name, members)
+                }
+                None => {
+                    return Err(format!("Unclosed members of struct \"{name}\" in EIP-712 type"))
+                }
+            },
+            None => {
+                return Err(format!("Expected `Name(type name,...)` in EIP-712 type: \"{rest}\""))
+            }
+        };
+        if !is_identifier(name) {
+            return Err(format!("Invalid struct name \"{name}\" in EIP-712 type"))
+        }
+        let mut parsed = vec![];
+        for member in members.split(',').filter(|_| !members.is_empty()) {
+            match member.split_once(' ') {
+                Some((ty, field)) if !ty.is_empty() && is_identifier(field) => {
+                    parsed.push((ty, field))
+                }
+                _ => {
+                    return Err(format!(
+                        "Invalid member \"{member}\" of struct \"{name}\", members must be `type name` separated by commas without spaces"
+                    ))
+                }
+            }
+        }
+        structs.push((name, parsed));
+    }
+
+    // Member types are elementary, in their canonical form, or structs of the encoded type
+    for (name, members) in &structs {
+        for (ty, _) in members {
+            let base = base_type(ty);
+            let arrays = ty[base.len()..].split_terminator(']').all(|a| {
+                a.strip_prefix('[').is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+            });
+            if !arrays || !is_elementary(base) && !structs.iter().any(|(s, _)| *s == base) {
+                return Err(format!(
+                    "Type \"{ty}\" of struct \"{name}\" is neither a canonical elementary type nor a struct of the EIP-712 type"
+                ))
+            }
+        }
+    }
+
+    // The structs referenced by the primary type, directly or not, follow it sorted by name
+    let mut referenced: Vec<&str> = vec![];
+    let mut pending = vec![structs[0].0];
+    while let Some(name) = pending.pop() {
+        let members = structs.iter().find(|(s, _)| *s == name).map_or(&[][..], |(_, m)| m);
+        for (ty, _) in members {
+            let base = base_type(ty);
+            if structs.iter().any(|(s, _)| *s == base) &&
+                base != structs[0].0 &&
+                !referenced.contains(&base)
+            {
+                referenced.push(base);
+                pending.push(base);
+            }
+        }
+    }
+    referenced.sort_unstable();
+    let following = structs[1..].iter().map(|(s, _)| *s).collect::<Vec<&str>>();
+    if following != referenced {
+        return Err(format!(
+            "The structs referenced by \"{}\" must follow it sorted by name: {}",
+            structs[0].0,
+            if referenced.is_empty() { "none".to_string() } else { referenced.join(", ") }
+        ))
+    }
+    Ok(())
+}
+
+/// The type of the elements of an array type, i.e. `Person` for `Person[][2]`
+fn base_type(ty: &str) -> &str {
+    match ty.find('[') {
+        Some(i) => &ty[..i],
+        None => ty,
+    }
+}
+
+/// Whether a type is an elementary type in its canonical form, i.e. `uint256` rather than `uint`
+fn is_elementary(ty: &str) -> bool {
+    match PrimitiveEVMType::try_from(ty.to_string()) {
+        Ok(t) => {
+            t.to_string() == ty &&
+                match t {
+                    PrimitiveEVMType::Uint(size) | PrimitiveEVMType::Int(size) => {
+                        (8..=256).contains(&size) && size % 8 == 0
+                    }
+                    PrimitiveEVMType::Bytes(size) => (1..=32).contains(&size),
+                    _ => true,
+                }
+        }
+        Err(_) => false,
+    }
+}
+
+/// Whether a name is a Solidity identifier
+fn is_identifier(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$') &&
+        name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
@@ -0,0 +1,338 @@
+//! ## EOF Containers
+//!
+//! Encodes and validates [EIP-3540](https://eips.ethereum.org/EIPS/eip-3540) EVM Object Format
+//! containers, separating the code of a contract from its data:
+//!
+//! ```text
+//! ef00 01 | 01 <code size> | 02 <data size> | 00 | <code> <data>
+//! ```
+//!
+//! The data section is omitted from the header when there's no data. Functions called with
+//! `callf` have code sections of their own, following the code section of the contract, along
+//! with a type section recording how many stack items each code section takes and returns, as
+//! in [EIP-4750](https://eips.ethereum.org/EIPS/eip-4750):
+//!
+//! ```text
+//! ef00 01 | 03 <types size> | 01 <code size> | 01 <function size>... | 02 <data size> | 00 |
+//!     <types> <code> <function>... <data>
+//! ```
+//!
+//! The code sections are validated per [EIP-3670](https://eips.ethereum.org/EIPS/eip-3670): they
+//! may only contain defined instructions, and must not end in the middle of an immediate. The
+//! relative jumps of [EIP-4200](https://eips.ethereum.org/EIPS/eip-4200) must land on an
+//! instruction of their section, and `callf` must call an existing section.
+//!
+//! Within a container, `pc` and jump destinations are offsets in the code section, while
+//! `codecopy` and `codesize` operate on the whole container.
+//!
+//! The relative jumps and functions use the opcodes EOF was eventually specified with, as the
+//! ones originally proposed are now taken by `tload` and `tstore`.
+
+use crate::{disassembly::DisassembledOp, evm::Opcode};
+use std::fmt;
+
+/// The magic bytes every container starts with
+pub const EOF_MAGIC: [u8; 2] = [0xef, 0x00];
+
+/// The version of the containers
+pub const EOF_VERSION: u8 = 0x01;
+
+/// The kind of the header of a code section
+pub const CODE_SECTION_KIND: u8 = 0x01;
+
+/// The kind of the header of the data section
+pub const DATA_SECTION_KIND: u8 = 0x02;
+
+/// The kind of the header of the type section
+pub const TYPE_SECTION_KIND: u8 = 0x03;
+
+/// The byte terminating the section headers
+pub const HEADER_TERMINATOR: u8 = 0x00;
+
+/// The maximum size of a section, as its header stores it in two bytes
+pub const MAX_SECTION_SIZE: usize = 0xffff;
+
+/// The maximum number of code sections of a container
+pub const MAX_CODE_SECTIONS: usize = 1024;
+
+/// The maximum number of stack items a function takes or returns
+pub const MAX_FUNCTION_STACK_ITEMS: u8 = 0x7f;
+
+/// The instructions only defined in EOF code, by name
+pub const EOF_OPCODES: [(&str, Opcode); 4] = [
+    ("rjump", Opcode::Rjump),
+    ("rjumpi", Opcode::Rjumpi),
+    ("callf", Opcode::Callf),
+    ("retf", Opcode::Retf),
+];
+
+/// The code section of a function called with `callf`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EofFunction {
+    /// The number of stack items the function takes
+    pub inputs: u8,
+    /// The number of stack items the function returns
+    pub outputs: u8,
+    /// The code of the function, ending with `retf`
+    pub code: Vec<u8>,
+}
+
+/// An EOF container of a code section, the code sections of its functions and an optional data
+/// section
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EofContainer {
+    /// The code section
+    pub code: Vec<u8>,
+    /// The code sections of the functions, in the order `callf` indexes them from 1
+    pub functions: Vec<EofFunction>,
+    /// The data section, omitted if empty
+    pub data: Vec<u8>,
+}
+
+impl EofContainer {
+    /// Public associated function to instantiate a new EofContainer.
+    pub fn new(code: Vec<u8>, data: Vec<u8>) -> Self {
+        Self { code, functions: vec![], data }
+    }
+
+    /// The container with the code sections of functions
+    pub fn with_functions(self, functions: Vec<EofFunction>) -> Self {
+        Self { functions, ..self }
+    }
+
+    /// The size of the header of a container along with its type section, i.e. the offset of
+    /// its code section, which depends on its number of functions and whether it has data
+    pub fn header_size(functions: usize, data_size: usize) -> usize {
+        let types = match functions {
+            0 => 0,
+            _ => 3 + 2 * (functions + 1),
+        };
+        let data = match data_size {
+            0 => 0,
+            _ => 3,
+        };
+        3 + types + 3 * (functions + 1) + data + 1
+    }
+
+    /// The offset of the data section in the container
+    pub fn data_offset(&self) -> usize {
+        EofContainer::header_size(self.functions.len(), self.data.len()) +
+            self.code.len() +
+            self.functions.iter().map(|f| f.code.len()).sum::<usize>()
+    }
+
+    /// Validates the sections of the container, per EIP-3540, EIP-3670, EIP-4200 and EIP-4750
+    pub fn validate(&self) -> Result<(), String> {
+        if self.code.is_empty() {
+            return Err("the code section is empty".to_string())
+        }
+        if self.functions.len() + 1 > MAX_CODE_SECTIONS {
+            return Err(format!(
+                "the container has {} code sections, more than {MAX_CODE_SECTIONS}",
+                self.functions.len() + 1
+            ))
+        }
+        let sizes = [("code", self.code.len()), ("data", self.data.len())]
+            .into_iter()
+            .chain(self.functions.iter().map(|f| ("function", f.code.len())));
+        for (name, size) in sizes {
+            if size > MAX_SECTION_SIZE {
+                return Err(format!(
+                    "the {name} section is {size} bytes, more than the {MAX_SECTION_SIZE} bytes of a section"
+                ))
+            }
+        }
+        validate_code(&self.code, self.functions.len() + 1)?;
+        for (i, function) in self.functions.iter().enumerate() {
+            let index = i + 1;
+            if function.code.is_empty() {
+                return Err(format!("the code section of function {index} is empty"))
+            }
+            let items = function.inputs.max(function.outputs);
+            if items > MAX_FUNCTION_STACK_ITEMS {
+                return Err(format!(
+                    "function {index} takes or returns {items} stack items, more than {MAX_FUNCTION_STACK_ITEMS}"
+                ))
+            }
+            validate_code(&function.code, self.functions.len() + 1)
+                .map_err(|e| format!("function {index}: {e}"))?;
+        }
+        Ok(())
+    }
+
+    /// Encodes the container
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = EOF_MAGIC.to_vec();
+        bytes.push(EOF_VERSION);
+        if !self.functions.is_empty() {
+            bytes.push(TYPE_SECTION_KIND);
+            bytes.extend((2 * (self.functions.len() as u16 + 1)).to_be_bytes());
+        }
+        bytes.push(CODE_SECTION_KIND);
+        bytes.extend((self.code.len() as u16).to_be_bytes());
+        for function in &self.functions {
+            bytes.push(CODE_SECTION_KIND);
+            bytes.extend((function.code.len() as u16).to_be_bytes());
+        }
+        if !self.data.is_empty() {
+            bytes.push(DATA_SECTION_KIND);
+            bytes.extend((self.data.len() as u16).to_be_bytes());
+        }
+        bytes.push(HEADER_TERMINATOR);
+        if !self.functions.is_empty() {
+            bytes.extend([0, 0]);
+            bytes.extend(self.functions.iter().flat_map(|f| [f.inputs, f.outputs]));
+        }
+        bytes.extend(&self.code);
+        self.functions.iter().for_each(|f| bytes.extend(&f.code));
+        bytes.extend(&self.data);
+        bytes
+    }
+
+    /// Decodes a container, checking its header matches the size of its sections
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.get(..2) != Some(&EOF_MAGIC[..]) {
+            return Err("missing the EOF magic".to_string())
+        }
+        if bytes.get(2) != Some(&EOF_VERSION) {
+            return Err(format!("unsupported version {:?}", bytes.get(2)))
+        }
+        let size = |at: usize| match bytes.get(at..at + 2) {
+            Some(s) => Ok(u16::from_be_bytes([s[0], s[1]]) as usize),
+            None => Err("truncated section header".to_string()),
+        };
+
+        let mut at = 3;
+        let types_size = match bytes.get(at) {
+            Some(&TYPE_SECTION_KIND) => {
+                at += 3;
+                Some(size(at - 2)?)
+            }
+            _ => None,
+        };
+        let mut code_sizes = vec![];
+        while bytes.get(at) == Some(&CODE_SECTION_KIND) {
+            code_sizes.push(size(at + 1)?);
+            at += 3;
+        }
+        if code_sizes.is_empty() {
+            return Err("missing the code section header".to_string())
+        }
+        let data_size = match bytes.get(at) {
+            Some(&DATA_SECTION_KIND) => {
+                at += 3;
+                size(at - 2)?
+            }
+            _ => 0,
+        };
+        if bytes.get(at) != Some(&HEADER_TERMINATOR) {
+            return Err("missing the header terminator".to_string())
+        }
+        at += 1;
+        let types_size = match types_size {
+            Some(s) if s != 2 * code_sizes.len() => {
+                return Err(format!(
+                    "the type section is {s} bytes, but there are {} code sections",
+                    code_sizes.len()
+                ))
+            }
+            Some(s) => s,
+            None if code_sizes.len() > 1 => {
+                return Err("missing the type section header".to_string())
+            }
+            None => 0,
+        };
+
+        let declared = at + types_size + code_sizes.iter().sum::<usize>() + data_size;
+        if bytes.len() != declared {
+            return Err(format!(
+                "the container is {} bytes, but its header declares {declared}",
+                bytes.len()
+            ))
+        }
+        let types = &bytes[at..at + types_size];
+        at += types_size;
+        if types.get(..2).is_some_and(|t| t != [0, 0]) {
+            return Err("the code section takes or returns stack items".to_string())
+        }
+        let mut sections = code_sizes.iter().map(|s| {
+            at += s;
+            bytes[at - s..at].to_vec()
+        });
+        let code = sections.next().unwrap_or_default();
+        let functions = sections
+            .zip(types.chunks(2).skip(1))
+            .map(|(code, t)| EofFunction { inputs: t[0], outputs: t[1], code })
+            .collect();
+        let data = bytes[declared - data_size..].to_vec();
+        Ok(Self { code, functions, data })
+    }
+}
+
+impl fmt::Display for EofContainer {
+    /// The hex encoded container
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.encode().iter().try_for_each(|b| write!(f, "{b:02x}"))
+    }
+}
+
+/// The instruction encoded as `byte` in EOF code, along with its mnemonic
+fn instruction(byte: u8) -> Option<(String, Opcode)> {
+    match EOF_OPCODES.iter().find(|(_, o)| o.byte() == byte) {
+        Some((name, opcode)) => Some((name.to_string(), *opcode)),
+        None => Opcode::from_byte(byte).map(|opcode| {
+            let op = DisassembledOp { offset: 0, byte, opcode: Some(opcode), immediate: vec![] };
+            (op.mnemonic(), opcode)
+        }),
+    }
+}
+
+/// Validates a code section of a container of `code_sections` code sections, erroring on the
+/// first undefined instruction, truncated immediate, relative jump to a destination that isn't
+/// an instruction of the section, or `callf` to a missing section
+pub fn validate_code(code: &[u8], code_sections: usize) -> Result<(), String> {
+    let mut instructions = vec![false; code.len()];
+    let mut jumps = vec![];
+    let mut offset = 0;
+    while offset < code.len() {
+        let (mnemonic, opcode) = match instruction(code[offset]) {
+            Some(i) => i,
+            None => {
+                return Err(format!(
+                    "undefined instruction 0x{:02x} at offset {offset:#x}",
+                    code[offset]
+                ))
+            }
+        };
+        let end = offset + 1 + opcode.immediate_size();
+        let immediate = match code.get(offset + 1..end) {
+            Some(immediate) => immediate,
+            None => return Err(format!("truncated {mnemonic} at offset {offset:#x}")),
+        };
+        match opcode {
+            Opcode::Rjump | Opcode::Rjumpi => {
+                let relative = i16::from_be_bytes([immediate[0], immediate[1]]) as isize;
+                jumps.push((mnemonic, offset, end as isize + relative));
+            }
+            Opcode::Callf => {
+                let index = u16::from_be_bytes([immediate[0], immediate[1]]) as usize;
+                if index >= code_sections {
+                    return Err(format!(
+                        "callf to undefined code section {index} at offset {offset:#x}"
+                    ))
+                }
+            }
+            _ => {}
+        }
+        instructions[offset] = true;
+        offset = end;
+    }
+    for (mnemonic, offset, destination) in jumps {
+        if !usize::try_from(destination).is_ok_and(|d| instructions.get(d) == Some(&true)) {
+            return Err(format!(
+                "{mnemonic} at offset {offset:#x} doesn't jump to an instruction of its section"
+            ))
+        }
+    }
+    Ok(())
+}
@@ -1,11 +1,15 @@
 use crate::{
     files::{Span, Spanned},
     io::UnpackError,
-    prelude::{parse_extension, AstSpan, Opcode},
+    prelude::{
+        parse_extension, AstSpan, EVMVersion, Feature, Literal, Opcode, Precompile,
+        PrimitiveEVMType,
+    },
     report::{Report, Reporter},
     token::TokenKind,
 };
-use std::{ffi::OsString, fmt, io::Write};
+use ethers_core::types::U256;
+use std::{borrow::Cow, ffi::OsString, fmt, io::Write};
 
 /// A Parser Error
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
@@ -37,6 +41,8 @@ pub enum ParserErrorKind {
     InvalidTokenInLabelDefinition(TokenKind),
     /// Unexpected Single Arg
     InvalidSingleArg(TokenKind),
+    /// A stack item count too large to be a usize
+    StackCountTooLarge(Literal),
     /// Unexpected Table Body Token
     InvalidTableBodyToken(TokenKind),
     /// Invalid constant
@@ -63,6 +69,30 @@ pub enum ParserErrorKind {
     InvalidDecoratorFlag(String),
     /// Invalid decorator flag argument
     InvalidDecoratorFlagArg(TokenKind),
+    /// Unknown or misplaced pragma
+    InvalidPragma(String),
+    /// Syntax of a later edition than the one of the file
+    UnsupportedFeature(Feature),
+    /// Invalid constant type annotation
+    InvalidConstantType(TokenKind),
+    /// Constant value does not fit its type annotation
+    ConstantTypeMismatch(String, PrimitiveEVMType),
+    /// A contract is defined more than once in a file
+    DuplicateContract(String),
+    /// A label, constant or macro argument is named after an opcode
+    OpcodeNameCollision(String),
+    /// A switch has more than one case for the same value
+    DuplicateCase(String),
+    /// The jump table of a switch can't be generated, the reason why
+    InvalidDispatchTable(String),
+    /// A constant is declared more than once in the same macro body
+    DuplicateConstant(String),
+    /// A negative literal isn't a literal, or is out of the range of an `int256`
+    InvalidNegativeLiteral(TokenKind),
+    /// The codec macros of a struct can't be generated, the reason why
+    InvalidStruct(String),
+    /// The verification macro of a merkle tree can't be generated, the reason why
+    InvalidMerkleTree(String),
 }
 
 /// A Lexing Error
@@ -79,10 +109,29 @@ impl<'a> LexicalError<'a> {
     pub fn new(kind: LexicalErrorKind<'a>, span: Span) -> Self {
         Self { kind, span }
     }
+
+    /// Detaches the error from the source it was lexed from.
+    pub fn into_owned(self) -> LexicalError<'static> {
+        let kind = match self.kind {
+            LexicalErrorKind::UnexpectedEof => LexicalErrorKind::UnexpectedEof,
+            LexicalErrorKind::InvalidCharacter(ch) => LexicalErrorKind::InvalidCharacter(ch),
+            LexicalErrorKind::InvalidArraySize(s) => {
+                LexicalErrorKind::InvalidArraySize(Cow::Owned(s.into_owned()))
+            }
+            LexicalErrorKind::InvalidPrimitiveType(s) => {
+                LexicalErrorKind::InvalidPrimitiveType(Cow::Owned(s.into_owned()))
+            }
+            LexicalErrorKind::OverflowingLiteral(s) => {
+                LexicalErrorKind::OverflowingLiteral(Cow::Owned(s.into_owned()))
+            }
+            LexicalErrorKind::UnsupportedFeature(f) => LexicalErrorKind::UnsupportedFeature(f),
+        };
+        LexicalError { kind, span: self.span }
+    }
 }
 
 /// A Lexical Error Kind
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum LexicalErrorKind<'a> {
     /// Unexpected end of file
     UnexpectedEof,
@@ -90,9 +139,13 @@ pub enum LexicalErrorKind<'a> {
     InvalidCharacter(char),
     /// Invalid Array Size
     /// String param expected to be usize parsable
-    InvalidArraySize(&'a str),
+    InvalidArraySize(Cow<'a, str>),
     /// Invalid Primitive EVM Type
-    InvalidPrimitiveType(&'a str),
+    InvalidPrimitiveType(Cow<'a, str>),
+    /// A numeric literal whose value doesn't fit in 32 bytes
+    OverflowingLiteral(Cow<'a, str>),
+    /// Syntax of a later edition than the one of the file
+    UnsupportedFeature(Feature),
 }
 
 impl<'a> Spanned for LexicalError<'a> {
@@ -103,7 +156,7 @@ impl<'a> Spanned for LexicalError<'a> {
 
 impl<'a, W: Write> Report<W> for LexicalError<'a> {
     fn report(&self, f: &mut Reporter<'_, W>) -> std::io::Result<()> {
-        match self.kind {
+        match &self.kind {
             LexicalErrorKind::InvalidCharacter(ch) => write!(f.out, "Invalid character '{ch}'"),
             LexicalErrorKind::UnexpectedEof => write!(f.out, "Found unexpected EOF"),
             LexicalErrorKind::InvalidArraySize(str) => {
@@ -112,6 +165,12 @@ impl<'a, W: Write> Report<W> for LexicalError<'a> {
             LexicalErrorKind::InvalidPrimitiveType(str) => {
                 write!(f.out, "Invalid Primitive EVM Type '{str}'")
             }
+            LexicalErrorKind::OverflowingLiteral(str) => {
+                write!(f.out, "Literal '{str}' exceeds 32 bytes")
+            }
+            LexicalErrorKind::UnsupportedFeature(feature) => {
+                write!(f.out, "Using {feature} requires edition {}", feature.edition())
+            }
         }
     }
 }
@@ -153,6 +212,8 @@ pub enum CodegenErrorKind {
     MissingConstantDefinition(String),
     /// Missing Error Definition
     MissingErrorDefinition(String),
+    /// Missing Data Section Definition
+    MissingDataDefinition(String),
     /// Abi Generation Failure
     AbiGenerationFailure,
     /// Unmatched Jump
@@ -179,6 +240,46 @@ pub enum CodegenErrorKind {
     TestInvocation(String),
     /// Incorrect dynamic argument index
     InvalidDynArgIndex,
+    /// Constant value does not fit its type annotation
+    ConstantTypeMismatch(String),
+    /// Opcode is not available in the targeted EVM version
+    UnsupportedOpcode(Opcode, EVMVersion),
+    /// Precompile is not available in the targeted EVM version
+    UnsupportedPrecompile(Precompile, EVMVersion),
+    /// Stack height can exceed the EVM's stack limit
+    StackTooDeep(usize, String),
+    /// Macro invocations are nested deeper than the nesting limit
+    MacroNestingTooDeep(usize, String),
+    /// A jump or table offset can't be patched into the bytecode
+    InvalidRelocation(String, String),
+    /// Constructor arguments don't match the constructor's input types
+    InvalidConstructorArgs(String),
+    /// Contracts in a file embed each other's bytecode
+    CircularContractReference(Vec<String>),
+    /// Storage pointer constants are assigned the same slot
+    StorageSlotCollision(Vec<String>, String),
+    /// Generated bytecode isn't valid hex, the reason why
+    MalformedBytecode(String),
+    /// The generated bytecode can't be laid out as a valid EOF container, the reason why
+    InvalidEofContainer(String),
+    /// A macro is invoked with a different number of arguments than it takes: the macro, the
+    /// number of parameters and the number of arguments passed
+    ArgumentCountMismatch(String, usize, usize),
+    /// A compile time assertion doesn't hold, its message
+    AssertionFailed(String),
+    /// A constant expression can't be evaluated, the reason why
+    InvalidConstantExpression(String),
+    /// The estimated gas of a macro exceeds its budget: the macro, the estimate and the budget
+    GasLimitExceeded(String, u64, u64),
+    /// A jump in a macro doesn't jump to a label, with static jumps required
+    DynamicJump(String),
+    /// Calls with a declared function's selector don't reach a body of their own in the dispatch
+    UnreachableFunction(String),
+    /// Calls with the selectors of two declared functions reach the same body: the functions and
+    /// the offset of the body
+    DispatchCollision(String, String, usize),
+    /// A `__FUNC_SIG` name isn't a declared function or error: the name and the declared functions
+    UndeclaredFunction(String, Vec<String>),
 }
 
 impl Spanned for CodegenError {
@@ -215,6 +316,9 @@ impl<W: Write> Report<W> for CodegenError {
             CodegenErrorKind::MissingErrorDefinition(ed) => {
                 write!(f.out, "Missing Error Definition for \"{ed}\"!")
             }
+            CodegenErrorKind::MissingDataDefinition(dd) => {
+                write!(f.out, "Missing Data Section Definition for \"{dd}\"!")
+            }
             CodegenErrorKind::AbiGenerationFailure => write!(f.out, "Abi generation failure!"),
             CodegenErrorKind::UnmatchedJumpLabel => write!(f.out, "Unmatched jump label!"),
             CodegenErrorKind::IOError(ioe) => write!(f.out, "IO ERROR: {ioe:?}"),
@@ -243,6 +347,77 @@ impl<W: Write> Report<W> for CodegenError {
             CodegenErrorKind::InvalidDynArgIndex => {
                 write!(f.out, "Invalid Dynamic Constructor Argument Index")
             }
+            CodegenErrorKind::ConstantTypeMismatch(name) => {
+                write!(f.out, "Constant \"{name}\" does not fit its type!")
+            }
+            CodegenErrorKind::UnsupportedOpcode(op, version) => {
+                write!(
+                    f.out,
+                    "Opcode \"{op:?}\" requires EVM version \"{}\" but the target is \"{version}\"!",
+                    op.introduced_in()
+                )
+            }
+            CodegenErrorKind::UnsupportedPrecompile(precompile, version) => {
+                write!(
+                    f.out,
+                    "Precompile \"{precompile}\" requires EVM version \"{}\" but the target is \"{version}\"!",
+                    precompile.introduced_in()
+                )
+            }
+            CodegenErrorKind::StackTooDeep(height, chain) => {
+                write!(f.out, "Stack height can reach {height} items through \"{chain}\"!")
+            }
+            CodegenErrorKind::MacroNestingTooDeep(limit, name) => {
+                write!(f.out, "Macro \"{name}\" is nested deeper than the limit of {limit}!")
+            }
+            CodegenErrorKind::InvalidRelocation(label, reason) => {
+                write!(f.out, "Invalid relocation for \"{label}\": {reason}!")
+            }
+            CodegenErrorKind::InvalidConstructorArgs(msg) => {
+                write!(f.out, "{msg}")
+            }
+            CodegenErrorKind::CircularContractReference(cycle) => {
+                write!(f.out, "Circular contract reference: {}", cycle.join(" -> "))
+            }
+            CodegenErrorKind::StorageSlotCollision(names, slot) => {
+                write!(f.out, "Storage slot {slot} is assigned to {}", names.join(" and "))
+            }
+            CodegenErrorKind::MalformedBytecode(reason) => {
+                write!(f.out, "Malformed bytecode: {reason}!")
+            }
+            CodegenErrorKind::InvalidEofContainer(reason) => {
+                write!(f.out, "Invalid EOF container: {reason}!")
+            }
+            CodegenErrorKind::ArgumentCountMismatch(name, expected, provided) => {
+                write!(
+                    f.out,
+                    "Macro \"{name}\" takes {expected} argument(s) but {provided} were provided!"
+                )
+            }
+            CodegenErrorKind::AssertionFailed(message) => {
+                write!(f.out, "Assertion failed: {message}")
+            }
+            CodegenErrorKind::InvalidConstantExpression(reason) => {
+                write!(f.out, "Invalid constant expression: {reason}!")
+            }
+            CodegenErrorKind::GasLimitExceeded(name, estimate, limit) => {
+                write!(f.out, "Macro \"{name}\" uses an estimated {estimate} gas, exceeding its limit of {limit}!")
+            }
+            CodegenErrorKind::DynamicJump(name) => {
+                write!(f.out, "Jump in macro \"{name}\" doesn't jump to a label!")
+            }
+            CodegenErrorKind::UnreachableFunction(name) => {
+                write!(
+                    f.out,
+                    "Function \"{name}\" isn't reachable from the dispatch with its selector!"
+                )
+            }
+            CodegenErrorKind::DispatchCollision(first, second, offset) => {
+                write!(f.out, "Functions \"{first}\" and \"{second}\" dispatch to the same body at offset {offset:#x}!")
+            }
+            CodegenErrorKind::UndeclaredFunction(name, _) => {
+                write!(f.out, "Function \"{name}\" passed to __FUNC_SIG isn't declared!")
+            }
         }
     }
 }
@@ -262,12 +437,26 @@ pub enum CompilerError<'a> {
     CodegenError(CodegenError),
     /// Multiple Failed Compiles
     FailedCompiles(Vec<CompilerError<'a>>),
+    /// Standard Library Resolution Failed
+    StdLibError(String),
+    /// The storage layout is incompatible with the previous layout
+    IncompatibleStorageLayout(Vec<String>),
+    /// The proxy can't be generated
+    InvalidProxy(String),
+    /// The data contract can't be generated
+    InvalidDataContract(String),
+    /// A requirement declared with a source pragma isn't met
+    IncompatiblePragma(String, AstSpan),
+    /// An ABI file imported with `#include abi` can't be read or isn't a valid ABI
+    InvalidAbiImport(String, AstSpan),
+    /// The leaves of a merkle tree can't be read from its json file
+    InvalidMerkleLeaves(String, AstSpan),
 }
 
 impl<'a> fmt::Display for CompilerError<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            CompilerError::LexicalError(le) => match le.kind {
+            CompilerError::LexicalError(le) => match &le.kind {
                 LexicalErrorKind::UnexpectedEof => {
                     write!(
                         f,
@@ -303,6 +492,25 @@ impl<'a> fmt::Display for CompilerError<'a> {
                         le.span.source_seg()
                     )
                 }
+                LexicalErrorKind::OverflowingLiteral(l) => {
+                    write!(
+                        f,
+                        "\nError: Literal Exceeds 32 Bytes: \"{}\" {}{}\n",
+                        l,
+                        le.span.identifier(),
+                        le.span.source_seg()
+                    )
+                }
+                LexicalErrorKind::UnsupportedFeature(feature) => {
+                    write!(
+                        f,
+                        "\nError: Feature Requires Edition {}: \"{}\" {}{}\n",
+                        feature.edition(),
+                        feature,
+                        le.span.identifier(),
+                        le.span.source_seg()
+                    )
+                }
             },
             CompilerError::FileUnpackError(ue) => match ue {
                 UnpackError::InvalidDirectory(id) => {
@@ -385,6 +593,14 @@ impl<'a> fmt::Display for CompilerError<'a> {
                         pe.spans.error(pe.hint.as_ref())
                     )
                 }
+                ParserErrorKind::StackCountTooLarge(count) => {
+                    write!(
+                        f,
+                        "\nError: Stack Item Count Too Large: \"{}\" \n{}\n",
+                        U256::from_big_endian(count),
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
                 ParserErrorKind::InvalidTableBodyToken(tbt) => {
                     write!(
                         f,
@@ -488,6 +704,104 @@ impl<'a> fmt::Display for CompilerError<'a> {
                         pe.spans.error(pe.hint.as_ref())
                     )
                 }
+                ParserErrorKind::UnsupportedFeature(feature) => {
+                    write!(
+                        f,
+                        "\nError: Feature Requires Edition {}: \"{}\" \n{}\n",
+                        feature.edition(),
+                        feature,
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
+                ParserErrorKind::InvalidPragma(p) => {
+                    write!(
+                        f,
+                        "\nError: Invalid Pragma: \"{}\" \n{}\n",
+                        p,
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
+                ParserErrorKind::InvalidConstantType(ct) => {
+                    write!(
+                        f,
+                        "\nError: Invalid Constant Type: \"{}\" \n{}\n",
+                        ct,
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
+                ParserErrorKind::ConstantTypeMismatch(name, ty) => {
+                    write!(
+                        f,
+                        "\nError: Constant \"{}\" Does Not Fit Type \"{}\" \n{}\n",
+                        name,
+                        ty,
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
+                ParserErrorKind::DuplicateContract(name) => {
+                    write!(
+                        f,
+                        "\nError: Duplicate Contract Definition: \"{}\" \n{}\n",
+                        name,
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
+                ParserErrorKind::OpcodeNameCollision(name) => {
+                    write!(
+                        f,
+                        "\nError: Name Collides With Opcode: \"{}\" \n{}\n",
+                        name,
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
+                ParserErrorKind::DuplicateCase(value) => {
+                    write!(
+                        f,
+                        "\nError: Duplicate Switch Case: \"{}\" \n{}\n",
+                        value,
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
+                ParserErrorKind::InvalidDispatchTable(reason) => {
+                    write!(
+                        f,
+                        "\nError: Invalid Dispatch Table: {} \n{}\n",
+                        reason,
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
+                ParserErrorKind::DuplicateConstant(name) => {
+                    write!(
+                        f,
+                        "\nError: Duplicate Constant Definition: \"{}\" \n{}\n",
+                        name,
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
+                ParserErrorKind::InvalidNegativeLiteral(kind) => {
+                    write!(
+                        f,
+                        "\nError: Invalid Negative Literal: \"-{}\" \n{}\n",
+                        kind,
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
+                ParserErrorKind::InvalidStruct(reason) => {
+                    write!(
+                        f,
+                        "\nError: Invalid Struct: {} \n{}\n",
+                        reason,
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
+                ParserErrorKind::InvalidMerkleTree(reason) => {
+                    write!(
+                        f,
+                        "\nError: Invalid Merkle Tree: {} \n{}\n",
+                        reason,
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
             },
             CompilerError::PathBufRead(os_str) => {
                 write!(
@@ -544,6 +858,14 @@ impl<'a> fmt::Display for CompilerError<'a> {
                 CodegenErrorKind::MissingErrorDefinition(_) => {
                     write!(f, "\nError: Missing Error Definition\n{}\n", ce.span.error(None))
                 }
+                CodegenErrorKind::MissingDataDefinition(dd) => {
+                    write!(
+                        f,
+                        "\nError: Missing Data Section Definition: \"{}\"\n{}\n",
+                        dd,
+                        ce.span.error(None)
+                    )
+                }
                 CodegenErrorKind::AbiGenerationFailure => {
                     write!(f, "\nError: ABI Generation Failed\n{}\n", ce.span.error(None))
                 }
@@ -589,6 +911,164 @@ impl<'a> fmt::Display for CompilerError<'a> {
                         ce.span.error(None)
                     )
                 }
+                CodegenErrorKind::ConstantTypeMismatch(name) => {
+                    write!(
+                        f,
+                        "\nError: Constant \"{}\" Does Not Fit Its Type\n{}\n",
+                        name,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::UnsupportedOpcode(op, version) => {
+                    write!(
+                        f,
+                        "\nError: Opcode \"{:?}\" Requires EVM Version \"{}\", Targeting \"{}\"\n{}\n",
+                        op,
+                        op.introduced_in(),
+                        version,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::UnsupportedPrecompile(precompile, version) => {
+                    write!(
+                        f,
+                        "\nError: Precompile \"{}\" Requires EVM Version \"{}\", Targeting \"{}\"\n{}\n",
+                        precompile,
+                        precompile.introduced_in(),
+                        version,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::StackTooDeep(height, chain) => {
+                    write!(
+                        f,
+                        "\nError: Stack Height Can Reach {} Items Through \"{}\"\n{}\n",
+                        height,
+                        chain,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::MacroNestingTooDeep(limit, name) => {
+                    write!(
+                        f,
+                        "\nError: Macro \"{}\" Is Nested Deeper Than The Limit Of {}\n{}\n",
+                        name,
+                        limit,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::InvalidRelocation(label, reason) => {
+                    write!(
+                        f,
+                        "\nError: Invalid Relocation For \"{}\": {}\n{}\n",
+                        label,
+                        reason,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::InvalidConstructorArgs(msg) => {
+                    write!(f, "\nError: {}\n{}\n", msg, ce.span.error(None))
+                }
+                CodegenErrorKind::CircularContractReference(cycle) => {
+                    write!(
+                        f,
+                        "\nError: Circular Contract Reference: {}\n{}\n",
+                        cycle.join(" -> "),
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::StorageSlotCollision(names, slot) => {
+                    write!(
+                        f,
+                        "\nError: Storage Slot Collision In Slot {}: {}\n{}\n",
+                        slot,
+                        names.join(", "),
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::MalformedBytecode(reason) => {
+                    write!(f, "\nError: Malformed Bytecode: {}\n{}\n", reason, ce.span.error(None))
+                }
+                CodegenErrorKind::InvalidEofContainer(reason) => {
+                    write!(
+                        f,
+                        "\nError: Invalid EOF Container: {}\n{}\n",
+                        reason,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::ArgumentCountMismatch(name, expected, provided) => {
+                    write!(
+                        f,
+                        "\nError: Macro \"{}\" Takes {} Argument(s) But {} Were Provided\n{}\n",
+                        name,
+                        expected,
+                        provided,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::AssertionFailed(message) => {
+                    write!(f, "\nError: Assertion Failed: {}\n{}\n", message, ce.span.error(None))
+                }
+                CodegenErrorKind::InvalidConstantExpression(reason) => {
+                    write!(
+                        f,
+                        "\nError: Invalid Constant Expression: {}\n{}\n",
+                        reason,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::GasLimitExceeded(name, estimate, limit) => {
+                    write!(
+                        f,
+                        "\nError: Macro \"{}\" Uses An Estimated {} Gas, Exceeding Its Limit Of {}\n{}\n",
+                        name,
+                        estimate,
+                        limit,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::DynamicJump(name) => {
+                    write!(
+                        f,
+                        "\nError: Jump In Macro \"{}\" Doesn't Jump To A Label\n{}\n",
+                        name,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::UnreachableFunction(name) => {
+                    write!(
+                        f,
+                        "\nError: Function \"{}\" Isn't Reachable From The Dispatch With Its Selector\n{}\n",
+                        name,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::DispatchCollision(first, second, offset) => {
+                    write!(
+                        f,
+                        "\nError: Functions \"{}\" And \"{}\" Dispatch To The Same Body At Offset {:#x}\n{}\n",
+                        first,
+                        second,
+                        offset,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::UndeclaredFunction(name, declared) => {
+                    let declared = match declared.is_empty() {
+                        true => "No functions are declared".to_string(),
+                        false => format!("Declared functions: {}", declared.join(", ")),
+                    };
+                    let hint = format!(
+                        "{declared}. Pass a signature as a string to hash it instead, e.g. __FUNC_SIG(\"{name}(uint256)\")"
+                    );
+                    write!(
+                        f,
+                        "\nError: Function \"{}\" Passed To __FUNC_SIG Isn't Declared\n{}\n",
+                        name,
+                        ce.span.error(Some(&hint))
+                    )
+                }
             },
             CompilerError::FailedCompiles(v) => {
                 v.iter().for_each(|ce| {
@@ -596,6 +1076,28 @@ impl<'a> fmt::Display for CompilerError<'a> {
                 });
                 Ok(())
             }
+            CompilerError::StdLibError(msg) => {
+                write!(f, "\nError: {msg}\n")
+            }
+            CompilerError::IncompatibleStorageLayout(incompatibilities) => {
+                write!(f, "\nError: Incompatible Storage Layout\n")?;
+                incompatibilities.iter().try_for_each(|i| writeln!(f, "  - {i}"))
+            }
+            CompilerError::InvalidProxy(msg) => {
+                write!(f, "\nError: Invalid Proxy: {msg}\n")
+            }
+            CompilerError::InvalidDataContract(msg) => {
+                write!(f, "\nError: Invalid Data Contract: {msg}\n")
+            }
+            CompilerError::IncompatiblePragma(msg, span) => {
+                write!(f, "\nError: Incompatible Pragma: {msg}\n{}\n", span.error(None))
+            }
+            CompilerError::InvalidAbiImport(msg, span) => {
+                write!(f, "\nError: Invalid ABI Import: {msg}\n{}\n", span.error(None))
+            }
+            CompilerError::InvalidMerkleLeaves(msg, span) => {
+                write!(f, "\nError: Invalid Merkle Leaves: {msg}\n{}\n", span.error(None))
+            }
         }
     }
 }
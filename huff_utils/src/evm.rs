@@ -1,4 +1,6 @@
+use crate::evm_version::SupportedEVMVersions;
 use phf::phf_map;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use strum_macros::EnumString;
 
@@ -6,7 +8,7 @@ use strum_macros::EnumString;
 /// They are arranged in a particular order such that all the opcodes that have common
 /// prefixes are ordered by decreasing length to avoid mismatch when lexing.
 /// Example : [origin, or] or [push32, ..., push3]
-pub const OPCODES: [&str; 146] = [
+pub const OPCODES: [&str; 150] = [
     "lt",
     "gt",
     "slt",
@@ -28,7 +30,9 @@ pub const OPCODES: [&str; 146] = [
     "calldatacopy",
     "codesize",
     "codecopy",
+    "blobbasefee",
     "basefee",
+    "blobhash",
     "blockhash",
     "coinbase",
     "timestamp",
@@ -42,6 +46,7 @@ pub const OPCODES: [&str; 146] = [
     "mload",
     "mstore8",
     "mstore",
+    "mcopy",
     "sload",
     "sstore",
     "jumpdest",
@@ -121,6 +126,7 @@ pub const OPCODES: [&str; 146] = [
     "push3",
     "push2",
     "push1",
+    "push0",
     "swap16",
     "swap15",
     "swap14",
@@ -179,6 +185,8 @@ pub static OPCODES_MAP: phf::Map<&'static str, Opcode> = phf_map! {
     "codesize" => Opcode::Codesize,
     "codecopy" => Opcode::Codecopy,
     "basefee" => Opcode::Basefee,
+    "blobhash" => Opcode::Blobhash,
+    "blobbasefee" => Opcode::Blobbasefee,
     "blockhash" => Opcode::Blockhash,
     "coinbase" => Opcode::Coinbase,
     "timestamp" => Opcode::Timestamp,
@@ -192,12 +200,14 @@ pub static OPCODES_MAP: phf::Map<&'static str, Opcode> = phf_map! {
     "mload" => Opcode::Mload,
     "mstore" => Opcode::Mstore,
     "mstore8" => Opcode::Mstore8,
+    "mcopy" => Opcode::Mcopy,
     "sload" => Opcode::Sload,
     "sstore" => Opcode::Sstore,
     "jump" => Opcode::Jump,
     "jumpi" => Opcode::Jumpi,
     "pc" => Opcode::Pc,
     "msize" => Opcode::Msize,
+    "push0" => Opcode::Push0,
     "push1" => Opcode::Push1,
     "push2" => Opcode::Push2,
     "push3" => Opcode::Push3,
@@ -307,8 +317,11 @@ pub static OPCODES_MAP: phf::Map<&'static str, Opcode> = phf_map! {
 
 /// EVM Opcodes
 /// References <https://evm.codes>
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, EnumString)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, EnumString, Serialize, Deserialize,
+)]
 #[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
 pub enum Opcode {
     /// Halts execution.
     Stop,
@@ -414,6 +427,10 @@ pub enum Opcode {
     Selfbalance,
     /// Base Fee
     Basefee,
+    /// Versioned Hash of a Blob in the Current Transaction
+    Blobhash,
+    /// Blob Base Fee of the Current Block
+    Blobbasefee,
     /// Removes an Item from the Stack
     Pop,
     /// Loads a word from Memory
@@ -422,6 +439,8 @@ pub enum Opcode {
     Mstore,
     /// Stores a byte in Memory
     Mstore8,
+    /// Copies an area of Memory to another
+    Mcopy,
     /// Load a word from Storage
     Sload,
     /// Store a word in Storage
@@ -438,6 +457,8 @@ pub enum Opcode {
     Gas,
     /// Marks a valid destination for jumps
     Jumpdest,
+    /// Places the value 0 on top of the stack
+    Push0,
     /// Places 1 byte item on top of the stack
     Push1,
     /// Places 2 byte item on top of the stack
@@ -603,6 +624,19 @@ pub enum Opcode {
     Selfdestruct,
     /// Get hash of an account’s code
     Extcodehash,
+    /// Jump to a destination relative to the next instruction, only generated for EOF code
+    #[strum(disabled)]
+    Rjump,
+    /// Conditionally jump to a destination relative to the next instruction, only generated for
+    /// EOF code
+    #[strum(disabled)]
+    Rjumpi,
+    /// Call the function of another code section, only generated for EOF code
+    #[strum(disabled)]
+    Callf,
+    /// Return from a function to the code section calling it, only generated for EOF code
+    #[strum(disabled)]
+    Retf,
 }
 
 impl Opcode {
@@ -662,6 +696,8 @@ impl Opcode {
             Opcode::Chainid => "46",
             Opcode::Selfbalance => "47",
             Opcode::Basefee => "48",
+            Opcode::Blobhash => "49",
+            Opcode::Blobbasefee => "4a",
             Opcode::Pop => "50",
             Opcode::Mload => "51",
             Opcode::Mstore => "52",
@@ -674,6 +710,10 @@ impl Opcode {
             Opcode::Msize => "59",
             Opcode::Gas => "5a",
             Opcode::Jumpdest => "5b",
+            Opcode::TLoad => "5c",
+            Opcode::TStore => "5d",
+            Opcode::Mcopy => "5e",
+            Opcode::Push0 => "5f",
             Opcode::Push1 => "60",
             Opcode::Push2 => "61",
             Opcode::Push3 => "62",
@@ -743,8 +783,6 @@ impl Opcode {
             Opcode::Log2 => "a2",
             Opcode::Log3 => "a3",
             Opcode::Log4 => "a4",
-            Opcode::TLoad => "b3",
-            Opcode::TStore => "b4",
             Opcode::Create => "f0",
             Opcode::Call => "f1",
             Opcode::Callcode => "f2",
@@ -755,10 +793,218 @@ impl Opcode {
             Opcode::Revert => "fd",
             Opcode::Invalid => "fe",
             Opcode::Selfdestruct => "ff",
+            Opcode::Rjump => "e0",
+            Opcode::Rjumpi => "e1",
+            Opcode::Callf => "e3",
+            Opcode::Retf => "e4",
         };
         opcode_str.to_string()
     }
 
+    /// Returns the hard fork in which the opcode was introduced
+    pub fn introduced_in(&self) -> SupportedEVMVersions {
+        match self {
+            Opcode::Returndatasize |
+            Opcode::Returndatacopy |
+            Opcode::Staticcall |
+            Opcode::Revert => SupportedEVMVersions::Byzantium,
+            Opcode::Shl | Opcode::Shr | Opcode::Sar | Opcode::Create2 | Opcode::Extcodehash => {
+                SupportedEVMVersions::Constantinople
+            }
+            Opcode::Chainid | Opcode::Selfbalance => SupportedEVMVersions::Istanbul,
+            Opcode::Basefee => SupportedEVMVersions::London,
+            Opcode::Prevrandao => SupportedEVMVersions::Paris,
+            Opcode::Push0 => SupportedEVMVersions::Shanghai,
+            Opcode::TLoad |
+            Opcode::TStore |
+            Opcode::Mcopy |
+            Opcode::Blobhash |
+            Opcode::Blobbasefee => SupportedEVMVersions::Cancun,
+            _ => SupportedEVMVersions::Homestead,
+        }
+    }
+
+    /// Returns the number of stack items consumed by the opcode
+    pub fn stack_inputs(&self) -> usize {
+        let byte = u8::from_str_radix(&self.string(), 16).unwrap_or_default();
+        match byte {
+            // PUSH0 - PUSH32
+            0x5f..=0x7f => 0,
+            // DUP1 - DUP16
+            0x80..=0x8f => (byte - 0x7f) as usize,
+            // SWAP1 - SWAP16
+            0x90..=0x9f => (byte - 0x8e) as usize,
+            // LOG0 - LOG4
+            0xa0..=0xa4 => (byte - 0x9e) as usize,
+            _ => match self {
+                Opcode::Stop |
+                Opcode::Address |
+                Opcode::Origin |
+                Opcode::Caller |
+                Opcode::Callvalue |
+                Opcode::Calldatasize |
+                Opcode::Codesize |
+                Opcode::Gasprice |
+                Opcode::Returndatasize |
+                Opcode::Coinbase |
+                Opcode::Timestamp |
+                Opcode::Number |
+                Opcode::Difficulty |
+                Opcode::Prevrandao |
+                Opcode::Gaslimit |
+                Opcode::Chainid |
+                Opcode::Selfbalance |
+                Opcode::Basefee |
+                Opcode::Blobbasefee |
+                Opcode::Pc |
+                Opcode::Msize |
+                Opcode::Gas |
+                Opcode::Jumpdest |
+                Opcode::Rjump |
+                Opcode::Callf |
+                Opcode::Retf |
+                Opcode::Invalid => 0,
+                Opcode::Iszero |
+                Opcode::Not |
+                Opcode::Balance |
+                Opcode::Calldataload |
+                Opcode::Extcodesize |
+                Opcode::Extcodehash |
+                Opcode::Blockhash |
+                Opcode::Blobhash |
+                Opcode::Pop |
+                Opcode::Mload |
+                Opcode::Sload |
+                Opcode::TLoad |
+                Opcode::Jump |
+                Opcode::Rjumpi |
+                Opcode::Selfdestruct => 1,
+                Opcode::Addmod |
+                Opcode::Mulmod |
+                Opcode::Calldatacopy |
+                Opcode::Codecopy |
+                Opcode::Returndatacopy |
+                Opcode::Mcopy |
+                Opcode::Create => 3,
+                Opcode::Extcodecopy | Opcode::Create2 => 4,
+                Opcode::Delegatecall | Opcode::Staticcall => 6,
+                Opcode::Call | Opcode::Callcode => 7,
+                _ => 2,
+            },
+        }
+    }
+
+    /// Returns the number of stack items produced by the opcode
+    pub fn stack_outputs(&self) -> usize {
+        let byte = u8::from_str_radix(&self.string(), 16).unwrap_or_default();
+        match byte {
+            // PUSH0 - PUSH32
+            0x5f..=0x7f => 1,
+            // DUP1 - DUP16
+            0x80..=0x8f => (byte - 0x7e) as usize,
+            // SWAP1 - SWAP16
+            0x90..=0x9f => (byte - 0x8e) as usize,
+            // LOG0 - LOG4
+            0xa0..=0xa4 => 0,
+            _ => match self {
+                Opcode::Stop |
+                Opcode::Calldatacopy |
+                Opcode::Codecopy |
+                Opcode::Extcodecopy |
+                Opcode::Returndatacopy |
+                Opcode::Pop |
+                Opcode::Mstore |
+                Opcode::Mstore8 |
+                Opcode::Sstore |
+                Opcode::TStore |
+                Opcode::Mcopy |
+                Opcode::Jump |
+                Opcode::Jumpi |
+                Opcode::Jumpdest |
+                Opcode::Rjump |
+                Opcode::Rjumpi |
+                Opcode::Callf |
+                Opcode::Retf |
+                Opcode::Return |
+                Opcode::Revert |
+                Opcode::Invalid |
+                Opcode::Selfdestruct => 0,
+                _ => 1,
+            },
+        }
+    }
+
+    /// Returns the static gas cost of the opcode
+    ///
+    /// State accesses are priced as cold, and `SSTORE` as setting a zero slot to a non-zero
+    /// value, so the cost is an upper bound of the opcode's cost excluding its dynamic costs:
+    /// memory expansion, copied words, hashed words, logged bytes and the gas forwarded by calls.
+    pub fn static_gas(&self) -> u64 {
+        let byte = u8::from_str_radix(&self.string(), 16).unwrap_or_default();
+        match byte {
+            // PUSH0
+            0x5f => 2,
+            // PUSH1 - PUSH32, DUP1 - DUP16, SWAP1 - SWAP16
+            0x60..=0x9f => 3,
+            // LOG0 - LOG4
+            0xa0..=0xa4 => 375 * (byte - 0x9f) as u64,
+            _ => match self {
+                Opcode::Stop | Opcode::Return | Opcode::Revert | Opcode::Invalid => 0,
+                Opcode::Jumpdest => 1,
+                Opcode::Rjump => 2,
+                Opcode::Retf => 3,
+                Opcode::Rjumpi => 4,
+                Opcode::Callf => 5,
+                Opcode::Address |
+                Opcode::Origin |
+                Opcode::Caller |
+                Opcode::Callvalue |
+                Opcode::Calldatasize |
+                Opcode::Codesize |
+                Opcode::Gasprice |
+                Opcode::Returndatasize |
+                Opcode::Coinbase |
+                Opcode::Timestamp |
+                Opcode::Number |
+                Opcode::Difficulty |
+                Opcode::Prevrandao |
+                Opcode::Gaslimit |
+                Opcode::Chainid |
+                Opcode::Basefee |
+                Opcode::Blobbasefee |
+                Opcode::Pop |
+                Opcode::Pc |
+                Opcode::Msize |
+                Opcode::Gas => 2,
+                Opcode::Mul |
+                Opcode::Div |
+                Opcode::Sdiv |
+                Opcode::Mod |
+                Opcode::Smod |
+                Opcode::Signextend |
+                Opcode::Selfbalance => 5,
+                Opcode::Addmod | Opcode::Mulmod | Opcode::Jump => 8,
+                Opcode::Exp | Opcode::Jumpi => 10,
+                Opcode::Blockhash => 20,
+                Opcode::Sha3 => 30,
+                Opcode::TLoad | Opcode::TStore => 100,
+                Opcode::Sload => 2100,
+                Opcode::Balance |
+                Opcode::Extcodesize |
+                Opcode::Extcodecopy |
+                Opcode::Extcodehash |
+                Opcode::Call |
+                Opcode::Callcode |
+                Opcode::Delegatecall |
+                Opcode::Staticcall => 2600,
+                Opcode::Selfdestruct => 5000,
+                Opcode::Sstore => 22100,
+                Opcode::Create | Opcode::Create2 => 32000,
+                _ => 3,
+            },
+        }
+    }
+
     /// Returns if the current opcode is a push opcode
     pub fn is_push(&self) -> bool {
         matches!(
@@ -831,6 +1077,31 @@ impl Opcode {
 
         false
     }
+
+    /// The byte the opcode is encoded as
+    pub fn byte(&self) -> u8 {
+        u8::from_str_radix(&self.string(), 16).unwrap_or_default()
+    }
+
+    /// The number of immediate bytes following the opcode in bytecode
+    pub fn immediate_size(&self) -> usize {
+        match self {
+            Opcode::Rjump | Opcode::Rjumpi | Opcode::Callf => 2,
+            _ if self.is_push() => (self.byte() - 0x5f) as usize,
+            _ => 0,
+        }
+    }
+
+    /// The `PUSH` opcode pushing `size` bytes, if there is one
+    pub fn push_n(size: usize) -> Option<Opcode> {
+        OPCODES_MAP.get(format!("push{size}").as_str()).copied()
+    }
+
+    /// The `SWAP` opcode swapping the top of the stack with the `n`th item below it, if there is
+    /// one
+    pub fn swap_n(n: usize) -> Option<Opcode> {
+        OPCODES_MAP.get(format!("swap{n}").as_str()).copied()
+    }
 }
 
 impl fmt::Display for Opcode {
@@ -0,0 +1,88 @@
+use crate::evm::Opcode;
+use std::{fmt, str::FromStr};
+use strum_macros::{Display, EnumString};
+
+/// Supported EVM Hard Forks
+///
+/// Ordered chronologically, such that a fork compares greater than all forks preceding it.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum SupportedEVMVersions {
+    /// The Homestead hard fork
+    Homestead,
+    /// The Byzantium hard fork
+    Byzantium,
+    /// The Constantinople hard fork
+    Constantinople,
+    /// The Petersburg hard fork
+    Petersburg,
+    /// The Istanbul hard fork
+    Istanbul,
+    /// The Berlin hard fork
+    Berlin,
+    /// The London hard fork
+    London,
+    /// The Paris hard fork (The Merge)
+    Paris,
+    /// The Shanghai hard fork
+    Shanghai,
+    /// The Cancun hard fork
+    #[default]
+    Cancun,
+}
+
+/// EVM Version
+///
+/// The hard fork targeted during compilation. Determines which opcodes are available, and
+/// whether code is generated for EOF containers, with relative jumps and functions in code
+/// sections of their own.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EVMVersion {
+    version: SupportedEVMVersions,
+    eof: bool,
+}
+
+impl EVMVersion {
+    /// Public associated function to instantiate a new EVMVersion.
+    pub fn new(version: SupportedEVMVersions) -> Self {
+        Self { version, eof: false }
+    }
+
+    /// The same hard fork, generating code for EOF containers if `eof`
+    pub fn with_eof(self, eof: bool) -> Self {
+        Self { eof, ..self }
+    }
+
+    /// Returns the targeted hard fork
+    pub fn version(&self) -> SupportedEVMVersions {
+        self.version
+    }
+
+    /// Whether code is generated for EOF containers
+    pub fn eof(&self) -> bool {
+        self.eof
+    }
+
+    /// Whether the given opcode is available in the targeted hard fork
+    pub fn supports(&self, opcode: &Opcode) -> bool {
+        self.version >= opcode.introduced_in()
+    }
+}
+
+impl FromStr for EVMVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SupportedEVMVersions::from_str(&s.to_lowercase())
+            .map(Self::new)
+            .map_err(|_| format!("Unsupported EVM version: \"{s}\""))
+    }
+}
+
+impl fmt::Display for EVMVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.version)
+    }
+}
@@ -31,7 +31,7 @@ impl<'a> FullFileSource<'a> {
     pub fn relative_span(&self, span: Ref<'a, Span>) -> Option<Span> {
         self.spans
             .iter()
-            .filter(|s| s.1.start < span.start && s.1.end > span.end)
+            .filter(|s| s.1.start <= span.start && s.1.end >= span.end)
             .map(|s| Span {
                 start: span.start - s.1.start,
                 end: span.end - s.1.start,
@@ -185,15 +185,15 @@ impl Remapper {
 impl Remapper {
     /// Tries to replace path segments in a string with our remappings
     pub fn remap(&self, path: &str) -> Option<String> {
-        let mut path = path.to_string();
-        for (k, v) in self.remappings.iter() {
-            if path.starts_with(k) {
-                tracing::debug!(target: "parser", "found key {} and value {}", k, v);
-                path = path.replace(k, v);
-                return Some(format!("{}{path}", self.base_dir))
-            }
-        }
-        None
+        // Use the longest matching prefix, so the remapping doesn't depend on the map's order
+        let (k, v) = self
+            .remappings
+            .iter()
+            .filter(|(k, _)| path.starts_with(k.as_str()))
+            .max_by_key(|(k, _)| k.len())?;
+        tracing::debug!(target: "parser", "found key {} and value {}", k, v);
+        let path = path.replace(k, v);
+        Some(format!("{}{path}", self.base_dir))
     }
 }
 
@@ -207,7 +207,8 @@ pub struct FileSource {
     pub path: String,
     /// File Source
     pub source: Option<String>,
-    /// Last File Access Time
+    /// Last File Access Time, not serialized so artifacts are reproducible
+    #[serde(skip)]
     pub access: Option<time::Time>,
     /// An Ordered List of File Dependencies
     pub dependencies: Option<Vec<Arc<FileSource>>>,
@@ -326,9 +327,32 @@ pub struct Span {
     /// The end of the span.
     pub end: usize,
     /// The Associated File
+    #[serde(with = "span_file")]
     pub file: Option<Arc<FileSource>>,
 }
 
+/// Serializes a span's file as its path, omitting the file source and dependencies that would
+/// otherwise be repeated for every span.
+mod span_file {
+    use super::FileSource;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::sync::Arc;
+
+    pub fn serialize<S: Serializer>(
+        file: &Option<Arc<FileSource>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        file.as_ref().map(|f| f.path.as_str()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Arc<FileSource>>, D::Error> {
+        Ok(Option::<String>::deserialize(deserializer)?
+            .map(|path| Arc::new(FileSource { path, ..Default::default() })))
+    }
+}
+
 impl Span {
     /// An EOF spans [0, 0].
     pub const EOF: Span = Span { start: 0, end: 0, file: None };
@@ -351,6 +375,12 @@ impl Span {
             .unwrap_or_default()
     }
 
+    /// The 1-indexed line of the file the span starts on
+    pub fn line(&self) -> Option<usize> {
+        let source = self.file.as_ref()?.source.as_ref()?;
+        Some(source.get(0..self.start)?.bytes().filter(|&c| c == b'\n').count() + 1)
+    }
+
     /// Produces a source segment string
     pub fn source_seg(&self) -> String {
         self.file
@@ -0,0 +1,135 @@
+use crate::{
+    ast::BuiltinFunctionKind,
+    evm::OPCODES,
+    token::{TokenKind, DATA_LOCATIONS, DIRECTIVES, KEYWORDS},
+    types::PrimitiveEVMType,
+};
+use serde_json::{json, Value};
+
+/// The TextMate scope name of Huff source files.
+pub const SCOPE_NAME: &str = "source.huff";
+
+/// The names of every primitive EVM type accepted in ABI definitions.
+pub fn primitive_types() -> Vec<String> {
+    let mut types = vec![
+        PrimitiveEVMType::Address,
+        PrimitiveEVMType::Bool,
+        PrimitiveEVMType::String,
+        PrimitiveEVMType::DynBytes,
+    ];
+    types.extend((1..=32).map(|size| PrimitiveEVMType::Uint(size * 8)));
+    types.extend((1..=32).map(|size| PrimitiveEVMType::Int(size * 8)));
+    types.extend((1..=32).map(PrimitiveEVMType::Bytes));
+
+    let mut names = types.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+    // `uint` and `int` default to 256 bits
+    names.extend(["uint".to_string(), "int".to_string()]);
+    names
+}
+
+/// Builds an alternation matching any of the given words.
+///
+/// Longer words are placed first so that a word is never shadowed by one of its prefixes.
+fn word_alternation(words: impl IntoIterator<Item = String>) -> String {
+    let mut words = words.into_iter().collect::<Vec<_>>();
+    words.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    words.dedup();
+    words.iter().map(|w| regex::escape(w)).collect::<Vec<_>>().join("|")
+}
+
+/// Generates a TextMate grammar for Huff.
+///
+/// Keywords, opcodes, builtins and types are taken from the tables the lexer uses, so the
+/// generated grammar can't drift from what the compiler actually accepts.
+pub fn textmate_grammar() -> Value {
+    let directives = word_alternation(DIRECTIVES.iter().map(TokenKind::to_string));
+    let keywords = word_alternation(KEYWORDS.iter().map(TokenKind::to_string));
+    let locations = word_alternation(DATA_LOCATIONS.iter().map(TokenKind::to_string));
+    let builtins = word_alternation(BuiltinFunctionKind::ALL.iter().map(|b| b.name().to_string()));
+    let opcodes = word_alternation(OPCODES.iter().map(|o| o.to_string()));
+    let types = word_alternation(primitive_types());
+    let free_pointers = word_alternation(
+        [TokenKind::FreeStoragePointer, TokenKind::FreeMemoryPointer, TokenKind::StorageSlot]
+            .iter()
+            .map(|t| t.to_string().trim_end_matches("()").to_string()),
+    );
+
+    json!({
+        "$schema": "https://raw.githubusercontent.com/martinring/tmlanguage/master/tmlanguage.json",
+        "name": "Huff",
+        "scopeName": SCOPE_NAME,
+        "fileTypes": ["huff"],
+        "patterns": [
+            { "include": "#comments" },
+            { "include": "#strings" },
+            { "include": "#directives" },
+            { "include": "#keywords" },
+            { "include": "#builtins" },
+            { "include": "#types" },
+            { "include": "#labels" },
+            { "include": "#arguments" },
+            { "include": "#constants" },
+            { "include": "#opcodes" },
+            { "include": "#numbers" }
+        ],
+        "repository": {
+            "comments": {
+                "patterns": [
+                    { "name": "comment.line.double-slash.huff", "match": "//.*$" },
+                    { "name": "comment.block.huff", "begin": "/\\*", "end": "\\*/" }
+                ]
+            },
+            "strings": {
+                "patterns": [
+                    { "name": "string.quoted.double.huff", "begin": "\"", "end": "\"" },
+                    { "name": "string.quoted.single.huff", "begin": "'", "end": "'" }
+                ]
+            },
+            "directives": {
+                "name": "keyword.control.directive.huff",
+                "match": format!("({directives})\\b")
+            },
+            "keywords": {
+                "patterns": [
+                    { "name": "storage.type.huff", "match": format!("\\b({keywords})\\b") },
+                    { "name": "storage.modifier.huff", "match": format!("\\b({locations})\\b") }
+                ]
+            },
+            "builtins": {
+                "patterns": [
+                    { "name": "support.function.builtin.huff", "match": format!("\\b({builtins})\\b") },
+                    {
+                        "name": "support.function.builtin.huff",
+                        "match": format!("\\b({free_pointers})\\b")
+                    }
+                ]
+            },
+            "types": {
+                "name": "support.type.primitive.huff",
+                "match": format!("\\b({types})\\b")
+            },
+            "labels": {
+                "name": "entity.name.label.huff",
+                "match": "\\b[a-zA-Z_][a-zA-Z0-9_]*:"
+            },
+            "arguments": {
+                "name": "variable.parameter.huff",
+                "match": "<[a-zA-Z_][a-zA-Z0-9_]*>"
+            },
+            "constants": {
+                "name": "constant.other.reference.huff",
+                "match": "\\[[a-zA-Z_][a-zA-Z0-9_]*\\]"
+            },
+            "opcodes": {
+                "name": "keyword.operator.opcode.huff",
+                "match": format!("\\b({opcodes})\\b")
+            },
+            "numbers": {
+                "patterns": [
+                    { "name": "constant.numeric.hex.huff", "match": "\\b0x[0-9a-fA-F]+\\b" },
+                    { "name": "constant.numeric.decimal.huff", "match": "\\b[0-9]+\\b" }
+                ]
+            }
+        }
+    })
+}
@@ -32,11 +32,13 @@ pub fn unpack_files(path: &str) -> Result<Vec<String>, UnpackError> {
                 Ok(files) => {
                     let input_files: Vec<String> =
                         files.map(|x| x.unwrap().path().to_str().unwrap().to_string()).collect();
-                    let filtered: Vec<String> = input_files
+                    let mut filtered: Vec<String> = input_files
                         .iter()
                         .filter(|&f| Path::new(&f).extension().unwrap_or_default().eq("huff"))
                         .cloned()
                         .collect();
+                    // Directory entries aren't ordered, sort them so builds are reproducible
+                    filtered.sort();
                     Ok(filtered)
                 }
                 Err(e) => {
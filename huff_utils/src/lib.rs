@@ -18,21 +18,47 @@ pub mod ast;
 /// Bytecode Traits Module
 pub mod bytecode;
 
+/// AST Builders Module
+pub mod builder;
+
+pub mod constructor_args;
+
 /// Token Module
 pub mod token;
 
 /// Lexing Error Module
 pub mod error;
 
+/// Disassembler Module
+pub mod disassembly;
+
+/// EIP-712 Typed Data Module
+pub mod eip712;
+
+/// EOF Containers Module
+pub mod eof;
+
 /// EVM Module
 pub mod evm;
 
+/// EVM Version Module
+pub mod evm_version;
+
+/// Language Editions Module
+pub mod edition;
+
 /// Files Module
 pub mod files;
 
+/// Syntax Highlighting Grammar Module
+pub mod grammar;
+
 /// Lexical Reporting Module
 pub mod report;
 
+/// Shared Identifier Module
+pub mod symbol;
+
 /// IO Module
 pub mod io;
 
@@ -45,6 +71,15 @@ pub mod bytes_util;
 /// Solidity Interface Generator
 pub mod sol_interface;
 
+/// Merkle Trees Module
+pub mod merkle;
+
+/// Precompiles Module
+pub mod precompile;
+
+/// Storage Layout Checks Module
+pub mod storage_check;
+
 /// File Provider Module
 pub mod file_provider;
 
@@ -54,10 +89,15 @@ pub mod time;
 /// Wasm Module
 pub mod wasm;
 
+/// Compiler Warning Module
+pub mod warning;
+
 /// Prelude wraps common utilities.
 pub mod prelude {
     pub use crate::{
-        abi::*, artifact::*, ast::*, bytecode::*, bytes_util::*, error::*, evm::*, files::*, io::*,
-        report::*, sol_interface::*, token::*, types::*,
+        abi::*, artifact::*, ast::*, builder::*, bytecode::*, bytes_util::*, constructor_args::*,
+        disassembly::*, edition::*, eof::*, error::*, evm::*, evm_version::*, files::*, io::*,
+        merkle::*, precompile::*, report::*, sol_interface::*, storage_check::*, symbol::*, token::*,
+        types::*, warning::*,
     };
 }
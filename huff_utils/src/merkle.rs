@@ -0,0 +1,119 @@
+//! ## Merkle Trees
+//!
+//! Computes the roots of merkle trees defined with `#define merkle`, from a json array of their
+//! 32 byte leaves.
+//!
+//! Trees are built like OpenZeppelin's `@openzeppelin/merkle-tree`: the leaves, in the order they
+//! are given, fill the last level of a complete binary tree, and the two children of a node are
+//! hashed sorted, so that proofs don't need to hold the side of each sibling. Their roots can be
+//! verified by `MerkleProof.verify` of OpenZeppelin's contracts for keccak256 trees.
+//!
+//! ```rust
+//! use huff_utils::merkle::*;
+//!
+//! let leaves = leaves_from_json(&format!("[\"0x{}\", \"0x{}\"]", "11".repeat(32), "22".repeat(32))).unwrap();
+//! let root = MerkleHash::Keccak256.root(&leaves).unwrap();
+//! assert_eq!(root, MerkleHash::Keccak256.hash_pair(&leaves[1], &leaves[0]));
+//! ```
+
+use crate::bytes_util::str_to_vec;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use tiny_keccak::{Hasher, Keccak};
+
+/// The hash function of a merkle tree
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MerkleHash {
+    /// Hashed with the `sha3` opcode
+    #[default]
+    Keccak256,
+    /// Hashed with the sha256 precompile at address `0x02`
+    Sha256,
+}
+
+impl TryFrom<&str> for MerkleHash {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "keccak256" => Ok(MerkleHash::Keccak256),
+            "sha256" => Ok(MerkleHash::Sha256),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for MerkleHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MerkleHash::Keccak256 => write!(f, "keccak256"),
+            MerkleHash::Sha256 => write!(f, "sha256"),
+        }
+    }
+}
+
+impl MerkleHash {
+    /// Hashes two nodes, the smaller one first
+    pub fn hash_pair(&self, a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        let (first, second) = if a <= b { (a, b) } else { (b, a) };
+        let mut hash = [0u8; 32];
+        match self {
+            MerkleHash::Keccak256 => {
+                let mut hasher = Keccak::v256();
+                hasher.update(first);
+                hasher.update(second);
+                hasher.finalize(&mut hash);
+            }
+            MerkleHash::Sha256 => hash.copy_from_slice(
+                &Sha256::new().chain_update(first).chain_update(second).finalize(),
+            ),
+        }
+        hash
+    }
+
+    /// The root of the tree of the leaves, or `None` if there are none
+    pub fn root(&self, leaves: &[[u8; 32]]) -> Option<[u8; 32]> {
+        if leaves.is_empty() {
+            return None
+        }
+        // The children of the node at `i` are at `2i + 1` and `2i + 2`, and the leaves are placed
+        // last, in reverse
+        let mut tree = vec![[0u8; 32]; 2 * leaves.len() - 1];
+        let len = tree.len();
+        for (i, leaf) in leaves.iter().enumerate() {
+            tree[len - 1 - i] = *leaf;
+        }
+        for i in (0..len - leaves.len()).rev() {
+            tree[i] = self.hash_pair(&tree[2 * i + 1], &tree[2 * i + 2]);
+        }
+        Some(tree[0])
+    }
+}
+
+/// Reads the leaves of a merkle tree from a json array of 32 byte hex strings
+pub fn leaves_from_json(json: &str) -> Result<Vec<[u8; 32]>, String> {
+    let values = match serde_json::from_str::<serde_json::Value>(json) {
+        Ok(serde_json::Value::Array(values)) => values,
+        Ok(_) => return Err("the leaves must be a json array of 32 byte hex strings".to_string()),
+        Err(e) => return Err(format!("invalid json: {e}")),
+    };
+    if values.is_empty() {
+        return Err("the tree has no leaves".to_string())
+    }
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let hex = value.as_str().map(|s| s.strip_prefix("0x").unwrap_or(s));
+            match hex.filter(|h| h.len() == 64 && h.is_ascii()).map(str_to_vec) {
+                Some(Ok(bytes)) => {
+                    let mut leaf = [0u8; 32];
+                    leaf.copy_from_slice(&bytes);
+                    Ok(leaf)
+                }
+                _ => Err(format!("leaf {i} isn't a 32 byte hex string: {value}")),
+            }
+        })
+        .collect()
+}
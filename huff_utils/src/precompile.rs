@@ -0,0 +1,117 @@
+//! ## Precompiles
+//!
+//! The precompiled contracts called by the precompile builtins, i.e. `__ECRECOVER()`, with the
+//! hard forks introducing them and the gas their calls cost in each hard fork.
+//!
+//! ```rust
+//! use huff_utils::{evm_version::SupportedEVMVersions, precompile::*};
+//!
+//! assert_eq!(Precompile::EcAdd.address(), 0x06);
+//! assert_eq!(Precompile::EcAdd.gas(SupportedEVMVersions::Cancun), (150, 0));
+//! assert_eq!(Precompile::EcPairing.gas(SupportedEVMVersions::Byzantium), (100_000, 80_000));
+//! ```
+
+use crate::evm_version::SupportedEVMVersions;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A precompiled contract
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Precompile {
+    /// Recovers the signer of a hash from a signature
+    Ecrecover,
+    /// Hashes memory with sha256
+    Sha256,
+    /// Modular exponentiation, of 32 byte operands
+    Modexp,
+    /// Adds two points of the alt_bn128 curve
+    EcAdd,
+    /// Multiplies a point of the alt_bn128 curve by a scalar
+    EcMul,
+    /// Checks the alt_bn128 pairing of pairs of points
+    EcPairing,
+    /// Verifies a KZG proof of a blob's evaluation at a point, from EIP-4844
+    PointEvaluation,
+}
+
+impl fmt::Display for Precompile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Precompile::Ecrecover => "ecrecover",
+            Precompile::Sha256 => "sha256",
+            Precompile::Modexp => "modexp",
+            Precompile::EcAdd => "ecadd",
+            Precompile::EcMul => "ecmul",
+            Precompile::EcPairing => "ecpairing",
+            Precompile::PointEvaluation => "point evaluation",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl Precompile {
+    /// The address of the precompile
+    pub fn address(&self) -> u8 {
+        match self {
+            Precompile::Ecrecover => 0x01,
+            Precompile::Sha256 => 0x02,
+            Precompile::Modexp => 0x05,
+            Precompile::EcAdd => 0x06,
+            Precompile::EcMul => 0x07,
+            Precompile::EcPairing => 0x08,
+            Precompile::PointEvaluation => 0x0a,
+        }
+    }
+
+    /// Returns the hard fork in which the precompile was introduced
+    pub fn introduced_in(&self) -> SupportedEVMVersions {
+        match self {
+            Precompile::Ecrecover | Precompile::Sha256 => SupportedEVMVersions::Homestead,
+            Precompile::Modexp | Precompile::EcAdd | Precompile::EcMul | Precompile::EcPairing => {
+                SupportedEVMVersions::Byzantium
+            }
+            Precompile::PointEvaluation => SupportedEVMVersions::Cancun,
+        }
+    }
+
+    /// The gas of a call in the hard fork: its base cost, and its cost per word of input for
+    /// sha256 or per pair of points for the pairing check
+    ///
+    /// Modexp is priced by the bit length of the exponent, so the cost of the largest 32 byte
+    /// exponent is given.
+    pub fn gas(&self, version: SupportedEVMVersions) -> (u64, u64) {
+        // EIP-1108 repriced the alt_bn128 precompiles, and EIP-2565 modexp
+        let istanbul = version >= SupportedEVMVersions::Istanbul;
+        let berlin = version >= SupportedEVMVersions::Berlin;
+        match self {
+            Precompile::Ecrecover => (3000, 0),
+            Precompile::Sha256 => (60, 12),
+            Precompile::Modexp if berlin => (16 * 255 / 3, 0),
+            Precompile::Modexp => (32 * 32 * 255 / 20, 0),
+            Precompile::EcAdd if istanbul => (150, 0),
+            Precompile::EcAdd => (500, 0),
+            Precompile::EcMul if istanbul => (6000, 0),
+            Precompile::EcMul => (40_000, 0),
+            Precompile::EcPairing if istanbul => (45_000, 34_000),
+            Precompile::EcPairing => (100_000, 80_000),
+            Precompile::PointEvaluation => (50_000, 0),
+        }
+    }
+
+    /// The stack items consumed by the precompile's builtin
+    pub fn stack_inputs(&self) -> usize {
+        match self {
+            Precompile::Ecrecover | Precompile::EcAdd | Precompile::PointEvaluation => 4,
+            Precompile::Modexp | Precompile::EcMul => 3,
+            Precompile::Sha256 | Precompile::EcPairing => 2,
+        }
+    }
+
+    /// The stack items pushed by the precompile's builtin
+    pub fn stack_outputs(&self) -> usize {
+        match self {
+            Precompile::EcAdd | Precompile::EcMul => 2,
+            _ => 1,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! ## Storage Check
+//!
+//! Verifies that a contract's storage layout is compatible with a previously deployed layout,
+//! so an upgraded implementation doesn't read or write the slots of other variables.
+//!
+//! The previous layout is either an artifact, whose `storageLayout` is checked against artifacts
+//! of the same contract, or the `storageLayout` array itself.
+//!
+//! ```rust
+//! use huff_utils::prelude::*;
+//!
+//! let check = StorageCheck::from_file_contents(r#"[{ "name": "OWNER", "slot": "0x00" }]"#).unwrap();
+//! let moved = vec![StorageSlot { name: "OWNER".to_string(), slot: str_to_bytes32("01") }];
+//! assert_eq!(check.incompatibilities(&moved).len(), 1);
+//! ```
+
+use crate::{artifact::Artifact, ast::StorageSlot, bytes_util::bytes32_to_string};
+use serde_json::Value;
+
+/// A previously deployed storage layout to check new layouts against
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StorageCheck {
+    /// The name of the contract the layout belongs to, if it's read from the artifact of a
+    /// contract defined with a `#define contract` block
+    pub contract_name: Option<String>,
+    /// The previous storage layout
+    pub layout: Vec<StorageSlot>,
+}
+
+impl StorageCheck {
+    /// Parses the contents of a previous layout file, either an artifact or a storage layout
+    pub fn from_file_contents(contents: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(contents)
+            .map_err(|e| format!("Invalid storage layout file: {e}"))?;
+        let (contract_name, layout) = match value {
+            Value::Object(mut artifact) => (
+                artifact.remove("contractName").and_then(|n| n.as_str().map(String::from)),
+                artifact.remove("storageLayout").unwrap_or_else(|| Value::Array(vec![])),
+            ),
+            layout => (None, layout),
+        };
+        let layout =
+            serde_json::from_value(layout).map_err(|e| format!("Invalid storage layout: {e}"))?;
+        Ok(StorageCheck { contract_name, layout })
+    }
+
+    /// Whether the artifact is checked against the previous layout
+    pub fn applies_to(&self, artifact: &Artifact) -> bool {
+        self.contract_name.is_none() || self.contract_name == artifact.contract_name
+    }
+
+    /// Lists the ways in which the layout is incompatible with the previous layout
+    ///
+    /// Variables of the previous layout must keep their slot, and the slots of removed variables
+    /// must not be reused by new ones. Variables may be removed and new ones added in free slots.
+    pub fn incompatibilities(&self, layout: &[StorageSlot]) -> Vec<String> {
+        let mut incompatibilities = vec![];
+        for current in layout {
+            let slot = bytes32_to_string(&current.slot, true);
+            match self.layout.iter().find(|p| p.name == current.name) {
+                Some(previous) if previous.slot != current.slot => {
+                    incompatibilities.push(format!(
+                        "\"{}\" moved from slot {} to slot {slot}",
+                        current.name,
+                        bytes32_to_string(&previous.slot, true)
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    if let Some(previous) = self.layout.iter().find(|p| {
+                        p.slot == current.slot && !layout.iter().any(|c| c.name == p.name)
+                    }) {
+                        incompatibilities.push(format!(
+                            "\"{}\" reuses slot {slot} of the removed \"{}\"",
+                            current.name, previous.name
+                        ));
+                    }
+                }
+            }
+        }
+        incompatibilities
+    }
+}
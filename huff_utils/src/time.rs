@@ -13,6 +13,11 @@ cfg_if::cfg_if! {
         pub fn get_current_time() -> Time {
             (js_sys::Date::now() / 1000.0) as u64
         }
+
+        /// Returns the milliseconds since the unix epoch, to measure elapsed time
+        pub fn get_current_millis() -> f64 {
+            js_sys::Date::now()
+        }
     } else {
         use std::time::SystemTime;
 
@@ -23,5 +28,13 @@ cfg_if::cfg_if! {
         pub fn get_current_time() -> Time {
             SystemTime::now()
         }
+
+        /// Returns the milliseconds since the unix epoch, to measure elapsed time
+        pub fn get_current_millis() -> f64 {
+            SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs_f64() * 1000.0)
+                .unwrap_or_default()
+        }
     }
 }
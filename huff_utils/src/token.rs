@@ -1,10 +1,45 @@
 use crate::{evm::Opcode, files::Span, types::PrimitiveEVMType};
+use serde::{Deserialize, Serialize};
 use std::{fmt, fmt::Write};
 
 type Literal = [u8; 32];
 
+/// The `#` prefixed directives recognized by the lexer.
+pub const DIRECTIVES: [TokenKind; 3] = [TokenKind::Define, TokenKind::Include, TokenKind::Pragma];
+
+/// The keywords recognized by the lexer outside of macro bodies.
+///
+/// Ordered by match priority, `jumptable__packed` must be checked before `jumptable`.
+pub const KEYWORDS: [TokenKind; 21] = [
+    TokenKind::Macro,
+    TokenKind::Fn,
+    TokenKind::Test,
+    TokenKind::Contract,
+    TokenKind::Function,
+    TokenKind::Constant,
+    TokenKind::Error,
+    TokenKind::Takes,
+    TokenKind::Returns,
+    TokenKind::Event,
+    TokenKind::NonPayable,
+    TokenKind::Payable,
+    TokenKind::Indexed,
+    TokenKind::View,
+    TokenKind::Pure,
+    TokenKind::JumpTablePacked,
+    TokenKind::JumpTable,
+    TokenKind::CodeTable,
+    TokenKind::Data,
+    TokenKind::Struct,
+    TokenKind::Merkle,
+];
+
+/// The data location keywords recognized in ABI argument lists.
+pub const DATA_LOCATIONS: [TokenKind; 3] =
+    [TokenKind::Calldata, TokenKind::Memory, TokenKind::Storage];
+
 /// A single Token
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct Token {
     /// The kind of token
     pub kind: TokenKind,
@@ -20,7 +55,7 @@ impl Token {
 }
 
 /// The kind of token
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub enum TokenKind {
     /// EOF Token
     Eof,
@@ -33,12 +68,16 @@ pub enum TokenKind {
     Define,
     /// "#include" keyword
     Include,
+    /// "#pragma" keyword
+    Pragma,
     /// "macro" keyword
     Macro,
     /// "fn" keyword
     Fn,
     /// "test" keyword
     Test,
+    /// "contract" keyword
+    Contract,
     /// "function" keyword
     Function,
     /// "event" keyword
@@ -63,6 +102,10 @@ pub enum TokenKind {
     Indexed,
     /// "FREE_STORAGE_POINTER()" keyword
     FreeStoragePointer,
+    /// "FREE_MEMORY_POINTER()" keyword
+    FreeMemoryPointer,
+    /// "STORAGE_SLOT()" keyword
+    StorageSlot,
     /// An Identifier
     Ident(String),
     /// Equal Sign
@@ -89,12 +132,16 @@ pub enum TokenKind {
     Sub,
     /// Multiplication
     Mul,
+    /// An Exclamation Mark
+    Bang,
     /// A comma
     Comma,
     /// A Colon
     Colon,
     /// A pound
     Pound,
+    /// An at sign, starting an assertion of an `asm` block, i.e. `@offset(0x20)`
+    At,
     /// Number
     Num(usize),
     /// A Space
@@ -121,6 +168,12 @@ pub enum TokenKind {
     JumpTablePacked,
     /// A Code Table
     CodeTable,
+    /// A Data Section
+    Data,
+    /// A Struct Definition
+    Struct,
+    /// A Merkle Tree Definition
+    Merkle,
     /// A builtin function (__codesize, __tablesize, __tablestart)
     BuiltinFunction(String),
     /// Calldata Data Location
@@ -139,9 +192,11 @@ impl fmt::Display for TokenKind {
             TokenKind::Div => "/",
             TokenKind::Define => "#define",
             TokenKind::Include => "#include",
+            TokenKind::Pragma => "#pragma",
             TokenKind::Macro => "macro",
             TokenKind::Fn => "fn",
             TokenKind::Test => "test",
+            TokenKind::Contract => "contract",
             TokenKind::Function => "function",
             TokenKind::Event => "event",
             TokenKind::Constant => "constant",
@@ -154,6 +209,8 @@ impl fmt::Display for TokenKind {
             TokenKind::Takes => "takes",
             TokenKind::Returns => "returns",
             TokenKind::FreeStoragePointer => "FREE_STORAGE_POINTER()",
+            TokenKind::FreeMemoryPointer => "FREE_MEMORY_POINTER()",
+            TokenKind::StorageSlot => "STORAGE_SLOT()",
             TokenKind::Ident(s) => return write!(f, "{s}"),
             TokenKind::Assign => "=",
             TokenKind::OpenParen => "(",
@@ -167,9 +224,11 @@ impl fmt::Display for TokenKind {
             TokenKind::Add => "+",
             TokenKind::Sub => "-",
             TokenKind::Mul => "*",
+            TokenKind::Bang => "!",
             TokenKind::Colon => ":",
             TokenKind::Comma => ",",
             TokenKind::Pound => "#",
+            TokenKind::At => "@",
             TokenKind::Num(num) => return write!(f, "{num}"),
             TokenKind::Whitespace => " ",
             TokenKind::Str(str) => str,
@@ -194,6 +253,9 @@ impl fmt::Display for TokenKind {
             TokenKind::JumpTable => "jumptable",
             TokenKind::JumpTablePacked => "jumptable__packed",
             TokenKind::CodeTable => "table",
+            TokenKind::Data => "data",
+            TokenKind::Struct => "struct",
+            TokenKind::Merkle => "merkle",
             TokenKind::BuiltinFunction(s) => return write!(f, "BuiltinFunction({s})"),
             TokenKind::Calldata => return write!(f, "calldata"),
             TokenKind::Memory => return write!(f, "memory"),
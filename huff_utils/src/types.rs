@@ -1,11 +1,12 @@
-use crate::bytes_util::*;
+use crate::{bytes_util::*, constructor_args::infer_json};
 use ethers_core::abi::{ethereum_types::*, token::*, Tokenizable};
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::{fmt, str::FromStr};
 
 /// Primitive EVM types
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum PrimitiveEVMType {
     /// String type
     String,
@@ -82,6 +83,38 @@ impl TryFrom<String> for PrimitiveEVMType {
     }
 }
 
+impl PrimitiveEVMType {
+    /// Returns the number of bytes a value of this type occupies when pushed onto the stack.
+    ///
+    /// Dynamically sized types (`string` and `bytes`) return `None`.
+    pub fn size(&self) -> Option<usize> {
+        match *self {
+            PrimitiveEVMType::Address => Some(20),
+            PrimitiveEVMType::Bool => Some(1),
+            PrimitiveEVMType::Uint(s) | PrimitiveEVMType::Int(s) => Some(s / 8),
+            PrimitiveEVMType::Bytes(s) => Some(s),
+            PrimitiveEVMType::String | PrimitiveEVMType::DynBytes => None,
+        }
+    }
+
+    /// Checks whether a 32 byte literal can be represented by this type without truncation.
+    ///
+    /// Signed literals are in two's complement, so they must be sign extended from the size of
+    /// the type, i.e. an `int8` fits `-0x80` to `0x7f`.
+    pub fn fits_literal(&self, literal: &[u8; 32]) -> bool {
+        let width = 32 - literal.iter().take_while(|b| **b == 0).count();
+        match (*self, self.size()) {
+            (PrimitiveEVMType::Bool, _) => width <= 1 && literal[31] <= 1,
+            (PrimitiveEVMType::Int(_), Some(size)) => {
+                let extension = if literal[32 - size] & 0x80 == 0 { 0x00 } else { 0xff };
+                literal[..32 - size].iter().all(|b| *b == extension)
+            }
+            (_, Some(size)) => width <= size,
+            (_, None) => false,
+        }
+    }
+}
+
 impl fmt::Display for PrimitiveEVMType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let x = match *self {
@@ -136,8 +169,11 @@ impl TryFrom<String> for EToken {
                 )))
             }
         }
-        // array
+        // array, or a tuple if its json elements are of different kinds, i.e. `[1, ["a b", 2]]`
         if input.starts_with('[') {
+            if let Ok(value @ serde_json::Value::Array(_)) = serde_json::from_str(&input) {
+                return infer_json(&value).map(EToken).map_err(|e| e.to_string())
+            }
             let trimmed_input = input.trim_start_matches('[').trim_end_matches(']');
             let v: Vec<String> =
                 trimmed_input.split(',').map(|x| x.replace([' ', '"', '\''], "")).collect();
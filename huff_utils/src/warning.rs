@@ -0,0 +1,108 @@
+use crate::prelude::AstSpan;
+use std::fmt;
+
+/// A Compiler Warning
+///
+/// Warnings never fail compilation, they point at code that is likely a mistake.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CompilerWarning {
+    /// The kind of warning
+    pub kind: WarningKind,
+    /// The span of the code the warning is about
+    pub span: AstSpan,
+}
+
+impl CompilerWarning {
+    /// Public associated function to instantiate a new CompilerWarning.
+    pub fn new(kind: WarningKind, span: AstSpan) -> Self {
+        Self { kind, span }
+    }
+}
+
+/// The kind of a [CompilerWarning]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WarningKind {
+    /// Statements in a macro can never be executed
+    UnreachableCode(String),
+    /// A selector dispatch can fall through into the first label
+    MissingFallthrough(String),
+    /// A store in a macro is overwritten before it can be read
+    DeadStore(String, String),
+    /// A decimal literal looks like a hex literal missing its `0x` prefix
+    DecimalLiteral(String),
+    /// A macro leaves a different number of stack items than the outputs it names, along with
+    /// the number it leaves
+    OutputMismatch(String, Vec<String>, usize),
+    /// The success flag of a call opcode in a macro is popped without being checked, along with
+    /// the opcode
+    UncheckedCall(String, String),
+    /// A loop in a macro leaves more items on the stack on every iteration, along with the label
+    /// it jumps back to and the number of items it adds
+    GrowingLoop(String, String, usize),
+}
+
+impl WarningKind {
+    /// The name of the warning, silencing it in a `// huff-ignore: <name>` comment
+    pub fn name(&self) -> &'static str {
+        match self {
+            WarningKind::UnreachableCode(_) => "unreachable-code",
+            WarningKind::MissingFallthrough(_) => "missing-fallthrough",
+            WarningKind::DeadStore(..) => "dead-store",
+            WarningKind::DecimalLiteral(_) => "decimal-literal",
+            WarningKind::OutputMismatch(..) => "output-mismatch",
+            WarningKind::UncheckedCall(..) => "unchecked-call",
+            WarningKind::GrowingLoop(..) => "growing-loop",
+        }
+    }
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WarningKind::UnreachableCode(name) => {
+                write!(f, "Unreachable Code In Macro \"{name}\"")
+            }
+            WarningKind::MissingFallthrough(name) => {
+                write!(
+                    f,
+                    "Selector Dispatch In Macro \"{name}\" Falls Through Into The First Label, Missing A Terminating Revert"
+                )
+            }
+            WarningKind::DeadStore(name, store) => {
+                write!(f, "{store} In Macro \"{name}\" Is Overwritten Before Being Read")
+            }
+            WarningKind::OutputMismatch(name, outputs, height) => {
+                write!(
+                    f,
+                    "Macro \"{name}\" Leaves {height} Stack Item(s) But Returns {} ({})",
+                    outputs.len(),
+                    outputs.join(", ")
+                )
+            }
+            WarningKind::UncheckedCall(name, opcode) => {
+                write!(
+                    f,
+                    "Success Of \"{opcode}\" In Macro \"{name}\" Is Popped Without Being Checked"
+                )
+            }
+            WarningKind::GrowingLoop(name, label, growth) => {
+                write!(
+                    f,
+                    "Loop At Label \"{label}\" In Macro \"{name}\" Adds {growth} Stack Item(s) Per Iteration"
+                )
+            }
+            WarningKind::DecimalLiteral(literal) => {
+                write!(
+                    f,
+                    "Decimal Literal \"{literal}\" Looks Like A Hex Literal Missing Its \"0x\" Prefix"
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for CompilerWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\nWarning: {}\n{}\n", self.kind, self.span.error(None))
+    }
+}
@@ -33,6 +33,30 @@ fn convert_function_param_type() {
     }
 }
 
+#[test]
+fn test_func_param_type_head_size() {
+    for (func_type, dynamic, head_size) in [
+        ("uint256", false, 32),
+        ("address", false, 32),
+        ("bytes", true, 32),
+        ("string", true, 32),
+        ("uint256[3]", false, 96),
+        ("uint8[2][3]", false, 192),
+        ("uint256[]", true, 32),
+        ("bytes[2]", true, 32),
+        ("address[2][]", true, 32),
+    ] {
+        let ty = FunctionParamType::convert_string_to_type(func_type).unwrap();
+        assert_eq!(ty.is_dynamic(), dynamic, "{func_type}");
+        assert_eq!(ty.head_size(), head_size, "{func_type}");
+    }
+
+    let tuple = FunctionParamType::Tuple(vec![FunctionParamType::Bool, FunctionParamType::Uint(8)]);
+    assert_eq!(tuple.head_size(), 64);
+    let tuple = FunctionParamType::Tuple(vec![FunctionParamType::Bool, FunctionParamType::Bytes]);
+    assert_eq!(tuple.head_size(), 32);
+}
+
 #[test]
 fn test_display_func_param_type() {
     let func_types = [
@@ -77,3 +101,89 @@ fn test_display_func_param_type() {
         }
     }
 }
+
+#[test]
+fn test_to_json_abi() {
+    let param = |name: &str, kind: FunctionParamType| FunctionParam {
+        name: name.to_string(),
+        kind,
+        internal_type: None,
+    };
+    let mut abi = Abi::new();
+    abi.constructor =
+        Some(Constructor { inputs: vec![param("owner", FunctionParamType::Address)] });
+    abi.functions.insert(
+        "balances".to_string(),
+        Function {
+            name: "balances".to_string(),
+            inputs: vec![param(
+                "keys",
+                FunctionParamType::Array(
+                    Box::new(FunctionParamType::Tuple(vec![
+                        FunctionParamType::Address,
+                        FunctionParamType::Uint(256),
+                    ])),
+                    vec![0],
+                ),
+            )],
+            outputs: vec![param("", FunctionParamType::Uint(256))],
+            constant: false,
+            state_mutability: huff_utils::ast::FunctionType::View,
+        },
+    );
+    abi.events.insert(
+        "Transfer".to_string(),
+        Event {
+            name: "Transfer".to_string(),
+            inputs: vec![EventParam {
+                name: "from".to_string(),
+                kind: FunctionParamType::Address,
+                indexed: true,
+            }],
+            anonymous: false,
+        },
+    );
+    abi.errors.insert(
+        "Unauthorized".to_string(),
+        Error { name: "Unauthorized".to_string(), inputs: vec![] },
+    );
+
+    assert_eq!(
+        abi.to_json_abi(),
+        serde_json::json!([
+            {
+                "type": "constructor",
+                "inputs": [{ "name": "owner", "type": "address" }],
+                "stateMutability": "nonpayable"
+            },
+            {
+                "type": "function",
+                "name": "balances",
+                "inputs": [{
+                    "name": "keys",
+                    "type": "tuple[]",
+                    "components": [
+                        { "name": "", "type": "address" },
+                        { "name": "", "type": "uint256" }
+                    ]
+                }],
+                "outputs": [{ "name": "", "type": "uint256" }],
+                "stateMutability": "view"
+            },
+            {
+                "type": "event",
+                "name": "Transfer",
+                "inputs": [{ "name": "from", "type": "address", "indexed": true }],
+                "anonymous": false
+            },
+            { "type": "error", "name": "Unauthorized", "inputs": [] }
+        ])
+    );
+
+    // The JSON ABI can be read by ethers
+    let parsed: ethers_core::abi::Abi = serde_json::from_value(abi.to_json_abi()).unwrap();
+    assert_eq!(
+        parsed.function("balances").unwrap().short_signature(),
+        abi.functions["balances"].selector()
+    );
+}
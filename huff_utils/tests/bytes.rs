@@ -10,6 +10,14 @@ fn test_bytes32_to_string() {
     assert_eq!(converted_string, "fe");
 }
 
+#[test]
+fn test_bytes32_to_push() {
+    assert_eq!(bytes32_to_push(&str_to_bytes32("0001")), "6001");
+    assert_eq!(bytes32_to_push(&str_to_bytes32("00")), "6000");
+    assert_eq!(bytes32_to_push(&str_to_bytes32("000000a57b")), "61a57b");
+    assert_eq!(bytes32_to_push(&[0xff; 32]), format!("7f{}", "ff".repeat(32)));
+}
+
 #[test]
 fn test_hex_to_usize() {
     for i in 0..255 {
@@ -18,3 +26,24 @@ fn test_hex_to_usize() {
         assert_eq!(converted_usize, i);
     }
 }
+
+#[test]
+fn test_eip1967_slot() {
+    assert_eq!(
+        bytes32_to_string(&eip1967_slot("eip1967.proxy.implementation"), false),
+        "360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc"
+    );
+    assert_eq!(
+        bytes32_to_string(&eip1967_slot("eip1967.proxy.beacon"), false),
+        "a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50"
+    );
+}
+
+#[test]
+fn test_erc7201_slot() {
+    // The example namespace of the ERC
+    assert_eq!(
+        bytes32_to_string(&erc7201_slot("example.main"), false),
+        "183a6125c38840424c4a85fa12bab2ab606c4b6d0e7cc73c0c06ba5300eab500"
+    );
+}
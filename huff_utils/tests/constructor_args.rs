@@ -0,0 +1,203 @@
+use ethers_core::{
+    abi::{Token, Tokenizable},
+    types::{H160, U256},
+    utils::hex,
+};
+use huff_utils::prelude::*;
+
+fn params(kinds: Vec<FunctionParamType>) -> Vec<FunctionParam> {
+    kinds
+        .into_iter()
+        .enumerate()
+        .map(|(i, kind)| FunctionParam { name: format!("arg{i}"), kind, internal_type: None })
+        .collect()
+}
+
+const OWNER: &str = "0x646dB8ffC21e7ddc2B6327448dd9Fa560Df41087";
+
+#[test]
+fn test_typed_values() {
+    let args = ConstructorArgs::Values(vec![
+        OWNER.to_string(),
+        "0x01".to_string(),
+        "0x1234".to_string(),
+        "[1, 2, 3]".to_string(),
+        "(true,hello)".to_string(),
+    ]);
+    let inputs = params(vec![
+        "address".into(),
+        "uint256".into(),
+        "bytes".into(),
+        "uint8[]".into(),
+        FunctionParamType::Tuple(vec![FunctionParamType::Bool, FunctionParamType::String]),
+    ]);
+    let tokens = args.tokenize(Some(&inputs)).unwrap();
+    assert_eq!(tokens[0], OWNER.parse::<H160>().unwrap().into_token());
+    assert_eq!(tokens[1], Token::Uint(U256::one()));
+    assert_eq!(tokens[2], Token::Bytes(vec![0x12, 0x34]));
+    assert_eq!(
+        tokens[3],
+        Token::Array(vec![
+            Token::Uint(U256::from(1)),
+            Token::Uint(U256::from(2)),
+            Token::Uint(U256::from(3))
+        ])
+    );
+    assert_eq!(
+        tokens[4],
+        Token::Tuple(vec![Token::Bool(true), Token::String("hello".to_string())])
+    );
+}
+
+#[test]
+fn test_json_file() {
+    let contents = format!(r#"["{OWNER}", 10, [[1, 2], [3, 4]], [true, ["a", "b"]]]"#);
+    let args = ConstructorArgs::from_file_contents(&contents).unwrap();
+    let inputs = params(vec![
+        "address".into(),
+        "uint256".into(),
+        "uint256[2][]".into(),
+        FunctionParamType::Tuple(vec![FunctionParamType::Bool, "string[]".into()]),
+    ]);
+    let tokens = args.tokenize(Some(&inputs)).unwrap();
+    assert_eq!(tokens[1], Token::Uint(U256::from(10)));
+    assert_eq!(
+        tokens[2],
+        Token::Array(vec![
+            Token::FixedArray(vec![Token::Uint(U256::from(1)), Token::Uint(U256::from(2))]),
+            Token::FixedArray(vec![Token::Uint(U256::from(3)), Token::Uint(U256::from(4))]),
+        ])
+    );
+    assert_eq!(
+        tokens[3],
+        Token::Tuple(vec![
+            Token::Bool(true),
+            Token::Array(vec![Token::String("a".to_string()), Token::String("b".to_string())])
+        ])
+    );
+}
+
+#[test]
+fn test_encoded_file() {
+    let tokens = vec![OWNER.parse::<H160>().unwrap().into_token(), Token::Uint(U256::from(7))];
+    let encoded = hex::encode(ethers_core::abi::encode(&tokens));
+    let args = ConstructorArgs::from_file_contents(&format!("0x{encoded}\n")).unwrap();
+    assert_eq!(
+        args.tokenize(Some(&params(vec!["address".into(), "uint256".into()]))).unwrap(),
+        tokens
+    );
+
+    // Encoded arguments can't be decoded without types
+    assert!(args.tokenize(None).is_err());
+}
+
+#[test]
+fn test_untyped_values() {
+    let args = ConstructorArgs::Values(vec!["10000".to_string(), "false".to_string()]);
+    assert_eq!(
+        args.tokenize(None).unwrap(),
+        vec![Token::Uint(U256::from(10000)), Token::Bool(false)]
+    );
+}
+
+#[test]
+fn test_mistyped_values() {
+    let inputs = params(vec!["address".into(), "uint256[2]".into()]);
+
+    let args = ConstructorArgs::Values(vec!["0x1234".to_string(), "[1, 2]".to_string()]);
+    let err = args.tokenize(Some(&inputs)).unwrap_err();
+    assert!(err
+        .starts_with("Invalid constructor argument \"0x1234\" for param \"arg0\" of type address"));
+
+    let args = ConstructorArgs::Values(vec![OWNER.to_string(), "[1, 2, 3]".to_string()]);
+    let err = args.tokenize(Some(&inputs)).unwrap_err();
+    assert!(err.ends_with("expected 2 elements, got 3"));
+
+    let args = ConstructorArgs::Values(vec![OWNER.to_string()]);
+    assert_eq!(
+        args.tokenize(Some(&inputs)).unwrap_err(),
+        "Expected 2 constructor arguments, got 1"
+    );
+
+    assert!(ConstructorArgs::from_file_contents("[1, 2").is_err());
+    assert!(ConstructorArgs::from_file_contents("0xzz").is_err());
+}
+
+#[test]
+fn test_uint_bounds() {
+    let inputs = params(vec!["uint8".into()]);
+    let args = ConstructorArgs::Values(vec!["0xff".to_string()]);
+    assert_eq!(args.tokenize(Some(&inputs)).unwrap(), vec![Token::Uint(U256::from(255))]);
+
+    let args = ConstructorArgs::Values(vec!["256".to_string()]);
+    assert!(args.tokenize(Some(&inputs)).unwrap_err().ends_with("value doesn't fit in 8 bits"));
+}
+
+#[test]
+fn test_json_values() {
+    let json = r#"["  spaced, with commas ", "0x1234", "115792089237316195423570985008687907853269984665640564039457584007913129639935", [[1, "two words"]]]"#;
+    let args = ConstructorArgs::from_json(json).unwrap();
+    let inputs = params(vec![
+        "string".into(),
+        "bytes".into(),
+        "uint256".into(),
+        FunctionParamType::Array(
+            Box::new(FunctionParamType::Tuple(vec!["uint8".into(), "string".into()])),
+            vec![0],
+        ),
+    ]);
+    let tokens = args.tokenize(Some(&inputs)).unwrap();
+    assert_eq!(tokens[0], Token::String("  spaced, with commas ".to_string()));
+    assert_eq!(tokens[1], Token::Bytes(vec![0x12, 0x34]));
+    assert_eq!(tokens[2], Token::Uint(U256::MAX));
+    assert_eq!(
+        tokens[3],
+        Token::Array(vec![Token::Tuple(vec![
+            Token::Uint(U256::one()),
+            Token::String("two words".to_string())
+        ])])
+    );
+
+    // Without a declared constructor, the type of each value is inferred from it
+    let args = ConstructorArgs::from_json(&format!(
+        r#"[7, -1, "{OWNER}", "hello world", [1, 2], [true, "a"]]"#
+    ))
+    .unwrap();
+    assert_eq!(
+        args.tokenize(None).unwrap(),
+        vec![
+            Token::Uint(U256::from(7)),
+            (-1i128).into_token(),
+            OWNER.parse::<H160>().unwrap().into_token(),
+            Token::String("hello world".to_string()),
+            Token::Array(vec![Token::Uint(U256::one()), Token::Uint(U256::from(2))]),
+            Token::Tuple(vec![Token::Bool(true), Token::String("a".to_string())]),
+        ]
+    );
+}
+
+#[test]
+fn test_json_error_locations() {
+    let inputs = params(vec![
+        "address".into(),
+        FunctionParamType::Tuple(vec!["bool".into(), "uint8[]".into()]),
+    ]);
+    let json = format!("[\n  \"{OWNER}\",\n  [true, [1, \"0x100\", 3]]\n]");
+    let err = ConstructorArgs::from_json(&json).unwrap().tokenize(Some(&inputs)).unwrap_err();
+    assert_eq!(
+        err,
+        "Invalid constructor argument \"0x100\" for param \"arg1\"[1][1] of type uint8 at line 3, column 14: value doesn't fit in 8 bits"
+    );
+
+    let err =
+        ConstructorArgs::from_json(r#"[true, [1, 1.5]]"#).unwrap().tokenize(None).unwrap_err();
+    assert_eq!(
+        err,
+        "Invalid constructor argument 1.5 for value[1] at line 1, column 12: not an integer json number, pass large integers as strings"
+    );
+
+    assert_eq!(
+        ConstructorArgs::from_json(r#"{"owner": 1}"#).unwrap_err(),
+        "Json constructor arguments must be an array of values"
+    );
+}
@@ -0,0 +1,63 @@
+use huff_utils::prelude::*;
+
+#[test]
+fn test_disassemble() {
+    let ops = disassemble("0x6001600201600052fe0c").unwrap();
+    let rendered = ops.iter().map(|op| op.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        rendered,
+        vec![
+            "0x0000 push1 0x01",
+            "0x0002 push1 0x02",
+            "0x0004 add",
+            "0x0005 push1 0x00",
+            "0x0007 mstore",
+            "0x0008 invalid",
+            "0x0009 unknown(0x0c)",
+        ]
+    );
+    assert_eq!(ops[0].opcode, Some(Opcode::Push1));
+    assert_eq!(ops[6].opcode, None);
+}
+
+#[test]
+fn test_disassemble_truncated_push() {
+    let ops = disassemble("61ff").unwrap();
+    assert_eq!(ops.len(), 1);
+    assert_eq!(ops[0].immediate, vec![0xff]);
+}
+
+#[test]
+fn test_disassemble_invalid_hex() {
+    assert!(disassemble("600").is_err());
+    assert!(disassemble("zz").is_err());
+}
+
+#[test]
+fn test_builders() {
+    let main = MacroDefinitionBuilder::new("MAIN")
+        .takes(1)
+        .returns(2)
+        .parameter("slot")
+        .arg_call("slot")
+        .label("end")
+        .opcode(Opcode::Stop)
+        .build();
+    assert_eq!((main.takes, main.returns), (1, 2));
+    assert_eq!(main.parameters[0].name.as_deref(), Some("slot"));
+    assert_eq!(main.statements.len(), 2);
+    match &main.statements[1].ty {
+        StatementType::Label(label) => {
+            assert_eq!(label.name, "end");
+            assert_eq!(label.inner[0].ty, StatementType::Opcode(Opcode::Stop));
+        }
+        ty => panic!("Expected a label, got {ty}"),
+    }
+
+    let contract = ContractBuilder::new()
+        .constant("SLOT", ConstVal::FreeStoragePointer(FreeStoragePointer::default()))
+        .macro_definition(MacroDefinitionBuilder::new("MAIN").constant("SLOT").build())
+        .build();
+    let constants = contract.constants.lock().unwrap();
+    assert_eq!(constants[0].value, ConstVal::Literal(str_to_bytes32("00")));
+}
@@ -0,0 +1,72 @@
+use huff_utils::{eip712::*, prelude::*};
+
+#[test]
+fn test_type_hash() {
+    // The type hashes of the examples of EIP-712
+    assert_eq!(
+        bytes32_to_string(&type_hash(DOMAIN_TYPE).unwrap(), false),
+        "8b73c3c69bb8fe3d512ecc4cf759cc79239f7b179b0ffacaa9a75d522b39400f"
+    );
+    assert_eq!(
+        bytes32_to_string(
+            &type_hash(
+                "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+            )
+            .unwrap(),
+            false
+        ),
+        "a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2"
+    );
+
+    // Structs without members, and arrays of structs
+    assert!(type_hash("Empty()").is_ok());
+    assert!(type_hash("Batch(Order[] orders,bytes32[2] salts)Order(address maker)").is_ok());
+}
+
+#[test]
+fn test_type_hash_errors() {
+    for (encode_type, reason) in [
+        ("", "Expected `Name(type name,...)` in EIP-712 type: \"\""),
+        ("Permit(address owner", "Unclosed members of struct \"Permit\" in EIP-712 type"),
+        ("Permit (address owner)", "Invalid struct name \"Permit \" in EIP-712 type"),
+        (
+            "Permit(address owner, uint256 value)",
+            "Invalid member \" uint256 value\" of struct \"Permit\", members must be `type name` separated by commas without spaces",
+        ),
+        (
+            "Permit(uint value)",
+            "Type \"uint\" of struct \"Permit\" is neither a canonical elementary type nor a struct of the EIP-712 type",
+        ),
+        (
+            "Mail(Person from)",
+            "Type \"Person\" of struct \"Mail\" is neither a canonical elementary type nor a struct of the EIP-712 type",
+        ),
+        (
+            "Mail(Person from,Asset asset)Person(string name)Asset(address token)",
+            "The structs referenced by \"Mail\" must follow it sorted by name: Asset, Person",
+        ),
+        (
+            "Mail(string contents)Person(string name)",
+            "The structs referenced by \"Mail\" must follow it sorted by name: none",
+        ),
+    ] {
+        assert_eq!(type_hash(encode_type).unwrap_err(), reason, "{encode_type}");
+    }
+}
+
+#[test]
+fn test_struct_type() {
+    let def = StructDefinition {
+        name: "Order".to_string(),
+        fields: [("address", "maker"), ("uint", "amount"), ("bytes32[]", "salts")]
+            .into_iter()
+            .map(|(ty, name)| Argument {
+                arg_type: Some(ty.to_string()),
+                name: Some(name.to_string()),
+                ..Default::default()
+            })
+            .collect(),
+        span: AstSpan(vec![]),
+    };
+    assert_eq!(struct_type(&def), "Order(address maker,uint256 amount,bytes32[] salts)");
+}
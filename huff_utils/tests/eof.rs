@@ -0,0 +1,119 @@
+use ethers_core::utils::hex;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_encode_container() {
+    // The data section is omitted from the header without data
+    let container = EofContainer::new(vec![0x00], vec![]);
+    assert_eq!(container.to_string(), "ef00010100010000");
+    assert_eq!(container.data_offset(), 8);
+
+    let container = EofContainer::new(vec![0x60, 0x01, 0x00], vec![0xaa, 0xbb]);
+    assert_eq!(container.to_string(), "ef000101000302000200600100aabb");
+    assert_eq!(container.data_offset(), 13);
+    assert_eq!(EofContainer::header_size(0, 0), 7);
+    assert_eq!(EofContainer::header_size(0, 2), 10);
+}
+
+#[test]
+fn test_encode_container_with_functions() {
+    // The type section lists the inputs and outputs of each code section, main's being zero
+    let container = EofContainer::new(vec![0xe3, 0x00, 0x01, 0x00], vec![0xaa])
+        .with_functions(vec![EofFunction { inputs: 1, outputs: 2, code: vec![0xe4] }]);
+    assert_eq!(container.to_string(), "ef00010300040100040100010200010000000102e3000100e4aa");
+    assert_eq!(container.data_offset(), 25);
+    assert_eq!(EofContainer::header_size(1, 1), 20);
+    assert_eq!(EofContainer::decode(&container.encode()).unwrap(), container);
+    assert!(container.validate().is_ok());
+
+    let invalid = |bytes: &str| EofContainer::decode(&hex::decode(bytes).unwrap()).unwrap_err();
+    assert_eq!(invalid("ef0001010001010001000000"), "missing the type section header");
+    assert_eq!(
+        invalid("ef0001030002010001010001000000000000"),
+        "the type section is 2 bytes, but there are 2 code sections"
+    );
+}
+
+#[test]
+fn test_decode_container() {
+    for container in [
+        EofContainer::new(vec![0x00], vec![]),
+        EofContainer::new(vec![0x60, 0x01, 0x00], vec![0xaa, 0xbb]),
+    ] {
+        assert_eq!(EofContainer::decode(&container.encode()).unwrap(), container);
+    }
+
+    let invalid = |bytes: &[u8]| EofContainer::decode(bytes).unwrap_err();
+    assert_eq!(invalid(&[0x60, 0x00]), "missing the EOF magic");
+    assert_eq!(invalid(&[0xef, 0x00, 0x02]), "unsupported version Some(2)");
+    assert_eq!(invalid(&[0xef, 0x00, 0x01, 0x02]), "missing the code section header");
+    assert_eq!(invalid(&[0xef, 0x00, 0x01, 0x01, 0x00]), "truncated section header");
+    assert_eq!(
+        invalid(&[0xef, 0x00, 0x01, 0x01, 0x00, 0x01, 0xff, 0x00]),
+        "missing the header terminator"
+    );
+    assert_eq!(
+        invalid(&[0xef, 0x00, 0x01, 0x01, 0x00, 0x02, 0x00, 0x00]),
+        "the container is 8 bytes, but its header declares 9"
+    );
+}
+
+#[test]
+fn test_validate_container() {
+    assert!(EofContainer::new(vec![0x60, 0x01, 0x00], vec![0x0c]).validate().is_ok());
+
+    let invalid = |code: Vec<u8>, data: Vec<u8>| EofContainer::new(code, data).validate();
+    assert_eq!(invalid(vec![], vec![]).unwrap_err(), "the code section is empty");
+    assert_eq!(
+        invalid(vec![0x00, 0x0c], vec![]).unwrap_err(),
+        "undefined instruction 0x0c at offset 0x1"
+    );
+    assert_eq!(
+        invalid(vec![0x00, 0x61, 0xff], vec![]).unwrap_err(),
+        "truncated push2 at offset 0x1"
+    );
+    assert_eq!(
+        invalid(vec![0x00], vec![0xff; MAX_SECTION_SIZE + 1]).unwrap_err(),
+        "the data section is 65536 bytes, more than the 65535 bytes of a section"
+    );
+}
+
+#[test]
+fn test_validate_relative_jumps() {
+    // `rjumpi` past its immediate to the `stop`, then `rjump` back to the start
+    assert!(validate_code(&[0x5f, 0xe1, 0x00, 0x03, 0xe0, 0xff, 0xfa, 0x00], 1).is_ok());
+
+    assert_eq!(
+        validate_code(&[0xe0, 0x00, 0x01, 0x60, 0x00], 1).unwrap_err(),
+        "rjump at offset 0x0 doesn't jump to an instruction of its section"
+    );
+    assert_eq!(
+        validate_code(&[0xe1, 0xff, 0xf0], 1).unwrap_err(),
+        "rjumpi at offset 0x0 doesn't jump to an instruction of its section"
+    );
+    assert_eq!(validate_code(&[0xe0, 0x00], 1).unwrap_err(), "truncated rjump at offset 0x0");
+}
+
+#[test]
+fn test_validate_functions() {
+    let function = |inputs, code: Vec<u8>| EofFunction { inputs, outputs: 0, code };
+    let invalid = |functions| {
+        EofContainer::new(vec![0xe3, 0x00, 0x01, 0x00], vec![])
+            .with_functions(functions)
+            .validate()
+            .unwrap_err()
+    };
+    assert_eq!(
+        invalid(vec![function(0, vec![0xe3, 0x00, 0x02, 0xe4])]),
+        "function 1: callf to undefined code section 2 at offset 0x0"
+    );
+    assert_eq!(invalid(vec![function(0, vec![])]), "the code section of function 1 is empty");
+    assert_eq!(
+        invalid(vec![function(0x80, vec![0xe4])]),
+        "function 1 takes or returns 128 stack items, more than 127"
+    );
+    assert_eq!(
+        EofContainer::new(vec![0xe3, 0x00, 0x01, 0x00], vec![]).validate().unwrap_err(),
+        "callf to undefined code section 1 at offset 0x0"
+    );
+}
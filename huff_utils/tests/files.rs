@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use huff_utils::{files, prelude::Span};
 use tracing_subscriber::EnvFilter;
@@ -27,6 +27,20 @@ fn test_remappings_from_file() {
     );
 }
 
+#[test]
+fn test_remap_longest_prefix() {
+    let remapper = files::Remapper {
+        remappings: HashMap::from([
+            ("@lib/".to_string(), "lib/".to_string()),
+            ("@lib/token/".to_string(), "lib/token/src/".to_string()),
+        ]),
+        base_dir: "./".to_string(),
+    };
+    assert_eq!(remapper.remap("@lib/token/ERC20.huff").unwrap(), "./lib/token/src/ERC20.huff");
+    assert_eq!(remapper.remap("@lib/utils/Math.huff").unwrap(), "./lib/utils/Math.huff");
+    assert_eq!(remapper.remap("src/Main.huff"), None);
+}
+
 #[test]
 fn test_source_seg() {
     let span = Span {
@@ -59,6 +73,8 @@ fn test_source_seg() {
             " ", 3, "#define function addressGetter() internal returns (address)", " ",
         )
     );
+    assert_eq!(span.line(), Some(3));
+    assert_eq!(Span { file: None, ..span }.line(), None);
 }
 
 #[test]
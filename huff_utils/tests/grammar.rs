@@ -0,0 +1,79 @@
+use huff_utils::{
+    ast::BuiltinFunctionKind,
+    evm::OPCODES,
+    grammar::{textmate_grammar, SCOPE_NAME},
+    token::{DATA_LOCATIONS, DIRECTIVES, KEYWORDS},
+};
+use regex::Regex;
+use serde_json::Value;
+
+fn pattern(grammar: &Value, rule: &str, index: Option<usize>) -> Regex {
+    let rule = &grammar["repository"][rule];
+    let rule = match index {
+        Some(i) => &rule["patterns"][i],
+        None => rule,
+    };
+    Regex::new(&format!("^(?:{})$", rule["match"].as_str().unwrap())).unwrap()
+}
+
+#[test]
+fn test_grammar_scope() {
+    let grammar = textmate_grammar();
+    assert_eq!(grammar["scopeName"], SCOPE_NAME);
+    assert_eq!(grammar["fileTypes"][0], "huff");
+}
+
+#[test]
+fn test_grammar_matches_every_opcode() {
+    let grammar = textmate_grammar();
+    let opcodes = pattern(&grammar, "opcodes", None);
+    for opcode in OPCODES {
+        assert!(opcodes.is_match(opcode), "opcode `{opcode}` is not highlighted");
+    }
+    assert!(!opcodes.is_match("push33"));
+}
+
+#[test]
+fn test_grammar_matches_every_keyword() {
+    let grammar = textmate_grammar();
+    let directives = pattern(&grammar, "directives", None);
+    for directive in DIRECTIVES {
+        assert!(directives.is_match(&directive.to_string()));
+    }
+    let keywords = pattern(&grammar, "keywords", Some(0));
+    for keyword in KEYWORDS {
+        assert!(keywords.is_match(&keyword.to_string()), "keyword `{keyword}` is not highlighted");
+    }
+    let locations = pattern(&grammar, "keywords", Some(1));
+    for location in DATA_LOCATIONS {
+        assert!(locations.is_match(&location.to_string()));
+    }
+}
+
+#[test]
+fn test_grammar_matches_every_builtin() {
+    let grammar = textmate_grammar();
+    let builtins = pattern(&grammar, "builtins", Some(0));
+    for builtin in BuiltinFunctionKind::ALL {
+        assert!(
+            builtins.is_match(builtin.name()),
+            "builtin `{}` is not highlighted",
+            builtin.name()
+        );
+        assert_eq!(BuiltinFunctionKind::try_from(&builtin.name().to_string()), Ok(builtin));
+    }
+    assert!(pattern(&grammar, "builtins", Some(1)).is_match("FREE_STORAGE_POINTER"));
+    assert!(pattern(&grammar, "builtins", Some(1)).is_match("FREE_MEMORY_POINTER"));
+    assert!(pattern(&grammar, "builtins", Some(1)).is_match("STORAGE_SLOT"));
+}
+
+#[test]
+fn test_grammar_matches_primitive_types() {
+    let grammar = textmate_grammar();
+    let types = pattern(&grammar, "types", None);
+    for ty in ["address", "bool", "string", "bytes", "bytes32", "uint", "uint8", "int256"] {
+        assert!(types.is_match(ty), "type `{ty}` is not highlighted");
+    }
+    assert!(!types.is_match("uint7"));
+    assert!(!types.is_match("bytes33"));
+}
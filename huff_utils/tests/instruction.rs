@@ -0,0 +1,63 @@
+use huff_utils::prelude::*;
+
+fn span() -> AstSpan {
+    AstSpan(vec![])
+}
+
+#[test]
+fn test_push_value_uses_smallest_push() {
+    let push = Instruction::push_value(&str_to_bytes32("000000a57b"), span());
+    assert_eq!(push.opcode, Some(Opcode::Push2));
+    assert_eq!(push.len(), 3);
+    assert_eq!(push.to_string(), "61a57b");
+
+    let zero = Instruction::push_value(&[0u8; 32], span());
+    assert_eq!(zero.to_string(), "6000");
+}
+
+#[test]
+fn test_push_keeps_leading_zeros() {
+    let push = Instruction::push(vec![0x00, 0x00, 0x12, 0x34], span());
+    assert_eq!(push.opcode, Some(Opcode::Push4));
+    assert_eq!(push.to_string(), "6300001234");
+}
+
+#[test]
+fn test_fill_placeholder() {
+    let mut push = Instruction::push_placeholder(2, span());
+    assert_eq!(push.len(), 3);
+    assert_eq!(push.to_string(), "61xxxx");
+
+    assert!(!push.fill(&[0x01]));
+    assert!(push.fill(&[0x01, 0x02]));
+    assert_eq!(push.to_string(), "610102");
+    assert!(!push.fill(&[0x01, 0x02]));
+}
+
+#[test]
+fn test_data_placeholder() {
+    let data = Instruction::data_placeholder(2, vec![0xab], span());
+    assert_eq!(data.len(), 3);
+    assert_eq!(data.to_string(), "xxxxab");
+}
+
+#[test]
+fn test_code_is_emitted_as_written() {
+    let code = Instruction::code("DEADbeef", span()).unwrap();
+    assert_eq!(code.immediate, vec![0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(code.len(), 4);
+    assert_eq!(code.to_string(), "DEADbeef");
+    assert!(Instruction::code("zz", span()).is_none());
+}
+
+#[test]
+fn test_opcode_bytes() {
+    assert_eq!(Opcode::Jumpdest.byte(), 0x5b);
+    assert_eq!(Opcode::Push32.immediate_size(), 32);
+    assert_eq!(Opcode::Add.immediate_size(), 0);
+    assert_eq!(Opcode::push_n(1), Some(Opcode::Push1));
+    assert_eq!(Opcode::push_n(33), None);
+    assert_eq!(Opcode::swap_n(16), Some(Opcode::Swap16));
+    assert_eq!(Opcode::swap_n(17), None);
+    assert_eq!(Opcode::from_byte(0x60), Some(Opcode::Push1));
+}
@@ -0,0 +1,48 @@
+use huff_utils::prelude::*;
+
+fn leaves(count: u8) -> Vec<[u8; 32]> {
+    (1..=count).map(|i| [i; 32]).collect()
+}
+
+#[test]
+fn test_merkle_roots() {
+    // The first leaves are paired first, and the last ones are left for the upper levels
+    let l = leaves(3);
+    let keccak = MerkleHash::Keccak256;
+    assert_eq!(keccak.root(&l).unwrap(), keccak.hash_pair(&keccak.hash_pair(&l[1], &l[0]), &l[2]));
+    assert_eq!(keccak.root(&l[..1]).unwrap(), l[0]);
+    assert_eq!(keccak.root(&[]), None);
+
+    // Pairs are hashed sorted
+    assert_eq!(keccak.hash_pair(&l[0], &l[1]), keccak.hash_pair(&l[1], &l[0]));
+    assert_ne!(keccak.hash_pair(&l[0], &l[1]), MerkleHash::Sha256.hash_pair(&l[0], &l[1]));
+
+    assert_eq!(
+        bytes32_to_string(&MerkleHash::Sha256.root(&l).unwrap(), false),
+        "60457daa082476d6d1c02c1581a36bb4fad5dac0c5e6bdd0ee4adc5cd0425c21"
+    );
+    assert_eq!(
+        bytes32_to_string(&MerkleHash::Sha256.root(&leaves(5)).unwrap(), false),
+        "ca16fd5ea02f6789de83d3c6e4a5a895eaa0f0b599391d40551e6a0089ea86a8"
+    );
+}
+
+#[test]
+fn test_leaves_from_json() {
+    let json = format!("[\"0x{}\", \"{}\"]", "01".repeat(32), "02".repeat(32));
+    assert_eq!(leaves_from_json(&json).unwrap(), leaves(2));
+
+    for (json, reason) in [
+        ("{}", "the leaves must be a json array of 32 byte hex strings".to_string()),
+        ("[]", "the tree has no leaves".to_string()),
+        ("[\"0x01\"]", "leaf 0 isn't a 32 byte hex string: \"0x01\"".to_string()),
+        ("[1]", "leaf 0 isn't a 32 byte hex string: 1".to_string()),
+        (
+            &format!("[\"0x{}\", \"0x{}\"]", "01".repeat(32), "zz".repeat(32)),
+            format!("leaf 1 isn't a 32 byte hex string: \"0x{}\"", "zz".repeat(32)),
+        ),
+    ] {
+        assert_eq!(leaves_from_json(json).unwrap_err(), reason);
+    }
+    assert!(leaves_from_json("[").unwrap_err().starts_with("invalid json: "));
+}
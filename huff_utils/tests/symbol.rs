@@ -0,0 +1,30 @@
+use huff_utils::prelude::*;
+use std::collections::BTreeMap;
+
+#[test]
+fn test_symbols_compare_by_string() {
+    let a = Symbol::new("transfer_jump");
+    let b = Symbol::from(String::from("transfer_jump"));
+    assert_eq!(a, b);
+    assert_ne!(a, Symbol::new("transferFrom_jump"));
+    assert_eq!(a.as_str(), "transfer_jump");
+    assert_eq!(a.to_string(), "transfer_jump");
+    assert_eq!(format!("{a:?}"), "\"transfer_jump\"");
+}
+
+#[test]
+fn test_symbol_keys_are_looked_up_by_str() {
+    let label_indices = BTreeMap::from([(Symbol::new("MAIN"), 0), (Symbol::new("end"), 12)]);
+    assert_eq!(label_indices.get("end"), Some(&12));
+    assert_eq!(label_indices.get("missing"), None);
+}
+
+#[test]
+fn test_contract_lookups_borrow() {
+    let contract = ContractBuilder::new()
+        .macro_definition(MacroDefinitionBuilder::new("MAIN").opcode(Opcode::Stop).build())
+        .build();
+    let main = contract.find_macro_by_name("MAIN").unwrap();
+    assert!(std::ptr::eq(main, &contract.macros[0]));
+    assert!(contract.find_macro_by_name("MISSING").is_none());
+}